@@ -0,0 +1,344 @@
+//! Screen reader support.
+//!
+//! The editor paints its own text, so the platform accessibility APIs can't
+//! discover the content through the regular view tree. When
+//! `CoreConfig::screen_reader_support` is on, a plain text description of the
+//! visible lines, the caret and the selection is derived from the editor's
+//! [`ScreenLines`] and cursor signals, and changes to it are turned into
+//! announcements held by a label of the window tab.
+//!
+//! The announcements don't reach the platform accessibility APIs yet, as the
+//! version of floem used has no AccessKit tree to publish them in. Until it
+//! has, the flag isn't a user setting and stays off.
+
+use floem::{
+    reactive::{RwSignal, Scope},
+    views::editor::view::ScreenLines,
+};
+use lapce_core::{
+    buffer::{rope_text::RopeText, Buffer},
+    cursor::{Cursor, CursorMode},
+};
+
+use crate::{panel::kind::PanelKind, window_tab::Focus};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibleLine {
+    /// The buffer line, zero based
+    pub line: usize,
+    pub text: String,
+}
+
+/// What a screen reader should know about an editor at a point in time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorAccessibilitySnapshot {
+    /// The buffer lines that are currently on screen, in order
+    pub lines: Vec<AccessibleLine>,
+    /// The zero based line of the caret and its column in chars
+    pub caret: (usize, usize),
+    /// The selected text, if there is a non-empty selection
+    pub selection: Option<String>,
+    pub rev: u64,
+}
+
+impl EditorAccessibilitySnapshot {
+    pub fn new(
+        buffer: &Buffer,
+        screen_lines: &ScreenLines,
+        cursor: &Cursor,
+    ) -> Self {
+        let mut lines: Vec<AccessibleLine> = Vec::new();
+        for rvline in screen_lines.lines.iter() {
+            // Wrapped lines produce several visual lines for one buffer line
+            if lines.last().map(|l| l.line) == Some(rvline.line) {
+                continue;
+            }
+            lines.push(AccessibleLine {
+                line: rvline.line,
+                text: line_text(buffer, rvline.line),
+            });
+        }
+
+        let selection = selection_range(buffer, cursor)
+            .map(|(start, end)| buffer.slice_to_cow(start..end).to_string())
+            .filter(|text| !text.is_empty());
+
+        Self {
+            lines,
+            caret: line_char_col(buffer, cursor.offset()),
+            selection,
+            rev: buffer.rev(),
+        }
+    }
+
+    /// The text of the line the caret is on.
+    pub fn caret_line_text(&self, buffer: &Buffer) -> String {
+        self.lines
+            .iter()
+            .find(|l| l.line == self.caret.0)
+            .map(|l| l.text.clone())
+            .unwrap_or_else(|| line_text(buffer, self.caret.0))
+    }
+
+    /// Work out what changed since the previous snapshot, if anything worth
+    /// reporting.
+    pub fn event_since(
+        &self,
+        buffer: &Buffer,
+        prev: Option<&EditorAccessibilitySnapshot>,
+    ) -> Option<AccessibilityEvent> {
+        let Some(prev) = prev else {
+            return Some(AccessibilityEvent::CaretMoved {
+                line: self.caret.0,
+                column: self.caret.1,
+                text: Some(self.caret_line_text(buffer)),
+            });
+        };
+
+        if self.rev != prev.rev {
+            return Some(AccessibilityEvent::TextEdited {
+                line: self.caret.0,
+                text: self.caret_line_text(buffer),
+            });
+        }
+
+        if self.selection != prev.selection {
+            if let Some(selection) = &self.selection {
+                return Some(AccessibilityEvent::SelectionChanged {
+                    text: selection.clone(),
+                });
+            }
+        }
+
+        if self.caret != prev.caret {
+            let text =
+                (self.caret.0 != prev.caret.0).then(|| self.caret_line_text(buffer));
+            return Some(AccessibilityEvent::CaretMoved {
+                line: self.caret.0,
+                column: self.caret.1,
+                text,
+            });
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessibilityEvent {
+    /// The caret moved. The line text is only included when the line changed.
+    CaretMoved {
+        line: usize,
+        column: usize,
+        text: Option<String>,
+    },
+    SelectionChanged {
+        text: String,
+    },
+    TextEdited {
+        line: usize,
+        text: String,
+    },
+    FocusChanged {
+        name: String,
+    },
+    ItemSelected {
+        text: String,
+    },
+}
+
+impl AccessibilityEvent {
+    /// The text that will be read out for this event
+    pub fn announcement(&self) -> String {
+        match self {
+            AccessibilityEvent::CaretMoved {
+                line,
+                column,
+                text: Some(text),
+            } => {
+                format!("Line {}, Column {}: {text}", line + 1, column + 1)
+            }
+            AccessibilityEvent::CaretMoved {
+                line,
+                column,
+                text: None,
+            } => format!("Line {}, Column {}", line + 1, column + 1),
+            AccessibilityEvent::SelectionChanged { text } => {
+                format!("Selected: {text}")
+            }
+            AccessibilityEvent::TextEdited { line, text } => {
+                format!("Line {}: {text}", line + 1)
+            }
+            AccessibilityEvent::FocusChanged { name } => name.clone(),
+            AccessibilityEvent::ItemSelected { text } => text.clone(),
+        }
+    }
+}
+
+/// The live region that the accessibility events are announced through.
+#[derive(Clone)]
+pub struct AccessibilityData {
+    pub announcement: RwSignal<String>,
+}
+
+impl AccessibilityData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            announcement: cx.create_rw_signal(String::new()),
+        }
+    }
+
+    pub fn announce(&self, event: AccessibilityEvent) {
+        self.announcement.set(event.announcement());
+    }
+}
+
+/// A readable name for the part of the workbench that has the focus
+pub fn focus_name(focus: &Focus) -> String {
+    match focus {
+        Focus::Workbench => "Editor".to_string(),
+        Focus::Palette => "Command Palette".to_string(),
+        Focus::CodeAction => "Code Actions".to_string(),
+        Focus::Rename => "Rename".to_string(),
+        Focus::AboutPopup => "About".to_string(),
+        Focus::Panel(kind) => format!("{} Panel", panel_name(kind)),
+    }
+}
+
+fn panel_name(kind: &PanelKind) -> &'static str {
+    match kind {
+        PanelKind::Terminal => "Terminal",
+        PanelKind::FileExplorer => "File Explorer",
+        PanelKind::SourceControl => "Source Control",
+        PanelKind::Plugin => "Plugins",
+        PanelKind::Search => "Search",
+        PanelKind::Problem => "Problems",
+        PanelKind::Debug => "Debug",
+        PanelKind::DebugConsole => "Debug Console",
+        PanelKind::Flame => "Flame Graph",
+        PanelKind::Output => "Output",
+        PanelKind::JsonTree => "JSON Tree",
+        PanelKind::SyntaxTree => "Syntax Tree",
+        PanelKind::Notifications => "Notifications",
+    }
+}
+
+fn line_text(buffer: &Buffer, line: usize) -> String {
+    buffer
+        .line_content(line)
+        .trim_end_matches(['\n', '\r'])
+        .to_string()
+}
+
+/// The line of `offset` and its column in chars, as read out rather than in
+/// bytes
+fn line_char_col(buffer: &Buffer, offset: usize) -> (usize, usize) {
+    let line = buffer.line_of_offset(offset);
+    let line_start = buffer.offset_of_line(line);
+    let column = buffer.slice_to_cow(line_start..offset).chars().count();
+    (line, column)
+}
+
+fn selection_range(buffer: &Buffer, cursor: &Cursor) -> Option<(usize, usize)> {
+    match &cursor.mode {
+        CursorMode::Normal(_) => None,
+        CursorMode::Visual { start, end, .. } => Some((
+            *start.min(end),
+            buffer.next_grapheme_offset(*start.max(end), 1, buffer.len()),
+        )),
+        CursorMode::Insert(selection) => selection
+            .last_inserted()
+            .filter(|region| !region.is_caret())
+            .map(|region| (region.min(), region.max())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::buffer::Buffer;
+
+    use super::{
+        line_char_col, AccessibilityEvent, AccessibleLine,
+        EditorAccessibilitySnapshot,
+    };
+
+    fn snapshot(
+        caret: (usize, usize),
+        selection: Option<&str>,
+        rev: u64,
+    ) -> EditorAccessibilitySnapshot {
+        EditorAccessibilitySnapshot {
+            lines: vec![
+                AccessibleLine {
+                    line: 0,
+                    text: "fn main() {".to_string(),
+                },
+                AccessibleLine {
+                    line: 1,
+                    text: "}".to_string(),
+                },
+            ],
+            caret,
+            selection: selection.map(|s| s.to_string()),
+            rev,
+        }
+    }
+
+    #[test]
+    fn test_line_char_col() {
+        let buffer = Buffer::new("a\u{e9}\u{4e2d}b\nc");
+        // "b" is after a two byte and a three byte char
+        assert_eq!(line_char_col(&buffer, 6), (0, 3));
+        assert_eq!(line_char_col(&buffer, 8), (1, 0));
+    }
+
+    #[test]
+    fn test_event_since() {
+        let buffer = Buffer::new("fn main() {\n}\n");
+        let start = snapshot((0, 0), None, 0);
+        assert_eq!(
+            start.event_since(&buffer, None),
+            Some(AccessibilityEvent::CaretMoved {
+                line: 0,
+                column: 0,
+                text: Some("fn main() {".to_string()),
+            })
+        );
+        assert_eq!(start.event_since(&buffer, Some(&start)), None);
+
+        // The line is only read out again when the caret moves to another one
+        let right = snapshot((0, 3), None, 0);
+        assert_eq!(
+            right.event_since(&buffer, Some(&start)),
+            Some(AccessibilityEvent::CaretMoved {
+                line: 0,
+                column: 3,
+                text: None,
+            })
+        );
+        let down = snapshot((1, 0), None, 0);
+        assert_eq!(
+            down.event_since(&buffer, Some(&right))
+                .map(|event| event.announcement()),
+            Some("Line 2, Column 1: }".to_string())
+        );
+
+        let selected = snapshot((0, 4), Some("main"), 0);
+        assert_eq!(
+            selected.event_since(&buffer, Some(&start)),
+            Some(AccessibilityEvent::SelectionChanged {
+                text: "main".to_string(),
+            })
+        );
+
+        // An edit is reported over the caret and selection changes it makes
+        let edited = snapshot((0, 4), None, 1);
+        assert_eq!(
+            edited.event_since(&buffer, Some(&selected)),
+            Some(AccessibilityEvent::TextEdited {
+                line: 0,
+                text: "fn main() {".to_string(),
+            })
+        );
+    }
+}
@@ -12,7 +12,7 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use crossbeam_channel::Sender;
-use floem::action::show_context_menu;
+use floem::action::{exec_after, show_context_menu};
 use floem::{
     cosmic_text::{Style as FontStyle, Weight},
     event::{Event, EventListener, EventPropagation},
@@ -58,14 +58,18 @@
     file::PathObject,
     RpcMessage,
 };
-use lsp_types::{CompletionItemKind, MessageType, ShowMessageParams};
+use lsp_types::{
+    CompletionItemKind, DiagnosticSeverity, MessageType, ShowMessageParams,
+};
 use notify::Watcher;
 use serde::{Deserialize, Serialize};
 use tracing_subscriber::{filter::Targets, reload::Handle};
 
 use crate::main_split::TabCloseKind;
 use crate::{
-    about, alert,
+    about,
+    alert::{self, AlertButton},
+    automation::{self, AutomationCall},
     code_action::CodeActionStatus,
     command::{
         CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand,
@@ -77,6 +81,7 @@
     },
     db::LapceDb,
     debug::RunDebugMode,
+    deep_link::DeepLink,
     editor::{
         diff::diff_show_more_section_view,
         location::{EditorLocation, EditorPosition},
@@ -84,18 +89,28 @@
     },
     editor_tab::{EditorTabChild, EditorTabData},
     focus_text::focus_text,
+    font_warmup::{self, FontWarmup},
+    hover::{diagnostic_source, related_information_label, HoverData},
     id::{EditorTabId, SplitId},
     keymap::keymap_view,
     keypress::keymap::KeyMap,
+    large_file::large_file_view,
     listener::Listener,
+    lsp::path_from_url,
     main_split::{SplitContent, SplitData, SplitDirection, SplitMoveDirection},
     markdown::MarkdownContent,
+    notification::{Notification, NotificationCenter},
     palette::{
-        item::{PaletteItem, PaletteItemContent},
+        item::{git_commit_hint, PaletteItem, PaletteItemContent},
         PaletteStatus,
     },
-    panel::{position::PanelContainerPosition, view::panel_container_view},
+    panel::{
+        notification_view::notification_view, position::PanelContainerPosition,
+        view::panel_container_view,
+    },
+    perf,
     plugin::{plugin_info_view, PluginData},
+    run_config::run_configs_view,
     settings::{settings_view, theme_color_settings_view},
     status::status,
     text_input::TextInputBuilder,
@@ -117,9 +132,27 @@ struct Cli {
     /// Launch new window even if Lapce is already running
     #[clap(short, long, action)]
     new: bool,
-    /// Don't return instantly when opened in a terminal
+    /// Open the paths in a new window of the running Lapce,
+    /// instead of the window of the workspace they belong to
+    #[clap(long, action)]
+    new_window: bool,
+    /// Wait for the opened files to be closed before returning,
+    /// e.g. to use Lapce as the editor for git commit messages
     #[clap(short, long, action)]
     wait: bool,
+    /// Set on the process that is relaunched in the background,
+    /// so that the terminal Lapce was started from isn't blocked
+    #[clap(long, action, hide = true)]
+    detached: bool,
+
+    /// Open a diff editor comparing two files
+    #[clap(long, num_args = 2, value_names = ["LEFT", "RIGHT"])]
+    #[clap(value_hint = clap::ValueHint::FilePath)]
+    diff: Vec<PathBuf>,
+
+    /// Open a `lapce://open?path=...&line=...` link
+    #[clap(long, value_name = "URL")]
+    open_url: Option<String>,
 
     /// Path(s) to plugins to load.  
     /// This is primarily used for plugin development to make it easier to test changes to the
@@ -161,6 +194,8 @@ pub struct AppData {
     /// The latest release information
     pub latest_release: RwSignal<Arc<Option<ReleaseInfo>>>,
     pub watcher: Arc<notify::RecommendedWatcher>,
+    /// Watches the grammars and the user's query overrides
+    pub syntax_watcher: Arc<notify::RecommendedWatcher>,
     pub tracing_handle: Handle<Targets>,
     pub config: RwSignal<Arc<LapceConfig>>,
     /// Paths to extra plugins to load
@@ -169,8 +204,18 @@ pub struct AppData {
 
 impl AppData {
     pub fn reload_config(&self) {
-        let config =
-            LapceConfig::load(&LapceWorkspace::default(), &[], &self.plugin_paths);
+        let config = LapceConfig::load(
+            &LapceWorkspace::default(),
+            None,
+            &[],
+            &self.plugin_paths,
+        );
+        if self
+            .config
+            .with_untracked(|old| FontWarmup::new(old) != FontWarmup::new(&config))
+        {
+            font_warmup::warm_up(&config);
+        }
         self.config.set(Arc::new(config));
         let windows = self.windows.get_untracked();
         for (_, window) in windows {
@@ -178,6 +223,16 @@ pub fn reload_config(&self) {
         }
     }
 
+    /// Reload the highlight configs and re-highlight the open documents, once
+    /// a grammar or a query override changed
+    pub fn reload_syntax(&self) {
+        reset_highlight_configs();
+        let windows = self.windows.get_untracked();
+        for (_, window) in windows {
+            window.reload_syntax();
+        }
+    }
+
     pub fn active_window_tab(&self) -> Option<Rc<WindowTabData>> {
         if let Some(window) = self.active_window() {
             return window.active_window_tab();
@@ -201,6 +256,11 @@ fn default_window_config(&self) -> WindowConfig {
     }
 
     pub fn new_window(&self) {
+        self.new_window_with(LapceWorkspace::default(), Vec::new());
+    }
+
+    /// Open a new window for the workspace, and open the files in it.
+    fn new_window_with(&self, workspace: LapceWorkspace, files: Vec<PathObject>) {
         let config = self
             .active_window()
             .map(|window| {
@@ -229,7 +289,7 @@ pub fn new_window(&self) {
         let app_data = self.clone();
         floem::new_window(
             move |window_id| {
-                app_data.app_view(
+                let view = app_data.app_view(
                     window_id,
                     WindowInfo {
                         size: Size::ZERO,
@@ -237,15 +297,210 @@ pub fn new_window(&self) {
                         maximised: false,
                         tabs: TabsInfo {
                             active_tab: 0,
-                            workspaces: vec![LapceWorkspace::default()],
+                            workspaces: vec![workspace.clone()],
                         },
                     },
-                )
+                );
+                if !files.is_empty() {
+                    if let Some(window_tab) = app_data.windows.with_untracked(|w| {
+                        w.get(&window_id).and_then(|w| w.active_window_tab())
+                    }) {
+                        window_tab.open_paths(&files);
+                    }
+                }
+                view
             },
             Some(config),
         );
     }
 
+    /// Open the paths passed to another invocation of Lapce. Each path goes to
+    /// the window tab whose workspace contains it, which is brought to the
+    /// front, and the rest are opened in new windows.
+    pub fn open_paths(&self, paths: &[PathObject], new_window: bool) {
+        if new_window {
+            self.open_in_new_windows(paths);
+            return;
+        }
+        if paths.is_empty() {
+            if let Some(window) = self.active_window() {
+                self.focus_window_tab(&window, window.active.get_untracked());
+            }
+            return;
+        }
+
+        let mut unmatched = Vec::new();
+        for path in paths {
+            let Some((window, index, window_tab)) =
+                self.workspace_window_tab(&path.path)
+            else {
+                unmatched.push(path.clone());
+                continue;
+            };
+            self.focus_window_tab(&window, index);
+            // A folder that is the workspace itself only needs focusing
+            if !(path.is_dir
+                && window_tab.workspace.path.as_ref() == Some(&path.path))
+            {
+                window_tab.open_paths(std::slice::from_ref(path));
+            }
+        }
+        if !unmatched.is_empty() {
+            self.open_in_new_windows(&unmatched);
+        }
+    }
+
+    /// A window for each folder, and one more for the files
+    fn open_in_new_windows(&self, paths: &[PathObject]) {
+        let (folders, files): (Vec<&PathObject>, Vec<&PathObject>) =
+            paths.iter().partition(|p| p.is_dir);
+        for folder in folders {
+            self.new_window_with(
+                LapceWorkspace {
+                    kind: LapceWorkspaceType::Local,
+                    path: Some(folder.path.clone()),
+                    last_open: 0,
+                },
+                Vec::new(),
+            );
+        }
+        if !files.is_empty() || paths.is_empty() {
+            self.new_window_with(
+                LapceWorkspace::default(),
+                files.into_iter().cloned().collect(),
+            );
+        }
+    }
+
+    /// The local window tab with the deepest workspace folder containing the
+    /// path
+    fn workspace_window_tab(
+        &self,
+        path: &std::path::Path,
+    ) -> Option<(WindowData, usize, Rc<WindowTabData>)> {
+        let windows = self.windows.get_untracked();
+        let mut found: Option<(WindowData, usize, Rc<WindowTabData>)> = None;
+        let mut found_depth = 0;
+        for (_, window) in windows.iter() {
+            let window_tabs = window.window_tabs.get_untracked();
+            for (index, (_, window_tab)) in window_tabs.iter().enumerate() {
+                if !window_tab.workspace.kind.is_local() {
+                    continue;
+                }
+                let Some(workspace_path) = window_tab.workspace.path.as_ref() else {
+                    continue;
+                };
+                if !path.starts_with(workspace_path) {
+                    continue;
+                }
+                let depth = workspace_path.components().count();
+                if found.is_none() || depth > found_depth {
+                    found = Some((window.clone(), index, window_tab.clone()));
+                    found_depth = depth;
+                }
+            }
+        }
+        found
+    }
+
+    fn focus_window_tab(&self, window: &WindowData, index: usize) {
+        window.active.set(index);
+        self.active_window.set(window.window_id);
+        floem::action::focus_window();
+    }
+
+    /// Open a `lapce://` link. A file in an open workspace is opened directly,
+    /// but opening a new workspace for it has to be confirmed first, as the
+    /// link could have come from anywhere.
+    pub fn open_url(&self, url: &str) {
+        let Some(window_tab) = self.active_window_tab() else {
+            return;
+        };
+        let link = match DeepLink::parse(url) {
+            Ok(link) => link,
+            Err(e) => {
+                window_tab.show_message(
+                    "Invalid Link",
+                    &ShowMessageParams {
+                        typ: MessageType::ERROR,
+                        message: format!("{url}: {e}"),
+                    },
+                );
+                return;
+            }
+        };
+
+        if let Some((window, index, window_tab)) =
+            self.workspace_window_tab(&link.path)
+        {
+            self.focus_window_tab(&window, index);
+            window_tab.open_paths(&[link.path_object()]);
+            return;
+        }
+
+        let Some(workspace) = link.workspace_folder() else {
+            return;
+        };
+        let app_data = self.clone();
+        let internal_command = window_tab.common.internal_command;
+        window_tab.show_alert(
+            format!("Open {} from a link?", link.path.display()),
+            format!(
+                "This will open the folder {} as a workspace. \
+                 Only open links from sources you trust, as opening a \
+                 workspace can start its language servers and plugins.",
+                workspace.display()
+            ),
+            vec![AlertButton {
+                text: "Open Workspace".to_string(),
+                action: Rc::new(move || {
+                    internal_command.send(InternalCommand::HideAlert);
+                    app_data.new_window_with(
+                        LapceWorkspace {
+                            kind: LapceWorkspaceType::Local,
+                            path: Some(workspace.clone()),
+                            last_open: 0,
+                        },
+                        vec![link.path_object()],
+                    );
+                }),
+            }],
+        );
+    }
+
+    /// Call `on_closed` once the files have been opened in some window, and
+    /// aren't shown in any editor anymore.
+    pub fn on_paths_closed(
+        &self,
+        paths: Vec<PathBuf>,
+        on_closed: impl FnOnce() + 'static,
+    ) {
+        let windows = self.windows;
+        let scope = Scope::new();
+        let mut on_closed = Some(on_closed);
+        scope.create_effect(move |was_open: Option<bool>| {
+            let is_open = windows.with(|windows| {
+                windows.values().any(|window| {
+                    window.window_tabs.with(|window_tabs| {
+                        window_tabs.iter().any(|(_, window_tab)| {
+                            window_tab.main_split.shows_any_path(&paths)
+                        })
+                    })
+                })
+            });
+            let was_open = was_open.unwrap_or(false);
+            if was_open && !is_open {
+                if let Some(on_closed) = on_closed.take() {
+                    on_closed();
+                    exec_after(std::time::Duration::ZERO, move |_| {
+                        scope.dispose();
+                    });
+                }
+            }
+            was_open || is_open
+        });
+    }
+
     pub fn run_app_command(&self, cmd: AppCommand) {
         match cmd {
             AppCommand::SaveApp => {
@@ -285,6 +540,8 @@ fn create_windows(
         &self,
         db: Arc<LapceDb>,
         paths: Vec<PathObject>,
+        diff: Option<(PathBuf, PathBuf)>,
+        url: Option<String>,
     ) -> floem::Application {
         let mut app = floem::Application::new();
 
@@ -304,19 +561,24 @@ fn create_windows(
 
             for dir in dirs {
                 #[cfg(windows)]
-                let workspace_type = if !std::env::var("WSL_DISTRO_NAME")
-                    .unwrap_or_default()
-                    .is_empty()
-                    || !std::env::var("WSL_INTEROP").unwrap_or_default().is_empty()
+                let (workspace_type, path) = match std::env::var("WSL_DISTRO_NAME")
+                    .ok()
+                    .filter(|distro| !distro.is_empty())
                 {
-                    LapceWorkspaceType::RemoteWSL(crate::workspace::WslHost {
-                        host: String::new(),
-                    })
-                } else {
-                    LapceWorkspaceType::Local
+                    // Started from a shell inside the distro, which passes its
+                    // paths the way Windows sees them
+                    Some(distro) => {
+                        let wsl = crate::workspace::WslHost { host: distro };
+                        let path = wsl
+                            .to_wsl_path(&dir.path)
+                            .unwrap_or_else(|| dir.path.clone());
+                        (LapceWorkspaceType::RemoteWSL(wsl), path)
+                    }
+                    None => (LapceWorkspaceType::Local, dir.path.clone()),
                 };
                 #[cfg(not(windows))]
-                let workspace_type = LapceWorkspaceType::Local;
+                let (workspace_type, path) =
+                    (LapceWorkspaceType::Local, dir.path.clone());
 
                 let info = WindowInfo {
                     size,
@@ -326,7 +588,7 @@ fn create_windows(
                         active_tab: 0,
                         workspaces: vec![LapceWorkspace {
                             kind: workspace_type,
-                            path: Some(dir.path.to_owned()),
+                            path: Some(path),
                             last_open: 0,
                         }],
                     },
@@ -437,6 +699,16 @@ fn create_windows(
                     },
                 });
             }
+            if let Some((left_path, right_path)) = diff {
+                window_tab.run_internal_command(InternalCommand::OpenDiffFiles {
+                    left_path,
+                    right_path,
+                });
+            }
+        }
+
+        if let Some(url) = url {
+            self.open_url(&url);
         }
 
         app
@@ -463,6 +735,7 @@ fn app_view(&self, window_id: WindowId, info: WindowInfo) -> impl View {
         let config = window_data.config;
         // The KeyDown and PointerDown event handlers both need ownership of a WindowData object.
         let key_down_window_data = window_data.clone();
+        let focus_window_data = window_data.clone();
         let view =
             stack((
                 workspace_tab_header(window_data.clone()),
@@ -582,8 +855,15 @@ fn app_view(&self, window_id: WindowId, info: WindowInfo) -> impl View {
                     position.set(*point);
                 }
             })
-            .on_event_stop(EventListener::WindowGotFocus, move |_| {
-                app_command.send(AppCommand::WindowGotFocus(window_id));
+            .on_event_stop(EventListener::WindowGotFocus, {
+                let window_data = focus_window_data.clone();
+                move |_| {
+                    window_data.set_focused(true);
+                    app_command.send(AppCommand::WindowGotFocus(window_id));
+                }
+            })
+            .on_event_stop(EventListener::WindowLostFocus, move |_| {
+                focus_window_data.set_focused(false);
             })
             .on_event_stop(EventListener::WindowClosed, move |_| {
                 app_command.send(AppCommand::WindowClosed(window_id));
@@ -1281,9 +1561,15 @@ fn editor_tab_content(
                 theme_color_settings_view(editors, common).into_any()
             }
             EditorTabChild::Keymap(_) => keymap_view(editors, common).into_any(),
+            EditorTabChild::RunConfigs(_) => {
+                run_configs_view(main_split.clone()).into_any()
+            }
             EditorTabChild::Volt(_, id) => {
                 plugin_info_view(plugin.clone(), id).into_any()
             }
+            EditorTabChild::LargeFile(_, path) => {
+                large_file_view(main_split.clone(), path).into_any()
+            }
         };
         child.style(|s| s.size_full())
     };
@@ -2020,7 +2306,7 @@ pub fn tooltip_label<S: std::fmt::Display + 'static, V: View + 'static>(
     })
 }
 
-fn tooltip_tip<V: View + 'static>(
+pub fn tooltip_tip<V: View + 'static>(
     config: ReadSignal<Arc<LapceConfig>>,
     child: V,
 ) -> impl IntoView {
@@ -2065,7 +2351,11 @@ fn workbench(window_tab_data: Rc<WindowTabData>) -> impl View {
             .style(|s| s.flex_col().flex_grow(1.0))
         },
         panel_container_view(window_tab_data.clone(), PanelContainerPosition::Right),
-        window_message_view(window_tab_data.messages, window_tab_data.common.config),
+        window_message_view(
+            window_tab_data.common.notifications,
+            window_tab_data.common.internal_command,
+            window_tab_data.common.config,
+        ),
     ))
     .on_resize(move |rect| {
         let size = rect.size();
@@ -2382,6 +2672,84 @@ fn palette_item(
                 .style(|s| s.align_items(Some(AlignItems::Center)).max_width_full()),
             )
         }
+        PaletteItemContent::GitCommit { commit, .. } => {
+            let text = commit.summary.clone();
+            let hint = git_commit_hint(commit);
+            // The filter text is the summary and the hint, separated by a space
+            let text_indices: Vec<usize> = item
+                .indices
+                .iter()
+                .filter(|&&i| i < text.len())
+                .copied()
+                .collect();
+            let hint_indices: Vec<usize> = item
+                .indices
+                .iter()
+                .filter(|&&i| i > text.len())
+                .map(|i| i - text.len() - 1)
+                .collect();
+            container(
+                stack((
+                    focus_text(
+                        move || text.clone(),
+                        move || text_indices.clone(),
+                        move || config.get().color(LapceColor::EDITOR_FOCUS),
+                    )
+                    .style(|s| s.margin_right(6.0).max_width_full()),
+                    focus_text(
+                        move || hint.clone(),
+                        move || hint_indices.clone(),
+                        move || config.get().color(LapceColor::EDITOR_FOCUS),
+                    )
+                    .style(move |s| {
+                        s.color(config.get().color(LapceColor::EDITOR_DIM))
+                            .min_width(0.0)
+                            .flex_grow(1.0)
+                            .flex_basis(0.0)
+                    }),
+                ))
+                .style(|s| s.align_items(Some(AlignItems::Center)).max_width_full()),
+            )
+        }
+        PaletteItemContent::Process { process } => {
+            let text = process.name.clone();
+            let hint = format!("{} {}", process.pid, process.cmdline);
+            // The filter text is the name and the hint, separated by a space
+            let text_indices: Vec<usize> = item
+                .indices
+                .iter()
+                .filter(|&&i| i < text.len())
+                .copied()
+                .collect();
+            let hint_indices: Vec<usize> = item
+                .indices
+                .iter()
+                .filter(|&&i| i > text.len())
+                .map(|i| i - text.len() - 1)
+                .collect();
+            container(
+                stack((
+                    focus_text(
+                        move || text.clone(),
+                        move || text_indices.clone(),
+                        move || config.get().color(LapceColor::EDITOR_FOCUS),
+                    )
+                    .style(|s| s.margin_right(6.0).max_width_full()),
+                    focus_text(
+                        move || hint.clone(),
+                        move || hint_indices.clone(),
+                        move || config.get().color(LapceColor::EDITOR_FOCUS),
+                    )
+                    .style(move |s| {
+                        s.color(config.get().color(LapceColor::EDITOR_DIM))
+                            .min_width(0.0)
+                            .flex_grow(1.0)
+                            .flex_basis(0.0)
+                    }),
+                ))
+                .style(|s| s.align_items(Some(AlignItems::Center)).max_width_full()),
+            )
+        }
         PaletteItemContent::PaletteHelp { .. }
         | PaletteItemContent::Command { .. } => {
             let text = item.filter_text;
@@ -2436,7 +2804,16 @@ fn palette_item(
         | PaletteItemContent::LineEnding { .. }
         | PaletteItemContent::ColorTheme { .. }
         | PaletteItemContent::SCMReference { .. }
+        | PaletteItemContent::RemotePath { .. }
+        | PaletteItemContent::RemoteFolder { .. }
         | PaletteItemContent::TerminalProfile { .. }
+        | PaletteItemContent::Profile { .. }
+        | PaletteItemContent::FileTemplate { .. }
+        | PaletteItemContent::Refactor { .. }
+        | PaletteItemContent::Grammar { .. }
+        | PaletteItemContent::HistoryCommand { .. }
+        | PaletteItemContent::Macro { .. }
+        | PaletteItemContent::AttachConfig { .. }
         | PaletteItemContent::IconTheme { .. } => {
             let text = item.filter_text;
             let indices = item.indices;
@@ -2707,81 +3084,49 @@ fn palette(window_tab_data: Rc<WindowTabData>) -> impl View {
 }
 
 fn window_message_view(
-    messages: RwSignal<Vec<(String, ShowMessageParams)>>,
+    notifications: RwSignal<NotificationCenter>,
+    internal_command: Listener<InternalCommand>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
-    let view_fn =
-        move |(i, (title, message)): (usize, (String, ShowMessageParams))| {
-            stack((
-                svg(move || {
-                    if let MessageType::ERROR = message.typ {
-                        config.get().ui_svg(LapceIcons::ERROR)
-                    } else {
-                        config.get().ui_svg(LapceIcons::WARNING)
-                    }
-                })
-                .style(move |s| {
-                    let config = config.get();
-                    let size = config.ui.icon_size() as f32;
-                    let color = if let MessageType::ERROR = message.typ {
-                        config.color(LapceColor::LAPCE_ERROR)
-                    } else {
-                        config.color(LapceColor::LAPCE_WARN)
-                    };
-                    s.min_width(size)
-                        .size(size, size)
-                        .margin_right(10.0)
-                        .margin_top(4.0)
-                        .color(color)
-                }),
-                stack((
-                    text(title.clone()).style(|s| {
-                        s.min_width(0.0).line_height(1.6).font_weight(Weight::BOLD)
-                    }),
-                    text(message.message.clone()).style(|s| {
-                        s.min_width(0.0).line_height(1.6).margin_top(5.0)
-                    }),
-                ))
-                .style(move |s| {
-                    s.flex_col().min_width(0.0).flex_basis(0.0).flex_grow(1.0)
-                }),
-                clickable_icon(
-                    || LapceIcons::CLOSE,
-                    move || {
-                        messages.update(|messages| {
-                            messages.remove(i);
-                        });
-                    },
-                    || false,
-                    || false,
-                    || "Close",
-                    config,
-                )
-                .style(|s| s.margin_left(6.0)),
-            ))
-            .style(move |s| {
-                let config = config.get();
-                s.width_full()
-                    .items_start()
-                    .padding(10.0)
-                    .border(1.0)
-                    .border_radius(6.0)
-                    .border_color(config.color(LapceColor::LAPCE_BORDER))
-                    .background(config.color(LapceColor::PANEL_BACKGROUND))
-                    .apply_if(i > 0, |s| s.margin_top(10.0))
+    let view_fn = move |notification: Notification| {
+        let id = notification.id;
+        stack((
+            notification_view(&notification, internal_command, config, move || {
+                notifications.update(|notifications| notifications.dismiss(id));
             })
-        };
+            .style(|s| s.min_width(0.0).flex_basis(0.0).flex_grow(1.0)),
+            clickable_icon(
+                || LapceIcons::CLOSE,
+                move || {
+                    notifications.update(|notifications| notifications.dismiss(id));
+                },
+                || false,
+                || false,
+                || "Close",
+                config,
+            )
+            .style(|s| s.margin_left(6.0)),
+        ))
+        .style(move |s| {
+            let config = config.get();
+            s.width_full()
+                .items_start()
+                .padding(10.0)
+                .margin_bottom(10.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+                .background(config.color(LapceColor::PANEL_BACKGROUND))
+        })
+    };
 
-    let id = AtomicU64::new(0);
     container(
         container(
             container(
                 scroll(
                     dyn_stack(
-                        move || messages.get().into_iter().enumerate(),
-                        move |_| {
-                            id.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
-                        },
+                        move || notifications.with(|n| n.toasts()),
+                        |notification| notification.id,
                         view_fn,
                     )
                     .style(|s| s.flex_col().width_full()),
@@ -2846,25 +3191,35 @@ fn hover(window_tab_data: Rc<WindowTabData>) -> impl View {
     let id = AtomicU64::new(0);
     let layout_rect = window_tab_data.common.hover.layout_rect;
 
+    let content = hover_data.content;
     scroll(
-        dyn_stack(
-            move || hover_data.content.get(),
-            move |_| id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
-            move |content| match content {
-                MarkdownContent::Text(text_layout) => container(
-                    rich_text(move || text_layout.clone())
-                        .style(|s| s.max_width(600.0)),
-                )
-                .style(|s| s.max_width_full()),
-                MarkdownContent::Image { .. } => container(empty()),
-                MarkdownContent::Separator => container(empty().style(move |s| {
-                    s.width_full()
-                        .margin_vert(5.0)
-                        .height(1.0)
-                        .background(config.get().color(LapceColor::LAPCE_BORDER))
-                })),
-            },
-        )
+        stack((
+            hover_diagnostics(
+                hover_data.clone(),
+                window_tab_data.common.internal_command,
+                config,
+            ),
+            dyn_stack(
+                move || content.get(),
+                move |_| id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+                move |content| match content {
+                    MarkdownContent::Text(text_layout) => container(
+                        rich_text(move || text_layout.clone())
+                            .style(|s| s.max_width(600.0)),
+                    )
+                    .style(|s| s.max_width_full()),
+                    MarkdownContent::Image { .. } => container(empty()),
+                    MarkdownContent::Separator => {
+                        container(empty().style(move |s| {
+                            s.width_full().margin_vert(5.0).height(1.0).background(
+                                config.get().color(LapceColor::LAPCE_BORDER),
+                            )
+                        }))
+                    }
+                },
+            )
+            .style(|s| s.flex_col()),
+        ))
         .style(|s| s.flex_col().padding_horiz(10.0).padding_vert(5.0)),
     )
     .on_resize(move |rect| {
@@ -2895,6 +3250,93 @@ fn hover(window_tab_data: Rc<WindowTabData>) -> impl View {
     .debug_name("Hover Layer")
 }
 
+/// The diagnostics at the hovered offset, with their source and code and the
+/// related information, which jumps to its location when clicked
+fn hover_diagnostics(
+    hover_data: HoverData,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let id = AtomicU64::new(0);
+    let diagnostics = hover_data.diagnostics;
+    let content = hover_data.content;
+    let active = hover_data.active;
+
+    dyn_stack(
+        move || diagnostics.get(),
+        move |_| id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        move |diagnostic| {
+            let severity = diagnostic.severity;
+            let message = diagnostic.message.clone();
+            let source = diagnostic_source(&diagnostic);
+            let has_source = source.is_some();
+            let related = diagnostic.related_information.unwrap_or_default();
+            stack((
+                label(move || message.clone()).style(move |s| {
+                    let config = config.get();
+                    let color = match severity {
+                        Some(DiagnosticSeverity::ERROR) => LapceColor::LAPCE_ERROR,
+                        Some(DiagnosticSeverity::WARNING) => LapceColor::LAPCE_WARN,
+                        _ => LapceColor::EDITOR_FOREGROUND,
+                    };
+                    s.max_width(600.0).color(config.color(color))
+                }),
+                label(move || source.clone().unwrap_or_default()).style(move |s| {
+                    s.color(config.get().color(LapceColor::EDITOR_DIM))
+                        .apply_if(!has_source, |s| s.hide())
+                }),
+                dyn_stack(
+                    move || related.clone(),
+                    |related| {
+                        (related.location.uri.clone(), related.message.clone())
+                    },
+                    move |related| {
+                        let text = related_information_label(&related);
+                        let location = EditorLocation {
+                            path: path_from_url(&related.location.uri),
+                            position: Some(EditorPosition::Position(
+                                related.location.range.start,
+                            )),
+                            scroll_offset: None,
+                            ignore_unconfirmed: false,
+                            same_editor_tab: false,
+                        };
+                        label(move || text.clone())
+                            .on_click_stop(move |_| {
+                                active.set(false);
+                                internal_command.send(
+                                    InternalCommand::JumpToLocation {
+                                        location: location.clone(),
+                                    },
+                                );
+                            })
+                            .style(move |s| {
+                                let config = config.get();
+                                s.max_width(600.0)
+                                    .color(config.color(LapceColor::EDITOR_LINK))
+                                    .hover(|s| s.cursor(CursorStyle::Pointer))
+                            })
+                    },
+                )
+                .style(|s| s.flex_col().margin_top(4.0)),
+            ))
+            .style(|s| s.flex_col().padding_vert(3.0))
+        },
+    )
+    .style(move |s| {
+        let config = config.get();
+        s.flex_col().apply_if(
+            diagnostics.with(|d| !d.is_empty()) && content.with(|c| !c.is_empty()),
+            |s| {
+                s.padding_bottom(5.0)
+                    .margin_bottom(5.0)
+                    .border_bottom(1.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+            },
+        )
+    })
+}
+
 fn completion(window_tab_data: Rc<WindowTabData>) -> impl View {
     let completion_data = window_tab_data.common.completion;
     let active_editor = window_tab_data.main_split.active_editor;
@@ -2911,6 +3353,12 @@ fn completion(window_tab_data: Rc<WindowTabData>) -> impl View {
             move || completion_data.with(|c| VectorItems(c.filtered_items.clone())),
             move |(i, _item)| (request_id(), *i),
             move |(i, item)| {
+                let source = completion_data.with_untracked(|c| {
+                    c.mixed_sources
+                        .then(|| c.source(item.plugin_id).map(str::to_string))
+                        .flatten()
+                });
+                let has_source = source.is_some();
                 stack((
                     container(
                         text(
@@ -2967,6 +3415,13 @@ fn completion(window_tab_data: Rc<WindowTabData>) -> impl View {
                                 )
                             })
                     }),
+                    text(source.unwrap_or_default()).style(move |s| {
+                        s.padding_horiz(5.0)
+                            .text_ellipsis()
+                            .max_width(120.0)
+                            .color(config.get().color(LapceColor::EDITOR_DIM))
+                            .apply_if(!has_source, |s| s.hide())
+                    }),
                 ))
                 .style(move |s| {
                     s.align_items(Some(AlignItems::Center))
@@ -3178,6 +3633,8 @@ fn window_tab(window_tab_data: Rc<WindowTabData>) -> impl View {
         palette(window_tab_data.clone()),
         about::about_popup(window_tab_data.clone()),
         alert::alert_box(window_tab_data.alert_data.clone()),
+        accessibility_live_region(window_tab_data.clone()),
+        perf::perf_overlay(window_tab_data.clone()),
     ))
     .on_cleanup(move || {
         window_tab_scope.dispose();
@@ -3207,14 +3664,38 @@ fn window_tab(window_tab_data: Rc<WindowTabData>) -> impl View {
     view
 }
 
+/// An invisible label holding the latest accessibility announcement, the
+/// editor and palette changes to be read out. See [`crate::accessibility`]
+/// for what it lacks to reach screen readers.
+fn accessibility_live_region(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let announcement = window_tab_data.common.accessibility.announcement;
+    label(move || announcement.get())
+        .style(move |s| {
+            s.absolute()
+                .size(1.0, 1.0)
+                .margin_left(-1.0)
+                .margin_top(-1.0)
+                .color(Color::TRANSPARENT)
+                .apply_if(
+                    !config.with(|config| config.core.screen_reader_support),
+                    |s| s.hide(),
+                )
+        })
+        .debug_name("Accessibility Live Region")
+}
+
 fn workspace_title(workspace: &LapceWorkspace) -> Option<String> {
     let p = workspace.path.as_ref()?;
     let dir = p.file_name().unwrap_or(p.as_os_str()).to_string_lossy();
     Some(match &workspace.kind {
         LapceWorkspaceType::Local => format!("{dir}"),
         LapceWorkspaceType::RemoteSSH(remote) => format!("{dir} [{remote}]"),
+        LapceWorkspaceType::RemoteContainer(remote) => {
+            format!("{dir} [Container: {remote}]")
+        }
         #[cfg(windows)]
-        LapceWorkspaceType::RemoteWSL(remote) => format!("{dir} [{remote}]"),
+        LapceWorkspaceType::RemoteWSL(remote) => format!("{dir} [WSL: {remote}]"),
     })
 }
 
@@ -3591,52 +4072,20 @@ pub fn launch() {
     let stdin = std::io::stdin();
     if !stdin.is_terminal() {
         trace!(TraceLevel::INFO, "Loading custom environment from shell");
-        load_shell_env();
+        lapce_proxy::env::load_shell_env();
     }
 
     let cli = Cli::parse();
 
+    let diff = match cli.diff.as_slice() {
+        [left, right] => Some((absolute_path(left), absolute_path(right))),
+        _ => None,
+    };
+
     // small hack to unblock terminal if launched from it
     // launch it as a separate process that waits
-    if !cli.wait {
-        let mut args = std::env::args().collect::<Vec<_>>();
-        args.push("--wait".to_string());
-        let mut cmd = std::process::Command::new(&args[0]);
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-        let stderr_file_path =
-            Directory::logs_directory().unwrap().join("stderr.log");
-        let stderr_file = std::fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .read(true)
-            .open(stderr_file_path)
-            .unwrap();
-        let stderr = Stdio::from(stderr_file);
-
-        let stdout_file_path =
-            Directory::logs_directory().unwrap().join("stdout.log");
-        let stdout_file = std::fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .read(true)
-            .open(stdout_file_path)
-            .unwrap();
-        let stdout = Stdio::from(stdout_file);
-
-        if let Err(why) = cmd
-            .args(&args[1..])
-            .stderr(stderr)
-            .stdout(stdout)
-            .env("LAPCE_LOG", "lapce_app::app=error,off")
-            .spawn()
-        {
-            eprintln!("Failed to launch lapce: {why}");
-            std::process::exit(1);
-        };
+    if !cli.wait && !cli.detached {
+        launch_detached();
         return;
     }
 
@@ -3644,13 +4093,34 @@ pub fn launch() {
     // in the existing Lapce process
     if !cli.new {
         if let Ok(socket) = get_socket() {
-            if let Err(e) = try_open_in_existing_process(socket, &cli.paths) {
-                trace!(TraceLevel::ERROR, "failed to open path(s): {e}");
-            };
-            return;
+            match try_open_in_existing_process(
+                socket,
+                &cli.paths,
+                diff.clone(),
+                cli.open_url.clone(),
+                cli.new_window,
+                cli.wait,
+            ) {
+                Ok(()) => return,
+                // The socket was left behind by a process that has crashed or
+                // hangs, so this one takes over
+                Err(e) => trace!(
+                    TraceLevel::ERROR,
+                    "existing Lapce process didn't respond, starting a new one: {e}"
+                ),
+            }
         }
     }
 
+    // Waiting here would tie the app to the command waiting on it, so the app
+    // is launched in the background as without --wait, and this process
+    // waits for the files to be closed in it as in an already running one
+    if cli.wait && !cli.detached {
+        launch_detached();
+        wait_in_launched_process(&cli.paths);
+        return;
+    }
+
     {
         let cx = Scope::new();
         let send = create_ext_action(cx, |_| {
@@ -3711,16 +4181,33 @@ pub fn launch() {
     if let Some(path) = LapceConfig::keymaps_file() {
         let _ = watcher.watch(&path, notify::RecursiveMode::Recursive);
     }
+    if let Some(path) = Directory::profiles_directory() {
+        let _ = watcher.watch(&path, notify::RecursiveMode::Recursive);
+    }
     if let Some(path) = Directory::plugins_directory() {
         let _ = watcher.watch(&path, notify::RecursiveMode::Recursive);
     }
 
+    let (syntax_tx, syntax_rx) = crossbeam_channel::bounded(1);
+    let mut syntax_watcher =
+        notify::recommended_watcher(ConfigWatcher::new(syntax_tx)).unwrap();
+    if let Some(path) = Directory::grammars_directory() {
+        let _ = syntax_watcher.watch(&path, notify::RecursiveMode::Recursive);
+    }
+    if let Some(path) = Directory::query_overrides_directory() {
+        let _ = syntax_watcher.watch(&path, notify::RecursiveMode::Recursive);
+    }
+
     let windows = scope.create_rw_signal(im::HashMap::new());
-    let config = LapceConfig::load(&LapceWorkspace::default(), &[], &plugin_paths);
+    let config =
+        LapceConfig::load(&LapceWorkspace::default(), None, &[], &plugin_paths);
 
     // Restore scale from config
     window_scale.set(config.ui.scale());
 
+    perf::set_enabled(config.ui.show_perf_overlay);
+    font_warmup::warm_up(&config);
+
     let config = scope.create_rw_signal(Arc::new(config));
     let app_data = AppData {
         windows,
@@ -3728,6 +4215,7 @@ pub fn launch() {
         window_scale,
         app_terminated: scope.create_rw_signal(false),
         watcher: Arc::new(watcher),
+        syntax_watcher: Arc::new(syntax_watcher),
         latest_release,
         app_command,
         tracing_handle: reload_handle,
@@ -3735,7 +4223,7 @@ pub fn launch() {
         plugin_paths,
     };
 
-    let app = app_data.create_windows(db.clone(), cli.paths);
+    let app = app_data.create_windows(db.clone(), cli.paths, diff, cli.open_url);
 
     {
         let app_data = app_data.clone();
@@ -3747,6 +4235,16 @@ pub fn launch() {
         });
     }
 
+    {
+        let app_data = app_data.clone();
+        let notification = create_signal_from_channel(syntax_rx);
+        create_effect(move |_| {
+            if notification.get().is_some() {
+                app_data.reload_syntax();
+            }
+        });
+    }
+
     #[cfg(feature = "updater")]
     {
         let (tx, rx) = crossbeam_channel::bounded(1);
@@ -3770,10 +4268,44 @@ pub fn launch() {
         let notification = create_signal_from_channel(rx);
         let app_data = app_data.clone();
         create_effect(move |_| {
-            if let Some(CoreNotification::OpenPaths { paths }) = notification.get() {
-                if let Some(window_tab) = app_data.active_window_tab() {
-                    window_tab.open_paths(&paths);
+            let Some(SocketNotification {
+                notification,
+                closed,
+            }) = notification.get()
+            else {
+                return;
+            };
+            match notification {
+                CoreNotification::OpenPaths { paths } => {
+                    app_data.open_paths(&paths, false);
+                }
+                CoreNotification::OpenPathsInNewWindow { paths } => {
+                    app_data.open_paths(&paths, true);
+                }
+                CoreNotification::OpenDiffFiles {
+                    left_path,
+                    right_path,
+                } => {
+                    if let Some(window_tab) = app_data.active_window_tab() {
+                        window_tab.common.internal_command.send(
+                            InternalCommand::OpenDiffFiles {
+                                left_path,
+                                right_path,
+                            },
+                        );
+                    }
+                }
+                CoreNotification::OpenUrl { url } => {
+                    app_data.open_url(&url);
+                }
+                CoreNotification::WaitPathsClosed { paths } => {
+                    if let Some(closed) = closed {
+                        app_data.on_paths_closed(paths, move || {
+                            let _ = closed.send(());
+                        });
+                    }
                 }
+                _ => {}
             }
         });
         std::thread::spawn(move || {
@@ -3781,6 +4313,22 @@ pub fn launch() {
         });
     }
 
+    if config.with_untracked(|config| config.core.automation_socket) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let call = create_signal_from_channel(rx);
+        let app_data = app_data.clone();
+        create_effect(move |_| {
+            if let Some(AutomationCall { request, reply }) = call.get() {
+                let _ = reply.send(automation::handle_request(&app_data, request));
+            }
+        });
+        std::thread::spawn(move || {
+            if let Err(e) = automation::listen_automation_socket(tx) {
+                trace!(TraceLevel::ERROR, "failed to start automation socket: {e}");
+            }
+        });
+    }
+
     {
         let app_data = app_data.clone();
         app_data.app_command.listen(move |command| {
@@ -3804,75 +4352,156 @@ pub fn launch() {
     .run();
 }
 
-/// Uses a login shell to load the correct shell environment for the current user.
-fn load_shell_env() {
-    use std::process::Command;
+/// Launch Lapce again in the background with the same arguments, so that the
+/// terminal it was started from isn't blocked
+fn launch_detached() {
+    let mut args = std::env::args().collect::<Vec<_>>();
+    args.push("--detached".to_string());
+    let mut cmd = std::process::Command::new(&args[0]);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let stderr_file_path = Directory::logs_directory().unwrap().join("stderr.log");
+    let stderr_file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .read(true)
+        .open(stderr_file_path)
+        .unwrap();
+    let stderr = Stdio::from(stderr_file);
+
+    let stdout_file_path = Directory::logs_directory().unwrap().join("stdout.log");
+    let stdout_file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .read(true)
+        .open(stdout_file_path)
+        .unwrap();
+    let stdout = Stdio::from(stdout_file);
+
+    if let Err(why) = cmd
+        .args(&args[1..])
+        .stderr(stderr)
+        .stdout(stdout)
+        .env("LAPCE_LOG", "lapce_app::app=error,off")
+        .spawn()
+    {
+        eprintln!("Failed to launch lapce: {why}");
+        std::process::exit(1);
+    };
+}
 
-    use tracing::warn;
+pub fn get_socket() -> Result<interprocess::local_socket::LocalSocketStream> {
+    let local_socket = Directory::local_socket()
+        .ok_or_else(|| anyhow!("can't get local socket folder"))?;
+    let socket =
+        interprocess::local_socket::LocalSocketStream::connect(local_socket)?;
+    Ok(socket)
+}
 
-    #[cfg(not(windows))]
-    let shell = match std::env::var("SHELL") {
-        Ok(s) => s,
-        Err(error) => {
-            // Shell variable is not set, so we can't determine the correct shell executable.
-            trace!(
-                TraceLevel::ERROR,
-                "Failed to obtain shell environment: {error}"
-            );
-            return;
+pub fn try_open_in_existing_process(
+    socket: interprocess::local_socket::LocalSocketStream,
+    paths: &[PathObject],
+    diff: Option<(PathBuf, PathBuf)>,
+    url: Option<String>,
+    new_window: bool,
+    wait: bool,
+) -> Result<()> {
+    let timeout = Some(std::time::Duration::from_millis(500));
+    let notification = if new_window {
+        CoreNotification::OpenPathsInNewWindow {
+            paths: paths.to_vec(),
+        }
+    } else {
+        CoreNotification::OpenPaths {
+            paths: paths.to_vec(),
         }
     };
+    let mut socket = send_to_existing_process(socket, notification, timeout)?;
+
+    if let Some((left_path, right_path)) = diff {
+        socket = send_to_existing_process(
+            socket,
+            CoreNotification::OpenDiffFiles {
+                left_path,
+                right_path,
+            },
+            timeout,
+        )?;
+    }
 
-    #[cfg(windows)]
-    let shell = "powershell";
-
-    let mut command = Command::new(shell);
-
-    #[cfg(not(windows))]
-    command.args(["--login", "-c", "printenv"]);
+    if let Some(url) = url {
+        socket = send_to_existing_process(
+            socket,
+            CoreNotification::OpenUrl { url },
+            timeout,
+        )?;
+    }
 
-    #[cfg(windows)]
-    command.args(["{ ls env: | foreach { '{0}={1}' -f $_.Name, $_.Value } }"]);
+    if wait {
+        // The files were opened already, so a failure here mustn't make the
+        // caller start a new process
+        wait_paths_closed(socket, paths);
+    }
 
-    let env = match command.output() {
-        Ok(output) => String::from_utf8(output.stdout).unwrap_or_default(),
+    Ok(())
+}
 
-        Err(error) => {
-            trace!(
-                TraceLevel::ERROR,
-                "Failed to obtain shell environment: {error}"
-            );
-            return;
+/// Wait for the files of `paths` to be opened and closed in the process
+/// launched in the background, once it listens on the single instance socket
+fn wait_in_launched_process(paths: &[PathObject]) {
+    const LAUNCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+    let started = std::time::Instant::now();
+    let socket = loop {
+        match get_socket() {
+            Ok(socket) => break socket,
+            Err(_) if started.elapsed() < LAUNCH_TIMEOUT => {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            Err(e) => {
+                trace!(TraceLevel::ERROR, "failed to wait for file(s): {e}");
+                return;
+            }
         }
     };
-
-    env.split('\n')
-        .filter_map(|line| line.split_once('='))
-        .for_each(|(key, value)| {
-            if let Ok(v) = std::env::var(key) {
-                if v != value {
-                    warn!("Overwriting '{key}', previous value: '{v}', new value '{value}'");
-                }
-            };
-            std::env::set_var(key, value);
-        })
+    wait_paths_closed(socket, paths);
 }
 
-pub fn get_socket() -> Result<interprocess::local_socket::LocalSocketStream> {
-    let local_socket = Directory::local_socket()
-        .ok_or_else(|| anyhow!("can't get local socket folder"))?;
-    let socket =
-        interprocess::local_socket::LocalSocketStream::connect(local_socket)?;
-    Ok(socket)
+/// Block until the files of `paths` are closed in the process at the other
+/// end of `socket`
+fn wait_paths_closed(
+    socket: interprocess::local_socket::LocalSocketStream,
+    paths: &[PathObject],
+) {
+    let files: Vec<PathBuf> = paths
+        .iter()
+        .filter(|p| !p.is_dir)
+        .map(|p| p.path.clone())
+        .collect();
+    if files.is_empty() {
+        return;
+    }
+    // Only answered once the files have been closed
+    if let Err(e) = send_to_existing_process(
+        socket,
+        CoreNotification::WaitPathsClosed { paths: files },
+        None,
+    ) {
+        trace!(TraceLevel::ERROR, "failed to wait for file(s): {e}");
+    }
 }
 
-pub fn try_open_in_existing_process(
+/// Send a notification over the single instance socket, and wait for it to be
+/// acknowledged.
+fn send_to_existing_process(
     mut socket: interprocess::local_socket::LocalSocketStream,
-    paths: &[PathObject],
-) -> Result<()> {
-    let msg: CoreMessage = RpcMessage::Notification(CoreNotification::OpenPaths {
-        paths: paths.to_vec(),
-    });
+    notification: CoreNotification,
+    timeout: Option<std::time::Duration>,
+) -> Result<interprocess::local_socket::LocalSocketStream> {
+    let msg: CoreMessage = RpcMessage::Notification(notification);
     lapce_rpc::stdio::write_msg(&mut socket, msg)?;
 
     let (tx, rx) = crossbeam_channel::bounded(1);
@@ -3883,18 +4512,30 @@ pub fn try_open_in_existing_process(
         } else {
             false
         };
-        tx.send(received)
+        tx.send((received, socket))
     });
 
-    let received = rx.recv_timeout(std::time::Duration::from_millis(500))?;
+    let (received, socket) = match timeout {
+        Some(timeout) => rx.recv_timeout(timeout)?,
+        None => rx.recv()?,
+    };
     if !received {
         return Err(anyhow!("didn't receive response"));
     }
 
-    Ok(())
+    Ok(socket)
 }
 
-fn listen_local_socket(tx: Sender<CoreNotification>) -> Result<()> {
+/// A notification read from the single instance socket. `closed` is set when
+/// the sender is waiting for files to be closed, and is dropped or sent to
+/// once they are.
+#[derive(Clone)]
+struct SocketNotification {
+    notification: CoreNotification,
+    closed: Option<Sender<()>>,
+}
+
+fn listen_local_socket(tx: Sender<SocketNotification>) -> Result<()> {
     let local_socket = Directory::local_socket()
         .ok_or_else(|| anyhow!("can't get local socket folder"))?;
     let _ = std::fs::remove_file(&local_socket);
@@ -3909,8 +4550,21 @@ fn listen_local_socket(tx: Sender<CoreNotification>) -> Result<()> {
                 let msg: Option<CoreMessage> =
                     lapce_rpc::stdio::read_msg(&mut reader)?;
 
-                if let Some(RpcMessage::Notification(msg)) = msg {
-                    tx.send(msg)?;
+                if let Some(RpcMessage::Notification(notification)) = msg {
+                    if let CoreNotification::WaitPathsClosed { .. } = notification {
+                        let (closed_tx, closed_rx) = crossbeam_channel::bounded(1);
+                        tx.send(SocketNotification {
+                            notification,
+                            closed: Some(closed_tx),
+                        })?;
+                        // Either sent to or dropped along with the window tab
+                        let _ = closed_rx.recv();
+                    } else {
+                        tx.send(SocketNotification {
+                            notification,
+                            closed: None,
+                        })?;
+                    }
                 } else {
                     trace!(TraceLevel::ERROR, "Unhandled message: {msg:?}");
                 }
@@ -3924,6 +4578,11 @@ fn listen_local_socket(tx: Sender<CoreNotification>) -> Result<()> {
     Ok(())
 }
 
+fn absolute_path(path: &std::path::Path) -> PathBuf {
+    path.canonicalize()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_default().join(path))
+}
+
 pub fn window_menu(
     lapce_command: Listener<LapceCommand>,
     workbench_command: Listener<LapceWorkbenchCommand>,
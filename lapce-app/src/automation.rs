@@ -0,0 +1,207 @@
+//! The automation socket.
+//!
+//! When `core.automation-socket` is enabled, a second local socket next to the
+//! single instance one accepts [`AutomationRequest`]s from external tools, so
+//! that scripts and integration tests can open files, run commands by name and
+//! read back buffer text and diagnostics. A connection that sends
+//! [`AutomationRequest::Subscribe`] receives [`AutomationEvent`] notifications
+//! from then on.
+//!
+//! Requests are read on a thread per connection and answered on the UI
+//! thread, by [`handle_request`].
+
+use std::{
+    io::BufReader,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use crossbeam_channel::Sender;
+use interprocess::local_socket::{LocalSocketListener, LocalSocketStream};
+use lapce_core::directory::Directory;
+use lapce_rpc::{
+    automation::{
+        AutomationEvent, AutomationMessage, AutomationRequest, AutomationResponse,
+    },
+    file::{LineCol, PathObject},
+    stdio::{read_msg, write_msg},
+    RpcError, RpcMessage,
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::{
+    app::AppData, command::LapceCommand, doc::Doc, tracing::*,
+    window_tab::WindowTabData,
+};
+
+/// How long a connection waits for the UI thread to answer a request
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+static SUBSCRIBERS: Lazy<Mutex<Vec<Sender<AutomationEvent>>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+/// A request read from a connection, along with where to send the answer
+#[derive(Clone)]
+pub struct AutomationCall {
+    pub request: AutomationRequest,
+    pub reply: Sender<Result<AutomationResponse, String>>,
+}
+
+/// Send the event to every subscribed connection.
+pub fn emit(event: AutomationEvent) {
+    let mut subscribers = SUBSCRIBERS.lock();
+    // Connections that have gone away have dropped their receiver
+    subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+}
+
+pub fn listen_automation_socket(tx: Sender<AutomationCall>) -> Result<()> {
+    let local_socket = Directory::automation_socket()
+        .ok_or_else(|| anyhow!("can't get local socket folder"))?;
+    let _ = std::fs::remove_file(&local_socket);
+    let socket = LocalSocketListener::bind(local_socket)?;
+
+    for stream in socket.incoming().flatten() {
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = serve_connection(stream, tx) {
+                trace!(TraceLevel::DEBUG, "automation connection closed: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve_connection(
+    stream: LocalSocketStream,
+    tx: Sender<AutomationCall>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let msg: Option<AutomationMessage> = read_msg(&mut reader)?;
+        let Some(RpcMessage::Request(id, request)) = msg else {
+            trace!(TraceLevel::ERROR, "Unhandled automation message: {msg:?}");
+            continue;
+        };
+
+        let stream = reader.get_mut();
+        if let AutomationRequest::Subscribe = request {
+            let (event_tx, event_rx) = crossbeam_channel::unbounded();
+            SUBSCRIBERS.lock().push(event_tx);
+            let msg: AutomationMessage =
+                RpcMessage::Response(id, AutomationResponse::Subscribed);
+            write_msg(stream, msg)?;
+            for event in event_rx {
+                let msg: AutomationMessage = RpcMessage::Notification(event);
+                write_msg(stream, msg)?;
+            }
+            return Ok(());
+        }
+
+        let (reply_tx, reply_rx) = crossbeam_channel::bounded(1);
+        tx.send(AutomationCall {
+            request,
+            reply: reply_tx,
+        })?;
+        let result = reply_rx
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| Err("timed out waiting for the editor".to_string()));
+        let msg: AutomationMessage = match result {
+            Ok(resp) => RpcMessage::Response(id, resp),
+            Err(message) => RpcMessage::Error(id, RpcError { code: 0, message }),
+        };
+        write_msg(stream, msg)?;
+    }
+}
+
+/// Answer a request against the active window tab.
+pub fn handle_request(
+    app_data: &AppData,
+    request: AutomationRequest,
+) -> Result<AutomationResponse, String> {
+    let window_tab = app_data
+        .active_window_tab()
+        .ok_or_else(|| "there is no open window".to_string())?;
+
+    match request {
+        AutomationRequest::OpenFile { path, line, column } => {
+            window_tab.open_paths(&[PathObject {
+                path: resolve_path(&window_tab, &path),
+                linecol: line.map(|line| LineCol {
+                    line,
+                    column: column.unwrap_or(1),
+                }),
+                is_dir: false,
+            }]);
+            Ok(AutomationResponse::Done)
+        }
+        AutomationRequest::RunCommand { command, data } => {
+            let cmd = window_tab
+                .common
+                .keypress
+                .with_untracked(|keypress| keypress.commands.get(&command).cloned())
+                .ok_or_else(|| format!("unknown command {command}"))?;
+            window_tab.run_lapce_command(LapceCommand {
+                kind: cmd.kind,
+                data: data.or(cmd.data),
+            });
+            Ok(AutomationResponse::Done)
+        }
+        AutomationRequest::GetText { path } => {
+            let path = target_path(&window_tab, path)?;
+            let doc = open_doc(&window_tab, &path)?;
+            let content = doc.buffer.with_untracked(|buffer| buffer.to_string());
+            Ok(AutomationResponse::Text { path, content })
+        }
+        AutomationRequest::GetDiagnostics { path } => {
+            let path = target_path(&window_tab, path)?;
+            let diagnostics = window_tab
+                .main_split
+                .diagnostics
+                .with_untracked(|diagnostics| {
+                    diagnostics.get(&path).map(|d| {
+                        d.diagnostics
+                            .with_untracked(|d| d.iter().cloned().collect())
+                    })
+                })
+                .unwrap_or_default();
+            Ok(AutomationResponse::Diagnostics { path, diagnostics })
+        }
+        AutomationRequest::Subscribe => {
+            Err("subscribe is handled by the connection".to_string())
+        }
+    }
+}
+
+/// Relative paths are taken to be relative to the workspace.
+fn resolve_path(window_tab: &WindowTabData, path: &Path) -> PathBuf {
+    match &window_tab.workspace.path {
+        Some(workspace) if path.is_relative() => workspace.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn target_path(
+    window_tab: &WindowTabData,
+    path: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    match path {
+        Some(path) => Ok(resolve_path(window_tab, &path)),
+        None => window_tab
+            .main_split
+            .active_editor
+            .get_untracked()
+            .and_then(|editor| editor.doc().content.get_untracked().path().cloned())
+            .ok_or_else(|| "the active editor isn't showing a file".to_string()),
+    }
+}
+
+fn open_doc(window_tab: &WindowTabData, path: &Path) -> Result<Rc<Doc>, String> {
+    window_tab
+        .main_split
+        .docs
+        .with_untracked(|docs| docs.get(path).cloned())
+        .ok_or_else(|| format!("{} is not open", path.display()))
+}
@@ -0,0 +1,82 @@
+//! The blame of a file, with how recent each commit in it is, for tinting the
+//! gutter by the age of the lines.
+
+use floem::peniko::Color;
+use lapce_rpc::source_control::{BlameInfo, GitCommitInfo};
+
+use crate::config::{color::LapceColor, LapceConfig};
+
+/// How many steps the legend of the tint shows
+pub const HEAT_LEGEND_STEPS: usize = 5;
+
+#[derive(Clone, Debug)]
+pub struct BlameData {
+    pub blame: BlameInfo,
+    /// How recent each commit is among the commits of the file, from 0.0 for
+    /// the oldest to 1.0 for the newest
+    heat: Vec<f64>,
+}
+
+impl BlameData {
+    pub fn new(blame: BlameInfo) -> Self {
+        let heat = commit_heat(&blame.commits);
+        Self { blame, heat }
+    }
+
+    /// The commit of a line of the `head` version, and how recent it is
+    pub fn line(&self, line: usize) -> Option<(&GitCommitInfo, f64)> {
+        let index = *self.blame.lines.get(line)?;
+        Some((self.blame.commits.get(index)?, self.heat[index]))
+    }
+}
+
+/// The tint of a line whose commit is `heat` recent.
+pub fn heat_color(config: &LapceConfig, heat: f64) -> Color {
+    config
+        .color(LapceColor::EDITOR_BLAME_HEAT)
+        .with_alpha_factor(0.05 + 0.45 * heat as f32)
+}
+
+/// Rank the commits by time rather than spread them over the time between
+/// the oldest and newest, so that a single old commit doesn't leave all the
+/// others looking equally new.
+fn commit_heat(commits: &[GitCommitInfo]) -> Vec<f64> {
+    let mut times: Vec<i64> = commits.iter().map(|commit| commit.time).collect();
+    times.sort_unstable();
+    times.dedup();
+    let newest = times.len().saturating_sub(1);
+    commits
+        .iter()
+        .map(|commit| {
+            if newest == 0 {
+                return 1.0;
+            }
+            let rank = times.binary_search(&commit.time).unwrap_or(0);
+            rank as f64 / newest as f64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::source_control::GitCommitInfo;
+
+    use super::commit_heat;
+
+    fn commit(time: i64) -> GitCommitInfo {
+        GitCommitInfo {
+            id: String::new(),
+            summary: String::new(),
+            author: String::new(),
+            time,
+        }
+    }
+
+    #[test]
+    fn test_commit_heat() {
+        let commits = [commit(10), commit(1_000_000), commit(20), commit(10)];
+        assert_eq!(commit_heat(&commits), vec![0.0, 1.0, 0.5, 0.0]);
+        assert_eq!(commit_heat(&[commit(5)]), vec![1.0]);
+        assert!(commit_heat(&[]).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+//! The primary selection of X11, which holds the text selected last and is
+//! pasted with a middle click. It is only supported in X11 sessions: the
+//! Wayland primary selection needs the display of the window, which floem
+//! doesn't share, so it is left out in Wayland sessions as on the platforms
+//! without one, where [`PrimaryClipboard`] is always empty.
+
+use floem::views::editor::core::register::Clipboard;
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use copypasta::{
+        x11_clipboard::{Primary, X11ClipboardContext},
+        ClipboardProvider,
+    };
+    use once_cell::sync::Lazy;
+    use parking_lot::Mutex;
+
+    /// Kept for the lifetime of the app, because the selection is served from
+    /// it for as long as Lapce owns it
+    static PRIMARY: Lazy<Mutex<Option<X11ClipboardContext<Primary>>>> =
+        Lazy::new(|| Mutex::new(X11ClipboardContext::<Primary>::new().ok()));
+
+    /// Asked for on a connection of its own, as the owner of the selection
+    /// may take a while to answer, and the one serving the selection is
+    /// kept free meanwhile
+    pub fn get_string() -> Option<String> {
+        X11ClipboardContext::<Primary>::new()
+            .ok()?
+            .get_contents()
+            .ok()
+    }
+
+    pub fn put_string(s: &str) {
+        if let Some(primary) = PRIMARY.lock().as_mut() {
+            let _ = primary.set_contents(s.to_string());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    pub fn get_string() -> Option<String> {
+        None
+    }
+
+    pub fn put_string(_s: &str) {}
+}
+
+/// The primary selection, used like [`floem::views::editor::text::SystemClipboard`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrimaryClipboard;
+
+impl PrimaryClipboard {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether the session has a primary selection, which is an X11 one.
+    /// A Wayland session is one even when X11 apps can run in it through
+    /// XWayland, as the windows are Wayland ones then.
+    pub fn is_supported() -> bool {
+        cfg!(target_os = "linux")
+            && std::env::var_os("DISPLAY").is_some()
+            && std::env::var_os("WAYLAND_DISPLAY").is_none()
+    }
+}
+
+impl Clipboard for PrimaryClipboard {
+    /// Blocks until the owner of the selection answers, so it is better not
+    /// called on the UI thread
+    fn get_string(&mut self) -> Option<String> {
+        platform::get_string()
+    }
+
+    fn put_string(&mut self, s: impl AsRef<str>) {
+        platform::put_string(s.as_ref())
+    }
+}
@@ -13,7 +13,8 @@
     dap_types::{DapId, RunDebugConfig},
     plugin::{PluginId, VoltID},
     proxy::ProxyStatus,
-    terminal::{TermId, TerminalProfile},
+    source_control::GitCommitInfo,
+    terminal::TermId,
 };
 use lsp_types::{CodeActionOrCommand, Position, WorkspaceEdit};
 use serde_json::Value;
@@ -29,6 +30,7 @@
     editor_tab::EditorTabChild,
     id::EditorTabId,
     main_split::{SplitDirection, SplitMoveDirection},
+    notification::NotificationAction,
     workspace::LapceWorkspace,
 };
 
@@ -195,6 +197,26 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Show Environment")]
     ShowEnvironment,
 
+    #[strum(serialize = "toggle_perf_overlay")]
+    #[strum(message = "Toggle Performance Overlay")]
+    TogglePerfOverlay,
+
+    #[strum(serialize = "export_settings")]
+    #[strum(message = "Export Settings")]
+    ExportSettings,
+
+    #[strum(serialize = "import_settings")]
+    #[strum(message = "Import Settings")]
+    ImportSettings,
+
+    #[strum(serialize = "open_flame_graph")]
+    #[strum(message = "Open Profile in Flame Graph")]
+    OpenFlameGraph,
+
+    #[strum(serialize = "sync_settings")]
+    #[strum(message = "Sync Settings")]
+    SyncSettings,
+
     #[strum(serialize = "change_color_theme")]
     #[strum(message = "Change Color Theme")]
     ChangeColorTheme,
@@ -203,6 +225,26 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Change Icon Theme")]
     ChangeIconTheme,
 
+    #[strum(serialize = "switch_profile")]
+    #[strum(message = "Switch Profile")]
+    SwitchProfile,
+
+    #[strum(serialize = "roll_back_workspace_refactor")]
+    #[strum(message = "Roll Back Workspace Refactor")]
+    RollBackWorkspaceRefactor,
+
+    #[strum(serialize = "manage_grammars")]
+    #[strum(message = "Manage Tree-sitter Grammars")]
+    ManageGrammars,
+
+    #[strum(serialize = "open_profile_settings_file")]
+    #[strum(message = "Open Profile Settings File")]
+    OpenProfileSettingsFile,
+
+    #[strum(serialize = "open_profile_keyboard_shortcuts_file")]
+    #[strum(message = "Open Profile Keyboard Shortcuts File")]
+    OpenProfileKeyboardShortcutsFile,
+
     #[strum(serialize = "open_settings")]
     #[strum(message = "Open Settings")]
     OpenSettings,
@@ -227,6 +269,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Keyboard Shortcuts File")]
     OpenKeyboardShortcutsFile,
 
+    #[strum(serialize = "open_run_configurations")]
+    #[strum(message = "Open Run and Debug Configurations")]
+    OpenRunConfigurations,
+
     #[strum(serialize = "open_log_file")]
     #[strum(message = "Open Log File")]
     OpenLogFile,
@@ -235,6 +281,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Logs Directory")]
     OpenLogsDirectory,
 
+    #[strum(serialize = "export_diagnostic_bundle")]
+    #[strum(message = "Export Diagnostic Bundle")]
+    ExportDiagnosticBundle,
+
     #[strum(serialize = "open_proxy_directory")]
     #[strum(message = "Open Proxy Directory")]
     OpenProxyDirectory,
@@ -247,6 +297,14 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Plugins Directory")]
     OpenPluginsDirectory,
 
+    #[strum(serialize = "open_file_templates_directory")]
+    #[strum(message = "Open File Templates Directory")]
+    OpenFileTemplatesDirectory,
+
+    #[strum(serialize = "open_query_overrides_directory")]
+    #[strum(message = "Open Query Overrides Directory")]
+    OpenQueryOverridesDirectory,
+
     #[strum(serialize = "zoom_in")]
     #[strum(message = "Zoom In")]
     ZoomIn,
@@ -283,6 +341,18 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Previous Terminal Tab")]
     PreviousTerminalTab,
 
+    #[strum(serialize = "terminal_previous_command")]
+    #[strum(message = "Terminal: Scroll to Previous Command")]
+    TerminalPreviousCommand,
+
+    #[strum(serialize = "terminal_next_command")]
+    #[strum(message = "Terminal: Scroll to Next Command")]
+    TerminalNextCommand,
+
+    #[strum(serialize = "open_last_command_output")]
+    #[strum(message = "Terminal: Open Last Command Output in Editor")]
+    OpenLastCommandOutput,
+
     #[strum(serialize = "next_window_tab")]
     #[strum(message = "Go To Next Window Tab")]
     NextWindowTab,
@@ -316,6 +386,18 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Connect to WSL Host")]
     ConnectWslHost,
 
+    #[strum(serialize = "open_folder_in_container")]
+    #[strum(message = "Dev Container: Open Folder in Container")]
+    OpenFolderInContainer,
+
+    #[strum(serialize = "rebuild_container")]
+    #[strum(message = "Dev Container: Rebuild Container")]
+    RebuildContainer,
+
+    #[strum(serialize = "stop_container")]
+    #[strum(message = "Dev Container: Stop Container")]
+    StopContainer,
+
     #[strum(serialize = "disconnect_remote")]
     #[strum(message = "Disconnect From Remote")]
     DisconnectRemote,
@@ -348,6 +430,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "palette.run_and_debug")]
     PaletteRunAndDebug,
 
+    #[strum(message = "Debug: Attach to Process")]
+    #[strum(serialize = "palette.attach_process")]
+    PaletteAttachProcess,
+
     #[strum(message = "Source Control: Checkout")]
     #[strum(serialize = "palette.scm_references")]
     PaletteSCMReferences,
@@ -356,6 +442,45 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "palette.palette_help")]
     PaletteHelp,
 
+    #[strum(message = "Command History")]
+    #[strum(serialize = "palette.command_history")]
+    PaletteCommandHistory,
+
+    #[strum(message = "Repeat Last Command")]
+    #[strum(serialize = "repeat_last_command")]
+    RepeatLastCommand,
+
+    /// Run the nth most recent command again, with n as the data, which is
+    /// bound in the keymaps as `repeat_command.<n>`
+    #[strum(serialize = "repeat_command")]
+    RepeatCommand,
+
+    #[strum(message = "Start Recording Macro")]
+    #[strum(serialize = "start_macro_recording")]
+    StartMacroRecording,
+
+    #[strum(message = "Stop Recording Macro")]
+    #[strum(serialize = "stop_macro_recording")]
+    StopMacroRecording,
+
+    /// Replay the last macro recorded, as many times as the data says, or
+    /// once when there is none
+    #[strum(message = "Replay Last Recorded Macro")]
+    #[strum(serialize = "replay_macro")]
+    ReplayMacro,
+
+    /// Save the last macro recorded under the name which is the data, or
+    /// pick the name in the palette when there is none
+    #[strum(message = "Save Last Recorded Macro")]
+    #[strum(serialize = "save_macro")]
+    SaveMacro,
+
+    /// Run the saved macro of the [`MacroRun`](crate::command_macro::MacroRun)
+    /// which is the data, or pick one in the palette when there is none
+    #[strum(message = "Run Saved Macro")]
+    #[strum(serialize = "run_macro")]
+    RunMacro,
+
     #[strum(message = "Run and Debug Restart Current Running")]
     #[strum(serialize = "palette.run_and_debug_restart")]
     RunAndDebugRestart,
@@ -416,6 +541,38 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "toggle_problem_focus")]
     ToggleProblemFocus,
 
+    #[strum(message = "Toggle Debug Console Focus")]
+    #[strum(serialize = "toggle_debug_console_focus")]
+    ToggleDebugConsoleFocus,
+
+    #[strum(message = "Toggle Flame Graph Focus")]
+    #[strum(serialize = "toggle_flame_focus")]
+    ToggleFlameFocus,
+
+    #[strum(message = "Toggle Output Focus")]
+    #[strum(serialize = "toggle_output_focus")]
+    ToggleOutputFocus,
+
+    #[strum(message = "Toggle JSON Tree Focus")]
+    #[strum(serialize = "toggle_json_tree_focus")]
+    ToggleJsonTreeFocus,
+
+    #[strum(message = "Toggle Syntax Tree Focus")]
+    #[strum(serialize = "toggle_syntax_tree_focus")]
+    ToggleSyntaxTreeFocus,
+
+    #[strum(message = "Toggle Notifications Focus")]
+    #[strum(serialize = "toggle_notifications_focus")]
+    ToggleNotificationsFocus,
+
+    #[strum(message = "Toggle Do Not Disturb")]
+    #[strum(serialize = "toggle_do_not_disturb")]
+    ToggleDoNotDisturb,
+
+    #[strum(message = "Clear Notifications")]
+    #[strum(serialize = "clear_notifications")]
+    ClearNotifications,
+
     #[strum(message = "Toggle Search Focus")]
     #[strum(serialize = "toggle_search_focus")]
     ToggleSearchFocus,
@@ -439,6 +596,24 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "toggle_debug_visual")]
     ToggleDebugVisual,
 
+    #[strum(serialize = "toggle_debug_console_visual")]
+    ToggleDebugConsoleVisual,
+
+    #[strum(serialize = "toggle_flame_visual")]
+    ToggleFlameVisual,
+
+    #[strum(serialize = "toggle_output_visual")]
+    ToggleOutputVisual,
+
+    #[strum(serialize = "toggle_json_tree_visual")]
+    ToggleJsonTreeVisual,
+
+    #[strum(serialize = "toggle_syntax_tree_visual")]
+    ToggleSyntaxTreeVisual,
+
+    #[strum(serialize = "toggle_notifications_visual")]
+    ToggleNotificationsVisual,
+
     #[strum(serialize = "toggle_search_visual")]
     ToggleSearchVisual,
 
@@ -494,6 +669,66 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Previous Editor Tab")]
     PreviousEditorTab,
 
+    #[strum(serialize = "toggle_word_wrap")]
+    #[strum(message = "Toggle Word Wrap")]
+    ToggleWordWrap,
+
+    #[strum(serialize = "toggle_log_mode")]
+    #[strum(message = "Toggle Log Mode")]
+    ToggleLogMode,
+
+    #[strum(serialize = "toggle_log_follow")]
+    #[strum(message = "Log: Toggle Follow Tail")]
+    ToggleLogFollow,
+
+    #[strum(serialize = "copy_json_path")]
+    #[strum(message = "Copy JSON Path")]
+    CopyJsonPath,
+
+    #[strum(serialize = "format_json")]
+    #[strum(message = "Format JSON")]
+    FormatJson,
+
+    #[strum(serialize = "minify_json")]
+    #[strum(message = "Minify JSON")]
+    MinifyJson,
+
+    #[strum(serialize = "sort_json_keys")]
+    #[strum(message = "Sort JSON Keys")]
+    SortJsonKeys,
+
+    #[strum(serialize = "format_toml")]
+    #[strum(message = "Format TOML")]
+    FormatToml,
+
+    #[strum(serialize = "format_xml")]
+    #[strum(message = "Format XML")]
+    FormatXml,
+
+    #[strum(serialize = "select_all_matches_in_selection")]
+    #[strum(message = "Select All Matches in Selection")]
+    SelectAllMatchesInSelection,
+
+    #[strum(serialize = "split_selection_into_lines")]
+    #[strum(message = "Split Selection into Lines")]
+    SplitSelectionIntoLines,
+
+    #[strum(serialize = "select_all_find_matches")]
+    #[strum(message = "Select All Find Matches")]
+    SelectAllFindMatches,
+
+    #[strum(serialize = "add_cursor_at_next_find_match")]
+    #[strum(message = "Add Cursor at Next Find Match")]
+    AddCursorAtNextFindMatch,
+
+    #[strum(serialize = "show_file_history")]
+    #[strum(message = "Source Control: File History")]
+    ShowFileHistory,
+
+    #[strum(serialize = "show_line_history")]
+    #[strum(message = "Source Control: Line/Selection History")]
+    ShowLineHistory,
+
     #[strum(serialize = "toggle_inlay_hints")]
     #[strum(message = "Toggle Inlay Hints")]
     ToggleInlayHints,
@@ -509,6 +744,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "save_all")]
     SaveAll,
 
+    #[strum(message = "Close and Free")]
+    #[strum(serialize = "close_and_free")]
+    CloseAndFree,
+
     #[cfg(target_os = "macos")]
     #[strum(message = "Install Lapce to PATH")]
     #[strum(serialize = "install_to_path")]
@@ -584,9 +823,22 @@ pub enum InternalCommand {
     JumpToLocation {
         location: EditorLocation,
     },
+    /// Open a file in the background, ahead of it being jumped to
+    PrefetchDoc {
+        path: PathBuf,
+    },
     PaletteReferences {
         references: Vec<EditorLocation>,
     },
+    PaletteGitHistory {
+        path: PathBuf,
+        commits: Vec<GitCommitInfo>,
+    },
+    /// Diff the file as of the commit against its parent
+    OpenCommitChanges {
+        path: PathBuf,
+        commit: String,
+    },
     SaveJumpLocation {
         path: PathBuf,
         offset: usize,
@@ -603,8 +855,9 @@ pub enum InternalCommand {
     SplitExchange {
         editor_tab_id: EditorTabId,
     },
+    /// Open a terminal with the profile of this name, or the default one
     NewTerminal {
-        profile: Option<TerminalProfile>,
+        profile: Option<String>,
     },
     SplitTerminal {
         term_id: TermId,
@@ -641,6 +894,18 @@ pub enum InternalCommand {
     },
     ApplyWorkspaceEdit {
         edit: WorkspaceEdit,
+        /// What the edit does, for the journal of the refactors
+        title: String,
+    },
+    RollbackRefactor {
+        id: u64,
+    },
+    /// Install the tree-sitter grammar at a URL or a local path
+    InstallGrammar {
+        source: String,
+    },
+    RunNotificationAction {
+        action: NotificationAction,
     },
     RunAndDebug {
         mode: RunDebugMode,
@@ -685,6 +950,10 @@ pub enum InternalCommand {
         /// Whether to save the theme to the config file
         save: bool,
     },
+    SetProfile {
+        /// `None` for the default profile
+        name: Option<String>,
+    },
     SetModal {
         modal: bool,
     },
@@ -0,0 +1,168 @@
+//! The commands run in a window tab, from the keymaps, the palette and the
+//! menus, for the most recent ones to be run again.
+
+use std::collections::VecDeque;
+
+use serde_json::Value;
+
+use crate::command::{CommandKind, LapceCommand, LapceWorkbenchCommand};
+
+/// The most commands kept, past which the oldest are forgotten
+const MAX_HISTORY: usize = 100;
+
+/// The keymap command that runs the nth most recent command, e.g.
+/// `repeat_command.2` for the one before the last
+const REPEAT_COMMAND_PREFIX: &str = "repeat_command.";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub cmd: LapceCommand,
+    /// The count typed before the command, in the modal mode
+    pub count: Option<usize>,
+}
+
+impl HistoryEntry {
+    /// The description of the command, with what it was run with
+    pub fn label(&self) -> String {
+        let mut label = self
+            .cmd
+            .kind
+            .desc()
+            .unwrap_or_else(|| self.cmd.kind.str())
+            .to_string();
+        if let Some(data) = &self.cmd.data {
+            label.push(' ');
+            label.push_str(&data.to_string());
+        }
+        if let Some(count) = self.count {
+            label.push_str(&format!(" ({count} times)"));
+        }
+        label
+    }
+}
+
+/// The commands run in a window tab, the most recent first
+#[derive(Clone, Debug, Default)]
+pub struct CommandHistory {
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl CommandHistory {
+    /// Record that `cmd` was run, unless it is a command which is not worth
+    /// running again, such as a cursor movement or one of running the history
+    pub fn record(&mut self, cmd: &LapceCommand, count: Option<usize>) {
+        if !is_recorded(&cmd.kind) {
+            return;
+        }
+        let entry = HistoryEntry {
+            cmd: cmd.clone(),
+            count,
+        };
+        // A command run again is moved up rather than listed twice
+        self.entries.retain(|e| e != &entry);
+        self.entries.push_front(entry);
+        self.entries.truncate(MAX_HISTORY);
+    }
+
+    /// The `n`th most recent command, from 1 for the last one
+    pub fn nth(&self, n: usize) -> Option<&HistoryEntry> {
+        self.entries.get(n.checked_sub(1)?)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+}
+
+fn is_recorded(kind: &CommandKind) -> bool {
+    match kind {
+        CommandKind::Workbench(cmd) => !matches!(
+            cmd,
+            LapceWorkbenchCommand::RepeatLastCommand
+                | LapceWorkbenchCommand::RepeatCommand
+                | LapceWorkbenchCommand::PaletteCommandHistory
+        ),
+        CommandKind::Edit(_)
+        | CommandKind::Focus(_)
+        | CommandKind::MultiSelection(_) => true,
+        CommandKind::Move(_)
+        | CommandKind::Scroll(_)
+        | CommandKind::MotionMode(_) => false,
+    }
+}
+
+/// The command of running the `n`th most recent command, from 1 for the last
+/// one
+pub fn repeat_command(n: usize) -> LapceCommand {
+    LapceCommand {
+        kind: CommandKind::Workbench(LapceWorkbenchCommand::RepeatCommand),
+        data: Some(Value::from(n)),
+    }
+}
+
+/// The command of a keymap bound to `repeat_command.<n>`
+pub fn parse_repeat_command(command: &str) -> Option<LapceCommand> {
+    let n = command.strip_prefix(REPEAT_COMMAND_PREFIX)?.parse().ok()?;
+    Some(repeat_command(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::command::{EditCommand, MoveCommand};
+    use serde_json::Value;
+
+    use super::{parse_repeat_command, repeat_command, CommandHistory};
+    use crate::command::{CommandKind, LapceCommand, LapceWorkbenchCommand};
+
+    fn command(kind: CommandKind) -> LapceCommand {
+        LapceCommand { kind, data: None }
+    }
+
+    #[test]
+    fn test_command_history() {
+        let mut history = CommandHistory::default();
+        let undo = command(CommandKind::Edit(EditCommand::Undo));
+        let zoom = command(CommandKind::Workbench(LapceWorkbenchCommand::ZoomIn));
+
+        history.record(&undo, None);
+        history.record(&zoom, None);
+        // Neither movements nor running the history are recorded
+        history.record(&command(CommandKind::Move(MoveCommand::Down)), None);
+        history.record(&repeat_command(2), None);
+        assert_eq!(history.nth(1).map(|e| &e.cmd), Some(&zoom));
+        assert_eq!(history.nth(2).map(|e| &e.cmd), Some(&undo));
+        assert_eq!(history.nth(0), None);
+        assert_eq!(history.nth(3), None);
+
+        // Running a command again moves it up
+        history.record(&undo, None);
+        assert_eq!(history.entries().count(), 2);
+        assert_eq!(history.nth(1).map(|e| &e.cmd), Some(&undo));
+
+        // The same command with another count is another entry
+        history.record(&zoom, Some(3));
+        assert_eq!(history.entries().count(), 3);
+        assert_eq!(history.nth(1).unwrap().label(), "Zoom In (3 times)");
+
+        let checkout = LapceCommand {
+            kind: CommandKind::Workbench(LapceWorkbenchCommand::CheckoutReference),
+            data: Some(Value::from("main")),
+        };
+        history.record(&checkout, None);
+        assert_eq!(
+            history.nth(1).unwrap().label(),
+            "source_control.checkout_reference \"main\""
+        );
+    }
+
+    #[test]
+    fn test_parse_repeat_command() {
+        assert_eq!(
+            parse_repeat_command("repeat_command.3"),
+            Some(repeat_command(3))
+        );
+        assert_eq!(repeat_command(3).data, Some(Value::from(3)));
+        assert_eq!(parse_repeat_command("repeat_command."), None);
+        assert_eq!(parse_repeat_command("undo"), None);
+    }
+}
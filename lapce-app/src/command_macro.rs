@@ -0,0 +1,339 @@
+//! Macros, the commands run and the text typed in a window tab, recorded to be
+//! replayed and saved under a name in the settings, for them to be run from
+//! the palette or bound in the keymaps as `run_macro.<name>`, or
+//! `run_macro.<name>.<count>` to run them count times.
+//!
+//! A saved macro is an array of steps in the `macros` table of the settings,
+//! each being a command, with its data as JSON and its count, or text typed:
+//!
+//! ```toml
+//! [macros]
+//! quote-line = [
+//!     { command = "line_start_non_blank" },
+//!     { text = "\"" },
+//!     { command = "line_end" },
+//!     { text = "\"" },
+//!     { command = "down", count = 2 },
+//! ]
+//! ```
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    command::{CommandKind, LapceCommand, LapceWorkbenchCommand},
+    palette::kind::PaletteKind,
+};
+
+const RUN_MACRO_PREFIX: &str = "run_macro.";
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum MacroStep {
+    Command {
+        command: String,
+        /// The data of the command, as JSON
+        #[serde(default)]
+        data: Option<String>,
+        /// The count typed before the command, in the modal mode
+        #[serde(default)]
+        count: Option<usize>,
+    },
+    Text {
+        text: String,
+    },
+}
+
+impl MacroStep {
+    pub fn command(cmd: &LapceCommand, count: Option<usize>) -> Self {
+        MacroStep::Command {
+            command: cmd.kind.str().to_string(),
+            data: cmd.data.as_ref().map(|data| data.to_string()),
+            count,
+        }
+    }
+}
+
+/// The data of [`LapceWorkbenchCommand::RunMacro`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroRun {
+    pub name: String,
+    /// How many times to run the macro, once when there is none
+    #[serde(default)]
+    pub count: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MacroRecorder {
+    /// The steps recorded so far, while recording
+    recording: Option<Vec<MacroStep>>,
+    /// The steps of the last macro recorded
+    last: Vec<MacroStep>,
+}
+
+impl MacroRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stop recording, keeping what was recorded as the last macro unless
+    /// nothing was
+    pub fn stop(&mut self) {
+        if let Some(steps) = self.recording.take() {
+            if !steps.is_empty() {
+                self.last = steps;
+            }
+        }
+    }
+
+    pub fn last(&self) -> &[MacroStep] {
+        &self.last
+    }
+
+    pub fn record_command(&mut self, cmd: &LapceCommand, count: Option<usize>) {
+        // The commands of the history are recorded as the commands they run,
+        // and the palettes as the commands picked in them
+        let is_recorded = !is_macro_command(&cmd.kind)
+            && !opens_palette(&cmd.kind)
+            && !matches!(
+                cmd.kind,
+                CommandKind::Workbench(
+                    LapceWorkbenchCommand::RepeatLastCommand
+                        | LapceWorkbenchCommand::RepeatCommand
+                )
+            );
+        if let Some(steps) = self.recording.as_mut().filter(|_| is_recorded) {
+            steps.push(MacroStep::command(cmd, count));
+        }
+    }
+
+    pub fn record_text(&mut self, text: &str) {
+        let Some(steps) = self.recording.as_mut() else {
+            return;
+        };
+        if let Some(MacroStep::Text { text: last }) = steps.last_mut() {
+            last.push_str(text);
+        } else {
+            steps.push(MacroStep::Text {
+                text: text.to_string(),
+            });
+        }
+    }
+}
+
+/// Whether `kind` records, saves or runs macros, which aren't recorded in
+/// macros nor run from them, so that a macro can't run itself
+pub fn is_macro_command(kind: &CommandKind) -> bool {
+    matches!(
+        kind,
+        CommandKind::Workbench(
+            LapceWorkbenchCommand::StartMacroRecording
+                | LapceWorkbenchCommand::StopMacroRecording
+                | LapceWorkbenchCommand::ReplayMacro
+                | LapceWorkbenchCommand::SaveMacro
+                | LapceWorkbenchCommand::RunMacro
+        )
+    )
+}
+
+fn opens_palette(kind: &CommandKind) -> bool {
+    let CommandKind::Workbench(cmd) = kind else {
+        return false;
+    };
+    PaletteKind::iter().any(|palette| palette.command().as_ref() == Some(cmd))
+}
+
+/// The command run by `step`, with its count, when it is a command which
+/// exists
+pub fn step_command(
+    step: &MacroStep,
+    commands: &IndexMap<String, LapceCommand>,
+) -> Option<(LapceCommand, Option<usize>)> {
+    let MacroStep::Command {
+        command,
+        data,
+        count,
+    } = step
+    else {
+        return None;
+    };
+    let mut cmd = commands.get(command)?.clone();
+    if let Some(data) = data {
+        cmd.data = Some(serde_json::from_str(data).ok()?);
+    }
+    Some((cmd, *count))
+}
+
+/// Macro names are keys of the settings, which are neither case sensitive
+/// nor allowed to have dots
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_'
+        })
+}
+
+/// The command of running the macro `name`, `count` times
+pub fn run_macro_command(name: &str, count: Option<usize>) -> LapceCommand {
+    LapceCommand {
+        kind: CommandKind::Workbench(LapceWorkbenchCommand::RunMacro),
+        data: serde_json::to_value(MacroRun {
+            name: name.to_string(),
+            count,
+        })
+        .ok(),
+    }
+}
+
+/// The command of a keymap bound to `run_macro.<name>` or
+/// `run_macro.<name>.<count>`
+pub fn parse_run_macro_command(command: &str) -> Option<LapceCommand> {
+    let name = command.strip_prefix(RUN_MACRO_PREFIX)?;
+    let (name, count) = match name.split_once('.') {
+        Some((name, count)) => (name, Some(count.parse().ok()?)),
+        None => (name, None),
+    };
+    is_valid_name(name).then(|| run_macro_command(name, count))
+}
+
+/// The steps as the value of a macro in the settings, one step a line
+pub fn steps_to_toml(steps: &[MacroStep]) -> toml_edit::Value {
+    let mut array = toml_edit::Array::new();
+    for step in steps {
+        let mut table = toml_edit::InlineTable::new();
+        match step {
+            MacroStep::Command {
+                command,
+                data,
+                count,
+            } => {
+                table.insert("command", command.as_str().into());
+                if let Some(data) = data {
+                    table.insert("data", data.as_str().into());
+                }
+                if let Some(count) = count {
+                    table.insert("count", (*count as i64).into());
+                }
+            }
+            MacroStep::Text { text } => {
+                table.insert("text", text.as_str().into());
+            }
+        }
+        let mut value = toml_edit::Value::from(table);
+        value.decor_mut().set_prefix("\n    ");
+        array.push_formatted(value);
+    }
+    array.set_trailing("\n");
+    array.set_trailing_comma(true);
+    toml_edit::Value::Array(array)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{
+        is_valid_name, parse_run_macro_command, run_macro_command, steps_to_toml,
+        MacroRecorder, MacroStep,
+    };
+    use crate::command::{CommandKind, LapceCommand, LapceWorkbenchCommand};
+
+    fn command(command: &str, count: Option<usize>) -> MacroStep {
+        MacroStep::Command {
+            command: command.to_string(),
+            data: None,
+            count,
+        }
+    }
+
+    #[test]
+    fn test_macro_recorder() {
+        let zoom = LapceCommand {
+            kind: CommandKind::Workbench(LapceWorkbenchCommand::ZoomIn),
+            data: None,
+        };
+        let mut recorder = MacroRecorder::default();
+        recorder.record_command(&zoom, None);
+        assert!(!recorder.is_recording());
+
+        recorder.start();
+        recorder.record_text("a");
+        recorder.record_text("b");
+        recorder.record_command(&zoom, Some(2));
+        // Neither running a macro nor opening a palette is recorded
+        recorder.record_command(&run_macro_command("other", None), None);
+        recorder.record_command(
+            &LapceCommand {
+                kind: CommandKind::Workbench(LapceWorkbenchCommand::PaletteCommand),
+                data: None,
+            },
+            None,
+        );
+        recorder.record_text("c");
+        recorder.stop();
+        assert_eq!(
+            recorder.last(),
+            [
+                MacroStep::Text {
+                    text: "ab".to_string()
+                },
+                command("zoom_in", Some(2)),
+                MacroStep::Text {
+                    text: "c".to_string()
+                },
+            ]
+        );
+
+        // Recording nothing keeps the last macro
+        recorder.start();
+        recorder.stop();
+        assert_eq!(recorder.last().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_run_macro_command() {
+        assert_eq!(
+            parse_run_macro_command("run_macro.quote-line"),
+            Some(run_macro_command("quote-line", None))
+        );
+        assert_eq!(
+            parse_run_macro_command("run_macro.quote-line.3"),
+            Some(run_macro_command("quote-line", Some(3)))
+        );
+        assert_eq!(parse_run_macro_command("run_macro.quote-line.x"), None);
+        assert_eq!(parse_run_macro_command("run_macro."), None);
+        assert_eq!(parse_run_macro_command("zoom_in"), None);
+
+        assert!(is_valid_name("quote_line-2"));
+        assert!(!is_valid_name("Quote"));
+        assert!(!is_valid_name("a.b"));
+    }
+
+    #[test]
+    fn test_steps_to_toml() {
+        #[derive(Deserialize)]
+        struct Settings {
+            steps: Vec<MacroStep>,
+        }
+
+        let steps = vec![
+            MacroStep::Command {
+                command: "source_control.checkout_reference".to_string(),
+                data: Some("\"main\"".to_string()),
+                count: None,
+            },
+            command("down", Some(2)),
+            MacroStep::Text {
+                text: "say \"hi\"\n".to_string(),
+            },
+        ];
+        let settings = format!("steps = {}", steps_to_toml(&steps));
+        let settings: Settings = toml_edit::de::from_str(&settings).unwrap();
+        assert_eq!(settings.steps, steps);
+    }
+}
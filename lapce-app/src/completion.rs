@@ -33,6 +33,14 @@ pub struct ScoredCompletionItem {
     pub indices: Vec<usize>,
 }
 
+/// A plugin or language server which provides completions
+#[derive(Clone)]
+pub struct CompletionProvider {
+    pub name: String,
+    /// The characters which start a completion when typed
+    pub trigger_characters: Vec<String>,
+}
+
 #[derive(Clone)]
 pub struct CompletionData {
     pub status: CompletionStatus,
@@ -61,6 +69,11 @@ pub struct CompletionData {
     pub layout_rect: Rect,
     /// The editor id that was most recently used to trigger a completion.
     pub latest_editor_id: Option<EditorId>,
+    /// The providers of the completion items
+    pub providers: im::HashMap<PluginId, CompletionProvider>,
+    /// Whether the items come from more than one provider, in which case the
+    /// source of each is shown
+    pub mixed_sources: bool,
     /// Matcher for filtering the completion items
     matcher: RwSignal<nucleo::Matcher>,
     config: ReadSignal<Arc<LapceConfig>>,
@@ -83,11 +96,31 @@ pub fn new(cx: Scope, config: ReadSignal<Arc<LapceConfig>>) -> Self {
             matcher: cx
                 .create_rw_signal(nucleo::Matcher::new(nucleo::Config::DEFAULT)),
             latest_editor_id: None,
+            providers: im::HashMap::new(),
+            mixed_sources: false,
             config,
         }
     }
 
-    /// Handle the response to a completion request.
+    /// Whether typing `char` should start a completion, even though it's not
+    /// part of an identifier
+    pub fn is_trigger_character(&self, char: &str) -> bool {
+        char == "."
+            || char == ":"
+            || self.providers.values().any(|provider| {
+                provider.trigger_characters.iter().any(|c| c == char)
+            })
+    }
+
+    /// The name of the provider of an item
+    pub fn source(&self, plugin_id: PluginId) -> Option<&str> {
+        self.providers
+            .get(&plugin_id)
+            .map(|provider| provider.name.as_str())
+    }
+
+    /// Handle the response to a completion request, merging its items with
+    /// those of the other providers.
     pub fn receive(
         &mut self,
         request_id: usize,
@@ -106,17 +139,18 @@ pub fn receive(
             // TODO: Possibly handle the 'is_incomplete' field on List.
             CompletionResponse::List(list) => &list.items,
         };
-        let items: im::Vector<ScoredCompletionItem> = items
-            .iter()
-            .map(|i| ScoredCompletionItem {
-                item: i.to_owned(),
-                plugin_id,
-                score: 0,
-                label_score: 0,
-                indices: Vec::new(),
-            })
-            .collect();
-        self.input_items.insert(input.to_string(), items);
+        let mut input_items =
+            self.input_items.get(input).cloned().unwrap_or_default();
+        input_items.retain(|i| i.plugin_id != plugin_id);
+        input_items.extend(items.iter().map(|i| ScoredCompletionItem {
+            item: i.to_owned(),
+            plugin_id,
+            score: 0,
+            label_score: 0,
+            indices: Vec::new(),
+        }));
+        input_items.sort_by(|a, b| sort_text(&a.item).cmp(sort_text(&b.item)));
+        self.input_items.insert(input.to_string(), input_items);
         self.filter_items();
     }
 
@@ -173,8 +207,11 @@ fn all_items(&self) -> im::Vector<ScoredCompletionItem> {
 
     pub fn filter_items(&mut self) {
         self.input_id += 1;
+        let all_items = self.all_items();
+        let first = all_items.front().map(|i| i.plugin_id);
+        self.mixed_sources = all_items.iter().any(|i| Some(i.plugin_id) != first);
         if self.input.is_empty() {
-            self.filtered_items = self.all_items();
+            self.filtered_items = all_items;
             return;
         }
 
@@ -187,7 +224,7 @@ pub fn filter_items(&mut self) {
                     nucleo::pattern::CaseMatching::Ignore,
                     nucleo::pattern::Normalization::Smart,
                 );
-                self.all_items()
+                all_items
                     .iter()
                     .filter_map(|i| {
                         let filter_text =
@@ -231,11 +268,13 @@ pub fn filter_items(&mut self) {
                     .collect()
             })
             .unwrap();
-        // Sort all the items by their score, then their label score, then their length.
+        // Sort all the items by their score, then their label score, then their sort
+        // text, then their length.
         items.sort_by(|a, b| {
             b.score
                 .cmp(&a.score)
                 .then_with(|| b.label_score.cmp(&a.label_score))
+                .then_with(|| sort_text(&a.item).cmp(sort_text(&b.item)))
                 .then_with(|| a.item.label.len().cmp(&b.item.label.len()))
         });
         self.filtered_items = items;
@@ -339,6 +378,11 @@ pub fn update_document_completion(
     }
 }
 
+/// The text an item is sorted by among the others with the same score
+fn sort_text(item: &CompletionItem) -> &str {
+    item.sort_text.as_deref().unwrap_or(&item.label)
+}
+
 /// Get the text of the completion lens for the given completion item.  
 /// Returns `None` if the completion lens should be hidden.
 /// Returns `Some(None)` if the completion lens should be shown, but not changed.
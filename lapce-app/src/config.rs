@@ -7,7 +7,7 @@
 use ::core::slice;
 use floem::peniko::Color;
 use itertools::Itertools;
-use lapce_core::directory::Directory;
+use lapce_core::{directory::Directory, language::LapceLanguage};
 use lapce_proxy::plugin::wasi::find_all_volts;
 use lapce_rpc::plugin::VoltID;
 use lsp_types::{CompletionItemKind, SymbolKind};
@@ -18,17 +18,28 @@
 use tracing::error;
 
 use self::{
-    color::LapceColor,
+    color::{ensure_contrast, LapceColor},
     color_theme::{ColorThemeConfig, ThemeColor, ThemeColorPreference},
     core::CoreConfig,
-    editor::{EditorConfig, WrapStyle, SCALE_OR_SIZE_LIMIT},
+    editor::{
+        EditorConfig, InlayHintDisplay, TextDirection, WrapStyle,
+        SCALE_OR_SIZE_LIMIT,
+    },
     icon::LapceIcons,
     icon_theme::IconThemeConfig,
+    language::{
+        resolve_language_names, FileAssociations, LanguageConfig,
+        LanguageEditorConfig,
+    },
     svg::SvgStore,
     terminal::TerminalConfig,
     ui::UIConfig,
 };
-use crate::workspace::{LapceWorkspace, LapceWorkspaceType};
+use crate::{
+    command_macro::MacroStep,
+    profile,
+    workspace::{LapceWorkspace, LapceWorkspaceType},
+};
 
 pub mod color;
 pub mod color_theme;
@@ -36,17 +47,26 @@
 pub mod editor;
 pub mod icon;
 pub mod icon_theme;
+pub mod language;
 pub mod svg;
 pub mod terminal;
 pub mod ui;
+pub mod validate;
 pub mod watcher;
 
 pub const LOGO: &str = include_str!("../../extra/images/logo.svg");
 const DEFAULT_SETTINGS: &str = include_str!("../../defaults/settings.toml");
 const DEFAULT_LIGHT_THEME: &str = include_str!("../../defaults/light-theme.toml");
 const DEFAULT_DARK_THEME: &str = include_str!("../../defaults/dark-theme.toml");
+const DEFAULT_HIGH_CONTRAST_LIGHT_THEME: &str =
+    include_str!("../../defaults/high-contrast-light-theme.toml");
+const DEFAULT_HIGH_CONTRAST_DARK_THEME: &str =
+    include_str!("../../defaults/high-contrast-dark-theme.toml");
 const DEFAULT_ICON_THEME: &str = include_str!("../../defaults/icon-theme.toml");
 
+/// The contrast ratio high contrast themes are held to (WCAG AAA)
+const HIGH_CONTRAST_MIN_RATIO: f64 = 7.0;
+
 static DEFAULT_CONFIG: Lazy<config::Config> = Lazy::new(LapceConfig::default_config);
 static DEFAULT_LAPCE_CONFIG: Lazy<LapceConfig> =
     Lazy::new(LapceConfig::default_lapce_config);
@@ -96,6 +116,10 @@ pub struct DropdownInfo {
 pub struct LapceConfig {
     #[serde(skip)]
     pub id: u64,
+    /// Changes only when a value that the text layouts of documents depend on
+    /// changes, see [`LapceConfig::carry_layout_rev`]
+    #[serde(skip)]
+    pub layout_rev: u64,
     pub core: CoreConfig,
     pub ui: UIConfig,
     pub editor: EditorConfig,
@@ -104,6 +128,16 @@ pub struct LapceConfig {
     pub color_theme: ColorThemeConfig,
     #[serde(default)]
     pub icon_theme: IconThemeConfig,
+    /// Editor settings for single languages, by language name
+    #[serde(default)]
+    pub language: HashMap<String, LanguageConfig>,
+    /// Languages for files by glob, extension or file name, such as
+    /// `"*.vue" = "html"`
+    #[serde(default)]
+    pub file_associations: HashMap<String, String>,
+    /// The macros saved, by name
+    #[serde(default)]
+    pub macros: HashMap<String, Vec<MacroStep>>,
     #[serde(flatten)]
     pub plugins: HashMap<String, HashMap<String, serde_json::Value>>,
     #[serde(skip)]
@@ -126,15 +160,52 @@ pub struct LapceConfig {
     /// The couple names for the wrap style
     #[serde(skip)]
     wrap_style_list: im::Vector<String>,
+    /// The profile whose settings are layered over the user's, if any
+    #[serde(skip)]
+    pub profile: Option<String>,
+    #[serde(skip)]
+    languages: HashMap<LapceLanguage, LanguageConfig>,
+    #[serde(skip)]
+    associations: FileAssociations,
+}
+
+/// The config values that the text layouts of documents depend on
+#[derive(PartialEq)]
+struct LayoutKeys<'a> {
+    font_family: &'a str,
+    font_size: usize,
+    line_height: usize,
+    text_direction: TextDirection,
+    tab_width: usize,
+    atomic_soft_tabs: bool,
+    render_control_characters: bool,
+    enable_inlay_hints: bool,
+    inlay_hint_display: InlayHintDisplay,
+    inlay_hint_font_family: &'a str,
+    inlay_hint_font_size: usize,
+    enable_error_lens: bool,
+    error_lens_end_of_line: bool,
+    error_lens_multiline: bool,
+    error_lens_font_family: &'a str,
+    error_lens_font_size: usize,
+    enable_completion_lens: bool,
+    completion_lens_font_family: &'a str,
+    completion_lens_font_size: usize,
+    bracket_pair_colorization: bool,
+    bracket_colorization_limit: u64,
+    long_line_length: usize,
+    languages: &'a HashMap<LapceLanguage, LanguageConfig>,
+    color: &'a ThemeColor,
 }
 
 impl LapceConfig {
     pub fn load(
         workspace: &LapceWorkspace,
+        profile: Option<&str>,
         disabled_volts: &[VoltID],
         extra_plugin_paths: &[PathBuf],
     ) -> Self {
-        let config = Self::merge_config(workspace, None, None);
+        let config = Self::merge_config(workspace, profile, None, None);
         let mut lapce_config: LapceConfig = match config.try_deserialize() {
             Ok(config) => config,
             Err(error) => {
@@ -142,6 +213,7 @@ pub fn load(
                 DEFAULT_LAPCE_CONFIG.clone()
             }
         };
+        lapce_config.profile = profile.map(|profile| profile.to_string());
 
         lapce_config.available_color_themes =
             Self::load_color_themes(disabled_volts, extra_plugin_paths);
@@ -168,7 +240,7 @@ pub fn load(
         lapce_config.wrap_style_list = im::vector![
             WrapStyle::None.to_string(),
             WrapStyle::EditorWidth.to_string(),
-            // TODO: WrapStyle::WrapColumn.to_string(),
+            WrapStyle::WrapColumn.to_string(),
             WrapStyle::WrapWidth.to_string()
         ];
 
@@ -179,6 +251,7 @@ pub fn load(
 
     fn merge_config(
         workspace: &LapceWorkspace,
+        profile: Option<&str>,
         color_theme_config: Option<config::Config>,
         icon_theme_config: Option<config::Config>,
     ) -> config::Config {
@@ -211,6 +284,14 @@ fn merge_config(
                 .unwrap_or_else(|_| config.clone());
         }
 
+        if let Some(path) = profile.and_then(profile::settings_file) {
+            config = config::Config::builder()
+                .add_source(config.clone())
+                .add_source(config::File::from(path.as_path()).required(false))
+                .build()
+                .unwrap_or_else(|_| config.clone());
+        }
+
         match workspace.kind {
             LapceWorkspaceType::Local => {
                 if let Some(path) = workspace.path.as_ref() {
@@ -224,6 +305,15 @@ fn merge_config(
                         .unwrap_or_else(|_| config.clone());
                 }
             }
+            // The settings of the folder mounted in the container
+            LapceWorkspaceType::RemoteContainer(remote) => {
+                let path = remote.folder.join("./.lapce/settings.toml");
+                config = config::Config::builder()
+                    .add_source(config.clone())
+                    .add_source(config::File::from(path.as_path()).required(false))
+                    .build()
+                    .unwrap_or_else(|_| config.clone());
+            }
             LapceWorkspaceType::RemoteSSH(_) => {}
             #[cfg(windows)]
             LapceWorkspaceType::RemoteWSL(_) => {}
@@ -232,6 +322,47 @@ fn merge_config(
         config
     }
 
+    /// Take over the layout revision of the config that this one replaces,
+    /// bumping it if a value that text layouts depend on changed, so that
+    /// documents are laid out again only when needed.
+    pub fn carry_layout_rev(&mut self, old: &LapceConfig) {
+        self.layout_rev = if self.layout_keys() == old.layout_keys() {
+            old.layout_rev
+        } else {
+            old.layout_rev + 1
+        };
+    }
+
+    fn layout_keys(&self) -> LayoutKeys<'_> {
+        let editor = &self.editor;
+        LayoutKeys {
+            font_family: &editor.font_family,
+            font_size: editor.font_size(),
+            line_height: editor.line_height(),
+            text_direction: editor.text_direction,
+            tab_width: editor.tab_width,
+            atomic_soft_tabs: editor.atomic_soft_tabs,
+            render_control_characters: editor.render_control_characters,
+            enable_inlay_hints: editor.enable_inlay_hints,
+            inlay_hint_display: editor.inlay_hint_display,
+            inlay_hint_font_family: &editor.inlay_hint_font_family,
+            inlay_hint_font_size: editor.inlay_hint_font_size(),
+            enable_error_lens: editor.enable_error_lens,
+            error_lens_end_of_line: editor.error_lens_end_of_line,
+            error_lens_multiline: editor.error_lens_multiline,
+            error_lens_font_family: &editor.error_lens_font_family,
+            error_lens_font_size: editor.error_lens_font_size(),
+            enable_completion_lens: editor.enable_completion_lens,
+            completion_lens_font_family: &editor.completion_lens_font_family,
+            completion_lens_font_size: editor.completion_lens_font_size(),
+            bracket_pair_colorization: editor.bracket_pair_colorization,
+            bracket_colorization_limit: editor.bracket_colorization_limit,
+            long_line_length: editor.long_line_length,
+            languages: &self.languages,
+            color: &self.color,
+        }
+    }
+
     fn update_id(&mut self) {
         self.id = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -239,6 +370,14 @@ fn update_id(&mut self) {
             .unwrap_or(0);
     }
 
+    /// The built-in default config, without any user or workspace settings.
+    #[cfg(feature = "headless")]
+    pub fn headless() -> Self {
+        let mut config = DEFAULT_LAPCE_CONFIG.clone();
+        config.update_id();
+        config
+    }
+
     fn default_config() -> config::Config {
         config::Config::builder()
             .add_source(config::File::from_str(
@@ -280,6 +419,7 @@ fn resolve_theme(&mut self, workspace: &LapceWorkspace) {
 
         if let Ok(new) = Self::merge_config(
             workspace,
+            self.profile.as_deref(),
             Some(color_theme_config.clone()),
             Some(icon_theme_config.clone()),
         )
@@ -296,8 +436,13 @@ fn resolve_theme(&mut self, workspace: &LapceWorkspace) {
             if let Some(icon_theme_path) = icon_theme_path {
                 self.icon_theme.path = icon_theme_path.clone().unwrap_or_default();
             }
+            self.language = new.language;
+            self.file_associations = new.file_associations;
+            self.macros = new.macros;
             self.plugins = new.plugins;
         }
+        self.languages = resolve_language_names(&self.language);
+        self.associations = FileAssociations::new(&self.file_associations);
         self.resolve_colors(Some(&default_lapce_config));
         self.update_id();
     }
@@ -320,6 +465,14 @@ fn load_color_themes(
         let (name, theme) =
             Self::load_color_theme_from_str(DEFAULT_DARK_THEME).unwrap();
         themes.insert(name.to_lowercase(), (name, theme));
+        let (name, theme) =
+            Self::load_color_theme_from_str(DEFAULT_HIGH_CONTRAST_LIGHT_THEME)
+                .unwrap();
+        themes.insert(name.to_lowercase(), (name, theme));
+        let (name, theme) =
+            Self::load_color_theme_from_str(DEFAULT_HIGH_CONTRAST_DARK_THEME)
+                .unwrap();
+        themes.insert(name.to_lowercase(), (name, theme));
 
         themes
     }
@@ -361,6 +514,23 @@ pub fn color(&self, name: &str) -> Color {
         }
     }
 
+    /// The colors of the indent guides of the successive indentation levels,
+    /// which repeat for deeper levels. The theme can define as many as it
+    /// wants, numbered from 1.
+    pub fn rainbow_indent_guide_colors(&self) -> Vec<Color> {
+        (1..)
+            .map_while(|level| {
+                self.color
+                    .ui
+                    .get(&format!(
+                        "{}.{level}",
+                        LapceColor::EDITOR_INDENT_GUIDE_RAINBOW
+                    ))
+                    .copied()
+            })
+            .collect()
+    }
+
     /// Retrieve a color value whose key starts with "style."
     pub fn style_color(&self, name: &str) -> Option<Color> {
         self.color.syntax.get(name).copied()
@@ -416,6 +586,25 @@ fn resolve_colors(&mut self, default_config: Option<&LapceConfig>) {
             (true, false) => ThemeColorPreference::Light,
             (false, false) => ThemeColorPreference::Dark,
         };
+
+        self.enforce_minimum_contrast(high_contrast);
+    }
+
+    /// Adjust the syntax colors so that they meet the configured minimum
+    /// contrast ratio against the editor background.
+    fn enforce_minimum_contrast(&mut self, high_contrast: bool) {
+        let mut min_ratio = self.editor.minimum_contrast_ratio;
+        if high_contrast {
+            min_ratio = min_ratio.max(HIGH_CONTRAST_MIN_RATIO);
+        }
+        if min_ratio <= 1.0 {
+            return;
+        }
+
+        let bg = self.color(LapceColor::EDITOR_BACKGROUND);
+        for color in self.color.syntax.values_mut() {
+            *color = ensure_contrast(*color, bg, min_ratio);
+        }
     }
 
     fn load_local_themes() -> Option<HashMap<String, (String, config::Config)>> {
@@ -588,6 +777,20 @@ pub fn keymaps_file() -> Option<PathBuf> {
         Some(path)
     }
 
+    /// The editor settings for the language, with its `[language.<name>]`
+    /// table applied over `[editor]`.
+    pub fn editor_for(&self, language: LapceLanguage) -> LanguageEditorConfig {
+        LanguageEditorConfig::resolve(&self.editor, self.languages.get(&language))
+    }
+
+    /// The language of the file at `path`, from `[file-associations]` or else
+    /// from its name.
+    pub fn language_of(&self, path: &Path) -> LapceLanguage {
+        self.associations
+            .language_of(path)
+            .unwrap_or_else(|| LapceLanguage::from_path(path))
+    }
+
     pub fn ui_svg(&self, icon: &'static str) -> String {
         let svg = self.icon_theme.ui.get(icon).and_then(|path| {
             let path = self.icon_theme.path.join(path);
@@ -874,6 +1077,48 @@ pub fn get_dropdown_info(&self, kind: &str, key: &str) -> Option<DropdownInfo> {
                     .unwrap_or(0),
                 items: self.wrap_style_list.clone(),
             }),
+            ("editor", "matching-bracket-style") => Some(DropdownInfo {
+                active_index: self.editor.matching_bracket_style as usize,
+                items: editor::MatchingBracketStyle::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
+            ("editor", "inlay-hint-display") => Some(DropdownInfo {
+                active_index: self.editor.inlay_hint_display as usize,
+                items: editor::InlayHintDisplay::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
+            ("editor", "line-spacing") => Some(DropdownInfo {
+                active_index: self.editor.line_spacing as usize,
+                items: editor::LineSpacing::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
+            ("editor", "text-direction") => Some(DropdownInfo {
+                active_index: self.editor.text_direction as usize,
+                items: editor::TextDirection::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
+            ("editor", "cursor-movement") => Some(DropdownInfo {
+                active_index: self.editor.cursor_movement as usize,
+                items: editor::CursorMovement::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
+            ("editor", "smooth-scroll-easing") => Some(DropdownInfo {
+                active_index: self.editor.smooth_scroll_easing as usize,
+                items: editor::ScrollEasing::VARIANTS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }),
             ("ui", "tab-close-button") => Some(DropdownInfo {
                 active_index: self.ui.tab_close_button as usize,
                 items: ui::TabCloseButton::VARIANTS
@@ -982,3 +1227,22 @@ pub fn update_file(
         Some(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DEFAULT_LAPCE_CONFIG;
+
+    #[test]
+    fn test_carry_layout_rev() {
+        let old = DEFAULT_LAPCE_CONFIG.clone();
+
+        let mut new = old.clone();
+        new.terminal.font_size = 20;
+        new.carry_layout_rev(&old);
+        assert_eq!(new.layout_rev, old.layout_rev);
+
+        new.editor.tab_width = old.editor.tab_width + 1;
+        new.carry_layout_rev(&old);
+        assert_eq!(new.layout_rev, old.layout_rev + 1);
+    }
+}
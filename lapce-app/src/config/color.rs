@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use floem::peniko::Color;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -70,9 +71,30 @@ impl LapceColor {
     pub const EDITOR_SELECTION: &'static str = "editor.selection";
     pub const EDITOR_DEBUG_BREAK_LINE: &'static str = "editor.debug_break_line";
     pub const EDITOR_CURRENT_LINE: &'static str = "editor.current_line";
+    pub const EDITOR_MATCHING_BRACKET_BACKGROUND: &'static str =
+        "editor.matching_bracket_background";
+    pub const EDITOR_LINKED_EDITING_RANGE: &'static str =
+        "editor.linked_editing_range";
+    pub const EDITOR_OCCURRENCE_READ: &'static str = "editor.occurrence.read";
+    pub const EDITOR_OCCURRENCE_WRITE: &'static str = "editor.occurrence.write";
+    pub const EDITOR_FIND_MATCH_ACTIVE: &'static str = "editor.find_match_active";
+    pub const EDITOR_FIND_MATCH_MARK: &'static str = "editor.find_match_mark";
+    pub const EDITOR_BLAME_HEAT: &'static str = "editor.blame_heat";
+    pub const EDITOR_COVERAGE_COVERED: &'static str = "editor.coverage.covered";
+    pub const EDITOR_COVERAGE_PARTIAL: &'static str = "editor.coverage.partial";
+    pub const EDITOR_COVERAGE_UNCOVERED: &'static str = "editor.coverage.uncovered";
+    pub const EDITOR_LOG_ERROR: &'static str = "editor.log.error";
+    pub const EDITOR_LOG_WARN: &'static str = "editor.log.warn";
+    pub const EDITOR_LOG_INFO: &'static str = "editor.log.info";
+    pub const EDITOR_LOG_DEBUG: &'static str = "editor.log.debug";
     pub const EDITOR_LINK: &'static str = "editor.link";
     pub const EDITOR_VISIBLE_WHITESPACE: &'static str = "editor.visible_whitespace";
     pub const EDITOR_INDENT_GUIDE: &'static str = "editor.indent_guide";
+    /// The prefix of the colors of the indent guides by indentation level,
+    /// numbered from 1
+    pub const EDITOR_INDENT_GUIDE_RAINBOW: &'static str =
+        "editor.indent_guide.rainbow";
+    pub const EDITOR_BRACKET_PAIR_GUIDE: &'static str = "editor.bracket_pair_guide";
     pub const EDITOR_DRAG_DROP_BACKGROUND: &'static str =
         "editor.drag_drop_background";
     pub const EDITOR_STICKY_HEADER_BACKGROUND: &'static str =
@@ -180,3 +202,93 @@ impl LapceColor {
 
     pub const MARKDOWN_BLOCKQUOTE: &'static str = "markdown.blockquote";
 }
+
+/// The relative luminance of a color, as defined by WCAG 2.
+pub fn relative_luminance(color: Color) -> f64 {
+    fn channel(c: u8) -> f64 {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+    0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+}
+
+/// The WCAG 2 contrast ratio between two colors, from 1 to 21.
+pub fn contrast_ratio(a: Color, b: Color) -> f64 {
+    let a = relative_luminance(a);
+    let b = relative_luminance(b);
+    let (light, dark) = if a > b { (a, b) } else { (b, a) };
+    (light + 0.05) / (dark + 0.05)
+}
+
+/// Adjust `fg` so that it has at least `min_ratio` contrast with `bg`.
+///
+/// The color is moved towards black or white, whichever contrasts more with
+/// the background, by as little as is needed. The alpha is kept as is.
+pub fn ensure_contrast(fg: Color, bg: Color, min_ratio: f64) -> Color {
+    if min_ratio <= 1.0 || contrast_ratio(fg, bg) >= min_ratio {
+        return fg;
+    }
+
+    let target =
+        if contrast_ratio(Color::WHITE, bg) >= contrast_ratio(Color::BLACK, bg) {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        };
+    let mix = |t: f64| {
+        let channel = |from: u8, to: u8| {
+            (from as f64 + (to as f64 - from as f64) * t).round() as u8
+        };
+        Color::rgba8(
+            channel(fg.r, target.r),
+            channel(fg.g, target.g),
+            channel(fg.b, target.b),
+            fg.a,
+        )
+    };
+
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..16 {
+        let mid = (low + high) / 2.0;
+        if contrast_ratio(mix(mid), bg) >= min_ratio {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    mix(high)
+}
+
+#[cfg(test)]
+mod tests {
+    use floem::peniko::Color;
+
+    use super::{contrast_ratio, ensure_contrast};
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(Color::WHITE, Color::WHITE) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_ensure_contrast() {
+        let bg = Color::rgb8(0x28, 0x2C, 0x34);
+        let comment = Color::rgb8(0x5C, 0x63, 0x70);
+        assert!(contrast_ratio(comment, bg) < 4.5);
+
+        let adjusted = ensure_contrast(comment, bg, 4.5);
+        assert!(contrast_ratio(adjusted, bg) >= 4.5);
+        // Lightened, as the background is dark
+        assert!(adjusted.r > comment.r);
+
+        // Colors that already have enough contrast are left alone
+        let text = Color::rgb8(0xAB, 0xB2, 0xBF);
+        assert_eq!(ensure_contrast(text, bg, 4.5), text);
+        assert_eq!(ensure_contrast(comment, bg, 0.0), comment);
+    }
+}
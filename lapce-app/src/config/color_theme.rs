@@ -8,7 +8,7 @@
 
 use super::color::LoadThemeError;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum ThemeColorPreference {
     #[default]
     Light,
@@ -18,7 +18,7 @@ pub enum ThemeColorPreference {
 }
 
 /// Holds all the resolved theme variables
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ThemeBaseColor(HashMap<String, Color>);
 impl ThemeBaseColor {
     pub fn get(&self, name: &str) -> Option<Color> {
@@ -28,7 +28,7 @@ pub fn get(&self, name: &str) -> Option<Color> {
 
 pub const THEME_RECURSION_LIMIT: usize = 6;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ThemeColor {
     pub color_preference: ThemeColorPreference,
     pub base: ThemeBaseColor,
@@ -191,7 +191,7 @@ fn test_resolve() {
         // Mimicking load
         let workspace = LapceWorkspace::default();
 
-        let config = LapceConfig::merge_config(&workspace, None, None);
+        let config = LapceConfig::merge_config(&workspace, None, None, None);
         let mut lapce_config: LapceConfig = config.try_deserialize().unwrap();
 
         let test_theme_str = r##"
@@ -210,6 +210,7 @@ fn test_resolve() {
 [color-theme.ui]
 "lapce.error" = "#ffffff"
 "editor.background" = "$blah"
+"editor.indent_guide.rainbow.5" = "$blah"
 "##;
         println!("Test theme: {test_theme_str}");
         let test_theme_cfg = Config::builder()
@@ -254,6 +255,12 @@ fn test_resolve() {
             Color::BLACK,
             "Failed to get from custom variable circle back around"
         );
+        // test that the theme extends the rainbow indent guides of the
+        // fallback theme
+        let rainbow = lapce_config.rainbow_indent_guide_colors();
+        assert_eq!(rainbow.len(), 5);
+        assert_eq!(rainbow[0], Color::rgb8(0xE5, 0xC0, 0x7B)); // $yellow
+        assert_eq!(rainbow[4], Color::rgb8(0xFF, 0x00, 0xFF));
 
         // don't bother filling color/icon theme list
         // don't bother with wrap style list
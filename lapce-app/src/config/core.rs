@@ -14,4 +14,21 @@ pub struct CoreConfig {
         desc = "Enable customised titlebar and disable OS native one (Linux, BSD, Windows)"
     )]
     pub custom_titlebar: bool,
+    /// Not a setting until floem can expose the live region to the platform
+    /// accessibility APIs, so it stays off
+    #[serde(skip)]
+    #[field_names(skip)]
+    pub screen_reader_support: bool,
+    #[field_names(
+        desc = "Accept automation requests from other programs on a local socket. Requires a restart"
+    )]
+    pub automation_socket: bool,
+    #[field_names(
+        desc = "A git repository to sync the settings, keymaps, themes and plugin list through"
+    )]
+    pub settings_sync_repository: String,
+    #[field_names(
+        desc = "Write a report of each error shown to a file in the logs directory"
+    )]
+    pub incident_reports: bool,
 }
@@ -1,4 +1,6 @@
-use floem::views::editor::text::RenderWhitespace;
+use std::time::Duration;
+
+use floem::{kurbo::Vec2, views::editor::text::RenderWhitespace};
 use serde::{Deserialize, Serialize};
 use structdesc::FieldNames;
 
@@ -15,6 +17,172 @@ pub enum ClickMode {
     DoubleClickAll,
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum MatchingBracketStyle {
+    /// Draw a box around both brackets
+    #[default]
+    Box,
+    /// Fill the background of both brackets
+    Background,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum CursorMovement {
+    /// Left and right move through the text in the order it is stored
+    #[default]
+    Logical,
+    /// Left and right move through the text in the order it is shown, which
+    /// differs from the stored order in right to left text
+    Visual,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum InlayHintDisplay {
+    /// Don't display the inlay hints
+    Off,
+    /// Display the inlay hints where they are in the text
+    #[default]
+    On,
+    /// Display the inlay hints of the line under the pointer only
+    OnHover,
+    /// Display all the inlay hints of a line together at its end, for the
+    /// text not to shift as they change
+    EndOfLine,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum ScrollEasing {
+    /// Scroll at the same speed all along
+    Linear,
+    /// Start fast and slow down towards the end
+    #[default]
+    EaseOut,
+    /// Speed up from the start and slow down towards the end
+    EaseInOut,
+}
+
+impl ScrollEasing {
+    /// How far along the scroll is, from 0 to 1, at `t` of its duration
+    pub fn ease(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            ScrollEasing::Linear => t,
+            ScrollEasing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            ScrollEasing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t.powi(3)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum LineSpacing {
+    /// Lines closer together, to fit more of them
+    Compact,
+    /// The line height as it is set
+    #[default]
+    Normal,
+    /// Lines further apart, for easier reading
+    Comfortable,
+}
+
+impl LineSpacing {
+    /// The space added between lines, in proportion to the font size
+    pub fn factor(self) -> f64 {
+        match self {
+            LineSpacing::Compact => -0.2,
+            LineSpacing::Normal => 0.0,
+            LineSpacing::Comfortable => 0.3,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum TextDirection {
+    /// Each line takes the direction of its first strong character
+    #[default]
+    Auto,
+    /// Every line reads from left to right
+    LeftToRight,
+    /// Every line reads from right to left
+    RightToLeft,
+}
+
+impl TextDirection {
+    /// The zero-width mark put at the start of each line to set its direction
+    pub fn mark(self) -> Option<char> {
+        match self {
+            TextDirection::Auto => None,
+            TextDirection::LeftToRight => Some('\u{200E}'),
+            TextDirection::RightToLeft => Some('\u{200F}'),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum WrapStyle {
@@ -23,8 +191,8 @@ pub enum WrapStyle {
     /// Wrap at the editor width
     #[default]
     EditorWidth,
-    // /// Wrap at the wrap-column
-    // WrapColumn,
+    /// Wrap at the wrap-column
+    WrapColumn,
     /// Wrap at a specific width
     WrapWidth,
 }
@@ -33,7 +201,7 @@ pub fn as_str(&self) -> &'static str {
         match self {
             WrapStyle::None => "none",
             WrapStyle::EditorWidth => "editor-width",
-            // WrapStyle::WrapColumn => "wrap-column",
+            WrapStyle::WrapColumn => "wrap-column",
             WrapStyle::WrapWidth => "wrap-width",
         }
     }
@@ -42,7 +210,7 @@ pub fn try_from_str(s: &str) -> Option<Self> {
         match s {
             "none" => Some(WrapStyle::None),
             "editor-width" => Some(WrapStyle::EditorWidth),
-            // "wrap-column" => Some(WrapStyle::WrapColumn),
+            "wrap-column" => Some(WrapStyle::WrapColumn),
             "wrap-width" => Some(WrapStyle::WrapWidth),
             _ => None,
         }
@@ -70,6 +238,18 @@ pub struct EditorConfig {
         desc = "Set the editor line height. If less than 5.0, line height will be a multiple of the font size."
     )]
     line_height: f64,
+    #[field_names(
+        desc = "A preset for the space between lines, on top of the line height: Compact, Normal or Comfortable"
+    )]
+    pub line_spacing: LineSpacing,
+    #[field_names(
+        desc = "The padding in pixels added above and below each line, on top of the line height"
+    )]
+    pub line_padding: usize,
+    #[field_names(
+        desc = "The direction lines are laid out in: Auto to follow the text of each line, LeftToRight or RightToLeft"
+    )]
+    pub text_direction: TextDirection,
     #[field_names(
         desc = "If enabled, when you input a tab character, it will insert indent that's detected based on your files."
     )]
@@ -88,14 +268,18 @@ pub struct EditorConfig {
     pub cursor_surrounding_lines: usize,
     #[field_names(desc = "The kind of wrapping to perform")]
     pub wrap_style: WrapStyle,
-    // #[field_names(desc = "The number of columns to wrap at")]
-    // pub wrap_column: usize,
+    #[field_names(desc = "The number of columns to wrap at")]
+    pub wrap_column: usize,
     #[field_names(desc = "The number of pixels to wrap at")]
     pub wrap_width: usize,
     #[field_names(
         desc = "Show code context like functions and classes at the top of editor when scroll"
     )]
     pub sticky_header: bool,
+    #[field_names(
+        desc = "The maximum number of lines the sticky header shows, the innermost ones are kept"
+    )]
+    pub sticky_header_max_lines: usize,
     #[field_names(
         desc = "If the editor should show the documentation of the current completion item"
     )]
@@ -116,6 +300,30 @@ pub struct EditorConfig {
         desc = "Whether the editor should automatically surround selected text when typing quotes or brackets"
     )]
     pub auto_surround: bool,
+    #[field_names(
+        desc = "Whether Tab should expand Emmet abbreviations, such as `ul>li*3`, in HTML, CSS and JSX"
+    )]
+    pub enable_emmet: bool,
+    #[field_names(
+        desc = "Whether renaming an HTML or JSX tag should rename the tag it pairs with"
+    )]
+    pub auto_rename_tags: bool,
+    #[field_names(
+        desc = "Whether to edit the ranges the language server links, such as the names of paired tags, together"
+    )]
+    pub linked_editing: bool,
+    #[field_names(
+        desc = "Whether to highlight the occurrences of the symbol under the cursor"
+    )]
+    pub highlight_occurrences: bool,
+    #[field_names(
+        desc = "Whether to highlight the other occurrences on the screen of the text selected on one line"
+    )]
+    pub highlight_selection_matches: bool,
+    #[field_names(
+        desc = "Whether middle-clicking pastes the text selected last, as is usual on Linux. Only supported in X11 sessions."
+    )]
+    pub middle_click_paste: bool,
     #[field_names(
         desc = "How long (in ms) it should take before the hover information appears"
     )]
@@ -137,11 +345,20 @@ pub struct EditorConfig {
     #[field_names(desc = "If matching brackets are highlighted")]
     pub highlight_matching_brackets: bool,
 
+    #[field_names(
+        desc = "How the bracket next to the cursor and its match are highlighted"
+    )]
+    pub matching_bracket_style: MatchingBracketStyle,
+
     #[field_names(desc = "If scope lines are highlighted")]
     pub highlight_scope_lines: bool,
 
     #[field_names(desc = "If inlay hints should be displayed")]
     pub enable_inlay_hints: bool,
+    #[field_names(
+        desc = "How inlay hints are displayed: in the text, on the line under the pointer only, or together at the end of their line"
+    )]
+    pub inlay_hint_display: InlayHintDisplay,
 
     #[field_names(
         desc = "Set the inlay hint font family. If empty, it uses the editor font family."
@@ -177,6 +394,14 @@ pub struct EditorConfig {
     pub enable_completion_lens: bool,
     #[field_names(desc = "If the editor should display inline completions")]
     pub enable_inline_completion: bool,
+    #[field_names(
+        desc = "The plugins whose inline completions come first, by their names separated by commas, in order of priority"
+    )]
+    pub inline_completion_providers: String,
+    #[field_names(
+        desc = "The plugins not asked for inline completions, by their names separated by commas"
+    )]
+    pub disabled_inline_completion_providers: String,
     #[field_names(
         desc = "Set completion lens font family. If empty, it uses the inlay hint font family."
     )]
@@ -203,6 +428,16 @@ pub struct EditorConfig {
     pub render_whitespace: RenderWhitespace,
     #[field_names(desc = "Whether the editor show indent guide.")]
     pub show_indent_guide: bool,
+
+    #[field_names(
+        desc = "If the indent guides are colored by indentation level, from the editor.indent_guide.rainbow colors of the theme"
+    )]
+    pub rainbow_indent_guides: bool,
+
+    #[field_names(
+        desc = "If a guide is shown from the opening to the closing bracket of the block containing the cursor"
+    )]
+    pub show_bracket_pair_guide: bool,
     #[field_names(
         desc = "Set the auto save delay (in milliseconds), Set to 0 to completely disable"
     )]
@@ -211,10 +446,18 @@ pub struct EditorConfig {
         desc = "Whether the document should be formatted when an autosave is triggered (required Format on Save)"
     )]
     pub format_on_autosave: bool,
+    #[field_names(
+        desc = "Whether a document is saved when its editor, or the window, loses the focus"
+    )]
+    pub autosave_on_focus_lost: bool,
     #[field_names(
         desc = "If enabled the cursor treats leading soft tabs as if they are hard tabs."
     )]
     pub atomic_soft_tabs: bool,
+    #[field_names(
+        desc = "Whether the left and right keys move the cursor in the order the text is stored (Logical) or shown (Visual), which differ in right to left text"
+    )]
+    pub cursor_movement: CursorMovement,
     #[field_names(
         desc = "Use a double click to interact with the file explorer.\nOptions: single (default), file or all."
     )]
@@ -225,10 +468,72 @@ pub struct EditorConfig {
         desc = "Set the default number of visible lines above and below the diff block (-1 for infinite)"
     )]
     pub diff_context_lines: i32,
+    #[field_names(
+        desc = "Tint the gutter by how recently each line was changed, from git blame. Hover over the gutter for the commit of a line."
+    )]
+    pub blame_heat_map: bool,
+    #[field_names(
+        desc = "The coverage report in the lcov or Cobertura format, relative to the workspace, whose line coverage is shown in the gutter. It's reloaded whenever it changes."
+    )]
+    pub coverage_file: String,
+    #[field_names(
+        desc = "The regular expression telling the lines of error level in log mode, which is on for the files ending in .log"
+    )]
+    pub log_error_pattern: String,
+    #[field_names(
+        desc = "The regular expression telling the lines of warning level in log mode"
+    )]
+    pub log_warn_pattern: String,
+    #[field_names(
+        desc = "The regular expression telling the lines of info level in log mode"
+    )]
+    pub log_info_pattern: String,
+    #[field_names(
+        desc = "The regular expression telling the lines of debug level in log mode"
+    )]
+    pub log_debug_pattern: String,
+    #[field_names(
+        desc = "The size in megabytes over which a file is opened in a read-only viewer reading it a chunk at a time rather than in an editor. Set it to 0 to open all files in editors."
+    )]
+    pub large_file_size: usize,
+    #[field_names(
+        desc = "The author the file templates fill ${AUTHOR} with, the user name of the system when it's empty"
+    )]
+    pub file_template_author: String,
+    #[field_names(
+        desc = "How fast the mouse wheel scrolls the editor and the terminal, as a multiple of the normal speed"
+    )]
+    pub scroll_speed_modifier: f64,
+    #[field_names(
+        desc = "Whether to reverse the direction the mouse wheel scrolls the editor and the terminal in"
+    )]
+    pub natural_scroll: bool,
+    #[field_names(
+        desc = "Whether scrolling carries on and slows down after a flick of the touchpad or mouse wheel. Leave it off where the system already does this, such as on macOS."
+    )]
+    pub kinetic_scrolling: bool,
+    #[field_names(
+        desc = "How long, in milliseconds, scrolling by page or by line with the keyboard is animated for. Set it to 0 to scroll at once."
+    )]
+    pub smooth_scroll_duration: u64,
+    #[field_names(desc = "How the speed of smooth scrolling changes along the way")]
+    pub smooth_scroll_easing: ScrollEasing,
     #[field_names(desc = "Whether the editor colorizes brackets")]
     pub bracket_pair_colorization: bool,
     #[field_names(desc = "Bracket colorization Limit")]
     pub bracket_colorization_limit: u64,
+    #[field_names(
+        desc = "The length in bytes over which a line is shown without syntax colors and inlay hints, to keep laying it out fast. The editors of a file with such lines cut them into segments of 1000 columns when they don't wrap. Set it to 0 for no limit."
+    )]
+    pub long_line_length: usize,
+    #[field_names(
+        desc = "Whether to show control characters, such as escape, as their control pictures, such as ␛"
+    )]
+    pub render_control_characters: bool,
+    #[field_names(
+        desc = "Minimum contrast ratio (1 to 21) between syntax colors and the editor background. Colors below it are adjusted. 0 disables it, high contrast themes always use at least 7."
+    )]
+    pub minimum_contrast_ratio: f64,
     #[field_names(
         desc = "Glob patterns for excluding files and folders (in file explorer)"
     )]
@@ -236,16 +541,46 @@ pub struct EditorConfig {
 }
 
 impl EditorConfig {
+    /// How far a turn of the mouse wheel scrolls, with the wheel scrolling
+    /// sideways while Shift is held.
+    pub fn wheel_delta(&self, delta: Vec2, shift: bool) -> Vec2 {
+        let delta = if shift && delta.x == 0.0 {
+            Vec2::new(delta.y, 0.0)
+        } else {
+            delta
+        };
+        let speed = if self.scroll_speed_modifier > 0.0 {
+            self.scroll_speed_modifier
+        } else {
+            1.0
+        };
+        let delta = delta * speed;
+        if self.natural_scroll {
+            -delta
+        } else {
+            delta
+        }
+    }
+
+    pub fn smooth_scroll_duration(&self) -> Duration {
+        Duration::from_millis(self.smooth_scroll_duration.min(1000))
+    }
+
     pub fn font_size(&self) -> usize {
         self.font_size.clamp(6, 32)
     }
 
+    /// The height of a line, with the line spacing and padding, which all
+    /// the positions of lines in the editor are computed from
     pub fn line_height(&self) -> usize {
         let line_height = if self.line_height < SCALE_OR_SIZE_LIMIT {
             self.line_height * self.font_size as f64
         } else {
             self.line_height
         };
+        let line_height = line_height
+            + self.line_spacing.factor() * self.font_size as f64
+            + 2.0 * self.line_padding as f64;
 
         // Prevent overlapping lines
         (line_height.round() as usize).max(self.font_size)
@@ -293,3 +628,48 @@ pub fn blink_interval(&self) -> u64 {
         self.blink_interval.max(200)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{EditorConfig, LineSpacing, ScrollEasing};
+
+    #[test]
+    fn test_line_height() {
+        let mut config = EditorConfig {
+            font_size: 10,
+            line_height: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(config.line_height(), 15);
+
+        config.line_padding = 2;
+        assert_eq!(config.line_height(), 19);
+        config.line_spacing = LineSpacing::Comfortable;
+        assert_eq!(config.line_height(), 22);
+
+        // Lines never overlap
+        config.line_padding = 0;
+        config.line_height = 1.0;
+        config.line_spacing = LineSpacing::Compact;
+        assert_eq!(config.line_height(), 10);
+    }
+
+    #[test]
+    fn test_scroll_easing() {
+        for easing in [
+            ScrollEasing::Linear,
+            ScrollEasing::EaseOut,
+            ScrollEasing::EaseInOut,
+        ] {
+            assert_eq!(easing.ease(0.0), 0.0);
+            assert_eq!(easing.ease(1.0), 1.0);
+            assert_eq!(easing.ease(2.0), 1.0);
+            // Always moving forward
+            let steps: Vec<f64> =
+                (0..=10).map(|i| easing.ease(i as f64 / 10.0)).collect();
+            assert!(steps.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert!(ScrollEasing::EaseOut.ease(0.5) > 0.5);
+        assert!((ScrollEasing::EaseInOut.ease(0.5) - 0.5).abs() < 1e-9);
+    }
+}
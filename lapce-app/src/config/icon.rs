@@ -27,10 +27,16 @@ impl LapceIcons {
     pub const DEBUG_STOP: &'static str = "debug_stop";
     pub const DEBUG_CONSOLE: &'static str = "debug_console";
     pub const DEBUG_DISCONNECT: &'static str = "debug_disconnect";
+    pub const FLAME: &'static str = "flame";
+    pub const OUTPUT: &'static str = "output";
+    pub const JSON_TREE: &'static str = "json_tree";
+    pub const SYNTAX_TREE: &'static str = "syntax_tree";
+    pub const NOTIFICATIONS: &'static str = "notifications";
     pub const START: &'static str = "start";
     pub const RUN_ERRORS: &'static str = "run_errors";
     pub const UNSAVED: &'static str = "unsaved";
     pub const WARNING: &'static str = "warning";
+    pub const INFO: &'static str = "info";
     pub const TERMINAL: &'static str = "terminal";
     pub const SETTINGS: &'static str = "settings";
     pub const LIGHTBULB: &'static str = "lightbulb";
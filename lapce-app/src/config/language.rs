@@ -0,0 +1,218 @@
+use std::{collections::HashMap, path::Path};
+
+use globset::{Glob, GlobMatcher};
+use lapce_core::language::LapceLanguage;
+use serde::{Deserialize, Serialize};
+
+use super::editor::{EditorConfig, WrapStyle};
+
+/// Editor settings for a single language, set in a `[language.<name>]` table
+/// such as `[language.rust]`. Whatever is left out comes from `[editor]`.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LanguageConfig {
+    pub tab_width: Option<usize>,
+    pub font_size: Option<usize>,
+    pub wrap_style: Option<WrapStyle>,
+    pub wrap_column: Option<usize>,
+    pub wrap_width: Option<usize>,
+    pub enable_inlay_hints: Option<bool>,
+    pub format_on_save: Option<bool>,
+    pub emmet: Option<bool>,
+}
+
+/// The editor settings in effect for a language.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LanguageEditorConfig {
+    pub tab_width: usize,
+    pub font_size: usize,
+    pub wrap_style: WrapStyle,
+    pub wrap_column: usize,
+    pub wrap_width: usize,
+    pub enable_inlay_hints: bool,
+    pub format_on_save: bool,
+    pub emmet: bool,
+}
+
+impl LanguageEditorConfig {
+    pub fn resolve(
+        editor: &EditorConfig,
+        language: Option<&LanguageConfig>,
+    ) -> Self {
+        let language = language.copied().unwrap_or_default();
+        Self {
+            tab_width: language.tab_width.unwrap_or(editor.tab_width),
+            font_size: language
+                .font_size
+                .map(|size| size.clamp(6, 32))
+                .unwrap_or_else(|| editor.font_size()),
+            wrap_style: language.wrap_style.unwrap_or(editor.wrap_style),
+            wrap_column: language.wrap_column.unwrap_or(editor.wrap_column),
+            wrap_width: language.wrap_width.unwrap_or(editor.wrap_width),
+            enable_inlay_hints: language
+                .enable_inlay_hints
+                .unwrap_or(editor.enable_inlay_hints),
+            format_on_save: language.format_on_save.unwrap_or(editor.format_on_save),
+            emmet: language.emmet.unwrap_or(editor.enable_emmet),
+        }
+    }
+}
+
+/// Key the `[language.<name>]` tables by language, dropping the names that
+/// aren't known languages.
+pub fn resolve_language_names(
+    tables: &HashMap<String, LanguageConfig>,
+) -> HashMap<LapceLanguage, LanguageConfig> {
+    tables
+        .iter()
+        .filter_map(|(name, config)| {
+            LapceLanguage::from_name(name).map(|language| (language, *config))
+        })
+        .collect()
+}
+
+/// The languages of `[file-associations]`, such as `"*.vue" = "html"` or
+/// `Containerfile = "dockerfile"`, used in place of the ones files get from their names.
+/// A key is a glob, an extension starting with a dot, or a file name.
+#[derive(Debug, Clone, Default)]
+pub struct FileAssociations {
+    names: Vec<(String, LapceLanguage)>,
+    globs: Vec<(GlobMatcher, LapceLanguage)>,
+    extensions: Vec<(String, LapceLanguage)>,
+}
+
+impl FileAssociations {
+    /// Drops the entries whose language isn't known or whose glob is invalid.
+    pub fn new(table: &HashMap<String, String>) -> Self {
+        let mut associations = Self::default();
+        let mut entries = table.iter().collect::<Vec<_>>();
+        // The globs are tried in order, so that order mustn't be random
+        entries.sort();
+        for (pattern, name) in entries {
+            let Some(language) = LapceLanguage::from_name(name) else {
+                continue;
+            };
+            if pattern.contains(['*', '?', '[', '{']) {
+                if let Ok(glob) = Glob::new(pattern) {
+                    associations.globs.push((glob.compile_matcher(), language));
+                }
+            } else if let Some(extension) = pattern.strip_prefix('.') {
+                associations
+                    .extensions
+                    .push((extension.to_lowercase(), language));
+            } else {
+                associations.names.push((pattern.clone(), language));
+            }
+        }
+        associations
+    }
+
+    /// The language of the file at `path`, the file names being matched
+    /// first, then the globs, then the extensions. Globs without a `/` are
+    /// matched against the file name only.
+    pub fn language_of(&self, path: &Path) -> Option<LapceLanguage> {
+        let file_name = path.file_name()?.to_str()?;
+        if let Some((_, language)) =
+            self.names.iter().find(|(name, _)| name == file_name)
+        {
+            return Some(*language);
+        }
+        let glob = self.globs.iter().find(|(glob, _)| {
+            if glob.glob().glob().contains('/') {
+                glob.is_match(path)
+            } else {
+                glob.is_match(file_name)
+            }
+        });
+        if let Some((_, language)) = glob {
+            return Some(*language);
+        }
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        self.extensions
+            .iter()
+            .find(|(e, _)| *e == extension)
+            .map(|(_, language)| *language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::Path};
+
+    use lapce_core::language::LapceLanguage;
+
+    use super::{
+        resolve_language_names, FileAssociations, LanguageConfig,
+        LanguageEditorConfig,
+    };
+    use crate::config::editor::EditorConfig;
+
+    #[test]
+    fn test_resolve() {
+        let editor = EditorConfig {
+            tab_width: 4,
+            format_on_save: true,
+            ..Default::default()
+        };
+        let rust = LanguageConfig {
+            tab_width: Some(2),
+            font_size: Some(100),
+            wrap_column: Some(100),
+            format_on_save: Some(false),
+            ..Default::default()
+        };
+
+        let resolved = LanguageEditorConfig::resolve(&editor, Some(&rust));
+        assert_eq!(resolved.tab_width, 2);
+        assert_eq!(resolved.font_size, 32);
+        assert!(!resolved.format_on_save);
+        assert_eq!(resolved.wrap_column, 100);
+        assert_eq!(resolved.wrap_width, editor.wrap_width);
+
+        let resolved = LanguageEditorConfig::resolve(&editor, None);
+        assert_eq!(resolved.tab_width, 4);
+        assert_eq!(resolved.font_size, editor.font_size());
+        assert!(resolved.format_on_save);
+    }
+
+    #[test]
+    fn test_resolve_language_names() {
+        let tables: HashMap<String, LanguageConfig> = [
+            ("rust".to_string(), LanguageConfig::default()),
+            ("not-a-language".to_string(), LanguageConfig::default()),
+        ]
+        .into_iter()
+        .collect();
+        let languages = resolve_language_names(&tables);
+        assert_eq!(languages.len(), 1);
+        assert!(languages.contains_key(&LapceLanguage::Rust));
+    }
+
+    #[test]
+    fn test_file_associations() {
+        let table: HashMap<String, String> = [
+            ("*.vue", "html"),
+            ("Containerfile", "dockerfile"),
+            (".tpl", "html"),
+            ("**/templates/*.txt", "markdown"),
+            ("*.unknown", "not-a-language"),
+        ]
+        .into_iter()
+        .map(|(pattern, name)| (pattern.to_string(), name.to_string()))
+        .collect();
+        let associations = FileAssociations::new(&table);
+        let language_of = |path: &str| associations.language_of(Path::new(path));
+        assert_eq!(language_of("/a/b.vue"), Some(LapceLanguage::Html));
+        assert_eq!(
+            language_of("/a/Containerfile"),
+            Some(LapceLanguage::Dockerfile)
+        );
+        assert_eq!(language_of("/a/b.TPL"), Some(LapceLanguage::Html));
+        assert_eq!(
+            language_of("/a/templates/b.txt"),
+            Some(LapceLanguage::Markdown)
+        );
+        assert_eq!(language_of("/a/b.txt"), None);
+        assert_eq!(language_of("/a/b.unknown"), None);
+    }
+}
@@ -1,9 +1,28 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 use floem::peniko::Color;
-use serde::{Deserialize, Serialize};
+use lapce_core::directory::Directory;
+use serde::{Deserialize, Deserializer, Serialize};
 use structdesc::FieldNames;
 
+use super::icon::LapceIcons;
+use crate::workspace::LapceWorkspace;
+
+/// The icons a terminal profile can show on its tabs
+pub const PROFILE_ICONS: &[&str] = &[
+    LapceIcons::TERMINAL,
+    LapceIcons::START,
+    LapceIcons::DEBUG,
+    LapceIcons::REMOTE,
+    LapceIcons::SCM,
+    LapceIcons::SEARCH,
+    LapceIcons::FILE,
+    LapceIcons::SETTINGS,
+    LapceIcons::EXTENSIONS,
+    LapceIcons::KEYBOARD,
+    LapceIcons::LIGHTBULB,
+];
+
 #[derive(FieldNames, Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct TerminalConfig {
@@ -35,12 +54,70 @@ pub struct TerminalConfig {
 pub struct TerminalProfile {
     #[field_names(desc = "Command to execute when launching terminal")]
     pub command: Option<String>,
-    #[field_names(desc = "Arguments passed to command")]
+    #[field_names(desc = "Arguments passed to command, separated by spaces")]
     pub arguments: Option<Vec<String>>,
-    #[field_names(desc = "Command to execute when launching terminal")]
+    #[field_names(
+        desc = "Directory the terminal starts in. If empty, it's chosen by Cwd."
+    )]
     pub workdir: Option<std::path::PathBuf>,
-    #[field_names(desc = "Arguments passed to command")]
+    #[field_names(desc = "Environment variables, as KEY=VALUE separated by spaces")]
+    #[serde(default, deserialize_with = "deserialize_environment")]
     pub environment: Option<HashMap<String, String>>,
+    #[field_names(
+        desc = "Start in the workspace folder, the folder of the active file, or the home folder"
+    )]
+    #[serde(default)]
+    pub cwd: TerminalCwd,
+    #[field_names(desc = "Icon shown on the terminal tabs")]
+    pub icon: Option<String>,
+    #[field_names(desc = "Color of the terminal tabs, such as #E06C75")]
+    pub color: Option<String>,
+}
+
+/// Where the terminals of a profile without a `workdir` start
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Deserialize,
+    Serialize,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::VariantNames,
+)]
+pub enum TerminalCwd {
+    #[default]
+    Workspace,
+    File,
+    Home,
+}
+
+/// The environment is a table, or a list of `KEY=VALUE` as written by the
+/// settings editor.
+fn deserialize_environment<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Environment {
+        Table(HashMap<String, String>),
+        List(Vec<String>),
+    }
+
+    Ok(
+        Option::<Environment>::deserialize(deserializer)?.map(|env| match env {
+            Environment::Table(env) => env,
+            Environment::List(env) => env
+                .iter()
+                .filter_map(|var| var.split_once('='))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }),
+    )
 }
 
 impl TerminalConfig {
@@ -74,28 +151,136 @@ pub fn get_indexed_colors(&mut self) {
         self.indexed_colors = Arc::new(indexed_colors);
     }
 
+    /// The name of the default profile of this OS
+    pub fn default_profile_name(&self) -> &str {
+        self.default_profile
+            .get(std::env::consts::OS)
+            .map(|name| name.as_str())
+            .unwrap_or("default")
+    }
+
     pub fn get_default_profile(
         &self,
+        workspace: &LapceWorkspace,
+        file: Option<&Path>,
     ) -> Option<lapce_rpc::terminal::TerminalProfile> {
-        let profile = self.profiles.get(
-            self.default_profile
-                .get(std::env::consts::OS)
-                .unwrap_or(&String::from("default")),
-        )?;
-        let workdir = if let Some(workdir) = &profile.workdir {
-            url::Url::parse(&workdir.display().to_string()).ok()
-        } else {
-            None
-        };
+        self.get_profile(self.default_profile_name(), workspace, file)
+    }
 
-        let profile = profile.clone();
+    /// The profile to launch a terminal with, starting in its `workdir` or
+    /// where its `cwd` says given the active `file`.
+    pub fn get_profile(
+        &self,
+        name: &str,
+        workspace: &LapceWorkspace,
+        file: Option<&Path>,
+    ) -> Option<lapce_rpc::terminal::TerminalProfile> {
+        let profile = self.profiles.get(name)?.clone();
+        let workdir = profile
+            .workdir
+            .clone()
+            .filter(|workdir| !workdir.as_os_str().is_empty());
+        let workdir = match (workdir, profile.cwd) {
+            (Some(workdir), _) => Some(workdir),
+            (None, TerminalCwd::Workspace) => workspace.path.clone(),
+            (None, TerminalCwd::File) => file
+                .and_then(|file| file.parent())
+                .map(|dir| dir.to_path_buf())
+                .or_else(|| workspace.path.clone()),
+            // The proxy starts terminals without a directory in the home
+            // folder of the machine it runs on
+            (None, TerminalCwd::Home) if workspace.kind.is_remote() => None,
+            (None, TerminalCwd::Home) => Directory::home_dir(),
+        };
 
         Some(lapce_rpc::terminal::TerminalProfile {
-            name: std::env::consts::OS.to_string(),
-            command: profile.command,
+            name: name.to_string(),
+            // The settings editor leaves the fields it clears empty
+            command: profile.command.filter(|command| !command.is_empty()),
             arguments: profile.arguments,
-            workdir,
+            workdir: workdir.and_then(|workdir| workspace.path_url(&workdir)),
             environment: profile.environment,
         })
     }
+
+    /// The icon of the tabs of a profile
+    pub fn profile_icon(&self, name: &str) -> &'static str {
+        self.profiles
+            .get(name)
+            .and_then(|profile| profile.icon.as_deref())
+            .and_then(|icon| PROFILE_ICONS.iter().find(|i| **i == icon))
+            .copied()
+            .unwrap_or(LapceIcons::TERMINAL)
+    }
+
+    /// The color of the tabs of a profile, if it has one
+    pub fn profile_color(&self, name: &str) -> Option<Color> {
+        let color = self.profiles.get(name)?.color.as_deref()?;
+        Color::parse(color.trim())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{TerminalConfig, TerminalCwd, TerminalProfile};
+    use crate::workspace::LapceWorkspace;
+
+    #[test]
+    fn test_environment() {
+        let profile: TerminalProfile =
+            toml::from_str(r#"environment = ["A=1", "B=x=y", "C"]"#).unwrap();
+        let env = profile.environment.unwrap();
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["B"], "x=y");
+
+        let profile: TerminalProfile =
+            toml::from_str(r#"environment = { A = "1" }"#).unwrap();
+        assert_eq!(profile.environment.unwrap()["A"], "1");
+
+        let profile: TerminalProfile = toml::from_str("").unwrap();
+        assert!(profile.environment.is_none());
+        assert_eq!(profile.cwd, TerminalCwd::Workspace);
+    }
+
+    #[test]
+    fn test_profile_workdir() {
+        let workspace = LapceWorkspace {
+            path: Some(PathBuf::from("/work")),
+            ..Default::default()
+        };
+        let file = Path::new("/work/src/main.rs");
+        let workdir = |profile: TerminalProfile| {
+            let config = TerminalConfig {
+                profiles: [("p".to_string(), profile)].into_iter().collect(),
+                ..Default::default()
+            };
+            config
+                .get_profile("p", &workspace, Some(file))
+                .unwrap()
+                .workdir
+                .and_then(|url| url.to_file_path().ok())
+        };
+
+        assert_eq!(
+            workdir(TerminalProfile::default()),
+            Some(PathBuf::from("/work"))
+        );
+        assert_eq!(
+            workdir(TerminalProfile {
+                cwd: TerminalCwd::File,
+                ..Default::default()
+            }),
+            Some(PathBuf::from("/work/src"))
+        );
+        assert_eq!(
+            workdir(TerminalProfile {
+                workdir: Some(PathBuf::from("/tmp")),
+                cwd: TerminalCwd::File,
+                ..Default::default()
+            }),
+            Some(PathBuf::from("/tmp"))
+        );
+    }
 }
@@ -62,6 +62,11 @@ pub struct UIConfig {
 
     #[field_names(desc = "Display the Open Editors section in the explorer")]
     pub open_editors_visible: bool,
+
+    #[field_names(
+        desc = "Show an overlay with input latency, paint and language server timings"
+    )]
+    pub show_perf_overlay: bool,
 }
 
 #[derive(
@@ -0,0 +1,750 @@
+//! Checks of Lapce's own settings, keymaps and color theme files against what
+//! Lapce reads from them, reported as diagnostics while they are edited:
+//! unknown keys, values of the wrong type, invalid colors and commands.
+
+use std::{collections::HashMap, ops::Range, path::Path};
+
+use floem::peniko::Color;
+use lapce_core::{directory::Directory, language::LapceLanguage};
+use lsp_types::DiagnosticSeverity;
+use toml_edit::{Document, Item, TomlError, Value};
+
+use super::{DEFAULT_DARK_THEME, DEFAULT_SETTINGS};
+use crate::{
+    command::lapce_internal_commands, command_history::parse_repeat_command,
+    command_macro::parse_run_macro_command, keypress::keymap::KeyMapPress,
+};
+
+/// The keys of `[language.<name>]`, with the keys of `[editor]` they stand in
+/// for
+const LANGUAGE_SETTINGS: &[(&str, &str)] = &[
+    ("tab-width", "tab-width"),
+    ("font-size", "font-size"),
+    ("wrap-style", "wrap-style"),
+    ("wrap-column", "wrap-column"),
+    ("wrap-width", "wrap-width"),
+    ("enable-inlay-hints", "enable-inlay-hints"),
+    ("format-on-save", "format-on-save"),
+    ("emmet", "enable-emmet"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFileKind {
+    Settings,
+    Keymaps,
+    ColorTheme,
+}
+
+impl ConfigFileKind {
+    /// The kind of the file at `path` if it's one of Lapce's config files:
+    /// the settings and the keymaps of the user or of a profile, the settings
+    /// of a workspace, or a color theme
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let dir = path.parent()?;
+        let is_config_dir = || {
+            Directory::config_directory().is_some_and(|config| config == dir)
+                || Directory::profiles_directory()
+                    .is_some_and(|profiles| dir.parent() == Some(profiles.as_path()))
+        };
+        match name {
+            "settings.toml" if dir.ends_with(".lapce") || is_config_dir() => {
+                Some(Self::Settings)
+            }
+            "keymaps.toml" if is_config_dir() => Some(Self::Keymaps),
+            _ if name.ends_with(".toml")
+                && Directory::themes_directory()
+                    .is_some_and(|themes| themes == dir) =>
+            {
+                Some(Self::ColorTheme)
+            }
+            _ => None,
+        }
+    }
+
+    /// The problems of the text of such a file, in the order of the text
+    pub fn check(&self, text: &str) -> Vec<ConfigProblem> {
+        let mut problems = match self {
+            ConfigFileKind::Settings => check_settings(text),
+            ConfigFileKind::Keymaps => {
+                let commands = lapce_internal_commands();
+                check_keymaps(text, |command| {
+                    commands.contains_key(command)
+                        || parse_repeat_command(command).is_some()
+                        || parse_run_macro_command(command).is_some()
+                })
+            }
+            ConfigFileKind::ColorTheme => check_color_theme(text),
+        };
+        problems.sort_by_key(|problem| problem.range.start);
+        problems
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub range: Range<usize>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+pub fn check_settings(text: &str) -> Vec<ConfigProblem> {
+    let doc = match text.parse::<Document>() {
+        Ok(doc) => doc,
+        Err(err) => return vec![syntax_error(&err)],
+    };
+    let defaults: Document = DEFAULT_SETTINGS
+        .parse()
+        .expect("Failed to parse default settings");
+    let mut checker = Checker::new(text);
+    for (name, item) in doc.iter() {
+        let path = [name.to_string()];
+        match name {
+            "core" | "editor" | "terminal" | "ui" => {
+                if let Some(default) = defaults.get(name) {
+                    checker.check_against(&path, item, default);
+                }
+            }
+            "color-theme" => checker.check_color_theme(&path, item),
+            "language" => {
+                if let Some(editor) = defaults.get("editor") {
+                    checker.check_languages(&path, item, editor);
+                }
+            }
+            "file-associations" => checker.check_file_associations(&path, item),
+            // The icon theme, and the settings of the plugins
+            _ => {}
+        }
+    }
+    checker.problems
+}
+
+/// The problems of a keymaps file, `is_command` telling the commands that
+/// exist
+pub fn check_keymaps(
+    text: &str,
+    is_command: impl Fn(&str) -> bool,
+) -> Vec<ConfigProblem> {
+    let doc = match text.parse::<Document>() {
+        Ok(doc) => doc,
+        Err(err) => return vec![syntax_error(&err)],
+    };
+    let mut checker = Checker::new(text);
+    for (name, item) in doc.iter() {
+        let path = [name.to_string()];
+        if name != "keymaps" {
+            checker.unknown(&path, "key");
+            continue;
+        }
+        let Some(keymaps) = item.as_array_of_tables() else {
+            checker.report(
+                &path,
+                DiagnosticSeverity::ERROR,
+                "The keymaps should be written as [[keymaps]] tables".to_string(),
+            );
+            continue;
+        };
+        for (i, keymap) in keymaps.iter().enumerate() {
+            let path = [name.to_string(), i.to_string()];
+            for key in ["key", "command"] {
+                if !keymap.contains_key(key) {
+                    checker.report(
+                        &path,
+                        DiagnosticSeverity::ERROR,
+                        format!("The keymap has no `{key}`"),
+                    );
+                }
+            }
+            for (key, item) in keymap.iter() {
+                let path = child(&path, key);
+                match key {
+                    "key" => {
+                        let Some(keys) = checker.string(&path, item) else {
+                            continue;
+                        };
+                        if let Err(err) = KeyMapPress::check(keys) {
+                            checker.report(&path, DiagnosticSeverity::ERROR, err);
+                        }
+                    }
+                    "command" => {
+                        let Some(command) = checker.string(&path, item) else {
+                            continue;
+                        };
+                        // A command starting with `-` removes a keymap
+                        let command = command.trim();
+                        let command = command.strip_prefix('-').unwrap_or(command);
+                        if !is_command(command) {
+                            checker.report(
+                                &path,
+                                DiagnosticSeverity::WARNING,
+                                format!("Unknown command `{command}`"),
+                            );
+                        }
+                    }
+                    "mode" => {
+                        let Some(modes) = checker.string(&path, item) else {
+                            continue;
+                        };
+                        if let Some(mode) = modes
+                            .chars()
+                            .find(|c| !matches!(c, 'i' | 'n' | 'v' | 't'))
+                        {
+                            checker.report(
+                                &path,
+                                DiagnosticSeverity::WARNING,
+                                format!(
+                                    "Unknown mode `{mode}`, the modes are `i`, `n`, \
+                                     `v` and `t`"
+                                ),
+                            );
+                        }
+                    }
+                    "when" => {
+                        checker.string(&path, item);
+                    }
+                    _ => checker.unknown(&path, "key"),
+                }
+            }
+        }
+    }
+    checker.problems
+}
+
+pub fn check_color_theme(text: &str) -> Vec<ConfigProblem> {
+    let doc = match text.parse::<Document>() {
+        Ok(doc) => doc,
+        Err(err) => return vec![syntax_error(&err)],
+    };
+    let defaults: Document = DEFAULT_SETTINGS
+        .parse()
+        .expect("Failed to parse default settings");
+    let mut checker = Checker::new(text);
+    for (name, item) in doc.iter() {
+        let path = [name.to_string()];
+        match name {
+            "color-theme" => checker.check_color_theme(&path, item),
+            "ui" => {
+                if let Some(default) = defaults.get("ui") {
+                    checker.check_against(&path, item, default);
+                }
+            }
+            _ => checker.unknown(&path, "key"),
+        }
+    }
+    checker.problems
+}
+
+fn syntax_error(err: &TomlError) -> ConfigProblem {
+    ConfigProblem {
+        range: err.span().unwrap_or(0..0),
+        severity: DiagnosticSeverity::ERROR,
+        message: err.message().trim().to_string(),
+    }
+}
+
+struct Checker {
+    keys: KeyPositions,
+    /// The colors of the user interface a theme can set
+    ui_colors: Option<Vec<String>>,
+    problems: Vec<ConfigProblem>,
+}
+
+impl Checker {
+    fn new(text: &str) -> Self {
+        Self {
+            keys: KeyPositions::new(text),
+            ui_colors: None,
+            problems: Vec::new(),
+        }
+    }
+
+    fn report(
+        &mut self,
+        path: &[String],
+        severity: DiagnosticSeverity,
+        message: String,
+    ) {
+        self.problems.push(ConfigProblem {
+            range: self.keys.range_of(path),
+            severity,
+            message,
+        });
+    }
+
+    fn unknown(&mut self, path: &[String], what: &str) {
+        self.report(
+            path,
+            DiagnosticSeverity::WARNING,
+            format!("Unknown {what} `{}`", path.join(".")),
+        );
+    }
+
+    /// Report the value at `path` unless it's of the `expected` kind
+    fn expect(&mut self, path: &[String], item: &Item, expected: &'static str) {
+        let found = kind_of(item);
+        if found != expected && !(expected == "a number" && found == "an integer") {
+            self.report(
+                path,
+                DiagnosticSeverity::ERROR,
+                format!("`{}` should be {expected}, not {found}", path.join(".")),
+            );
+        }
+    }
+
+    /// The value at `path` if it's a string
+    fn string<'a>(&mut self, path: &[String], item: &'a Item) -> Option<&'a str> {
+        self.expect(path, item, "a string");
+        item.as_str()
+    }
+
+    /// Check the settings at `path` have the keys and the kinds of values of
+    /// the default ones
+    fn check_against(&mut self, path: &[String], item: &Item, default: &Item) {
+        let Some(defaults) = default.as_table_like() else {
+            self.expect(path, item, kind_of(default));
+            return;
+        };
+        let Some(table) = item.as_table_like() else {
+            self.expect(path, item, "a table");
+            return;
+        };
+        // A table of entries named by the user, such as the terminal profiles
+        if defaults.iter().all(|(_, item)| item.is_table_like()) {
+            return;
+        }
+        for (key, item) in table.iter() {
+            let path = child(path, key);
+            match defaults.get(key) {
+                Some(default) => self.check_against(&path, item, default),
+                None => self.unknown(&path, "setting"),
+            }
+        }
+    }
+
+    /// Check the `[language.<name>]` tables
+    fn check_languages(&mut self, path: &[String], item: &Item, editor: &Item) {
+        let Some(languages) = item.as_table_like() else {
+            self.expect(path, item, "a table");
+            return;
+        };
+        for (name, settings) in languages.iter() {
+            let path = child(path, name);
+            if LapceLanguage::from_name(name).is_none() {
+                self.unknown(&path, "language");
+            }
+            let Some(settings) = settings.as_table_like() else {
+                self.expect(&path, settings, "a table");
+                continue;
+            };
+            for (key, item) in settings.iter() {
+                let path = child(&path, key);
+                let default = LANGUAGE_SETTINGS
+                    .iter()
+                    .find(|(name, _)| *name == key)
+                    .and_then(|(_, editor_key)| editor.get(editor_key));
+                match default {
+                    Some(default) => self.check_against(&path, item, default),
+                    None => self.unknown(&path, "setting"),
+                }
+            }
+        }
+    }
+
+    fn check_file_associations(&mut self, path: &[String], item: &Item) {
+        let Some(associations) = item.as_table_like() else {
+            self.expect(path, item, "a table");
+            return;
+        };
+        for (pattern, item) in associations.iter() {
+            let path = child(path, pattern);
+            let Some(language) = self.string(&path, item) else {
+                continue;
+            };
+            if LapceLanguage::from_name(language).is_none() {
+                self.report(
+                    &path,
+                    DiagnosticSeverity::WARNING,
+                    format!("Unknown language `{language}`"),
+                );
+            }
+        }
+    }
+
+    /// Check a `[color-theme]` table, of a theme or of the settings
+    fn check_color_theme(&mut self, path: &[String], item: &Item) {
+        let Some(theme) = item.as_table_like() else {
+            self.expect(path, item, "a table");
+            return;
+        };
+        for (key, item) in theme.iter() {
+            let path = child(path, key);
+            match key {
+                "name" => self.expect(&path, item, "a string"),
+                "high-contrast" => self.expect(&path, item, "a boolean"),
+                "base" | "syntax" => self.check_colors(&path, item, false),
+                "ui" => self.check_colors(&path, item, true),
+                _ => self.unknown(&path, "key"),
+            }
+        }
+    }
+
+    fn check_colors(&mut self, path: &[String], item: &Item, is_ui: bool) {
+        let Some(colors) = item.as_table_like() else {
+            self.expect(path, item, "a table");
+            return;
+        };
+        for (name, item) in colors.iter() {
+            let path = child(path, name);
+            if is_ui && !self.ui_colors().iter().any(|ui| ui == name) {
+                self.report(
+                    &path,
+                    DiagnosticSeverity::WARNING,
+                    format!("Unknown color `{name}`"),
+                );
+            }
+            let Some(color) = self.string(&path, item) else {
+                continue;
+            };
+            if !is_color(color) {
+                self.report(
+                    &path,
+                    DiagnosticSeverity::ERROR,
+                    format!(
+                        "`{color}` isn't a color, such as `#61AFEF`, or a base \
+                         color, such as `$blue`"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn ui_colors(&mut self) -> &[String] {
+        self.ui_colors.get_or_insert_with(|| {
+            DEFAULT_DARK_THEME
+                .parse::<Document>()
+                .ok()
+                .and_then(|theme| {
+                    let ui = theme.get("color-theme")?.get("ui")?.as_table_like()?;
+                    Some(ui.iter().map(|(name, _)| name.to_string()).collect())
+                })
+                .unwrap_or_default()
+        })
+    }
+}
+
+fn child(path: &[String], key: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    path
+}
+
+fn kind_of(item: &Item) -> &'static str {
+    match item {
+        Item::None => "nothing",
+        Item::Value(Value::String(_)) => "a string",
+        Item::Value(Value::Integer(_)) => "an integer",
+        Item::Value(Value::Float(_)) => "a number",
+        Item::Value(Value::Boolean(_)) => "a boolean",
+        Item::Value(Value::Datetime(_)) => "a date",
+        Item::Value(Value::Array(_)) => "an array",
+        Item::Value(Value::InlineTable(_)) | Item::Table(_) => "a table",
+        Item::ArrayOfTables(_) => "an array of tables",
+    }
+}
+
+/// Whether a theme's value is a color, or a reference to a base color such
+/// as `$blue`, an empty value leaving the color unset
+fn is_color(value: &str) -> bool {
+    if value.is_empty() {
+        return true;
+    }
+    match value.strip_prefix('$') {
+        Some(name) => !name.is_empty(),
+        None => Color::parse(value).is_some(),
+    }
+}
+
+/// Where the tables and the keys of a document are, as toml_edit doesn't keep
+/// the spans of what it parses. They are found by scanning the lines, so the
+/// keys of inline tables are left out.
+struct KeyPositions {
+    /// The paths of the keys, with the indices of the tables of arrays, and
+    /// where the keys or the table headers are
+    keys: Vec<(Vec<String>, Range<usize>)>,
+}
+
+impl KeyPositions {
+    fn new(text: &str) -> Self {
+        let mut keys = Vec::new();
+        let mut table = Vec::new();
+        let mut array_lens: HashMap<Vec<String>, usize> = HashMap::new();
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let trimmed = line.trim_start();
+            let start = line_start + line.len() - trimmed.len();
+            if let Some(header) = trimmed.strip_prefix("[[") {
+                let Some(end) = header.find("]]") else {
+                    continue;
+                };
+                let mut path = split_key(&header[..end]);
+                let len = array_lens.entry(path.clone()).or_default();
+                path.push(len.to_string());
+                *len += 1;
+                keys.push((path.clone(), start..start + end + 4));
+                table = path;
+            } else if let Some(header) = trimmed.strip_prefix('[') {
+                let Some(end) = header.find(']') else {
+                    continue;
+                };
+                let path = split_key(&header[..end]);
+                keys.push((path.clone(), start..start + end + 2));
+                table = path;
+            } else if let Some(eq) = find_unquoted(trimmed, '=') {
+                let key = trimmed[..eq].trim_end();
+                if key.is_empty() {
+                    continue;
+                }
+                let mut path = table.clone();
+                path.extend(split_key(key));
+                keys.push((path, start..start + key.len()));
+            }
+        }
+        Self { keys }
+    }
+
+    /// Where the key at `path` is, or else its closest ancestor
+    fn range_of(&self, path: &[String]) -> Range<usize> {
+        (0..=path.len())
+            .rev()
+            .find_map(|len| {
+                self.keys
+                    .iter()
+                    .find(|(key, _)| key.as_slice() == &path[..len])
+                    .map(|(_, range)| range.clone())
+            })
+            .unwrap_or(0..0)
+    }
+}
+
+/// The index of the first `c` out of quotes in a line, before any comment
+fn find_unquoted(line: &str, c: char) -> Option<usize> {
+    let mut quote = None;
+    for (i, ch) in line.char_indices() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => return None,
+            None if ch == c => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+/// The parts of a dotted key, such as `a`, `b.c` and `d` for `a."b.c".d`
+fn split_key(key: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = key;
+    loop {
+        let end = find_unquoted(rest, '.').unwrap_or(rest.len());
+        let part = rest[..end].trim();
+        let part = part
+            .strip_prefix('"')
+            .and_then(|part| part.strip_suffix('"'))
+            .or_else(|| {
+                part.strip_prefix('\'')
+                    .and_then(|part| part.strip_suffix('\''))
+            })
+            .unwrap_or(part);
+        parts.push(part.to_string());
+        if end == rest.len() {
+            return parts;
+        }
+        rest = &rest[end + 1..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::DiagnosticSeverity;
+
+    use super::{check_color_theme, check_keymaps, check_settings, split_key};
+
+    fn messages(
+        text: &str,
+        problems: Vec<super::ConfigProblem>,
+    ) -> Vec<(&str, DiagnosticSeverity, String)> {
+        problems
+            .into_iter()
+            .map(|p| (&text[p.range], p.severity, p.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        use crate::config::{
+            DEFAULT_DARK_THEME, DEFAULT_HIGH_CONTRAST_DARK_THEME,
+            DEFAULT_HIGH_CONTRAST_LIGHT_THEME, DEFAULT_LIGHT_THEME,
+            DEFAULT_SETTINGS,
+        };
+
+        assert_eq!(check_settings(DEFAULT_SETTINGS), vec![]);
+        for theme in [
+            DEFAULT_DARK_THEME,
+            DEFAULT_LIGHT_THEME,
+            DEFAULT_HIGH_CONTRAST_DARK_THEME,
+            DEFAULT_HIGH_CONTRAST_LIGHT_THEME,
+        ] {
+            assert_eq!(check_color_theme(theme), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_split_key() {
+        assert_eq!(split_key("a.\"b.c\" . d"), vec!["a", "b.c", "d"]);
+        assert_eq!(split_key("'x'"), vec!["x"]);
+    }
+
+    #[test]
+    fn test_check_settings() {
+        let text = r#"
+[editor]
+font-size = "big"
+tab-width = 2.5
+unknown-setting = true
+
+[ui]
+scale = 1
+
+[terminal.profiles.build]
+command = "cargo"
+
+[language.rust]
+tab-width = 2
+emmet = "no"
+
+[file-associations]
+"*.vue" = "nothing"
+
+[color-theme.ui]
+"editor.background" = "blue-ish"
+
+[some-plugin]
+anything = 1
+"#;
+        assert_eq!(
+            messages(text, check_settings(text)),
+            vec![
+                (
+                    "font-size",
+                    DiagnosticSeverity::ERROR,
+                    "`editor.font-size` should be an integer, not a string"
+                        .to_string()
+                ),
+                (
+                    "tab-width",
+                    DiagnosticSeverity::ERROR,
+                    "`editor.tab-width` should be an integer, not a number"
+                        .to_string()
+                ),
+                (
+                    "unknown-setting",
+                    DiagnosticSeverity::WARNING,
+                    "Unknown setting `editor.unknown-setting`".to_string()
+                ),
+                (
+                    "emmet",
+                    DiagnosticSeverity::ERROR,
+                    "`language.rust.emmet` should be a boolean, not a string"
+                        .to_string()
+                ),
+                (
+                    "\"*.vue\"",
+                    DiagnosticSeverity::WARNING,
+                    "Unknown language `nothing`".to_string()
+                ),
+                (
+                    "\"editor.background\"",
+                    DiagnosticSeverity::ERROR,
+                    "`blue-ish` isn't a color, such as `#61AFEF`, or a base color, \
+                     such as `$blue`"
+                        .to_string()
+                ),
+            ]
+        );
+
+        let problems = check_settings("[editor\nfont-size = 1");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, DiagnosticSeverity::ERROR);
+    }
+
+    #[test]
+    fn test_check_keymaps() {
+        let text = r#"
+[[keymaps]]
+key = "ctrl+k"
+command = "save"
+
+[[keymaps]]
+key = "hyper+k"
+command = "-no_such_command"
+mode = "x"
+
+[[keymaps]]
+command = "save"
+"#;
+        let problems = check_keymaps(text, |command| command == "save");
+        assert_eq!(
+            messages(text, problems),
+            vec![
+                (
+                    "key",
+                    DiagnosticSeverity::ERROR,
+                    "Invalid key modifier `hyper`".to_string()
+                ),
+                (
+                    "command",
+                    DiagnosticSeverity::WARNING,
+                    "Unknown command `no_such_command`".to_string()
+                ),
+                (
+                    "mode",
+                    DiagnosticSeverity::WARNING,
+                    "Unknown mode `x`, the modes are `i`, `n`, `v` and `t`"
+                        .to_string()
+                ),
+                (
+                    "[[keymaps]]",
+                    DiagnosticSeverity::ERROR,
+                    "The keymap has no `key`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_color_theme() {
+        let text = r##"
+[color-theme]
+name = "Test"
+
+[color-theme.base]
+blue = "#61AFEF"
+
+[color-theme.ui]
+"editor.background" = "$blue"
+"no.such.color" = "#000000"
+"##;
+        assert_eq!(
+            messages(text, check_color_theme(text)),
+            vec![(
+                "\"no.such.color\"",
+                DiagnosticSeverity::WARNING,
+                "Unknown color `no.such.color`".to_string()
+            )]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::{BTreeSet, HashMap},
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
@@ -7,14 +8,19 @@
 use anyhow::{anyhow, Result};
 use crossbeam_channel::{unbounded, Sender};
 use floem::peniko::kurbo::Vec2;
-use lapce_core::directory::Directory;
+use lapce_core::{directory::Directory, language::LapceLanguage};
 use lapce_rpc::plugin::VoltID;
 use sha2::{Digest, Sha256};
 
 use crate::{
     app::{AppData, AppInfo},
     doc::DocInfo,
-    panel::{data::PanelOrder, kind::PanelKind, position::PanelPosition},
+    font_warmup,
+    panel::{
+        data::{default_panel_order, PanelOrder},
+        kind::PanelKind,
+        position::PanelPosition,
+    },
     window::{WindowData, WindowInfo},
     window_tab::WindowTabData,
     workspace::{LapceWorkspace, WorkspaceInfo},
@@ -27,6 +33,9 @@
 const PANEL_ORDERS: &str = "panel_orders";
 const DISABLED_VOLTS: &str = "disabled_volts";
 const RECENT_WORKSPACES: &str = "recent_workspaces";
+const WORKSPACE_PROFILE: &str = "profile";
+const FILE_LANGUAGES: &str = "file_languages";
+const SEEN_CHARS: &str = "seen_chars";
 
 pub enum SaveEvent {
     App(AppInfo),
@@ -36,6 +45,7 @@ pub enum SaveEvent {
     DisabledVolts(Vec<VoltID>),
     WorkspaceDisabledVolts(Arc<LapceWorkspace>, Vec<VoltID>),
     PanelOrder(PanelOrder),
+    SeenChars(BTreeSet<char>),
 }
 
 #[derive(Clone)]
@@ -87,6 +97,9 @@ pub fn new() -> Result<Self> {
                     SaveEvent::PanelOrder(order) => {
                         let _ = local_db.insert_panel_orders(&order);
                     }
+                    SaveEvent::SeenChars(chars) => {
+                        let _ = local_db.insert_seen_chars(&chars);
+                    }
                 }
             }
         });
@@ -144,6 +157,86 @@ pub fn get_workspace_disabled_volts(
         Ok(volts)
     }
 
+    /// The profile that windows opening the workspace start with, `None`
+    /// being the default one
+    pub fn get_workspace_profile(
+        &self,
+        workspace: &LapceWorkspace,
+    ) -> Result<Option<String>> {
+        let folder = self.workspace_folder.join(workspace_folder_name(workspace));
+        let profile = std::fs::read_to_string(folder.join(WORKSPACE_PROFILE))?;
+        let profile = profile.trim();
+        Ok((!profile.is_empty()).then(|| profile.to_string()))
+    }
+
+    pub fn save_workspace_profile(
+        &self,
+        workspace: &LapceWorkspace,
+        profile: Option<&str>,
+    ) -> Result<()> {
+        let folder = self.workspace_folder.join(workspace_folder_name(workspace));
+        let path = folder.join(WORKSPACE_PROFILE);
+        match profile {
+            Some(profile) => {
+                std::fs::create_dir_all(&folder)?;
+                std::fs::write(path, profile)?;
+            }
+            None => {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The language chosen for a file of the workspace, in place of the one
+    /// it gets from the config
+    pub fn get_file_language(
+        &self,
+        workspace: &LapceWorkspace,
+        path: &Path,
+    ) -> Result<Option<LapceLanguage>> {
+        let languages = self.get_file_languages(workspace)?;
+        Ok(languages
+            .get(path)
+            .and_then(|name| LapceLanguage::from_name(name)))
+    }
+
+    /// Remember the language chosen for a file of the workspace, `None`
+    /// forgetting it
+    pub fn save_file_language(
+        &self,
+        workspace: &LapceWorkspace,
+        path: &Path,
+        language: Option<LapceLanguage>,
+    ) -> Result<()> {
+        let mut languages = self.get_file_languages(workspace).unwrap_or_default();
+        match language {
+            Some(language) => {
+                languages.insert(path.to_path_buf(), language.to_string());
+            }
+            None => {
+                languages.remove(path);
+            }
+        }
+        let folder = self.workspace_folder.join(workspace_folder_name(workspace));
+        std::fs::create_dir_all(&folder)?;
+        let languages = serde_json::to_string_pretty(&languages)?;
+        std::fs::write(folder.join(FILE_LANGUAGES), languages)?;
+        Ok(())
+    }
+
+    fn get_file_languages(
+        &self,
+        workspace: &LapceWorkspace,
+    ) -> Result<HashMap<PathBuf, String>> {
+        let folder = self.workspace_folder.join(workspace_folder_name(workspace));
+        let languages = std::fs::read_to_string(folder.join(FILE_LANGUAGES))?;
+        let languages = serde_json::from_str(&languages)?;
+        Ok(languages)
+    }
+
     pub fn recent_workspaces(&self) -> Result<Vec<LapceWorkspace>> {
         let workspaces =
             std::fs::read_to_string(self.folder.join(RECENT_WORKSPACES))?;
@@ -242,6 +335,8 @@ pub fn save_app(&self, data: &AppData) -> Result<()> {
         }
 
         self.save_tx.send(SaveEvent::App(info))?;
+        self.save_tx
+            .send(SaveEvent::SeenChars(font_warmup::seen_chars()))?;
 
         Ok(())
     }
@@ -271,6 +366,19 @@ pub fn insert_app(&self, data: AppData) -> Result<()> {
         Ok(())
     }
 
+    /// The characters outside ASCII seen in the documents of the last
+    /// session, for warming up the fonts with
+    pub fn get_seen_chars(&self) -> Result<BTreeSet<char>> {
+        let chars = std::fs::read_to_string(self.folder.join(SEEN_CHARS))?;
+        Ok(chars.chars().collect())
+    }
+
+    fn insert_seen_chars(&self, chars: &BTreeSet<char>) -> Result<()> {
+        let chars: String = chars.iter().collect();
+        std::fs::write(self.folder.join(SEEN_CHARS), chars)?;
+        Ok(())
+    }
+
     pub fn get_app(&self) -> Result<AppInfo> {
         let info = std::fs::read_to_string(self.folder.join(APP))?;
         let mut info: AppInfo = serde_json::from_str(&info)?;
@@ -328,10 +436,16 @@ pub fn get_panel_orders(&self) -> Result<PanelOrder> {
         let panel_orders = std::fs::read_to_string(self.folder.join(PANEL_ORDERS))?;
         let mut panel_orders: PanelOrder = serde_json::from_str(&panel_orders)?;
 
+        // Panels added since the order was saved go where they are by default
         use strum::IntoEnumIterator;
+        let default_order = default_panel_order();
         for kind in PanelKind::iter() {
             if kind.position(&panel_orders).is_none() {
-                let panels = panel_orders.entry(PanelPosition::LeftTop).or_default();
+                let position = kind
+                    .position(&default_order)
+                    .map(|(_, position)| position)
+                    .unwrap_or(PanelPosition::LeftTop);
+                let panels = panel_orders.entry(position).or_default();
                 panels.push_back(kind);
             }
         }
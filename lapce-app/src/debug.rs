@@ -1,29 +1,41 @@
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::Display,
-    path::PathBuf,
+    path::{Path, PathBuf},
     rc::Rc,
     time::Instant,
 };
 
 use floem::{
     ext_event::create_ext_action,
+    keyboard::Modifiers,
     reactive::{Memo, RwSignal, Scope},
     views::VirtualVector,
 };
+use lapce_core::{
+    command::{EditCommand, FocusCommand},
+    mode::Mode,
+};
 use lapce_rpc::{
     dap_types::{
-        self, DapId, RunDebugConfig, SourceBreakpoint, StackFrame, Stopped,
-        ThreadId, Variable,
+        self, DapId, DebuggerCapabilities, RunDebugConfig, SourceBreakpoint,
+        StackFrame, Stopped, ThreadId, Variable,
     },
-    proxy::ProxyResponse,
+    proxy::{ProxyResponse, ProxyRpcHandler},
     terminal::TermId,
 };
+use lapce_xi_rope::Rope;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    command::InternalCommand,
-    editor::location::{EditorLocation, EditorPosition},
+    command::{CommandExecuted, CommandKind, InternalCommand, LapceCommand},
+    debug_console::{ConsoleBuffer, ConsoleCategory},
+    editor::{
+        location::{EditorLocation, EditorPosition},
+        EditorData,
+    },
+    keypress::{condition::Condition, KeyPressFocus},
+    main_split::Editors,
     window_tab::CommonData,
 };
 
@@ -62,6 +74,9 @@ pub struct RunDebugData {
     pub active_term: RwSignal<Option<TermId>>,
     pub daps: RwSignal<im::HashMap<DapId, DapData>>,
     pub breakpoints: RwSignal<BTreeMap<PathBuf, BTreeMap<usize, LapceBreakpoint>>>,
+    /// What the adapters support, which is known before the sessions have
+    /// their [`DapData`]
+    pub capabilities: RwSignal<im::HashMap<DapId, DebuggerCapabilities>>,
 }
 
 impl RunDebugData {
@@ -77,36 +92,68 @@ pub fn new(
             active_term,
             daps,
             breakpoints,
+            capabilities: cx.create_rw_signal(im::HashMap::new()),
         }
     }
 
+    /// Whether the adapter of the session supports a feature, false until
+    /// it's known
+    pub fn supports(
+        &self,
+        dap_id: DapId,
+        f: impl Fn(&DebuggerCapabilities) -> Option<bool>,
+    ) -> bool {
+        self.capabilities.with_untracked(|capabilities| {
+            capabilities.get(&dap_id).and_then(f).unwrap_or(false)
+        })
+    }
+
     pub fn source_breakpoints(&self) -> HashMap<PathBuf, Vec<SourceBreakpoint>> {
         self.breakpoints
             .get_untracked()
             .iter()
             .map(|(path, breakpoints)| {
-                (
-                    path.to_path_buf(),
-                    breakpoints
-                        .iter()
-                        .filter_map(|(_, b)| {
-                            if b.active {
-                                Some(SourceBreakpoint {
-                                    line: b.line + 1,
-                                    column: None,
-                                    condition: None,
-                                    hit_condition: None,
-                                    log_message: None,
-                                })
-                            } else {
-                                None
-                            }
-                        })
-                        .collect(),
-                )
+                (path.to_path_buf(), active_source_breakpoints(breakpoints))
             })
             .collect()
     }
+
+    /// Send the breakpoints of the file to every debug session, after they
+    /// were changed.
+    pub fn sync_breakpoints(&self, proxy: &ProxyRpcHandler, path: &Path) {
+        let source_breakpoints = self.breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .get(path)
+                .map(active_source_breakpoints)
+                .unwrap_or_default()
+        });
+        let daps: Vec<DapId> = self
+            .daps
+            .with_untracked(|daps| daps.keys().cloned().collect());
+        for dap_id in daps {
+            proxy.dap_set_breakpoints(
+                dap_id,
+                path.to_path_buf(),
+                source_breakpoints.clone(),
+            );
+        }
+    }
+}
+
+fn active_source_breakpoints(
+    breakpoints: &BTreeMap<usize, LapceBreakpoint>,
+) -> Vec<SourceBreakpoint> {
+    breakpoints
+        .values()
+        .filter(|b| b.active)
+        .map(|b| SourceBreakpoint {
+            line: b.line + 1,
+            column: None,
+            condition: None,
+            hit_condition: None,
+            log_message: None,
+        })
+        .collect()
 }
 
 #[derive(Clone, PartialEq)]
@@ -114,6 +161,79 @@ pub struct StackTraceData {
     pub expanded: RwSignal<bool>,
     pub frames: RwSignal<im::Vector<StackFrame>>,
     pub frames_shown: usize,
+    /// The folded external frames that were unfolded, by their first frame
+    pub expanded_external: RwSignal<im::HashSet<usize>>,
+}
+
+/// A row in the frames of a thread
+#[derive(Clone, Debug, PartialEq)]
+pub enum StackFrameRow {
+    Frame(StackFrame),
+    /// Consecutive external frames folded together, by the id of the first
+    External {
+        first_id: usize,
+        count: usize,
+    },
+}
+
+/// Whether the frame is in code with nothing to show in the editor, like a
+/// library without its sources
+pub fn is_external_frame(frame: &StackFrame) -> bool {
+    let hidden_source = frame.source.as_ref().map_or(true, |source| {
+        source.path.is_none()
+            || source.presentation_hint.as_deref() == Some("deemphasize")
+    });
+    hidden_source || frame.presentation_hint.as_deref() == Some("subtle")
+}
+
+/// The rows of the frames of a thread, where the runs of external frames are
+/// folded unless expanded. The top frame is always shown, since it's where
+/// the thread stopped.
+pub fn stack_frame_rows(
+    frames: &im::Vector<StackFrame>,
+    expanded: &im::HashSet<usize>,
+) -> Vec<StackFrameRow> {
+    let mut rows = Vec::new();
+    let mut run: Vec<&StackFrame> = Vec::new();
+    let flush = |run: &mut Vec<&StackFrame>, rows: &mut Vec<StackFrameRow>| {
+        match run.as_slice() {
+            [] => {}
+            [frame] => rows.push(StackFrameRow::Frame((*frame).clone())),
+            [first, ..] if expanded.contains(&first.id) => {
+                rows.extend(run.iter().map(|f| StackFrameRow::Frame((*f).clone())));
+            }
+            [first, ..] => rows.push(StackFrameRow::External {
+                first_id: first.id,
+                count: run.len(),
+            }),
+        }
+        run.clear();
+    };
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 && is_external_frame(frame) {
+            run.push(frame);
+        } else {
+            flush(&mut run, &mut rows);
+            rows.push(StackFrameRow::Frame(frame.clone()));
+        }
+    }
+    flush(&mut run, &mut rows);
+    rows
+}
+
+/// Whether a thread matches the filter of the threads, by its id or the
+/// names of its frames
+pub fn thread_matches(
+    thread_id: ThreadId,
+    frames: &im::Vector<StackFrame>,
+    filter: &str,
+) -> bool {
+    let filter = filter.trim().to_lowercase();
+    filter.is_empty()
+        || thread_id.to_string().contains(&filter)
+        || frames
+            .iter()
+            .any(|frame| frame.name.to_lowercase().contains(&filter))
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -180,6 +300,14 @@ pub struct DapVariable {
     pub children_expanded_count: usize,
 }
 
+/// A breakpoint on the changes of a variable's value
+#[derive(Clone, PartialEq)]
+pub struct DapDataBreakpoint {
+    pub data_id: String,
+    pub description: String,
+    pub verified: bool,
+}
+
 #[derive(Clone)]
 pub struct DapData {
     pub term_id: TermId,
@@ -190,6 +318,11 @@ pub struct DapData {
     pub variables_id: RwSignal<usize>,
     pub variables: RwSignal<DapVariable>,
     pub breakline: Memo<Option<(usize, PathBuf)>>,
+    /// The stack frame the debug console evaluates in
+    pub frame_id: RwSignal<Option<usize>>,
+    pub console: RwSignal<ConsoleBuffer>,
+    /// The data breakpoints of the session, whose ids are only valid in it
+    pub data_breakpoints: RwSignal<im::Vector<DapDataBreakpoint>>,
     pub common: Rc<CommonData>,
 }
 
@@ -204,28 +337,32 @@ pub fn new(
         let thread_id = cx.create_rw_signal(None);
         let stack_traces: RwSignal<BTreeMap<ThreadId, StackTraceData>> =
             cx.create_rw_signal(BTreeMap::new());
+        let frame_id = cx.create_rw_signal(None);
+        // The line of the selected frame, or of the top frame of the stopped
+        // thread when none is selected
         let breakline = cx.create_memo(move |_| {
-            let thread_id = thread_id.get();
-            if let Some(thread_id) = thread_id {
-                let trace = stack_traces
-                    .with(|stack_traces| stack_traces.get(&thread_id).cloned());
-
-                if let Some(trace) = trace {
-                    let breakline = trace.frames.with(|f| {
-                        f.get(0)
-                            .and_then(|f| {
-                                f.source
-                                    .as_ref()
-                                    .map(|s| (f.line.saturating_sub(1), s))
-                            })
-                            .and_then(|(line, s)| s.path.clone().map(|p| (line, p)))
-                    });
+            let frame_line = |f: &StackFrame| {
+                f.source
+                    .as_ref()
+                    .and_then(|s| s.path.clone())
+                    .map(|p| (f.line.saturating_sub(1), p))
+            };
+            if let Some(frame_id) = frame_id.get() {
+                let selected = stack_traces.with(|stack_traces| {
+                    stack_traces.values().find_map(|trace| {
+                        trace.frames.with(|frames| {
+                            frames.iter().find(|f| f.id == frame_id).map(frame_line)
+                        })
+                    })
+                });
+                if let Some(breakline) = selected {
                     return breakline;
                 }
-                None
-            } else {
-                None
             }
+            let thread_id = thread_id.get()?;
+            let trace = stack_traces
+                .with(|stack_traces| stack_traces.get(&thread_id).cloned())?;
+            trace.frames.with(|f| f.get(0).and_then(frame_line))
         });
         Self {
             term_id,
@@ -243,6 +380,9 @@ pub fn new(
                 children_expanded_count: 0,
             }),
             breakline,
+            frame_id,
+            console: cx.create_rw_signal(ConsoleBuffer::default()),
+            data_breakpoints: cx.create_rw_signal(im::Vector::new()),
             common,
         }
     }
@@ -265,6 +405,7 @@ pub fn stopped(
         for (thread_id, frames) in stack_traces {
             let is_main_thread = main_thread_id.as_ref() == Some(thread_id);
             if is_main_thread {
+                self.frame_id.set(frames.first().map(|frame| frame.id));
                 if let Some(frame) = frames.first() {
                     if let Some(path) =
                         frame.source.as_ref().and_then(|source| source.path.clone())
@@ -297,6 +438,7 @@ pub fn stopped(
                         expanded: cx.create_rw_signal(is_main_thread),
                         frames: cx.create_rw_signal(frames.into()),
                         frames_shown: 20,
+                        expanded_external: cx.create_rw_signal(im::HashSet::new()),
                     },
                 );
             }
@@ -355,6 +497,176 @@ pub fn toggle_expand(&self, parent: Vec<usize>, reference: usize) {
         });
     }
 
+    /// Rewind the execution to the start of a frame
+    pub fn restart_frame(&self, frame_id: usize) {
+        self.common.proxy.dap_restart_frame(self.dap_id, frame_id);
+    }
+
+    /// Print an error of a request in the debug console of the session
+    fn console_error(&self, message: &str) {
+        self.console.update(|console| {
+            console.push(ConsoleCategory::Error, &format!("{message}\n"));
+        });
+    }
+
+    /// Set the value of a variable, whose parents end with its container
+    pub fn set_variable(&self, parent: Vec<usize>, name: String, value: String) {
+        let Some((&container, container_parent)) = parent.split_last() else {
+            return;
+        };
+        let container_parent = container_parent.to_vec();
+        let root = self.variables;
+        let variables_id = self.variables_id;
+        let data = self.clone();
+        let var_name = name.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            let resp = match result {
+                Ok(ProxyResponse::DapSetVariableResponse { resp }) => resp,
+                Ok(_) => return,
+                Err(err) => {
+                    data.console_error(&format!(
+                        "Failed to set {var_name}: {}",
+                        err.message
+                    ));
+                    return;
+                }
+            };
+            variables_id.update(|id| {
+                *id += 1;
+            });
+            root.update(|root| {
+                let Some(var) = root.get_var_mut(&container_parent, container)
+                else {
+                    return;
+                };
+                let Some(child) = var
+                    .children
+                    .iter_mut()
+                    .find(|child| child.item.name() == var_name)
+                else {
+                    return;
+                };
+                if let ScopeOrVar::Var(var) = &mut child.item {
+                    var.value = resp.value;
+                    if resp.ty.is_some() {
+                        var.ty = resp.ty;
+                    }
+                    if let Some(reference) = resp.variables_reference {
+                        // The children are read again when expanded
+                        if reference != var.variables_reference {
+                            var.variables_reference = reference;
+                            child.expanded = false;
+                            child.read = false;
+                            child.children.clear();
+                        }
+                    }
+                }
+                root.update_count_recursive(&container_parent, container);
+            });
+        });
+        self.common.proxy.dap_set_variable(
+            self.dap_id,
+            container,
+            name,
+            value,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Break when the value of a variable, whose parents end with its
+    /// container, changes
+    pub fn add_data_breakpoint(&self, parent: &[usize], name: String) {
+        let Some(&container) = parent.last() else {
+            return;
+        };
+        let data = self.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            let info = match result {
+                Ok(ProxyResponse::DapDataBreakpointInfoResponse { info }) => info,
+                Ok(_) => return,
+                Err(err) => {
+                    data.console_error(&err.message);
+                    return;
+                }
+            };
+            let Some(data_id) = info.data_id else {
+                data.console_error(&info.description);
+                return;
+            };
+            if data
+                .data_breakpoints
+                .with_untracked(|b| b.iter().any(|b| b.data_id == data_id))
+            {
+                return;
+            }
+            data.data_breakpoints.update(|breakpoints| {
+                breakpoints.push_back(DapDataBreakpoint {
+                    data_id,
+                    description: info.description,
+                    verified: false,
+                });
+            });
+            data.sync_data_breakpoints();
+        });
+        self.common.proxy.dap_data_breakpoint_info(
+            self.dap_id,
+            container,
+            name,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    pub fn remove_data_breakpoint(&self, data_id: &str) {
+        self.data_breakpoints.update(|breakpoints| {
+            breakpoints.retain(|b| b.data_id != data_id);
+        });
+        self.sync_data_breakpoints();
+    }
+
+    /// Send all the data breakpoints to the adapter, which replaces the ones
+    /// it had with them
+    fn sync_data_breakpoints(&self) {
+        let breakpoints = self.data_breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .iter()
+                .map(|b| dap_types::DataBreakpoint {
+                    data_id: b.data_id.clone(),
+                    access_type: Some(dap_types::DataBreakpointAccessType::Write),
+                    condition: None,
+                    hit_condition: None,
+                })
+                .collect::<Vec<_>>()
+        });
+        let data = self.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            match result {
+                Ok(ProxyResponse::DapSetDataBreakpointsResponse { breakpoints }) => {
+                    // The response is in the order of the request
+                    data.data_breakpoints.update(|data_breakpoints| {
+                        for (b, resp) in
+                            data_breakpoints.iter_mut().zip(breakpoints.iter())
+                        {
+                            b.verified = resp.verified;
+                        }
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => data.console_error(&err.message),
+            }
+        });
+        self.common.proxy.dap_set_data_breakpoints(
+            self.dap_id,
+            breakpoints,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
     fn read_var_children(&self, parent: &[usize], reference: usize) {
         let root = self.variables;
         let parent = parent.to_vec();
@@ -394,6 +706,111 @@ fn read_var_children(&self, parent: &[usize], reference: usize) {
     }
 }
 
+/// The variable whose value is being edited in the variables tree
+#[derive(Clone, PartialEq)]
+pub struct DapVariableEdit {
+    pub dap_id: DapId,
+    pub parent: Vec<usize>,
+    pub name: String,
+}
+
+#[derive(Clone)]
+pub struct DapVariableEditData {
+    pub editor: EditorData,
+    pub editing: RwSignal<Option<DapVariableEdit>>,
+    daps: RwSignal<im::HashMap<DapId, DapData>>,
+}
+
+impl KeyPressFocus for DapVariableEditData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        self.editing.with_untracked(|editing| editing.is_some())
+            && condition == Condition::ModalFocus
+    }
+
+    fn run_command(
+        &self,
+        command: &LapceCommand,
+        count: Option<usize>,
+        mods: Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Focus(FocusCommand::ModalClose) => {
+                self.cancel();
+                CommandExecuted::Yes
+            }
+            CommandKind::Edit(EditCommand::InsertNewLine) => {
+                self.finish();
+                CommandExecuted::Yes
+            }
+            CommandKind::Edit(_)
+            | CommandKind::Move(_)
+            | CommandKind::MultiSelection(_) => {
+                self.editor.run_command(command, count, mods)
+            }
+            _ => CommandExecuted::No,
+        }
+    }
+
+    fn receive_char(&self, c: &str) {
+        self.editor.receive_char(c);
+    }
+}
+
+impl DapVariableEditData {
+    pub fn new(
+        cx: Scope,
+        editors: Editors,
+        daps: RwSignal<im::HashMap<DapId, DapData>>,
+        common: Rc<CommonData>,
+    ) -> Self {
+        Self {
+            editor: editors.make_local(cx, common),
+            editing: cx.create_rw_signal(None),
+            daps,
+        }
+    }
+
+    /// Edit the value of a variable, starting from its current value
+    pub fn start(&self, edit: DapVariableEdit, value: &str) {
+        self.editor.doc().reload(Rope::from(value), true);
+        self.editor
+            .cursor()
+            .update(|cursor| cursor.set_offset(value.len(), false, false));
+        self.editing.set(Some(edit));
+    }
+
+    pub fn cancel(&self) {
+        self.editing.set(None);
+    }
+
+    /// Ask the adapter to set the variable to the edited value
+    pub fn finish(&self) {
+        let Some(edit) = self.editing.get_untracked() else {
+            return;
+        };
+        self.editing.set(None);
+        let value = self
+            .editor
+            .doc()
+            .buffer
+            .with_untracked(|buffer| buffer.to_string());
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        let dap = self
+            .daps
+            .with_untracked(|daps| daps.get(&edit.dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.set_variable(edit.parent, edit.name, value.to_string());
+        }
+    }
+}
+
 pub struct DapVariableViewdata {
     pub item: ScopeOrVar,
     pub parent: Vec<usize>,
@@ -515,9 +932,64 @@ pub fn update_count(
 
 #[cfg(test)]
 mod tests {
-    use lapce_rpc::dap_types::{Scope, Variable};
+    use std::path::PathBuf;
+
+    use lapce_rpc::dap_types::{Scope, Source, StackFrame, Variable};
+
+    use super::{stack_frame_rows, DapVariable, ScopeOrVar, StackFrameRow};
+
+    fn frame(id: usize, path: Option<&str>) -> StackFrame {
+        StackFrame {
+            id,
+            name: format!("frame{id}"),
+            source: path.map(|path| Source {
+                path: Some(PathBuf::from(path)),
+                ..Default::default()
+            }),
+            line: 1,
+            column: 1,
+            end_line: None,
+            end_column: None,
+            can_restart: None,
+            instruction_pointer_reference: None,
+            module_id: None,
+            presentation_hint: None,
+        }
+    }
 
-    use super::{DapVariable, ScopeOrVar};
+    #[test]
+    fn test_stack_frame_rows() {
+        let frames: im::Vector<StackFrame> = vec![
+            frame(1, None),
+            frame(2, Some("main.rs")),
+            frame(3, None),
+            frame(4, Some("lib.rs")),
+            frame(5, None),
+            frame(6, None),
+            frame(7, None),
+        ]
+        .into();
+
+        let rows = stack_frame_rows(&frames, &im::HashSet::new());
+        assert_eq!(
+            rows,
+            vec![
+                StackFrameRow::Frame(frame(1, None)),
+                StackFrameRow::Frame(frame(2, Some("main.rs"))),
+                StackFrameRow::Frame(frame(3, None)),
+                StackFrameRow::Frame(frame(4, Some("lib.rs"))),
+                StackFrameRow::External {
+                    first_id: 5,
+                    count: 3
+                },
+            ]
+        );
+
+        let expanded: im::HashSet<usize> = [5].into_iter().collect();
+        let rows = stack_frame_rows(&frames, &expanded);
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[6], StackFrameRow::Frame(frame(7, None)));
+    }
 
     #[test]
     fn test_update_count() {
@@ -0,0 +1,611 @@
+//! The debug console, with what the program being debugged and its adapter
+//! print in the colors of their ANSI escapes, and a prompt evaluating
+//! expressions in the selected stack frame.
+
+use std::{ops::Range, rc::Rc};
+
+use alacritty_terminal::vte::ansi::{Color as AnsiColor, Rgb};
+use floem::{
+    ext_event::create_ext_action,
+    keyboard::Modifiers,
+    reactive::{RwSignal, Scope},
+};
+use lapce_core::{
+    command::{EditCommand, MoveCommand},
+    encoding::{offset_utf16_to_utf8_str, offset_utf8_to_utf16_str},
+    mode::Mode,
+};
+use lapce_rpc::{
+    dap_types::CompletionItem, proxy::ProxyResponse, rich_output::RichOutput,
+};
+use lapce_xi_rope::Rope;
+
+use crate::{
+    command::{CommandExecuted, CommandKind, LapceCommand},
+    editor::EditorData,
+    keypress::{condition::Condition, KeyPressFocus},
+    main_split::Editors,
+    terminal::panel::TerminalPanelData,
+    window_tab::CommonData,
+};
+
+/// How many lines the console of a session keeps
+const MAX_LINES: usize = 10_000;
+/// How many evaluated expressions are remembered
+const MAX_HISTORY: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConsoleCategory {
+    Stdout,
+    Stderr,
+    /// What the adapter itself prints
+    Console,
+    /// An expression typed at the prompt
+    Input,
+    Result,
+    Error,
+}
+
+impl ConsoleCategory {
+    /// The category of an output event of the adapter
+    pub fn from_output(category: Option<&str>) -> Self {
+        match category {
+            Some("stdout") => ConsoleCategory::Stdout,
+            Some("stderr") => ConsoleCategory::Stderr,
+            _ => ConsoleCategory::Console,
+        }
+    }
+
+    /// The category the lines are filtered by, the evaluations going along
+    /// with what the adapter prints
+    pub fn filter(&self) -> ConsoleCategory {
+        match self {
+            ConsoleCategory::Stdout => ConsoleCategory::Stdout,
+            ConsoleCategory::Stderr => ConsoleCategory::Stderr,
+            _ => ConsoleCategory::Console,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnsiStyle {
+    pub fg: Option<AnsiColor>,
+    pub bold: bool,
+}
+
+/// A run of text with a style other than the default
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnsiSpan {
+    pub range: Range<usize>,
+    pub style: AnsiStyle,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleLine {
+    pub id: usize,
+    pub category: ConsoleCategory,
+    /// The text without the escapes
+    pub text: String,
+    pub spans: Vec<AnsiSpan>,
+    /// A table or an image shown in place of the text
+    pub rich: Option<Rc<RichOutput>>,
+}
+
+/// The lines of the console of a debug session. The output comes in pieces
+/// which don't have to end at the end of a line.
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleBuffer {
+    pub lines: im::Vector<ConsoleLine>,
+    /// The category of the last output, and whether its line is still open
+    last: Option<(ConsoleCategory, bool)>,
+    /// The style the escapes of the last output left on
+    style: AnsiStyle,
+    next_id: usize,
+}
+
+impl ConsoleBuffer {
+    pub fn push(&mut self, category: ConsoleCategory, text: &str) {
+        if self.last.map(|(last, _)| last) != Some(category) {
+            self.style = AnsiStyle::default();
+        }
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (line, ended) = match rest.find('\n') {
+                Some(i) => {
+                    let line = &rest[..i];
+                    rest = &rest[i + 1..];
+                    (line, true)
+                }
+                None => {
+                    let line = rest;
+                    rest = "";
+                    (line, false)
+                }
+            };
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let (text, spans) = parse_ansi(line, &mut self.style);
+
+            let open = self.last == Some((category, true));
+            match self.lines.back_mut() {
+                Some(last) if open => {
+                    let offset = last.text.len();
+                    last.text.push_str(&text);
+                    last.spans.extend(spans.into_iter().map(|span| AnsiSpan {
+                        range: span.range.start + offset..span.range.end + offset,
+                        style: span.style,
+                    }));
+                }
+                _ => {
+                    self.lines.push_back(ConsoleLine {
+                        id: self.next_id,
+                        category,
+                        text,
+                        spans,
+                        rich: None,
+                    });
+                    self.next_id += 1;
+                }
+            }
+            self.last = Some((category, !ended));
+        }
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Add a table or an image on a line of its own, the output that is
+    /// text being added like the rest of the text
+    pub fn push_rich(&mut self, category: ConsoleCategory, output: RichOutput) {
+        match output {
+            RichOutput::Text { text } | RichOutput::Ansi { text } => {
+                self.push(category, &text);
+            }
+            output => {
+                self.style = AnsiStyle::default();
+                self.lines.push_back(ConsoleLine {
+                    id: self.next_id,
+                    category,
+                    text: output.plain_text(),
+                    spans: Vec::new(),
+                    rich: Some(Rc::new(output)),
+                });
+                self.next_id += 1;
+                self.last = Some((category, false));
+                while self.lines.len() > MAX_LINES {
+                    self.lines.pop_front();
+                }
+            }
+        }
+    }
+}
+
+/// Remove the escapes from `text`, returning the spans of the colors they
+/// set. `style` is the one the previous text left on, and is updated.
+pub fn parse_ansi(text: &str, style: &mut AnsiStyle) -> (String, Vec<AnsiSpan>) {
+    let mut out = String::with_capacity(text.len());
+    let mut spans: Vec<AnsiSpan> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            if c == 'm' {
+                                apply_sgr(&params, style);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // Titles and the like, up to BEL or ST
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if c.is_control() && c != '\t' {
+            continue;
+        }
+
+        let start = out.len();
+        out.push(c);
+        if *style == AnsiStyle::default() {
+            continue;
+        }
+        match spans.last_mut() {
+            Some(span) if span.style == *style && span.range.end == start => {
+                span.range.end = out.len();
+            }
+            _ => spans.push(AnsiSpan {
+                range: start..out.len(),
+                style: *style,
+            }),
+        }
+    }
+    (out, spans)
+}
+
+/// Apply the parameters of a Select Graphic Rendition escape. Only the
+/// foreground and boldness are kept.
+fn apply_sgr(params: &str, style: &mut AnsiStyle) {
+    let mut params = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+    while let Some(param) = params.next() {
+        match param {
+            0 => *style = AnsiStyle::default(),
+            1 => style.bold = true,
+            22 => style.bold = false,
+            30..=37 => style.fg = Some(AnsiColor::Indexed(param - 30)),
+            39 => style.fg = None,
+            90..=97 => style.fg = Some(AnsiColor::Indexed(param - 90 + 8)),
+            38 => match params.next() {
+                Some(5) => {
+                    style.fg = params.next().map(AnsiColor::Indexed);
+                }
+                Some(2) => {
+                    let mut next = || params.next().unwrap_or(0);
+                    style.fg = Some(AnsiColor::Spec(Rgb {
+                        r: next(),
+                        g: next(),
+                        b: next(),
+                    }));
+                }
+                _ => {}
+            },
+            // The background isn't shown, but its parameters are skipped
+            48 => match params.next() {
+                Some(5) => {
+                    params.next();
+                }
+                Some(2) => {
+                    params.by_ref().take(3).for_each(drop);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// The text with the completion in place of what it completes, and the
+/// offset after it
+fn complete_text(
+    text: &str,
+    offset: usize,
+    item: &CompletionItem,
+) -> (String, usize) {
+    let offset = offset.min(text.len());
+    let insert = item.text.as_deref().unwrap_or(&item.label);
+    let start = match item.start {
+        // Counted from 1 like the column of the request
+        Some(start) => offset_utf16_to_utf8_str(text, start.saturating_sub(1)),
+        None => text[..offset]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0),
+    }
+    .min(offset);
+    let end = match item.length {
+        Some(length) => offset_utf16_to_utf8_str(
+            text,
+            offset_utf8_to_utf16_str(text, start) + length,
+        ),
+        None => offset,
+    }
+    .max(start);
+    let mut new_text = String::with_capacity(text.len() + insert.len());
+    new_text.push_str(&text[..start]);
+    new_text.push_str(insert);
+    new_text.push_str(&text[end..]);
+    (new_text, start + insert.len())
+}
+
+#[derive(Clone)]
+pub struct DebugConsoleData {
+    pub editor: EditorData,
+    /// The categories whose lines are hidden
+    pub hidden: RwSignal<im::HashSet<ConsoleCategory>>,
+    /// The candidates of the last completion, when there were several
+    pub completions: RwSignal<Vec<CompletionItem>>,
+    history: RwSignal<Vec<String>>,
+    /// Where Up and Down went in the history, `None` at the prompt
+    history_index: RwSignal<Option<usize>>,
+    terminal: TerminalPanelData,
+    pub common: Rc<CommonData>,
+}
+
+impl KeyPressFocus for DebugConsoleData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        matches!(condition, Condition::PanelFocus)
+    }
+
+    fn run_command(
+        &self,
+        command: &LapceCommand,
+        count: Option<usize>,
+        mods: Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Edit(EditCommand::InsertNewLine) => {
+                self.evaluate();
+                CommandExecuted::Yes
+            }
+            CommandKind::Edit(EditCommand::InsertTab) => {
+                self.complete();
+                CommandExecuted::Yes
+            }
+            CommandKind::Move(MoveCommand::Up) => {
+                self.history_step(true);
+                CommandExecuted::Yes
+            }
+            CommandKind::Move(MoveCommand::Down) => {
+                self.history_step(false);
+                CommandExecuted::Yes
+            }
+            CommandKind::Edit(_)
+            | CommandKind::Move(_)
+            | CommandKind::MultiSelection(_) => {
+                self.completions.set(Vec::new());
+                self.editor.run_command(command, count, mods)
+            }
+            _ => CommandExecuted::No,
+        }
+    }
+
+    fn receive_char(&self, c: &str) {
+        self.completions.set(Vec::new());
+        self.editor.receive_char(c);
+    }
+}
+
+impl DebugConsoleData {
+    pub fn new(
+        cx: Scope,
+        editors: Editors,
+        terminal: TerminalPanelData,
+        common: Rc<CommonData>,
+    ) -> Self {
+        Self {
+            editor: editors.make_local(cx, common.clone()),
+            hidden: cx.create_rw_signal(im::HashSet::new()),
+            completions: cx.create_rw_signal(Vec::new()),
+            history: cx.create_rw_signal(Vec::new()),
+            history_index: cx.create_rw_signal(None),
+            terminal,
+            common,
+        }
+    }
+
+    fn text(&self) -> String {
+        self.editor
+            .doc()
+            .buffer
+            .with_untracked(|buffer| buffer.to_string())
+    }
+
+    fn set_text(&self, text: &str, offset: usize) {
+        self.editor.doc().reload(Rope::from(text), true);
+        self.editor
+            .cursor()
+            .update(|cursor| cursor.set_offset(offset, false, false));
+    }
+
+    /// Evaluate the expression at the prompt in the frame selected in the
+    /// active debug session, printing it and its value in the console.
+    pub fn evaluate(&self) {
+        let expression = self.text().trim().to_string();
+        if expression.is_empty() {
+            return;
+        }
+        let Some(dap) = self.terminal.get_active_dap(false) else {
+            return;
+        };
+
+        self.editor.reset();
+        self.completions.set(Vec::new());
+        self.history_index.set(None);
+        self.history.update(|history| {
+            history.retain(|e| e != &expression);
+            history.push(expression.clone());
+            if history.len() > MAX_HISTORY {
+                history.remove(0);
+            }
+        });
+
+        let console = dap.console;
+        console.update(|console| {
+            console.push(ConsoleCategory::Input, &format!("> {expression}\n"));
+        });
+        let send =
+            create_ext_action(self.common.scope, move |result| match result {
+                Ok(ProxyResponse::DapEvaluateResponse { result }) => {
+                    console.update(|console| {
+                        console.push(
+                            ConsoleCategory::Result,
+                            &format!("{}\n", result.result),
+                        );
+                    });
+                }
+                Err(err) => {
+                    console.update(|console| {
+                        console.push(
+                            ConsoleCategory::Error,
+                            &format!("{}\n", err.message),
+                        );
+                    });
+                }
+                Ok(_) => {}
+            });
+        self.common.proxy.dap_evaluate(
+            dap.dap_id,
+            dap.frame_id.get_untracked(),
+            expression,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Ask the adapter to complete the expression at the cursor. A single
+    /// candidate is inserted right away, several are offered to pick from.
+    pub fn complete(&self) {
+        let Some(dap) = self.terminal.get_active_dap(false) else {
+            return;
+        };
+        let text = self.text();
+        let offset = self.editor.cursor().with_untracked(|c| c.offset());
+        let column = offset_utf8_to_utf16_str(&text, offset) + 1;
+
+        let console = self.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            let Ok(ProxyResponse::DapCompletionsResponse { targets }) = result
+            else {
+                return;
+            };
+            match targets.as_slice() {
+                [] => {}
+                [item] => console.apply_completion(item),
+                _ => console.completions.set(targets),
+            }
+        });
+        self.common.proxy.dap_completions(
+            dap.dap_id,
+            dap.frame_id.get_untracked(),
+            text,
+            column,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    pub fn apply_completion(&self, item: &CompletionItem) {
+        let text = self.text();
+        let offset = self.editor.cursor().with_untracked(|c| c.offset());
+        let (text, offset) = complete_text(&text, offset, item);
+        self.set_text(&text, offset);
+        self.completions.set(Vec::new());
+    }
+
+    /// Go back or forward in the history of the evaluated expressions
+    fn history_step(&self, back: bool) {
+        let len = self.history.with_untracked(|history| history.len());
+        let index = match (self.history_index.get_untracked(), back) {
+            (None, true) if len > 0 => Some(len - 1),
+            (None, _) => return,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.history_index.set(index);
+        let text = index
+            .and_then(|i| {
+                self.history
+                    .with_untracked(|history| history.get(i).cloned())
+            })
+            .unwrap_or_default();
+        self.set_text(&text, text.len());
+    }
+
+    pub fn toggle_category(&self, category: ConsoleCategory) {
+        self.hidden.update(|hidden| {
+            if hidden.remove(&category).is_none() {
+                hidden.insert(category);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::vte::ansi::Color as AnsiColor;
+    use lapce_rpc::{dap_types::CompletionItem, rich_output::RichOutput};
+
+    use super::{
+        complete_text, parse_ansi, AnsiStyle, ConsoleBuffer, ConsoleCategory,
+    };
+
+    #[test]
+    fn test_parse_ansi() {
+        let mut style = AnsiStyle::default();
+        let (text, spans) =
+            parse_ansi("ok \x1b[1;31merror\x1b[0m done\x1b]0;t\x07", &mut style);
+        assert_eq!(text, "ok error done");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].range, 3..8);
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(1)));
+        assert!(spans[0].style.bold);
+        assert_eq!(style, AnsiStyle::default());
+
+        // The style carries over to the next output
+        parse_ansi("\x1b[92m", &mut style);
+        let (_, spans) = parse_ansi("green", &mut style);
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(10)));
+    }
+
+    #[test]
+    fn test_console_buffer() {
+        let mut buffer = ConsoleBuffer::default();
+        buffer.push(ConsoleCategory::Stdout, "one\ntw");
+        buffer.push(ConsoleCategory::Stdout, "o\r\n");
+        buffer.push(ConsoleCategory::Stderr, "three");
+        buffer.push(ConsoleCategory::Stdout, "four\n");
+        buffer.push_rich(
+            ConsoleCategory::Stdout,
+            RichOutput::Image {
+                mime: "image/png".to_string(),
+                data: String::new(),
+            },
+        );
+        buffer.push(ConsoleCategory::Stdout, "five\n");
+        let lines: Vec<_> = buffer
+            .lines
+            .iter()
+            .map(|line| (line.category, line.text.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (ConsoleCategory::Stdout, "one"),
+                (ConsoleCategory::Stdout, "two"),
+                (ConsoleCategory::Stderr, "three"),
+                (ConsoleCategory::Stdout, "four"),
+                (ConsoleCategory::Stdout, "[image/png]"),
+                (ConsoleCategory::Stdout, "five"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_complete_text() {
+        let item = |label: &str, start: Option<usize>| CompletionItem {
+            label: label.to_string(),
+            start,
+            ..Default::default()
+        };
+        assert_eq!(
+            complete_text("self.ve", 7, &item("vec", None)),
+            ("self.vec".to_string(), 8)
+        );
+        assert_eq!(
+            complete_text("self.ve x", 7, &item("self.vec", Some(1))),
+            ("self.vec x".to_string(), 8)
+        );
+    }
+}
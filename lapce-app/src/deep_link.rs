@@ -0,0 +1,147 @@
+//! `lapce://` links, which let other programs such as browsers and issue
+//! trackers point at a location in a file, e.g.
+//! `lapce://open?path=/home/me/project/src/main.rs&line=12&column=5`.
+//!
+//! The scheme is registered by the Linux desktop entry and the Windows
+//! installer to run `lapce --open-url <url>`.
+
+use std::path::{Component, PathBuf};
+
+use anyhow::{anyhow, Result};
+use lapce_rpc::file::{LineCol, PathObject};
+use url::Url;
+
+pub const SCHEME: &str = "lapce";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeepLink {
+    /// An absolute path to a file
+    pub path: PathBuf,
+    /// One based
+    pub line: Option<usize>,
+    /// One based
+    pub column: Option<usize>,
+    /// The workspace folder to open the file in, if it isn't in an open
+    /// workspace already
+    pub workspace: Option<PathBuf>,
+}
+
+impl DeepLink {
+    pub fn parse(url: &str) -> Result<Self> {
+        let url = Url::parse(url)?;
+        if url.scheme() != SCHEME {
+            return Err(anyhow!("not a {SCHEME}:// link"));
+        }
+        if url.host_str() != Some("open") {
+            return Err(anyhow!("unsupported action {:?}", url.host_str()));
+        }
+
+        let mut path = None;
+        let mut line = None;
+        let mut column = None;
+        let mut workspace = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "path" => path = Some(PathBuf::from(value.as_ref())),
+                "line" => line = Some(value.parse()?),
+                "column" => column = Some(value.parse()?),
+                "workspace" => workspace = Some(PathBuf::from(value.as_ref())),
+                _ => {}
+            }
+        }
+
+        let path = path.ok_or_else(|| anyhow!("the link has no path"))?;
+        // Relative paths would depend on where Lapce happens to be running
+        if !path.is_absolute() {
+            return Err(anyhow!("{} is not an absolute path", path.display()));
+        }
+        // `..` would make the workspace check below meaningless
+        if path.components().any(|c| c == Component::ParentDir) {
+            return Err(anyhow!("{} is not a normalized path", path.display()));
+        }
+        if let Some(workspace) = &workspace {
+            if !workspace.is_absolute() || !path.starts_with(workspace) {
+                return Err(anyhow!(
+                    "{} is not a folder containing {}",
+                    workspace.display(),
+                    path.display()
+                ));
+            }
+        }
+
+        Ok(Self {
+            path,
+            line,
+            column,
+            workspace,
+        })
+    }
+
+    /// The workspace to open when the file isn't in an open one already
+    pub fn workspace_folder(&self) -> Option<PathBuf> {
+        self.workspace
+            .clone()
+            .or_else(|| self.path.parent().map(|p| p.to_path_buf()))
+    }
+
+    pub fn path_object(&self) -> PathObject {
+        PathObject {
+            path: self.path.clone(),
+            linecol: self.line.map(|line| LineCol {
+                line,
+                column: self.column.unwrap_or(1),
+            }),
+            is_dir: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::DeepLink;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_parse() {
+        let link = DeepLink::parse(
+            "lapce://open?path=/home/me/project/src/main%20file.rs&line=12&column=5",
+        )
+        .unwrap();
+        assert_eq!(
+            link.path,
+            PathBuf::from("/home/me/project/src/main file.rs")
+        );
+        assert_eq!(link.line, Some(12));
+        assert_eq!(link.column, Some(5));
+        assert_eq!(
+            link.workspace_folder(),
+            Some(PathBuf::from("/home/me/project/src"))
+        );
+
+        let link = DeepLink::parse(
+            "lapce://open?path=/home/me/project/src/main.rs&workspace=/home/me/project",
+        )
+        .unwrap();
+        assert_eq!(link.line, None);
+        assert_eq!(
+            link.workspace_folder(),
+            Some(PathBuf::from("/home/me/project"))
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_parse_rejects() {
+        assert!(DeepLink::parse("https://open?path=/a.rs").is_err());
+        assert!(DeepLink::parse("lapce://run?path=/a.rs").is_err());
+        assert!(DeepLink::parse("lapce://open?line=1").is_err());
+        assert!(DeepLink::parse("lapce://open?path=a.rs").is_err());
+        assert!(DeepLink::parse("lapce://open?path=/a.rs&line=x").is_err());
+        assert!(DeepLink::parse("lapce://open?path=/a/b.rs&workspace=/c").is_err());
+        assert!(
+            DeepLink::parse("lapce://open?path=/a/../c/b.rs&workspace=/a").is_err()
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Workspaces inside a Docker or Podman container, set up from the
+//! `devcontainer.json` of a folder. The folder is mounted into the container,
+//! which is kept running in the background with the proxy inside it.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::{
+    proxy::new_command,
+    workspace::{ContainerHost, LapceWorkspace, LapceWorkspaceType},
+};
+
+/// Where the config is looked for in the folder, in order
+const CONFIG_FILES: &[&str] =
+    &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// The subset of the dev container config that is supported.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DevContainerConfig {
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub build: Option<BuildConfig>,
+    /// Where the folder is mounted in the container
+    pub workspace_folder: Option<String>,
+    /// Extra mounts, in the `--mount` syntax such as
+    /// `source=cache,target=/cache,type=volume`
+    pub mounts: Vec<String>,
+    pub run_args: Vec<String>,
+    pub container_env: HashMap<String, String>,
+    /// The user the container runs as
+    pub container_user: Option<String>,
+    /// The user the proxy and terminals run as
+    pub remote_user: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BuildConfig {
+    /// Relative to the config file
+    pub dockerfile: Option<String>,
+    /// Relative to the config file, its directory by default
+    pub context: Option<String>,
+    pub args: HashMap<String, String>,
+}
+
+impl DevContainerConfig {
+    /// The config of `folder`, and the file it was read from.
+    pub fn load(folder: &Path) -> Result<(PathBuf, Self)> {
+        let path = CONFIG_FILES
+            .iter()
+            .map(|file| folder.join(file))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} has no .devcontainer/devcontainer.json",
+                    folder.display()
+                )
+            })?;
+        let text = std::fs::read_to_string(&path)?;
+        let config = serde_json::from_str(&strip_jsonc(&text))
+            .map_err(|e| anyhow!("Invalid {}: {e}", path.display()))?;
+        Ok((path, config))
+    }
+
+    /// Where `folder` is mounted in the container, `/workspaces/<folder name>`
+    /// unless the config says otherwise.
+    pub fn workspace_folder(&self, folder: &Path) -> String {
+        self.workspace_folder.clone().unwrap_or_else(|| {
+            let name = folder
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "workspace".to_string());
+            format!("/workspaces/{name}")
+        })
+    }
+}
+
+/// The workspace of `folder` opened in its dev container. The container
+/// itself is started along with the proxy.
+pub fn container_workspace(folder: &Path) -> Result<LapceWorkspace> {
+    let (_, config) = DevContainerConfig::load(folder)?;
+    let host = ContainerHost {
+        engine: container_engine(),
+        name: container_name(config.name.as_deref(), folder),
+        folder: folder.to_path_buf(),
+        user: config.remote_user.clone().or(config.container_user.clone()),
+    };
+    Ok(LapceWorkspace {
+        kind: LapceWorkspaceType::RemoteContainer(host),
+        path: Some(PathBuf::from(config.workspace_folder(folder))),
+        last_open: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    })
+}
+
+/// Start the container, creating it from the config of its folder if it
+/// doesn't exist yet.
+pub fn start(host: &ContainerHost) -> Result<()> {
+    match container_running(host) {
+        Some(true) => return Ok(()),
+        Some(false) => return engine(host, &["start", &host.name]),
+        None => {}
+    }
+
+    let (config_path, config) = DevContainerConfig::load(&host.folder)?;
+    let config_dir = config_path.parent().unwrap_or(&host.folder);
+    let image = match (&config.build, &config.image) {
+        (Some(build), _) => {
+            let tag = format!("{}-image", host.name);
+            engine(host, &build_args(build, config_dir, &tag))?;
+            tag
+        }
+        (None, Some(image)) => image.clone(),
+        (None, None) => {
+            return Err(anyhow!(
+                "{} has neither an image nor a build",
+                config_path.display()
+            ))
+        }
+    };
+    engine(host, &run_args(&config, host, &image))
+}
+
+/// Stop the container, keeping it to be started again.
+pub fn stop(host: &ContainerHost) -> Result<()> {
+    engine(host, &["stop", &host.name])
+}
+
+/// Remove the container, so that it is created again from the config the
+/// next time it's opened.
+pub fn remove(host: &ContainerHost) -> Result<()> {
+    engine(host, &["rm", "-f", &host.name])
+}
+
+/// Whether the container is running, or `None` if it doesn't exist
+fn container_running(host: &ContainerHost) -> Option<bool> {
+    let output = new_command(&host.engine)
+        .args(["inspect", "-f", "{{.State.Running}}", &host.name])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+fn engine<S: AsRef<std::ffi::OsStr>>(
+    host: &ContainerHost,
+    args: &[S],
+) -> Result<()> {
+    let output = new_command(&host.engine).args(args).output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed: {}",
+            host.engine,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Docker if it's installed, otherwise Podman.
+fn container_engine() -> String {
+    let docker = new_command("docker")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if docker { "docker" } else { "podman" }.to_string()
+}
+
+/// A container name for the folder, from the name in its config or the name
+/// of the folder.
+fn container_name(name: Option<&str>, folder: &Path) -> String {
+    let name = name
+        .map(|name| name.to_string())
+        .or_else(|| {
+            folder
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("lapce-{}", name.trim_matches('-'))
+}
+
+fn build_args(build: &BuildConfig, config_dir: &Path, tag: &str) -> Vec<String> {
+    let mut args = vec!["build".to_string(), "-t".to_string(), tag.to_string()];
+    if let Some(dockerfile) = &build.dockerfile {
+        args.push("-f".to_string());
+        args.push(config_dir.join(dockerfile).to_string_lossy().to_string());
+    }
+    let mut build_args: Vec<_> = build.args.iter().collect();
+    build_args.sort();
+    for (key, value) in build_args {
+        args.push("--build-arg".to_string());
+        args.push(format!("{key}={value}"));
+    }
+    let context = config_dir.join(build.context.as_deref().unwrap_or("."));
+    args.push(context.to_string_lossy().to_string());
+    args
+}
+
+/// The arguments creating the container, which idles so that the proxy and
+/// terminals can be run in it.
+fn run_args(
+    config: &DevContainerConfig,
+    host: &ContainerHost,
+    image: &str,
+) -> Vec<String> {
+    let mut args = vec![
+        "run".to_string(),
+        "-d".to_string(),
+        "--name".to_string(),
+        host.name.clone(),
+        "-v".to_string(),
+        format!(
+            "{}:{}",
+            host.folder.to_string_lossy(),
+            config.workspace_folder(&host.folder)
+        ),
+    ];
+    for mount in &config.mounts {
+        args.push("--mount".to_string());
+        args.push(mount.clone());
+    }
+    let mut env: Vec<_> = config.container_env.iter().collect();
+    env.sort();
+    for (key, value) in env {
+        args.push("-e".to_string());
+        args.push(format!("{key}={value}"));
+    }
+    if let Some(user) = &config.container_user {
+        args.push("-u".to_string());
+        args.push(user.clone());
+    }
+    args.extend(config.run_args.iter().cloned());
+    args.extend([
+        "--entrypoint".to_string(),
+        "/bin/sh".to_string(),
+        image.to_string(),
+        "-c".to_string(),
+        "while sleep 1000; do :; done".to_string(),
+    ]);
+    args
+}
+
+/// The config is JSON with comments and trailing commas, which are removed
+/// here. Strings are left as they are.
+fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push(c);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '}' | ']' => {
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{container_name, run_args, strip_jsonc, DevContainerConfig};
+    use crate::workspace::ContainerHost;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let text = r#"{
+            // The image
+            "image": "rust:1", /* inline */
+            "runArgs": ["--init", "//not a comment",],
+        }"#;
+        let config: DevContainerConfig =
+            serde_json::from_str(&strip_jsonc(text)).unwrap();
+        assert_eq!(config.image.as_deref(), Some("rust:1"));
+        assert_eq!(config.run_args, vec!["--init", "//not a comment"]);
+    }
+
+    #[test]
+    fn test_run_args() {
+        let folder = PathBuf::from("/home/me/My Project");
+        assert_eq!(
+            container_name(None, &folder),
+            "lapce-my-project".to_string()
+        );
+
+        let config = DevContainerConfig {
+            mounts: vec!["source=cache,target=/cache,type=volume".to_string()],
+            run_args: vec!["--init".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(config.workspace_folder(&folder), "/workspaces/My Project");
+        let host = ContainerHost {
+            engine: "docker".to_string(),
+            name: "lapce-my-project".to_string(),
+            folder,
+            user: None,
+        };
+        let args = run_args(&config, &host, "rust:1");
+        assert_eq!(
+            args[..6],
+            [
+                "run",
+                "-d",
+                "--name",
+                "lapce-my-project",
+                "-v",
+                "/home/me/My Project:/workspaces/My Project",
+            ]
+        );
+        assert!(args.contains(&"--init".to_string()));
+        let image = args.iter().position(|arg| arg == "rust:1").unwrap();
+        assert_eq!(args[image - 1], "/bin/sh");
+    }
+}
@@ -0,0 +1,193 @@
+//! A zip of the state of Lapce to attach to a bug report: the open files, the
+//! settings changed from the defaults, the plugins, the language servers, the
+//! performance counters and the recent logs. The paths of the workspace and
+//! of the home directory are replaced by placeholders in all of it.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use lapce_core::{directory::Directory, meta::VERSION};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+/// The number of most recent log files included
+const LOG_FILES: usize = 2;
+/// The number of lines kept from the end of each log file
+const LOG_LINES: usize = 2000;
+
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticBundle {
+    pub workspace: Option<PathBuf>,
+    pub open_files: Vec<PathBuf>,
+    /// The settings and keymaps files of the user, the profile and the
+    /// workspace, which only hold what differs from the defaults
+    pub config_files: Vec<PathBuf>,
+    pub plugins: Vec<String>,
+    pub language_servers: Vec<String>,
+    pub perf: String,
+}
+
+impl DiagnosticBundle {
+    /// Write the bundle to a zip file, reading the config files and the logs
+    pub fn write_zip(&self, path: &Path) -> Result<()> {
+        let anonymizer = Anonymizer::new(
+            self.workspace.as_deref(),
+            Directory::home_dir().as_deref(),
+        );
+        let mut zip = ZipWriter::new(std::fs::File::create(path)?);
+        let options =
+            FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut add = |name: &str, content: &str| -> Result<()> {
+            zip.start_file(name, options)?;
+            zip.write_all(anonymizer.apply(content).as_bytes())?;
+            Ok(())
+        };
+
+        add("summary.txt", &self.summary())?;
+        add("config.toml", &self.read_config_files())?;
+        for (name, content) in recent_logs() {
+            add(&format!("logs/{name}"), &content)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        let list = |items: &[String]| {
+            if items.is_empty() {
+                "  (none)".to_string()
+            } else {
+                items
+                    .iter()
+                    .map(|item| format!("  {item}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        };
+        let open_files: Vec<String> = self
+            .open_files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let perf = self
+            .perf
+            .lines()
+            .map(|line| format!("  {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "Lapce: {VERSION}\nOS: {} {}\n\nOpen files:\n{}\n\nPlugins:\n{}\n\n\
+             Language servers:\n{}\n\nPerformance:\n{perf}\n",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            list(&open_files),
+            list(&self.plugins),
+            list(&self.language_servers),
+        )
+    }
+
+    fn read_config_files(&self) -> String {
+        self.config_files
+            .iter()
+            .filter_map(|path| {
+                let content = std::fs::read_to_string(path).ok()?;
+                Some(format!("# {}\n{}\n", path.display(), content.trim_end()))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// The end of the most recent log files, by file name
+fn recent_logs() -> Vec<(String, String)> {
+    let Some(dir) = Directory::logs_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with("lapce.") && name.ends_with(".log")
+                })
+        })
+        .collect();
+    // The names end with the date
+    files.sort();
+    files
+        .iter()
+        .rev()
+        .take(LOG_FILES)
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            let content = std::fs::read(path).ok()?;
+            let content = String::from_utf8_lossy(&content);
+            let lines: Vec<&str> = content.lines().collect();
+            let start = lines.len().saturating_sub(LOG_LINES);
+            Some((name, lines[start..].join("\n")))
+        })
+        .collect()
+}
+
+/// Replaces the paths of the workspace and of the home directory in text
+struct Anonymizer {
+    replacements: Vec<(String, &'static str)>,
+}
+
+impl Anonymizer {
+    fn new(workspace: Option<&Path>, home: Option<&Path>) -> Self {
+        // The workspace is usually in the home directory, so it goes first
+        let replacements = [(workspace, "<workspace>"), (home, "~")]
+            .into_iter()
+            .filter_map(|(path, placeholder)| {
+                let path = path?
+                    .to_string_lossy()
+                    .trim_end_matches(['/', '\\'])
+                    .to_string();
+                (!path.is_empty()).then_some((path, placeholder))
+            })
+            .collect();
+        Self { replacements }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (path, placeholder) in &self.replacements {
+            text = text.replace(path.as_str(), placeholder);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::Anonymizer;
+
+    #[test]
+    fn test_anonymizer() {
+        let anonymizer = Anonymizer::new(
+            Some(Path::new("/home/jane/code/app/")),
+            Some(Path::new("/home/jane")),
+        );
+        assert_eq!(
+            anonymizer.apply(
+                "open /home/jane/code/app/src/main.rs\nread /home/jane/.config/x"
+            ),
+            "open <workspace>/src/main.rs\nread ~/.config/x"
+        );
+
+        // The root isn't a path to hide
+        let anonymizer = Anonymizer::new(None, Some(Path::new("/")));
+        assert_eq!(anonymizer.apply("/usr/bin"), "/usr/bin");
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     borrow::Cow,
     cell::RefCell,
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     ops::Range,
     path::{Path, PathBuf},
     rc::Rc,
@@ -9,7 +9,7 @@
         atomic::{self, AtomicUsize},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use floem::{
@@ -17,8 +17,8 @@
     cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout},
     ext_event::create_ext_action,
     keyboard::Modifiers,
-    peniko::Color,
-    reactive::{batch, ReadSignal, RwSignal, Scope},
+    peniko::{kurbo::Vec2, Color},
+    reactive::{batch, Memo, ReadSignal, RwSignal, Scope},
     views::editor::{
         actions::CommonAction,
         command::{Command, CommandExecuted},
@@ -33,9 +33,10 @@
 };
 use itertools::Itertools;
 use lapce_core::{
+    auto_pairs,
     buffer::{
         diff::{rope_diff, DiffLines},
-        rope_text::RopeText,
+        rope_text::{RopeText, RopeTextRef},
         Buffer, InvalLines,
     },
     char_buffer::CharBuffer,
@@ -51,9 +52,11 @@
     selection::{InsertDrift, Selection},
     style::line_styles,
     syntax::{edit::SyntaxEdit, BracketParser, Syntax},
+    util::matching_bracket_general,
     word::{get_char_property, CharClassification, WordCursor},
 };
 use lapce_rpc::{
+    automation::AutomationEvent,
     buffer::BufferId,
     plugin::PluginId,
     proxy::ProxyResponse,
@@ -67,21 +70,43 @@
     CodeActionResponse, Diagnostic, DiagnosticSeverity, InlayHint, InlayHintLabel,
 };
 use serde::{Deserialize, Serialize};
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 
 use crate::{
+    automation,
+    blame::BlameData,
     command::{CommandKind, LapceCommand},
-    config::{color::LapceColor, LapceConfig},
+    config::{
+        color::LapceColor,
+        editor::InlayHintDisplay,
+        language::LanguageEditorConfig,
+        validate::{ConfigFileKind, ConfigProblem},
+        LapceConfig,
+    },
+    document_highlight::DocumentHighlights,
     editor::{compute_screen_lines, EditorData},
+    editor_event::EditorEvent,
+    error_report::ErrorReport,
     find::{Find, FindProgress, FindResult},
+    font_warmup,
     history::DocumentHistory,
+    json_tree::{parse_json_tree, JsonTree},
     keypress::KeyPressFocus,
+    log_mode::{is_log_file, LogModeData},
     main_split::Editors,
     panel::kind::PanelKind,
+    perf::{self, PerfSubsystem},
     window_tab::{CommonData, Focus},
     workspace::LapceWorkspace,
 };
 
+/// How many lines before and after those an editor shows have their injected
+/// languages parsed
+const INJECTION_MARGIN_LINES: usize = 300;
+/// Past this length, a file changed on disk is reloaded as a whole rather
+/// than diffed, as the diff runs on the UI thread
+const RELOAD_DIFF_MAX_LEN: usize = 4 * 1024 * 1024;
+
 #[derive(Clone, Debug)]
 pub struct DiagnosticData {
     pub expanded: RwSignal<bool>,
@@ -160,11 +185,20 @@ pub struct Doc {
     pub cache_rev: RwSignal<u64>,
     /// Whether the buffer's content has been loaded/initialized into the buffer.
     pub loaded: RwSignal<bool>,
+    /// The bytes read of the file and its size, while it is being loaded
+    pub load_progress: RwSignal<Option<(u64, u64)>>,
     pub buffer: RwSignal<Buffer>,
     pub syntax: RwSignal<Syntax>,
+    /// The language of the syntax, tracked apart from its reparses
+    language: Memo<LapceLanguage>,
     semantic_styles: RwSignal<Option<Spans<Style>>>,
     /// Inlay hints for the document
     pub inlay_hints: RwSignal<Option<Spans<InlayHint>>>,
+    /// The line under the pointer, whose inlay hints are displayed when they
+    /// are displayed on hover only
+    pub inlay_hint_hover_line: RwSignal<Option<usize>>,
+    /// The occurrences of the symbol under the cursor
+    pub document_highlights: RwSignal<Option<DocumentHighlights>>,
     /// Current completion lens text, if any.
     /// This will be displayed even on views that are not focused.
     pub completion_lens: RwSignal<Option<String>>,
@@ -183,6 +217,13 @@ pub struct Doc {
     /// Stores information about different versions of the document from source control.
     histories: RwSignal<im::HashMap<String, DocumentHistory>>,
     pub head_changes: RwSignal<im::Vector<DiffLines>>,
+    /// The blame of the `head` version, kept while the blame heat map is on
+    pub blame: RwSignal<Option<Rc<BlameData>>>,
+    /// The log mode, on by default for the files ending in `.log`
+    pub log: LogModeData,
+    /// The tree of the values of a JSON document, `None` for the other
+    /// languages
+    pub json_tree: RwSignal<Option<Rc<JsonTree>>>,
 
     line_styles: Rc<RefCell<LineStyles>>,
     pub parser: Rc<RefCell<BracketParser>>,
@@ -208,13 +249,16 @@ pub fn new(
         editors: Editors,
         common: Rc<CommonData>,
     ) -> Self {
-        let syntax = Syntax::init(&path);
+        let syntax =
+            cx.create_rw_signal(Syntax::from_language(common.language_of(&path)));
         let config = common.config.get_untracked();
+        let log = LogModeData::new(cx, is_log_file(&path), common.config);
         Doc {
             scope: cx,
             buffer_id: BufferId::next(),
             buffer: cx.create_rw_signal(Buffer::new("")),
-            syntax: cx.create_rw_signal(syntax),
+            syntax,
+            language: language_memo(cx, syntax),
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             parser: Rc::new(RefCell::new(BracketParser::new(
                 String::new(),
@@ -224,6 +268,8 @@ pub fn new(
             semantic_styles: cx.create_rw_signal(None),
             inlay_hints: cx.create_rw_signal(None),
             diagnostics,
+            inlay_hint_hover_line: cx.create_rw_signal(None),
+            document_highlights: cx.create_rw_signal(None),
             completion_lens: cx.create_rw_signal(None),
             completion_pos: cx.create_rw_signal((0, 0)),
             inline_completion: cx.create_rw_signal(None),
@@ -234,8 +280,12 @@ pub fn new(
                 read_only: false,
             }),
             loaded: cx.create_rw_signal(false),
+            load_progress: cx.create_rw_signal(None),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
+            blame: cx.create_rw_signal(None),
+            log,
+            json_tree: cx.create_rw_signal(None),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             code_actions: cx.create_rw_signal(im::HashMap::new()),
             find_result: FindResult::new(cx),
@@ -257,11 +307,13 @@ pub fn new_content(
     ) -> Doc {
         let cx = cx.create_child();
         let config = common.config.get_untracked();
+        let syntax = cx.create_rw_signal(Syntax::plaintext());
         Self {
             scope: cx,
             buffer_id: BufferId::next(),
             buffer: cx.create_rw_signal(Buffer::new("")),
-            syntax: cx.create_rw_signal(Syntax::plaintext()),
+            syntax,
+            language: language_memo(cx, syntax),
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             parser: Rc::new(RefCell::new(BracketParser::new(
                 String::new(),
@@ -275,6 +327,8 @@ pub fn new_content(
                 diagnostics: cx.create_rw_signal(im::Vector::new()),
                 diagnostics_span: cx.create_rw_signal(SpansBuilder::new(0).build()),
             },
+            inlay_hint_hover_line: cx.create_rw_signal(None),
+            document_highlights: cx.create_rw_signal(None),
             completion_lens: cx.create_rw_signal(None),
             completion_pos: cx.create_rw_signal((0, 0)),
             inline_completion: cx.create_rw_signal(None),
@@ -283,8 +337,12 @@ pub fn new_content(
             content: cx.create_rw_signal(content),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
+            blame: cx.create_rw_signal(None),
+            log: LogModeData::new(cx, false, common.config),
+            json_tree: cx.create_rw_signal(None),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             loaded: cx.create_rw_signal(true),
+            load_progress: cx.create_rw_signal(None),
             find_result: FindResult::new(cx),
             code_actions: cx.create_rw_signal(im::HashMap::new()),
             preedit: PreeditData::new(cx),
@@ -301,15 +359,17 @@ pub fn new_history(
     ) -> Doc {
         let config = common.config.get_untracked();
         let syntax = if let DocContent::History(history) = &content {
-            Syntax::init(&history.path)
+            Syntax::from_language(common.language_of(&history.path))
         } else {
             Syntax::plaintext()
         };
+        let syntax = cx.create_rw_signal(syntax);
         Self {
             scope: cx,
             buffer_id: BufferId::next(),
             buffer: cx.create_rw_signal(Buffer::new("")),
-            syntax: cx.create_rw_signal(syntax),
+            syntax,
+            language: language_memo(cx, syntax),
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             parser: Rc::new(RefCell::new(BracketParser::new(
                 String::new(),
@@ -323,6 +383,8 @@ pub fn new_history(
                 diagnostics: cx.create_rw_signal(im::Vector::new()),
                 diagnostics_span: cx.create_rw_signal(SpansBuilder::new(0).build()),
             },
+            inlay_hint_hover_line: cx.create_rw_signal(None),
+            document_highlights: cx.create_rw_signal(None),
             completion_lens: cx.create_rw_signal(None),
             completion_pos: cx.create_rw_signal((0, 0)),
             inline_completion: cx.create_rw_signal(None),
@@ -331,8 +393,12 @@ pub fn new_history(
             content: cx.create_rw_signal(content),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             loaded: cx.create_rw_signal(true),
+            load_progress: cx.create_rw_signal(None),
             histories: cx.create_rw_signal(im::HashMap::new()),
             head_changes: cx.create_rw_signal(im::Vector::new()),
+            blame: cx.create_rw_signal(None),
+            log: LogModeData::new(cx, false, common.config),
+            json_tree: cx.create_rw_signal(None),
             code_actions: cx.create_rw_signal(im::HashMap::new()),
             find_result: FindResult::new(cx),
             preedit: PreeditData::new(cx),
@@ -368,7 +434,11 @@ pub fn create_editor(
             blink_interval: Rc::new(move || config.editor.blink_interval()),
             blink_timer: common.window_common.cursor_blink_timer,
             hidden: common.window_common.hide_cursor,
-            should_blink: Rc::new(should_blink(common.focus, common.keyboard_focus)),
+            should_blink: Rc::new(should_blink(
+                common.focus,
+                common.keyboard_focus,
+                common.window_common.focused,
+            )),
         };
         let mut editor =
             Editor::new_direct(cx, id, self.clone(), self.styling(), modal);
@@ -390,7 +460,16 @@ pub fn syntax(&self) -> ReadSignal<Syntax> {
         self.syntax.read_only()
     }
 
-    pub fn set_syntax(&self, syntax: Syntax) {
+    /// The editor settings for the document's language.
+    pub fn editor_config(&self) -> LanguageEditorConfig {
+        let language = self.language.get();
+        self.common
+            .config
+            .with_untracked(|config| config.editor_for(language))
+    }
+
+    pub fn set_syntax(&self, mut syntax: Syntax) {
+        syntax.injection_range = self.injection_range();
         batch(|| {
             self.syntax.set(syntax);
             if self.semantic_styles.with_untracked(|s| s.is_none()) {
@@ -402,7 +481,15 @@ pub fn set_syntax(&self, syntax: Syntax) {
 
     /// Set the syntax highlighting this document should use.
     pub fn set_language(&self, language: LapceLanguage) {
-        self.syntax.set(Syntax::from_language(language));
+        let mut syntax = Syntax::from_language(language);
+        // What the editors show stays the same
+        syntax.injection_range = self.injection_range();
+        self.syntax.set(syntax);
+    }
+
+    fn injection_range(&self) -> Option<Range<usize>> {
+        self.syntax
+            .with_untracked(|syntax| syntax.injection_range.clone())
     }
 
     pub fn find(&self) -> &Find {
@@ -414,8 +501,15 @@ pub fn loaded(&self) -> bool {
         self.loaded.get_untracked()
     }
 
+    /// Whether the buffer can be edited, which it can't be until it is
+    /// loaded, as the content would replace the edits
+    fn editable(&self) -> bool {
+        self.loaded() && !self.content.with_untracked(|c| c.read_only())
+    }
+
     //// Initialize the content with some text, this marks the document as loaded.
     pub fn init_content(&self, content: Rope) {
+        font_warmup::note_text(&content);
         batch(|| {
             self.syntax.with_untracked(|syntax| {
                 self.buffer.update(|buffer| {
@@ -426,6 +520,7 @@ pub fn init_content(&self, content: Rope) {
                 });
             });
             self.loaded.set(true);
+            self.load_progress.set(None);
             self.on_update(None);
             self.init_parser();
             self.init_diagnostics();
@@ -465,35 +560,176 @@ pub fn reload(&self, content: Rope, set_pristine: bool) {
     }
 
     pub fn handle_file_changed(&self, content: Rope) {
-        if self.is_pristine() {
-            self.reload(content, true);
+        if !self.is_pristine() {
+            return;
+        }
+        // Only the lines that changed are edited, for the cursors and the
+        // scroll of the editors to stay where they were
+        if let Some(edits) = self.reload_edits(&content) {
+            if edits.is_empty() {
+                return;
+            }
+            let edits: Vec<_> = edits
+                .iter()
+                .map(|(selection, text)| (selection, text.as_str()))
+                .collect();
+            let old_text = self.buffer.with_untracked(|b| b.text().clone());
+            if let Some((_, delta, _)) = self.do_raw_edit(&edits, EditType::Other) {
+                self.buffer.update(|buffer| buffer.set_pristine());
+                self.remap_editors(&old_text, &delta);
+                return;
+            }
+        }
+        self.reload(content, true);
+    }
+
+    /// The edits turning the text into `content`, from their line diff, or
+    /// `None` when it is too long to be diffed here or most of it changed
+    fn reload_edits(&self, content: &Rope) -> Option<Vec<(Selection, String)>> {
+        let (text, rev, atomic_rev) = self
+            .buffer
+            .with_untracked(|b| (b.text().clone(), b.rev(), b.atomic_rev()));
+        if text.len().max(content.len()) > RELOAD_DIFF_MAX_LEN {
+            return None;
+        }
+        let changes =
+            rope_diff(text.clone(), content.clone(), rev, atomic_rev, None)?;
+        let changes = changed_lines(&changes);
+
+        let old_text = RopeTextRef::new(&text);
+        let new_text = RopeTextRef::new(content);
+        let lines = old_text.last_line().max(new_text.last_line()) + 1;
+        let changed: usize = changes
+            .iter()
+            .map(|(old, new)| old.len().max(new.len()))
+            .sum();
+        if changed * 2 > lines {
+            return None;
+        }
+
+        let edits = changes
+            .into_iter()
+            .map(|(old, new)| {
+                let start = old_text.offset_of_line(old.start);
+                let end = old_text.offset_of_line(old.end);
+                let new = new_text.offset_of_line(new.start)
+                    ..new_text.offset_of_line(new.end);
+                (
+                    Selection::region(start, end),
+                    content.slice_to_cow(new).to_string(),
+                )
+            })
+            .collect();
+        Some(edits)
+    }
+
+    /// Move the cursors of the editors of the doc through `delta`, and scroll
+    /// them by the lines added or removed above what they show
+    fn remap_editors(&self, old_text: &Rope, delta: &RopeDelta) {
+        let line_height =
+            self.common.config.get_untracked().editor.line_height() as f64;
+        let text = self.buffer.with_untracked(|b| b.text().clone());
+        let (old_text, text) = (RopeTextRef::new(old_text), RopeTextRef::new(&text));
+        let editors: Vec<EditorData> = self.editors.0.with_untracked(|editors| {
+            editors
+                .values()
+                .filter(|editor| editor.doc().buffer_id == self.buffer_id)
+                .cloned()
+                .collect()
+        });
+        for editor in editors {
+            editor.cursor().update(|cursor| cursor.apply_delta(delta));
+
+            let origin = editor.viewport().get_untracked().origin();
+            let top_line = (origin.y / line_height) as usize;
+            let offset = old_text.offset_of_line(top_line);
+            let offset = Transformer::new(delta).transform(offset, false);
+            let shift = text.line_of_offset(offset) as f64 - top_line as f64;
+            if shift != 0.0 {
+                editor.scroll_to().set(Some(Vec2::new(
+                    origin.x,
+                    (origin.y + shift * line_height).max(0.0),
+                )));
+            }
         }
     }
 
+    /// Append the text appended to the file since the proxy had `offset` of it,
+    /// which is what a log growing needs rather than a reload of all of it
+    pub fn handle_file_appended(&self, offset: usize, content: &str) {
+        if !self.is_pristine() {
+            return;
+        }
+        let (len, known) = self.buffer.with_untracked(|buffer| {
+            let len = buffer.len();
+            let known = (offset <= len)
+                .then(|| buffer.text().slice_to_cow(offset..len).to_string());
+            (len, known)
+        });
+        // A change racing the update of the proxy can send some of the text
+        // appended before again
+        let Some(appended) =
+            known.and_then(|known| content.strip_prefix(known.as_str()))
+        else {
+            return;
+        };
+        if appended.is_empty() {
+            return;
+        }
+        self.do_raw_edit(&[(Selection::caret(len), appended)], EditType::Other);
+        self.buffer.update(|buffer| buffer.set_pristine());
+    }
+
     pub fn do_insert(
         &self,
         cursor: &mut Cursor,
         s: &str,
         config: &LapceConfig,
     ) -> Vec<(Rope, RopeDelta, InvalLines)> {
-        if self.content.with_untracked(|c| c.read_only()) {
+        if !self.editable() {
             return Vec::new();
         }
 
         let old_cursor = cursor.mode.clone();
         let deltas = self.syntax.with_untracked(|syntax| {
+            let pairs = syntax.language.auto_pairs();
+            let mut auto_closing = config.editor.auto_closing_matching_pairs;
+            let mut auto_surround = config.editor.auto_surround;
+            let mut chars = s.chars();
+            let c = chars.next().filter(|_| chars.next().is_none());
+            // The editor core pairs brackets and quotes whatever the
+            // language, so leave them alone where the language doesn't pair
+            // them
+            if let Some(c) = c {
+                if auto_pairs::is_builtin(c) && !auto_pairs::is_paired(pairs, c) {
+                    auto_closing = false;
+                    auto_surround = false;
+                }
+            }
             self.buffer
                 .try_update(|buffer| {
-                    Action::insert(
-                        cursor,
-                        buffer,
-                        s,
-                        &|buffer, c, offset| {
-                            syntax_prev_unmatched(buffer, syntax, c, offset)
-                        },
-                        config.editor.auto_closing_matching_pairs,
-                        config.editor.auto_surround,
-                    )
+                    c.and_then(|c| {
+                        auto_pairs::insert(
+                            cursor,
+                            buffer,
+                            c,
+                            pairs,
+                            auto_closing,
+                            auto_surround,
+                        )
+                    })
+                    .unwrap_or_else(|| {
+                        Action::insert(
+                            cursor,
+                            buffer,
+                            s,
+                            &|buffer, c, offset| {
+                                syntax_prev_unmatched(buffer, syntax, c, offset)
+                            },
+                            auto_closing,
+                            auto_surround,
+                        )
+                    })
                 })
                 .unwrap()
         });
@@ -511,7 +747,7 @@ pub fn do_raw_edit(
         edits: &[(impl AsRef<Selection>, &str)],
         edit_type: EditType,
     ) -> Option<(Rope, RopeDelta, InvalLines)> {
-        if self.content.with_untracked(|c| c.read_only()) {
+        if !self.editable() {
             return None;
         }
 
@@ -531,9 +767,7 @@ pub fn do_edit(
         register: &mut Register,
         smart_tab: bool,
     ) -> Vec<(Rope, RopeDelta, InvalLines)> {
-        if self.content.with_untracked(|c| c.read_only())
-            && !cmd.not_changing_buffer()
-        {
+        if !self.editable() && !cmd.not_changing_buffer() {
             return Vec::new();
         }
 
@@ -580,6 +814,7 @@ pub fn apply_deltas(&self, deltas: &[(Rope, RopeDelta, InvalLines)]) {
                 self.update_diagnostics(delta);
                 self.update_completion_lens(delta);
                 self.update_find_result(delta);
+                self.update_head_changes(inval);
                 if let DocContent::File { path, .. } = self.content.get_untracked() {
                     self.update_breakpoints(delta, &path, &inval.old_text);
                     self.common.proxy.update(
@@ -622,6 +857,8 @@ pub fn line_ending(&self) -> LineEnding {
     fn on_update(&self, edits: Option<SmallVec<[SyntaxEdit; 3]>>) {
         batch(|| {
             self.trigger_syntax_change(edits);
+            self.trigger_json_tree();
+            self.trigger_config_check();
             self.trigger_head_change();
             self.check_auto_save();
             self.get_inlay_hints();
@@ -653,6 +890,23 @@ fn do_bracket_colorization(&self) {
         }
     }
 
+    /// Apply changed bracket colorization settings from the config.
+    pub fn set_bracket_colorization(&self, active: bool, limit: u64) {
+        {
+            let mut parser = self.parser.borrow_mut();
+            if parser.active == active && parser.limit == limit {
+                return;
+            }
+            parser.active = active;
+            parser.limit = limit;
+            if !active {
+                parser.bracket_pos.clear();
+            }
+        }
+        self.do_bracket_colorization();
+        self.clear_text_cache();
+    }
+
     fn check_auto_save(&self) {
         let config = self.common.config.get_untracked();
         if config.editor.autosave_interval > 0 {
@@ -709,28 +963,181 @@ fn update_inlay_hints(&self, delta: &RopeDelta) {
         });
     }
 
+    /// Have the injected languages, such as the code blocks of markdown,
+    /// parsed around `lines`, which an editor shows, if they aren't already.
+    /// Only the injections around what the editors show are parsed, so that
+    /// documents with many of them stay fast to highlight.
+    pub fn parse_injections_around(&self, lines: Range<usize>) {
+        let (start, end) = self.buffer.with_untracked(|buffer| {
+            (
+                buffer.offset_of_line(lines.start),
+                buffer.offset_of_line(lines.end),
+            )
+        });
+        let parsed = self.syntax.with_untracked(|syntax| {
+            syntax.layers.is_none()
+                || syntax
+                    .injection_range
+                    .as_ref()
+                    .is_some_and(|range| range.start <= start && end <= range.end)
+        });
+        if parsed {
+            return;
+        }
+
+        let range = self.buffer.with_untracked(|buffer| {
+            buffer.offset_of_line(lines.start.saturating_sub(INJECTION_MARGIN_LINES))
+                ..buffer.offset_of_line(lines.end + INJECTION_MARGIN_LINES)
+        });
+        self.syntax
+            .update(|syntax| syntax.injection_range = Some(range));
+        // Nothing changed, so the trees of the layers are all reused
+        self.trigger_syntax_change(Some(SmallVec::new()));
+    }
+
     pub fn trigger_syntax_change(&self, edits: Option<SmallVec<[SyntaxEdit; 3]>>) {
         let (rev, text) =
             self.buffer.with_untracked(|b| (b.rev(), b.text().clone()));
 
         let doc = self.clone();
-        let send = create_ext_action(self.scope, move |syntax| {
-            if doc.buffer.with_untracked(|b| b.rev()) == rev {
-                doc.syntax.set(syntax);
-                doc.do_bracket_colorization();
-                doc.clear_style_cache();
-                doc.clear_sticky_headers_cache();
-            }
-        });
+        let send = create_ext_action(
+            self.scope,
+            move |(syntax, chunks): (Syntax, Vec<Range<usize>>)| {
+                if doc.buffer.with_untracked(|b| b.rev()) == rev {
+                    let cancel_flag = syntax.cancel_flag.clone();
+                    doc.syntax.set(syntax);
+                    doc.do_bracket_colorization();
+                    doc.clear_style_cache();
+                    doc.clear_sticky_headers_cache();
+                    doc.highlight_chunks(rev, cancel_flag, chunks.into());
+                }
+            },
+        );
 
         self.syntax.update(|syntax| {
             syntax.cancel_flag.store(1, atomic::Ordering::Relaxed);
             syntax.cancel_flag = Arc::new(AtomicUsize::new(0));
         });
         let mut syntax = self.syntax.get_untracked();
+        // No injection is parsed until an editor shows it, see
+        // `parse_injections_around`
+        syntax.injection_range.get_or_insert(0..0);
+        rayon::spawn(move || {
+            let chunks = syntax.parse_tree(rev, text, edits.as_deref());
+            send((syntax, chunks));
+        });
+    }
+
+    /// Highlight the chunks of the text given by `Syntax::parse_tree` one
+    /// after the other in the background, each one showing as soon as it's
+    /// done, until the document is edited or parsed again
+    fn highlight_chunks(
+        &self,
+        rev: u64,
+        cancel_flag: Arc<AtomicUsize>,
+        mut chunks: VecDeque<Range<usize>>,
+    ) {
+        let Some(range) = chunks.pop_front() else {
+            return;
+        };
+        let syntax = self.syntax.get_untracked();
+
+        let doc = self.clone();
+        let flag = cancel_flag.clone();
+        let chunk = range.clone();
+        let send = create_ext_action(self.scope, move |spans| {
+            if doc.buffer.with_untracked(|b| b.rev()) != rev
+                || flag.load(atomic::Ordering::Relaxed) == 1
+            {
+                return;
+            }
+            doc.syntax
+                .update(|syntax| syntax.set_highlights(chunk, spans));
+            doc.clear_style_cache();
+            doc.highlight_chunks(rev, flag, chunks);
+        });
+        rayon::spawn(move || {
+            if cancel_flag.load(atomic::Ordering::Relaxed) == 1 {
+                return;
+            }
+            send(syntax.highlight(range));
+        });
+    }
+
+    /// Parse a JSON document in the background, the tree of its last valid
+    /// version being kept along with the error while it is invalid
+    fn trigger_json_tree(&self) {
+        let language = self.syntax.with_untracked(|syntax| syntax.language);
+        if language != LapceLanguage::Json {
+            if self.json_tree.with_untracked(|tree| tree.is_some()) {
+                self.json_tree.set(None);
+            }
+            return;
+        }
+
+        let (rev, text) =
+            self.buffer.with_untracked(|b| (b.rev(), b.text().clone()));
+        let doc = self.clone();
+        let send = create_ext_action(self.scope, move |result| {
+            if doc.buffer.with_untracked(|b| b.rev()) != rev {
+                return;
+            }
+            let tree = match result {
+                Ok(root) => JsonTree {
+                    root: Some(Rc::new(root)),
+                    error: None,
+                },
+                Err(error) => JsonTree {
+                    root: doc
+                        .json_tree
+                        .with_untracked(|tree| tree.as_ref()?.root.clone()),
+                    error: Some(error),
+                },
+            };
+            doc.json_tree.set(Some(Rc::new(tree)));
+        });
+        rayon::spawn(move || {
+            send(parse_json_tree(&text.to_string()));
+        });
+    }
+
+    /// Check the document in the background if it's one of Lapce's settings,
+    /// keymaps or color theme files, its problems becoming its diagnostics
+    fn trigger_config_check(&self) {
+        let Some(kind) = self.content.with_untracked(|content| {
+            content.path().and_then(|path| ConfigFileKind::of(path))
+        }) else {
+            return;
+        };
+
+        let (rev, text) =
+            self.buffer.with_untracked(|b| (b.rev(), b.text().clone()));
+        let doc = self.clone();
+        let send =
+            create_ext_action(self.scope, move |problems: Vec<ConfigProblem>| {
+                if doc.buffer.with_untracked(|b| b.rev()) != rev {
+                    return;
+                }
+                let diagnostics = doc.buffer.with_untracked(|buffer| {
+                    problems
+                        .into_iter()
+                        .map(|problem| Diagnostic {
+                            range: lsp_types::Range::new(
+                                buffer.offset_to_position(problem.range.start),
+                                buffer.offset_to_position(problem.range.end),
+                            ),
+                            severity: Some(problem.severity),
+                            source: Some("lapce".to_string()),
+                            message: problem.message,
+                            ..Default::default()
+                        })
+                        .collect()
+                });
+                doc.diagnostics.diagnostics.set(diagnostics);
+                doc.init_diagnostics();
+            });
         rayon::spawn(move || {
-            syntax.parse(rev, text, edits.as_deref());
-            send(syntax);
+            send(kind.check(&text.to_string()));
         });
     }
 
@@ -745,6 +1152,16 @@ fn clear_code_actions(&self) {
         });
     }
 
+    /// Turn the log mode on or off, with the following of the end of the file
+    pub fn toggle_log_mode(&self) {
+        let enabled = !self.log.enabled.get_untracked();
+        batch(|| {
+            self.log.enabled.set(enabled);
+            self.log.follow.set(enabled);
+        });
+        self.clear_text_cache();
+    }
+
     /// Inform any dependents on this document that they should clear any cached text.
     pub fn clear_text_cache(&self) {
         self.cache_rev.try_update(|cache_rev| {
@@ -771,6 +1188,38 @@ fn styles(&self) -> Option<Spans<Style>> {
         }
     }
 
+    /// Whether `line` is longer than the long line length of the config, so
+    /// that it is laid out without syntax colors and inlay hints
+    pub fn is_long_line(&self, line: usize) -> bool {
+        let max_len = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.long_line_length);
+        max_len > 0
+            && self.buffer.with_untracked(|buffer| {
+                buffer.offset_of_line(line + 1) - buffer.offset_of_line(line)
+            }) > max_len
+    }
+
+    /// Whether any line is longer than the long line length of the config,
+    /// checked once the document is loaded rather than on each edit
+    pub fn has_long_lines(&self) -> bool {
+        if !self.loaded.get() {
+            return false;
+        }
+        let max_len = self
+            .common
+            .config
+            .with(|config| config.editor.long_line_length);
+        max_len > 0
+            && self.buffer.with_untracked(|buffer| {
+                buffer
+                    .text()
+                    .lines_raw(0..buffer.len())
+                    .any(|line| line.len() > max_len)
+            })
+    }
+
     /// Get the style information for the particular line from semantic/syntax highlighting.
     /// This caches the result if possible.
     pub fn line_style(&self, line: usize) -> Arc<Vec<LineStyle>> {
@@ -818,7 +1267,9 @@ fn get_semantic_styles(&self) {
             }
         });
 
+        let start = Instant::now();
         self.common.proxy.get_semantic_tokens(path, move |result| {
+            perf::record(PerfSubsystem::Lsp, start.elapsed());
             if let Ok(ProxyResponse::GetSemanticTokens { styles }) = result {
                 if atomic_rev.load(atomic::Ordering::Acquire) != rev {
                     send(None);
@@ -936,6 +1387,16 @@ pub fn completion_lens(&self) -> Option<String> {
         self.completion_lens.get_untracked()
     }
 
+    /// Set the line under the pointer, for its inlay hints to be displayed
+    /// when they are displayed on hover only
+    pub fn set_inlay_hint_hover_line(&self, line: Option<usize>) {
+        if self.inlay_hint_hover_line.get_untracked() != line {
+            self.inlay_hint_hover_line.set(line);
+            // TODO: more granular invalidation
+            self.clear_text_cache();
+        }
+    }
+
     pub fn set_completion_lens(
         &self,
         completion_lens: String,
@@ -1033,6 +1494,26 @@ fn update_find_result(&self, delta: &RopeDelta) {
         })
     }
 
+    /// Move the changes since `head` through an edit, so that their markers
+    /// stay next to their lines until they are diffed again
+    fn update_head_changes(&self, inval: &InvalLines) {
+        if inval.inval_count == inval.new_count
+            || self
+                .head_changes
+                .with_untracked(|changes| changes.is_empty())
+        {
+            return;
+        }
+        self.head_changes.update(|changes| {
+            shift_diff_lines(
+                changes,
+                inval.start_line,
+                inval.inval_count,
+                inval.new_count,
+            );
+        });
+    }
+
     pub fn update_find(&self) {
         let find_rev = self.common.find.rev.get_untracked();
         if self.find_result.find_rev.get_untracked() != find_rev {
@@ -1128,6 +1609,156 @@ pub fn head_changes(&self) -> RwSignal<im::Vector<DiffLines>> {
         self.head_changes
     }
 
+    /// The lines of the `head` version that lines `start..=end` of the buffer
+    /// come from, or `None` when they were all added since. Without the head
+    /// changes, the lines are taken to be unchanged.
+    pub fn head_lines(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        self.head_changes.with_untracked(|changes| {
+            if changes.is_empty() {
+                return Some((start, end));
+            }
+            changes
+                .iter()
+                .filter_map(|change| match change {
+                    DiffLines::Both(info) => {
+                        let from = start.max(info.right.start);
+                        let to = (end + 1).min(info.right.end);
+                        (from < to).then(|| {
+                            (
+                                info.left.start + from - info.right.start,
+                                info.left.start + to - 1 - info.right.start,
+                            )
+                        })
+                    }
+                    _ => None,
+                })
+                .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+        })
+    }
+
+    /// The change since `head` at `line` of the buffer, with the text of the
+    /// lines of `head` it replaced
+    pub fn head_hunk(&self, line: usize) -> Option<HeadHunk> {
+        let changes = self
+            .head_changes
+            .with(|changes| changed_lines(&changes.iter().cloned().collect_vec()));
+        let (old, new) = hunk_at_line(&changes, line)?;
+        let head = self.histories.with_untracked(|histories| {
+            histories
+                .get("head")
+                .map(|history| history.buffer.text().clone())
+        })?;
+        let head_text = RopeTextRef::new(&head);
+        let old_text = head
+            .slice_to_cow(
+                head_text.offset_of_line(old.start)
+                    ..head_text.offset_of_line(old.end),
+            )
+            .to_string();
+        Some(HeadHunk { old, new, old_text })
+    }
+
+    /// Undo a change since `head`, putting back the lines it replaced
+    pub fn revert_head_hunk(&self, hunk: &HeadHunk) {
+        let selection = self.buffer.with_untracked(|buffer| {
+            Selection::region(
+                buffer.offset_of_line(hunk.new.start),
+                buffer.offset_of_line(hunk.new.end),
+            )
+        });
+        self.do_raw_edit(&[(selection, hunk.old_text.as_str())], EditType::Other);
+    }
+
+    /// Stage a change since `head` alone, leaving the other changes of the
+    /// file unstaged. What was staged of the file before is replaced.
+    pub fn stage_head_hunk(&self, hunk: &HeadHunk) {
+        let Some(path) = self.content.with_untracked(|c| c.path().cloned()) else {
+            return;
+        };
+        let Some(head) = self.histories.with_untracked(|histories| {
+            histories
+                .get("head")
+                .map(|history| history.buffer.text().clone())
+        }) else {
+            return;
+        };
+        let new_text = self.buffer.with_untracked(|buffer| {
+            buffer
+                .text()
+                .slice_to_cow(
+                    buffer.offset_of_line(hunk.new.start)
+                        ..buffer.offset_of_line(hunk.new.end),
+                )
+                .to_string()
+        });
+        let head_text = RopeTextRef::new(&head);
+        let start = head_text.offset_of_line(hunk.old.start);
+        let end = head_text.offset_of_line(hunk.old.end);
+        let content = format!(
+            "{}{new_text}{}",
+            head.slice_to_cow(0..start),
+            head.slice_to_cow(end..head.len())
+        );
+        self.common.proxy.git_stage_content(path, content);
+    }
+
+    /// Load the content of a history doc from git, at the version it is of
+    pub fn load_history(&self) {
+        let DocContent::History(history) = self.content.get_untracked() else {
+            return;
+        };
+        let send = {
+            let doc = self.clone();
+            create_ext_action(self.scope, move |result| {
+                if let Ok(ProxyResponse::BufferHeadResponse { content, .. }) = result
+                {
+                    doc.init_content(Rope::from(content));
+                }
+            })
+        };
+        if history.version == "head" {
+            self.common
+                .proxy
+                .get_buffer_head(history.path, move |result| {
+                    send(result);
+                });
+        } else {
+            self.common.proxy.get_buffer_version(
+                history.path,
+                history.version,
+                move |result| {
+                    send(result);
+                },
+            );
+        }
+    }
+
+    /// Retrieve the blame of the `head` version of the file, for the blame
+    /// heat map
+    pub fn retrieve_blame(&self) {
+        let DocContent::File { path, .. } = self.content.get_untracked() else {
+            return;
+        };
+        if !self.common.config.get_untracked().editor.blame_heat_map {
+            if self.blame.with_untracked(Option::is_some) {
+                self.blame.set(None);
+            }
+            return;
+        }
+
+        let blame = self.blame;
+        let send = create_ext_action(self.scope, move |result| match result {
+            Ok(ProxyResponse::GitBlame { blame: info }) => {
+                blame.set(Some(Rc::new(BlameData::new(info))));
+            }
+            // Not in a repository, or not committed yet
+            _ => blame.set(None),
+        });
+        self.common.proxy.git_blame(path, move |result| {
+            send(result);
+        });
+    }
+
     /// Retrieve the `head` version of the buffer
     pub fn retrieve_head(&self) {
         if let DocContent::File { path, .. } = self.content.get_untracked() {
@@ -1162,6 +1793,8 @@ pub fn retrieve_head(&self) {
                     send(result);
                 });
             });
+            // The blame changes with `head`
+            self.retrieve_blame();
         }
     }
 
@@ -1213,16 +1846,40 @@ pub fn save(&self, after_action: impl FnOnce() + 'static) {
         if let DocContent::File { path, .. } = content {
             let rev = self.rev();
             let buffer = self.buffer;
-            let send = create_ext_action(self.scope, move |result| {
-                if let Ok(ProxyResponse::SaveResponse {}) = result {
+            let saved_path = path.clone();
+            let common = self.common.clone();
+            let send = create_ext_action(self.scope, move |result| match result {
+                Ok(ProxyResponse::SaveResponse {}) => {
                     let current_rev = buffer.with_untracked(|buffer| buffer.rev());
                     if current_rev == rev {
                         buffer.update(|buffer| {
                             buffer.set_pristine();
                         });
                         after_action();
+                        common.editor_events.send(EditorEvent::DocumentSaved {
+                            path: saved_path.clone(),
+                        });
+                        automation::emit(AutomationEvent::FileSaved {
+                            path: saved_path,
+                        });
                     }
                 }
+                Ok(_) => {}
+                Err(err) => {
+                    common.report_error(
+                        ErrorReport::new(
+                            "Save",
+                            format!(
+                                "Failed to save {}: {}",
+                                saved_path.display(),
+                                err.message
+                            ),
+                        )
+                        .path(saved_path)
+                        .rev(rev)
+                        .command("save"),
+                    );
+                }
             });
 
             self.common.proxy.save(rev, path, true, move |result| {
@@ -1316,6 +1973,26 @@ pub fn find_enclosing_brackets(&self, offset: usize) -> Option<(usize, usize)> {
                 })
             })
     }
+
+    /// Find the bracket right after the offset, or else right before it, and
+    /// the bracket that it matches. The offsets are in order.
+    pub fn find_adjacent_brackets(&self, offset: usize) -> Option<(usize, usize)> {
+        let candidates = [Some(offset), offset.checked_sub(1)];
+        candidates.into_iter().flatten().find_map(|bracket| {
+            let is_bracket = self.buffer.with_untracked(|buffer| {
+                bracket < buffer.len()
+                    && matching_bracket_general::<char>(
+                        buffer.text().byte_at(bracket) as char,
+                    )
+                    .is_some()
+            });
+            if !is_bracket {
+                return None;
+            }
+            let other = Document::find_matching_pair(self, bracket);
+            (other != bracket).then(|| (bracket.min(other), bracket.max(other)))
+        })
+    }
 }
 impl Document for Doc {
     fn text(&self) -> Rope {
@@ -1377,15 +2054,17 @@ fn compute_screen_lines(
             };
         };
 
-        compute_screen_lines(
-            self.common.config,
-            base,
-            editor_data.kind.read_only(),
-            &editor_data.doc_signal().get(),
-            editor.lines(),
-            editor.text_prov(),
-            editor.config_id(),
-        )
+        perf::time(PerfSubsystem::ScreenLines, || {
+            compute_screen_lines(
+                self.common.config,
+                base,
+                editor_data.kind.read_only(),
+                &editor_data.doc_signal().get(),
+                editor.lines(),
+                editor.text_prov(),
+                editor.config_id(),
+            )
+        })
     }
 
     fn run_command(
@@ -1428,6 +2107,91 @@ fn edit(
     }
 }
 
+/// A change of the buffer since `head`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeadHunk {
+    /// The lines of `head` that were replaced
+    pub old: Range<usize>,
+    /// The lines of the buffer that replaced them
+    pub new: Range<usize>,
+    pub old_text: String,
+}
+
+/// The change of `changes` at `line` of the new text. A change only removing
+/// lines is at both of the lines around where they were.
+fn hunk_at_line(
+    changes: &[(Range<usize>, Range<usize>)],
+    line: usize,
+) -> Option<(Range<usize>, Range<usize>)> {
+    changes
+        .iter()
+        .find(|(_, new)| {
+            new.contains(&line)
+                || (new.is_empty() && (line == new.start || line + 1 == new.start))
+        })
+        .cloned()
+}
+
+/// Move the lines of the new text in `changes` through an edit replacing
+/// `inval_count` lines from `start_line` with `new_count` lines
+fn shift_diff_lines(
+    changes: &mut im::Vector<DiffLines>,
+    start_line: usize,
+    inval_count: usize,
+    new_count: usize,
+) {
+    let shift = |line: usize| {
+        if line >= start_line + inval_count {
+            line + new_count - inval_count
+        } else {
+            // Within the lines the edit removed
+            line.min(start_line + new_count)
+        }
+    };
+    for change in changes.iter_mut() {
+        match change {
+            DiffLines::Left(_) => {}
+            DiffLines::Right(range) => {
+                *range = shift(range.start)..shift(range.end);
+            }
+            DiffLines::Both(info) => {
+                info.right = shift(info.right.start)..shift(info.right.end);
+            }
+        }
+    }
+}
+
+/// The old lines replaced by new ones in a line diff, merging the lines
+/// removed and added next to each other into one change
+fn changed_lines(changes: &[DiffLines]) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut changed = Vec::new();
+    let (mut old_line, mut new_line) = (0, 0);
+    let mut pending: Option<(Range<usize>, Range<usize>)> = None;
+    for change in changes {
+        match change {
+            DiffLines::Left(range) => {
+                let (old, _) =
+                    pending.get_or_insert((old_line..old_line, new_line..new_line));
+                old.end = range.end;
+                old_line = range.end;
+            }
+            DiffLines::Right(range) => {
+                let (_, new) =
+                    pending.get_or_insert((old_line..old_line, new_line..new_line));
+                new.end = range.end;
+                new_line = range.end;
+            }
+            DiffLines::Both(info) => {
+                changed.extend(pending.take());
+                old_line = info.left.end;
+                new_line = info.right.end;
+            }
+        }
+    }
+    changed.extend(pending);
+    changed
+}
+
 impl DocumentPhantom for Doc {
     fn phantom_text(
         &self,
@@ -1442,11 +2206,19 @@ fn phantom_text(
         });
 
         let inlay_hints = self.inlay_hints.get_untracked();
-        // If hints are enabled, and the hints field is filled, then get the hints for this line
+        let inlay_hint_display = config.editor.inlay_hint_display;
+        let show_inlay_hints = self.editor_config().enable_inlay_hints
+            && !self.is_long_line(line)
+            && match inlay_hint_display {
+                InlayHintDisplay::Off => false,
+                InlayHintDisplay::On | InlayHintDisplay::EndOfLine => true,
+                InlayHintDisplay::OnHover => {
+                    self.inlay_hint_hover_line.get_untracked() == Some(line)
+                }
+            };
+        // If hints are displayed, and the hints field is filled, then get the hints for this line
         // and convert them into PhantomText instances
-        let hints = config
-            .editor
-            .enable_inlay_hints
+        let hints = show_inlay_hints
             .then_some(())
             .and(inlay_hints.as_ref())
             .map(|hints| hints.iter_chunks(start_offset..end_offset))
@@ -1519,6 +2291,60 @@ fn phantom_text(
         // overall.
         let mut text: SmallVec<[PhantomText; 6]> = hints.collect();
 
+        // All the hints of the line together at its end, where the error lens
+        // goes, rather than shifting the text between them
+        if inlay_hint_display == InlayHintDisplay::EndOfLine && !text.is_empty() {
+            let hint_text = text.iter().map(|hint| hint.text.trim()).join("  ");
+            text = smallvec![PhantomText {
+                kind: PhantomTextKind::InlayHint,
+                col: end_offset - start_offset,
+                text: format!("    {hint_text}"),
+                affinity: Some(CursorAffinity::Backward),
+                fg: Some(config.color(LapceColor::INLAY_HINT_FOREGROUND)),
+                font_size: Some(config.editor.inlay_hint_font_size()),
+                bg: None,
+                under_line: None,
+            }];
+        }
+
+        // Control chars have no glyphs of their own, so their control pictures
+        // are shown before them
+        if config.editor.render_control_characters && !self.is_long_line(line) {
+            let content = self.buffer.with_untracked(|buffer| {
+                buffer
+                    .text()
+                    .slice_to_cow(start_offset..end_offset)
+                    .to_string()
+            });
+            text.extend(content.char_indices().filter_map(|(col, c)| {
+                Some(PhantomText {
+                    kind: PhantomTextKind::InlayHint,
+                    col,
+                    text: control_picture(c)?.to_string(),
+                    affinity: Some(CursorAffinity::Forward),
+                    fg: Some(config.color(LapceColor::EDITOR_DIM)),
+                    font_size: None,
+                    bg: None,
+                    under_line: None,
+                })
+            }));
+        }
+
+        // A leading bidi mark is the first strong character of the line, so
+        // it decides the direction the line is laid out in
+        if let Some(mark) = config.editor.text_direction.mark() {
+            text.push(PhantomText {
+                kind: PhantomTextKind::InlayHint,
+                col: 0,
+                text: mark.to_string(),
+                affinity: Some(CursorAffinity::Forward),
+                fg: None,
+                font_size: None,
+                bg: None,
+                under_line: None,
+            });
+        }
+
         // If error lens is enabled, and the diagnostics field is filled, then get the diagnostics
         // that end on this line which have a severity worse than HINT and convert them into
         // PhantomText instances
@@ -1736,12 +2562,11 @@ fn apply_colorization(
 }
 impl Styling for DocStyling {
     fn id(&self) -> u64 {
-        self.config.with_untracked(|config| config.id)
+        self.config.with_untracked(|config| config.layout_rev)
     }
 
     fn font_size(&self, _: EditorId, _line: usize) -> usize {
-        self.config
-            .with_untracked(|config| config.editor.font_size())
+        self.doc.editor_config().font_size
     }
 
     fn line_height(&self, _: EditorId, _line: usize) -> f32 {
@@ -1787,7 +2612,7 @@ fn indent_line(&self, _: EditorId, line: usize, line_content: &str) -> usize {
     }
 
     fn tab_width(&self, _: EditorId, _line: usize) -> usize {
-        self.config.with_untracked(|config| config.editor.tab_width)
+        self.doc.editor_config().tab_width
     }
 
     fn atomic_soft_tabs(&self, _: EditorId, _line: usize) -> bool {
@@ -1803,11 +2628,21 @@ fn apply_attr_styles(
         default: Attrs,
         attrs_list: &mut AttrsList,
     ) {
+        if self.doc.is_long_line(line) {
+            return;
+        }
+
         let config = self.doc.common.config.get_untracked();
 
+        let phantom_text = self.doc.phantom_text(edid, style, line);
+        let line_content = self.doc.rope_text().line_content(line);
+        if let Some(level) = self.doc.log.level(&line_content) {
+            let end = phantom_text.col_at(line_content.len());
+            attrs_list.add_span(0..end, default.color(level.color(&config)));
+        }
+
         self.apply_colorization(edid, style, line, &default, attrs_list);
 
-        let phantom_text = self.doc.phantom_text(edid, style, line);
         for line_style in self.doc.line_style(line).iter() {
             if let Some(fg_color) = line_style.style.fg_color.as_ref() {
                 if let Some(fg_color) = config.style_color(fg_color) {
@@ -1942,13 +2777,9 @@ fn apply_layout_styles(
         }
     }
 
-    fn paint_caret(&self, edid: EditorId, _line: usize) -> bool {
-        let Some(e_data) = self.doc.editor_data(edid) else {
-            return true;
-        };
-
-        // If the find is active, then we don't want to paint the caret
-        !e_data.find_focus.get_untracked()
+    fn paint_caret(&self, _edid: EditorId, _line: usize) -> bool {
+        // The editor view paints the caret itself
+        false
     }
 }
 
@@ -1958,6 +2789,23 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+/// The language of `syntax`, which notifies only when the language changes
+/// rather than on every reparse
+fn language_memo(cx: Scope, syntax: RwSignal<Syntax>) -> Memo<LapceLanguage> {
+    cx.create_memo(move |_| syntax.with(|syntax| syntax.language))
+}
+
+/// The control picture shown for the control char `c`, such as `␛` for
+/// escape. Tabs and line endings have none.
+fn control_picture(c: char) -> Option<char> {
+    match c {
+        '\t' | '\n' | '\r' => None,
+        '\u{0}'..='\u{1F}' => char::from_u32(0x2400 + c as u32),
+        '\u{7F}' => Some('\u{2421}'),
+        _ => None,
+    }
+}
+
 /// Get the previous unmatched character `c` from the `offset` using `syntax` if applicable
 fn syntax_prev_unmatched(
     buffer: &Buffer,
@@ -1975,8 +2823,12 @@ fn syntax_prev_unmatched(
 fn should_blink(
     focus: RwSignal<Focus>,
     keyboard_focus: RwSignal<Option<ViewId>>,
+    window_focused: RwSignal<bool>,
 ) -> impl Fn() -> bool {
     move || {
+        if !window_focused.try_get_untracked().unwrap_or(false) {
+            return false;
+        }
         let Some(focus) = focus.try_get_untracked() else {
             return false;
         };
@@ -2047,3 +2899,47 @@ fn extra_styles_for_range(
             })
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::buffer::diff::{DiffBothInfo, DiffLines};
+
+    use super::{hunk_at_line, shift_diff_lines};
+
+    #[test]
+    fn test_hunk_at_line() {
+        // Line 2 was changed into lines 2..4, and line 8 was removed
+        let changes = [(2..3, 2..4), (8..9, 7..7)];
+        assert_eq!(hunk_at_line(&changes, 3), Some((2..3, 2..4)));
+        assert_eq!(hunk_at_line(&changes, 4), None);
+        assert_eq!(hunk_at_line(&changes, 6), Some((8..9, 7..7)));
+        assert_eq!(hunk_at_line(&changes, 7), Some((8..9, 7..7)));
+    }
+
+    #[test]
+    fn test_shift_diff_lines() {
+        let mut changes = im::vector![
+            DiffLines::Both(DiffBothInfo {
+                left: 0..2,
+                right: 0..2,
+                skip: None,
+            }),
+            DiffLines::Right(2..4),
+            DiffLines::Both(DiffBothInfo {
+                left: 2..10,
+                right: 4..12,
+                skip: None,
+            }),
+        ];
+        // Two lines added after line 0
+        shift_diff_lines(&mut changes, 0, 1, 3);
+        assert!(matches!(&changes[1], DiffLines::Right(range) if *range == (4..6)));
+        // Lines 5 and 6 joined
+        shift_diff_lines(&mut changes, 5, 2, 1);
+        assert!(matches!(&changes[1], DiffLines::Right(range) if *range == (4..6)));
+        let DiffLines::Both(info) = &changes[2] else {
+            panic!("expected unchanged lines");
+        };
+        assert_eq!(info.right, 6..13);
+    }
+}
@@ -0,0 +1,128 @@
+//! The documents kept open without being shown in an editor: those opened
+//! ahead of being jumped to, and those whose last editor was closed, for
+//! reopening them to be instant. Past the capacity of the cache, or the length
+//! of text it may hold, the least recently used ones are closed again.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocCache {
+    capacity: usize,
+    /// The paths and the length of their text, from the least to the most
+    /// recently used
+    entries: VecDeque<(PathBuf, usize)>,
+}
+
+impl DocCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Make `path` the most recently used, returning the paths evicted to
+    /// stay within the capacity and `max_len`
+    pub fn insert(
+        &mut self,
+        path: PathBuf,
+        len: usize,
+        max_len: usize,
+    ) -> Vec<PathBuf> {
+        self.remove(&path);
+        self.entries.push_back((path, len));
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        let mut evicted: Vec<PathBuf> =
+            self.entries.drain(..excess).map(|(path, _)| path).collect();
+        evicted.extend(self.shrink(max_len));
+        evicted
+    }
+
+    /// Evict the least recently used paths until the length of the text of
+    /// the rest is within `max_len`, returning them
+    pub fn shrink(&mut self, max_len: usize) -> Vec<PathBuf> {
+        let mut len = self.len();
+        let mut evicted = Vec::new();
+        while len > max_len {
+            let Some((path, path_len)) = self.entries.pop_front() else {
+                break;
+            };
+            len -= path_len;
+            evicted.push(path);
+        }
+        evicted
+    }
+
+    /// Take `path` out of the cache, as it is shown in an editor, returning
+    /// whether it was there
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let index = self.entries.iter().position(|(p, _)| p == path);
+        if let Some(index) = index {
+            self.entries.remove(index);
+        }
+        index.is_some()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.iter().any(|(p, _)| p == path)
+    }
+
+    /// The length of the text of all the cached docs
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(|(_, len)| len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::DocCache;
+
+    #[test]
+    fn test_doc_cache() {
+        let mut cache = DocCache::new(2);
+        assert!(cache.insert(PathBuf::from("a"), 1, 100).is_empty());
+        assert!(cache.insert(PathBuf::from("b"), 1, 100).is_empty());
+        // Using "a" again makes "b" the least recently used
+        assert!(cache.insert(PathBuf::from("a"), 1, 100).is_empty());
+        assert_eq!(
+            cache.insert(PathBuf::from("c"), 1, 100),
+            [PathBuf::from("b")]
+        );
+        assert!(cache.contains(Path::new("a")));
+        assert!(!cache.contains(Path::new("b")));
+
+        assert!(cache.remove(Path::new("a")));
+        assert!(!cache.remove(Path::new("a")));
+        assert!(cache.insert(PathBuf::from("d"), 1, 100).is_empty());
+    }
+
+    #[test]
+    fn test_doc_cache_len() {
+        let mut cache = DocCache::new(8);
+        assert!(cache.insert(PathBuf::from("a"), 40, 100).is_empty());
+        assert!(cache.insert(PathBuf::from("b"), 40, 100).is_empty());
+        assert_eq!(
+            cache.insert(PathBuf::from("c"), 40, 100),
+            [PathBuf::from("a")]
+        );
+        assert_eq!(cache.len(), 80);
+
+        // Less room, as more text is shown in editors
+        assert_eq!(cache.shrink(50), [PathBuf::from("b")]);
+        // Too long to be cached at all
+        assert_eq!(
+            cache.insert(PathBuf::from("d"), 60, 50),
+            [PathBuf::from("c"), PathBuf::from("d")]
+        );
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! The occurrences of the symbol under the cursor, found by the language
+//! server, or by matching the word under the cursor when it doesn't find them.
+
+/// The highlighted occurrences in a document
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentHighlights {
+    /// The revision of the document they were found in
+    pub rev: u64,
+    pub ranges: Vec<HighlightRange>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+    /// Whether the symbol is written to there, rather than read
+    pub write: bool,
+}
+
+impl DocumentHighlights {
+    /// Whether `offset` is within one of the occurrences, so that they are
+    /// still those of the symbol at it
+    pub fn contains(&self, offset: usize) -> bool {
+        self.ranges
+            .iter()
+            .any(|range| (range.start..=range.end).contains(&offset))
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `word` is an identifier, for matching its occurrences
+pub fn is_identifier(word: &str) -> bool {
+    !word.is_empty()
+        && word.chars().all(is_identifier_char)
+        && !word.starts_with(|c: char| c.is_numeric())
+}
+
+/// The ranges of `word` in `text` that are whole words
+pub fn word_occurrences(text: &str, word: &str) -> Vec<HighlightRange> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+    text.match_indices(word)
+        .filter(|(start, _)| {
+            let end = start + word.len();
+            !text[..*start].ends_with(is_identifier_char)
+                && !text[end..].starts_with(is_identifier_char)
+        })
+        .map(|(start, _)| HighlightRange {
+            start,
+            end: start + word.len(),
+            write: false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_identifier, word_occurrences};
+
+    #[test]
+    fn test_word_occurrences() {
+        let text = "let len = a.len();\nlength(len_a, len)";
+        let starts: Vec<usize> = word_occurrences(text, "len")
+            .iter()
+            .map(|range| range.start)
+            .collect();
+        assert_eq!(starts, [4, 12, 33]);
+        assert!(word_occurrences(text, "").is_empty());
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("foo_bar2"));
+        assert!(is_identifier("größe"));
+        assert!(!is_identifier("2nd"));
+        assert!(!is_identifier("a.b"));
+        assert!(!is_identifier(""));
+    }
+}
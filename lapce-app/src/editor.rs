@@ -1,9 +1,11 @@
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
     rc::Rc,
     str::FromStr,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use floem::{
@@ -12,10 +14,13 @@
     keyboard::Modifiers,
     kurbo::{Point, Rect, Vec2},
     menu::{Menu, MenuItem},
-    pointer::{PointerButton, PointerInputEvent, PointerMoveEvent},
+    pointer::{
+        PointerButton, PointerInputEvent, PointerMoveEvent, PointerWheelEvent,
+    },
     reactive::{batch, use_context, ReadSignal, RwSignal, Scope},
     views::editor::{
         command::CommandExecuted,
+        core::register::Clipboard,
         id::EditorId,
         movement,
         text::Document,
@@ -36,39 +41,60 @@
         EditCommand, FocusCommand, MotionModeCommand, MultiSelectionCommand,
         ScrollCommand,
     },
-    cursor::{Cursor, CursorMode},
+    cursor::{Cursor, CursorAffinity, CursorMode},
     editor::EditType,
-    mode::{Mode, MotionMode},
+    language::LapceLanguage,
+    line_ending::LineEnding,
+    mode::{Mode, MotionMode, VisualMode},
     rope_text_pos::RopeTextPosition,
     selection::{InsertDrift, SelRegion, Selection},
 };
 use lapce_rpc::{buffer::BufferId, plugin::PluginId, proxy::ProxyResponse};
 use lapce_xi_rope::{Rope, RopeDelta, Transformer};
 use lsp_types::{
-    CompletionItem, CompletionTextEdit, GotoDefinitionResponse, HoverContents,
-    InlineCompletionTriggerKind, Location, MarkedString, MarkupKind, TextEdit,
+    CompletionItem, CompletionTextEdit, Diagnostic, DocumentHighlight,
+    DocumentHighlightKind, GotoDefinitionResponse, HoverContents,
+    InlineCompletionTriggerKind, LinkedEditingRanges, Location, MarkedString,
+    MarkupKind, TextEdit,
 };
 use serde::{Deserialize, Serialize};
 
 use self::{
     diff::DiffInfo,
     location::{EditorLocation, EditorPosition},
+    screen_lines_view::ScreenLinesView,
 };
 use crate::{
+    clipboard::PrimaryClipboard,
     command::{CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand},
     completion::CompletionStatus,
-    config::LapceConfig,
+    config::{
+        editor::{CursorMovement, InlayHintDisplay, WrapStyle},
+        LapceConfig,
+    },
     db::LapceDb,
     doc::{Doc, DocContent},
+    document_highlight::{
+        is_identifier, word_occurrences, DocumentHighlights, HighlightRange,
+    },
     editor_tab::EditorTabChild,
+    emmet::{self, EmmetSyntax},
+    error_report::ErrorReport,
+    file_template::{expand_variables, TemplateVariables},
+    find::{next_unselected_match, Find, FindSearchString},
+    formatter::{changed_range, BuiltinFormat},
     id::{DiffEditorId, EditorTabId},
-    inline_completion::{InlineCompletionItem, InlineCompletionStatus},
+    inline_completion::{
+        provider_names, InlineCompletionItem, InlineCompletionStatus,
+    },
+    json_tree::json_path,
     keypress::{condition::Condition, KeyPressFocus},
     lsp::path_from_url,
     main_split::{Editors, MainSplitData, SplitDirection, SplitMoveDirection},
     markdown::{
         from_marked_string, from_plaintext, parse_markdown, MarkdownContent,
     },
+    perf::{self, PerfSubsystem},
     snippet::Snippet,
     tracing::*,
     window_tab::{CommonData, Focus, WindowTabData},
@@ -76,7 +102,11 @@
 
 pub mod diff;
 pub mod gutter;
+pub mod head_hunk;
 pub mod location;
+pub mod log_bar;
+pub mod overview_ruler;
+pub mod screen_lines_view;
 pub mod view;
 
 #[derive(Clone, Debug)]
@@ -91,6 +121,8 @@ pub struct EditorInfo {
     pub unsaved: Option<String>,
     pub offset: usize,
     pub scroll_offset: (f64, f64),
+    #[serde(default)]
+    pub wrap_style: Option<WrapStyle>,
 }
 
 impl EditorInfo {
@@ -127,6 +159,7 @@ pub fn to_data(
                     new_doc,
                     None,
                 );
+                editor.wrap_style.set(self.wrap_style);
 
                 editor.id()
             }
@@ -185,6 +218,87 @@ pub fn is_normal(&self) -> bool {
 
 pub type SnippetIndex = Vec<(usize, (usize, usize))>;
 
+/// Ranges with the same text, such as the names of an HTML tag and of the
+/// tag it pairs with, which are being edited together with a cursor in each.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkedEditing {
+    /// In document order
+    pub ranges: Vec<(usize, usize)>,
+    /// The range that the user is editing
+    pub primary: usize,
+}
+
+/// The ranges the language server linked to the cursor position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LspLinkedEditing {
+    pub rev: u64,
+    pub offset: usize,
+    /// `None` until the server responds, and when it has no ranges there
+    pub ranges: Option<Vec<(usize, usize)>>,
+}
+
+/// Selected text being dragged with the mouse
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DragSelection {
+    pub start: usize,
+    pub end: usize,
+    /// Where the text was pressed on
+    pub origin: usize,
+    /// Where the text would be dropped, once the mouse has moved
+    pub drop: Option<usize>,
+}
+
+/// A column selection being made by dragging with Alt+Shift or with the
+/// middle button
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnSelection {
+    /// Where the drag started
+    pub origin: Point,
+    /// Started with the middle button, which pastes the primary selection
+    /// instead when released without dragging
+    pub middle: bool,
+    /// Whether the pointer has moved far enough to select
+    pub dragged: bool,
+}
+
+/// How far the middle button has to be dragged to start a column selection
+const COLUMN_DRAG_THRESHOLD: f64 = 4.0;
+
+/// How long after the last turn of the wheel kinetic scrolling starts
+const KINETIC_SCROLL_START: Duration = Duration::from_millis(50);
+/// How often kinetic scrolling moves the viewport
+const KINETIC_SCROLL_STEP: Duration = Duration::from_millis(16);
+/// How much of its speed kinetic scrolling keeps at each step
+const KINETIC_SCROLL_FRICTION: f64 = 0.92;
+
+/// Scrolling that carries on after the wheel stops
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KineticScroll {
+    /// How far the next step scrolls
+    pub velocity: Vec2,
+    /// Where the viewport was at the last step
+    pub origin: Point,
+    pub timer: TimerToken,
+}
+
+/// How long the cursor has to rest on a symbol for its occurrences to be
+/// highlighted
+const DOCUMENT_HIGHLIGHT_DELAY: Duration = Duration::from_millis(250);
+
+/// How often smooth scrolling moves the viewport
+const SMOOTH_SCROLL_STEP: Duration = Duration::from_millis(16);
+
+/// Scrolling by a scroll command, animated to where it goes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothScroll {
+    /// How far it scrolls in all
+    pub delta: Vec2,
+    /// How far it has scrolled so far
+    pub scrolled: Vec2,
+    pub start: Instant,
+    pub timer: TimerToken,
+}
+
 /// Shares data between cloned instances as long as the signals aren't swapped out.
 #[derive(Clone, Debug)]
 pub struct EditorData {
@@ -193,12 +307,29 @@ pub struct EditorData {
     pub diff_editor_id: RwSignal<Option<(EditorTabId, DiffEditorId)>>,
     pub confirmed: RwSignal<bool>,
     pub snippet: RwSignal<Option<SnippetIndex>>,
+    pub linked_editing: RwSignal<Option<LinkedEditing>>,
+    pub lsp_linked_editing: RwSignal<Option<LspLinkedEditing>>,
+    document_highlight_timer: RwSignal<TimerToken>,
+    /// The selections from before cursors were added, each with the
+    /// selection right after, for undoing the added cursors
+    pub cursor_history: RwSignal<Vec<(Selection, Selection)>>,
+    pub drag_selection: RwSignal<Option<DragSelection>>,
+    /// The column selection being made by dragging
+    pub column_selection: RwSignal<Option<ColumnSelection>>,
+    pub kinetic_scroll: RwSignal<Option<KineticScroll>>,
+    pub smooth_scroll: RwSignal<Option<SmoothScroll>>,
     pub inline_find: RwSignal<Option<InlineFindDirection>>,
     pub last_inline_find: RwSignal<Option<(InlineFindDirection, String)>>,
     pub find_focus: RwSignal<bool>,
+    /// The line of the change since `head` whose old lines are shown
+    pub head_hunk_line: RwSignal<Option<usize>>,
     pub editor: Rc<Editor>,
     pub kind: RwSignal<EditorViewKind>,
     pub sticky_header_height: RwSignal<f64>,
+    /// The lines shown in the sticky header, the outermost first
+    pub sticky_header_lines: RwSignal<Vec<usize>>,
+    /// Overrides the wrap style of the document's language in this editor
+    pub wrap_style: RwSignal<Option<WrapStyle>>,
     pub common: Rc<CommonData>,
 }
 
@@ -226,12 +357,23 @@ fn new(
             diff_editor_id: cx.create_rw_signal(diff_editor_id),
             confirmed,
             snippet: cx.create_rw_signal(None),
+            linked_editing: cx.create_rw_signal(None),
+            lsp_linked_editing: cx.create_rw_signal(None),
+            document_highlight_timer: cx.create_rw_signal(TimerToken::INVALID),
+            cursor_history: cx.create_rw_signal(Vec::new()),
+            drag_selection: cx.create_rw_signal(None),
+            column_selection: cx.create_rw_signal(None),
+            kinetic_scroll: cx.create_rw_signal(None),
+            smooth_scroll: cx.create_rw_signal(None),
             inline_find: cx.create_rw_signal(None),
             last_inline_find: cx.create_rw_signal(None),
             find_focus: cx.create_rw_signal(false),
+            head_hunk_line: cx.create_rw_signal(None),
             editor: Rc::new(editor),
             kind: cx.create_rw_signal(EditorViewKind::Normal),
             sticky_header_height: cx.create_rw_signal(0.0),
+            sticky_header_lines: cx.create_rw_signal(Vec::new()),
+            wrap_style: cx.create_rw_signal(None),
             common,
         }
     }
@@ -310,6 +452,7 @@ pub fn copy(
             .editor
             .last_movement
             .set(self.editor.last_movement.get_untracked());
+        editor.wrap_style.set(self.wrap_style.get_untracked());
 
         editor
     }
@@ -333,7 +476,251 @@ pub fn editor_info(&self, _data: &WindowTabData) -> EditorInfo {
             unsaved,
             offset,
             scroll_offset: (scroll_offset.x, scroll_offset.y),
+            wrap_style: self.wrap_style.get_untracked(),
+        }
+    }
+
+    /// Turn wrapping on or off in this editor only, leaving the setting alone.
+    pub fn toggle_word_wrap(&self) {
+        // The two sides of a diff are aligned line by line, so they never wrap
+        if self.diff_editor_id.get_untracked().is_some() {
+            return;
+        }
+        let configured = self.doc().editor_config().wrap_style;
+        let current = self.wrap_style.get_untracked().unwrap_or(configured);
+        let wrap_style = match current {
+            WrapStyle::None if configured == WrapStyle::None => {
+                WrapStyle::EditorWidth
+            }
+            WrapStyle::None => configured,
+            _ => WrapStyle::None,
+        };
+        // Follow the setting again once toggled back to it
+        self.wrap_style
+            .set((wrap_style != configured).then_some(wrap_style));
+    }
+
+    /// Select each match of the search, or of the word at the cursor when
+    /// nothing is being searched for, that is within the selected text.
+    pub fn select_all_matches_in_selection(&self) {
+        let mut cursor = self.cursor().get_untracked();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            return;
+        };
+        let scopes: Vec<(usize, usize)> = selection
+            .regions()
+            .iter()
+            .filter(|region| !region.is_caret())
+            .map(|region| (region.min(), region.max()))
+            .collect();
+        if scopes.is_empty() {
+            return;
+        }
+
+        let rope_text = self.rope_text();
+        let find = &self.common.find;
+        let search = find
+            .search_string
+            .get_untracked()
+            .filter(|search| !search.content.is_empty())
+            .or_else(|| {
+                let offset = selection.last_inserted()?.end;
+                let (start, end) = rope_text.select_word(offset);
+                (start < end).then(|| FindSearchString {
+                    content: rope_text.slice_to_cow(start..end).to_string(),
+                    regex: None,
+                })
+            });
+        let Some(search) = search else {
+            return;
+        };
+
+        let mut occurrences = Selection::new();
+        for (start, end) in &scopes {
+            Find::find(
+                rope_text.text(),
+                &search,
+                *start,
+                *end,
+                find.case_matching.get_untracked(),
+                find.whole_words.get_untracked(),
+                false,
+                &mut occurrences,
+            );
+        }
+        let mut selection = Selection::new();
+        for region in occurrences.regions() {
+            // The search can run past the edges of the selected text
+            if scopes
+                .iter()
+                .any(|(start, end)| *start <= region.min() && region.max() <= *end)
+            {
+                selection.add_region(*region);
+            }
+        }
+        if selection.is_empty() {
+            return;
+        }
+        cursor.set_insert(selection);
+        self.cursor().set(cursor);
+    }
+
+    /// Replace each selection that spans lines with a caret on each of its
+    /// lines, at the column the selection ends at.
+    pub fn split_selection_into_lines(&self) {
+        let mut cursor = self.cursor().get_untracked();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            return;
+        };
+
+        let rope_text = self.rope_text();
+        let mut new_selection = Selection::new();
+        for region in selection.regions() {
+            let start_line = rope_text.line_of_offset(region.min());
+            let end_line = rope_text.line_of_offset(region.max());
+            if start_line == end_line {
+                new_selection.add_region(*region);
+                continue;
+            }
+            let (_, col) = rope_text.offset_to_line_col(region.end);
+            for line in start_line..=end_line {
+                let offset = rope_text.offset_of_line_col(line, col);
+                new_selection.add_region(SelRegion::caret(offset));
+            }
+        }
+        cursor.set_insert(new_selection);
+        self.cursor().set(cursor);
+    }
+
+    /// Select every match of the search, with a cursor on each.
+    pub fn select_all_find_matches(&self) {
+        let before = self.cursor().get_untracked();
+        if !matches!(before.mode, CursorMode::Insert(_)) {
+            return;
+        }
+        let occurrences = self.doc().find_result.occurrences.get_untracked();
+        if occurrences.is_empty() {
+            return;
+        }
+        self.cursor()
+            .update(|cursor| cursor.set_insert(occurrences));
+        self.record_added_cursors(&before);
+        // The text typed next is for the cursors rather than the search
+        self.find_focus.set(false);
+    }
+
+    /// Add a cursor selecting the next match of the search after the last
+    /// cursor added, going round to the start of the document after the
+    /// last match. A single caret is moved to the match instead.
+    pub fn add_cursor_at_next_find_match(&self) {
+        let before = self.cursor().get_untracked();
+        let CursorMode::Insert(mut selection) = before.mode.clone() else {
+            return;
+        };
+        let matches: Vec<(usize, usize)> = self
+            .doc()
+            .find_result
+            .occurrences
+            .with_untracked(|occurrences| {
+                occurrences
+                    .regions()
+                    .iter()
+                    .map(|region| (region.min(), region.max()))
+                    .collect()
+            });
+        let selected: Vec<(usize, usize)> = selection
+            .regions()
+            .iter()
+            .map(|region| (region.min(), region.max()))
+            .collect();
+        let offset = selection.last_inserted().map_or(0, |region| region.max());
+        let Some((start, end)) = next_unselected_match(&matches, &selected, offset)
+        else {
+            return;
+        };
+        let region = SelRegion::new(start, end, None);
+        if selected.len() == 1 && selected[0].0 == selected[0].1 {
+            selection = Selection::new();
+        }
+        selection.add_region(region);
+        self.cursor().update(|cursor| cursor.set_insert(selection));
+        self.record_added_cursors(&before);
+        self.find_focus.set(false);
+    }
+
+    /// The buffer offsets at `x` on each of the visual lines from the one
+    /// at `y0` to the one at `y1`, in order. The offsets are found by hit
+    /// testing the laid out lines, so they are at the same place on screen
+    /// whatever wrapping and phantom text come before them. A line shorter
+    /// than `x` gives its end.
+    pub fn column_offsets(&self, x: f64, y0: f64, y1: f64) -> Vec<usize> {
+        let line_height = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.line_height())
+            as f64;
+        let mode = self.cursor().with_untracked(|c| c.get_mode());
+        let vline = |y: f64| (y.max(0.0) / line_height).floor() as usize;
+        let (start, end) = (vline(y0.min(y1)), vline(y0.max(y1)));
+        let last_offset = self.rope_text().len();
+        let mut offsets = Vec::with_capacity(end - start + 1);
+        for vline in start..=end {
+            let y = (vline as f64 + 0.5) * line_height;
+            let (offset, _) = self.editor.offset_of_point(mode, Point::new(x, y));
+            // Below the last line every point gives the end of the buffer
+            if offset == last_offset && offsets.last() == Some(&last_offset) {
+                break;
+            }
+            offsets.push(offset);
+        }
+        if y0 > y1 {
+            offsets.reverse();
+        }
+        offsets
+    }
+
+    /// Select the column from `origin` to `point`, a region on each visual
+    /// line between them, or a blockwise visual selection in the modal
+    /// editing
+    fn select_column(&self, origin: Point, point: Point) {
+        let starts = self.column_offsets(origin.x, origin.y, point.y);
+        let ends = self.column_offsets(point.x, origin.y, point.y);
+        if self.editor.es.with_untracked(|s| s.modal()) {
+            let (Some(&start), Some(&end)) = (starts.first(), ends.last()) else {
+                return;
+            };
+            self.cursor().update(|cursor| {
+                cursor.mode = CursorMode::Visual {
+                    start,
+                    end,
+                    mode: VisualMode::Blockwise,
+                };
+                cursor.horiz = None;
+            });
+            return;
         }
+        let mut selection = Selection::new();
+        for (start, end) in starts.into_iter().zip(ends) {
+            selection.add_region(SelRegion::new(start, end, None));
+        }
+        if selection.is_empty() {
+            return;
+        }
+        self.cursor().update(|cursor| cursor.set_insert(selection));
+    }
+
+    /// The first and last lines of the selection
+    pub fn selection_lines(&self) -> (usize, usize) {
+        let cursor = self.cursor().get_untracked();
+        let rope_text = self.rope_text();
+        let selection = cursor.edit_selection(&rope_text);
+        let regions = selection.regions();
+        let start = regions.first().map(|r| r.min()).unwrap_or(0);
+        let end = regions.last().map(|r| r.max()).unwrap_or(start);
+        (
+            rope_text.line_of_offset(start),
+            rope_text.line_of_offset(end),
+        )
     }
 
     pub fn cursor(&self) -> RwSignal<Cursor> {
@@ -365,6 +752,18 @@ pub fn screen_lines(&self) -> RwSignal<ScreenLines> {
         self.editor.screen_lines
     }
 
+    /// The geometry of the lines on the screen, for the views around the text.
+    /// Called in an effect, it reruns when they change.
+    pub fn screen_lines_view(&self) -> ScreenLinesView {
+        let line_height =
+            self.common
+                .config
+                .with(|config| config.editor.line_height()) as f64;
+        self.editor.screen_lines.with(|screen_lines| {
+            ScreenLinesView::from_screen_lines(screen_lines, line_height)
+        })
+    }
+
     pub fn doc(&self) -> Rc<Doc> {
         let doc = self.editor.doc();
         let Ok(doc) = doc.downcast_rc() else {
@@ -390,6 +789,19 @@ pub fn rope_text(&self) -> RopeTextVal {
     }
 
     fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
+        if *cmd == EditCommand::InsertTab && self.expand_emmet() {
+            return CommandExecuted::Yes;
+        }
+        match cmd {
+            EditCommand::DeleteBackward => {
+                self.link_editing_ranges(|offset, (start, _)| offset > start)
+            }
+            EditCommand::DeleteForward => {
+                self.link_editing_ranges(|offset, (_, end)| offset < end)
+            }
+            _ => self.unlink_editing_ranges(),
+        }
+
         let doc = self.doc();
         let text = self.editor.rope_text();
         let is_local = doc.content.with_untracked(|content| content.is_local());
@@ -479,12 +891,20 @@ fn run_multi_selection_command(
         cmd: &MultiSelectionCommand,
     ) -> CommandExecuted {
         let mut cursor = self.editor.cursor.get_untracked();
+        let before = cursor.clone();
         let rope_text = self.rope_text();
         let doc = self.doc();
         let config = self.common.config.get_untracked();
 
         // This is currently special-cased in Lapce because floem editor does not have 'find'
         match cmd {
+            MultiSelectionCommand::SelectUndo => {
+                if let Some(selection) = self.undo_added_cursors() {
+                    cursor.set_insert(selection);
+                } else {
+                    movement::do_multi_selection(&self.editor, &mut cursor, cmd);
+                }
+            }
             MultiSelectionCommand::SelectAllCurrent => {
                 if let CursorMode::Insert(mut selection) = cursor.mode.clone() {
                     if !selection.is_empty() {
@@ -614,12 +1034,63 @@ fn run_multi_selection_command(
         };
 
         self.editor.cursor.set(cursor);
+        self.record_added_cursors(&before);
         // self.cancel_signature();
         self.cancel_completion();
         self.cancel_inline_completion();
         CommandExecuted::Yes
     }
 
+    /// Remember the selection from before cursors were added, so that
+    /// [`MultiSelectionCommand::SelectUndo`] can take the new ones away again.
+    fn record_added_cursors(&self, before: &Cursor) {
+        let (CursorMode::Insert(before), CursorMode::Insert(after)) =
+            (&before.mode, self.cursor().get_untracked().mode)
+        else {
+            return;
+        };
+        if after.regions().len() > before.regions().len() {
+            self.cursor_history
+                .update(|history| history.push((before.clone(), after)));
+        }
+    }
+
+    /// The selection from before the last cursors were added, as long as the
+    /// cursors haven't changed in some other way since.
+    fn undo_added_cursors(&self) -> Option<Selection> {
+        let CursorMode::Insert(current) = self.cursor().get_untracked().mode else {
+            return None;
+        };
+        let mut history = self.cursor_history.get_untracked();
+        let (before, after) = history.pop()?;
+        if after.regions() != current.regions() {
+            // The cursors moved or were edited since, so this is out of date
+            self.cursor_history.set(Vec::new());
+            return None;
+        }
+        self.cursor_history.set(history);
+        Some(before)
+    }
+
+    /// Remember where the cursor is, to go back to it after a jump
+    fn save_jump_location(&self) {
+        let path = self
+            .doc()
+            .content
+            .with_untracked(|content| content.path().cloned());
+        if let Some(path) = path {
+            let offset = self.cursor().with_untracked(|c| c.offset());
+            let scroll_offset = self.viewport().get_untracked().origin().to_vec2();
+            self.common
+                .internal_command
+                .send(InternalCommand::SaveJumpLocation {
+                    path,
+                    offset,
+                    scroll_offset,
+                });
+        }
+    }
+
     fn run_move_command(
         &self,
         movement: &lapce_core::movement::Movement,
@@ -630,25 +1101,15 @@ fn run_move_command(
         if movement.is_jump()
             && movement != &self.editor.last_movement.get_untracked()
         {
-            let path = self
-                .doc()
-                .content
-                .with_untracked(|content| content.path().cloned());
-            if let Some(path) = path {
-                let offset = self.cursor().with_untracked(|c| c.offset());
-                let scroll_offset =
-                    self.viewport().get_untracked().origin().to_vec2();
-                self.common.internal_command.send(
-                    InternalCommand::SaveJumpLocation {
-                        path,
-                        offset,
-                        scroll_offset,
-                    },
-                );
-            }
+            self.save_jump_location();
         }
         self.editor.last_movement.set(movement.clone());
 
+        let visual = self
+            .visual_move_offset(movement, count.unwrap_or(1))
+            .map(lapce_core::movement::Movement::Offset);
+        let movement = visual.as_ref().unwrap_or(movement);
+
         let mut cursor = self.cursor().get_untracked();
         self.common.register.update(|register| {
             movement::move_cursor(
@@ -663,6 +1124,24 @@ fn run_move_command(
         });
 
         self.editor.cursor.set(cursor);
+        if mods.shift() {
+            self.update_primary_selection();
+        }
+
+        if let Some(linked) = self.linked_editing.get_untracked() {
+            let (start, end) = linked.ranges[linked.primary];
+            let within_range = self.editor.cursor.with_untracked(|cursor| {
+                let CursorMode::Insert(selection) = &cursor.mode else {
+                    return false;
+                };
+                selection.regions().len() == linked.ranges.len()
+                    && (start..=end)
+                        .contains(&selection.regions()[linked.primary].end)
+            });
+            if !within_range {
+                self.unlink_editing_ranges();
+            }
+        }
 
         if self.snippet.with_untracked(|s| s.is_some()) {
             self.snippet.update(|snippet| {
@@ -696,10 +1175,10 @@ pub fn run_scroll_command(
 
         match cmd {
             ScrollCommand::PageUp => {
-                self.editor.page_move(false, mods);
+                self.page_move(false, mods);
             }
             ScrollCommand::PageDown => {
-                self.editor.page_move(true, mods);
+                self.page_move(true, mods);
             }
             ScrollCommand::ScrollUp => {
                 self.scroll(false, count.unwrap_or(1), mods);
@@ -707,8 +1186,10 @@ pub fn run_scroll_command(
             ScrollCommand::ScrollDown => {
                 self.scroll(true, count.unwrap_or(1), mods);
             }
+            ScrollCommand::CenterOfWindow => {
+                self.center_window();
+            }
             // TODO:
-            ScrollCommand::CenterOfWindow => {}
             ScrollCommand::TopOfWindow => {}
             ScrollCommand::BottomOfWindow => {}
         }
@@ -1085,6 +1566,38 @@ pub fn run_focus_command(
         CommandExecuted::Yes
     }
 
+    /// Follow the `path:line:column` under the cursor, for the text that isn't
+    /// a file, like the output of a command, relative to the workspace.
+    fn go_to_location_at_cursor(&self) {
+        let offset = self.cursor().with_untracked(|c| c.offset());
+        let (line_content, index) = self.doc().buffer.with_untracked(|buffer| {
+            let line = buffer.line_of_offset(offset);
+            (
+                buffer.line_content(line).to_string(),
+                offset - buffer.offset_of_line(line),
+            )
+        });
+        let Some((path, position)) = location::location_at(&line_content, index)
+        else {
+            return;
+        };
+        let path = match self.common.workspace.path.as_ref() {
+            Some(workspace) => workspace.join(path),
+            None => PathBuf::from(path),
+        };
+        self.common
+            .internal_command
+            .send(InternalCommand::JumpToLocation {
+                location: EditorLocation {
+                    path,
+                    position: Some(position),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                },
+            });
+    }
+
     /// Jump to the next/previous column on the line which matches the given text
     fn inline_find(&self, direction: InlineFindDirection, c: &str) {
         let offset = self.cursor().with_untracked(|c| c.offset());
@@ -1134,7 +1647,10 @@ fn go_to_definition(&self) {
             None
         } {
             Some(path) => path,
-            None => return,
+            None => {
+                self.go_to_location_at_cursor();
+                return;
+            }
         };
 
         let offset = self.cursor().with_untracked(|c| c.offset());
@@ -1182,15 +1698,28 @@ enum DefinitionOrReferece {
             }
         });
         let proxy = self.common.proxy.clone();
+        let start = Instant::now();
         self.common.proxy.get_definition(
             offset,
             path.clone(),
             position,
             move |result| {
+                perf::record(PerfSubsystem::Lsp, start.elapsed());
                 if let Ok(ProxyResponse::GetDefinitionResponse {
                     definition, ..
                 }) = result
                 {
+                    // Several definitions, from one provider or merged from
+                    // several, are listed to pick from
+                    let definition = match definition {
+                        GotoDefinitionResponse::Array(locations)
+                            if locations.len() > 1 =>
+                        {
+                            send(DefinitionOrReferece::References(locations));
+                            return;
+                        }
+                        definition => definition,
+                    };
                     if let Some(location) = match definition {
                         GotoDefinitionResponse::Scalar(location) => Some(location),
                         GotoDefinitionResponse::Array(locations) => {
@@ -1265,13 +1794,205 @@ enum DefinitionOrReferece {
         );
     }
 
+    /// Where moving left or right by `count` goes with the visual cursor
+    /// movement, which follows the order right to left text is shown in.
+    /// `None` for the logical movement, which is also used on lines without
+    /// right to left text and with several cursors.
+    fn visual_move_offset(
+        &self,
+        movement: &lapce_core::movement::Movement,
+        count: usize,
+    ) -> Option<usize> {
+        let right = match movement {
+            lapce_core::movement::Movement::Left => false,
+            lapce_core::movement::Movement::Right => true,
+            _ => return None,
+        };
+        let cursor_movement = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.cursor_movement);
+        if cursor_movement != CursorMovement::Visual {
+            return None;
+        }
+        let (mut offset, caret) =
+            self.cursor().with_untracked(|cursor| match &cursor.mode {
+                CursorMode::Insert(selection) if selection.len() > 1 => None,
+                _ => Some((cursor.offset(), !cursor.is_insert())),
+            })?;
+
+        let rope_text = self.rope_text();
+        let line = rope_text.line_of_offset(offset);
+        let line_start = rope_text.offset_of_line(line);
+        let line_end = rope_text.line_end_offset(line, caret);
+        if !rope_text
+            .slice_to_cow(line_start..line_end)
+            .chars()
+            .any(is_rtl_char)
+        {
+            return None;
+        }
+
+        // The positions the cursor can be at on the visual line it is on
+        let y = self
+            .editor
+            .points_of_offset(offset, CursorAffinity::Forward)
+            .0
+            .y;
+        let mut stops = Vec::new();
+        let mut stop = line_start;
+        loop {
+            let (point, _) =
+                self.editor.points_of_offset(stop, CursorAffinity::Forward);
+            if point.y == y {
+                stops.push((point.x, stop));
+            }
+            if stop >= line_end {
+                break;
+            }
+            stop = rope_text
+                .text()
+                .next_grapheme_offset(stop)
+                .map_or(line_end, |next| next.min(line_end));
+        }
+
+        for _ in 0..count {
+            let x = self
+                .editor
+                .points_of_offset(offset, CursorAffinity::Forward)
+                .0
+                .x;
+            let Some(next) = visual_neighbor(&stops, x, right) else {
+                break;
+            };
+            offset = next;
+        }
+        Some(offset)
+    }
+
+    /// Scroll by `count` lines, moving the cursor along if it would go out
+    /// of view
     fn scroll(&self, down: bool, count: usize, mods: Modifiers) {
-        self.editor.scroll(
-            self.sticky_header_height.get_untracked(),
-            down,
-            count,
-            mods,
-        )
+        let viewport = self.viewport().get_untracked();
+        let line_height =
+            self.common.config.get_untracked().editor.line_height() as f64;
+        let diff = line_height * count as f64;
+        let diff = if down { diff } else { -diff };
+        let offset = self.cursor().with_untracked(|cursor| cursor.offset());
+        let (line, _) = self.rope_text().offset_to_line_col(offset);
+        let top = viewport.y0 + diff + self.sticky_header_height.get_untracked();
+        let bottom = viewport.y0 + diff + viewport.height();
+        let new_line = if (line + 1) as f64 * line_height + line_height > bottom {
+            ((bottom / line_height).floor() as usize).saturating_sub(2)
+        } else if line as f64 * line_height - line_height < top {
+            (top / line_height).ceil() as usize + 1
+        } else {
+            line
+        };
+
+        self.scroll_by(Vec2::new(0.0, diff));
+        let movement = match new_line.cmp(&line) {
+            Ordering::Greater => {
+                Some((lapce_core::movement::Movement::Down, new_line - line))
+            }
+            Ordering::Less => {
+                Some((lapce_core::movement::Movement::Up, line - new_line))
+            }
+            Ordering::Equal => None,
+        };
+        if let Some((movement, count)) = movement {
+            self.run_move_command(&movement, Some(count), mods);
+        }
+    }
+
+    /// Move the cursor and scroll by half of the viewport
+    fn page_move(&self, down: bool, mods: Modifiers) {
+        let viewport = self.viewport().get_untracked();
+        let line_height =
+            self.common.config.get_untracked().editor.line_height() as f64;
+        let lines = (viewport.height() / line_height / 2.0).round() as usize;
+        let distance = lines as f64 * line_height;
+        self.scroll_by(Vec2::new(0.0, if down { distance } else { -distance }));
+        let movement = if down {
+            lapce_core::movement::Movement::Down
+        } else {
+            lapce_core::movement::Movement::Up
+        };
+        self.run_move_command(&movement, Some(lines), mods);
+    }
+
+    /// Scroll the line of the cursor to the middle of the viewport
+    fn center_window(&self) {
+        let viewport = self.viewport().get_untracked();
+        let (offset, affinity) = self
+            .cursor()
+            .with_untracked(|cursor| (cursor.offset(), cursor.affinity));
+        let (above, below) = self.editor.points_of_offset(offset, affinity);
+        let center = (above.y + below.y) / 2.0;
+        self.scroll_by(Vec2::new(0.0, center - viewport.center().y));
+    }
+
+    /// Scroll by `delta`, animated for the smooth scroll duration of the
+    /// config. Scrolling again before the animation ends carries on from
+    /// where it is to where both go.
+    fn scroll_by(&self, delta: Vec2) {
+        let duration = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.smooth_scroll_duration());
+        if duration.is_zero() {
+            self.editor.scroll_delta.set(delta);
+            return;
+        }
+        let remaining = self
+            .smooth_scroll
+            .get_untracked()
+            .map(|scroll| scroll.delta - scroll.scrolled)
+            .unwrap_or(Vec2::ZERO);
+        let editor = self.clone();
+        let timer = exec_after(SMOOTH_SCROLL_STEP, move |token| {
+            editor.smooth_scroll_step(token);
+        });
+        self.smooth_scroll.set(Some(SmoothScroll {
+            delta: remaining + delta,
+            scrolled: Vec2::ZERO,
+            start: Instant::now(),
+            timer,
+        }));
+    }
+
+    fn smooth_scroll_step(&self, token: TimerToken) {
+        let Some(mut scroll) = self.smooth_scroll.try_get_untracked().flatten()
+        else {
+            return;
+        };
+        if scroll.timer != token {
+            return;
+        }
+        let (duration, easing) = self.common.config.with_untracked(|config| {
+            (
+                config.editor.smooth_scroll_duration(),
+                config.editor.smooth_scroll_easing,
+            )
+        });
+        let t = if duration.is_zero() {
+            1.0
+        } else {
+            scroll.start.elapsed().as_secs_f64() / duration.as_secs_f64()
+        };
+        let scrolled = scroll.delta * easing.ease(t);
+        self.editor.scroll_delta.set(scrolled - scroll.scrolled);
+        if t >= 1.0 {
+            self.smooth_scroll.set(None);
+            return;
+        }
+
+        let editor = self.clone();
+        scroll.scrolled = scrolled;
+        scroll.timer = exec_after(SMOOTH_SCROLL_STEP, move |token| {
+            editor.smooth_scroll_step(token);
+        });
+        self.smooth_scroll.set(Some(scroll));
     }
 
     fn select_inline_completion(&self) {
@@ -1333,6 +2054,11 @@ pub fn cancel_inline_completion(&self) {
             return;
         }
 
+        let request_id = self
+            .common
+            .inline_completion
+            .with_untracked(|c| c.request_id);
+        self.common.proxy.cancel_inline_completion(request_id);
         self.common.inline_completion.update(|c| {
             c.cancel();
         });
@@ -1340,6 +2066,46 @@ pub fn cancel_inline_completion(&self) {
         self.doc().clear_inline_completion();
     }
 
+    /// Show the inline completions a plugin provides for a request of this
+    /// editor
+    pub fn set_plugin_inline_completions(
+        &self,
+        request_id: u64,
+        provider: &str,
+        items: &[lsp_types::InlineCompletionItem],
+    ) {
+        let doc = self.doc();
+        let path = doc.content.with_untracked(|c| c.path().cloned());
+        let inline_completion = self.common.inline_completion;
+        if path.is_none()
+            || inline_completion
+                .with_untracked(|c| Some(&c.request_path) != path.as_ref())
+        {
+            return;
+        }
+
+        let items = doc.buffer.with_untracked(|buffer| {
+            items
+                .iter()
+                .map(|item| {
+                    InlineCompletionItem::from_lsp(
+                        buffer,
+                        item.clone(),
+                        Some(provider.to_string()),
+                    )
+                })
+                .collect()
+        });
+        let priority = self.common.config.with_untracked(|config| {
+            config.editor.inline_completion_providers.clone()
+        });
+        inline_completion.update(|c| {
+            if c.set_items(request_id, Some(provider), items, &priority) {
+                c.update_doc(&doc, c.request_offset);
+            }
+        });
+    }
+
     /// Update the current inline completion
     fn update_inline_completion(&self, trigger_kind: InlineCompletionTriggerKind) {
         if self.get_mode() != Mode::Insert {
@@ -1384,29 +2150,53 @@ fn update_inline_completion(&self, trigger_kind: InlineCompletionTriggerKind) {
             });
         }
 
-        let path2 = path.clone();
-        let send = create_ext_action(
-            self.scope,
+        // The providers still working on the previous request can stop
+        let previous = inline_completion.with_untracked(|c| {
+            (c.status != InlineCompletionStatus::Inactive).then_some(c.request_id)
+        });
+        if let Some(previous) = previous {
+            self.common.proxy.cancel_inline_completion(previous);
+        }
+        let request_id = inline_completion
+            .try_update(|c| c.start_request(offset, path.clone()))
+            .unwrap();
+
+        let (priority, disabled_providers) =
+            self.common.config.with_untracked(|config| {
+                (
+                    config.editor.inline_completion_providers.clone(),
+                    provider_names(
+                        &config.editor.disabled_inline_completion_providers,
+                    )
+                    .map(str::to_string)
+                    .collect(),
+                )
+            });
+        let send = create_ext_action(
+            self.scope,
             move |items: Vec<lsp_types::InlineCompletionItem>| {
                 let items = doc.buffer.with_untracked(|buffer| {
                     items
                         .into_iter()
-                        .map(|item| InlineCompletionItem::from_lsp(buffer, item))
+                        .map(|item| {
+                            InlineCompletionItem::from_lsp(buffer, item, None)
+                        })
                         .collect()
                 });
                 inline_completion.update(|c| {
-                    c.set_items(items, offset, path2);
-                    c.update_doc(&doc, offset);
+                    if c.set_items(request_id, None, items, &priority) {
+                        c.update_doc(&doc, offset);
+                    }
                 });
             },
         );
 
-        inline_completion.update(|c| c.status = InlineCompletionStatus::Started);
-
         self.common.proxy.get_inline_completions(
             path,
             position,
             trigger_kind,
+            request_id,
+            disabled_providers,
             move |res| {
                 if let Ok(ProxyResponse::GetInlineCompletions {
                     completions: items,
@@ -1528,7 +2318,12 @@ fn update_completion(&self, display_if_empty_input: bool) {
             };
             (start_offset, input, char)
         });
-        if !display_if_empty_input && input.is_empty() && char != "." && char != ":"
+        if !display_if_empty_input
+            && input.is_empty()
+            && !self
+                .common
+                .completion
+                .with_untracked(|c| c.is_trigger_character(&char))
         {
             self.cancel_completion();
             return;
@@ -1758,6 +2553,439 @@ pub fn completion_apply_snippet(
         Ok(())
     }
 
+    /// Fill an empty new file with a template, as a snippet whose tab stops
+    /// are gone through. An untitled file gets the language of the template.
+    pub fn apply_file_template(&self, template: &Path) {
+        let doc = self.doc();
+        if !doc.buffer.with_untracked(|buffer| buffer.is_empty()) {
+            return;
+        }
+        let filename = match doc.content.get_untracked() {
+            DocContent::File { path, .. } => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            DocContent::Scratch { name, .. } => {
+                if let Some(language) = LapceLanguage::from_path_raw(template) {
+                    doc.set_language(language);
+                    doc.trigger_syntax_change(None);
+                }
+                name
+            }
+            _ => return,
+        };
+
+        let text = match std::fs::read_to_string(template) {
+            Ok(text) => text,
+            Err(err) => {
+                self.common.report_error(
+                    ErrorReport::new(
+                        "File Templates",
+                        format!(
+                            "Failed to read the template {}: {err}",
+                            template.display()
+                        ),
+                    )
+                    .path(template),
+                );
+                return;
+            }
+        };
+        let author = self
+            .common
+            .config
+            .get_untracked()
+            .editor
+            .file_template_author
+            .clone();
+        let snippet =
+            expand_variables(&text, &TemplateVariables::new(&filename, &author));
+        if let Err(err) = self.completion_apply_snippet(
+            &snippet,
+            &Selection::caret(0),
+            Vec::new(),
+            0,
+        ) {
+            trace!(
+                TraceLevel::ERROR,
+                "failed to apply the file template: {err}"
+            );
+        }
+    }
+
+    /// Before an edit at the cursor, put a cursor in each of the ranges
+    /// linked to the one the cursor is in, so that they are all edited
+    /// together. The ranges come from the language server, or else are the
+    /// names of a pair of HTML or JSX tags. They stay linked while `fits`
+    /// says that the edit at the cursor keeps to the range.
+    fn link_editing_ranges(&self, fits: impl Fn(usize, (usize, usize)) -> bool) {
+        let mut cursor = self.cursor().get_untracked();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            self.unlink_editing_ranges();
+            return;
+        };
+
+        if let Some(linked) = self.linked_editing.get_untracked() {
+            let regions = selection.regions();
+            let primary = regions.get(linked.primary).map(|r| r.end);
+            let still_linked = regions.len() == linked.ranges.len()
+                && regions.iter().zip(&linked.ranges).all(|(region, range)| {
+                    region.is_caret()
+                        && (range.0..=range.1).contains(&region.end)
+                        && region.end - range.0
+                            == regions[0].end - linked.ranges[0].0
+                });
+            match primary {
+                Some(offset)
+                    if still_linked
+                        && fits(offset, linked.ranges[linked.primary]) =>
+                {
+                    return;
+                }
+                _ => {
+                    self.unlink_editing_ranges();
+                    return;
+                }
+            }
+        }
+
+        let [region] = selection.regions() else {
+            return;
+        };
+        if !region.is_caret() {
+            return;
+        }
+        let offset = region.end;
+        let Some(mut ranges) = self
+            .lsp_linked_editing_ranges(offset)
+            .or_else(|| self.linked_tag_names(offset))
+        else {
+            return;
+        };
+        ranges.sort();
+        let Some(primary) = ranges
+            .iter()
+            .position(|(start, end)| (*start..=*end).contains(&offset))
+        else {
+            return;
+        };
+        let (start, end) = ranges[primary];
+        // The cursors have to be at the same place in each range
+        if !fits(offset, (start, end))
+            || ranges.iter().any(|(s, e)| e - s != end - start)
+        {
+            return;
+        }
+
+        let mut selection = Selection::new();
+        for (range_start, _) in &ranges {
+            selection.add_region(SelRegion::caret(range_start + offset - start));
+        }
+        cursor.set_insert(selection);
+        self.cursor().set(cursor);
+        self.linked_editing
+            .set(Some(LinkedEditing { ranges, primary }));
+    }
+
+    /// The ranges linked to the one at the offset by the language server,
+    /// if it has sent them for the current text.
+    fn lsp_linked_editing_ranges(
+        &self,
+        offset: usize,
+    ) -> Option<Vec<(usize, usize)>> {
+        let rev = self.doc().rev();
+        self.lsp_linked_editing.with_untracked(|linked| {
+            let linked = linked.as_ref()?;
+            let ranges = linked.ranges.as_ref()?;
+            (linked.rev == rev
+                && ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&offset)))
+            .then(|| ranges.clone())
+        })
+    }
+
+    fn linked_tag_names(&self, offset: usize) -> Option<Vec<(usize, usize)>> {
+        if !self
+            .common
+            .config
+            .with_untracked(|config| config.editor.auto_rename_tags)
+        {
+            return None;
+        }
+        let (name, other) = self
+            .doc()
+            .syntax
+            .with_untracked(|syntax| syntax.find_tag_name_pair(offset))?;
+        Some(vec![(name.start, name.end), (other.start, other.end)])
+    }
+
+    /// Stop editing ranges together, leaving only the cursor the user put in
+    /// one of them.
+    fn unlink_editing_ranges(&self) {
+        let Some(linked) = self.linked_editing.get_untracked() else {
+            return;
+        };
+        self.linked_editing.set(None);
+
+        let mut cursor = self.cursor().get_untracked();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            return;
+        };
+        if selection.regions().len() == linked.ranges.len() {
+            let region = selection.regions()[linked.primary];
+            let mut selection = Selection::new();
+            selection.add_region(region);
+            cursor.set_insert(selection);
+            self.cursor().set(cursor);
+        }
+    }
+
+    /// Ask the language server for the ranges linked to the one at the
+    /// cursor, for [`EditorData::link_editing_ranges`] to use once the
+    /// cursor edits it.
+    pub fn get_linked_editing_ranges(&self) {
+        let doc = self.doc();
+        let path = match if doc.loaded() {
+            doc.content.with_untracked(|c| c.path().cloned())
+        } else {
+            None
+        } {
+            Some(path) => path,
+            None => return,
+        };
+        if !self
+            .common
+            .config
+            .with_untracked(|config| config.editor.linked_editing)
+        {
+            return;
+        }
+
+        let offset = self.cursor().with_untracked(|c| c.offset());
+        let rev = doc.rev();
+        let requested = self.lsp_linked_editing.with_untracked(|linked| {
+            linked.as_ref().is_some_and(|linked| {
+                linked.rev == rev
+                    && (linked.offset == offset
+                        || linked.ranges.as_ref().is_some_and(|ranges| {
+                            ranges.iter().any(|(start, end)| {
+                                (*start..=*end).contains(&offset)
+                            })
+                        }))
+            })
+        });
+        if requested {
+            return;
+        }
+        self.lsp_linked_editing.set(Some(LspLinkedEditing {
+            rev,
+            offset,
+            ranges: None,
+        }));
+
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+        let lsp_linked_editing = self.lsp_linked_editing;
+        let send = create_ext_action(
+            self.scope,
+            move |ranges: Option<LinkedEditingRanges>| {
+                if doc.rev() != rev {
+                    return;
+                }
+                let ranges = ranges.map(|ranges| {
+                    doc.buffer.with_untracked(|buffer| {
+                        ranges
+                            .ranges
+                            .iter()
+                            .map(|range| {
+                                (
+                                    buffer.offset_of_position(&range.start),
+                                    buffer.offset_of_position(&range.end),
+                                )
+                            })
+                            .collect()
+                    })
+                });
+                lsp_linked_editing.update(|linked| {
+                    if let Some(linked) =
+                        linked.as_mut().filter(|linked| linked.offset == offset)
+                    {
+                        linked.ranges = ranges;
+                    }
+                });
+            },
+        );
+        self.common
+            .proxy
+            .get_linked_editing_range(path, position, move |result| {
+                // Servers without linked editing fall back to tag names
+                if let Ok(ProxyResponse::GetLinkedEditingRange { ranges }) = result {
+                    send(ranges)
+                }
+            });
+    }
+
+    /// Highlight the occurrences of the symbol at the cursor, once the cursor
+    /// has rested on it
+    pub fn get_document_highlights(&self) {
+        if !self
+            .common
+            .config
+            .with_untracked(|config| config.editor.highlight_occurrences)
+        {
+            return;
+        }
+        let editor = self.clone();
+        let timer = exec_after(DOCUMENT_HIGHLIGHT_DELAY, move |token| {
+            if editor.document_highlight_timer.try_get_untracked() == Some(token) {
+                editor.request_document_highlights();
+            }
+        });
+        self.document_highlight_timer.set(timer);
+    }
+
+    fn request_document_highlights(&self) {
+        let doc = self.doc();
+        if !doc.loaded() {
+            return;
+        }
+        let offset = self.cursor().with_untracked(|c| c.offset());
+        let rev = doc.rev();
+        let highlighted = doc.document_highlights.with_untracked(|highlights| {
+            highlights.as_ref().is_some_and(|highlights| {
+                highlights.rev == rev && highlights.contains(offset)
+            })
+        });
+        if highlighted {
+            return;
+        }
+
+        let (text, (start, end)) = doc.buffer.with_untracked(|buffer| {
+            (buffer.text().clone(), buffer.select_word(offset))
+        });
+        let word = text.slice_to_cow(start..end).to_string();
+        if !is_identifier(&word) {
+            doc.document_highlights.set(None);
+            return;
+        }
+
+        let path = doc.content.with_untracked(|c| c.path().cloned());
+        let send = {
+            let doc = doc.clone();
+            create_ext_action(
+                self.scope,
+                move |highlights: Option<Vec<DocumentHighlight>>| {
+                    if doc.rev() != rev {
+                        return;
+                    }
+                    let ranges = match highlights {
+                        Some(highlights) => doc.buffer.with_untracked(|buffer| {
+                            highlights
+                                .iter()
+                                .map(|highlight| HighlightRange {
+                                    start: buffer
+                                        .offset_of_position(&highlight.range.start),
+                                    end: buffer
+                                        .offset_of_position(&highlight.range.end),
+                                    write: highlight.kind
+                                        == Some(DocumentHighlightKind::WRITE),
+                                })
+                                .collect()
+                        }),
+                        // Servers without document highlights fall back to the
+                        // occurrences of the word
+                        None => word_occurrences(&text.slice_to_cow(..), &word),
+                    };
+                    doc.document_highlights
+                        .set(Some(DocumentHighlights { rev, ranges }));
+                },
+            )
+        };
+        let Some(path) = path else {
+            send(None);
+            return;
+        };
+        let position = doc
+            .buffer
+            .with_untracked(|buffer| buffer.offset_to_position(offset));
+        self.common
+            .proxy
+            .get_document_highlight(path, position, move |result| match result {
+                Ok(ProxyResponse::GetDocumentHighlight { highlights }) => {
+                    send(highlights)
+                }
+                _ => send(None),
+            });
+    }
+
+    /// Expand the Emmet abbreviation before the cursor, if the language at the
+    /// cursor has them enabled. Returns whether there was one.
+    fn expand_emmet(&self) -> bool {
+        let cursor = self.cursor().get_untracked();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            return false;
+        };
+        let [region] = selection.regions() else {
+            return false;
+        };
+        if !region.is_caret() {
+            return false;
+        }
+        let offset = region.end;
+
+        let doc = self.doc();
+        // Injections count, such as CSS in a `<style>` element
+        let language = doc
+            .syntax
+            .with_untracked(|syntax| syntax.language_at(offset));
+        let Some(syntax) = EmmetSyntax::from_language(language) else {
+            return false;
+        };
+        if !self
+            .common
+            .config
+            .with_untracked(|config| config.editor_for(language).emmet)
+        {
+            return false;
+        }
+
+        let (line_start, line, indent_unit) = doc.buffer.with_untracked(|buffer| {
+            let line_start = buffer.offset_of_line(buffer.line_of_offset(offset));
+            (
+                line_start,
+                buffer.slice_to_cow(line_start..offset).to_string(),
+                buffer.indent_unit(),
+            )
+        });
+        let Some(start) = emmet::abbreviation_start(&line, syntax) else {
+            return false;
+        };
+        let indent: String =
+            line.chars().take_while(|c| c.is_whitespace()).collect();
+        let Some(snippet) =
+            emmet::expand(&line[start..], syntax, &indent, indent_unit)
+        else {
+            return false;
+        };
+
+        let start = line_start + start;
+        if let Err(err) = self.completion_apply_snippet(
+            &snippet,
+            &Selection::region(start, offset),
+            Vec::new(),
+            start,
+        ) {
+            trace!(
+                TraceLevel::ERROR,
+                "failed to expand emmet abbreviation: {err}"
+            );
+            return false;
+        }
+        true
+    }
+
     fn add_snippet_placeholders(
         &self,
         new_placeholders: Vec<(usize, (usize, usize))>,
@@ -1812,7 +3040,18 @@ pub fn do_edit(
     }
 
     pub fn do_text_edit(&self, edits: &[TextEdit]) {
-        let (selection, edits) = self.doc().buffer.with_untracked(|buffer| {
+        let doc = self.doc();
+        // The edits of a refactor are recorded for rolling it back
+        let refactor_path = doc.content.with_untracked(|content| {
+            content.path().cloned().filter(|path| {
+                self.common
+                    .refactors
+                    .with_untracked(|refactors| refactors.is_pending(path))
+            })
+        });
+        let before = doc.buffer.with_untracked(|buffer| buffer.text().clone());
+
+        let (selection, edits) = doc.buffer.with_untracked(|buffer| {
             let selection = self.cursor().get_untracked().edit_selection(buffer);
             let edits = edits
                 .iter()
@@ -1828,6 +3067,13 @@ pub fn do_text_edit(&self, edits: &[TextEdit]) {
         });
 
         self.do_edit(&selection, &edits);
+
+        if let Some(path) = refactor_path {
+            let after = doc.buffer.with_untracked(|buffer| buffer.text().clone());
+            self.common
+                .refactors
+                .update(|refactors| refactors.record(&path, before, after));
+        }
     }
 
     fn apply_deltas(&self, deltas: &[(Rope, RopeDelta, InvalLines)]) {
@@ -1837,11 +3083,26 @@ fn apply_deltas(&self, deltas: &[(Rope, RopeDelta, InvalLines)]) {
         for (_, delta, _) in deltas {
             // self.inactive_apply_delta(delta);
             self.update_snippet_offset(delta);
+            self.update_linked_editing(delta);
             // self.update_breakpoints(delta);
         }
         // self.update_signature();
     }
 
+    fn update_linked_editing(&self, delta: &RopeDelta) {
+        if self.linked_editing.with_untracked(|l| l.is_some()) {
+            self.linked_editing.update(|linked| {
+                let mut transformer = Transformer::new(delta);
+                if let Some(linked) = linked {
+                    for (start, end) in &mut linked.ranges {
+                        *start = transformer.transform(*start, false);
+                        *end = transformer.transform(*end, true);
+                    }
+                }
+            });
+        }
+    }
+
     fn update_snippet_offset(&self, delta: &RopeDelta) {
         if self.snippet.with_untracked(|s| s.is_some()) {
             self.snippet.update(|snippet| {
@@ -1937,6 +3198,26 @@ pub fn go_to_position(
         }
     }
 
+    /// Select a range of the text, the cursor only going to its start in
+    /// the modal editing
+    pub fn select_range(&self, start: usize, end: usize) {
+        self.go_to_position(EditorPosition::Offset(start), None, None);
+        if !self.common.config.get_untracked().core.modal {
+            self.cursor().update(|cursor| {
+                cursor.set_insert(Selection::region(start, end));
+            });
+        }
+    }
+
+    /// The JSON path of the value at the cursor, `None` unless the document
+    /// is JSON
+    pub fn json_path(&self) -> Option<String> {
+        let tree = self.doc_signal().with(|doc| doc.json_tree.get())?;
+        let root = tree.root.as_ref()?;
+        let offset = self.cursor().with(|cursor| cursor.offset());
+        Some(json_path(root, &root.path_at(offset)))
+    }
+
     pub fn get_code_actions(&self) {
         let doc = self.doc();
         let path = match if doc.loaded() {
@@ -2061,16 +3342,17 @@ pub fn save(
         }
 
         let rev = doc.rev();
-        let format_on_save = allow_formatting && config.editor.format_on_save;
+        let format_on_save = allow_formatting && doc.editor_config().format_on_save;
         if format_on_save {
             let editor = self.clone();
             let send = create_ext_action(self.scope, move |result| {
-                if let Ok(Ok(ProxyResponse::GetDocumentFormatting { edits })) =
-                    result
-                {
-                    let current_rev = editor.doc().rev();
-                    if current_rev == rev {
+                if editor.doc().rev() == rev {
+                    if let Ok(Ok(ProxyResponse::GetDocumentFormatting { edits })) =
+                        result
+                    {
                         editor.do_text_edit(&edits);
+                    } else {
+                        editor.format_fallback();
                     }
                 }
                 editor.do_save(after_action);
@@ -2098,12 +3380,13 @@ pub fn format(&self) {
         if let DocContent::File { path, .. } = content {
             let editor = self.clone();
             let send = create_ext_action(self.scope, move |result| {
-                if let Ok(Ok(ProxyResponse::GetDocumentFormatting { edits })) =
-                    result
-                {
-                    let current_rev = editor.doc().rev();
-                    if current_rev == rev {
+                if editor.doc().rev() == rev {
+                    if let Ok(Ok(ProxyResponse::GetDocumentFormatting { edits })) =
+                        result
+                    {
                         editor.do_text_edit(&edits);
+                    } else {
+                        editor.format_fallback();
                     }
                 }
             });
@@ -2120,6 +3403,41 @@ pub fn format(&self) {
         }
     }
 
+    /// Format the document with the built-in formatter of its language, when
+    /// no language server formatted it
+    fn format_fallback(&self) {
+        let language = self.doc().syntax().with_untracked(|s| s.language);
+        if let Some(format) = BuiltinFormat::for_language(language) {
+            let _ = self.format_builtin(format);
+        }
+    }
+
+    /// Format the document with a built-in formatter, the text the
+    /// formatting leaves as it is not being edited
+    pub fn format_builtin(&self, format: BuiltinFormat) -> Result<(), String> {
+        let doc = self.doc();
+        let (text, indent) = doc
+            .buffer
+            .with_untracked(|b| (b.text().clone(), b.indent_unit().to_string()));
+        let line_ending = if doc.line_ending() == LineEnding::CrLf {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let formatted = format.format(&text, &indent, line_ending)?;
+        let text = text.to_string();
+        if let Some((range, new_text)) = changed_range(&text, &formatted) {
+            let selection = doc.buffer.with_untracked(|buffer| {
+                self.cursor().get_untracked().edit_selection(buffer)
+            });
+            self.do_edit(
+                &selection,
+                &[(Selection::region(range.start, range.end), new_text)],
+            );
+        }
+        Ok(())
+    }
+
     fn search_whole_word_forward(&self, mods: Modifiers) {
         let offset = self.cursor().with_untracked(|c| c.offset());
         let (word, buffer) = self.doc().buffer.with_untracked(|buffer| {
@@ -2369,6 +3687,8 @@ fn search(&self) {
 
     #[instrument]
     pub fn pointer_down(&self, pointer_event: &PointerInputEvent) {
+        self.kinetic_scroll.set(None);
+        self.smooth_scroll.set(None);
         self.cancel_completion();
         self.cancel_inline_completion();
         if let Some(editor_tab_id) = self.editor_tab_id.get_untracked() {
@@ -2386,8 +3706,26 @@ pub fn pointer_down(&self, pointer_event: &PointerInputEvent) {
         }
         match pointer_event.button {
             PointerButton::Primary => {
+                if self.click_sticky_header(pointer_event.pos) {
+                    return;
+                }
                 self.active().set(true);
+                if self.start_drag_selection(pointer_event) {
+                    return;
+                }
+                let mods = pointer_event.modifiers;
+                if pointer_event.count == 1 && mods.alt() && mods.shift() {
+                    self.column_selection.set(Some(ColumnSelection {
+                        origin: pointer_event.pos,
+                        middle: false,
+                        dragged: true,
+                    }));
+                    self.select_column(pointer_event.pos, pointer_event.pos);
+                    return;
+                }
                 self.left_click(pointer_event);
+                self.get_linked_editing_ranges();
+                self.get_document_highlights();
 
                 if cfg!(target_os = "macos") && pointer_event.modifiers.meta() {
                     self.common.lapce_command.send(LapceCommand {
@@ -2408,12 +3746,219 @@ pub fn pointer_down(&self, pointer_event: &PointerInputEvent) {
             PointerButton::Secondary => {
                 self.right_click(pointer_event);
             }
+            PointerButton::Auxiliary => {
+                // Whether it pastes or selects a column is known once it is
+                // released or dragged
+                self.active().set(true);
+                self.column_selection.set(Some(ColumnSelection {
+                    origin: pointer_event.pos,
+                    middle: true,
+                    dragged: false,
+                }));
+            }
             _ => {}
         }
     }
 
+    /// Start dragging the selected text when it is pressed on, rather than
+    /// putting the cursor there. Returns whether the drag started.
+    fn start_drag_selection(&self, pointer_event: &PointerInputEvent) -> bool {
+        let mods = pointer_event.modifiers;
+        if pointer_event.count != 1
+            || mods.shift()
+            || mods.alt()
+            || mods.control()
+            || mods.meta()
+        {
+            return false;
+        }
+        let cursor = self.cursor().get_untracked();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            return false;
+        };
+        let (origin, _) = self
+            .editor
+            .offset_of_point(cursor.get_mode(), pointer_event.pos);
+        let Some(region) = selection
+            .regions()
+            .iter()
+            .find(|region| region.min() < origin && origin < region.max())
+        else {
+            return false;
+        };
+        self.drag_selection.set(Some(DragSelection {
+            start: region.min(),
+            end: region.max(),
+            origin,
+            drop: None,
+        }));
+        true
+    }
+
+    /// Move the selected text being dragged to where it is dropped, or copy
+    /// it there with Ctrl (Option on macOS) held. Releasing the text without
+    /// moving it puts the cursor where it was pressed instead.
+    fn drop_drag_selection(
+        &self,
+        drag: DragSelection,
+        pointer_event: &PointerInputEvent,
+    ) {
+        let DragSelection {
+            start,
+            end,
+            origin,
+            drop,
+        } = drag;
+        let Some(drop) = drop.filter(|drop| *drop < start || *drop > end) else {
+            let offset = drop.unwrap_or(origin);
+            self.cursor().update(|cursor| {
+                cursor.set_insert(Selection::caret(offset));
+            });
+            return;
+        };
+
+        let copy = if cfg!(target_os = "macos") {
+            pointer_event.modifiers.alt()
+        } else {
+            pointer_event.modifiers.control()
+        };
+        let text = self.rope_text().slice_to_cow(start..end).to_string();
+        let insert = (Selection::caret(drop), text.as_str());
+        let remove = (Selection::region(start, end), "");
+        // The edits have to be in the order they are in the text
+        let edits = if copy {
+            vec![insert]
+        } else if drop < start {
+            vec![insert, remove]
+        } else {
+            vec![remove, insert]
+        };
+        self.do_edit(&Selection::caret(drop), &edits);
+
+        let new_start = if !copy && drop > end {
+            drop - (end - start)
+        } else {
+            drop
+        };
+        self.cursor().update(|cursor| {
+            cursor.set_insert(Selection::region(new_start, new_start + text.len()));
+        });
+    }
+
+    /// Paste the primary selection where the middle click is, as X11 apps do.
+    /// The selection is fetched in the background, as its owner may be slow
+    /// to answer.
+    fn middle_click(&self, pointer_event: &PointerInputEvent) {
+        if !PrimaryClipboard::is_supported()
+            || !self
+                .common
+                .config
+                .with_untracked(|config| config.editor.middle_click_paste)
+        {
+            return;
+        }
+        let mode = self.cursor().with_untracked(|c| c.get_mode());
+        let (offset, _) = self.editor.offset_of_point(mode, pointer_event.pos);
+        let rev = self.doc().rev();
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |text: Option<String>| {
+            // The offset clicked is out of date once the text has changed
+            if editor.doc().rev() != rev {
+                return;
+            }
+            if let Some(text) = text.filter(|text| !text.is_empty()) {
+                editor.paste_primary_selection(offset, &text);
+            }
+        });
+        std::thread::spawn(move || {
+            send(PrimaryClipboard::new().get_string());
+        });
+    }
+
+    /// Insert the primary selection `text` at `offset`, leaving the cursor
+    /// after it in its current mode
+    fn paste_primary_selection(&self, offset: usize, text: &str) {
+        self.do_edit(
+            &Selection::caret(offset),
+            &[(Selection::caret(offset), text)],
+        );
+        let end = offset + text.len();
+        let last_char = self
+            .doc()
+            .buffer
+            .with_untracked(|buffer| buffer.prev_grapheme_offset(end, 1, 0));
+        self.cursor().update(|cursor| match cursor.mode {
+            CursorMode::Insert(_) => cursor.set_insert(Selection::caret(end)),
+            // A caret of the modal mode is on a char rather than between two
+            CursorMode::Normal(_) | CursorMode::Visual { .. } => {
+                cursor.mode = CursorMode::Normal(last_char);
+            }
+        });
+    }
+
+    /// Make the selected text the primary selection, for pasting with a
+    /// middle click.
+    fn update_primary_selection(&self) {
+        if !PrimaryClipboard::is_supported()
+            || !self
+                .common
+                .config
+                .with_untracked(|config| config.editor.middle_click_paste)
+        {
+            return;
+        }
+        let rope_text = self.rope_text();
+        let text = self.cursor().with_untracked(|cursor| {
+            let CursorMode::Insert(selection) = &cursor.mode else {
+                return String::new();
+            };
+            selection
+                .regions()
+                .iter()
+                .filter(|region| !region.is_caret())
+                .map(|region| rope_text.slice_to_cow(region.min()..region.max()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        if !text.is_empty() {
+            PrimaryClipboard::new().put_string(text);
+        }
+    }
+
     #[instrument]
+    /// Go to the line of the sticky header at `pos`. Returns whether there's
+    /// one there.
+    fn click_sticky_header(&self, pos: Point) -> bool {
+        let config = self.common.config.get_untracked();
+        if !config.editor.sticky_header || !self.kind.get_untracked().is_normal() {
+            return false;
+        }
+        let y = pos.y - self.viewport().get_untracked().y0;
+        if y < 0.0 || y >= self.sticky_header_height.get_untracked() {
+            return false;
+        }
+
+        let line_height = config.editor.line_height() as f64;
+        let mut bottom = 0.0;
+        let line =
+            self.sticky_header_lines
+                .get_untracked()
+                .into_iter()
+                .find(|line| {
+                    bottom += self.editor.text_layout(*line).line_count() as f64
+                        * line_height;
+                    y < bottom
+                });
+        let Some(line) = line else {
+            return false;
+        };
+        self.save_jump_location();
+        self.go_to_position(EditorPosition::Line(line), None, None);
+        true
+    }
+
     fn left_click(&self, pointer_event: &PointerInputEvent) {
+        let before = self.cursor().get_untracked();
         match pointer_event.count {
             1 => {
                 self.single_click(pointer_event);
@@ -2426,6 +3971,7 @@ fn left_click(&self, pointer_event: &PointerInputEvent) {
             }
             _ => {}
         }
+        self.record_added_cursors(&before);
     }
 
     #[instrument]
@@ -2448,6 +3994,38 @@ pub fn pointer_move(&self, pointer_event: &PointerMoveEvent) {
         let mode = self.cursor().with_untracked(|c| c.get_mode());
         let (offset, is_inside) =
             self.editor.offset_of_point(mode, pointer_event.pos);
+        if self.common.config.with_untracked(|config| {
+            config.editor.inlay_hint_display == InlayHintDisplay::OnHover
+        }) {
+            let doc = self.doc();
+            let line = doc.buffer.with_untracked(|b| b.line_of_offset(offset));
+            doc.set_inlay_hint_hover_line(Some(line));
+        }
+        if self.active().get_untracked()
+            && self.drag_selection.with_untracked(|drag| drag.is_some())
+        {
+            self.drag_selection.update(|drag| {
+                if let Some(drag) = drag {
+                    drag.drop = Some(offset);
+                }
+            });
+            return;
+        }
+        if self.active().get_untracked() {
+            if let Some(mut column) = self.column_selection.get_untracked() {
+                if !column.dragged
+                    && column.origin.distance(pointer_event.pos)
+                        >= COLUMN_DRAG_THRESHOLD
+                {
+                    column.dragged = true;
+                    self.column_selection.set(Some(column));
+                }
+                if column.dragged {
+                    self.select_column(column.origin, pointer_event.pos);
+                }
+                return;
+            }
+        }
         if self.active().get_untracked()
             && self.cursor().with_untracked(|c| c.offset()) != offset
         {
@@ -2497,12 +4075,97 @@ pub fn pointer_move(&self, pointer_event: &PointerMoveEvent) {
 
     #[instrument]
     pub fn pointer_up(&self, pointer_event: &PointerInputEvent) {
+        if let Some(drag) = self.drag_selection.get_untracked() {
+            self.drag_selection.set(None);
+            self.drop_drag_selection(drag, pointer_event);
+        }
+        if let Some(column) = self.column_selection.get_untracked() {
+            self.column_selection.set(None);
+            if column.middle && !column.dragged {
+                self.middle_click(pointer_event);
+            }
+        }
         self.editor.pointer_up(pointer_event);
+        self.update_primary_selection();
+    }
+
+    /// Scroll by the wheel, or zoom with Ctrl held.
+    pub fn pointer_wheel(&self, pointer_event: &PointerWheelEvent) {
+        let mods = pointer_event.modifiers;
+        if mods.control() {
+            let cmd = if pointer_event.delta.y < 0.0 {
+                LapceWorkbenchCommand::ZoomIn
+            } else if pointer_event.delta.y > 0.0 {
+                LapceWorkbenchCommand::ZoomOut
+            } else {
+                return;
+            };
+            self.common.lapce_command.send(LapceCommand {
+                kind: CommandKind::Workbench(cmd),
+                data: None,
+            });
+            return;
+        }
+        let (delta, kinetic) = self.common.config.with_untracked(|config| {
+            (
+                config.editor.wheel_delta(pointer_event.delta, mods.shift()),
+                config.editor.kinetic_scrolling,
+            )
+        });
+        // Scrolling with the wheel stops the animation of a scroll command
+        self.smooth_scroll.set(None);
+        self.editor.scroll_delta.set(delta);
+        if kinetic {
+            self.start_kinetic_scroll(delta);
+        }
+    }
+
+    /// Keep scrolling by `velocity` once the wheel stops, slowing down until
+    /// it comes to rest or reaches the edge of the document.
+    fn start_kinetic_scroll(&self, velocity: Vec2) {
+        let editor = self.clone();
+        let timer = exec_after(KINETIC_SCROLL_START, move |token| {
+            editor.kinetic_scroll_step(token);
+        });
+        self.kinetic_scroll.set(Some(KineticScroll {
+            velocity,
+            origin: self.viewport().get_untracked().origin(),
+            timer,
+        }));
+    }
+
+    fn kinetic_scroll_step(&self, token: TimerToken) {
+        let Some(kinetic) = self.kinetic_scroll.try_get_untracked().flatten() else {
+            return;
+        };
+        if kinetic.timer != token {
+            return;
+        }
+        let origin = self.viewport().get_untracked().origin();
+        let velocity = kinetic.velocity * KINETIC_SCROLL_FRICTION;
+        // The scroll view stops at the edges of the document, so an unmoved
+        // viewport means that one was reached
+        if origin == kinetic.origin || velocity.hypot() < 0.5 {
+            self.kinetic_scroll.set(None);
+            return;
+        }
+
+        self.editor.scroll_delta.set(velocity);
+        let editor = self.clone();
+        let timer = exec_after(KINETIC_SCROLL_STEP, move |token| {
+            editor.kinetic_scroll_step(token);
+        });
+        self.kinetic_scroll.set(Some(KineticScroll {
+            velocity,
+            origin,
+            timer,
+        }));
     }
 
     #[instrument]
     pub fn pointer_leave(&self) {
         self.common.mouse_hover_timer.set(TimerToken::INVALID);
+        self.doc().set_inlay_hint_hover_line(None);
     }
 
     #[instrument]
@@ -2583,6 +4246,25 @@ fn update_hover(&self, offset: usize) {
         let config = self.common.config;
         let hover_data = self.common.hover.clone();
         let editor_id = self.id();
+
+        // The diagnostics are shown right away, with the content of the
+        // language server added once it answers
+        let diagnostics: Vec<Diagnostic> =
+            doc.diagnostics().diagnostics_span.with_untracked(|span| {
+                span.iter_chunks(offset..offset)
+                    .filter(|(iv, _)| iv.start <= offset && iv.end >= offset)
+                    .map(|(_, diagnostic)| diagnostic.clone())
+                    .collect()
+            });
+        let has_diagnostics = !diagnostics.is_empty();
+        hover_data.diagnostics.set(diagnostics);
+        if has_diagnostics {
+            hover_data.content.set(Vec::new());
+            hover_data.offset.set(offset);
+            hover_data.editor_id.set(editor_id);
+            hover_data.active.set(true);
+        }
+
         let send = create_ext_action(self.scope, move |resp| {
             if let Ok(ProxyResponse::HoverResponse { hover, .. }) = resp {
                 let content = parse_hover_resp(hover, &config.get_untracked());
@@ -2592,7 +4274,9 @@ fn update_hover(&self, offset: usize) {
                 hover_data.active.set(true);
             }
         });
-        self.common.proxy.get_hover(0, path, position, |resp| {
+        let start = Instant::now();
+        self.common.proxy.get_hover(0, path, position, move |resp| {
+            perf::record(PerfSubsystem::Lsp, start.elapsed());
             send(resp);
         });
     }
@@ -2946,6 +4630,11 @@ fn receive_char(&self, c: &str) {
         } else {
             // normal editor receive char
             if self.get_mode() == Mode::Insert {
+                self.link_editing_ranges(|_, _| {
+                    c.chars().all(|c| {
+                        c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')
+                    })
+                });
                 let mut cursor = self.cursor().get_untracked();
                 let deltas = self.doc().do_insert(
                     &mut cursor,
@@ -3068,6 +4757,37 @@ fn show_inline_completion(cmd: &EditCommand) -> bool {
     )
 }
 
+/// Whether `c` is of a script written right to left
+fn is_rtl_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+/// The offset of the stop nearest to `x` on its right, or on its left, out of
+/// the `x` positions and offsets of the stops of a visual line
+fn visual_neighbor(stops: &[(f64, usize)], x: f64, right: bool) -> Option<usize> {
+    const EPSILON: f64 = 0.5;
+    let beyond = stops.iter().filter(|(stop_x, _)| {
+        if right {
+            *stop_x > x + EPSILON
+        } else {
+            *stop_x < x - EPSILON
+        }
+    });
+    if right {
+        beyond.min_by(|a, b| a.0.total_cmp(&b.0))
+    } else {
+        beyond.max_by(|a, b| a.0.total_cmp(&b.0))
+    }
+    .map(|(_, offset)| *offset)
+}
+
 // TODO(minor): Should we just put this on view, since it only requires those values?
 pub(crate) fn compute_screen_lines(
     config: ReadSignal<Arc<LapceConfig>>,
@@ -3155,8 +4875,9 @@ pub(crate) fn compute_screen_lines(
             }
         }
         EditorViewKind::Diff(diff_info) => {
-            // TODO: let lines in diff view be wrapped, possibly screen_lines should be impl'd
-            // on DiffEditorData
+            // The editors of a diff don't wrap (see `DiffEditorData::new`), so each
+            // buffer line takes a single row here. Wrapping would need the layout
+            // of the other side to keep the rows of both aligned.
 
             let mut y_idx = 0;
             let mut rvlines = Vec::new();
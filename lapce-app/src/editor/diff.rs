@@ -14,13 +14,12 @@
     diff::{expand_diff_lines, rope_diff, DiffExpand, DiffLines},
     rope_text::RopeText,
 };
-use lapce_rpc::{buffer::BufferId, proxy::ProxyResponse};
-use lapce_xi_rope::Rope;
+use lapce_rpc::buffer::BufferId;
 use serde::{Deserialize, Serialize};
 
 use super::{EditorData, EditorViewKind};
 use crate::{
-    config::{color::LapceColor, icon::LapceIcons},
+    config::{color::LapceColor, editor::WrapStyle, icon::LapceIcons},
     doc::{Doc, DocContent},
     id::{DiffEditorId, EditorTabId},
     main_split::{Editors, MainSplitData},
@@ -61,7 +60,7 @@ pub fn to_data(
                 DocContent::Local => {
                     Rc::new(Doc::new_local(cx, data.editors, common.clone()))
                 }
-                DocContent::History(history) => {
+                DocContent::History(_) => {
                     let doc = Doc::new_history(
                         cx,
                         content.clone(),
@@ -69,26 +68,7 @@ pub fn to_data(
                         common.clone(),
                     );
                     let doc = Rc::new(doc);
-
-                    {
-                        let doc = doc.clone();
-                        let send = create_ext_action(cx, move |result| {
-                            if let Ok(ProxyResponse::BufferHeadResponse {
-                                content,
-                                ..
-                            }) = result
-                            {
-                                doc.init_content(Rope::from(content));
-                            }
-                        });
-                        common.proxy.get_buffer_head(
-                            history.path.clone(),
-                            move |result| {
-                                send(result);
-                            },
-                        );
-                    }
-
+                    doc.load_history();
                     doc
                 }
                 DocContent::Scratch { name, .. } => {
@@ -158,14 +138,18 @@ pub fn new(
 
         // TODO: ensure that left/right are cleaned up
         let [left, right] = [left_doc, right_doc].map(|doc| {
-            editors.make_from_doc(
+            let editor = editors.make_from_doc(
                 cx,
                 doc,
                 None,
                 Some((editor_tab_id, id)),
                 Some(confirmed),
                 common.clone(),
-            )
+            );
+            // The rows of both sides are aligned line by line, which wrapping
+            // would break
+            editor.wrap_style.set(Some(WrapStyle::None));
+            editor
         });
 
         let data = Self {
@@ -2,12 +2,17 @@
     context::PaintCx,
     cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout},
     peniko::kurbo::{Point, Rect, Size},
+    reactive::create_effect,
     Renderer, View, ViewId,
 };
 use lapce_core::{buffer::rope_text::RopeText, mode::Mode};
+use lapce_rpc::coverage::LineCoverage;
 
 use super::{view::changes_colors_screen, EditorData};
-use crate::config::{color::LapceColor, LapceConfig};
+use crate::{
+    blame::heat_color,
+    config::{color::LapceColor, LapceConfig},
+};
 
 pub struct EditorGutterView {
     id: ViewId,
@@ -18,6 +23,15 @@ pub struct EditorGutterView {
 pub fn editor_gutter_view(editor: EditorData) -> EditorGutterView {
     let id = ViewId::new();
 
+    let doc = editor.doc_signal();
+    let coverage = editor.common.coverage;
+    create_effect(move |_| {
+        let blame = doc.with(|doc| doc.blame);
+        blame.track();
+        coverage.track();
+        id.request_paint();
+    });
+
     EditorGutterView {
         id,
         editor,
@@ -62,6 +76,84 @@ fn paint_head_changes(
         }
     }
 
+    /// Tint the lines by how recently they were changed, leaving the lines
+    /// changed since `head` as they are
+    fn paint_blame_heat(
+        &self,
+        cx: &mut PaintCx,
+        viewport: Rect,
+        is_normal: bool,
+        config: &LapceConfig,
+    ) {
+        if !is_normal || !config.editor.blame_heat_map {
+            return;
+        }
+        let doc = self.editor.doc();
+        let Some(blame) = doc.blame.get_untracked() else {
+            return;
+        };
+        let line_height = config.editor.line_height() as f64;
+
+        for (line, y) in self.editor.screen_lines_view().iter_lines_y() {
+            let Some((head_line, _)) = doc.head_lines(line, line) else {
+                continue;
+            };
+            let Some((_, heat)) = blame.line(head_line) else {
+                continue;
+            };
+            cx.fill(
+                &Size::new(self.width, line_height)
+                    .to_rect()
+                    .with_origin(Point::new(0.0, y - viewport.y0)),
+                heat_color(config, heat),
+                0.0,
+            );
+        }
+    }
+
+    /// Mark the lines with code by whether the tests of the coverage report
+    /// ran them
+    fn paint_coverage(
+        &self,
+        cx: &mut PaintCx,
+        viewport: Rect,
+        is_normal: bool,
+        config: &LapceConfig,
+    ) {
+        if !is_normal {
+            return;
+        }
+        let doc = self.editor.doc();
+        let Some(coverage) = doc.content.with_untracked(|content| {
+            let path = content.path()?;
+            self.editor
+                .common
+                .coverage
+                .with_untracked(|coverage| coverage.get(path).cloned())
+        }) else {
+            return;
+        };
+        let line_height = config.editor.line_height() as f64;
+
+        for (line, y) in self.editor.screen_lines_view().iter_lines_y() {
+            let Some(line_coverage) = coverage.lines.get(&line) else {
+                continue;
+            };
+            let color = match line_coverage {
+                LineCoverage::Covered => LapceColor::EDITOR_COVERAGE_COVERED,
+                LineCoverage::Partial => LapceColor::EDITOR_COVERAGE_PARTIAL,
+                LineCoverage::Uncovered => LapceColor::EDITOR_COVERAGE_UNCOVERED,
+            };
+            cx.fill(
+                &Size::new(3.0, line_height)
+                    .to_rect()
+                    .with_origin(Point::new(0.0, y - viewport.y0)),
+                config.color(color),
+                0.0,
+            );
+        }
+    }
+
     fn paint_sticky_headers(
         &self,
         cx: &mut PaintCx,
@@ -117,7 +209,6 @@ fn compute_layout(
     fn paint(&mut self, cx: &mut floem::context::PaintCx) {
         let viewport = self.editor.viewport().get_untracked();
         let cursor = self.editor.cursor();
-        let screen_lines = self.editor.screen_lines();
         let config = self.editor.common.config;
 
         let kind_is_normal =
@@ -146,42 +237,43 @@ fn paint(&mut self, cx: &mut floem::context::PaintCx) {
             && mode != Mode::Insert
             && kind_is_normal;
 
-        screen_lines.with_untracked(|screen_lines| {
-            for (line, y) in screen_lines.iter_lines_y() {
-                // If it ends up outside the bounds of the file, stop trying to display line numbers
-                if line > last_line {
-                    break;
-                }
+        self.paint_blame_heat(cx, viewport, kind_is_normal, &config);
+        self.paint_coverage(cx, viewport, kind_is_normal, &config);
 
-                let text = if show_relative {
-                    if line == current_line {
-                        line + 1
-                    } else {
-                        line.abs_diff(current_line)
-                    }
-                } else {
-                    line + 1
-                }
-                .to_string();
+        for (line, y) in self.editor.screen_lines_view().iter_lines_y() {
+            // If it ends up outside the bounds of the file, stop trying to display line numbers
+            if line > last_line {
+                break;
+            }
 
-                let mut text_layout = TextLayout::new();
+            let text = if show_relative {
                 if line == current_line {
-                    text_layout.set_text(&text, current_line_attrs_list.clone());
+                    line + 1
                 } else {
-                    text_layout.set_text(&text, attrs_list.clone());
+                    line.abs_diff(current_line)
                 }
-                let size = text_layout.size();
-                let height = size.height;
-
-                cx.draw_text(
-                    &text_layout,
-                    Point::new(
-                        (self.width - (size.width)).max(0.0),
-                        y + (line_height - height) / 2.0 - viewport.y0,
-                    ),
-                );
+            } else {
+                line + 1
+            }
+            .to_string();
+
+            let mut text_layout = TextLayout::new();
+            if line == current_line {
+                text_layout.set_text(&text, current_line_attrs_list.clone());
+            } else {
+                text_layout.set_text(&text, attrs_list.clone());
             }
-        });
+            let size = text_layout.size();
+            let height = size.height;
+
+            cx.draw_text(
+                &text_layout,
+                Point::new(
+                    (self.width - (size.width)).max(0.0),
+                    y + (line_height - height) / 2.0 - viewport.y0,
+                ),
+            );
+        }
 
         self.paint_head_changes(cx, &self.editor, viewport, kind_is_normal, &config);
         self.paint_sticky_headers(cx, kind_is_normal, &config);
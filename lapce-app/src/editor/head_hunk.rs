@@ -0,0 +1,173 @@
+//! The lines of `head` that a change of the document replaced, shown below
+//! the change when its marker in the gutter is clicked, with the change being
+//! reverted or staged from there.
+
+use std::sync::Arc;
+
+use floem::{
+    reactive::{create_memo, ReadSignal, RwSignal},
+    style::CursorStyle,
+    views::{empty, label, stack, Decorators},
+    View,
+};
+
+use super::EditorData;
+use crate::{
+    app::clickable_icon,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+};
+
+fn lines(count: usize) -> String {
+    if count == 1 {
+        "1 line".to_string()
+    } else {
+        format!("{count} lines")
+    }
+}
+
+fn hunk_button(
+    text: &'static str,
+    on_click: impl Fn() + 'static,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    label(move || text.to_string())
+        .on_click_stop(move |_| on_click())
+        .style(move |s| {
+            let config = config.get();
+            s.margin_right(6.0)
+                .padding_horiz(8.0)
+                .border(1.0)
+                .border_radius(4.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+                .color(config.color(LapceColor::EDITOR_FOREGROUND))
+                .hover(|s| {
+                    s.cursor(CursorStyle::Pointer).background(
+                        config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                    )
+                })
+                .selectable(false)
+        })
+}
+
+pub fn head_hunk_view(editor: RwSignal<EditorData>) -> impl View {
+    let (doc, config, hunk_line, ed) = editor.with_untracked(|e| {
+        (
+            e.doc_signal(),
+            e.common.config,
+            e.head_hunk_line,
+            e.editor.clone(),
+        )
+    });
+    let viewport = ed.viewport;
+
+    let hunk = create_memo(move |_| {
+        let line = hunk_line.get()?;
+        doc.get().head_hunk(line)
+    });
+
+    let summary = move || {
+        let Some(hunk) = hunk.get() else {
+            return String::new();
+        };
+        match (hunk.old.len(), hunk.new.len()) {
+            (0, new) => format!("Added {} since HEAD", lines(new)),
+            (old, 0) => format!("Removed {} since HEAD", lines(old)),
+            (old, new) => {
+                format!("Changed {} of HEAD into {}", lines(old), lines(new))
+            }
+        }
+    };
+
+    stack((
+        stack((
+            label(summary),
+            empty().style(|s| s.flex_grow(1.0)),
+            hunk_button(
+                "Revert",
+                move || {
+                    if let Some(hunk) = hunk.get_untracked() {
+                        doc.get_untracked().revert_head_hunk(&hunk);
+                        hunk_line.set(None);
+                    }
+                },
+                config,
+            ),
+            hunk_button(
+                "Stage",
+                move || {
+                    if let Some(hunk) = hunk.get_untracked() {
+                        doc.get_untracked().stage_head_hunk(&hunk);
+                        hunk_line.set(None);
+                    }
+                },
+                config,
+            ),
+            clickable_icon(
+                || LapceIcons::CLOSE,
+                move || hunk_line.set(None),
+                || false,
+                || false,
+                || "Close",
+                config,
+            ),
+        ))
+        .style(move |s| {
+            s.items_center()
+                .width_full()
+                .padding_horiz(10.0)
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+        }),
+        label(move || {
+            hunk.with(|hunk| {
+                hunk.as_ref()
+                    .map(|hunk| hunk.old_text.trim_end_matches('\n').to_string())
+                    .unwrap_or_default()
+            })
+        })
+        .style(move |s| {
+            let config = config.get();
+            let removed = config.color(LapceColor::SOURCE_CONTROL_REMOVED);
+            s.width_full()
+                .padding_horiz(10.0)
+                .font_family(config.editor.font_family.clone())
+                .font_size(config.editor.font_size() as f32)
+                .background(removed.with_alpha_factor(0.2))
+                .apply_if(
+                    hunk.with(|hunk| {
+                        hunk.as_ref().map_or(true, |hunk| hunk.old.is_empty())
+                    }),
+                    |s| s.hide(),
+                )
+        }),
+    ))
+    .style(move |s| {
+        let config = config.get();
+        let line_height = config.editor.line_height();
+        // Below the lines that replaced the old ones, or the line above where
+        // the old ones were
+        let top = hunk
+            .get()
+            .map(|hunk| {
+                let line = hunk.new.end.max(1);
+                let vline = if line <= ed.last_line() {
+                    ed.vline_of_line(line).get()
+                } else {
+                    ed.vline_of_line(ed.last_line()).get() + 1
+                };
+                vline * line_height
+            })
+            .unwrap_or(0) as f64
+            - viewport.get().y0;
+        s.absolute()
+            .flex_col()
+            .width_pct(100.0)
+            .inset_top(top)
+            .padding_vert(4.0)
+            .border_top(1.0)
+            .border_bottom(1.0)
+            .border_color(config.color(LapceColor::LAPCE_BORDER))
+            .background(config.color(LapceColor::EDITOR_BACKGROUND))
+            .apply_if(hunk.with(|hunk| hunk.is_none()), |s| s.hide())
+    })
+    .debug_name("Head Hunk")
+}
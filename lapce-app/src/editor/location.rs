@@ -32,3 +32,73 @@ pub fn to_offset(&self, text: &impl RopeText) -> usize {
         }
     }
 }
+
+/// The `path:line[:column]` around `index` in a line of text, the way
+/// compilers and other tools print locations, with the line and column
+/// counted from one.
+pub fn location_at(text: &str, index: usize) -> Option<(&str, EditorPosition)> {
+    let is_separator = |c: char| c.is_whitespace() || "\"'`()[]<>,;".contains(c);
+    let index = index.min(text.len());
+    if !text.is_char_boundary(index) {
+        return None;
+    }
+    let start = text[..index]
+        .rfind(is_separator)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let end = text[index..]
+        .find(is_separator)
+        .map(|i| index + i)
+        .unwrap_or(text.len());
+    let word = text[start..end].trim_end_matches([':', '.']);
+
+    let (rest, last) = word.rsplit_once(':')?;
+    let last: u32 = last.parse().ok()?;
+    let (path, line, column) = match rest.rsplit_once(':') {
+        Some((path, line)) => match line.parse::<u32>() {
+            Ok(line) => (path, line, Some(last)),
+            Err(_) => (rest, last, None),
+        },
+        None => (rest, last, None),
+    };
+    if path.is_empty() {
+        return None;
+    }
+    let line = line.saturating_sub(1);
+    let position = match column {
+        Some(column) => {
+            EditorPosition::Position(Position::new(line, column.saturating_sub(1)))
+        }
+        None => EditorPosition::Line(line as usize),
+    };
+    Some((path, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::{location_at, EditorPosition};
+
+    #[test]
+    fn test_location_at() {
+        let line = "  --> src/main.rs:3:5";
+        assert_eq!(
+            location_at(line, 10),
+            Some(("src/main.rs", EditorPosition::Position(Position::new(2, 4))))
+        );
+        assert_eq!(
+            location_at("src/lib.rs:12: warning", 3),
+            Some(("src/lib.rs", EditorPosition::Line(11)))
+        );
+        assert_eq!(
+            location_at(r"at C:\src\app.ts:7:1.", 8),
+            Some((
+                r"C:\src\app.ts",
+                EditorPosition::Position(Position::new(6, 0))
+            ))
+        );
+        assert_eq!(location_at(line, 1), None);
+        assert_eq!(location_at("no location here", 4), None);
+    }
+}
@@ -0,0 +1,198 @@
+use std::rc::Rc;
+
+use floem::{
+    reactive::{create_effect, create_memo, RwSignal, Scope},
+    style::CursorStyle,
+    views::{
+        container, label, scroll, stack, virtual_stack, Decorators,
+        VirtualDirection, VirtualItemSize,
+    },
+    View,
+};
+
+use super::{location::EditorPosition, EditorData};
+use crate::{
+    config::color::LapceColor,
+    log_mode::{log_filter, matching_lines},
+    text_input::TextInputBuilder,
+    window_tab::WindowTabData,
+};
+
+/// How many of the matching lines are shown before the list scrolls
+const MAX_VISIBLE_MATCHES: f64 = 8.0;
+
+/// The bar above an editor of a document in log mode, with the following of
+/// the end of the file and the lines matching a filter
+pub fn log_bar(
+    window_tab_data: Rc<WindowTabData>,
+    editor: RwSignal<EditorData>,
+) -> impl View {
+    let config = window_tab_data.common.config;
+    let doc = editor.with_untracked(|editor| editor.doc_signal());
+    let enabled = move || doc.with(|doc| doc.log.enabled.get());
+
+    // Go to the end of the file whenever it grows
+    create_effect(move |_| {
+        let doc = doc.get();
+        if !doc.log.enabled.get() || !doc.log.follow.get() {
+            return;
+        }
+        let len = doc.buffer.with(|buffer| buffer.len());
+        if doc.is_pristine() {
+            editor.get_untracked().go_to_position(
+                EditorPosition::Offset(len),
+                None,
+                None,
+            );
+        }
+    });
+
+    let cx = Scope::current();
+    let filter_input = TextInputBuilder::new().build(
+        cx,
+        window_tab_data.main_split.editors,
+        window_tab_data.common.clone(),
+    );
+    let filter_doc = filter_input.doc_signal();
+    let matches = create_memo(move |_| {
+        let pattern = filter_doc.with(|doc| doc.buffer.with(|b| b.to_string()));
+        let doc = doc.get();
+        if !doc.log.enabled.get() {
+            return im::Vector::new();
+        }
+        let Some(filter) = log_filter(pattern.trim()) else {
+            return im::Vector::new();
+        };
+        let text = doc.buffer.with(|buffer| buffer.to_string());
+        matching_lines(&text, &filter)
+            .map(|(line, content)| (line, content.to_string()))
+            .collect::<im::Vector<_>>()
+    });
+
+    let follow = move || doc.with(|doc| doc.log.follow.get());
+    let line_height = move || config.get().editor.line_height() as f64;
+
+    stack((
+        stack((
+            label(move || {
+                if follow() {
+                    "Following".to_string()
+                } else {
+                    "Follow".to_string()
+                }
+            })
+            .on_click_stop(move |_| {
+                doc.with_untracked(|doc| doc.log.follow.update(|f| *f = !*f));
+            })
+            .style(move |s| {
+                let config = config.get();
+                s.padding_horiz(8.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .apply_if(follow(), |s| {
+                        s.background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            }),
+            filter_input
+                .placeholder(|| "Filter Lines".to_string())
+                .keyboard_navigatable()
+                .style(move |s| {
+                    s.width(300.0)
+                        .margin_left(10.0)
+                        .border(1.0)
+                        .border_radius(4.0)
+                        .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+                }),
+            label(move || {
+                let count = matches.with(|matches| matches.len());
+                if count == 1 {
+                    "1 line".to_string()
+                } else {
+                    format!("{count} lines")
+                }
+            })
+            .style(move |s| {
+                s.margin_left(10.0)
+                    .color(config.get().color(LapceColor::EDITOR_DIM))
+                    .apply_if(
+                        filter_doc.with(|doc| doc.buffer.with(|b| b.is_empty())),
+                        |s| s.hide(),
+                    )
+            }),
+        ))
+        .style(|s| s.items_center().padding_horiz(10.0).padding_vert(4.0)),
+        container(
+            scroll(
+                virtual_stack(
+                    VirtualDirection::Vertical,
+                    VirtualItemSize::Fixed(Box::new(line_height)),
+                    move || matches.get(),
+                    |(line, _)| *line,
+                    move |(line, content)| {
+                        let text = format!("{:>6}  {content}", line + 1);
+                        let level =
+                            doc.with_untracked(|doc| doc.log.level(&content));
+                        label(move || text.clone())
+                            .on_click_stop(move |_| {
+                                let editor = editor.get_untracked();
+                                editor.doc().log.follow.set(false);
+                                editor.go_to_position(
+                                    EditorPosition::Line(line),
+                                    None,
+                                    None,
+                                );
+                            })
+                            .style(move |s| {
+                                let config = config.get();
+                                let color = level.map_or_else(
+                                    || config.color(LapceColor::EDITOR_FOREGROUND),
+                                    |level| level.color(&config),
+                                );
+                                s.height(line_height())
+                                    .items_center()
+                                    .padding_horiz(10.0)
+                                    .min_width_full()
+                                    .font_family(config.editor.font_family.clone())
+                                    .font_size(config.editor.font_size() as f32)
+                                    .color(color)
+                                    .hover(|s| {
+                                        s.cursor(CursorStyle::Pointer).background(
+                                            config.color(
+                                                LapceColor::PANEL_HOVERED_BACKGROUND,
+                                            ),
+                                        )
+                                    })
+                            })
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full()),
+            )
+            .style(|s| s.absolute().size_full()),
+        )
+        .style(move |s| {
+            let count = matches.with(|matches| matches.len()) as f64;
+            s.width_full()
+                .height(count.min(MAX_VISIBLE_MATCHES) * line_height())
+                .apply_if(count == 0.0, |s| s.hide())
+        }),
+    ))
+    .style(move |s| {
+        let config = config.get();
+        s.flex_col()
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(config.color(LapceColor::LAPCE_BORDER))
+            .background(config.color(LapceColor::PANEL_BACKGROUND))
+            .apply_if(!enabled(), |s| s.hide())
+    })
+    .debug_name("Log Bar")
+}
@@ -0,0 +1,258 @@
+//! The marks over the scroll bar of an editor of where in the document the
+//! changes, the find matches and the diagnostics are, which jump to them when
+//! clicked.
+
+use std::collections::HashSet;
+
+use floem::{
+    event::EventListener,
+    peniko::Color,
+    reactive::{create_memo, RwSignal},
+    style::CursorStyle,
+    views::{dyn_stack, empty, Decorators},
+    View,
+};
+use lapce_core::{buffer::rope_text::RopeText, cursor::CursorAffinity};
+use lsp_types::DiagnosticSeverity;
+
+use super::{location::EditorPosition, view::changes_color_iter, EditorData};
+use crate::config::color::LapceColor;
+
+/// The width of the ruler, which is that of the scroll bar
+pub const RULER_WIDTH: f64 = 10.0;
+
+/// The most marks of each kind, past which the rest aren't shown
+const MAX_MARKS: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum MarkKind {
+    Change,
+    Find,
+    Diagnostic,
+}
+
+impl MarkKind {
+    /// The horizontal position and width of the marks in the ruler, each kind
+    /// having its own column
+    fn column(self) -> (f64, f64) {
+        match self {
+            MarkKind::Change => (1.0, 3.0),
+            MarkKind::Find => (4.0, 3.0),
+            MarkKind::Diagnostic => (7.0, 3.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct RulerMark {
+    kind: MarkKind,
+    /// The position from the top of the ruler
+    top: f64,
+    height: f64,
+    color: Color,
+    /// Where to go when the mark is clicked
+    offset: usize,
+}
+
+/// The height of the content that the whole height of the ruler stands for,
+/// the same as the one of the scroll bar
+pub fn content_height(
+    last_line: usize,
+    line_height: usize,
+    viewport_height: f64,
+    scroll_beyond_last_line: bool,
+) -> f64 {
+    let height = (last_line * line_height) as f64;
+    if scroll_beyond_last_line {
+        height + viewport_height - line_height as f64
+    } else {
+        height
+    }
+}
+
+/// The position in a ruler `ruler_height` high of a point `y` down the content
+pub fn ruler_position(y: f64, content_height: f64, ruler_height: f64) -> f64 {
+    if content_height <= 0.0 {
+        return 0.0;
+    }
+    (y / content_height * ruler_height).clamp(0.0, ruler_height)
+}
+
+pub fn overview_ruler(editor: RwSignal<EditorData>) -> impl View {
+    let (doc, config, find_visual, kind, viewport) = editor.with_untracked(|e| {
+        (
+            e.doc_signal(),
+            e.common.config,
+            e.common.find.visual,
+            e.kind,
+            e.editor.viewport,
+        )
+    });
+
+    let marks = create_memo(move |_| {
+        if !kind.with(|kind| kind.is_normal()) {
+            return Vec::new();
+        }
+        let doc = doc.get();
+        if doc.content.with(|content| content.is_local()) {
+            return Vec::new();
+        }
+        let config = config.get();
+        let ruler_height = viewport.with(|viewport| viewport.height());
+        let line_height = config.editor.line_height();
+        let last_line = doc.buffer.with(|buffer| buffer.last_line());
+        let content_height = content_height(
+            last_line,
+            line_height,
+            ruler_height,
+            config.editor.scroll_beyond_last_line,
+        );
+        let ed = editor.with_untracked(|e| e.editor.clone());
+        let top_of_offset = |offset: usize| {
+            let rvline = ed.rvline_of_offset(offset, CursorAffinity::Forward);
+            let y = (ed.vline_of_rvline(rvline).get() * line_height) as f64;
+            ruler_position(y, content_height, ruler_height).round()
+        };
+
+        let mut marks: Vec<RulerMark> = Vec::new();
+        // Marks of the same kind that would overlap are shown as one
+        let mut rows = HashSet::new();
+        let mut push = |marks: &mut Vec<RulerMark>, mark: RulerMark| {
+            if rows.insert((mark.kind, mark.top as i64 / 2)) {
+                marks.push(mark);
+            }
+        };
+
+        let changes = doc.head_changes().get();
+        let mut line = 0;
+        for (len, color, modified) in
+            changes_color_iter(&changes, &config).take(MAX_MARKS)
+        {
+            let start_line = line;
+            line += len;
+            let Some(color) = color else {
+                continue;
+            };
+            if modified {
+                // The removal just before is part of the modification
+                marks.pop();
+            }
+            let offset = doc
+                .buffer
+                .with_untracked(|buffer| buffer.offset_of_line(start_line));
+            let height = ruler_position(
+                (len * line_height) as f64,
+                content_height,
+                ruler_height,
+            );
+            marks.push(RulerMark {
+                kind: MarkKind::Change,
+                top: top_of_offset(offset),
+                height: height.max(3.0),
+                color,
+                offset,
+            });
+        }
+
+        if find_visual.get() {
+            let color = config.color(LapceColor::EDITOR_FIND_MATCH_MARK);
+            doc.find_result.occurrences.with(|occurrences| {
+                for region in occurrences.regions().iter().take(MAX_MARKS) {
+                    push(
+                        &mut marks,
+                        RulerMark {
+                            kind: MarkKind::Find,
+                            top: top_of_offset(region.min()),
+                            height: 2.0,
+                            color,
+                            offset: region.min(),
+                        },
+                    );
+                }
+            });
+        }
+
+        // Errors first, so that they aren't hidden by the warnings next to them
+        doc.diagnostics.diagnostics_span.with(|span| {
+            for error in [true, false] {
+                let color = if error {
+                    config.color(LapceColor::LAPCE_ERROR)
+                } else {
+                    config.color(LapceColor::LAPCE_WARN)
+                };
+                let offsets = span
+                    .iter()
+                    .filter(|(_, diag)| {
+                        diag.severity < Some(DiagnosticSeverity::HINT)
+                            && (diag.severity == Some(DiagnosticSeverity::ERROR))
+                                == error
+                    })
+                    .map(|(iv, _)| iv.start())
+                    .take(MAX_MARKS);
+                for offset in offsets {
+                    push(
+                        &mut marks,
+                        RulerMark {
+                            kind: MarkKind::Diagnostic,
+                            top: top_of_offset(offset),
+                            height: 3.0,
+                            color,
+                            offset,
+                        },
+                    );
+                }
+            }
+        });
+
+        marks
+    });
+
+    dyn_stack(
+        move || marks.get(),
+        |mark| (mark.kind, mark.offset, mark.top as i64),
+        move |mark| {
+            let (left, width) = mark.kind.column();
+            empty()
+                .on_event_stop(EventListener::PointerDown, move |_| {
+                    editor.get_untracked().go_to_position(
+                        EditorPosition::Offset(mark.offset),
+                        None,
+                        None,
+                    );
+                })
+                .style(move |s| {
+                    s.absolute()
+                        .inset_left(left)
+                        .inset_top(mark.top)
+                        .width(width)
+                        .height(mark.height)
+                        .background(mark.color)
+                        .cursor(CursorStyle::Pointer)
+                })
+        },
+    )
+    .style(|s| {
+        s.absolute()
+            .inset_right(0.0)
+            .width(RULER_WIDTH)
+            .height_full()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_height, ruler_position};
+
+    #[test]
+    fn test_ruler_position() {
+        // 100 lines of 20px, in a viewport 500px high
+        let height = content_height(100, 20, 500.0, false);
+        assert_eq!(height, 2000.0);
+        assert_eq!(ruler_position(1000.0, height, 500.0), 250.0);
+        assert_eq!(ruler_position(4000.0, height, 500.0), 500.0);
+
+        let height = content_height(100, 20, 500.0, true);
+        assert_eq!(height, 2480.0);
+        assert_eq!(ruler_position(0.0, 0.0, 500.0), 0.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! The geometry of the lines an editor shows, for the views around the text,
+//! such as the gutter, to agree with it on where the lines are rather than
+//! each working it out from the screen lines again.
+
+use std::ops::Range;
+
+use floem::views::editor::view::ScreenLines;
+
+/// A visual line shown in the editor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VisibleLine {
+    /// The buffer line it is part of
+    pub line: usize,
+    /// Which of the visual lines of the buffer line it is, when it wraps
+    pub line_index: usize,
+    /// The offsets of its text
+    pub start: usize,
+    pub end: usize,
+    /// The position of its top, in the coordinates of the whole text
+    pub y: f64,
+    /// The position of the top of the first visual line of its buffer line
+    pub line_y: f64,
+}
+
+/// The visual lines shown in an editor, from the top
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScreenLinesView {
+    lines: Vec<VisibleLine>,
+    line_height: f64,
+    /// The buffer lines between the shown ones that aren't shown
+    folded: Vec<Range<usize>>,
+}
+
+impl ScreenLinesView {
+    pub fn new(lines: Vec<VisibleLine>, line_height: f64) -> Self {
+        let folded = lines
+            .windows(2)
+            .filter(|pair| pair[1].line > pair[0].line + 1)
+            .map(|pair| pair[0].line + 1..pair[1].line)
+            .collect();
+        Self {
+            lines,
+            line_height,
+            folded,
+        }
+    }
+
+    pub fn from_screen_lines(screen_lines: &ScreenLines, line_height: f64) -> Self {
+        let lines = screen_lines
+            .iter_line_info()
+            .map(|info| VisibleLine {
+                line: info.vline_info.rvline.line,
+                line_index: info.vline_info.rvline.line_index,
+                start: info.vline_info.interval.start,
+                end: info.vline_info.interval.end,
+                y: info.vline_y,
+                line_y: info.y,
+            })
+            .collect();
+        Self::new(lines, line_height)
+    }
+
+    pub fn lines(&self) -> &[VisibleLine] {
+        &self.lines
+    }
+
+    /// The buffer lines from the first shown to the last shown
+    pub fn visible_lines(&self) -> Option<Range<usize>> {
+        let first = self.lines.first()?;
+        let last = self.lines.last()?;
+        Some(first.line..last.line + 1)
+    }
+
+    /// The buffer lines within the visible ones that aren't shown
+    pub fn folded_regions(&self) -> &[Range<usize>] {
+        &self.folded
+    }
+
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.folded.iter().any(|range| range.contains(&line))
+    }
+
+    /// The buffer lines shown, each with the top of its first visual line,
+    /// even when only the visual lines after it are shown
+    pub fn iter_lines_y(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| *i == 0 || self.lines[i - 1].line != line.line)
+            .map(|(_, line)| (line.line, line.line_y))
+    }
+
+    pub fn y_of_line(&self, line: usize) -> Option<f64> {
+        self.lines.iter().find(|l| l.line == line).map(|l| l.y)
+    }
+
+    /// The top of the visual line that `offset` is on
+    pub fn y_of_offset(&self, offset: usize) -> Option<f64> {
+        self.lines
+            .iter()
+            .find(|line| (line.start..line.end).contains(&offset))
+            // The end of the last line, which no line contains
+            .or_else(|| self.lines.iter().rev().find(|line| line.end == offset))
+            .map(|line| line.y)
+    }
+
+    /// The visual line at `y`, in the coordinates of the whole text
+    pub fn visible_line_of_y(&self, y: f64) -> Option<&VisibleLine> {
+        self.lines
+            .iter()
+            .find(|line| y >= line.y && y < line.y + self.line_height)
+    }
+
+    pub fn line_of_y(&self, y: f64) -> Option<usize> {
+        self.visible_line_of_y(y).map(|line| line.line)
+    }
+
+    /// The start of the visual line at `y`
+    pub fn offset_of_y(&self, y: f64) -> Option<usize> {
+        self.visible_line_of_y(y).map(|line| line.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScreenLinesView, VisibleLine};
+
+    fn visible_line(
+        line: usize,
+        line_index: usize,
+        start: usize,
+        end: usize,
+        y: f64,
+    ) -> VisibleLine {
+        VisibleLine {
+            line,
+            line_index,
+            start,
+            end,
+            y,
+            line_y: y - line_index as f64 * 20.0,
+        }
+    }
+
+    #[test]
+    fn test_screen_lines_view_wrap() {
+        // Line 0 is scrolled to its second visual line, and line 1 wraps
+        // into two visual lines
+        let view = ScreenLinesView::new(
+            vec![
+                visible_line(0, 1, 0, 5, 0.0),
+                visible_line(1, 0, 5, 15, 20.0),
+                visible_line(1, 1, 15, 22, 40.0),
+                visible_line(2, 0, 22, 25, 60.0),
+            ],
+            20.0,
+        );
+        assert_eq!(view.visible_lines(), Some(0..3));
+        assert!(view.folded_regions().is_empty());
+        assert_eq!(
+            view.iter_lines_y().collect::<Vec<_>>(),
+            [(0, -20.0), (1, 20.0), (2, 60.0)]
+        );
+        assert_eq!(view.y_of_offset(16), Some(40.0));
+        assert_eq!(view.y_of_offset(25), Some(60.0));
+        assert_eq!(view.y_of_offset(30), None);
+        assert_eq!(view.line_of_y(45.0), Some(1));
+        assert_eq!(view.offset_of_y(45.0), Some(15));
+        assert_eq!(view.offset_of_y(80.0), None);
+    }
+
+    #[test]
+    fn test_screen_lines_view_fold() {
+        // Lines 2 to 4 are folded into line 1
+        let view = ScreenLinesView::new(
+            vec![
+                visible_line(0, 0, 0, 5, 0.0),
+                visible_line(1, 0, 5, 10, 20.0),
+                visible_line(5, 0, 30, 35, 40.0),
+            ],
+            20.0,
+        );
+        assert_eq!(view.folded_regions(), [2..5]);
+        assert!(view.is_folded(3));
+        assert!(!view.is_folded(5));
+        assert_eq!(view.y_of_line(3), None);
+        assert_eq!(view.y_of_line(5), Some(40.0));
+        assert_eq!(view.line_of_y(41.0), Some(5));
+    }
+}
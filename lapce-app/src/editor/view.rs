@@ -3,6 +3,7 @@
 use floem::{
     action::{set_ime_allowed, set_ime_cursor_area},
     context::{PaintCx, StyleCx},
+    cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout},
     event::{Event, EventListener, EventPropagation},
     keyboard::Modifiers,
     peniko::{
@@ -31,7 +32,7 @@
         },
         empty, label,
         scroll::{scroll, HideBar, PropagatePointerWheel},
-        stack, svg, Decorators,
+        stack, svg, tooltip, Decorators,
     },
     Renderer, View, ViewId,
 };
@@ -39,48 +40,123 @@
 use lapce_core::{
     buffer::{diff::DiffLines, rope_text::RopeText, Buffer},
     cursor::{CursorAffinity, CursorMode},
+    mode::VisualMode,
 };
-use lapce_rpc::dap_types::{DapId, SourceBreakpoint};
 use lapce_xi_rope::find::CaseMatching;
 
-use super::{gutter::editor_gutter_view, DocSignal, EditorData};
+use super::{
+    gutter::editor_gutter_view, head_hunk::head_hunk_view, log_bar::log_bar,
+    overview_ruler::overview_ruler, DocSignal, EditorData,
+};
 use crate::{
-    app::clickable_icon,
+    accessibility::EditorAccessibilitySnapshot,
+    app::{clickable_icon, tooltip_tip},
+    blame::{heat_color, HEAT_LEGEND_STEPS},
     command::InternalCommand,
-    config::{color::LapceColor, editor::WrapStyle, icon::LapceIcons, LapceConfig},
+    config::{
+        color::LapceColor,
+        editor::{MatchingBracketStyle, WrapStyle},
+        icon::LapceIcons,
+        language::LanguageEditorConfig,
+        LapceConfig,
+    },
     debug::LapceBreakpoint,
     doc::DocContent,
+    main_split::MainSplitData,
+    palette::item::git_commit_hint,
+    perf::{self, PerfSubsystem},
     text_input::TextInputBuilder,
     window_tab::{Focus, WindowTabData},
     workspace::LapceWorkspace,
 };
 
+/// The longest selection whose other occurrences on the screen are highlighted
+const SELECTION_MATCH_MAX_LEN: usize = 256;
+/// The columns of the segments very long lines are cut into, in the editors
+/// which don't wrap otherwise
+const LONG_LINE_SEGMENT_COLUMNS: usize = 1000;
+
 struct StickyHeaderInfo {
     sticky_lines: Vec<usize>,
     last_sticky_should_scroll: bool,
     y_diff: f64,
 }
 
-fn editor_wrap(config: &LapceConfig) -> WrapMethod {
+fn editor_wrap(
+    config: &LapceConfig,
+    editor_config: &LanguageEditorConfig,
+    wrap_style: Option<WrapStyle>,
+    has_long_lines: bool,
+) -> WrapMethod {
     /// Minimum width that we'll allow the view to be wrapped at.
     const MIN_WRAPPED_WIDTH: f32 = 100.0;
 
-    match config.editor.wrap_style {
+    // A line of a minified file laid out as a single row is far wider than
+    // the screen, so it is cut into segments, unless wrapping was turned off
+    // in the editor itself
+    if wrap_style.is_none()
+        && editor_config.wrap_style == WrapStyle::None
+        && has_long_lines
+    {
+        let advance = glyph_advance(config, editor_config.font_size);
+        return WrapMethod::WrapWidth {
+            width: LONG_LINE_SEGMENT_COLUMNS as f32 * advance,
+        };
+    }
+
+    match wrap_style.unwrap_or(editor_config.wrap_style) {
         WrapStyle::None => WrapMethod::None,
         WrapStyle::EditorWidth => WrapMethod::EditorWidth,
+        // The text layouts only wrap at a width, so the column is turned into
+        // the width of as many glyphs of the editor font, which keeps it at
+        // the same column whatever the font size
+        WrapStyle::WrapColumn => {
+            let advance = glyph_advance(config, editor_config.font_size);
+            WrapMethod::WrapWidth {
+                width: (editor_config.wrap_column.max(1) as f32 * advance + 0.5)
+                    .max(MIN_WRAPPED_WIDTH),
+            }
+        }
         WrapStyle::WrapWidth => WrapMethod::WrapWidth {
-            width: (config.editor.wrap_width as f32).max(MIN_WRAPPED_WIDTH),
+            width: (editor_config.wrap_width as f32).max(MIN_WRAPPED_WIDTH),
         },
     }
 }
 
+/// The width of an indentation level in the editor font
+fn indent_unit_width(config: &LapceConfig, indent_unit: &str) -> f64 {
+    let family: Vec<FamilyOwned> =
+        FamilyOwned::parse_list(&config.editor.font_family).collect();
+    let attrs = Attrs::new()
+        .family(&family)
+        .font_size(config.editor.font_size() as f32);
+    let mut text_layout = TextLayout::new();
+    // Followed by a glyph, as trailing whitespace may not be measured
+    text_layout.set_text(&format!("{indent_unit}a"), AttrsList::new(attrs));
+    text_layout.hit_position(indent_unit.len()).point.x
+}
+
+/// The width of a glyph of the editor font, the monospace font the columns
+/// are counted in
+fn glyph_advance(config: &LapceConfig, font_size: usize) -> f32 {
+    let family: Vec<FamilyOwned> =
+        FamilyOwned::parse_list(&config.editor.font_family).collect();
+    let attrs = Attrs::new().family(&family).font_size(font_size as f32);
+    let mut text_layout = TextLayout::new();
+    // Measured over several glyphs, as a single one rounds its width
+    text_layout.set_text("WWWWWWWWWW", AttrsList::new(attrs));
+    text_layout.size().width as f32 / 10.0
+}
+
 pub fn editor_style(
     config: ReadSignal<Arc<LapceConfig>>,
     doc: DocSignal,
+    wrap_style: RwSignal<Option<WrapStyle>>,
     s: Style,
 ) -> Style {
     let config = config.get();
     let doc = doc.get();
+    let wrap_style = wrap_style.get();
 
     s.set(
         IndentStyleProp,
@@ -109,14 +185,26 @@ pub fn editor_style(
         PreeditUnderlineColor,
         config.color(LapceColor::EDITOR_FOREGROUND),
     )
-    .set(ShowIndentGuide, config.editor.show_indent_guide)
+    // Rainbow indent guides are painted by `paint_rainbow_indent_guides`
+    .set(
+        ShowIndentGuide,
+        config.editor.show_indent_guide && !config.editor.rainbow_indent_guides,
+    )
     .set(Modal, config.core.modal)
     .set(
         ModalRelativeLine,
         config.editor.modal_mode_relative_line_numbers,
     )
     .set(SmartTab, config.editor.smart_tab)
-    .set(WrapProp, editor_wrap(&config))
+    .set(
+        WrapProp,
+        editor_wrap(
+            &config,
+            &doc.editor_config(),
+            wrap_style,
+            doc.has_long_lines(),
+        ),
+    )
     .set(
         CursorSurroundingLines,
         config.editor.cursor_surrounding_lines,
@@ -132,6 +220,8 @@ pub struct EditorView {
     viewport: RwSignal<Rect>,
     debug_breakline: Memo<Option<(usize, PathBuf)>>,
     sticky_header_info: StickyHeaderInfo,
+    /// Whether it was painted since its document loaded
+    painted: bool,
 }
 
 pub fn editor_view(
@@ -146,6 +236,7 @@ pub fn editor_view(
 
     let doc = e_data.doc_signal();
     let view_kind = e_data.kind;
+    let wrap_style = e_data.wrap_style;
     let screen_lines = e_data.screen_lines();
     create_effect(move |_| {
         doc.track();
@@ -154,10 +245,13 @@ pub fn editor_view(
     });
 
     let hide_cursor = e_data.common.window_common.hide_cursor;
+    let drag_selection = e_data.drag_selection;
     create_effect(move |_| {
         hide_cursor.track();
+        drag_selection.track();
         let occurrences = doc.with(|doc| doc.find_result.occurrences);
         occurrences.track();
+        doc.with(|doc| doc.document_highlights).track();
         id.request_paint();
     });
 
@@ -208,6 +302,15 @@ pub fn editor_view(
         rev
     });
 
+    // Only the injected languages around what the editors show are parsed
+    let editor = e_data.clone();
+    create_effect(move |_| {
+        let doc = doc.get();
+        if let Some(lines) = editor.screen_lines_view().visible_lines() {
+            doc.parse_injections_around(lines);
+        }
+    });
+
     let ed1 = e_data.editor.clone();
     let ed2 = ed1.clone();
     let ed3 = ed1.clone();
@@ -242,6 +345,30 @@ pub fn editor_view(
         }
     });
 
+    let accessibility = e_data.common.accessibility.clone();
+    create_effect(move |last: Option<Option<EditorAccessibilitySnapshot>>| {
+        if !config.with(|config| config.core.screen_reader_support)
+            || !is_active.get()
+        {
+            return None;
+        }
+
+        let buffer = doc.with(|doc| doc.buffer);
+        buffer.with(|buffer| {
+            let snapshot = screen_lines.with(|screen_lines| {
+                cursor.with(|cursor| {
+                    EditorAccessibilitySnapshot::new(buffer, screen_lines, cursor)
+                })
+            });
+            if let Some(event) =
+                snapshot.event_since(buffer, last.flatten().as_ref())
+            {
+                accessibility.announce(event);
+            }
+            Some(snapshot)
+        })
+    });
+
     let doc = e_data.doc_signal();
     EditorView {
         id,
@@ -255,6 +382,7 @@ pub fn editor_view(
             last_sticky_should_scroll: false,
             y_diff: 0.0,
         },
+        painted: false,
     }
     .on_event(EventListener::ImePreedit, move |event| {
         if !is_active.get_untracked() {
@@ -283,7 +411,7 @@ pub fn editor_view(
         EventPropagation::Stop
     })
     .class(EditorViewClass)
-    .style(move |s| editor_style(config, doc, s))
+    .style(move |s| editor_style(config, doc, wrap_style, s))
 }
 
 impl EditorView {
@@ -499,6 +627,8 @@ fn paint_find(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
         doc.update_find();
         let start = ed.offset_of_line(min_line);
         let end = ed.offset_of_line(max_line + 1);
+        // The match the cursor is on, which find next and previous go to
+        let cursor_offset = e_data.cursor().with_untracked(|c| c.offset());
 
         // TODO: The selection rect creation logic for find is quite similar to the version
         // within insert cursor. It would be good to deduplicate it.
@@ -508,6 +638,7 @@ fn paint_find(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
         }) {
             let start = region.min();
             let end = region.max();
+            let active = (start..=end).contains(&cursor_offset);
 
             // TODO(minor): the proper affinity here should probably be tracked by selregion
             let (start_rvline, start_col) =
@@ -555,21 +686,286 @@ fn paint_find(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
                     .x;
 
                 if !rvline_info.is_empty() && start != end && left_col != right_col {
-                    rects.push(
+                    rects.push((
                         Size::new(x1 - x0, line_height)
                             .to_rect()
                             .with_origin(Point::new(x0, line_info.vline_y)),
-                    );
+                        active,
+                    ));
                 }
             }
         }
 
         let color = config.color(LapceColor::EDITOR_FOREGROUND);
-        for rect in rects {
+        let active_color = config.color(LapceColor::EDITOR_FIND_MATCH_ACTIVE);
+        for (rect, active) in rects {
+            if active {
+                cx.fill(&rect, active_color, 0.0);
+            }
             cx.stroke(&rect, color, 1.0);
         }
     }
 
+    /// Fill the background of the occurrences of the symbol under the cursor,
+    /// in another color where it is written to.
+    fn paint_document_highlights(
+        &self,
+        cx: &mut PaintCx,
+        screen_lines: &ScreenLines,
+    ) {
+        let doc = self.editor.doc();
+        let Some(highlights) = doc.document_highlights.get_untracked() else {
+            return;
+        };
+        if highlights.rev != doc.rev() {
+            return;
+        }
+        let (Some(first), Some(last)) =
+            (screen_lines.lines.first(), screen_lines.lines.last())
+        else {
+            return;
+        };
+        let ed = &self.editor.editor;
+        let start = ed.offset_of_line(first.line);
+        let end = ed.offset_of_line(last.line + 1);
+        let config = self.editor.common.config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+
+        for range in highlights.ranges {
+            if range.end < start || range.start > end {
+                continue;
+            }
+            let Some(rect) =
+                self.range_rect(screen_lines, range.start, range.end, line_height)
+            else {
+                continue;
+            };
+            let color = if range.write {
+                LapceColor::EDITOR_OCCURRENCE_WRITE
+            } else {
+                LapceColor::EDITOR_OCCURRENCE_READ
+            };
+            cx.fill(&rect, config.color(color), 0.0);
+        }
+    }
+
+    /// Fill the background of the other occurrences of the selected text on
+    /// the screen, while a single piece of one line is selected.
+    fn paint_selection_matches(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
+        let config = self.editor.common.config.get_untracked();
+        if !config.editor.highlight_selection_matches {
+            return;
+        }
+        let doc = self.editor.doc();
+        let selection = self.editor.cursor().with_untracked(|cursor| match &cursor
+            .mode
+        {
+            CursorMode::Insert(selection) => match selection.regions() {
+                [region] if !region.is_caret() => Some((region.min(), region.max())),
+                _ => None,
+            },
+            CursorMode::Visual {
+                start,
+                end,
+                mode: VisualMode::Normal,
+            } => Some((
+                *start.min(end),
+                doc.buffer.with_untracked(|buffer| {
+                    buffer.next_grapheme_offset(*start.max(end), 1, buffer.len())
+                }),
+            )),
+            _ => None,
+        });
+        let Some((start, end)) = selection else {
+            return;
+        };
+        if end - start > SELECTION_MATCH_MAX_LEN {
+            return;
+        }
+        let (Some(first), Some(last)) =
+            (screen_lines.lines.first(), screen_lines.lines.last())
+        else {
+            return;
+        };
+        let ed = &self.editor.editor;
+        // Only the text on the screen is searched
+        let visible_start = ed.offset_of_line(first.line);
+        let visible_end = ed.offset_of_line(last.line + 1);
+        let (selected, visible) = doc.buffer.with_untracked(|buffer| {
+            (
+                buffer.text().slice_to_cow(start..end).to_string(),
+                buffer
+                    .text()
+                    .slice_to_cow(visible_start..visible_end)
+                    .to_string(),
+            )
+        });
+        if selected.trim().is_empty() || selected.contains('\n') {
+            return;
+        }
+
+        let line_height = config.editor.line_height() as f64;
+        let color = config
+            .color(LapceColor::EDITOR_SELECTION)
+            .with_alpha_factor(0.5);
+        for (index, _) in visible.match_indices(&selected) {
+            let match_start = visible_start + index;
+            if match_start == start {
+                continue;
+            }
+            if let Some(rect) = self.range_rect(
+                screen_lines,
+                match_start,
+                match_start + selected.len(),
+                line_height,
+            ) {
+                cx.fill(&rect, color, 0.0);
+            }
+        }
+    }
+
+    /// The rectangle of a range on the screen, if it is within one visual line
+    fn range_rect(
+        &self,
+        screen_lines: &ScreenLines,
+        start: usize,
+        end: usize,
+        line_height: f64,
+    ) -> Option<Rect> {
+        let ed = &self.editor.editor;
+        let (start_rvline, start_col) =
+            ed.rvline_col_of_offset(start, CursorAffinity::Forward);
+        let (end_rvline, end_col) =
+            ed.rvline_col_of_offset(end, CursorAffinity::Backward);
+        if start_rvline != end_rvline {
+            return None;
+        }
+        let line_info = screen_lines.info(start_rvline)?;
+        let line = start_rvline.line;
+        let x0 = ed
+            .line_point_of_line_col(line, start_col, CursorAffinity::Forward, true)
+            .x;
+        let x1 = ed
+            .line_point_of_line_col(line, end_col, CursorAffinity::Backward, true)
+            .x;
+        Some(
+            Size::new(x1 - x0, line_height)
+                .to_rect()
+                .with_origin(Point::new(x0, line_info.vline_y)),
+        )
+    }
+
+    /// Outline the ranges that are being edited together, so that it is
+    /// clear which text the other cursors are changing.
+    fn paint_linked_editing(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
+        let Some(linked) = self.editor.linked_editing.get_untracked() else {
+            return;
+        };
+        let ed = &self.editor.editor;
+        let config = self.editor.common.config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+        let color = config.color(LapceColor::EDITOR_LINKED_EDITING_RANGE);
+
+        for (start, end) in linked.ranges {
+            let (start_rvline, start_col) =
+                ed.rvline_col_of_offset(start, CursorAffinity::Forward);
+            let (end_rvline, end_col) =
+                ed.rvline_col_of_offset(end, CursorAffinity::Backward);
+            // The linked ranges are names, which don't span lines
+            if start_rvline != end_rvline {
+                continue;
+            }
+            let Some(line_info) = screen_lines
+                .iter_line_info()
+                .find(|info| info.vline_info.rvline == start_rvline)
+            else {
+                continue;
+            };
+            let line = start_rvline.line;
+            let x0 = ed
+                .line_point_of_line_col(
+                    line,
+                    start_col,
+                    CursorAffinity::Forward,
+                    true,
+                )
+                .x;
+            let x1 = ed
+                .line_point_of_line_col(
+                    line,
+                    end_col,
+                    CursorAffinity::Backward,
+                    true,
+                )
+                .x;
+            // Keep an emptied range visible
+            let width = (x1 - x0).max(2.0);
+            let rect = Size::new(width, line_height)
+                .to_rect()
+                .with_origin(Point::new(x0, line_info.vline_y));
+            cx.stroke(&rect, color, 1.0);
+        }
+    }
+
+    /// Paint the carets of the cursor, which are painted here rather than by
+    /// floem so that a block caret covers the whole of a wide glyph.
+    fn paint_cursor_caret(
+        &self,
+        cx: &mut PaintCx,
+        is_active: bool,
+        screen_lines: &ScreenLines,
+    ) {
+        let ed = &self.editor.editor;
+        if !is_active || ed.cursor_info.hidden.get_untracked() {
+            return;
+        }
+        let config = self.editor.common.config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+        let caret_color = config.color(LapceColor::EDITOR_CARET);
+        self.editor.cursor().with_untracked(|cursor| {
+            let block = !cursor.is_insert();
+            for (_, end) in cursor.regions_iter() {
+                let LineRegion { x, width, rvline } =
+                    caret_region(ed, end, block, cursor.affinity);
+                if let Some(info) = screen_lines.info(rvline) {
+                    let rect = Rect::from_origin_size(
+                        (x, info.vline_y),
+                        (width, line_height),
+                    );
+                    cx.fill(&rect, caret_color, 0.0);
+                }
+            }
+        });
+    }
+
+    /// Show where the selected text being dragged would be dropped.
+    fn paint_drop_caret(&self, cx: &mut PaintCx, screen_lines: &ScreenLines) {
+        let Some(drop) = self
+            .editor
+            .drag_selection
+            .with_untracked(|drag| drag.and_then(|drag| drag.drop))
+        else {
+            return;
+        };
+        let ed = &self.editor.editor;
+        let (rvline, col) = ed.rvline_col_of_offset(drop, CursorAffinity::Forward);
+        let Some(line_info) = screen_lines
+            .iter_line_info()
+            .find(|info| info.vline_info.rvline == rvline)
+        else {
+            return;
+        };
+        let config = self.editor.common.config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+        let x = ed
+            .line_point_of_line_col(rvline.line, col, CursorAffinity::Forward, true)
+            .x;
+        let rect = Size::new(2.0, line_height)
+            .to_rect()
+            .with_origin(Point::new(x - 1.0, line_info.vline_y));
+        cx.fill(&rect, config.color(LapceColor::EDITOR_CARET), 0.0);
+    }
+
     fn paint_sticky_headers(
         &self,
         cx: &mut PaintCx,
@@ -706,45 +1102,16 @@ fn paint_scroll_bar(
             config.color(LapceColor::LAPCE_SCROLL_BAR),
             0.0,
         );
-
-        if !self.editor.kind.get_untracked().is_normal() {
-            return;
-        }
-
-        let doc = self.editor.doc();
-        let total_len = doc.buffer.with_untracked(|buffer| buffer.last_line());
-        let changes = doc.head_changes().get_untracked();
-        let total_height = viewport.height();
-        let total_width = viewport.width();
-        let line_height = config.editor.line_height();
-        let content_height = if config.editor.scroll_beyond_last_line {
-            (total_len * line_height) as f64 + total_height - line_height as f64
-        } else {
-            (total_len * line_height) as f64
-        };
-
-        let colors = changes_colors_all(&config, &self.editor.editor, changes);
-        for (y, height, _, color) in colors {
-            let y = y / content_height * total_height;
-            let height = ((height * line_height) as f64 / content_height
-                * total_height)
-                .max(3.0);
-            let rect = Rect::ZERO.with_size(Size::new(3.0, height)).with_origin(
-                Point::new(
-                    viewport.x0 + total_width - BAR_WIDTH + 1.0,
-                    y + viewport.y0,
-                ),
-            );
-            cx.fill(&rect, color, 0.0);
-        }
     }
 
-    /// Paint a highlight around the characters at the given positions.
+    /// Paint a highlight around, or behind, the characters at the given
+    /// positions.
     fn paint_char_highlights(
         &self,
         cx: &mut PaintCx,
         screen_lines: &ScreenLines,
         highlight_line_cols: impl Iterator<Item = (RVLine, usize)>,
+        style: MatchingBracketStyle,
     ) {
         let editor = &self.editor.editor;
         let config = self.editor.common.config.get_untracked();
@@ -775,7 +1142,18 @@ fn paint_char_highlights(
 
                 let rect = Rect::new(x0, y0, x1, y1);
 
-                cx.stroke(&rect, config.color(LapceColor::EDITOR_FOREGROUND), 1.0);
+                match style {
+                    MatchingBracketStyle::Box => cx.stroke(
+                        &rect,
+                        config.color(LapceColor::EDITOR_FOREGROUND),
+                        1.0,
+                    ),
+                    MatchingBracketStyle::Background => cx.fill(
+                        &rect,
+                        config.color(LapceColor::EDITOR_MATCHING_BRACKET_BACKGROUND),
+                        0.0,
+                    ),
+                }
             }
         }
     }
@@ -958,10 +1336,24 @@ fn paint_bracket_highlights_scope_lines(
             });
 
             if config.editor.highlight_matching_brackets {
+                // The bracket next to the cursor takes precedence over the
+                // ones enclosing it
+                let adjacent_line_cols = e_data
+                    .doc_signal()
+                    .with_untracked(|doc| doc.find_adjacent_brackets(offset))
+                    .map(|(start, end)| {
+                        [start, end].map(|offset| {
+                            ed.rvline_col_of_offset(offset, CursorAffinity::Forward)
+                        })
+                    });
                 self.paint_char_highlights(
                     cx,
                     screen_lines,
-                    bracket_line_cols.into_iter().flatten(),
+                    adjacent_line_cols
+                        .or(bracket_line_cols)
+                        .into_iter()
+                        .flatten(),
+                    config.editor.matching_bracket_style,
                 );
             }
 
@@ -978,6 +1370,126 @@ fn paint_bracket_highlights_scope_lines(
             }
         }
     }
+
+    /// Paint the indent guides in the colors of their indentation levels, in
+    /// place of the single colored ones of `paint_text`, if rainbow indent
+    /// guides are enabled.
+    fn paint_rainbow_indent_guides(
+        &self,
+        cx: &mut PaintCx,
+        screen_lines: &ScreenLines,
+    ) {
+        let config = self.editor.common.config.get_untracked();
+        if !config.editor.show_indent_guide || !config.editor.rainbow_indent_guides {
+            return;
+        }
+        let colors = config.rainbow_indent_guide_colors();
+        if colors.is_empty() {
+            return;
+        }
+
+        let ed = &self.editor.editor;
+        let indent_unit = self
+            .editor
+            .doc()
+            .buffer
+            .with_untracked(|buffer| buffer.indent_unit());
+        let indent_width = indent_unit_width(&config, indent_unit);
+        if indent_width <= 0.0 {
+            return;
+        }
+
+        for (line, y) in screen_lines.iter_lines_y() {
+            let text_layout = ed.text_layout(line);
+            let line_height = f64::from(ed.line_height(line));
+            let mut x = 0.0;
+            let mut level = 0;
+            while x + 1.0 < text_layout.indent {
+                let guide =
+                    Line::new(Point::new(x, y), Point::new(x, y + line_height));
+                cx.stroke(&guide, colors[level % colors.len()], 1.0);
+                x += indent_width;
+                level += 1;
+            }
+        }
+    }
+
+    /// Paint a guide from the opening to the closing bracket of the block
+    /// containing the cursor, over its indent guide, if the setting is
+    /// enabled.
+    fn paint_bracket_pair_guide(
+        &self,
+        cx: &mut PaintCx,
+        viewport: Rect,
+        screen_lines: &ScreenLines,
+    ) {
+        let config = self.editor.common.config.get_untracked();
+        if !config.editor.show_bracket_pair_guide {
+            return;
+        }
+
+        let e_data = &self.editor;
+        let ed = &e_data.editor;
+        let doc = e_data.doc();
+        let offset = ed.cursor.with_untracked(|cursor| cursor.mode.offset());
+        let Some((start_offset, end_offset)) = doc.find_enclosing_brackets(offset)
+        else {
+            return;
+        };
+        let (start, _) =
+            ed.rvline_col_of_offset(start_offset, CursorAffinity::Forward);
+        let (end, _) = ed.rvline_col_of_offset(end_offset, CursorAffinity::Forward);
+        if start.line == end.line {
+            return;
+        }
+
+        // From below the opening bracket to above the closing one, the parts
+        // that are off screen being cut
+        let line_height = config.editor.line_height() as f64;
+        let y0 = match screen_lines.info(start) {
+            Some(info) => info.vline_y + line_height,
+            None if screen_lines
+                .lines
+                .first()
+                .is_some_and(|&first| first > start) =>
+            {
+                viewport.min_y()
+            }
+            None => return,
+        };
+        let y1 = match screen_lines.info(end) {
+            Some(info) => info.vline_y,
+            None if screen_lines.lines.last().is_some_and(|&last| last < end) => {
+                viewport.max_y()
+            }
+            None => return,
+        };
+        if y0 >= y1 {
+            return;
+        }
+
+        // At the indentation of the line of the opening bracket, where the
+        // indent guide of the block is
+        let x = doc.buffer.with_untracked(|buffer| {
+            let non_blank_offset =
+                buffer.first_non_blank_character_on_line(start.line);
+            let (_, col) = ed.offset_to_line_col(non_blank_offset);
+            ed.line_point_of_line_col(
+                start.line,
+                col,
+                CursorAffinity::Backward,
+                true,
+            )
+            .x
+        });
+
+        let line = Line::new(Point::new(x, y0), Point::new(x, y1));
+        cx.stroke(
+            &line,
+            config.color(LapceColor::EDITOR_BRACKET_PAIR_GUIDE),
+            1.0,
+        );
+    }
 }
 
 impl View for EditorView {
@@ -1084,6 +1596,19 @@ fn compute_layout(
     }
 
     fn paint(&mut self, cx: &mut PaintCx) {
+        let subsystem = if !self.painted && self.editor.doc().loaded() {
+            self.painted = true;
+            PerfSubsystem::FirstPaint
+        } else {
+            PerfSubsystem::Paint
+        };
+        perf::time(subsystem, || self.paint_editor(cx));
+        perf::painted();
+    }
+}
+
+impl EditorView {
+    fn paint_editor(&mut self, cx: &mut PaintCx) {
         let viewport = self.viewport.get_untracked();
         let e_data = &self.editor;
         let ed = &e_data.editor;
@@ -1104,21 +1629,67 @@ fn paint(&mut self, cx: &mut PaintCx) {
         // within the active screen lines without issue.
         let screen_lines = ed.screen_lines.get_untracked();
         self.paint_current_line(cx, is_local, &screen_lines);
+        self.paint_document_highlights(cx, &screen_lines);
+        self.paint_selection_matches(cx, &screen_lines);
         FloemEditorView::paint_selection(cx, ed, &screen_lines);
         let screen_lines = ed.screen_lines.get_untracked();
         self.paint_diff_sections(cx, viewport, &screen_lines, &config);
         let screen_lines = ed.screen_lines.get_untracked();
         self.paint_find(cx, &screen_lines);
         let screen_lines = ed.screen_lines.get_untracked();
+        self.paint_linked_editing(cx, &screen_lines);
+        let screen_lines = ed.screen_lines.get_untracked();
+        self.paint_drop_caret(cx, &screen_lines);
+        let screen_lines = ed.screen_lines.get_untracked();
         self.paint_bracket_highlights_scope_lines(cx, viewport, &screen_lines);
         let screen_lines = ed.screen_lines.get_untracked();
+        self.paint_rainbow_indent_guides(cx, &screen_lines);
+        self.paint_cursor_caret(cx, is_active, &screen_lines);
         FloemEditorView::paint_text(cx, ed, viewport, is_active, &screen_lines);
+        self.paint_bracket_pair_guide(cx, viewport, &screen_lines);
         let screen_lines = ed.screen_lines.get_untracked();
         self.paint_sticky_headers(cx, viewport, &screen_lines);
         self.paint_scroll_bar(cx, viewport, is_local, config);
     }
 }
 
+/// The region of the caret at `offset`. A block caret covers the grapheme
+/// cluster after it with the advance of its glyphs, so it is as wide as a CJK
+/// char, an emoji or a ZWJ sequence rather than as its first char.
+pub fn caret_region(
+    ed: &Editor,
+    offset: usize,
+    block: bool,
+    affinity: CursorAffinity,
+) -> LineRegion {
+    let region = cursor_caret(ed, offset, block, affinity);
+    if !block {
+        return region;
+    }
+    let rope_text = ed.rope_text();
+    let line = rope_text.line_of_offset(offset);
+    let line_end = rope_text.line_end_offset(line, false);
+    if offset >= line_end {
+        return region;
+    }
+    let end = rope_text
+        .text()
+        .next_grapheme_offset(offset)
+        .map_or(line_end, |end| end.min(line_end));
+    let col = end - rope_text.offset_of_line(line);
+    let x1 = ed
+        .line_point_of_line_col(line, col, CursorAffinity::Backward, true)
+        .x;
+    // The cluster wraps onto the next visual line
+    if x1 <= region.x {
+        return region;
+    }
+    LineRegion {
+        width: x1 - region.x,
+        ..region
+    }
+}
+
 fn get_sticky_header_info(
     editor_data: &EditorData,
     viewport: RwSignal<Rect>,
@@ -1145,13 +1716,22 @@ fn get_sticky_header_info(
 
     let y_diff = viewport.y0 - start_info.vline_y;
 
+    let max_lines = config.editor.sticky_header_max_lines;
+    let sticky_headers = |line: usize| {
+        doc.sticky_headers(line).map(|mut lines| {
+            // Keep the innermost headers
+            lines.drain(..lines.len().saturating_sub(max_lines));
+            lines
+        })
+    };
+
     let mut last_sticky_should_scroll = false;
     let mut sticky_lines = Vec::new();
-    if let Some(lines) = doc.sticky_headers(start_line) {
+    if let Some(lines) = sticky_headers(start_line) {
         let total_lines = lines.len();
         if total_lines > 0 {
             let line = start_line + total_lines;
-            if let Some(new_lines) = doc.sticky_headers(line) {
+            if let Some(new_lines) = sticky_headers(line) {
                 if new_lines.len() > total_lines {
                     sticky_lines = new_lines;
                 } else {
@@ -1159,7 +1739,7 @@ fn get_sticky_header_info(
                     last_sticky_should_scroll = new_lines.len() < total_lines;
                     if new_lines.len() < total_lines {
                         if let Some(new_new_lines) =
-                            doc.sticky_headers(start_line + total_lines - 1)
+                            sticky_headers(start_line + total_lines - 1)
                         {
                             if new_new_lines.len() < total_lines {
                                 sticky_lines.pop();
@@ -1194,6 +1774,7 @@ fn get_sticky_header_info(
 
     if total_sticky_lines == 0 {
         sticky_header_height_signal.set(0.0);
+        editor_data.sticky_header_lines.set(Vec::new());
         return StickyHeaderInfo {
             sticky_lines: Vec::new(),
             last_sticky_should_scroll: false,
@@ -1225,6 +1806,7 @@ fn get_sticky_header_info(
         .sum();
 
     sticky_header_height_signal.set(sticky_header_height);
+    editor_data.sticky_header_lines.set(sticky_lines.clone());
     StickyHeaderInfo {
         sticky_lines,
         last_sticky_should_scroll,
@@ -1260,9 +1842,13 @@ pub fn editor_container_view(
 
     stack((
         editor_breadcrumbs(workspace, editor.get_untracked(), config),
+        log_bar(window_tab_data.clone(), editor),
+        long_line_banner(editor),
         stack((
             editor_gutter(window_tab_data.clone(), editor, is_active),
             editor_content(editor, debug_breakline, is_active),
+            overview_ruler(editor),
+            head_hunk_view(editor),
             empty().style(move |s| {
                 let config = config.get();
                 s.absolute()
@@ -1289,6 +1875,7 @@ pub fn editor_container_view(
                 replace_focus,
                 is_active,
             ),
+            loading_placeholder(main_split.clone(), editor),
         ))
         .style(|s| s.width_full().flex_basis(0).flex_grow(1.0)),
     ))
@@ -1315,19 +1902,211 @@ pub fn editor_container_view(
             {
                 doc.scope.dispose();
             }
+        } else {
+            main_split.doc_closed(&doc);
         }
     })
     .style(|s| s.flex_col().absolute().size_pct(100.0, 100.0))
     .debug_name("Editor Container")
 }
 
+/// The note above an editor of a document with lines over the long line
+/// length, that they are cut into segments and shown without colors and
+/// inlay hints
+fn long_line_banner(editor: RwSignal<EditorData>) -> impl View {
+    let (doc, config) =
+        editor.with_untracked(|editor| (editor.doc_signal(), editor.common.config));
+    let dismissed = create_rw_signal(false);
+    let has_long_lines = create_memo(move |_| doc.get().has_long_lines());
+
+    stack((
+        label(|| {
+            "Very long lines are cut into segments and shown without syntax \
+             colors and inlay hints"
+                .to_string()
+        }),
+        label(|| "Dismiss".to_string())
+            .on_click_stop(move |_| dismissed.set(true))
+            .style(move |s| {
+                let config = config.get();
+                s.margin_left(10.0)
+                    .padding_horiz(8.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            }),
+    ))
+    .style(move |s| {
+        let config = config.get();
+        s.items_center()
+            .width_full()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .border_bottom(1.0)
+            .border_color(config.color(LapceColor::LAPCE_BORDER))
+            .background(config.color(LapceColor::PANEL_BACKGROUND))
+            .apply_if(dismissed.get() || !has_long_lines.get(), |s| s.hide())
+    })
+    .debug_name("Long Line Banner")
+}
+
+/// Covers the editor while its file is being read, with how much of it was
+/// and a button to stop opening it
+fn loading_placeholder(
+    main_split: MainSplitData,
+    editor: RwSignal<EditorData>,
+) -> impl View {
+    let config = main_split.common.config;
+    let doc = move || editor.with(|editor| editor.doc_signal()).get();
+    let text = move || {
+        let doc = doc();
+        let name = doc
+            .content
+            .with(|content| {
+                content
+                    .path()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        match doc.load_progress.get() {
+            Some((loaded, total)) if total > 0 => {
+                format!("Loading {name}... {}%", loaded * 100 / total)
+            }
+            _ => format!("Loading {name}..."),
+        }
+    };
+
+    stack((
+        label(text),
+        label(|| "Cancel".to_string())
+            .on_click_stop(move |_| {
+                let path = doc().content.with_untracked(|c| c.path().cloned());
+                if let Some(path) = path {
+                    main_split.cancel_open(&path);
+                }
+            })
+            .style(move |s| {
+                let config = config.get();
+                s.margin_top(10.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(4.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            }),
+    ))
+    .style(move |s| {
+        let doc = doc();
+        let loading = !doc.loaded.get() && doc.content.with(|c| c.is_file());
+        let config = config.get();
+        s.absolute()
+            .size_pct(100.0, 100.0)
+            .flex_col()
+            .items_center()
+            .justify_center()
+            .color(config.color(LapceColor::EDITOR_DIM))
+            .background(config.color(LapceColor::EDITOR_BACKGROUND))
+            .apply_if(!loading, |s| s.hide())
+    })
+    .debug_name("Loading Placeholder")
+}
+
+/// Show the commit that last changed the hovered line of the gutter when the
+/// blame heat map is on, with a legend of the tint.
+fn blame_tooltip<V: View + 'static>(
+    config: ReadSignal<Arc<LapceConfig>>,
+    doc: DocSignal,
+    hovered_line: RwSignal<Option<usize>>,
+    child: V,
+) -> impl View {
+    let blame_of_line = move || {
+        if !config.get_untracked().editor.blame_heat_map {
+            return None;
+        }
+        let line = hovered_line.get_untracked()?;
+        let doc = doc.get_untracked();
+        let blame = doc.blame.get_untracked()?;
+        let (head_line, _) = doc.head_lines(line, line)?;
+        blame
+            .line(head_line)
+            .map(|(commit, heat)| (commit.clone(), heat))
+    };
+
+    tooltip(child, move || {
+        let blame = blame_of_line();
+        let hidden = blame.is_none();
+        let (commit, heat) = blame.unzip();
+        let hint = commit.as_ref().map(git_commit_hint).unwrap_or_default();
+        let summary = commit.map(|commit| commit.summary).unwrap_or_default();
+        // The step of the legend closest to the hovered line's tint
+        let step = heat
+            .map(|heat| (heat * (HEAT_LEGEND_STEPS - 1) as f64).round() as usize)
+            .unwrap_or(0);
+
+        let legend = stack((
+            label(|| "Older".to_string()),
+            dyn_stack(
+                || 0..HEAT_LEGEND_STEPS,
+                |i| *i,
+                move |i| {
+                    empty().style(move |s| {
+                        let config = config.get();
+                        let heat = i as f64 / (HEAT_LEGEND_STEPS - 1) as f64;
+                        s.size(12.0, 12.0)
+                            .margin_horiz(1.0)
+                            .border(1.0)
+                            .border_radius(2.0)
+                            .border_color(if i == step {
+                                config.color(LapceColor::EDITOR_FOREGROUND)
+                            } else {
+                                Color::TRANSPARENT
+                            })
+                            .background(heat_color(&config, heat))
+                    })
+                },
+            )
+            .style(|s| s.margin_horiz(6.0)),
+            label(|| "Newer".to_string()),
+        ))
+        .style(move |s| {
+            s.items_center()
+                .margin_top(6.0)
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+        });
+
+        container(tooltip_tip(
+            config,
+            stack((
+                label(move || hint.clone()),
+                label(move || summary.clone()),
+                legend,
+            ))
+            .style(|s| s.flex_col()),
+        ))
+        .style(move |s| s.apply_if(hidden, |s| s.hide()))
+    })
+}
+
 fn editor_gutter(
     window_tab_data: Rc<WindowTabData>,
     e_data: RwSignal<EditorData>,
     is_active: impl Fn(bool) -> bool + 'static + Copy,
 ) -> impl View {
-    let breakpoints = window_tab_data.terminal.debug.breakpoints;
-    let daps = window_tab_data.terminal.debug.daps;
+    let debug = window_tab_data.terminal.debug.clone();
+    let breakpoints = debug.breakpoints;
 
     let padding_left = 25.0;
     let padding_right = 30.0;
@@ -1368,8 +2147,10 @@ fn editor_gutter(
 
     let gutter_rect = create_rw_signal(Rect::ZERO);
     let gutter_width = create_memo(move |_| gutter_rect.get().width());
+    let hovered_line = create_rw_signal(None);
 
     let breakpoints_view = move |i: usize| {
+        let debug = debug.clone();
         let hovered = create_rw_signal(false);
         container(
             svg(move || config.get().ui_svg(LapceIcons::DEBUG_BREAKPOINT)).style(
@@ -1393,62 +2174,34 @@ fn editor_gutter(
             let doc = e_data.doc();
             let offset = doc.buffer.with_untracked(|b| b.offset_of_line(line));
             if let Some(path) = doc.content.get_untracked().path() {
-                let path_breakpoints = breakpoints
-                    .try_update(|breakpoints| {
-                        let breakpoints =
-                            breakpoints.entry(path.clone()).or_default();
-                        if let std::collections::btree_map::Entry::Vacant(e) =
-                            breakpoints.entry(line)
-                        {
-                            e.insert(LapceBreakpoint {
-                                id: None,
-                                verified: false,
-                                message: None,
-                                line,
-                                offset,
-                                dap_line: None,
-                                active: true,
-                            });
-                        } else {
-                            let mut toggle_active = false;
-                            if let Some(breakpint) = breakpoints.get_mut(&line) {
-                                if !breakpint.active {
-                                    breakpint.active = true;
-                                    toggle_active = true;
-                                }
-                            }
-                            if !toggle_active {
-                                breakpoints.remove(&line);
+                breakpoints.update(|breakpoints| {
+                    let breakpoints = breakpoints.entry(path.clone()).or_default();
+                    if let std::collections::btree_map::Entry::Vacant(e) =
+                        breakpoints.entry(line)
+                    {
+                        e.insert(LapceBreakpoint {
+                            id: None,
+                            verified: false,
+                            message: None,
+                            line,
+                            offset,
+                            dap_line: None,
+                            active: true,
+                        });
+                    } else {
+                        let mut toggle_active = false;
+                        if let Some(breakpint) = breakpoints.get_mut(&line) {
+                            if !breakpint.active {
+                                breakpint.active = true;
+                                toggle_active = true;
                             }
                         }
-                        breakpoints.clone()
-                    })
-                    .unwrap();
-                let source_breakpoints: Vec<SourceBreakpoint> = path_breakpoints
-                    .iter()
-                    .filter_map(|(_, b)| {
-                        if b.active {
-                            Some(SourceBreakpoint {
-                                line: b.line + 1,
-                                column: None,
-                                condition: None,
-                                hit_condition: None,
-                                log_message: None,
-                            })
-                        } else {
-                            None
+                        if !toggle_active {
+                            breakpoints.remove(&line);
                         }
-                    })
-                    .collect();
-                let daps: Vec<DapId> =
-                    daps.with_untracked(|daps| daps.keys().cloned().collect());
-                for dap_id in daps {
-                    e_data.common.proxy.dap_set_breakpoints(
-                        dap_id,
-                        path.to_path_buf(),
-                        source_breakpoints.clone(),
-                    );
-                }
+                    }
+                });
+                debug.sync_breakpoints(&e_data.common.proxy, path);
             }
         })
         .on_event_stop(EventListener::PointerEnter, move |_| {
@@ -1554,16 +2307,32 @@ fn editor_gutter(
         }),
         clip(
             stack((
-                editor_gutter_view(e_data.get_untracked())
-                    .on_resize(move |rect| {
-                        gutter_rect.set(rect);
-                    })
-                    .on_event_stop(EventListener::PointerWheel, move |event| {
-                        if let Event::PointerWheel(pointer_event) = event {
-                            scroll_delta.set(pointer_event.delta);
-                        }
-                    })
-                    .style(|s| s.size_pct(100.0, 100.0)),
+                blame_tooltip(
+                    config,
+                    doc,
+                    hovered_line,
+                    editor_gutter_view(e_data.get_untracked())
+                        .on_resize(move |rect| {
+                            gutter_rect.set(rect);
+                        })
+                        .on_event_stop(EventListener::PointerWheel, move |event| {
+                            if let Event::PointerWheel(pointer_event) = event {
+                                scroll_delta.set(pointer_event.delta);
+                            }
+                        })
+                        .on_event_cont(EventListener::PointerMove, move |event| {
+                            if let Event::PointerMove(pointer_event) = event {
+                                let y = pointer_event.pos.y
+                                    + viewport.get_untracked().y0;
+                                let line = e_data.with_untracked(|e| {
+                                    e.screen_lines_view().line_of_y(y)
+                                });
+                                hovered_line.set(line);
+                            }
+                        })
+                        .style(|s| s.size_pct(100.0, 100.0)),
+                )
+                .style(|s| s.size_pct(100.0, 100.0)),
                 container(
                     svg(move || config.get().ui_svg(LapceIcons::LIGHTBULB)).style(
                         move |s| {
@@ -1616,6 +2385,31 @@ fn editor_gutter(
             ))
             .style(|s| s.size_pct(100.0, 100.0)),
         )
+        .on_event_cont(EventListener::PointerDown, move |event| {
+            // Clicking the marker of a change since `head` shows the lines it
+            // replaced
+            let Event::PointerDown(pointer_event) = event else {
+                return;
+            };
+            let marker_x = padding_left + gutter_width.get_untracked() + 7.0;
+            if (pointer_event.pos.x - marker_x - 1.5).abs() > 5.0 {
+                return;
+            }
+            let y = pointer_event.pos.y + viewport.get_untracked().y0;
+            let (line, hunk_line) = e_data.with_untracked(|e| {
+                (e.screen_lines_view().line_of_y(y), e.head_hunk_line)
+            });
+            let Some(line) = line else {
+                return;
+            };
+            if hunk_line.get_untracked() == Some(line)
+                || doc.get_untracked().head_hunk(line).is_none()
+            {
+                hunk_line.set(None);
+            } else {
+                hunk_line.set(Some(line));
+            }
+        })
         .style(move |s| {
             s.absolute()
                 .size_pct(100.0, 100.0)
@@ -1642,6 +2436,7 @@ fn editor_breadcrumbs(
             content.path().cloned()
         }
     });
+    let json_path = create_memo(move |_| e_data.json_path());
     container(
         scroll(
             stack((
@@ -1715,6 +2510,12 @@ fn editor_breadcrumbs(
 
                     s.padding_right(10.0).apply_if(!is_history, |s| s.hide())
                 }),
+                label(move || json_path.get().unwrap_or_default()).style(move |s| {
+                    s.padding_right(10.0)
+                        .color(config.get().color(LapceColor::EDITOR_DIM))
+                        .selectable(false)
+                        .apply_if(json_path.with(|p| p.is_none()), |s| s.hide())
+                }),
             ))
             .style(|s| s.items_center()),
         )
@@ -1822,6 +2623,11 @@ fn editor_content(
                     e_data.get_untracked().pointer_leave();
                 }
             })
+            .on_event_stop(EventListener::PointerWheel, move |event| {
+                if let Event::PointerWheel(pointer_event) = event {
+                    e_data.get_untracked().pointer_wheel(pointer_event);
+                }
+            })
     })
     .on_move(move |point| {
         window_origin.set(point);
@@ -1839,8 +2645,13 @@ fn editor_content(
         let offset = cursor.offset();
         e_data.doc_signal().track();
         e_data.kind.track();
+        // A scroll command moves the cursor along with the animated scroll,
+        // which would otherwise snap the view to the cursor at once
+        if e_data.smooth_scroll.get_untracked().is_some() {
+            return viewport.get_untracked();
+        }
 
-        let LineRegion { x, width, rvline } = cursor_caret(
+        let LineRegion { x, width, rvline } = caret_region(
             &e_data.editor,
             offset,
             !cursor.is_insert(),
@@ -2207,7 +3018,7 @@ fn find_view(
 }
 
 /// Iterator over (len, color, modified) for each change in the diff
-fn changes_color_iter<'a>(
+pub fn changes_color_iter<'a>(
     changes: &'a im::Vector<DiffLines>,
     config: &'a LapceConfig,
 ) -> impl Iterator<Item = (usize, Option<Color>, bool)> + 'a {
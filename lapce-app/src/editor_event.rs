@@ -0,0 +1,87 @@
+//! The events of the documents and editors of a window tab. They are sent
+//! through `CommonData::editor_events`, for the panels, the status bar and the
+//! rest of the window tab to follow them by listening there, rather than each
+//! watching the signals they come from.
+
+use std::path::PathBuf;
+
+use floem::views::editor::id::EditorId;
+use lapce_core::{cursor::CursorMode, mode::Mode};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditorEvent {
+    DocumentOpened {
+        path: PathBuf,
+    },
+    /// The document was closed and freed, rather than kept in the cache
+    DocumentClosed {
+        path: PathBuf,
+    },
+    DocumentSaved {
+        path: PathBuf,
+    },
+    /// The editor of the document was left for another one, or the window
+    /// lost the focus
+    FocusLost {
+        path: PathBuf,
+    },
+    /// The cursor of the active editor moved
+    CursorMoved {
+        editor_id: EditorId,
+        offset: usize,
+    },
+    /// The selection of the active editor changed, other than the caret
+    /// moving without selecting anything
+    SelectionChanged {
+        editor_id: EditorId,
+    },
+    DiagnosticsUpdated {
+        path: PathBuf,
+    },
+    /// The modal mode of the active editor changed
+    ModeChanged {
+        editor_id: EditorId,
+        mode: Mode,
+    },
+}
+
+/// The ranges selected in `mode`, with only a caret as an empty range
+pub fn selected_ranges(mode: &CursorMode) -> Vec<(usize, usize)> {
+    match mode {
+        CursorMode::Normal(offset) => vec![(*offset, *offset)],
+        CursorMode::Visual { start, end, .. } => {
+            vec![(*start.min(end), *start.max(end) + 1)]
+        }
+        CursorMode::Insert(selection) => selection
+            .regions()
+            .iter()
+            .map(|region| (region.min(), region.max()))
+            .collect(),
+    }
+}
+
+/// Whether going from the ranges `old` to `new` changes the selection, rather
+/// than only moving a single caret
+pub fn selection_changed(old: &[(usize, usize)], new: &[(usize, usize)]) -> bool {
+    let is_caret =
+        |ranges: &[(usize, usize)]| ranges.len() == 1 && ranges[0].0 == ranges[0].1;
+    old != new && !(is_caret(old) && is_caret(new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::selection_changed;
+
+    #[test]
+    fn test_selection_changed() {
+        // Moving the caret
+        assert!(!selection_changed(&[(2, 2)], &[(5, 5)]));
+        // Selecting, extending the selection and clearing it
+        assert!(selection_changed(&[(2, 2)], &[(2, 5)]));
+        assert!(selection_changed(&[(2, 5)], &[(2, 6)]));
+        assert!(selection_changed(&[(2, 6)], &[(6, 6)]));
+        // Adding a cursor
+        assert!(selection_changed(&[(2, 2)], &[(2, 2), (8, 8)]));
+        assert!(!selection_changed(&[(2, 5)], &[(2, 5)]));
+    }
+}
@@ -24,8 +24,8 @@
         EditorData, EditorInfo,
     },
     id::{
-        DiffEditorId, EditorTabId, KeymapId, SettingsId, SplitId,
-        ThemeColorSettingsId, VoltViewId,
+        DiffEditorId, EditorTabId, KeymapId, LargeFileViewId, RunConfigsId,
+        SettingsId, SplitId, ThemeColorSettingsId, VoltViewId,
     },
     main_split::{Editors, MainSplitData},
     plugin::PluginData,
@@ -39,7 +39,9 @@ pub enum EditorTabChildInfo {
     Settings,
     ThemeColorSettings,
     Keymap,
+    RunConfigs,
     Volt(VoltID),
+    LargeFile(PathBuf),
 }
 
 impl EditorTabChildInfo {
@@ -64,9 +66,15 @@ pub fn to_data(
                 EditorTabChild::ThemeColorSettings(ThemeColorSettingsId::next())
             }
             EditorTabChildInfo::Keymap => EditorTabChild::Keymap(KeymapId::next()),
+            EditorTabChildInfo::RunConfigs => {
+                EditorTabChild::RunConfigs(RunConfigsId::next())
+            }
             EditorTabChildInfo::Volt(id) => {
                 EditorTabChild::Volt(VoltViewId::next(), id.to_owned())
             }
+            EditorTabChildInfo::LargeFile(path) => {
+                EditorTabChild::LargeFile(LargeFileViewId::next(), path.to_owned())
+            }
         }
     }
 }
@@ -127,7 +135,9 @@ pub enum EditorTabChildSource {
     Settings,
     ThemeColorSettings,
     Keymap,
+    RunConfigs,
     Volt(VoltID),
+    LargeFile(PathBuf),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -137,7 +147,9 @@ pub enum EditorTabChild {
     Settings(SettingsId),
     ThemeColorSettings(ThemeColorSettingsId),
     Keymap(KeymapId),
+    RunConfigs(RunConfigsId),
     Volt(VoltViewId, VoltID),
+    LargeFile(LargeFileViewId, PathBuf),
 }
 
 #[derive(PartialEq)]
@@ -157,7 +169,9 @@ pub fn id(&self) -> u64 {
             EditorTabChild::Settings(id) => id.to_raw(),
             EditorTabChild::ThemeColorSettings(id) => id.to_raw(),
             EditorTabChild::Keymap(id) => id.to_raw(),
+            EditorTabChild::RunConfigs(id) => id.to_raw(),
             EditorTabChild::Volt(id, _) => id.to_raw(),
+            EditorTabChild::LargeFile(id, _) => id.to_raw(),
         }
     }
 
@@ -190,7 +204,11 @@ pub fn child_info(&self, data: &WindowTabData) -> EditorTabChildInfo {
                 EditorTabChildInfo::ThemeColorSettings
             }
             EditorTabChild::Keymap(_) => EditorTabChildInfo::Keymap,
+            EditorTabChild::RunConfigs(_) => EditorTabChildInfo::RunConfigs,
             EditorTabChild::Volt(_, id) => EditorTabChildInfo::Volt(id.to_owned()),
+            EditorTabChild::LargeFile(_, path) => {
+                EditorTabChildInfo::LargeFile(path.to_owned())
+            }
         }
     }
 
@@ -363,6 +381,16 @@ pub fn view_info(
                     is_pristine: true,
                 }
             }),
+            EditorTabChild::RunConfigs(_) => create_memo(move |_| {
+                let config = config.get();
+                EditorTabChildViewInfo {
+                    icon: config.ui_svg(LapceIcons::DEBUG_ALT),
+                    color: Some(config.color(LapceColor::LAPCE_ICON_ACTIVE)),
+                    path: "Run Configurations".to_string(),
+                    confirmed: None,
+                    is_pristine: true,
+                }
+            }),
             EditorTabChild::Volt(_, id) => create_memo(move |_| {
                 let config = config.get();
                 let display_name = plugin
@@ -386,6 +414,20 @@ pub fn view_info(
                     is_pristine: true,
                 }
             }),
+            EditorTabChild::LargeFile(_, path) => create_memo(move |_| {
+                let config = config.get();
+                let (icon, color) = config.file_svg(&path);
+                EditorTabChildViewInfo {
+                    icon,
+                    color,
+                    path: format!(
+                        "{} (Large File)",
+                        path.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                    confirmed: None,
+                    is_pristine: true,
+                }
+            }),
         }
     }
 }
@@ -0,0 +1,846 @@
+//! Emmet abbreviations, which expand into markup or CSS when Tab is pressed
+//! after them, e.g. `ul>li.item*3` or `m10`.
+//!
+//! The expansion is a snippet, with tab stops at the empty attribute values
+//! and element contents.
+
+use lapce_core::language::LapceLanguage;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmmetSyntax {
+    Html,
+    Jsx,
+    Css,
+}
+
+impl EmmetSyntax {
+    pub fn from_language(language: LapceLanguage) -> Option<Self> {
+        match language {
+            LapceLanguage::Html
+            | LapceLanguage::Vue
+            | LapceLanguage::Svelte
+            | LapceLanguage::Glimmer => Some(EmmetSyntax::Html),
+            LapceLanguage::Jsx | LapceLanguage::Tsx => Some(EmmetSyntax::Jsx),
+            LapceLanguage::Css | LapceLanguage::Scss => Some(EmmetSyntax::Css),
+            _ => None,
+        }
+    }
+}
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+    "source", "track", "wbr",
+];
+
+/// The tags that expand on their own, without any operator after them
+const KNOWN_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "code",
+    "col",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "meta",
+    "nav",
+    "ol",
+    "option",
+    "p",
+    "pre",
+    "script",
+    "section",
+    "select",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "video",
+    "wbr",
+];
+
+/// Attributes that are added empty, for a tab stop, when they aren't given
+const DEFAULT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("img", &["src", "alt"]),
+    ("link", &["rel", "href"]),
+    ("form", &["action"]),
+    ("label", &["for"]),
+    ("input", &["type"]),
+];
+
+/// CSS abbreviations, and the property they stand for
+const CSS_PROPERTIES: &[(&str, &str)] = &[
+    ("m", "margin"),
+    ("mt", "margin-top"),
+    ("mr", "margin-right"),
+    ("mb", "margin-bottom"),
+    ("ml", "margin-left"),
+    ("p", "padding"),
+    ("pt", "padding-top"),
+    ("pr", "padding-right"),
+    ("pb", "padding-bottom"),
+    ("pl", "padding-left"),
+    ("w", "width"),
+    ("h", "height"),
+    ("maw", "max-width"),
+    ("mah", "max-height"),
+    ("miw", "min-width"),
+    ("mih", "min-height"),
+    ("t", "top"),
+    ("r", "right"),
+    ("b", "bottom"),
+    ("l", "left"),
+    ("fz", "font-size"),
+    ("fw", "font-weight"),
+    ("ff", "font-family"),
+    ("lh", "line-height"),
+    ("ta", "text-align"),
+    ("td", "text-decoration"),
+    ("c", "color"),
+    ("bg", "background"),
+    ("bgc", "background-color"),
+    ("bd", "border"),
+    ("bdrs", "border-radius"),
+    ("d", "display"),
+    ("pos", "position"),
+    ("z", "z-index"),
+    ("op", "opacity"),
+    ("ov", "overflow"),
+    ("cur", "cursor"),
+    ("jc", "justify-content"),
+    ("ai", "align-items"),
+    ("fxd", "flex-direction"),
+    ("fx", "flex"),
+    ("g", "gap"),
+    ("trf", "transform"),
+    ("trs", "transition"),
+];
+
+/// CSS abbreviations that stand for a whole declaration
+const CSS_DECLARATIONS: &[(&str, &str)] = &[
+    ("db", "display: block"),
+    ("dib", "display: inline-block"),
+    ("di", "display: inline"),
+    ("df", "display: flex"),
+    ("dg", "display: grid"),
+    ("dn", "display: none"),
+    ("posa", "position: absolute"),
+    ("posr", "position: relative"),
+    ("posf", "position: fixed"),
+    ("poss", "position: sticky"),
+    ("tac", "text-align: center"),
+    ("tal", "text-align: left"),
+    ("tar", "text-align: right"),
+    ("fwb", "font-weight: bold"),
+    ("ovh", "overflow: hidden"),
+    ("curp", "cursor: pointer"),
+    ("jcc", "justify-content: center"),
+    ("jcsb", "justify-content: space-between"),
+    ("aic", "align-items: center"),
+    ("fxdc", "flex-direction: column"),
+];
+
+/// Properties whose bare numbers don't get a `px` unit
+const UNITLESS_PROPERTIES: &[&str] =
+    &["line-height", "z-index", "opacity", "font-weight", "flex"];
+
+/// Find the abbreviation at the end of `line`, returning where it starts.
+pub fn abbreviation_start(line: &str, syntax: EmmetSyntax) -> Option<usize> {
+    let start = match syntax {
+        EmmetSyntax::Css => line
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '#' | '%')
+            })
+            .last()
+            .map(|(i, _)| i)?,
+        EmmetSyntax::Html | EmmetSyntax::Jsx => markup_abbreviation_start(line)?,
+    };
+    let before = line[..start].chars().next_back();
+    let boundary = match syntax {
+        // Only at the start of a declaration, not in a value
+        EmmetSyntax::Css => {
+            let declaration = line[..start].rsplit(['{', ';']).next();
+            before.map_or(true, |c| c.is_whitespace() || c == '{' || c == ';')
+                && !declaration.is_some_and(|d| d.contains(':'))
+        }
+        EmmetSyntax::Html | EmmetSyntax::Jsx => {
+            before.map_or(true, |c| !c.is_alphanumeric() && c != '/' && c != '<')
+        }
+    };
+    let first = line[start..].chars().next()?;
+    (boundary && !first.is_ascii_digit()).then_some(start)
+}
+
+fn markup_abbreviation_start(line: &str) -> Option<usize> {
+    let mut start = None;
+    // Attributes in `[]` and text in `{}` can hold any character
+    let mut nesting = 0usize;
+    for (i, c) in line.char_indices().rev() {
+        match c {
+            ']' | '}' => nesting += 1,
+            '[' | '{' if nesting > 0 => nesting -= 1,
+            _ if nesting > 0 => {}
+            '<' => {
+                // The abbreviation follows a tag, as in `<div>ul>li`
+                let tag_end = line[i..].find('>')? + i + 1;
+                start = (tag_end < line.len()).then_some(tag_end);
+                break;
+            }
+            c if c.is_alphanumeric()
+                || matches!(
+                    c,
+                    '-' | '_'
+                        | ':'
+                        | '.'
+                        | '#'
+                        | '*'
+                        | '>'
+                        | '+'
+                        | '^'
+                        | '$'
+                        | '@'
+                        | '!'
+                        | '('
+                        | ')'
+                        | '='
+                        | '%'
+                ) => {}
+            _ => break,
+        }
+        if nesting == 0 {
+            start = Some(i);
+        }
+    }
+    if nesting > 0 {
+        return None;
+    }
+    start
+}
+
+/// Expand the abbreviation into a snippet. The lines after the first start
+/// with `indent`, and nested elements are indented further by `unit`.
+pub fn expand(
+    abbreviation: &str,
+    syntax: EmmetSyntax,
+    indent: &str,
+    unit: &str,
+) -> Option<String> {
+    match syntax {
+        EmmetSyntax::Css => expand_css(abbreviation),
+        EmmetSyntax::Html | EmmetSyntax::Jsx => {
+            expand_markup(abbreviation, syntax, indent, unit)
+        }
+    }
+}
+
+fn expand_css(abbreviation: &str) -> Option<String> {
+    if let Some((_, declaration)) = CSS_DECLARATIONS
+        .iter()
+        .find(|(abbr, _)| *abbr == abbreviation)
+    {
+        return Some(format!("{declaration};$0"));
+    }
+
+    // The longest abbreviation that the rest can be a value for
+    let (property, value) = CSS_PROPERTIES
+        .iter()
+        .filter_map(|(abbr, property)| {
+            let value = abbreviation.strip_prefix(abbr)?;
+            let is_value = value.is_empty()
+                || value.starts_with('#')
+                || value.starts_with(|c: char| c.is_ascii_digit() || c == '-');
+            is_value.then_some((abbr.len(), *property, value))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map(|(_, property, value)| (property, value))?;
+
+    if value.is_empty() {
+        return Some(format!("{property}: $1;$0"));
+    }
+    if value.starts_with('#') {
+        return Some(format!("{property}: {value};$0"));
+    }
+
+    // `m10-auto` is two values, `m-10` is a negative one
+    let mut values = Vec::new();
+    let mut rest = value;
+    while !rest.is_empty() {
+        let negative = rest.starts_with('-');
+        let number_end = rest[negative as usize..]
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .map(|i| i + negative as usize)
+            .unwrap_or(rest.len());
+        let value_end = rest[number_end..]
+            .find('-')
+            .map(|i| i + number_end)
+            .unwrap_or(rest.len());
+        let (number, unit) = (&rest[..number_end], &rest[number_end..value_end]);
+        values.push(css_value(property, number, unit)?);
+        rest = rest[value_end..]
+            .strip_prefix('-')
+            .unwrap_or(&rest[value_end..]);
+    }
+    Some(format!("{property}: {};$0", values.join(" ")))
+}
+
+fn css_value(property: &str, number: &str, unit: &str) -> Option<String> {
+    if number.is_empty() || number == "-" {
+        // A keyword, such as `auto`
+        return (!unit.is_empty() && unit.chars().all(|c| c.is_ascii_alphabetic()))
+            .then(|| unit.to_string());
+    }
+    let unit = match unit {
+        "" if number.parse::<f64>().ok()? == 0.0
+            || UNITLESS_PROPERTIES.contains(&property) =>
+        {
+            ""
+        }
+        "" => "px",
+        "p" => "%",
+        "e" => "em",
+        "r" => "rem",
+        "x" => "ex",
+        unit if unit.chars().all(|c| c.is_ascii_alphabetic() || c == '%') => unit,
+        _ => return None,
+    };
+    Some(format!("{number}{unit}"))
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+    text: Option<String>,
+    count: usize,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Group(Vec<Node>, usize),
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+        &self.s[start..self.pos]
+    }
+
+    fn name(&mut self) -> String {
+        self.take_while(|c| {
+            c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '$' | '@')
+        })
+        .to_string()
+    }
+
+    /// Siblings and their children, up to the end of a group or the end
+    fn nodes(&mut self) -> Option<Vec<Node>> {
+        // The siblings at each level of `>`
+        let mut levels: Vec<Vec<Node>> = vec![Vec::new()];
+        loop {
+            let node = self.node()?;
+            levels.last_mut()?.push(node);
+            match self.peek() {
+                Some('>') => {
+                    self.bump();
+                    levels.push(Vec::new());
+                }
+                Some('+') => {
+                    self.bump();
+                }
+                Some('^') => {
+                    while self.peek() == Some('^') {
+                        self.bump();
+                        if levels.len() > 1 {
+                            close_level(&mut levels)?;
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+        while levels.len() > 1 {
+            close_level(&mut levels)?;
+        }
+        levels.pop()
+    }
+
+    fn node(&mut self) -> Option<Node> {
+        if self.peek() == Some('(') {
+            self.bump();
+            let nodes = self.nodes()?;
+            if self.bump() != Some(')') {
+                return None;
+            }
+            let count = self.count()?;
+            return Some(Node::Group(nodes, count));
+        }
+
+        let mut element = Element {
+            name: self.name(),
+            ..Default::default()
+        };
+        let mut empty = element.name.is_empty();
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.bump();
+                    element.id = Some(self.name());
+                }
+                Some('.') => {
+                    self.bump();
+                    let class = self.name();
+                    if class.is_empty() {
+                        return None;
+                    }
+                    element.classes.push(class);
+                }
+                Some('[') => {
+                    self.bump();
+                    self.attributes(&mut element.attributes)?;
+                }
+                Some('{') => {
+                    self.bump();
+                    let text = self.take_while(|c| c != '}').to_string();
+                    self.bump()?;
+                    element.text = Some(text);
+                }
+                _ => break,
+            }
+            empty = false;
+        }
+        if empty {
+            return None;
+        }
+        element.count = self.count()?;
+        Some(Node::Element(element))
+    }
+
+    fn attributes(
+        &mut self,
+        attributes: &mut Vec<(String, Option<String>)>,
+    ) -> Option<()> {
+        loop {
+            self.take_while(char::is_whitespace);
+            if self.peek() == Some(']') {
+                self.bump();
+                return Some(());
+            }
+            let name = self.name();
+            if name.is_empty() {
+                return None;
+            }
+            let value = if self.peek() == Some('=') {
+                self.bump();
+                let value = match self.peek() {
+                    Some(quote @ ('"' | '\'')) => {
+                        self.bump();
+                        let value = self.take_while(|c| c != quote).to_string();
+                        self.bump()?;
+                        value
+                    }
+                    _ => self
+                        .take_while(|c| !c.is_whitespace() && c != ']')
+                        .to_string(),
+                };
+                Some(value)
+            } else {
+                None
+            };
+            attributes.push((name, value));
+        }
+    }
+
+    fn count(&mut self) -> Option<usize> {
+        if self.peek() != Some('*') {
+            return Some(1);
+        }
+        self.bump();
+        self.take_while(|c| c.is_ascii_digit()).parse().ok()
+    }
+}
+
+/// Make the siblings of the innermost level the children of the last
+/// element of the level above.
+fn close_level(levels: &mut Vec<Vec<Node>>) -> Option<()> {
+    let children = levels.pop()?;
+    match levels.last_mut()?.last_mut()? {
+        Node::Element(element) => element.children.extend(children),
+        // `(a+b)>c` has no single parent for `c`
+        Node::Group(..) => return None,
+    }
+    Some(())
+}
+
+fn expand_markup(
+    abbreviation: &str,
+    syntax: EmmetSyntax,
+    indent: &str,
+    unit: &str,
+) -> Option<String> {
+    let mut parser = Parser {
+        s: abbreviation,
+        pos: 0,
+    };
+    let nodes = parser.nodes()?;
+    if parser.pos != abbreviation.len() {
+        return None;
+    }
+    // A lone word is more likely to be text than a tag
+    if let [Node::Element(element)] = nodes.as_slice() {
+        let bare = element.id.is_none()
+            && element.classes.is_empty()
+            && element.attributes.is_empty()
+            && element.text.is_none()
+            && element.count == 1
+            && element.children.is_empty();
+        if bare
+            && (syntax == EmmetSyntax::Jsx
+                || !KNOWN_TAGS.contains(&element.name.as_str()))
+        {
+            return None;
+        }
+    }
+
+    let mut renderer = Renderer {
+        syntax,
+        unit,
+        tab_stop: 0,
+    };
+    let mut lines = Vec::new();
+    renderer.nodes(&nodes, "", &[], &mut lines);
+    let mut snippet = lines.join(&format!("\n{indent}"));
+    snippet.push_str("$0");
+    Some(snippet)
+}
+
+struct Renderer<'a> {
+    syntax: EmmetSyntax,
+    unit: &'a str,
+    tab_stop: usize,
+}
+
+impl Renderer<'_> {
+    fn next_tab_stop(&mut self) -> String {
+        self.tab_stop += 1;
+        format!("${}", self.tab_stop)
+    }
+
+    /// Render the nodes as lines, numbering `$` with the innermost of
+    /// `numbers`.
+    fn nodes(
+        &mut self,
+        nodes: &[Node],
+        parent: &str,
+        numbers: &[usize],
+        lines: &mut Vec<String>,
+    ) {
+        for node in nodes {
+            match node {
+                Node::Element(element) => {
+                    for i in 1..=element.count {
+                        let numbers = if element.count > 1 {
+                            [numbers, &[i]].concat()
+                        } else {
+                            numbers.to_vec()
+                        };
+                        self.element(element, parent, &numbers, lines);
+                    }
+                }
+                Node::Group(nodes, count) => {
+                    for i in 1..=*count {
+                        let numbers = if *count > 1 {
+                            [numbers, &[i]].concat()
+                        } else {
+                            numbers.to_vec()
+                        };
+                        self.nodes(nodes, parent, &numbers, lines);
+                    }
+                }
+            }
+        }
+    }
+
+    fn element(
+        &mut self,
+        element: &Element,
+        parent: &str,
+        numbers: &[usize],
+        lines: &mut Vec<String>,
+    ) {
+        let number = numbers.last().copied().unwrap_or(1);
+        let name = if element.name.is_empty() {
+            if element.id.is_none()
+                && element.classes.is_empty()
+                && element.attributes.is_empty()
+            {
+                // Just `{text}`
+                let text = escape(&numbered(
+                    element.text.as_deref().unwrap_or_default(),
+                    number,
+                ));
+                lines.push(text);
+                return;
+            }
+            implicit_tag(parent).to_string()
+        } else {
+            numbered(&element.name, number)
+        };
+
+        let mut open = format!("<{name}");
+        if let Some(id) = &element.id {
+            open.push_str(&format!(" id=\"{}\"", escape(&numbered(id, number))));
+        }
+        if !element.classes.is_empty() {
+            let classes = element
+                .classes
+                .iter()
+                .map(|class| escape(&numbered(class, number)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let attribute = match self.syntax {
+                EmmetSyntax::Jsx => "className",
+                _ => "class",
+            };
+            open.push_str(&format!(" {attribute}=\"{classes}\""));
+        }
+        let defaults = DEFAULT_ATTRIBUTES
+            .iter()
+            .find(|(tag, _)| *tag == name)
+            .map(|(_, attributes)| *attributes)
+            .unwrap_or_default()
+            .iter()
+            .filter(|default| {
+                !element.attributes.iter().any(|(name, _)| name == *default)
+            })
+            .map(|default| (default.to_string(), None));
+        let attributes: Vec<_> =
+            element.attributes.iter().cloned().chain(defaults).collect();
+        for (attribute, value) in attributes {
+            let attribute = match (self.syntax, attribute.as_str()) {
+                (EmmetSyntax::Jsx, "class") => "className".to_string(),
+                (EmmetSyntax::Jsx, "for") => "htmlFor".to_string(),
+                _ => attribute,
+            };
+            let value = match value {
+                Some(value) => escape(&numbered(&value, number)),
+                None => self.next_tab_stop(),
+            };
+            open.push_str(&format!(" {attribute}=\"{value}\""));
+        }
+
+        if VOID_TAGS.contains(&name.as_str()) && element.children.is_empty() {
+            match self.syntax {
+                EmmetSyntax::Jsx => open.push_str(" />"),
+                _ => open.push('>'),
+            }
+            lines.push(open);
+            return;
+        }
+        open.push('>');
+
+        let text = element
+            .text
+            .as_deref()
+            .map(|text| escape(&numbered(text, number)));
+        if element.children.is_empty() {
+            let content = text.unwrap_or_else(|| self.next_tab_stop());
+            lines.push(format!("{open}{content}</{name}>"));
+            return;
+        }
+
+        lines.push(format!("{open}{}", text.unwrap_or_default()));
+        let mut children = Vec::new();
+        self.nodes(&element.children, &name, numbers, &mut children);
+        lines.extend(
+            children
+                .into_iter()
+                .map(|line| format!("{}{line}", self.unit)),
+        );
+        lines.push(format!("</{name}>"));
+    }
+}
+
+/// The tag of an element that has only an id, classes or attributes
+fn implicit_tag(parent: &str) -> &'static str {
+    match parent {
+        "ul" | "ol" => "li",
+        "table" | "tbody" | "thead" | "tfoot" => "tr",
+        "tr" => "td",
+        "select" => "option",
+        "span" | "a" | "em" | "strong" | "b" | "i" | "label" => "span",
+        _ => "div",
+    }
+}
+
+/// Replace each run of `$` with the number, padded with zeros to the length
+/// of the run.
+fn numbered(s: &str, number: usize) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+        let mut width = 1;
+        while chars.peek() == Some(&'$') {
+            chars.next();
+            width += 1;
+        }
+        result.push_str(&format!("{number:0width$}"));
+    }
+    result
+}
+
+/// Escape the characters that have a meaning in snippets
+fn escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        if matches!(c, '$' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{abbreviation_start, expand, EmmetSyntax};
+
+    #[test]
+    fn test_expand_markup() {
+        assert_eq!(
+            expand("ul>li.item$*3", EmmetSyntax::Html, "  ", "\t").unwrap(),
+            "<ul>\n  \t<li class=\"item1\">$1</li>\n  \t<li class=\"item2\">$2</li>\n  \t<li class=\"item3\">$3</li>\n  </ul>$0"
+        );
+        assert_eq!(
+            expand("a+img", EmmetSyntax::Html, "", "  ").unwrap(),
+            "<a href=\"$1\">$2</a>\n<img src=\"$3\" alt=\"$4\">$0"
+        );
+        assert_eq!(
+            expand("div#main>p{Hi}^span", EmmetSyntax::Html, "", "  ").unwrap(),
+            "<div id=\"main\">\n  <p>Hi</p>\n</div>\n<span>$1</span>$0"
+        );
+        assert_eq!(
+            expand(".row>.col[data-x=\"a b\"]", EmmetSyntax::Jsx, "", "  ")
+                .unwrap(),
+            "<div className=\"row\">\n  <div className=\"col\" data-x=\"a b\">$1</div>\n</div>$0"
+        );
+        assert_eq!(
+            expand("(dt+dd)*2", EmmetSyntax::Html, "", "").unwrap(),
+            "<dt>$1</dt>\n<dd>$2</dd>\n<dt>$3</dt>\n<dd>$4</dd>$0"
+        );
+        assert_eq!(expand("hello", EmmetSyntax::Html, "", ""), None);
+        assert_eq!(expand("div", EmmetSyntax::Jsx, "", ""), None);
+        assert_eq!(expand("ul>", EmmetSyntax::Html, "", ""), None);
+    }
+
+    #[test]
+    fn test_expand_css() {
+        let css = |abbreviation| expand(abbreviation, EmmetSyntax::Css, "", "");
+        assert_eq!(css("m10").unwrap(), "margin: 10px;$0");
+        assert_eq!(css("m10-auto").unwrap(), "margin: 10px auto;$0");
+        assert_eq!(css("w100p").unwrap(), "width: 100%;$0");
+        assert_eq!(css("lh1.5").unwrap(), "line-height: 1.5;$0");
+        assert_eq!(css("c#fff").unwrap(), "color: #fff;$0");
+        assert_eq!(css("df").unwrap(), "display: flex;$0");
+        assert_eq!(css("pos").unwrap(), "position: $1;$0");
+        assert_eq!(css("hello"), None);
+    }
+
+    #[test]
+    fn test_abbreviation_start() {
+        assert_eq!(abbreviation_start("  ul>li*3", EmmetSyntax::Html), Some(2));
+        assert_eq!(abbreviation_start("<div>ul>li", EmmetSyntax::Html), Some(5));
+        assert_eq!(
+            abbreviation_start("a p{some text}", EmmetSyntax::Html),
+            Some(2)
+        );
+        assert_eq!(abbreviation_start("</div", EmmetSyntax::Html), None);
+        assert_eq!(abbreviation_start("a { m10", EmmetSyntax::Css), Some(4));
+        assert_eq!(abbreviation_start("margin: m10", EmmetSyntax::Css), None);
+    }
+}
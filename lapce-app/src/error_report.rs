@@ -0,0 +1,114 @@
+//! Errors reported to the user with the context they happened in, the
+//! document and the command, instead of only being logged. The details can
+//! be copied, and written to an incident report in the logs directory.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use lapce_core::{directory::Directory, meta::VERSION};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorReport {
+    pub title: String,
+    pub message: String,
+    /// The file the error is about
+    pub path: Option<PathBuf>,
+    /// The revision of the document when it happened
+    pub rev: Option<u64>,
+    /// The command or the request that failed
+    pub command: Option<String>,
+}
+
+impl ErrorReport {
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn rev(mut self, rev: u64) -> Self {
+        self.rev = Some(rev);
+        self
+    }
+
+    pub fn command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// The error and its context as text, to be copied or written to an
+    /// incident report
+    pub fn details(&self, time: DateTime<Local>) -> String {
+        let mut lines = vec![
+            format!("{}: {}", self.title, self.message),
+            format!("Time: {}", time.format("%Y-%m-%d %H:%M:%S%.3f %:z")),
+        ];
+        if let Some(path) = &self.path {
+            lines.push(format!("Path: {}", path.display()));
+        }
+        if let Some(rev) = self.rev {
+            lines.push(format!("Revision: {rev}"));
+        }
+        if let Some(command) = &self.command {
+            lines.push(format!("Command: {command}"));
+        }
+        lines.push(format!("Lapce: {VERSION}"));
+        lines.push(format!(
+            "OS: {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ));
+        lines.join("\n")
+    }
+}
+
+/// Write the details of an error to a new file of the logs directory
+pub fn write_incident_report(
+    details: &str,
+    time: DateTime<Local>,
+) -> Result<PathBuf> {
+    let dir = Directory::logs_directory()
+        .ok_or_else(|| anyhow!("can't find the logs directory"))?;
+    let path = dir.join(format!(
+        "incident-{}.txt",
+        time.format("%Y-%m-%d-%H%M%S%.3f")
+    ));
+    std::fs::write(&path, format!("{details}\n"))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::ErrorReport;
+
+    #[test]
+    fn test_details() {
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+        let details = ErrorReport::new("Save", "permission denied")
+            .path("/a/b.rs")
+            .rev(7)
+            .command("save")
+            .details(time);
+        let lines: Vec<&str> = details.lines().collect();
+        assert_eq!(lines[0], "Save: permission denied");
+        assert!(lines[1].starts_with("Time: 2024-05-01 12:30:00.000 "));
+        assert_eq!(
+            &lines[2..5],
+            ["Path: /a/b.rs", "Revision: 7", "Command: save"]
+        );
+
+        let details = ErrorReport::new("Process", "not found").details(time);
+        assert!(!details.contains("Path:"));
+        assert!(!details.contains("Revision:"));
+    }
+}
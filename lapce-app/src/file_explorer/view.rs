@@ -172,24 +172,43 @@ fn file_node_text_view(
 ) -> impl View {
     let config = data.common.config;
     let ui_line_height = data.common.ui_line_height;
+    let coverage = data.common.coverage;
 
     let view = match node.kind.clone() {
         FileNodeViewKind::Path(path) => container(
-            label(move || {
-                path.file_name()
-                    .map(|f| f.to_string_lossy().to_string())
-                    .unwrap_or_default()
-            })
-            .style(move |s| {
-                s.flex_grow(1.0)
-                    .height(ui_line_height.get())
-                    .color(file_node_text_color(
-                        config,
-                        node.clone(),
-                        source_control.clone(),
-                    ))
-                    .selectable(false)
-            }),
+            stack((
+                label({
+                    let path = path.clone();
+                    move || {
+                        path.file_name()
+                            .map(|f| f.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    }
+                })
+                .style(move |s| {
+                    s.flex_grow(1.0)
+                        .height(ui_line_height.get())
+                        .color(file_node_text_color(
+                            config,
+                            node.clone(),
+                            source_control.clone(),
+                        ))
+                        .selectable(false)
+                }),
+                // The percentage of the lines the tests of the coverage report ran
+                label(move || {
+                    coverage
+                        .with(|coverage| coverage.get(&path)?.percent())
+                        .map(|percent| format!("{percent:.0}%"))
+                        .unwrap_or_default()
+                })
+                .style(move |s| {
+                    s.margin_left(6.0)
+                        .color(config.get().color(LapceColor::EDITOR_DIM))
+                        .selectable(false)
+                }),
+            ))
+            .style(|s| s.width_full().items_center()),
         ),
         FileNodeViewKind::Renaming { path, err } => {
             if data.naming.with_untracked(Naming::editor_needs_reset) {
@@ -0,0 +1,191 @@
+//! The templates new files are filled with, `<name>.<extension>` files of
+//! the user templates directory written in the snippet syntax, with
+//! variables such as `${FILENAME}` filled in.
+
+use std::path::{Path, PathBuf};
+
+use lapce_core::directory::Directory;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileTemplate {
+    pub name: String,
+    /// The extension of the files the template is for
+    pub extension: String,
+    pub path: PathBuf,
+}
+
+/// The templates of the user templates directory, only those for the files
+/// with `extension` when it's given
+pub fn file_templates(extension: Option<&str>) -> Vec<FileTemplate> {
+    let Some(entries) =
+        Directory::templates_directory().and_then(|dir| std::fs::read_dir(dir).ok())
+    else {
+        return Vec::new();
+    };
+    let mut templates = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !path.is_file() {
+                return None;
+            }
+            Some(FileTemplate {
+                name: path.file_stem()?.to_str()?.to_string(),
+                extension: path.extension()?.to_str()?.to_string(),
+                path,
+            })
+        })
+        .filter(|template| match extension {
+            Some(extension) => template.extension.eq_ignore_ascii_case(extension),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    templates.sort_by(|a, b| (&a.extension, &a.name).cmp(&(&b.extension, &b.name)));
+    templates
+}
+
+/// The values of the variables of the templates
+#[derive(Clone, Debug)]
+pub struct TemplateVariables {
+    pub filename: String,
+    pub date: String,
+    pub year: String,
+    pub author: String,
+}
+
+impl TemplateVariables {
+    /// The variables for a new file, `author` being the one of the config,
+    /// the user name of the system when it's empty
+    pub fn new(filename: &str, author: &str) -> Self {
+        let now = chrono::Local::now();
+        let author = if author.is_empty() {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_default()
+        } else {
+            author.to_string()
+        };
+        Self {
+            filename: filename.to_string(),
+            date: now.format("%Y-%m-%d").to_string(),
+            year: now.format("%Y").to_string(),
+            author,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "FILENAME" => Some(&self.filename),
+            "FILENAME_BASE" => Path::new(&self.filename)
+                .file_stem()
+                .and_then(|stem| stem.to_str()),
+            "DATE" => Some(&self.date),
+            "YEAR" => Some(&self.year),
+            "AUTHOR" => Some(&self.author),
+            _ => None,
+        }
+    }
+}
+
+/// The template with its `${NAME}` variables filled in, as a snippet. The
+/// unknown variables and the `$` that don't start tab stops are escaped to
+/// be kept as they are.
+pub fn expand_variables(template: &str, variables: &TemplateVariables) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['$', '\\']) {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        if rest.starts_with('\\') {
+            // What is escaped is left for the snippet
+            let len = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+            expanded.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+
+        let name = rest.strip_prefix("${").and_then(|name| {
+            let name = &name[..name.find('}')?];
+            (!name.is_empty()
+                && name.chars().all(|c| c.is_ascii_uppercase() || c == '_'))
+            .then_some(name)
+        });
+        if let Some(name) = name {
+            let len = name.len() + 3;
+            match variables.get(name) {
+                Some(value) => expanded.push_str(&escape_snippet(value)),
+                None => {
+                    expanded.push('\\');
+                    expanded.push_str(&rest[..len]);
+                }
+            }
+            rest = &rest[len..];
+            continue;
+        }
+
+        let tab_stop = rest[1..]
+            .strip_prefix('{')
+            .unwrap_or(&rest[1..])
+            .starts_with(|c: char| c.is_ascii_digit());
+        if !tab_stop {
+            expanded.push('\\');
+        }
+        expanded.push('$');
+        rest = &rest[1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn escape_snippet(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '$' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{expand_variables, TemplateVariables};
+    use crate::snippet::Snippet;
+
+    fn variables() -> TemplateVariables {
+        TemplateVariables {
+            filename: "main.rs".to_string(),
+            date: "2024-05-01".to_string(),
+            year: "2024".to_string(),
+            author: "A $ B".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let template = "// ${FILENAME_BASE} (c) ${YEAR} ${AUTHOR}\nfn ${1:main}() {\n    $0\n}\n";
+        let expanded = expand_variables(template, &variables());
+        assert_eq!(
+            expanded,
+            "// main (c) 2024 A \\$ B\nfn ${1:main}() {\n    $0\n}\n"
+        );
+        let snippet = Snippet::from_str(&expanded).unwrap();
+        assert_eq!(
+            snippet.text(),
+            "// main (c) 2024 A $ B\nfn main() {\n    \n}\n"
+        );
+        assert_eq!(snippet.tabs(0).len(), 2);
+    }
+
+    #[test]
+    fn test_expand_unknown_variables() {
+        let template = "echo $HOME ${UNKNOWN} \\$1 ${DATE}";
+        let expanded = expand_variables(template, &variables());
+        let snippet = Snippet::from_str(&expanded).unwrap();
+        assert_eq!(snippet.text(), "echo $HOME ${UNKNOWN} $1 2024-05-01");
+    }
+}
@@ -537,3 +537,44 @@ pub fn reset(&self) {
         self.progress.set(FindProgress::Started);
     }
 }
+
+/// The first of the `matches` starting from `offset` which isn't one of the
+/// `selected` ranges, going round to the first ones after the last
+pub fn next_unselected_match(
+    matches: &[(usize, usize)],
+    selected: &[(usize, usize)],
+    offset: usize,
+) -> Option<(usize, usize)> {
+    let first = matches.partition_point(|(start, _)| *start < offset);
+    matches[first..]
+        .iter()
+        .chain(&matches[..first])
+        .find(|m| !selected.contains(m))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_unselected_match;
+
+    #[test]
+    fn test_next_unselected_match() {
+        let matches = [(2, 5), (10, 13), (20, 23)];
+        assert_eq!(next_unselected_match(&matches, &[], 0), Some((2, 5)));
+        assert_eq!(
+            next_unselected_match(&matches, &[(2, 5)], 5),
+            Some((10, 13))
+        );
+        // Going round past the last match
+        assert_eq!(
+            next_unselected_match(&matches, &[(20, 23)], 23),
+            Some((2, 5))
+        );
+        assert_eq!(
+            next_unselected_match(&matches, &[(2, 5), (20, 23)], 23),
+            Some((10, 13))
+        );
+        assert_eq!(next_unselected_match(&matches, &matches, 0), None);
+        assert_eq!(next_unselected_match(&[], &[], 0), None);
+    }
+}
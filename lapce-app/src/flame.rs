@@ -0,0 +1,410 @@
+//! Flame graphs of the profiles in the collapsed stack format, which is one
+//! line for each distinct stack with its frames from the root separated by
+//! semicolons and the number of samples, as written by `cargo flamegraph`,
+//! `inferno-collapse-perf` or `stackcollapse-perf.pl`, or of the output of
+//! `perf script`, which gets collapsed here.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use floem::{
+    action::open_file,
+    ext_event::create_ext_action,
+    file::FileDialogOptions,
+    reactive::{RwSignal, Scope},
+};
+
+use crate::{
+    command::InternalCommand,
+    editor::location::{location_at, EditorLocation, EditorPosition},
+    window_tab::CommonData,
+};
+
+/// A frame of the graph, with the samples of all the stacks going through
+/// it, including those of its children
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlameNode {
+    pub name: String,
+    pub value: u64,
+    pub children: Vec<FlameNode>,
+}
+
+impl FlameNode {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: 0,
+            children: Vec::new(),
+        }
+    }
+
+    /// Add the samples of a stack, whose frames go from the root to the leaf
+    fn add<'a>(&mut self, mut frames: impl Iterator<Item = &'a str>, value: u64) {
+        self.value += value;
+        let Some(frame) = frames.next() else {
+            return;
+        };
+        let index = match self.children.iter().position(|c| c.name == frame) {
+            Some(index) => index,
+            None => {
+                self.children.push(FlameNode::new(frame));
+                self.children.len() - 1
+            }
+        };
+        self.children[index].add(frames, value);
+    }
+
+    /// The children of every node ordered by their names, the way flame
+    /// graphs are drawn so that the same frames line up
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| a.name.cmp(&b.name));
+        for child in self.children.iter_mut() {
+            child.sort();
+        }
+    }
+
+    /// The node at the end of a path of child indices
+    pub fn descendant(&self, path: &[usize]) -> Option<&FlameNode> {
+        path.iter()
+            .try_fold(self, |node, index| node.children.get(*index))
+    }
+}
+
+/// The samples of a profile, the root frame standing for all of them
+pub fn parse_profile(content: &str) -> FlameNode {
+    let is_perf_script = content
+        .lines()
+        .any(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty());
+    let mut root = FlameNode::new("all");
+    if is_perf_script {
+        for (stack, count) in collapse_perf_script(content) {
+            root.add(stack.iter().map(String::as_str), count);
+        }
+    } else {
+        for line in content.lines() {
+            let Some((stack, count)) = line.trim_end().rsplit_once(' ') else {
+                continue;
+            };
+            let Ok(count) = count.parse::<u64>() else {
+                continue;
+            };
+            root.add(stack.split(';').filter(|f| !f.is_empty()), count);
+        }
+    }
+    root.sort();
+    root
+}
+
+/// The distinct stacks of the samples printed by `perf script`, each being a
+/// header line with the command and the event followed by the frames from
+/// the leaf to the root, one on each indented line:
+///
+/// ```text
+/// myapp 1234 100.123: 250000 cycles:
+///         55d4c2a1b2c3 myapp::compute+0x23 (/usr/bin/myapp)
+///         55d4c2a1b000 main+0x10 (/usr/bin/myapp)
+/// ```
+fn collapse_perf_script(content: &str) -> Vec<(Vec<String>, u64)> {
+    let mut counts: HashMap<Vec<String>, u64> = HashMap::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut command: Option<String> = None;
+    let mut flush = |command: &mut Option<String>, stack: &mut Vec<String>| {
+        if let Some(command) = command.take() {
+            stack.push(command);
+            stack.reverse();
+            *counts.entry(std::mem::take(stack)).or_default() += 1;
+        }
+        stack.clear();
+    };
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            flush(&mut command, &mut stack);
+        } else if line.starts_with([' ', '\t']) {
+            if command.is_some() {
+                stack.push(perf_frame_name(line.trim()));
+            }
+        } else if !line.starts_with('#') {
+            flush(&mut command, &mut stack);
+            command = line.split_whitespace().next().map(str::to_string);
+        }
+    }
+    flush(&mut command, &mut stack);
+    counts.into_iter().collect()
+}
+
+/// The function of a frame line of `perf script`, without its address, its
+/// offset into the function and its module
+fn perf_frame_name(line: &str) -> String {
+    let without_address = match line.split_once(char::is_whitespace) {
+        Some((address, rest)) if address.chars().all(|c| c.is_ascii_hexdigit()) => {
+            rest.trim_start()
+        }
+        _ => line,
+    };
+    let name = match without_address.rfind(" (") {
+        Some(i) if without_address.ends_with(')') => &without_address[..i],
+        _ => without_address,
+    };
+    let name = match name.rfind("+0x") {
+        Some(i) => &name[..i],
+        None => name,
+    };
+    if name.is_empty() || name == "[unknown]" {
+        "[unknown]".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// The `file:line` a frame name has, such as the ones of profilers resolving
+/// inlined frames, `compute (src/lib.rs:12)`, only counting paths with an
+/// extension so that the names of functions aren't taken for files.
+pub fn frame_location(name: &str) -> Option<(&str, EditorPosition)> {
+    name.match_indices(':').find_map(|(i, _)| {
+        let (path, position) = location_at(name, i)?;
+        let file_name = path.rsplit(['/', '\\']).next()?;
+        file_name
+            .rsplit_once('.')
+            .is_some_and(|(stem, ext)| {
+                !stem.is_empty() && ext.chars().all(char::is_alphanumeric)
+            })
+            .then_some((path, position))
+    })
+}
+
+/// A frame of the graph where it's drawn, its start and width being the
+/// fractions of the width of the graph
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlameRect {
+    /// The path of child indices from the root to the frame
+    pub path: Vec<usize>,
+    pub depth: usize,
+    pub start: f64,
+    pub width: f64,
+    pub name: String,
+    pub value: u64,
+}
+
+/// The frames to draw with the node at `zoom` taking the whole width, its
+/// ancestors above it, leaving out the frames narrower than `min_width`.
+pub fn flame_rects(
+    root: &FlameNode,
+    zoom: &[usize],
+    min_width: f64,
+) -> Vec<FlameRect> {
+    let mut rects = Vec::new();
+    let mut node = root;
+    for depth in 0..=zoom.len() {
+        rects.push(FlameRect {
+            path: zoom[..depth].to_vec(),
+            depth,
+            start: 0.0,
+            width: 1.0,
+            name: node.name.clone(),
+            value: node.value,
+        });
+        if let Some(child) = zoom.get(depth).and_then(|i| node.children.get(*i)) {
+            node = child;
+        } else {
+            break;
+        }
+    }
+    if node.value == 0 {
+        return rects;
+    }
+
+    let total = node.value as f64;
+    let mut stack = vec![(node, zoom.to_vec(), zoom.len(), 0.0)];
+    while let Some((node, path, depth, start)) = stack.pop() {
+        let mut child_start = start;
+        for (i, child) in node.children.iter().enumerate() {
+            let width = child.value as f64 / total;
+            if width >= min_width {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                rects.push(FlameRect {
+                    path: child_path.clone(),
+                    depth: depth + 1,
+                    start: child_start,
+                    width,
+                    name: child.name.clone(),
+                    value: child.value,
+                });
+                stack.push((child, child_path, depth + 1, child_start));
+            }
+            child_start += width;
+        }
+    }
+    rects
+}
+
+/// A loaded profile
+pub struct FlameGraph {
+    pub path: PathBuf,
+    pub root: FlameNode,
+}
+
+#[derive(Clone)]
+pub struct FlameData {
+    pub graph: RwSignal<Option<Rc<FlameGraph>>>,
+    /// The path of child indices to the frame taking the whole width
+    pub zoom: RwSignal<Vec<usize>>,
+    pub hovered: RwSignal<Option<FlameRect>>,
+    pub error: RwSignal<Option<String>>,
+    pub common: Rc<CommonData>,
+}
+
+impl FlameData {
+    pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
+        Self {
+            graph: cx.create_rw_signal(None),
+            zoom: cx.create_rw_signal(Vec::new()),
+            hovered: cx.create_rw_signal(None),
+            error: cx.create_rw_signal(None),
+            common,
+        }
+    }
+
+    /// Pick a profile to load with the file dialog
+    pub fn open(&self) {
+        let data = self.clone();
+        open_file(FileDialogOptions::new(), move |file| {
+            if let Some(path) = file.and_then(|mut file| file.path.pop()) {
+                data.load(path);
+            }
+        });
+    }
+
+    pub fn load(&self, path: PathBuf) {
+        let data = self.clone();
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: std::io::Result<FlameNode>| match result {
+                Ok(root) => {
+                    data.zoom.set(Vec::new());
+                    data.hovered.set(None);
+                    data.error.set(None);
+                    data.graph.set(Some(Rc::new(FlameGraph {
+                        path: path.clone(),
+                        root,
+                    })));
+                }
+                Err(e) => {
+                    data.error.set(Some(format!(
+                        "Failed to load {}: {e}",
+                        path.display()
+                    )));
+                }
+            },
+        );
+        let file = path.clone();
+        std::thread::spawn(move || {
+            send(
+                std::fs::read_to_string(&file)
+                    .map(|content| parse_profile(&content)),
+            );
+        });
+    }
+
+    /// Open the source of a frame whose name has a `file:line` in it, a
+    /// relative path being resolved against the workspace
+    pub fn jump_to_source(&self, name: &str) {
+        let Some((path, position)) = frame_location(name) else {
+            return;
+        };
+        let path = Path::new(path);
+        let path = match self.common.workspace.path.as_ref() {
+            Some(workspace) if path.is_relative() => workspace.join(path),
+            _ => path.to_path_buf(),
+        };
+        self.common
+            .internal_command
+            .send(InternalCommand::JumpToLocation {
+                location: EditorLocation {
+                    path,
+                    position: Some(position),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                },
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flame_rects, frame_location, parse_profile, EditorPosition};
+
+    #[test]
+    fn test_parse_collapsed() {
+        let root = parse_profile("main;a;b 3\nmain;c 1\nmain;a 2\nbad line\n");
+        assert_eq!(root.value, 6);
+        let main = &root.children[0];
+        assert_eq!(main.name, "main");
+        assert_eq!(
+            main.children
+                .iter()
+                .map(|c| (c.name.as_str(), c.value))
+                .collect::<Vec<_>>(),
+            vec![("a", 5), ("c", 1)]
+        );
+        assert_eq!(main.children[0].children[0].value, 3);
+    }
+
+    #[test]
+    fn test_parse_perf_script() {
+        let content = "\
+myapp 1234 100.1: 250000 cycles:
+\t55d4c2a1b2c3 myapp::compute+0x23 (/usr/bin/myapp)
+\t55d4c2a1b000 main+0x10 (/usr/bin/myapp)
+
+myapp 1234 100.2: 250000 cycles:
+\t55d4c2a1b2c3 myapp::compute+0x2f (/usr/bin/myapp)
+\t55d4c2a1b000 main+0x10 (/usr/bin/myapp)
+
+myapp 1234 100.3: 250000 cycles:
+\t55d4c2a1b000 main+0x18 (/usr/bin/myapp)
+";
+        let root = parse_profile(content);
+        assert_eq!(root.value, 3);
+        let main = root.descendant(&[0, 0]).unwrap();
+        assert_eq!(root.children[0].name, "myapp");
+        assert_eq!(main.name, "main");
+        assert_eq!(main.value, 3);
+        assert_eq!(main.children[0].name, "myapp::compute");
+        assert_eq!(main.children[0].value, 2);
+    }
+
+    #[test]
+    fn test_flame_rects() {
+        let root = parse_profile("main;a 3\nmain;b 1\n");
+        let rects = flame_rects(&root, &[], 0.0);
+        let b = rects.iter().find(|r| r.name == "b").unwrap();
+        assert_eq!((b.depth, b.start, b.width), (2, 0.75, 0.25));
+
+        let rects = flame_rects(&root, &[0, 1], 0.0);
+        assert_eq!(
+            rects
+                .iter()
+                .map(|r| (r.name.as_str(), r.width))
+                .collect::<Vec<_>>(),
+            vec![("all", 1.0), ("main", 1.0), ("b", 1.0)]
+        );
+
+        let rects = flame_rects(&root, &[], 0.5);
+        assert!(rects.iter().all(|r| r.name != "b"));
+    }
+
+    #[test]
+    fn test_frame_location() {
+        assert_eq!(
+            frame_location("compute (src/lib.rs:12)"),
+            Some(("src/lib.rs", EditorPosition::Line(11)))
+        );
+        assert_eq!(frame_location("std::rt::lang_start"), None);
+        assert_eq!(frame_location("core::ops::function:12"), None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Laying out text in the fonts of the editor and of the UI in the background
+//! at startup, so that the fonts are loaded and the shaping caches of the text
+//! system are filled before the first paint of a large file. Those caches
+//! can't be written to disk, so the characters outside ASCII seen in the
+//! opened documents are kept instead, and warmed up with in the next session.
+
+use std::{collections::BTreeSet, time::Instant};
+
+use floem::{
+    cosmic_text::{Attrs, AttrsList, FamilyOwned, Style, TextLayout, Weight},
+    reactive::use_context,
+};
+use lapce_xi_rope::Rope;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::{
+    config::LapceConfig,
+    db::LapceDb,
+    perf::{self, PerfSubsystem},
+};
+
+/// The most characters outside ASCII kept for the next session
+const MAX_SEEN_CHARS: usize = 2048;
+/// How much of the start of a document is looked at for characters outside
+/// ASCII, about what its first paint shows
+const SCAN_LEN: usize = 64 * 1024;
+
+static SEEN_CHARS: Lazy<Mutex<BTreeSet<char>>> =
+    Lazy::new(|| Mutex::new(BTreeSet::new()));
+
+/// The fonts to warm up, read from the config
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FontWarmup {
+    families: Vec<String>,
+    sizes: Vec<usize>,
+}
+
+impl FontWarmup {
+    pub fn new(config: &LapceConfig) -> Self {
+        let mut families = vec![config.editor.font_family.clone()];
+        if config.ui.font_family != config.editor.font_family {
+            families.push(config.ui.font_family.clone());
+        }
+        let mut sizes = vec![
+            config.editor.font_size(),
+            config.editor.inlay_hint_font_size(),
+            config.editor.error_lens_font_size(),
+            config.editor.completion_lens_font_size(),
+            config.ui.font_size(),
+        ];
+        sizes.sort_unstable();
+        sizes.dedup();
+        Self { families, sizes }
+    }
+
+    /// Lay out the text in every font, size and style on another thread
+    pub fn run(self, chars: &BTreeSet<char>) {
+        let text = warmup_text(chars);
+        std::thread::Builder::new()
+            .name("FontWarmup".to_owned())
+            .spawn(move || {
+                let start = Instant::now();
+                for family in &self.families {
+                    let family: Vec<FamilyOwned> =
+                        FamilyOwned::parse_list(family).collect();
+                    for size in &self.sizes {
+                        for (weight, style) in [
+                            (Weight::NORMAL, Style::Normal),
+                            (Weight::BOLD, Style::Normal),
+                            (Weight::NORMAL, Style::Italic),
+                        ] {
+                            let attrs = Attrs::new()
+                                .family(&family)
+                                .font_size(*size as f32)
+                                .weight(weight)
+                                .style(style);
+                            let mut text_layout = TextLayout::new();
+                            text_layout.set_text(&text, AttrsList::new(attrs));
+                        }
+                    }
+                }
+                perf::record(PerfSubsystem::FontWarmup, start.elapsed());
+            })
+            .ok();
+    }
+}
+
+/// Warm up the fonts of the config with the characters seen in the last
+/// session
+pub fn warm_up(config: &LapceConfig) {
+    let db: Option<std::sync::Arc<LapceDb>> = use_context();
+    let mut seen = SEEN_CHARS.lock();
+    if seen.is_empty() {
+        if let Some(chars) = db.and_then(|db| db.get_seen_chars().ok()) {
+            *seen = chars.into_iter().take(MAX_SEEN_CHARS).collect();
+        }
+    }
+    FontWarmup::new(config).run(&seen);
+}
+
+/// Remember the characters outside ASCII at the start of a document, for
+/// warming up with in the next session
+pub fn note_text(text: &Rope) {
+    let mut seen = SEEN_CHARS.lock();
+    let mut scanned = 0;
+    // Whole chunks, as the scan length may not be at a char boundary
+    for chunk in text.iter_chunks(..) {
+        if scanned >= SCAN_LEN {
+            break;
+        }
+        add_non_ascii_chars(&mut seen, chunk, MAX_SEEN_CHARS);
+        scanned += chunk.len();
+    }
+}
+
+/// The characters outside ASCII seen in this session and the last ones
+pub fn seen_chars() -> BTreeSet<char> {
+    SEEN_CHARS.lock().clone()
+}
+
+/// Add the characters of `text` outside ASCII to `chars`, keeping at most
+/// `limit`
+fn add_non_ascii_chars(chars: &mut BTreeSet<char>, text: &str, limit: usize) {
+    if text.is_ascii() {
+        return;
+    }
+    for c in text.chars().filter(|c| !c.is_ascii() && !c.is_control()) {
+        if chars.len() >= limit {
+            return;
+        }
+        chars.insert(c);
+    }
+}
+
+/// The printable ASCII characters followed by `chars`
+fn warmup_text(chars: &BTreeSet<char>) -> String {
+    (' '..='~').chain(chars.iter().copied()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{add_non_ascii_chars, warmup_text};
+
+    #[test]
+    fn test_add_non_ascii_chars() {
+        let mut chars = BTreeSet::new();
+        add_non_ascii_chars(&mut chars, "plain text", 10);
+        assert!(chars.is_empty());
+
+        add_non_ascii_chars(&mut chars, "naïve → café\u{85}", 10);
+        assert_eq!(chars.into_iter().collect::<String>(), "éï→");
+
+        let mut chars = BTreeSet::new();
+        add_non_ascii_chars(&mut chars, "αβγδ", 2);
+        assert_eq!(chars.len(), 2);
+    }
+
+    #[test]
+    fn test_warmup_text() {
+        let text = warmup_text(&BTreeSet::from(['é']));
+        assert!(text.starts_with(" !\"#"));
+        assert!(text.ends_with("}~é"));
+        assert_eq!(text.chars().count(), 96);
+    }
+}
@@ -0,0 +1,538 @@
+//! The formatters of the configuration formats built in, for the documents
+//! no language server formats.
+
+use std::ops::Range;
+
+use lapce_core::language::LapceLanguage;
+use lapce_xi_rope::Rope;
+
+use crate::json_tree::{parse_json_tree, JsonKey, JsonKind, JsonNode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinFormat {
+    Json,
+    JsonMinify,
+    JsonSortKeys,
+    Toml,
+    Xml,
+}
+
+impl BuiltinFormat {
+    /// The formatter the documents of a language are formatted with when no
+    /// language server formats them
+    pub fn for_language(language: LapceLanguage) -> Option<Self> {
+        match language {
+            LapceLanguage::Json => Some(BuiltinFormat::Json),
+            LapceLanguage::Toml => Some(BuiltinFormat::Toml),
+            LapceLanguage::Xml => Some(BuiltinFormat::Xml),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinFormat::Json
+            | BuiltinFormat::JsonMinify
+            | BuiltinFormat::JsonSortKeys => "JSON",
+            BuiltinFormat::Toml => "TOML",
+            BuiltinFormat::Xml => "XML",
+        }
+    }
+
+    /// The formatted text, with its lines ending with `line_ending`
+    pub fn format(
+        &self,
+        text: &Rope,
+        indent: &str,
+        line_ending: &str,
+    ) -> Result<String, String> {
+        let formatted = match self {
+            BuiltinFormat::Json => format_json(text, Some(indent), false),
+            BuiltinFormat::JsonMinify => format_json(text, None, false),
+            BuiltinFormat::JsonSortKeys => format_json(text, Some(indent), true),
+            BuiltinFormat::Toml => format_toml(text, indent),
+            BuiltinFormat::Xml => format_xml(text, indent),
+        }?;
+        if line_ending == "\n" {
+            Ok(formatted)
+        } else {
+            Ok(formatted.replace('\n', line_ending))
+        }
+    }
+}
+
+/// The text with its lines ending with `\n`
+fn normalized(text: &Rope) -> String {
+    text.to_string().replace("\r\n", "\n")
+}
+
+/// The JSON pretty printed with `indent`, or minified without it, the
+/// numbers and strings kept as written
+pub fn format_json(
+    text: &Rope,
+    indent: Option<&str>,
+    sort_keys: bool,
+) -> Result<String, String> {
+    let text = normalized(text);
+    let root = parse_json_tree(&text)?;
+    let mut formatted = String::new();
+    write_json(&root, &text, indent, sort_keys, 0, &mut formatted);
+    if text.ends_with('\n') {
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+fn write_json(
+    node: &JsonNode,
+    text: &str,
+    indent: Option<&str>,
+    sort_keys: bool,
+    depth: usize,
+    out: &mut String,
+) {
+    let (open, close) = match node.kind {
+        JsonKind::Object => ('{', '}'),
+        JsonKind::Array => ('[', ']'),
+        _ => {
+            out.push_str(&text[node.range.clone()]);
+            return;
+        }
+    };
+    out.push(open);
+    if node.children.is_empty() {
+        out.push(close);
+        return;
+    }
+
+    let mut children = node.children.iter().collect::<Vec<_>>();
+    if sort_keys {
+        children.sort_by(|a, b| key_name(a).cmp(key_name(b)));
+    }
+    for (i, child) in children.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if let Some(indent) = indent {
+            out.push('\n');
+            out.push_str(&indent.repeat(depth + 1));
+        }
+        if let Some(key_range) = &child.key_range {
+            out.push_str(&text[key_range.clone()]);
+            out.push(':');
+            if indent.is_some() {
+                out.push(' ');
+            }
+        }
+        write_json(child, text, indent, sort_keys, depth + 1, out);
+    }
+    if let Some(indent) = indent {
+        out.push('\n');
+        out.push_str(&indent.repeat(depth));
+    }
+    out.push(close);
+}
+
+fn key_name(node: &JsonNode) -> &str {
+    match &node.key {
+        Some(JsonKey::Name(name)) => name,
+        _ => "",
+    }
+}
+
+/// The TOML with the space around the `=` of the keys and the blank lines
+/// made regular, and the multi-line arrays and inline tables indented, the
+/// comments and the multi-line strings kept as they are
+pub fn format_toml(text: &Rope, indent: &str) -> Result<String, String> {
+    let text = normalized(text);
+    text.parse::<toml_edit::Document>()
+        .map_err(|e| e.to_string())?;
+
+    let mut formatted = String::new();
+    let mut scan = TomlScan::default();
+    let mut blank = false;
+    let mut after_comment = false;
+    for line in text.lines() {
+        if scan.multiline.is_some() {
+            formatted.push_str(line);
+            formatted.push('\n');
+            scan.line(line);
+            continue;
+        }
+
+        let depth = scan.depth;
+        let line = line.trim();
+        if line.is_empty() {
+            blank = true;
+            continue;
+        }
+        let is_header = depth == 0 && line.starts_with('[');
+        if !formatted.is_empty() && (blank || (is_header && !after_comment)) {
+            formatted.push('\n');
+        }
+        blank = false;
+        after_comment = line.starts_with('#');
+
+        let level = if line.starts_with([']', '}']) {
+            depth.saturating_sub(1)
+        } else {
+            depth
+        };
+        formatted.push_str(&indent.repeat(level));
+        match scan.line(line) {
+            Some(eq) if depth == 0 && !is_header => {
+                formatted.push_str(line[..eq].trim_end());
+                formatted.push_str(" = ");
+                formatted.push_str(line[eq + 1..].trim_start());
+            }
+            _ => formatted.push_str(line),
+        }
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+/// Where the lines of a TOML document start, in a multi-line string or in
+/// brackets
+#[derive(Default)]
+struct TomlScan {
+    /// The delimiter of the multi-line string the line is in
+    multiline: Option<&'static str>,
+    /// How many arrays and inline tables the line is in
+    depth: usize,
+}
+
+impl TomlScan {
+    /// Go over a line, returning the offset of the `=` of its key when it's
+    /// not in brackets
+    fn line(&mut self, line: &str) -> Option<usize> {
+        let bytes = line.as_bytes();
+        let mut eq = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            if let Some(delimiter) = self.multiline {
+                if bytes[i..].starts_with(delimiter.as_bytes()) {
+                    self.multiline = None;
+                    i += delimiter.len();
+                } else if delimiter == "\"\"\"" && bytes[i] == b'\\' {
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+            match bytes[i] {
+                b'#' => break,
+                quote @ (b'"' | b'\'') => {
+                    let delimiter = if quote == b'"' { "\"\"\"" } else { "'''" };
+                    if bytes[i..].starts_with(delimiter.as_bytes()) {
+                        self.multiline = Some(delimiter);
+                        i += delimiter.len();
+                        continue;
+                    }
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote {
+                        if quote == b'"' && bytes[i] == b'\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                b'[' | b'{' => {
+                    self.depth += 1;
+                    i += 1;
+                }
+                b']' | b'}' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    i += 1;
+                }
+                b'=' if self.depth == 0 && eq.is_none() => {
+                    eq = Some(i);
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+        eq
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum XmlToken<'a> {
+    Open {
+        raw: &'a str,
+        name: &'a str,
+        self_closing: bool,
+    },
+    Close {
+        raw: &'a str,
+        name: &'a str,
+    },
+    /// A comment, a processing instruction, a doctype or a CDATA section
+    Other(&'a str),
+    Text(&'a str),
+}
+
+fn xml_tokens(text: &str) -> Result<Vec<XmlToken<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        if !rest.starts_with('<') {
+            let len = rest.find('<').unwrap_or(rest.len());
+            tokens.push(XmlToken::Text(&rest[..len]));
+            pos += len;
+            continue;
+        }
+
+        let delimiter = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")]
+            .into_iter()
+            .find(|(start, _)| rest.starts_with(start));
+        let len = if let Some((_, end)) = delimiter {
+            rest.find(end).map(|i| i + end.len())
+        } else {
+            tag_len(rest)
+        }
+        .ok_or_else(|| format!("unterminated markup at offset {pos}"))?;
+        let raw = &rest[..len];
+        pos += len;
+
+        if delimiter.is_some() || raw.starts_with("<!") {
+            tokens.push(XmlToken::Other(raw));
+        } else if let Some(name) = raw.strip_prefix("</") {
+            let name = name.trim_end_matches('>').trim();
+            tokens.push(XmlToken::Close { raw, name });
+        } else {
+            let name = raw[1..]
+                .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                .next()
+                .unwrap_or_default();
+            tokens.push(XmlToken::Open {
+                raw,
+                name,
+                self_closing: raw.ends_with("/>"),
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// The length of the tag at the start of `text`, to its `>` outside the
+/// quotes of the attributes and the brackets of a doctype
+fn tag_len(text: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth = depth.saturating_sub(1),
+            (None, '>') if depth == 0 => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The XML with each element on its own line, indented with `indent`, the
+/// elements with only text in them kept on one line
+pub fn format_xml(text: &Rope, indent: &str) -> Result<String, String> {
+    let text = normalized(text);
+    let tokens = xml_tokens(&text)?;
+
+    let mut open = Vec::new();
+    for token in &tokens {
+        match token {
+            XmlToken::Open {
+                name,
+                self_closing: false,
+                ..
+            } => open.push(*name),
+            XmlToken::Close { name, .. } => match open.pop() {
+                Some(expected) if expected == *name => {}
+                Some(expected) => {
+                    return Err(format!("expected </{expected}>, found </{name}>"));
+                }
+                None => return Err(format!("</{name}> closes no element")),
+            },
+            _ => {}
+        }
+    }
+    if let Some(name) = open.pop() {
+        return Err(format!("<{name}> isn't closed"));
+    }
+
+    let mut formatted = String::new();
+    let mut push_line = |depth: usize, line: &str| {
+        formatted.push_str(&indent.repeat(depth));
+        formatted.push_str(line);
+        formatted.push('\n');
+    };
+    let mut depth = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (
+                XmlToken::Open {
+                    raw,
+                    self_closing: false,
+                    ..
+                },
+                Some(XmlToken::Text(text)),
+                Some(XmlToken::Close { raw: close, .. }),
+            ) if !text.trim().is_empty() => {
+                push_line(depth, &format!("{raw}{text}{close}"));
+                i += 3;
+                continue;
+            }
+            (
+                XmlToken::Open {
+                    raw,
+                    self_closing: false,
+                    ..
+                },
+                Some(XmlToken::Close { raw: close, .. }),
+                _,
+            ) => {
+                push_line(depth, &format!("{raw}{close}"));
+                i += 2;
+                continue;
+            }
+            (
+                XmlToken::Open {
+                    raw, self_closing, ..
+                },
+                _,
+                _,
+            ) => {
+                push_line(depth, raw);
+                if !self_closing {
+                    depth += 1;
+                }
+            }
+            (XmlToken::Close { raw, .. }, _, _) => {
+                depth = depth.saturating_sub(1);
+                push_line(depth, raw);
+            }
+            (XmlToken::Other(raw), _, _) => push_line(depth, raw),
+            (XmlToken::Text(text), _, _) => {
+                let text = text.trim();
+                if !text.is_empty() {
+                    push_line(depth, text);
+                }
+            }
+        }
+        i += 1;
+    }
+    Ok(formatted)
+}
+
+/// The range of `old` to replace and what with for it to become `new`, the
+/// start and end they have in common left out for the cursors there to stay
+pub fn changed_range<'a>(
+    old: &str,
+    new: &'a str,
+) -> Option<(Range<usize>, &'a str)> {
+    if old == new {
+        return None;
+    }
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take((old.len() - prefix).min(new.len() - prefix))
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix)
+        || !new.is_char_boundary(new.len() - suffix)
+    {
+        suffix -= 1;
+    }
+    Some((prefix..old.len() - suffix, &new[prefix..new.len() - suffix]))
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_xi_rope::Rope;
+
+    use super::{
+        changed_range, format_json, format_toml, format_xml, BuiltinFormat,
+    };
+
+    #[test]
+    fn test_format_json() {
+        let text = Rope::from("{\"b\": [1, 2.50, {}],\n \"a\":\"\\u00e9\"}\n");
+        assert_eq!(
+            format_json(&text, Some("  "), false).unwrap(),
+            "{\n  \"b\": [\n    1,\n    2.50,\n    {}\n  ],\n  \
+             \"a\": \"\\u00e9\"\n}\n"
+        );
+        assert_eq!(
+            format_json(&text, None, false).unwrap(),
+            "{\"b\":[1,2.50,{}],\"a\":\"\\u00e9\"}\n"
+        );
+        assert_eq!(
+            format_json(&text, None, true).unwrap(),
+            "{\"a\":\"\\u00e9\",\"b\":[1,2.50,{}]}\n"
+        );
+        assert!(format_json(&Rope::from("{\"a\": 1,}"), None, false).is_err());
+    }
+
+    #[test]
+    fn test_format_toml() {
+        let text = Rope::from(
+            "\n\nname=\"lapce\"   # the name\n[dependencies]\nfloem =  \"0.1\"\n\n\n\
+             list = [\n1,\n  2,\n]\n# the docs\n[docs]\n\
+             text = \"\"\"\n  kept  \n\"\"\"\n",
+        );
+        assert_eq!(
+            format_toml(&text, "    ").unwrap(),
+            "name = \"lapce\"   # the name\n\n[dependencies]\nfloem = \"0.1\"\n\n\
+             list = [\n    1,\n    2,\n]\n# the docs\n[docs]\n\
+             text = \"\"\"\n  kept  \n\"\"\"\n"
+        );
+        assert!(format_toml(&Rope::from("a = "), "    ").is_err());
+    }
+
+    #[test]
+    fn test_format_xml() {
+        let text = Rope::from(
+            "<?xml version=\"1.0\"?><root a=\"x > y\"><item>one</item>\n\
+             <!-- note --><empty></empty><br/><list><i>1</i></list></root>",
+        );
+        assert_eq!(
+            format_xml(&text, "  ").unwrap(),
+            "<?xml version=\"1.0\"?>\n<root a=\"x > y\">\n  <item>one</item>\n  \
+             <!-- note -->\n  <empty></empty>\n  <br/>\n  <list>\n    <i>1</i>\n  \
+             </list>\n</root>\n"
+        );
+        assert!(format_xml(&Rope::from("<a><b></a>"), "  ").is_err());
+    }
+
+    #[test]
+    fn test_line_ending() {
+        let text = Rope::from("{\"a\":1}\r\n");
+        assert_eq!(
+            BuiltinFormat::Json.format(&text, "  ", "\r\n").unwrap(),
+            "{\r\n  \"a\": 1\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_changed_range() {
+        assert_eq!(changed_range("abc", "abc"), None);
+        assert_eq!(changed_range("a = 1\nb", "a=1\nb"), Some((1..4, "=")));
+        assert_eq!(changed_range("xé", "xè"), Some((1..3, "è")));
+    }
+}
@@ -0,0 +1,138 @@
+//! The tree-sitter grammars of the grammars directory, `tree-sitter-<name>`
+//! libraries loaded for the languages whose grammar is `name`, and installing
+//! them from a URL or a local path.
+
+use std::{
+    env::consts::DLL_EXTENSION,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use lapce_core::directory::Directory;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstalledGrammar {
+    pub name: String,
+    pub path: PathBuf,
+    /// The URL or path the grammar was installed from, `None` for those which
+    /// come with Lapce
+    pub source: Option<String>,
+}
+
+/// The grammars of the grammars directory, by name
+pub fn installed_grammars() -> Vec<InstalledGrammar> {
+    let Some(dir) = Directory::grammars_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut grammars = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix("tree-sitter-")?
+                .strip_suffix(DLL_EXTENSION)?
+                .strip_suffix('.')?
+                .to_string();
+            let source = std::fs::read_to_string(source_file(&dir, &name))
+                .ok()
+                .map(|source| source.trim().to_string());
+            Some(InstalledGrammar { name, path, source })
+        })
+        .collect::<Vec<_>>();
+    grammars.sort_by(|a, b| a.name.cmp(&b.name));
+    grammars
+}
+
+/// Install the grammar library at a URL or a local path, in place of the
+/// grammar with the same name if there's one. Returns the name of the
+/// grammar and whether it replaced one.
+pub fn install_grammar(source: &str) -> Result<(String, bool)> {
+    let source = source.trim();
+    let is_url = source.starts_with("https://") || source.starts_with("http://");
+    let file_name = if is_url {
+        source
+            .split(['?', '#'])
+            .next()
+            .and_then(|url| url.rsplit('/').next())
+            .unwrap_or_default()
+    } else {
+        Path::new(source)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    };
+    let name = grammar_name(file_name).ok_or_else(|| {
+        anyhow!(
+            "{file_name:?} isn't a tree-sitter grammar library (.{DLL_EXTENSION})"
+        )
+    })?;
+
+    let (bytes, source) = if is_url {
+        let resp = reqwest::blocking::ClientBuilder::new()
+            .user_agent("Lapce")
+            .build()?
+            .get(source)
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("download failed with {}", resp.status()));
+        }
+        (resp.bytes()?.to_vec(), source.to_string())
+    } else {
+        let path = Path::new(source).canonicalize()?;
+        (std::fs::read(&path)?, path.to_string_lossy().into_owned())
+    };
+
+    let dir = Directory::grammars_directory()
+        .ok_or_else(|| anyhow!("can't get grammars directory"))?;
+    let target = dir.join(format!("tree-sitter-{name}.{DLL_EXTENSION}"));
+    let replaced = target.exists();
+    // The library may be loaded already, so it's replaced rather than
+    // written over
+    let partial = dir.join(format!(".tree-sitter-{name}.{DLL_EXTENSION}.part"));
+    std::fs::write(&partial, bytes)?;
+    std::fs::rename(&partial, &target)?;
+    std::fs::write(source_file(&dir, &name), source)?;
+
+    Ok((name, replaced))
+}
+
+/// The file keeping where a grammar was installed from
+fn source_file(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("tree-sitter-{name}.source"))
+}
+
+/// The name of the grammar in a library file, such as `rust` for
+/// `tree-sitter-rust.so` or `libtree-sitter-rust.so`
+fn grammar_name(file_name: &str) -> Option<String> {
+    let stem = file_name.strip_suffix(DLL_EXTENSION)?.strip_suffix('.')?;
+    let stem = stem.strip_prefix("lib").unwrap_or(stem);
+    let name = stem.strip_prefix("tree-sitter-").unwrap_or(stem);
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| name.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::consts::DLL_EXTENSION;
+
+    use super::grammar_name;
+
+    #[test]
+    fn test_grammar_name() {
+        let name = |stem: &str| grammar_name(&format!("{stem}.{DLL_EXTENSION}"));
+        assert_eq!(name("tree-sitter-rust"), Some("rust".to_string()));
+        assert_eq!(name("libtree-sitter-c_sharp"), Some("c_sharp".to_string()));
+        assert_eq!(name("Kotlin"), Some("kotlin".to_string()));
+        assert_eq!(name("tree-sitter-"), None);
+        assert_eq!(name("../rust"), None);
+        assert_eq!(grammar_name("tree-sitter-rust.zip"), None);
+    }
+}
@@ -0,0 +1,293 @@
+//! A windowless harness for the editing pipeline.
+//!
+//! [`HeadlessEditor`] sets up a local [`Doc`] and [`EditorData`] with the
+//! built-in default config and a proxy that is never started, so that key
+//! presses and commands can be driven from tests and the resulting visual
+//! lines, phantom text and style spans compared against a text snapshot.
+
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf, rc::Rc, sync::Arc};
+
+use floem::{
+    action::TimerToken,
+    keyboard::Modifiers,
+    peniko::kurbo::{Point, Rect, Size},
+    reactive::Scope,
+    views::editor::{
+        text::DocumentPhantom,
+        visual_line::{ResolvedWrap, VLine},
+    },
+    ViewId,
+};
+use lapce_core::{buffer::rope_text::RopeText, register::Register};
+use lapce_rpc::proxy::ProxyRpcHandler;
+
+use crate::{
+    accessibility::AccessibilityData,
+    command::{lapce_internal_commands, CommandKind, LapceCommand},
+    completion::CompletionData,
+    config::LapceConfig,
+    doc::Doc,
+    editor::EditorData,
+    find::Find,
+    hover::HoverData,
+    inline_completion::InlineCompletionData,
+    keypress::{KeyPressData, KeyPressFocus},
+    listener::Listener,
+    main_split::Editors,
+    notification::NotificationCenter,
+    refactor::RefactorJournal,
+    window::WindowCommonData,
+    window_tab::{CommonData, Focus},
+    workspace::LapceWorkspace,
+};
+
+pub struct HeadlessEditor {
+    pub scope: Scope,
+    pub common: Rc<CommonData>,
+    pub editor: EditorData,
+}
+
+impl HeadlessEditor {
+    /// Create an editor holding `text`, using the default config.
+    pub fn new(text: &str) -> Self {
+        Self::with_config(text, |_| {})
+    }
+
+    /// Create an editor holding `text`, with the default config adjusted by
+    /// `f`.
+    pub fn with_config(text: &str, f: impl FnOnce(&mut LapceConfig)) -> Self {
+        let cx = Scope::new();
+        let mut config = LapceConfig::headless();
+        f(&mut config);
+
+        let common = Rc::new(headless_common_data(cx, config));
+        let editors = Editors(cx.create_rw_signal(im::HashMap::new()));
+        let editor = editors.make_local(cx, common.clone());
+        editor.doc().reload(text.into(), true);
+        editor
+            .viewport()
+            .set(Rect::from_origin_size(Point::ZERO, Size::new(800.0, 600.0)));
+
+        Self {
+            scope: cx,
+            common,
+            editor,
+        }
+    }
+
+    pub fn doc(&self) -> Rc<Doc> {
+        self.editor.doc()
+    }
+
+    pub fn text(&self) -> String {
+        self.doc().buffer.with_untracked(|b| b.to_string())
+    }
+
+    pub fn offset(&self) -> usize {
+        self.editor.cursor().with_untracked(|c| c.offset())
+    }
+
+    /// The zero based line and column of the cursor
+    pub fn line_col(&self) -> (usize, usize) {
+        let offset = self.offset();
+        self.doc()
+            .buffer
+            .with_untracked(|b| b.offset_to_line_col(offset))
+    }
+
+    pub fn set_offset(&self, offset: usize) {
+        self.editor
+            .cursor()
+            .update(|c| c.set_offset(offset, false, false));
+    }
+
+    /// Wrap lines at the given width in pixels, or stop wrapping with `None`.
+    pub fn set_wrap_width(&self, width: Option<f32>) {
+        let wrap = width.map(ResolvedWrap::Width).unwrap_or(ResolvedWrap::None);
+        self.editor.editor.lines().set_wrap(wrap);
+        self.doc().clear_text_cache();
+    }
+
+    /// Run a command by the name it has in keymaps, e.g. `"down"`.
+    ///
+    /// # Panics
+    /// If there is no command with that name.
+    pub fn run(&self, name: &str) {
+        let command = lapce_internal_commands()
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| panic!("unknown command {name}"));
+        self.run_command(command);
+    }
+
+    pub fn run_command(&self, command: LapceCommand) {
+        self.editor.run_command(&command, None, Modifiers::empty());
+    }
+
+    pub fn run_kind(&self, kind: CommandKind) {
+        self.run_command(LapceCommand { kind, data: None });
+    }
+
+    /// Type the text one character at a time, like a key press for each.
+    pub fn type_text(&self, text: &str) {
+        let mut buf = [0; 4];
+        for c in text.chars() {
+            self.editor.receive_char(c.encode_utf8(&mut buf));
+        }
+    }
+
+    /// A text snapshot of the visual lines, with the phantom text and the
+    /// style spans of each buffer line.
+    pub fn snapshot(&self) -> String {
+        let doc = self.doc();
+        let ed = &self.editor.editor;
+        let mut out = String::new();
+        let mut last_line = None;
+        for info in ed.iter_vlines(false, VLine(0)) {
+            let line = info.rvline.line;
+            let text = doc.buffer.with_untracked(|b| {
+                b.slice_to_cow(info.interval.start..info.interval.end)
+                    .trim_end_matches(['\n', '\r'])
+                    .to_string()
+            });
+            let _ = writeln!(out, "{}:{} {:?}", line, info.rvline.line_index, text);
+
+            if last_line == Some(line) {
+                continue;
+            }
+            last_line = Some(line);
+
+            let phantom = ed
+                .es
+                .with_untracked(|es| doc.phantom_text(ed.id(), es, line));
+            for p in phantom.text.iter() {
+                let _ = writeln!(out, "  phantom {} {:?}", p.col, p.text);
+            }
+            for style in doc.line_style(line).iter() {
+                if let Some(fg) = &style.style.fg_color {
+                    let _ =
+                        writeln!(out, "  style {}..{} {fg}", style.start, style.end);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Drop for HeadlessEditor {
+    fn drop(&mut self) {
+        self.scope.dispose();
+    }
+}
+
+fn headless_common_data(cx: Scope, config: LapceConfig) -> CommonData {
+    let workspace = Arc::new(LapceWorkspace::default());
+    let keypress = cx.create_rw_signal(KeyPressData::new(cx, &config));
+    let (config, _) = cx.create_signal(Arc::new(config));
+    let (term_tx, _) = crossbeam_channel::unbounded();
+    let (term_notification_tx, _) = crossbeam_channel::unbounded();
+
+    let window_common = Rc::new(WindowCommonData {
+        window_command: Listener::new_empty(cx),
+        window_scale: cx.create_rw_signal(1.0),
+        size: cx.create_rw_signal(Size::ZERO),
+        num_window_tabs: cx.create_memo(|_| 1),
+        window_maximized: cx.create_rw_signal(false),
+        window_tab_header_height: cx.create_rw_signal(0.0),
+        latest_release: cx.create_rw_signal(Arc::new(None)).read_only(),
+        ime_allowed: cx.create_rw_signal(false),
+        cursor_blink_timer: cx.create_rw_signal(TimerToken::INVALID),
+        hide_cursor: cx.create_rw_signal(false),
+        focused: cx.create_rw_signal(true),
+        app_view_id: cx.create_rw_signal(ViewId::new()),
+        extra_plugin_paths: Arc::new(Vec::<PathBuf>::new()),
+    });
+
+    CommonData {
+        workspace,
+        scope: cx,
+        focus: cx.create_rw_signal(Focus::Workbench),
+        keypress,
+        completion: cx.create_rw_signal(CompletionData::new(cx, config)),
+        inline_completion: cx.create_rw_signal(InlineCompletionData::new(cx)),
+        hover: HoverData::new(cx),
+        register: cx.create_rw_signal(Register::default()),
+        find: Find::new(cx),
+        workbench_size: cx.create_rw_signal(Size::ZERO),
+        window_origin: cx.create_rw_signal(Point::ZERO),
+        internal_command: Listener::new_empty(cx),
+        lapce_command: Listener::new_empty(cx),
+        workbench_command: Listener::new_empty(cx),
+        editor_events: Listener::new_empty(cx),
+        term_tx,
+        term_notification_tx,
+        // Never started, requests to it are simply not answered
+        proxy: ProxyRpcHandler::new(),
+        view_id: cx.create_rw_signal(ViewId::new()),
+        ui_line_height: cx.create_memo(|_| 20.0),
+        dragging: cx.create_rw_signal(None),
+        config,
+        profile: cx.create_rw_signal(None),
+        proxy_status: cx.create_rw_signal(None),
+        mouse_hover_timer: cx.create_rw_signal(TimerToken::INVALID),
+        breakpoints: cx.create_rw_signal(BTreeMap::new()),
+        coverage: cx.create_rw_signal(im::HashMap::new()),
+        refactors: cx.create_rw_signal(RefactorJournal::default()),
+        notifications: cx.create_rw_signal(NotificationCenter::default()),
+        language_servers: cx.create_rw_signal(im::HashMap::new()),
+        keyboard_focus: cx.create_rw_signal(None),
+        accessibility: AccessibilityData::new(cx),
+        window_common,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use floem::action::TimerToken;
+
+    use super::HeadlessEditor;
+
+    #[test]
+    fn test_typing_and_cursor_movement() {
+        let editor = HeadlessEditor::new("hello\nworld\n");
+        editor.run("down");
+        assert_eq!(editor.line_col(), (1, 0));
+
+        editor.run("line_end");
+        editor.type_text("!");
+        assert_eq!(editor.text(), "hello\nworld!\n");
+        assert_eq!(editor.line_col(), (1, 6));
+    }
+
+    #[test]
+    fn test_no_blink_while_window_unfocused() {
+        let editor = HeadlessEditor::new("hello\n");
+        let window_common = &editor.common.window_common;
+
+        window_common.focused.set(false);
+        editor.editor.editor.cursor_info.reset();
+        assert_eq!(
+            window_common.cursor_blink_timer.get_untracked(),
+            TimerToken::INVALID
+        );
+        assert!(!window_common.hide_cursor.get_untracked());
+
+        window_common.focused.set(true);
+        editor.editor.editor.cursor_info.reset();
+        assert_ne!(
+            window_common.cursor_blink_timer.get_untracked(),
+            TimerToken::INVALID
+        );
+    }
+
+    #[test]
+    fn test_snapshot_wrapping() {
+        let editor = HeadlessEditor::new("aaaa bbbb cccc dddd\n");
+        let unwrapped = editor.snapshot();
+        assert!(unwrapped.starts_with("0:0 \"aaaa bbbb cccc dddd\"\n"));
+
+        editor.set_wrap_width(Some(1.0));
+        let wrapped = editor.snapshot();
+        assert!(wrapped.contains("0:1 "));
+    }
+}
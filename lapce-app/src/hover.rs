@@ -3,8 +3,9 @@
     reactive::{RwSignal, Scope},
     views::editor::id::EditorId,
 };
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, NumberOrString};
 
-use crate::markdown::MarkdownContent;
+use crate::{lsp::path_from_url, markdown::MarkdownContent};
 
 #[derive(Clone)]
 pub struct HoverData {
@@ -12,6 +13,8 @@ pub struct HoverData {
     pub offset: RwSignal<usize>,
     pub editor_id: RwSignal<EditorId>,
     pub content: RwSignal<Vec<MarkdownContent>>,
+    /// The diagnostics at the hovered offset, shown above the content
+    pub diagnostics: RwSignal<Vec<Diagnostic>>,
     pub layout_rect: RwSignal<Rect>,
 }
 
@@ -21,8 +24,86 @@ pub fn new(cx: Scope) -> Self {
             active: cx.create_rw_signal(false),
             offset: cx.create_rw_signal(0),
             content: cx.create_rw_signal(Vec::new()),
+            diagnostics: cx.create_rw_signal(Vec::new()),
             editor_id: cx.create_rw_signal(EditorId::next()),
             layout_rect: cx.create_rw_signal(Rect::ZERO),
         }
     }
 }
+
+/// Where a diagnostic comes from and its code, such as `rustc(E0308)`
+pub fn diagnostic_source(diagnostic: &Diagnostic) -> Option<String> {
+    let code = diagnostic.code.as_ref().map(|code| match code {
+        NumberOrString::Number(n) => n.to_string(),
+        NumberOrString::String(s) => s.clone(),
+    });
+    match (diagnostic.source.as_deref(), code) {
+        (Some(source), Some(code)) => Some(format!("{source}({code})")),
+        (Some(source), None) => Some(source.to_string()),
+        (None, Some(code)) => Some(code),
+        (None, None) => None,
+    }
+}
+
+/// The file name and the line and column, counted from one, of related
+/// information, followed by its message
+pub fn related_information_label(related: &DiagnosticRelatedInformation) -> String {
+    let path = path_from_url(&related.location.uri);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let start = related.location.range.start;
+    format!(
+        "{name}:{}:{}: {}",
+        start.line + 1,
+        start.character + 1,
+        related.message
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        Diagnostic, DiagnosticRelatedInformation, Location, NumberOrString,
+        Position, Range, Url,
+    };
+
+    use super::{diagnostic_source, related_information_label};
+
+    #[test]
+    fn test_diagnostic_source() {
+        let mut diagnostic = Diagnostic {
+            source: Some("rustc".to_string()),
+            code: Some(NumberOrString::String("E0308".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(
+            diagnostic_source(&diagnostic).as_deref(),
+            Some("rustc(E0308)")
+        );
+
+        diagnostic.code = Some(NumberOrString::Number(7));
+        diagnostic.source = None;
+        assert_eq!(diagnostic_source(&diagnostic).as_deref(), Some("7"));
+
+        diagnostic.code = None;
+        assert_eq!(diagnostic_source(&diagnostic), None);
+    }
+
+    #[test]
+    fn test_related_information_label() {
+        let position = Position::new(11, 4);
+        let related = DiagnosticRelatedInformation {
+            location: Location {
+                uri: Url::parse("file:///src/main.rs").unwrap(),
+                range: Range::new(position, position),
+            },
+            message: "first borrow here".to_string(),
+        };
+        assert_eq!(
+            related_information_label(&related),
+            "main.rs:12:5: first borrow here"
+        );
+    }
+}
@@ -5,7 +5,9 @@
 pub type EditorTabId = Id;
 pub type SettingsId = Id;
 pub type KeymapId = Id;
+pub type RunConfigsId = Id;
 pub type ThemeColorSettingsId = Id;
 pub type VoltViewId = Id;
+pub type LargeFileViewId = Id;
 pub type DiffEditorId = Id;
 pub type TerminalTabId = Id;
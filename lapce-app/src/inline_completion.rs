@@ -26,9 +26,15 @@ pub struct InlineCompletionItem {
     pub range: Option<Range<usize>>,
     pub command: Option<lsp_types::Command>,
     pub insert_text_format: Option<InsertTextFormat>,
+    /// The plugin which provided the item, `None` for a language server
+    pub provider: Option<String>,
 }
 impl InlineCompletionItem {
-    pub fn from_lsp(buffer: &Buffer, item: lsp_types::InlineCompletionItem) -> Self {
+    pub fn from_lsp(
+        buffer: &Buffer,
+        item: lsp_types::InlineCompletionItem,
+        provider: Option<String>,
+    ) -> Self {
         let range = item.range.map(|r| {
             let start = buffer.offset_of_position(&r.start);
             let end = buffer.offset_of_position(&r.end);
@@ -40,6 +46,7 @@ pub fn from_lsp(buffer: &Buffer, item: lsp_types::InlineCompletionItem) -> Self
             range,
             command: item.command,
             insert_text_format: item.insert_text_format,
+            provider,
         }
     }
 
@@ -98,6 +105,13 @@ pub struct InlineCompletionData {
     pub items: im::Vector<InlineCompletionItem>,
     pub start_offset: usize,
     pub path: PathBuf,
+    /// The id of the last request for inline completions
+    pub request_id: u64,
+    /// Where the cursor was at the last request
+    pub request_offset: usize,
+    pub request_path: PathBuf,
+    /// The request the items are from
+    items_request_id: u64,
 }
 impl InlineCompletionData {
     pub fn new(cx: Scope) -> Self {
@@ -107,6 +121,10 @@ pub fn new(cx: Scope) -> Self {
             items: im::vector![],
             start_offset: 0,
             path: PathBuf::new(),
+            request_id: 0,
+            request_offset: 0,
+            request_path: PathBuf::new(),
+            items_request_id: 0,
         }
     }
 
@@ -142,21 +160,54 @@ pub fn cancel(&mut self) {
         self.status = InlineCompletionStatus::Inactive;
     }
 
-    /// Set the items for the inline completion.  
-    /// Sets `active` to `0` and `status` to `InlineCompletionStatus::Active`.
+    /// Start a request for the inline completions at `offset`, returning its
+    /// id. The items of the previous request are kept until those of this
+    /// one come, to avoid flickering.
+    pub fn start_request(&mut self, offset: usize, path: PathBuf) -> u64 {
+        self.request_id += 1;
+        self.request_offset = offset;
+        self.request_path = path;
+        self.status = InlineCompletionStatus::Started;
+        self.request_id
+    }
+
+    /// Set the items a provider gives for a request, in place of those it
+    /// gave before, `None` being the language servers. The items of the
+    /// providers named in the comma separated `priority` come first, in its
+    /// order. Sets `status` to `InlineCompletionStatus::Active`, returning
+    /// false when the request is stale and the items are ignored.
     pub fn set_items(
         &mut self,
+        request_id: u64,
+        provider: Option<&str>,
         items: im::Vector<InlineCompletionItem>,
-        start_offset: usize,
-        path: PathBuf,
-    ) {
+        priority: &str,
+    ) -> bool {
+        if request_id != self.request_id
+            || self.status == InlineCompletionStatus::Inactive
+        {
+            return false;
+        }
+
         batch(|| {
-            self.items = items;
-            self.active.set(0);
+            if self.items_request_id != request_id {
+                self.items.clear();
+                self.items_request_id = request_id;
+                self.active.set(0);
+            }
+            self.items
+                .retain(|item| item.provider.as_deref() != provider);
+            self.items.append(items);
+            let mut items = self.items.iter().cloned().collect::<Vec<_>>();
+            items.sort_by_key(|item| {
+                provider_rank(priority, item.provider.as_deref())
+            });
+            self.items = items.into();
             self.status = InlineCompletionStatus::Active;
-            self.start_offset = start_offset;
-            self.path = path;
+            self.start_offset = self.request_offset;
+            self.path = self.request_path.clone();
         });
+        true
     }
 
     pub fn update_doc(&self, doc: &Doc, offset: usize) {
@@ -226,6 +277,23 @@ pub fn update_inline_completion(
     }
 }
 
+/// The names in a comma separated list of inline completion providers
+pub fn provider_names(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// Where the items of a provider go among the others, those of the providers
+/// named in `priority` first
+fn provider_rank(priority: &str, provider: Option<&str>) -> usize {
+    provider
+        .and_then(|provider| {
+            provider_names(priority).position(|name| name == provider)
+        })
+        .unwrap_or(usize::MAX)
+}
+
 enum ICompletionRes {
     Hide,
     Unchanged,
@@ -286,3 +354,21 @@ fn inline_completion_text(
         ICompletionRes::Set(text.to_string(), prefix.len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{provider_names, provider_rank};
+
+    #[test]
+    fn test_provider_rank() {
+        let priority = "copilot, ,local-llm";
+        assert_eq!(
+            provider_names(priority).collect::<Vec<_>>(),
+            vec!["copilot", "local-llm"]
+        );
+        assert_eq!(provider_rank(priority, Some("copilot")), 0);
+        assert_eq!(provider_rank(priority, Some("local-llm")), 1);
+        assert_eq!(provider_rank(priority, Some("other")), usize::MAX);
+        assert_eq!(provider_rank(priority, None), usize::MAX);
+    }
+}
@@ -0,0 +1,404 @@
+//! The tree of the values of a JSON document, with the ranges of the text
+//! they are at, for the JSON panel and the JSON path of the cursor.
+
+use std::{collections::HashSet, ops::Range, rc::Rc};
+
+use floem::reactive::{RwSignal, Scope};
+use serde::de::IgnoredAny;
+
+/// How much of a string or number is shown in the tree
+const MAX_PREVIEW_LEN: usize = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonKind {
+    Object,
+    Array,
+    String,
+    Number,
+    Bool,
+    Null,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonKey {
+    Name(String),
+    Index(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonNode {
+    /// The name of the member or the index of the item, `None` for the root
+    pub key: Option<JsonKey>,
+    /// Where the name of the member is, with its quotes
+    pub key_range: Option<Range<usize>>,
+    pub kind: JsonKind,
+    /// Where the value is
+    pub range: Range<usize>,
+    /// The value as written for the strings, numbers and literals
+    pub preview: String,
+    pub children: Vec<JsonNode>,
+}
+
+impl JsonNode {
+    /// The path of the deepest node at `offset`, on its value or its name
+    pub fn path_at(&self, offset: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut node = self;
+        while let Some((i, child)) =
+            node.children.iter().enumerate().find(|(_, child)| {
+                child.range.start <= offset && offset <= child.range.end
+                    || child
+                        .key_range
+                        .as_ref()
+                        .is_some_and(|r| r.start <= offset && offset < r.end)
+            })
+        {
+            path.push(i);
+            node = child;
+        }
+        path
+    }
+
+    fn label(&self) -> String {
+        let value = match self.kind {
+            JsonKind::Object => format!("{{{}}}", self.children.len()),
+            JsonKind::Array => format!("[{}]", self.children.len()),
+            _ => self.preview.clone(),
+        };
+        match &self.key {
+            Some(JsonKey::Name(name)) => format!("{name}: {value}"),
+            Some(JsonKey::Index(i)) => format!("{i}: {value}"),
+            None => format!("$ {value}"),
+        }
+    }
+}
+
+/// The JSON path of a node, such as `$.items[2]["first name"]`
+pub fn json_path(root: &JsonNode, path: &[usize]) -> String {
+    let mut json_path = "$".to_string();
+    let mut node = root;
+    for i in path {
+        let Some(child) = node.children.get(*i) else {
+            break;
+        };
+        match &child.key {
+            Some(JsonKey::Name(name)) if is_identifier(name) => {
+                json_path.push('.');
+                json_path.push_str(name);
+            }
+            Some(JsonKey::Name(name)) => {
+                let quoted = serde_json::to_string(name).unwrap_or_default();
+                json_path.push_str(&format!("[{quoted}]"));
+            }
+            Some(JsonKey::Index(i)) => json_path.push_str(&format!("[{i}]")),
+            None => {}
+        }
+        node = child;
+    }
+    json_path
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The tree of a JSON document, checked by serde_json for the errors to be
+/// reported the way it does
+pub fn parse_json_tree(text: &str) -> Result<JsonNode, String> {
+    serde_json::from_str::<IgnoredAny>(text).map_err(|e| e.to_string())?;
+    let mut scanner = Scanner { text, pos: 0 };
+    scanner
+        .value(None, None)
+        .ok_or_else(|| "invalid JSON".to_string())
+}
+
+/// Finds the ranges of the values of a document serde_json found valid
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(
+        &mut self,
+        key: Option<JsonKey>,
+        key_range: Option<Range<usize>>,
+    ) -> Option<JsonNode> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let (kind, children) = match self.peek()? {
+            b'{' => (JsonKind::Object, self.object()?),
+            b'[' => (JsonKind::Array, self.array()?),
+            b'"' => {
+                self.string()?;
+                (JsonKind::String, Vec::new())
+            }
+            b't' | b'f' => {
+                self.literal();
+                (JsonKind::Bool, Vec::new())
+            }
+            b'n' => {
+                self.literal();
+                (JsonKind::Null, Vec::new())
+            }
+            _ => {
+                self.literal();
+                (JsonKind::Number, Vec::new())
+            }
+        };
+        let range = start..self.pos;
+        let preview = if !matches!(kind, JsonKind::Object | JsonKind::Array) {
+            self.text[range.clone()]
+                .chars()
+                .take(MAX_PREVIEW_LEN)
+                .collect()
+        } else {
+            String::new()
+        };
+        Some(JsonNode {
+            key,
+            key_range,
+            kind,
+            range,
+            preview,
+            children,
+        })
+    }
+
+    fn object(&mut self) -> Option<Vec<JsonNode>> {
+        self.pos += 1;
+        let mut children = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                b'}' => {
+                    self.pos += 1;
+                    return Some(children);
+                }
+                b',' => self.pos += 1,
+                _ => {
+                    let key_start = self.pos;
+                    self.string()?;
+                    let key_range = key_start..self.pos;
+                    let name = serde_json::from_str::<String>(
+                        &self.text[key_range.clone()],
+                    )
+                    .ok()?;
+                    self.skip_whitespace();
+                    // The colon
+                    self.pos += 1;
+                    children.push(
+                        self.value(Some(JsonKey::Name(name)), Some(key_range))?,
+                    );
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Vec<JsonNode>> {
+        self.pos += 1;
+        let mut children = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                b']' => {
+                    self.pos += 1;
+                    return Some(children);
+                }
+                b',' => self.pos += 1,
+                _ => {
+                    let index = children.len();
+                    children.push(self.value(Some(JsonKey::Index(index)), None)?);
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<()> {
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn literal(&mut self) {
+        while self.peek().is_some_and(|b| {
+            !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace()
+        }) {
+            self.pos += 1;
+        }
+    }
+}
+
+/// The tree of a document, and the error of its last parse, the tree of the
+/// last valid version being kept while the document is being edited
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JsonTree {
+    pub root: Option<Rc<JsonNode>>,
+    pub error: Option<String>,
+}
+
+/// A node shown in the JSON panel
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonRow {
+    pub path: Vec<usize>,
+    pub label: String,
+    pub kind: JsonKind,
+    pub has_children: bool,
+    pub range: Range<usize>,
+}
+
+/// The nodes shown in the panel, the children of the expanded ones in
+/// order
+pub fn visible_rows(
+    root: &JsonNode,
+    expanded: &HashSet<Vec<usize>>,
+) -> Vec<JsonRow> {
+    fn push(
+        node: &JsonNode,
+        path: &mut Vec<usize>,
+        expanded: &HashSet<Vec<usize>>,
+        rows: &mut Vec<JsonRow>,
+    ) {
+        rows.push(JsonRow {
+            path: path.clone(),
+            label: node.label(),
+            kind: node.kind,
+            has_children: !node.children.is_empty(),
+            range: node.range.clone(),
+        });
+        if !expanded.contains(path) {
+            return;
+        }
+        for (i, child) in node.children.iter().enumerate() {
+            path.push(i);
+            push(child, path, expanded, rows);
+            path.pop();
+        }
+    }
+
+    let mut rows = Vec::new();
+    push(root, &mut Vec::new(), expanded, &mut rows);
+    rows
+}
+
+#[derive(Clone)]
+pub struct JsonTreeData {
+    /// The paths of the nodes whose children are shown
+    pub expanded: RwSignal<HashSet<Vec<usize>>>,
+}
+
+impl JsonTreeData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            expanded: cx.create_rw_signal(HashSet::from([Vec::new()])),
+        }
+    }
+
+    pub fn toggle(&self, path: &[usize]) {
+        self.expanded.update(|expanded| {
+            if !expanded.remove(path) {
+                expanded.insert(path.to_vec());
+            }
+        });
+    }
+
+    /// Expand the ancestors of a node for it to be shown
+    pub fn reveal(&self, path: &[usize]) {
+        let hidden = self.expanded.with_untracked(|expanded| {
+            (0..path.len()).any(|i| !expanded.contains(&path[..i]))
+        });
+        if hidden {
+            self.expanded.update(|expanded| {
+                for i in 0..path.len() {
+                    expanded.insert(path[..i].to_vec());
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{json_path, parse_json_tree, visible_rows, JsonKey, JsonKind};
+
+    const TEXT: &str = r#"{
+    "name": "lapce",
+    "items": [1, {"first name": true}],
+    "empty": null
+}"#;
+
+    #[test]
+    fn test_parse_json_tree() {
+        let root = parse_json_tree(TEXT).unwrap();
+        assert_eq!(root.kind, JsonKind::Object);
+        assert_eq!(root.range, 0..TEXT.len());
+        assert_eq!(root.children.len(), 3);
+        let name = &root.children[0];
+        assert_eq!(name.key, Some(JsonKey::Name("name".to_string())));
+        assert_eq!(&TEXT[name.range.clone()], "\"lapce\"");
+        let items = &root.children[1];
+        assert_eq!(items.kind, JsonKind::Array);
+        assert_eq!(
+            &TEXT[items.children[1].range.clone()],
+            r#"{"first name": true}"#
+        );
+        assert_eq!(root.children[2].kind, JsonKind::Null);
+
+        assert!(parse_json_tree("{\"a\": }").is_err());
+    }
+
+    #[test]
+    fn test_json_path() {
+        let root = parse_json_tree(TEXT).unwrap();
+        let offset = TEXT.find("true").unwrap();
+        let path = root.path_at(offset);
+        assert_eq!(path, vec![1, 1, 0]);
+        assert_eq!(json_path(&root, &path), r#"$.items[1]["first name"]"#);
+        // On the name of a member
+        let offset = TEXT.find("\"empty\"").unwrap() + 1;
+        assert_eq!(json_path(&root, &root.path_at(offset)), "$.empty");
+        assert_eq!(json_path(&root, &root.path_at(0)), "$");
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        let root = parse_json_tree(TEXT).unwrap();
+        let mut expanded = HashSet::from([vec![]]);
+        let labels = |expanded: &HashSet<Vec<usize>>| {
+            visible_rows(&root, expanded)
+                .into_iter()
+                .map(|row| row.label)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            labels(&expanded),
+            vec!["$ {3}", "name: \"lapce\"", "items: [2]", "empty: null"]
+        );
+        expanded.insert(vec![1]);
+        assert_eq!(labels(&expanded)[3..5], ["0: 1", "1: {1}"]);
+    }
+}
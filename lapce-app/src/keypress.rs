@@ -24,11 +24,14 @@
 };
 use crate::{
     command::{lapce_internal_commands, CommandExecuted, CommandKind, LapceCommand},
+    command_history::{parse_repeat_command, CommandHistory},
+    command_macro::{parse_run_macro_command, MacroRecorder},
     config::LapceConfig,
     keypress::{
         condition::{CheckCondition, Condition},
         keymap::KeymapMatch,
     },
+    profile,
     tracing::*,
 };
 
@@ -59,6 +62,13 @@ fn focus_only(&self) -> bool {
         false
     }
 
+    /// Whether the commands run and the text typed in the focus are recorded
+    /// in the command history and the macros, rather than only what they
+    /// lead to
+    fn records_commands(&self) -> bool {
+        true
+    }
+
     fn receive_char(&self, c: &str);
 }
 impl KeyPressFocus for () {
@@ -115,6 +125,10 @@ fn focus_only(&self) -> bool {
         (**self).focus_only()
     }
 
+    fn records_commands(&self) -> bool {
+        (**self).records_commands()
+    }
+
     fn receive_char(&self, c: &str) {
         (**self).receive_char(c)
     }
@@ -153,6 +167,9 @@ pub struct KeyPressData {
     pub command_keymaps: Rc<IndexMap<String, Vec<KeyMap>>>,
     pub commands_with_keymap: Rc<Vec<KeyMap>>,
     pub commands_without_keymap: Rc<Vec<LapceCommand>>,
+    /// The commands run in the window tab
+    pub history: RwSignal<CommandHistory>,
+    pub macros: RwSignal<MacroRecorder>,
 }
 
 impl KeyPressData {
@@ -167,6 +184,8 @@ pub fn new(cx: Scope, config: &LapceConfig) -> Self {
             commands: Rc::new(lapce_internal_commands()),
             commands_with_keymap: Rc::new(Vec::new()),
             commands_without_keymap: Rc::new(Vec::new()),
+            history: cx.create_rw_signal(CommandHistory::default()),
+            macros: cx.create_rw_signal(MacroRecorder::default()),
         };
         keypress.load_commands();
         keypress
@@ -242,10 +261,37 @@ fn run_command<T: KeyPressFocus + ?Sized>(
         mods: Modifiers,
         focus: &T,
     ) -> CommandExecuted {
-        if let Some(cmd) = self.commands.get(command) {
-            focus.run_command(cmd, count, mods)
+        let parsed;
+        let cmd = if let Some(cmd) = self.commands.get(command) {
+            cmd
+        } else if let Some(cmd) = parse_repeat_command(command)
+            .or_else(|| parse_run_macro_command(command))
+        {
+            parsed = cmd;
+            &parsed
         } else {
-            CommandExecuted::No
+            return CommandExecuted::No;
+        };
+        let executed = focus.run_command(cmd, count, mods);
+        if executed == CommandExecuted::Yes && focus.records_commands() {
+            self.record_command(cmd, count);
+        }
+        executed
+    }
+
+    pub fn record_command(&self, cmd: &LapceCommand, count: Option<usize>) {
+        self.history.update(|history| history.record(cmd, count));
+        if self.macros.with_untracked(|macros| macros.is_recording()) {
+            self.macros
+                .update(|macros| macros.record_command(cmd, count));
+        }
+    }
+
+    fn record_text<T: KeyPressFocus + ?Sized>(&self, focus: &T, text: &str) {
+        if focus.records_commands()
+            && self.macros.with_untracked(|macros| macros.is_recording())
+        {
+            self.macros.update(|macros| macros.record_text(text));
         }
     }
 
@@ -375,6 +421,9 @@ pub fn handle_keymatch<T: KeyPressFocus + ?Sized>(
                             if let CommandKind::Move(_) = cmd.kind {
                                 let handled = focus.run_command(cmd, None, mods)
                                     == CommandExecuted::Yes;
+                                if handled && focus.records_commands() {
+                                    self.record_command(cmd, None);
+                                }
                                 return KeyPressHandle {
                                     handled,
                                     keymatch,
@@ -403,6 +452,7 @@ pub fn handle_keymatch<T: KeyPressFocus + ?Sized>(
             if let KeyInput::Keyboard { logical, .. } = &keypress.key {
                 if let Key::Character(c) = logical {
                     focus.receive_char(c);
+                    self.record_text(focus, c);
                     self.count.set(None);
                     return KeyPressHandle {
                         handled: true,
@@ -411,6 +461,7 @@ pub fn handle_keymatch<T: KeyPressFocus + ?Sized>(
                     };
                 } else if let Key::Named(NamedKey::Space) = logical {
                     focus.receive_char(" ");
+                    self.record_text(focus, " ");
                     self.count.set(None);
                     return KeyPressHandle {
                         handled: true,
@@ -560,7 +611,8 @@ fn get_keymaps(
             trace!(TraceLevel::ERROR, "Failed to load OS defaults: {err}");
         }
 
-        if let Some(path) = Self::file() {
+        let profile_file = config.profile.as_deref().and_then(profile::keymaps_file);
+        for path in Self::file().into_iter().chain(profile_file) {
             if let Ok(content) = std::fs::read_to_string(&path) {
                 if let Err(err) = loader.load_from_str(&content, is_modal) {
                     trace!(TraceLevel::WARN, "Failed to load from {path:?}: {err}");
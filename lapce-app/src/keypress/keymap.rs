@@ -115,16 +115,7 @@ pub fn label(&self) -> String {
     pub fn parse(key: &str) -> Vec<Self> {
         key.split(' ')
             .filter_map(|k| {
-                let (modifiers, key) = if k == "+" {
-                    ("", "+")
-                } else if let Some(remaining) = k.strip_suffix("++") {
-                    (remaining, "+")
-                } else {
-                    match k.rsplit_once('+') {
-                        Some(pair) => pair,
-                        None => ("", k),
-                    }
-                };
+                let (modifiers, key) = split_modifiers(k);
 
                 let key = match key.parse().ok() {
                     Some(key) => key,
@@ -152,6 +143,36 @@ pub fn parse(key: &str) -> Vec<Self> {
             })
             .collect()
     }
+
+    /// Why the keys of a keymap, such as `ctrl+k ctrl+s`, can't be parsed,
+    /// if they can't
+    pub fn check(key: &str) -> Result<(), String> {
+        for k in key.split(' ') {
+            let (modifiers, key) = split_modifiers(k);
+            if key.parse::<KeyMapKey>().is_err() {
+                return Err(format!("Unrecognized key `{key}`"));
+            }
+            for part in modifiers.to_lowercase().split('+') {
+                if !matches!(part, "ctrl" | "meta" | "shift" | "alt" | "altgr" | "")
+                {
+                    return Err(format!("Invalid key modifier `{part}`"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The modifiers and the key of a key press, such as `ctrl+shift` and `k` for
+/// `ctrl+shift+k`
+fn split_modifiers(k: &str) -> (&str, &str) {
+    if k == "+" {
+        ("", "+")
+    } else if let Some(remaining) = k.strip_suffix("++") {
+        (remaining, "+")
+    } else {
+        k.rsplit_once('+').unwrap_or(("", k))
+    }
 }
 
 impl FromStr for KeyMapKey {
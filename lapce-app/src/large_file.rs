@@ -0,0 +1,583 @@
+//! The read-only viewer of the files too large to be opened in an editor,
+//! which reads them a chunk at a time through the proxy.
+
+use std::{path::PathBuf, rc::Rc};
+
+use floem::{
+    ext_event::create_ext_action,
+    kurbo::Point,
+    reactive::{create_memo, RwSignal, Scope},
+    style::CursorStyle,
+    views::{
+        container, label, scroll, stack, virtual_stack, Decorators,
+        VirtualDirection, VirtualItemSize,
+    },
+    View,
+};
+use lapce_rpc::{
+    large_file::{FileChunk, FileMatch},
+    proxy::ProxyResponse,
+};
+use lapce_xi_rope::Rope;
+
+use crate::{
+    config::color::LapceColor, editor_tab::EditorTabChild,
+    main_split::MainSplitData, text_input::TextInputBuilder, window_tab::CommonData,
+};
+
+/// How much of the file is read at a time
+const CHUNK_SIZE: u64 = 256 * 1024;
+/// The matching lines a search of the whole file stops at
+const MAX_SEARCH_RESULTS: usize = 1_000;
+
+/// Where to go in a file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LargeFileTarget {
+    /// A zero based line, which is estimated from the length of the lines
+    /// when it isn't in the chunk
+    Line(usize),
+    Offset(u64),
+    Percent(f64),
+}
+
+/// What is typed to go somewhere in a file: a line number, a percentage like
+/// `50%`, or a byte offset like `@1024` or `0x400`
+pub fn parse_target(input: &str) -> Option<LargeFileTarget> {
+    let input = input.trim();
+    if let Some(percent) = input.strip_suffix('%') {
+        let percent: f64 = percent.trim().parse().ok()?;
+        return Some(LargeFileTarget::Percent(percent.clamp(0.0, 100.0)));
+    }
+    if let Some(offset) = input.strip_prefix('@') {
+        return offset.trim().parse().ok().map(LargeFileTarget::Offset);
+    }
+    if let Some(hex) = input.strip_prefix("0x") {
+        return u64::from_str_radix(hex, 16)
+            .ok()
+            .map(LargeFileTarget::Offset);
+    }
+    let line: usize = input.parse().ok()?;
+    Some(LargeFileTarget::Line(line.saturating_sub(1)))
+}
+
+/// How many lines of the chunk are before the byte `offset` of the file
+fn lines_before(chunk: &FileChunk, offset: u64) -> usize {
+    let before = offset.saturating_sub(chunk.start) as usize;
+    chunk.text.as_bytes()[..before.min(chunk.text.len())]
+        .iter()
+        .filter(|b| **b == b'\n')
+        .count()
+}
+
+/// The first line of a chunk, exact when the line of an offset in it is known
+/// and estimated from the length of its lines otherwise
+pub fn first_line_of(
+    chunk: &FileChunk,
+    known: Option<(u64, usize)>,
+) -> (usize, bool) {
+    if let Some((offset, line)) = known {
+        if (chunk.start..=chunk.end).contains(&offset) {
+            return (line.saturating_sub(lines_before(chunk, offset)), true);
+        }
+    }
+    let estimate = chunk
+        .average_line_len()
+        .map_or(0, |len| (chunk.start as f64 / len) as usize);
+    (estimate, chunk.start == 0)
+}
+
+#[derive(Clone)]
+pub struct LargeFileData {
+    pub path: PathBuf,
+    pub chunk: RwSignal<Option<Rc<FileChunk>>>,
+    /// The line the chunk starts at, and whether it is exact
+    pub first_line: RwSignal<(usize, bool)>,
+    /// The line of the chunk to scroll to
+    pub scroll_line: RwSignal<Option<usize>>,
+    pub matches: RwSignal<im::Vector<FileMatch>>,
+    /// Whether the whole file is being searched
+    pub searching: RwSignal<bool>,
+    /// Whether the last search went through the whole file
+    pub search_complete: RwSignal<bool>,
+    pub error: RwSignal<Option<String>>,
+    pub scope: Scope,
+    pub common: Rc<CommonData>,
+}
+
+impl LargeFileData {
+    pub fn new(cx: Scope, path: PathBuf, common: Rc<CommonData>) -> Self {
+        let data = Self {
+            path,
+            chunk: cx.create_rw_signal(None),
+            first_line: cx.create_rw_signal((0, true)),
+            scroll_line: cx.create_rw_signal(None),
+            matches: cx.create_rw_signal(im::Vector::new()),
+            searching: cx.create_rw_signal(false),
+            search_complete: cx.create_rw_signal(true),
+            error: cx.create_rw_signal(None),
+            scope: cx,
+            common,
+        };
+        data.load(0, Some((0, 0)), None);
+        data
+    }
+
+    /// Read the chunk of the file from `offset`, with the line of an offset
+    /// in it if known and the line to scroll to once read
+    fn load(
+        &self,
+        offset: u64,
+        known: Option<(u64, usize)>,
+        scroll_to: Option<u64>,
+    ) {
+        let chunk = self.chunk;
+        let first_line = self.first_line;
+        let scroll_line = self.scroll_line;
+        let error = self.error;
+        let send = create_ext_action(self.scope, move |result| match result {
+            Ok(ProxyResponse::ReadFileChunkResponse { chunk: new_chunk }) => {
+                first_line.set(first_line_of(&new_chunk, known));
+                let line =
+                    scroll_to.map_or(0, |offset| lines_before(&new_chunk, offset));
+                chunk.set(Some(Rc::new(new_chunk)));
+                scroll_line.set(Some(line));
+                error.set(None);
+            }
+            Ok(_) => {}
+            Err(e) => error.set(Some(e.message)),
+        });
+        self.common.proxy.read_file_chunk(
+            self.path.clone(),
+            offset,
+            CHUNK_SIZE,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    pub fn next_chunk(&self) {
+        let Some(chunk) = self.chunk.get_untracked() else {
+            return;
+        };
+        if chunk.end >= chunk.size {
+            return;
+        }
+        let (first_line, exact) = self.first_line.get_untracked();
+        let known =
+            exact.then(|| (chunk.end, first_line + chunk.text.lines().count()));
+        self.load(chunk.end, known, None);
+    }
+
+    pub fn previous_chunk(&self) {
+        let Some(chunk) = self.chunk.get_untracked() else {
+            return;
+        };
+        if chunk.start == 0 {
+            return;
+        }
+        let (first_line, exact) = self.first_line.get_untracked();
+        let known = exact.then_some((chunk.start, first_line));
+        let offset = chunk.start.saturating_sub(CHUNK_SIZE);
+        self.load(offset, known, Some(chunk.start));
+    }
+
+    pub fn go_to(&self, target: LargeFileTarget) {
+        let Some(chunk) = self.chunk.get_untracked() else {
+            return;
+        };
+        let offset = match target {
+            LargeFileTarget::Line(line) => {
+                let (first_line, exact) = self.first_line.get_untracked();
+                let lines = chunk.text.lines().count();
+                if exact && (first_line..first_line + lines).contains(&line) {
+                    self.scroll_line.set(Some(line - first_line));
+                    return;
+                }
+                let len = chunk.average_line_len().unwrap_or(80.0);
+                (line as f64 * len) as u64
+            }
+            LargeFileTarget::Offset(offset) => offset,
+            LargeFileTarget::Percent(percent) => {
+                (chunk.size as f64 * percent / 100.0) as u64
+            }
+        };
+        let offset = offset.min(chunk.size);
+        self.load(offset.saturating_sub(CHUNK_SIZE / 2), None, Some(offset));
+    }
+
+    /// Show the chunk around a line found by the search
+    pub fn go_to_match(&self, file_match: &FileMatch) {
+        let known = Some((file_match.offset, file_match.line));
+        self.load(
+            file_match.offset.saturating_sub(CHUNK_SIZE / 2),
+            known,
+            Some(file_match.offset),
+        );
+    }
+
+    /// Search the whole file in the background
+    pub fn search(&self, pattern: String) {
+        if pattern.is_empty() {
+            self.matches.set(im::Vector::new());
+            return;
+        }
+        let matches = self.matches;
+        let searching = self.searching;
+        let search_complete = self.search_complete;
+        let error = self.error;
+        searching.set(true);
+        let send = create_ext_action(self.scope, move |result| {
+            searching.set(false);
+            match result {
+                Ok(ProxyResponse::SearchLargeFileResponse {
+                    matches: new_matches,
+                    complete,
+                }) => {
+                    matches.set(new_matches.into());
+                    search_complete.set(complete);
+                }
+                Ok(_) => {}
+                Err(e) => error.set(Some(e.message)),
+            }
+        });
+        self.common.proxy.search_large_file(
+            self.path.clone(),
+            pattern,
+            false,
+            false,
+            MAX_SEARCH_RESULTS,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+}
+
+pub fn large_file_view(main_split: MainSplitData, path: PathBuf) -> impl View {
+    let common = main_split.common.clone();
+    let config = common.config;
+    let cx = Scope::current();
+    let data = LargeFileData::new(cx.create_child(), path.clone(), common.clone());
+    let chunk = data.chunk;
+    let first_line = data.first_line;
+    let scroll_line = data.scroll_line;
+    let matches = data.matches;
+    let error = data.error;
+
+    let go_to_input =
+        TextInputBuilder::new().build(cx, main_split.editors, common.clone());
+    let go_to_doc = go_to_input.doc_signal();
+    let find_input =
+        TextInputBuilder::new().build(cx, main_split.editors, common.clone());
+    let find_doc = find_input.doc_signal();
+    let pattern = create_memo(move |_| {
+        find_doc.with(|doc| doc.buffer.with(|b| b.to_string().to_lowercase()))
+    });
+
+    let lines = create_memo(move |_| {
+        chunk.with(|chunk| {
+            chunk.as_ref().map_or_else(im::Vector::new, |chunk| {
+                chunk
+                    .text
+                    .lines()
+                    .map(str::to_string)
+                    .enumerate()
+                    .collect::<im::Vector<_>>()
+            })
+        })
+    });
+    let line_height = move || config.get().editor.line_height() as f64;
+
+    let button = move |name: &'static str, action: Box<dyn Fn()>| {
+        label(move || name.to_string())
+            .on_click_stop(move |_| action())
+            .style(move |s| {
+                let config = config.get();
+                s.margin_left(6.0)
+                    .padding_horiz(8.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            })
+    };
+    let input_style = move |s: floem::style::Style| {
+        s.width(160.0)
+            .margin_left(6.0)
+            .border(1.0)
+            .border_radius(4.0)
+            .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+    };
+
+    let toolbar = {
+        let (start, previous, next, go, search) = (
+            data.clone(),
+            data.clone(),
+            data.clone(),
+            data.clone(),
+            data.clone(),
+        );
+        let (load_fully, edit_copy) = (main_split.clone(), main_split.clone());
+        stack((
+            button("Start", Box::new(move || start.load(0, Some((0, 0)), None))),
+            button("Previous", Box::new(move || previous.previous_chunk())),
+            button("Next", Box::new(move || next.next_chunk())),
+            go_to_input
+                .placeholder(|| "Line, 50% or @offset".to_string())
+                .keyboard_navigatable()
+                .style(input_style),
+            button(
+                "Go",
+                Box::new(move || {
+                    let input = go_to_doc.with_untracked(|doc| {
+                        doc.buffer.with_untracked(|b| b.to_string())
+                    });
+                    if let Some(target) = parse_target(&input) {
+                        go.go_to(target);
+                    }
+                }),
+            ),
+            find_input
+                .placeholder(|| "Find".to_string())
+                .keyboard_navigatable()
+                .style(input_style),
+            button(
+                "Search File",
+                Box::new(move || {
+                    let pattern = find_doc.with_untracked(|doc| {
+                        doc.buffer.with_untracked(|b| b.to_string())
+                    });
+                    search.search(pattern);
+                }),
+            ),
+            button(
+                "Load Fully",
+                Box::new(move || load_fully.open_large_file_fully(path.clone())),
+            ),
+            button(
+                "Edit Copy",
+                Box::new(move || {
+                    let Some(chunk) = chunk.get_untracked() else {
+                        return;
+                    };
+                    if let EditorTabChild::Editor(id) = edit_copy.new_file() {
+                        if let Some(editor) = edit_copy.editors.editor_untracked(id)
+                        {
+                            editor
+                                .doc()
+                                .reload(Rope::from(chunk.text.as_str()), false);
+                        }
+                    }
+                }),
+            ),
+            label(move || {
+                if let Some(error) = error.get() {
+                    return error;
+                }
+                let Some(chunk) = chunk.get() else {
+                    return "Reading...".to_string();
+                };
+                let (line, exact) = first_line.get();
+                format!(
+                    "Bytes {}-{} of {}, from line {}{}",
+                    chunk.start,
+                    chunk.end,
+                    chunk.size,
+                    if exact { "" } else { "~" },
+                    line + 1,
+                )
+            })
+            .style(move |s| {
+                let config = config.get();
+                s.margin_left(10.0).color(if error.with(|e| e.is_some()) {
+                    config.color(LapceColor::LAPCE_ERROR)
+                } else {
+                    config.color(LapceColor::EDITOR_DIM)
+                })
+            }),
+        ))
+        .style(move |s| {
+            s.width_full()
+                .items_center()
+                .padding_vert(4.0)
+                .padding_right(10.0)
+                .border_bottom(1.0)
+                .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+        })
+    };
+
+    let content = container(
+        scroll(
+            virtual_stack(
+                VirtualDirection::Vertical,
+                VirtualItemSize::Fixed(Box::new(line_height)),
+                move || lines.get(),
+                |(i, _)| *i,
+                move |(i, text)| {
+                    let lowercase = text.to_lowercase();
+                    let line_text = text.clone();
+                    stack((
+                        label(move || {
+                            let (line, exact) = first_line.get();
+                            format!(
+                                "{}{}",
+                                if exact { "" } else { "~" },
+                                line + i + 1
+                            )
+                        })
+                        .style(move |s| {
+                            let config = config.get();
+                            s.min_width(80.0)
+                                .padding_right(10.0)
+                                .justify_end()
+                                .color(config.color(LapceColor::EDITOR_DIM))
+                        }),
+                        label(move || line_text.clone()),
+                    ))
+                    .style(move |s| {
+                        let config = config.get();
+                        let found = pattern.with(|pattern| {
+                            !pattern.is_empty() && lowercase.contains(pattern)
+                        });
+                        s.height(line_height())
+                            .items_center()
+                            .min_width_full()
+                            .font_family(config.editor.font_family.clone())
+                            .font_size(config.editor.font_size() as f32)
+                            .color(config.color(LapceColor::EDITOR_FOREGROUND))
+                            .apply_if(found, |s| {
+                                s.background(
+                                    config.color(LapceColor::EDITOR_SELECTION),
+                                )
+                            })
+                    })
+                },
+            )
+            .style(|s| s.flex_col().min_width_full()),
+        )
+        .scroll_to(move || {
+            let line = scroll_line.get()?;
+            Some(Point::new(0.0, line as f64 * line_height()))
+        })
+        .style(|s| s.absolute().size_full()),
+    )
+    .style(|s| s.width_full().flex_grow(1.0).flex_basis(0.0));
+
+    let results = {
+        let data = data.clone();
+        container(
+            scroll(
+                virtual_stack(
+                    VirtualDirection::Vertical,
+                    VirtualItemSize::Fixed(Box::new(line_height)),
+                    move || matches.get(),
+                    |file_match| file_match.offset,
+                    move |file_match| {
+                        let data = data.clone();
+                        let text = format!(
+                            "{:>8}  {}",
+                            file_match.line + 1,
+                            file_match.text
+                        );
+                        label(move || text.clone())
+                            .on_click_stop(move |_| data.go_to_match(&file_match))
+                            .style(move |s| {
+                                let config = config.get();
+                                s.height(line_height())
+                                    .items_center()
+                                    .padding_horiz(10.0)
+                                    .min_width_full()
+                                    .font_family(config.editor.font_family.clone())
+                                    .font_size(config.editor.font_size() as f32)
+                                    .hover(|s| {
+                                        s.cursor(CursorStyle::Pointer).background(
+                                            config.color(
+                                                LapceColor::PANEL_HOVERED_BACKGROUND,
+                                            ),
+                                        )
+                                    })
+                            })
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full()),
+            )
+            .style(|s| s.absolute().size_full()),
+        )
+        .style(move |s| {
+            let config = config.get();
+            s.width_full()
+                .height_pct(30.0)
+                .border_top(1.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+                .background(config.color(LapceColor::PANEL_BACKGROUND))
+                .apply_if(matches.with(|matches| matches.is_empty()), |s| s.hide())
+        })
+    };
+
+    let searching = data.searching;
+    let search_complete = data.search_complete;
+    stack((
+        toolbar,
+        content,
+        label(move || {
+            if searching.get() {
+                "Searching the file...".to_string()
+            } else {
+                let count = matches.with(|matches| matches.len());
+                let more = if search_complete.get() { "" } else { "+" };
+                format!("{count}{more} matching lines")
+            }
+        })
+        .style(move |s| {
+            s.padding_horiz(10.0)
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+                .apply_if(
+                    !searching.get() && matches.with(|matches| matches.is_empty()),
+                    |s| s.hide(),
+                )
+        }),
+        results,
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .size_full()
+            .background(config.get().color(LapceColor::EDITOR_BACKGROUND))
+    })
+    .debug_name("Large File View")
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::large_file::FileChunk;
+
+    use super::{first_line_of, parse_target, LargeFileTarget};
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(parse_target("120"), Some(LargeFileTarget::Line(119)));
+        assert_eq!(parse_target(" 50% "), Some(LargeFileTarget::Percent(50.0)));
+        assert_eq!(parse_target("@1024"), Some(LargeFileTarget::Offset(1024)));
+        assert_eq!(parse_target("0x400"), Some(LargeFileTarget::Offset(1024)));
+        assert_eq!(parse_target("abc"), None);
+    }
+
+    #[test]
+    fn test_first_line_of() {
+        let chunk = FileChunk {
+            size: 1000,
+            start: 100,
+            end: 121,
+            text: "line a\nline b\nline c\n".to_string(),
+        };
+        // Line 12 starting at the second line of the chunk
+        assert_eq!(first_line_of(&chunk, Some((107, 12))), (11, true));
+        // Without a known line, 100 bytes of lines of 7 bytes
+        assert_eq!(first_line_of(&chunk, None), (14, false));
+    }
+}
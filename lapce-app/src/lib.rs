@@ -1,38 +1,71 @@
 pub mod about;
+pub mod accessibility;
 pub mod alert;
 pub mod app;
+pub mod automation;
+pub mod blame;
+pub mod clipboard;
 pub mod code_action;
 pub mod command;
+pub mod command_history;
+pub mod command_macro;
 pub mod completion;
 pub mod config;
 pub mod db;
 pub mod debug;
+pub mod debug_console;
+pub mod deep_link;
+pub mod devcontainer;
+pub mod diagnostic_bundle;
 pub mod doc;
+pub mod doc_cache;
+pub mod document_highlight;
 pub mod editor;
+pub mod editor_event;
 pub mod editor_tab;
+pub mod emmet;
+pub mod error_report;
 pub mod file_explorer;
+pub mod file_template;
 pub mod find;
+pub mod flame;
 pub mod focus_text;
+pub mod font_warmup;
+pub mod formatter;
 pub mod global_search;
+pub mod grammar;
+#[cfg(feature = "headless")]
+pub mod headless;
 pub mod history;
 pub mod hover;
 pub mod id;
 pub mod inline_completion;
+pub mod json_tree;
 pub mod keymap;
 pub mod keypress;
+pub mod large_file;
 pub mod listener;
+pub mod log_mode;
 pub mod lsp;
 pub mod main_split;
 pub mod markdown;
+pub mod notification;
 pub mod palette;
 pub mod panel;
+pub mod perf;
 pub mod plugin;
+pub mod profile;
 pub mod proxy;
+pub mod refactor;
 pub mod rename;
+pub mod rich_output;
+pub mod run_config;
 pub mod settings;
+pub mod settings_sync;
 pub mod snippet;
 pub mod source_control;
 pub mod status;
+pub mod syntax_tree;
 pub mod terminal;
 pub mod text_area;
 pub mod text_input;
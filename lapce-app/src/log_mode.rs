@@ -0,0 +1,181 @@
+//! The log mode of a document, for the append-only files programs write
+//! their logs to: the lines colored by their levels, following the end of
+//! the file as it grows, and listing the lines matching a filter.
+
+use std::{path::Path, rc::Rc, sync::Arc};
+
+use floem::{
+    peniko::Color,
+    reactive::{Memo, ReadSignal, RwSignal, Scope},
+};
+use regex::{Regex, RegexBuilder};
+
+use crate::config::{color::LapceColor, editor::EditorConfig, LapceConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn color(&self, config: &LapceConfig) -> Color {
+        config.color(match self {
+            LogLevel::Error => LapceColor::EDITOR_LOG_ERROR,
+            LogLevel::Warn => LapceColor::EDITOR_LOG_WARN,
+            LogLevel::Info => LapceColor::EDITOR_LOG_INFO,
+            LogLevel::Debug => LapceColor::EDITOR_LOG_DEBUG,
+        })
+    }
+}
+
+/// The patterns of the settings telling the level of a line, the first one
+/// matching winning
+#[derive(Clone, Debug, Default)]
+pub struct LogLevelRules {
+    rules: Vec<(LogLevel, Regex)>,
+}
+
+impl LogLevelRules {
+    pub fn new(config: &EditorConfig) -> Self {
+        let rules = [
+            (LogLevel::Error, &config.log_error_pattern),
+            (LogLevel::Warn, &config.log_warn_pattern),
+            (LogLevel::Info, &config.log_info_pattern),
+            (LogLevel::Debug, &config.log_debug_pattern),
+        ]
+        .into_iter()
+        .filter(|(_, pattern)| !pattern.is_empty())
+        .filter_map(|(level, pattern)| Some((level, Regex::new(pattern).ok()?)))
+        .collect();
+        Self { rules }
+    }
+
+    pub fn level(&self, line: &str) -> Option<LogLevel> {
+        self.rules
+            .iter()
+            .find(|(_, regex)| regex.is_match(line))
+            .map(|(level, _)| *level)
+    }
+}
+
+/// Whether a file is a log by its name, such as `app.log` or the rotated
+/// `app.log.1`
+pub fn is_log_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let name = name.to_lowercase();
+    name.ends_with(".log")
+        || name
+            .rsplit_once(".log.")
+            .is_some_and(|(_, n)| n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// A filter matching the lines by a case insensitive regular expression, or
+/// by the text as is when it isn't one
+pub fn log_filter(pattern: &str) -> Option<Regex> {
+    if pattern.is_empty() {
+        return None;
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .or_else(|_| {
+            RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(true)
+                .build()
+        })
+        .ok()
+}
+
+/// The lines of `text` the filter matches, with their line numbers
+pub fn matching_lines<'a>(
+    text: &'a str,
+    filter: &Regex,
+) -> impl Iterator<Item = (usize, &'a str)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| filter.is_match(line))
+}
+
+#[derive(Clone)]
+pub struct LogModeData {
+    pub enabled: RwSignal<bool>,
+    /// Whether the editors go to the end of the file as it grows
+    pub follow: RwSignal<bool>,
+    pub rules: Memo<Rc<LogLevelRules>>,
+}
+
+impl LogModeData {
+    pub fn new(
+        cx: Scope,
+        enabled: bool,
+        config: ReadSignal<Arc<LapceConfig>>,
+    ) -> Self {
+        Self {
+            enabled: cx.create_rw_signal(enabled),
+            follow: cx.create_rw_signal(enabled),
+            rules: cx.create_memo(move |_| {
+                Rc::new(LogLevelRules::new(&config.get().editor))
+            }),
+        }
+    }
+
+    /// The level of a line, when the document is in log mode
+    pub fn level(&self, line: &str) -> Option<LogLevel> {
+        if !self.enabled.get_untracked() {
+            return None;
+        }
+        self.rules.with_untracked(|rules| rules.level(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use regex::Regex;
+
+    use super::{is_log_file, log_filter, matching_lines, LogLevel, LogLevelRules};
+
+    #[test]
+    fn test_log_level() {
+        let rules = LogLevelRules {
+            rules: vec![
+                (LogLevel::Error, Regex::new(r"\bERROR\b").unwrap()),
+                (LogLevel::Info, Regex::new(r"\bINFO\b").unwrap()),
+            ],
+        };
+        assert_eq!(
+            rules.level("2024-01-01 ERROR failed after INFO"),
+            Some(LogLevel::Error)
+        );
+        assert_eq!(rules.level("INFO started"), Some(LogLevel::Info));
+        assert_eq!(rules.level("INFORMATION"), None);
+    }
+
+    #[test]
+    fn test_is_log_file() {
+        assert!(is_log_file(Path::new("/var/log/app.log")));
+        assert!(is_log_file(Path::new("app.LOG.3")));
+        assert!(!is_log_file(Path::new("catalog.rs")));
+        assert!(!is_log_file(Path::new("app.log.gz")));
+    }
+
+    #[test]
+    fn test_matching_lines() {
+        let text = "a error\nb ok\nc Error (x\n";
+        let filter = log_filter("error").unwrap();
+        assert_eq!(
+            matching_lines(text, &filter).collect::<Vec<_>>(),
+            vec![(0, "a error"), (2, "c Error (x")]
+        );
+        // Not a regex, so taken as is
+        let filter = log_filter("(x").unwrap();
+        assert_eq!(matching_lines(text, &filter).count(), 1);
+        assert!(log_filter("").is_none());
+    }
+}
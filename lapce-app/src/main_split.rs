@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     rc::Rc,
 };
@@ -16,10 +16,12 @@
 use itertools::Itertools;
 use lapce_core::{
     buffer::rope_text::RopeText, command::FocusCommand, cursor::Cursor,
-    rope_text_pos::RopeTextPosition, selection::Selection, syntax::Syntax,
+    editor::EditType, rope_text_pos::RopeTextPosition, selection::Selection,
+    syntax::Syntax,
 };
 use lapce_rpc::{
     buffer::BufferId,
+    large_file::FILE_TOO_LARGE,
     plugin::{PluginId, VoltID},
     proxy::ProxyResponse,
 };
@@ -35,22 +37,33 @@
     alert::AlertButton,
     command::InternalCommand,
     doc::{DiagnosticData, Doc, DocContent, DocHistory, EditorDiagnostic},
+    doc_cache::DocCache,
     editor::{
         diff::DiffEditorData,
         location::{EditorLocation, EditorPosition},
         EditorData,
     },
+    editor_event::EditorEvent,
     editor_tab::{
         EditorTabChild, EditorTabChildSource, EditorTabData, EditorTabInfo,
     },
+    error_report::ErrorReport,
+    formatter::changed_range,
     id::{
-        DiffEditorId, EditorTabId, KeymapId, SettingsId, SplitId,
-        ThemeColorSettingsId, VoltViewId,
+        DiffEditorId, EditorTabId, KeymapId, LargeFileViewId, RunConfigsId,
+        SettingsId, SplitId, ThemeColorSettingsId, VoltViewId,
     },
     keypress::{EventRef, KeyPressData, KeyPressHandle},
     window_tab::{CommonData, Focus, WindowTabData},
 };
 
+/// How many docs are kept open without an editor showing them, prefetched or
+/// closed
+const CACHED_DOCS: usize = 16;
+/// The most text in all the open docs, past which the cached docs are freed
+/// first
+const OPEN_DOCS_LEN: usize = 256 * 1024 * 1024;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SplitDirection {
     Vertical,
@@ -377,6 +390,11 @@ pub struct MainSplitData {
     pub editors: Editors,
     pub diff_editors: RwSignal<im::HashMap<DiffEditorId, DiffEditorData>>,
     pub docs: RwSignal<im::HashMap<PathBuf, Rc<Doc>>>,
+    /// The docs no editor shows kept open, those opened in the background
+    /// ahead of being jumped to and those recently closed
+    pub cached_docs: RwSignal<DocCache>,
+    /// The files to free rather than cache once their editors are closed
+    pub free_on_close: RwSignal<HashSet<PathBuf>>,
     pub scratch_docs: RwSignal<im::HashMap<String, Rc<Doc>>>,
     pub diagnostics: RwSignal<im::HashMap<PathBuf, DiagnosticData>>,
     pub active_editor: Memo<Option<EditorData>>,
@@ -408,6 +426,8 @@ pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
             cx.create_rw_signal(im::HashMap::new());
         let docs: RwSignal<im::HashMap<PathBuf, Rc<Doc>>> =
             cx.create_rw_signal(im::HashMap::new());
+        let cached_docs = cx.create_rw_signal(DocCache::new(CACHED_DOCS));
+        let free_on_close = cx.create_rw_signal(HashSet::new());
         let scratch_docs = cx.create_rw_signal(im::HashMap::new());
         let locations = cx.create_rw_signal(im::Vector::new());
         let current_location = cx.create_rw_signal(0);
@@ -459,6 +479,8 @@ pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
             editors,
             diff_editors,
             docs,
+            cached_docs,
+            free_on_close,
             scratch_docs,
             active_editor,
             find_editor,
@@ -488,6 +510,8 @@ pub fn key_down<'a>(
                 let editor = self.editors.editor_untracked(editor_id)?;
                 let handle = keypress.key_down(event, &editor);
                 editor.get_code_actions();
+                editor.get_linked_editing_ranges();
+                editor.get_document_highlights();
                 Some(handle)
             }
             EditorTabChild::DiffEditor(diff_editor_id) => {
@@ -502,12 +526,16 @@ pub fn key_down<'a>(
                 };
                 let handle = keypress.key_down(event, editor);
                 editor.get_code_actions();
+                editor.get_linked_editing_ranges();
+                editor.get_document_highlights();
                 Some(handle)
             }
             EditorTabChild::Settings(_) => None,
             EditorTabChild::ThemeColorSettings(_) => None,
             EditorTabChild::Keymap(_) => None,
+            EditorTabChild::RunConfigs(_) => None,
             EditorTabChild::Volt(_, _) => None,
+            EditorTabChild::LargeFile(_, _) => None,
         }
     }
 
@@ -588,11 +616,28 @@ pub fn get_doc(
         path: PathBuf,
         unsaved: Option<String>,
     ) -> (Rc<Doc>, bool) {
-        let cx = self.scope;
+        let max_size = self
+            .common
+            .config
+            .with_untracked(|config| config.editor.large_file_size);
+        let max_size = (max_size > 0).then(|| max_size as u64 * 1024 * 1024);
+        self.get_doc_with_max_size(path, unsaved, max_size)
+    }
+
+    /// Get the doc of a file, which is opened in the large file viewer instead
+    /// once the proxy finds it is larger than `max_size`
+    fn get_doc_with_max_size(
+        &self,
+        path: PathBuf,
+        unsaved: Option<String>,
+        max_size: Option<u64>,
+    ) -> (Rc<Doc>, bool) {
         let doc = self.docs.with_untracked(|docs| docs.get(&path).cloned());
         if let Some(doc) = doc {
             (doc, false)
         } else {
+            // Its own scope, for the doc to be freed when it is closed
+            let cx = self.scope.create_child();
             let diagnostic_data = self.get_diagnostic_data(&path);
 
             let doc = Doc::new(
@@ -606,16 +651,17 @@ pub fn get_doc(
             self.docs.update(|docs| {
                 docs.insert(path.clone(), doc.clone());
             });
+            self.common
+                .editor_events
+                .send(EditorEvent::DocumentOpened { path: path.clone() });
 
             {
                 let doc = doc.clone();
                 let local_doc = doc.clone();
-                let send = create_ext_action(cx, move |result| {
-                    if let Ok(ProxyResponse::NewBufferResponse {
-                        content,
-                        read_only,
-                    }) = result
-                    {
+                let main_split = self.clone();
+                let local_path = path.clone();
+                let send = create_ext_action(cx, move |result| match result {
+                    Ok(ProxyResponse::NewBufferResponse { content, read_only }) => {
                         local_doc.init_content(Rope::from(content));
                         if read_only {
                             local_doc.content.update(|content| {
@@ -627,13 +673,20 @@ pub fn get_doc(
                             local_doc.reload(Rope::from(unsaved), false);
                         }
                     }
+                    Err(e) if e.code == FILE_TOO_LARGE => {
+                        main_split.open_large_file_instead(local_path);
+                    }
+                    _ => {}
                 });
 
-                self.common
-                    .proxy
-                    .new_buffer(doc.buffer_id, path, move |result| {
+                self.common.proxy.new_buffer(
+                    doc.buffer_id,
+                    path,
+                    max_size,
+                    move |result| {
                         send(result);
-                    });
+                    },
+                );
             }
 
             (doc, true)
@@ -650,6 +703,14 @@ pub fn go_to_location(
         }
         let path = location.path.clone();
         let (doc, new_doc) = self.get_doc(path.clone(), None);
+        self.cached_docs.update(|docs| {
+            docs.remove(&path);
+        });
+        self.free_on_close.update(|paths| {
+            paths.remove(&path);
+        });
+        // A prefetched doc may still be loading
+        let new_doc = new_doc || !doc.loaded();
 
         let child = self.get_editor_tab_child(
             EditorTabChildSource::Editor { path, doc },
@@ -675,18 +736,30 @@ pub fn open_file_changes(&self, path: PathBuf) {
             self.common.clone(),
         );
         let left = Rc::new(left);
+        left.load_history();
 
-        let send = {
-            let left = left.clone();
-            create_ext_action(self.scope, move |result| {
-                if let Ok(ProxyResponse::BufferHeadResponse { content, .. }) = result
-                {
-                    left.init_content(Rope::from(content));
-                }
-            })
-        };
-        self.common.proxy.get_buffer_head(path, move |result| {
-            send(result);
+        self.get_editor_tab_child(
+            EditorTabChildSource::DiffEditor { left, right },
+            false,
+            false,
+        );
+    }
+
+    /// Open a diff of the file as of `commit` against its parent commit
+    pub fn open_commit_changes(&self, path: PathBuf, commit: String) {
+        let [left, right] = [format!("{commit}^"), commit].map(|version| {
+            let doc = Doc::new_history(
+                self.scope,
+                DocContent::History(DocHistory {
+                    path: path.clone(),
+                    version,
+                }),
+                self.editors,
+                self.common.clone(),
+            );
+            let doc = Rc::new(doc);
+            doc.load_history();
+            doc
         });
 
         self.get_editor_tab_child(
@@ -696,6 +769,18 @@ pub fn open_file_changes(&self, path: PathBuf) {
         );
     }
 
+    /// Whether any of the files is shown in an editor. This is tracked, so
+    /// effects rerun when editors are opened or closed.
+    pub fn shows_any_path(&self, paths: &[PathBuf]) -> bool {
+        self.editors.with_editors(|editors| {
+            editors.values().any(|editor| {
+                editor.doc().content.with_untracked(|content| {
+                    content.path().is_some_and(|path| paths.contains(path))
+                })
+            })
+        })
+    }
+
     pub fn open_diff_files(&self, left_path: PathBuf, right_path: PathBuf) {
         let [left, right] =
             [left_path, right_path].map(|path| self.get_doc(path, None).0);
@@ -838,7 +923,9 @@ fn get_editor_tab_child(
                         EditorTabChild::Settings(_) => true,
                         EditorTabChild::ThemeColorSettings(_) => true,
                         EditorTabChild::Keymap(_) => true,
+                        EditorTabChild::RunConfigs(_) => true,
                         EditorTabChild::Volt(_, _) => true,
+                        EditorTabChild::LargeFile(_, _) => true,
                     };
 
                     if can_be_selected {
@@ -978,6 +1065,28 @@ fn get_editor_tab_child(
                         })
                     }
                 }
+                EditorTabChildSource::RunConfigs => {
+                    if let Some(index) =
+                        active_editor_tab.with_untracked(|editor_tab| {
+                            editor_tab.children.iter().position(|(_, _, child)| {
+                                matches!(child, EditorTabChild::RunConfigs(_))
+                            })
+                        })
+                    {
+                        Some(index)
+                    } else if ignore_unconfirmed {
+                        None
+                    } else {
+                        active_editor_tab.with_untracked(|editor_tab| {
+                            editor_tab
+                                .get_unconfirmed_editor_tab_child(
+                                    editors,
+                                    &diff_editors,
+                                )
+                                .map(|(i, _)| i)
+                        })
+                    }
+                }
                 EditorTabChildSource::Volt(id) => {
                     if let Some(index) =
                         active_editor_tab.with_untracked(|editor_tab| {
@@ -1004,6 +1113,16 @@ fn get_editor_tab_child(
                         })
                     }
                 }
+                EditorTabChildSource::LargeFile(path) => active_editor_tab
+                    .with_untracked(|editor_tab| {
+                        editor_tab.children.iter().position(|(_, _, child)| {
+                            matches!(
+                                child,
+                                EditorTabChild::LargeFile(_, current_path)
+                                    if current_path == path
+                            )
+                        })
+                    }),
             }
         };
 
@@ -1058,9 +1177,16 @@ fn get_editor_tab_child(
                 EditorTabChildSource::Keymap => {
                     EditorTabChild::Keymap(KeymapId::next())
                 }
+                EditorTabChildSource::RunConfigs => {
+                    EditorTabChild::RunConfigs(RunConfigsId::next())
+                }
                 EditorTabChildSource::Volt(id) => {
                     EditorTabChild::Volt(VoltViewId::next(), id.to_owned())
                 }
+                EditorTabChildSource::LargeFile(path) => EditorTabChild::LargeFile(
+                    LargeFileViewId::next(),
+                    path.to_owned(),
+                ),
                 EditorTabChildSource::DiffEditor { left, right } => {
                     let diff_editor_id = DiffEditorId::next();
                     let diff_editor = DiffEditorData::new(
@@ -1096,7 +1222,9 @@ fn get_editor_tab_child(
                         EditorTabChild::Settings(_) => {}
                         EditorTabChild::ThemeColorSettings(_) => {}
                         EditorTabChild::Keymap(_) => {}
+                        EditorTabChild::RunConfigs(_) => {}
                         EditorTabChild::Volt(_, _) => {}
+                        EditorTabChild::LargeFile(_, _) => {}
                     }
                     (editor_tab_id, current_child.clone())
                 });
@@ -1162,7 +1290,9 @@ fn get_editor_tab_child(
                 EditorTabChild::Settings(_) => {}
                 EditorTabChild::ThemeColorSettings(_) => {}
                 EditorTabChild::Keymap(_) => {}
+                EditorTabChild::RunConfigs(_) => {}
                 EditorTabChild::Volt(_, _) => {}
+                EditorTabChild::LargeFile(_, _) => {}
             }
 
             // Now loading the new child
@@ -1226,6 +1356,12 @@ fn get_editor_tab_child(
                                 .position(|(_, _, child)| {
                                     matches!(child, EditorTabChild::Keymap(_))
                                 }),
+                            EditorTabChildSource::RunConfigs => editor_tab
+                                .children
+                                .iter()
+                                .position(|(_, _, child)| {
+                                    matches!(child, EditorTabChild::RunConfigs(_))
+                                }),
                             EditorTabChildSource::Volt(id) => editor_tab
                                 .children
                                 .iter()
@@ -1238,6 +1374,16 @@ fn get_editor_tab_child(
                                         false
                                     }
                                 }),
+                            EditorTabChildSource::LargeFile(path) => editor_tab
+                                .children
+                                .iter()
+                                .position(|(_, _, child)| {
+                                    matches!(
+                                        child,
+                                        EditorTabChild::LargeFile(_, current_path)
+                                            if current_path == path
+                                    )
+                                }),
                             EditorTabChildSource::NewFileEditor => None,
                         })
                     {
@@ -1539,9 +1685,15 @@ fn split_editor_tab(
                 EditorTabChild::ThemeColorSettings(ThemeColorSettingsId::next())
             }
             EditorTabChild::Keymap(_) => EditorTabChild::Keymap(KeymapId::next()),
+            EditorTabChild::RunConfigs(_) => {
+                EditorTabChild::RunConfigs(RunConfigsId::next())
+            }
             EditorTabChild::Volt(_, id) => {
                 EditorTabChild::Volt(VoltViewId::next(), id.to_owned())
             }
+            EditorTabChild::LargeFile(_, path) => {
+                EditorTabChild::LargeFile(LargeFileViewId::next(), path.to_owned())
+            }
         };
 
         let editor_tab = {
@@ -1883,7 +2035,9 @@ fn editor_tab_child_close_warning(
             EditorTabChild::Settings(_) => None,
             EditorTabChild::ThemeColorSettings(_) => None,
             EditorTabChild::Keymap(_) => None,
+            EditorTabChild::RunConfigs(_) => None,
             EditorTabChild::Volt(_, _) => None,
+            EditorTabChild::LargeFile(_, _) => None,
         }
     }
 
@@ -2119,7 +2273,9 @@ pub fn editor_tab_child_close(
             EditorTabChild::Settings(_) => {}
             EditorTabChild::ThemeColorSettings(_) => {}
             EditorTabChild::Keymap(_) => {}
+            EditorTabChild::RunConfigs(_) => {}
             EditorTabChild::Volt(_, _) => {}
+            EditorTabChild::LargeFile(_, _) => {}
         }
 
         if editor_tab_children_len == 0 {
@@ -2153,7 +2309,7 @@ pub fn run_code_action(&self, plugin_id: PluginId, action: CodeActionOrCommand)
             CodeActionOrCommand::Command(_) => {}
             CodeActionOrCommand::CodeAction(action) => {
                 if let Some(edit) = action.edit.as_ref() {
-                    self.apply_workspace_edit(edit);
+                    self.apply_workspace_edit(edit, action.title);
                 } else {
                     self.resolve_code_action(plugin_id, action);
                 }
@@ -2164,8 +2320,9 @@ pub fn run_code_action(&self, plugin_id: PluginId, action: CodeActionOrCommand)
     /// Resolve a code action and apply its held workspace edit
     fn resolve_code_action(&self, plugin_id: PluginId, action: CodeAction) {
         let main_split = self.clone();
+        let title = action.title.clone();
         let send = create_ext_action(self.scope, move |edit| {
-            main_split.apply_workspace_edit(&edit);
+            main_split.apply_workspace_edit(&edit, title);
         });
         self.common
             .proxy
@@ -2180,7 +2337,10 @@ fn resolve_code_action(&self, plugin_id: PluginId, action: CodeAction) {
     }
 
     /// Perform a workspace edit, which are from the LSP (such as code actions, or symbol renaming)
-    pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit) {
+    ///
+    /// An edit of several files is recorded in the journal of the refactors
+    /// under `title`, to be rolled back as a whole.
+    pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit, title: String) {
         if let Some(DocumentChanges::Operations(_op)) =
             edit.document_changes.as_ref()
         {
@@ -2188,6 +2348,15 @@ pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit) {
         }
 
         if let Some(edits) = workspace_edits(edit) {
+            let paths = edits
+                .keys()
+                .filter_map(|url| url.to_file_path().ok())
+                .collect::<Vec<_>>();
+            if paths.len() > 1 {
+                self.common
+                    .refactors
+                    .update(|refactors| refactors.begin(title, paths));
+            }
             for (url, edits) in edits {
                 if let Ok(path) = url.to_file_path() {
                     let active_path = self
@@ -2216,6 +2385,73 @@ pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit) {
         }
     }
 
+    /// Roll back a refactor of the journal, restoring all the files it edited
+    /// to their text from before it, or none of them when one has been edited
+    /// since. The files which were saved are saved again. `on_error` is given
+    /// why the refactor can't be rolled back.
+    pub fn rollback_refactor(&self, id: u64, on_error: impl Fn(String) + 'static) {
+        let Some(refactor) = self
+            .common
+            .refactors
+            .with_untracked(|refactors| refactors.get(id).cloned())
+        else {
+            return;
+        };
+        let files = refactor
+            .applied_files()
+            .map(|(path, before, after)| {
+                let (doc, _) = self.get_doc(path.to_path_buf(), None);
+                (path.to_path_buf(), before.clone(), after.clone(), doc)
+            })
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return;
+        }
+
+        let refactors = self.common.refactors;
+        // The files which aren't open any longer are loaded first
+        self.scope.create_effect(move |done| {
+            if done == Some(true) {
+                return true;
+            }
+            if !files.iter().all(|(_, _, _, doc)| doc.loaded.get()) {
+                return false;
+            }
+
+            let changed = files.iter().find(|(_, _, after, doc)| {
+                doc.buffer.with_untracked(|buffer| {
+                    buffer.text().slice_to_cow(..) != after.slice_to_cow(..)
+                })
+            });
+            if let Some((path, _, _, _)) = changed {
+                on_error(format!(
+                    "Can't roll back \"{}\": {} was edited since",
+                    refactor.title,
+                    path.display()
+                ));
+                return true;
+            }
+
+            for (_, before, after, doc) in &files {
+                let after = after.slice_to_cow(..);
+                let before = before.slice_to_cow(..);
+                let Some((range, text)) = changed_range(&after, &before) else {
+                    continue;
+                };
+                let was_pristine = doc.is_pristine();
+                doc.do_raw_edit(
+                    &[(Selection::region(range.start, range.end), text)],
+                    EditType::Other,
+                );
+                if was_pristine {
+                    doc.save(|| {});
+                }
+            }
+            refactors.update(|refactors| refactors.remove(id));
+            true
+        });
+    }
+
     pub fn next_error(&self) {
         let file_diagnostics =
             self.file_diagnostics_items(DiagnosticSeverity::ERROR);
@@ -2236,13 +2472,14 @@ pub fn next_error(&self) {
         let (path, position) =
             next_in_file_errors_offset(active_path, &file_diagnostics);
         let location = EditorLocation {
-            path,
+            path: path.clone(),
             position: Some(position),
             scroll_offset: None,
             ignore_unconfirmed: false,
             same_editor_tab: false,
         };
         self.jump_to_location(location, None);
+        self.prefetch_next_problem(&path, DiagnosticSeverity::ERROR);
     }
 
     fn file_diagnostics_items(
@@ -2312,6 +2549,26 @@ pub fn get_diagnostic_data(&self, path: &Path) -> DiagnosticData {
         }
     }
 
+    /// Re-highlight the open documents with the current grammars and queries
+    pub fn reload_syntax(&self) {
+        let docs = self
+            .docs
+            .get_untracked()
+            .into_iter()
+            .map(|(_, doc)| doc)
+            .chain(
+                self.scratch_docs
+                    .get_untracked()
+                    .into_iter()
+                    .map(|(_, doc)| doc),
+            );
+        for doc in docs {
+            let language = doc.syntax.with_untracked(|syntax| syntax.language);
+            doc.set_language(language);
+            doc.trigger_syntax_change(None);
+        }
+    }
+
     pub fn open_file_changed(&self, path: &Path, content: &str) {
         let doc = self.docs.with_untracked(|docs| docs.get(path).cloned());
         let doc = match doc {
@@ -2322,6 +2579,13 @@ pub fn open_file_changed(&self, path: &Path, content: &str) {
         doc.handle_file_changed(Rope::from(content));
     }
 
+    pub fn open_file_appended(&self, path: &Path, offset: usize, content: &str) {
+        let doc = self.docs.with_untracked(|docs| docs.get(path).cloned());
+        if let Some(doc) = doc {
+            doc.handle_file_appended(offset, content);
+        }
+    }
+
     pub fn set_find_pattern(&self, pattern: Option<String>) {
         if let Some(pattern) = pattern {
             self.find_editor.doc().reload(Rope::from(pattern), true);
@@ -2356,6 +2620,218 @@ pub fn open_keymap(&self) {
         self.get_editor_tab_child(EditorTabChildSource::Keymap, false, false);
     }
 
+    pub fn open_run_configs(&self) {
+        self.get_editor_tab_child(EditorTabChildSource::RunConfigs, false, false);
+    }
+
+    pub fn open_large_file(&self, path: PathBuf) {
+        self.get_editor_tab_child(
+            EditorTabChildSource::LargeFile(path),
+            false,
+            false,
+        );
+    }
+
+    /// Close the editors of a file the proxy found too large to open, and open
+    /// it in the large file viewer instead
+    fn open_large_file_instead(&self, path: PathBuf) {
+        if self.close_doc(&path).is_some() {
+            self.open_large_file(path);
+        }
+    }
+
+    /// Open a file in the background, for jumping to it to be instant as its
+    /// content is read and parsed already
+    pub fn prefetch(&self, path: PathBuf) {
+        let is_open = self.docs.with_untracked(|docs| docs.contains_key(&path));
+        if is_open && !self.cached_docs.with_untracked(|docs| docs.contains(&path)) {
+            return;
+        }
+        self.get_doc(path.clone(), None);
+        self.cache_doc(path);
+    }
+
+    /// Prefetch the next file after `path` with problems of `severity`, in
+    /// the order they are walked through
+    pub fn prefetch_next_problem(&self, path: &Path, severity: DiagnosticSeverity) {
+        let next = self
+            .file_diagnostics_items(severity)
+            .into_iter()
+            .map(|(path, _)| path)
+            .find(|next| next.as_path() > path);
+        if let Some(next) = next {
+            self.prefetch(next);
+        }
+    }
+
+    /// Called once the last editor of a file doc is gone. The doc is kept in
+    /// the cache for reopening it to be instant, unless it has changes that
+    /// weren't saved or it was closed with "Close and Free".
+    pub fn doc_closed(&self, doc: &Rc<Doc>) {
+        let DocContent::File { path, .. } = doc.content.get_untracked() else {
+            return;
+        };
+        let is_open = self.docs.with_untracked(|docs| {
+            docs.get(&path).is_some_and(|open| Rc::ptr_eq(open, doc))
+        });
+        if !is_open || self.doc_shown(doc) {
+            return;
+        }
+        let free = self
+            .free_on_close
+            .try_update(|paths| paths.remove(&path))
+            .unwrap_or(false);
+        if free || !doc.is_pristine() {
+            self.free_doc(&path);
+        } else {
+            self.cache_doc(path);
+        }
+    }
+
+    /// Close the active editor, freeing its doc rather than caching it
+    pub fn close_and_free_active(&self) {
+        let Some(editor) = self.active_editor.get_untracked() else {
+            return;
+        };
+        if let DocContent::File { path, .. } = editor.doc().content.get_untracked() {
+            self.free_on_close.update(|paths| {
+                paths.insert(path);
+            });
+        }
+        self.editor_tab_child_close_active();
+    }
+
+    /// Make the doc of `path` the most recently used of the cache, freeing
+    /// those evicted
+    fn cache_doc(&self, path: PathBuf) {
+        let Some(doc) = self.docs.with_untracked(|docs| docs.get(&path).cloned())
+        else {
+            return;
+        };
+        let len = doc.buffer.with_untracked(|buffer| buffer.len());
+        let max_len = self.cached_docs_max_len(&path);
+        let evicted = self
+            .cached_docs
+            .try_update(|docs| docs.insert(path, len, max_len))
+            .unwrap_or_default();
+        for path in evicted {
+            self.free_doc(&path);
+        }
+    }
+
+    /// The length of text the cache may hold, what is left of
+    /// [`OPEN_DOCS_LEN`] by the other open docs than those cached and
+    /// `path`. The more text is shown, the less is cached.
+    fn cached_docs_max_len(&self, path: &Path) -> usize {
+        let cached_docs = self.cached_docs.get_untracked();
+        let open_len: usize = self.docs.with_untracked(|docs| {
+            docs.iter()
+                .filter(|(p, _)| p.as_path() != path && !cached_docs.contains(p))
+                .map(|(_, doc)| doc.buffer.with_untracked(|buffer| buffer.len()))
+                .sum()
+        });
+        OPEN_DOCS_LEN.saturating_sub(open_len)
+    }
+
+    /// Whether an editor or a diff editor shows `doc`
+    fn doc_shown(&self, doc: &Rc<Doc>) -> bool {
+        let in_editor = self.editors.0.with_untracked(|editors| {
+            editors
+                .values()
+                .any(|editor| Rc::ptr_eq(&editor.doc(), doc))
+        });
+        in_editor
+            || self.diff_editors.with_untracked(|diff_editors| {
+                diff_editors.values().any(|diff_editor| {
+                    Rc::ptr_eq(&diff_editor.left.doc(), doc)
+                        || Rc::ptr_eq(&diff_editor.right.doc(), doc)
+                })
+            })
+    }
+
+    /// Close a doc no editor shows, and free it along with its buffer in the
+    /// proxy, which tells the language servers the file was closed
+    fn free_doc(&self, path: &Path) {
+        let Some(doc) = self.docs.with_untracked(|docs| docs.get(path).cloned())
+        else {
+            return;
+        };
+        if self.doc_shown(&doc) {
+            return;
+        }
+        self.docs.update(|docs| {
+            docs.remove(path);
+        });
+        self.cached_docs.update(|docs| {
+            docs.remove(path);
+        });
+        if !doc.loaded() {
+            self.common.proxy.cancel_new_buffer(doc.buffer_id);
+        }
+        self.common.proxy.close_buffer(path.to_path_buf());
+        doc.scope.dispose();
+        self.common.editor_events.send(EditorEvent::DocumentClosed {
+            path: path.to_path_buf(),
+        });
+    }
+
+    /// Stop opening a file that is still being read, closing its editors
+    pub fn cancel_open(&self, path: &Path) {
+        if self
+            .docs
+            .with_untracked(|docs| docs.get(path).is_some_and(|doc| doc.loaded()))
+        {
+            return;
+        }
+        if let Some(doc) = self.close_doc(path) {
+            self.common.proxy.cancel_new_buffer(doc.buffer_id);
+            // In case the file was read before the proxy got the cancel
+            self.common.proxy.close_buffer(path.to_path_buf());
+        }
+    }
+
+    /// Forget the doc of a file and close its editors without saving it
+    fn close_doc(&self, path: &Path) -> Option<Rc<Doc>> {
+        let doc = self.docs.try_update(|docs| docs.remove(path)).flatten()?;
+        let editors: Vec<_> = self.editors.0.with_untracked(|editors| {
+            editors
+                .values()
+                .filter(|editor| Rc::ptr_eq(&editor.doc(), &doc))
+                .filter_map(|editor| {
+                    Some((editor.editor_tab_id.get_untracked()?, editor.id()))
+                })
+                .collect()
+        });
+        for (editor_tab_id, editor_id) in editors {
+            self.editor_tab_child_close(
+                editor_tab_id,
+                EditorTabChild::Editor(editor_id),
+                true,
+            );
+        }
+        self.common.editor_events.send(EditorEvent::DocumentClosed {
+            path: path.to_path_buf(),
+        });
+        Some(doc)
+    }
+
+    /// Open a file in an editor whatever its size, from the large file viewer
+    pub fn open_large_file_fully(&self, path: PathBuf) {
+        if !self.docs.with_untracked(|docs| docs.contains_key(&path)) {
+            self.get_doc_with_max_size(path.clone(), None, None);
+        }
+        self.go_to_location(
+            EditorLocation {
+                path,
+                position: None,
+                scroll_offset: None,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            },
+            None,
+        );
+    }
+
     pub fn new_file(&self) -> EditorTabChild {
         self.get_editor_tab_child(EditorTabChildSource::NewFileEditor, false, false)
     }
@@ -2371,6 +2847,7 @@ pub fn save_as(&self, doc: Rc<Doc>, path: PathBuf, action: impl Fn() + 'static)
             DocContent::Scratch { .. } => {
                 let send = {
                     let path = path.clone();
+                    let common = self.common.clone();
                     create_ext_action(self.scope, move |result| {
                         if let Err(err) = result {
                             event!(
@@ -2378,8 +2855,22 @@ pub fn save_as(&self, doc: Rc<Doc>, path: PathBuf, action: impl Fn() + 'static)
                                 "Failed to save as a file: {:?}",
                                 err
                             );
+                            common.report_error(
+                                ErrorReport::new(
+                                    "Save",
+                                    format!(
+                                        "Failed to save {}: {}",
+                                        path.display(),
+                                        err.message
+                                    ),
+                                )
+                                .path(path.clone())
+                                .rev(rev)
+                                .command("save_buffer_as"),
+                            );
                         } else {
-                            let syntax = Syntax::init(&path);
+                            let syntax =
+                                Syntax::from_language(common.language_of(&path));
                             doc.content.set(DocContent::File {
                                 path: path.clone(),
                                 read_only: false,
@@ -2426,6 +2917,7 @@ pub fn save_as2(
             DocContent::Scratch { .. } => {
                 let send = {
                     let path = path.clone();
+                    let common = self.common.clone();
                     create_ext_action(self.scope, move |result| {
                         if let Err(err) = result {
                             event!(
@@ -2433,8 +2925,22 @@ pub fn save_as2(
                                 "Failed to save as a file: {:?}",
                                 err
                             );
+                            common.report_error(
+                                ErrorReport::new(
+                                    "Save",
+                                    format!(
+                                        "Failed to save {}: {}",
+                                        path.display(),
+                                        err.message
+                                    ),
+                                )
+                                .path(path.clone())
+                                .rev(rev)
+                                .command("save_buffer_as"),
+                            );
                         } else {
-                            let syntax = Syntax::init(&path);
+                            let syntax =
+                                Syntax::from_language(common.language_of(&path));
                             doc.content.set(DocContent::File {
                                 path: path.clone(),
                                 read_only: false,
@@ -2667,7 +3173,9 @@ fn editor_tab_child_set_parent(
             EditorTabChild::Settings(_) => {}
             EditorTabChild::ThemeColorSettings(_) => {}
             EditorTabChild::Keymap(_) => {}
+            EditorTabChild::RunConfigs(_) => {}
             EditorTabChild::Volt(_, _) => {}
+            EditorTabChild::LargeFile(_, _) => {}
         }
         Some(())
     }
@@ -2887,6 +3395,23 @@ pub fn export_theme(&self) {
         }
     }
 
+    /// Open the output of a terminal command in a scratch editor, below the
+    /// command that printed it.
+    pub fn show_command_output(&self, command: &str, output: &str) {
+        let child = self.new_file();
+        if let EditorTabChild::Editor(id) = child {
+            if let Some(editor) = self.editors.editor_untracked(id) {
+                let doc = editor.doc();
+                let text = if command.is_empty() {
+                    output.to_string()
+                } else {
+                    format!("$ {command}\n{output}")
+                };
+                doc.reload(Rope::from(text), true);
+            }
+        }
+    }
+
     pub fn show_env(&self) {
         let child = self.new_file();
         if let EditorTabChild::Editor(id) = child {
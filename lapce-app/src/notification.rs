@@ -0,0 +1,196 @@
+//! The notifications of a window tab: the messages of Lapce and of the
+//! plugins, shown as toasts and kept in a history, some with actions to deal
+//! with what they report.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use lapce_rpc::plugin::VoltID;
+use lsp_types::MessageType;
+
+/// How many notifications the history keeps
+const MAX_NOTIFICATIONS: usize = 200;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationAction {
+    OpenSettings,
+    OpenLogFile,
+    /// Reload a plugin, which restarts its language server
+    RestartPlugin(VoltID),
+    /// Put the details of an error in the clipboard
+    CopyDetails(String),
+    OpenIncidentReport(PathBuf),
+}
+
+impl NotificationAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationAction::OpenSettings => "Open Settings",
+            NotificationAction::OpenLogFile => "Open Log",
+            NotificationAction::RestartPlugin(_) => "Restart Plugin",
+            NotificationAction::CopyDetails(_) => "Copy Details",
+            NotificationAction::OpenIncidentReport(_) => "Open Report",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub id: u64,
+    pub title: String,
+    pub message: String,
+    pub typ: MessageType,
+    pub actions: Vec<NotificationAction>,
+    pub time: DateTime<Local>,
+}
+
+/// The least severe notifications the history lists
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotificationFilter {
+    #[default]
+    All,
+    Warnings,
+    Errors,
+}
+
+impl NotificationFilter {
+    pub fn shows(&self, typ: MessageType) -> bool {
+        match self {
+            NotificationFilter::All => true,
+            NotificationFilter::Warnings => {
+                typ == MessageType::ERROR || typ == MessageType::WARNING
+            }
+            NotificationFilter::Errors => typ == MessageType::ERROR,
+        }
+    }
+}
+
+/// The notifications of a window tab, the last one first
+#[derive(Clone, Default)]
+pub struct NotificationCenter {
+    pub history: im::Vector<Notification>,
+    /// The ids of the notifications still shown as toasts
+    pub toasts: im::Vector<u64>,
+    /// While on, notifications only go to the history
+    pub do_not_disturb: bool,
+    pub filter: NotificationFilter,
+    next_id: u64,
+}
+
+impl NotificationCenter {
+    pub fn push(
+        &mut self,
+        title: impl Into<String>,
+        typ: MessageType,
+        message: impl Into<String>,
+        actions: Vec<NotificationAction>,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.history.push_front(Notification {
+            id,
+            title: title.into(),
+            message: message.into(),
+            typ,
+            actions,
+            time: Local::now(),
+        });
+        if self.history.len() > MAX_NOTIFICATIONS {
+            self.history.truncate(MAX_NOTIFICATIONS);
+            let history = &self.history;
+            self.toasts.retain(|id| {
+                history.iter().any(|notification| notification.id == *id)
+            });
+        }
+        if !self.do_not_disturb {
+            self.toasts.push_back(id);
+        }
+    }
+
+    /// Stop showing a notification as a toast, it stays in the history
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| *toast != id);
+    }
+
+    pub fn set_do_not_disturb(&mut self, do_not_disturb: bool) {
+        self.do_not_disturb = do_not_disturb;
+        if do_not_disturb {
+            self.toasts.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.toasts.clear();
+    }
+
+    /// The notifications shown as toasts, the first one first
+    pub fn toasts(&self) -> Vec<Notification> {
+        self.toasts
+            .iter()
+            .filter_map(|id| {
+                self.history
+                    .iter()
+                    .find(|notification| notification.id == *id)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// The notifications of the history the filter shows
+    pub fn filtered(&self) -> im::Vector<Notification> {
+        self.history
+            .iter()
+            .filter(|notification| self.filter.shows(notification.typ))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::MessageType;
+
+    use super::{NotificationCenter, NotificationFilter, MAX_NOTIFICATIONS};
+
+    #[test]
+    fn test_notification_center() {
+        let mut center = NotificationCenter::default();
+        center.push("Save", MessageType::ERROR, "failed", Vec::new());
+        center.push("Plugin", MessageType::INFO, "started", Vec::new());
+        let titles = |notifications: Vec<super::Notification>| {
+            notifications
+                .into_iter()
+                .map(|n| n.title)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(center.toasts()), vec!["Save", "Plugin"]);
+
+        center.dismiss(0);
+        assert_eq!(titles(center.toasts()), vec!["Plugin"]);
+        assert_eq!(center.history.len(), 2);
+
+        center.set_do_not_disturb(true);
+        center.push("Format", MessageType::WARNING, "no formatter", Vec::new());
+        assert!(center.toasts().is_empty());
+        assert_eq!(center.history[0].title, "Format");
+
+        center.filter = NotificationFilter::Warnings;
+        assert_eq!(
+            titles(center.filtered().into_iter().collect()),
+            vec!["Format", "Save"]
+        );
+        center.filter = NotificationFilter::Errors;
+        assert_eq!(
+            titles(center.filtered().into_iter().collect()),
+            vec!["Save"]
+        );
+
+        center.set_do_not_disturb(false);
+        for _ in 0..MAX_NOTIFICATIONS {
+            center.push("Log", MessageType::LOG, "line", Vec::new());
+        }
+        assert_eq!(center.history.len(), MAX_NOTIFICATIONS);
+        assert_eq!(center.toasts().len(), MAX_NOTIFICATIONS);
+    }
+}
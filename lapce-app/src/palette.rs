@@ -23,7 +23,9 @@
     line_ending::LineEnding, mode::Mode, movement::Movement, selection::Selection,
     syntax::Syntax,
 };
-use lapce_rpc::proxy::ProxyResponse;
+use lapce_rpc::{
+    dap_types::RunDebugConfig, proxy::ProxyResponse, source_control::GitCommitInfo,
+};
 use lapce_xi_rope::Rope;
 use lsp_types::DocumentSymbolResponse;
 use nucleo::Utf32Str;
@@ -31,22 +33,32 @@
 use tracing::error;
 
 use self::{
-    item::{PaletteItem, PaletteItemContent},
+    item::{git_commit_hint, PaletteItem, PaletteItemContent},
     kind::PaletteKind,
 };
 use crate::{
+    accessibility::AccessibilityEvent,
     command::{
-        CommandExecuted, CommandKind, InternalCommand, LapceCommand, WindowCommand,
+        CommandExecuted, CommandKind, InternalCommand, LapceCommand,
+        LapceWorkbenchCommand, WindowCommand,
     },
+    command_history::repeat_command,
+    command_macro::{is_valid_name, run_macro_command},
     db::LapceDb,
     debug::{RunDebugConfigs, RunDebugMode},
+    doc::DocContent,
     editor::{
         location::{EditorLocation, EditorPosition},
         EditorData,
     },
+    error_report::ErrorReport,
+    file_template::file_templates,
+    grammar::installed_grammars,
     keypress::{condition::Condition, KeyPressData, KeyPressFocus},
     lsp::path_from_url,
     main_split::MainSplitData,
+    profile,
+    run_config::attach_config,
     source_control::SourceControlData,
     window_tab::{CommonData, Focus},
     workspace::{LapceWorkspace, LapceWorkspaceType, SshHost},
@@ -78,6 +90,14 @@ pub fn update_input(&mut self, input: String, kind: PaletteKind) {
     }
 }
 
+/// The directory the remote path palette is showing
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemoteBrowse {
+    pub dir: PathBuf,
+    /// Whether it picks a folder to open as the workspace, rather than a file
+    pub pick_folder: bool,
+}
+
 #[derive(Clone)]
 pub struct PaletteData {
     run_id_counter: Arc<AtomicU64>,
@@ -96,13 +116,23 @@ pub struct PaletteData {
     pub keypress: ReadSignal<KeyPressData>,
     /// Listened on for which entry in the palette has been clicked
     pub clicked_index: RwSignal<Option<usize>>,
-    pub executed_commands: Rc<RefCell<HashMap<String, Instant>>>,
     pub executed_run_configs: Rc<RefCell<HashMap<(RunDebugMode, String), Instant>>>,
     pub main_split: MainSplitData,
     pub references: RwSignal<Vec<EditorLocation>>,
+    /// The file and the commits listed by the history palette
+    pub git_history: RwSignal<Option<(PathBuf, Vec<GitCommitInfo>)>>,
+    pub remote_browse: RwSignal<RemoteBrowse>,
+    /// The entries of the remote directories read while browsing, as paths
+    /// and whether they are directories
+    remote_dirs: Rc<RefCell<HashMap<PathBuf, Vec<(PathBuf, bool)>>>>,
     pub source_control: SourceControlData,
     pub common: Rc<CommonData>,
     left_diff_path: RwSignal<Option<PathBuf>>,
+    /// The config whose debugger attaches to the process picked next
+    attach_config: RwSignal<Option<RunDebugConfig>>,
+    /// The new file the templates are offered for, `None` for an untitled
+    /// one
+    file_template_path: RwSignal<Option<PathBuf>>,
 }
 
 impl std::fmt::Debug for PaletteData {
@@ -125,6 +155,8 @@ pub fn new(
         let preselect_index = cx.create_rw_signal(None);
         let index = cx.create_rw_signal(0);
         let references = cx.create_rw_signal(Vec::new());
+        let git_history = cx.create_rw_signal(None);
+        let remote_browse = cx.create_rw_signal(RemoteBrowse::default());
         let input = cx.create_rw_signal(PaletteInput {
             input: "".to_string(),
             kind: PaletteKind::File,
@@ -201,6 +233,8 @@ pub fn new(
 
         let clicked_index = cx.create_rw_signal(Option::<usize>::None);
         let left_diff_path = cx.create_rw_signal(None);
+        let attach_config = cx.create_rw_signal(None);
+        let file_template_path = cx.create_rw_signal(None);
 
         let palette = Self {
             run_id_counter,
@@ -219,12 +253,16 @@ pub fn new(
             kind,
             keypress,
             clicked_index,
-            executed_commands: Rc::new(RefCell::new(HashMap::new())),
             executed_run_configs: Rc::new(RefCell::new(HashMap::new())),
             references,
+            git_history,
+            remote_browse,
+            remote_dirs: Rc::new(RefCell::new(HashMap::new())),
             source_control,
             common,
             left_diff_path,
+            attach_config,
+            file_template_path,
         };
 
         {
@@ -300,6 +338,30 @@ pub fn new(
             });
         }
 
+        {
+            let palette = palette.clone();
+            cx.create_effect(move |_| {
+                let index = palette.index.get();
+                if palette.status.get_untracked() == PaletteStatus::Inactive
+                    || !palette
+                        .common
+                        .config
+                        .with_untracked(|c| c.core.screen_reader_support)
+                {
+                    return;
+                }
+                let text = palette.filtered_items.with_untracked(|items| {
+                    items.get(index).map(|item| item.filter_text.clone())
+                });
+                if let Some(text) = text {
+                    palette
+                        .common
+                        .accessibility
+                        .announce(AccessibilityEvent::ItemSelected { text });
+                }
+            });
+        }
+
         {
             let palette = palette.clone();
             cx.create_effect(move |_| {
@@ -341,6 +403,31 @@ pub fn placeholder_text(&self) -> &'static str {
                     "Seleft left file"
                 }
             }
+            PaletteKind::Profile => "Select a profile or type a name to create one",
+            PaletteKind::FileTemplate => "Select a template for the new file",
+            PaletteKind::Refactor => "Select a refactor to roll back",
+            PaletteKind::CommandHistory => "Select a command to run again",
+            PaletteKind::Macro => "Select a macro to run",
+            PaletteKind::SaveMacro => {
+                "Type a name for the macro, or select one to save it over"
+            }
+            PaletteKind::Grammar => {
+                "Select a grammar to update or type the URL or path of one to install"
+            }
+            PaletteKind::AttachProcess => {
+                if self.attach_config.with(Option::is_some) {
+                    "Select the process to attach to"
+                } else {
+                    "Select the debugger to attach with"
+                }
+            }
+            PaletteKind::RemotePath => {
+                if self.remote_browse.with(|browse| browse.pick_folder) {
+                    "Select a folder, or type an absolute path to go to"
+                } else {
+                    "Select a file, or type an absolute path to go to"
+                }
+            }
             _ => "",
         }
     }
@@ -386,6 +473,13 @@ fn run_inner(&self, kind: PaletteKind) {
             PaletteKind::RunAndDebug => {
                 self.get_run_configs();
             }
+            PaletteKind::AttachProcess => {
+                if self.attach_config.with_untracked(Option::is_some) {
+                    self.get_processes();
+                } else {
+                    self.get_run_configs();
+                }
+            }
             PaletteKind::ColorTheme => {
                 self.get_color_themes();
             }
@@ -401,7 +495,19 @@ fn run_inner(&self, kind: PaletteKind) {
             PaletteKind::SCMReferences => {
                 self.get_scm_references();
             }
+            PaletteKind::GitHistory => {
+                self.get_git_history();
+            }
+            PaletteKind::RemotePath => {
+                self.get_remote_paths();
+            }
             PaletteKind::TerminalProfile => self.get_terminal_profiles(),
+            PaletteKind::Profile => self.get_profiles(),
+            PaletteKind::FileTemplate => self.get_file_templates(),
+            PaletteKind::Refactor => self.get_refactors(),
+            PaletteKind::Grammar => self.get_grammars(),
+            PaletteKind::CommandHistory => self.get_command_history(),
+            PaletteKind::Macro | PaletteKind::SaveMacro => self.get_macros(),
         }
     }
 
@@ -520,16 +626,19 @@ fn get_commands(&self) {
         const EXCLUDED_ITEMS: &[&str] = &["palette.command"];
 
         let items = self.keypress.with_untracked(|keypress| {
-            // Get all the commands we've executed, and sort them by how recently they were
-            // executed. Ignore commands without descriptions.
-            let mut items: im::Vector<PaletteItem> = self
-                .executed_commands
-                .borrow()
+            // Get all the commands we've executed, from the most recently executed.
+            // Ignore commands without descriptions.
+            let executed: Vec<&str> = keypress.history.with_untracked(|history| {
+                history
+                    .entries()
+                    .map(|entry| entry.cmd.kind.str())
+                    .unique()
+                    .collect()
+            });
+            let mut items: im::Vector<PaletteItem> = executed
                 .iter()
-                .sorted_by_key(|(_, i)| *i)
-                .rev()
-                .filter_map(|(key, _)| {
-                    keypress.commands.get(key).and_then(|c| {
+                .filter_map(|key| {
+                    keypress.commands.get(*key).and_then(|c| {
                         c.kind.desc().as_ref().map(|m| PaletteItem {
                             content: PaletteItemContent::Command { cmd: c.clone() },
                             filter_text: m.to_string(),
@@ -546,7 +655,7 @@ fn get_commands(&self) {
                     return None;
                 }
 
-                if self.executed_commands.borrow().contains_key(c.kind.str()) {
+                if executed.contains(&c.kind.str()) {
                     return None;
                 }
 
@@ -578,6 +687,9 @@ fn get_workspaces(&self) {
                     LapceWorkspaceType::RemoteSSH(remote) => {
                         format!("[{remote}] {text}")
                     }
+                    LapceWorkspaceType::RemoteContainer(remote) => {
+                        format!("[{remote}] {text}")
+                    }
                     #[cfg(windows)]
                     LapceWorkspaceType::RemoteWSL(remote) => {
                         format!("[{remote}] {text}")
@@ -833,10 +945,31 @@ fn set_run_configs(&self, content: String) {
             }
         }
 
+        // Picking the debugger to attach with lists only the attach items
+        let attach_only = self.kind.get_untracked() == PaletteKind::AttachProcess;
         let executed_run_configs = self.executed_run_configs.borrow();
         let mut items = Vec::new();
         if let Some(configs) = configs.as_ref() {
             for config in &configs.configs {
+                if config.ty.is_some() {
+                    items.push((
+                        None,
+                        PaletteItem {
+                            content: PaletteItemContent::AttachConfig {
+                                config: config.clone(),
+                            },
+                            filter_text: format!(
+                                "Attach {} to a process",
+                                config.name
+                            ),
+                            score: 0,
+                            indices: vec![],
+                        },
+                    ));
+                }
+                if attach_only {
+                    continue;
+                }
                 items.push((
                     executed_run_configs
                         .get(&(RunDebugMode::Run, config.name.clone())),
@@ -912,6 +1045,51 @@ fn get_run_configs(&self) {
         }
     }
 
+    /// List the running processes, to attach the debugger of the picked
+    /// config to one.
+    fn get_processes(&self) {
+        self.items.update(|items| items.clear());
+        let set_items = self.items.write_only();
+        let run_id = self.run_id;
+        let current_run_id = run_id.get_untracked();
+        let common = self.common.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            let processes = match result {
+                Ok(ProxyResponse::ListProcessesResponse { processes }) => processes,
+                Ok(_) => return,
+                Err(err) => {
+                    common.report_error(
+                        ErrorReport::new(
+                            "Attach to Process",
+                            format!("Failed to list the processes: {}", err.message),
+                        )
+                        .command("list_processes"),
+                    );
+                    return;
+                }
+            };
+            if run_id.get_untracked() != current_run_id {
+                return;
+            }
+            let items = processes
+                .into_iter()
+                .map(|process| PaletteItem {
+                    filter_text: format!(
+                        "{} {} {}",
+                        process.name, process.pid, process.cmdline
+                    ),
+                    content: PaletteItemContent::Process { process },
+                    score: 0,
+                    indices: Vec::new(),
+                })
+                .collect();
+            set_items.set(items);
+        });
+        self.common.proxy.list_processes(move |result| {
+            send(result);
+        });
+    }
+
     fn get_color_themes(&self) {
         let config = self.common.config.get_untracked();
         let items = config
@@ -931,6 +1109,141 @@ fn get_color_themes(&self) {
         self.items.set(items);
     }
 
+    /// Initialize the palette with the configuration profiles. Typing a name
+    /// that isn't listed creates a new profile.
+    fn get_profiles(&self) {
+        let items = std::iter::once(None)
+            .chain(profile::profiles().into_iter().map(Some))
+            .map(|name| PaletteItem {
+                filter_text: name
+                    .clone()
+                    .unwrap_or_else(|| profile::DEFAULT_PROFILE_NAME.to_string()),
+                content: PaletteItemContent::Profile { name },
+                score: 0,
+                indices: Vec::new(),
+            })
+            .collect();
+        let active = self
+            .common
+            .profile
+            .get_untracked()
+            .unwrap_or_else(|| profile::DEFAULT_PROFILE_NAME.to_string());
+        self.preselect_matching(&items, &active);
+        self.items.set(items);
+    }
+
+    /// Offer the templates for a new file just created, when there are some
+    /// for its extension, or for an untitled file when `path` is `None`
+    pub fn offer_file_templates(&self, path: Option<PathBuf>) {
+        let extension = path.as_ref().map(|path| {
+            path.extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        if file_templates(extension.as_deref()).is_empty() {
+            return;
+        }
+        self.file_template_path.set(path);
+        self.run(PaletteKind::FileTemplate);
+    }
+
+    fn get_file_templates(&self) {
+        let extension = self.file_template_path.with_untracked(|path| {
+            path.as_ref().map(|path| {
+                path.extension()
+                    .map(|extension| extension.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            })
+        });
+        let items = file_templates(extension.as_deref())
+            .into_iter()
+            .map(|template| PaletteItem {
+                filter_text: format!("{}.{}", template.name, template.extension),
+                content: PaletteItemContent::FileTemplate {
+                    path: template.path,
+                },
+                score: 0,
+                indices: Vec::new(),
+            })
+            .collect();
+        self.items.set(items);
+    }
+
+    fn get_refactors(&self) {
+        let items = self.common.refactors.with_untracked(|refactors| {
+            refactors
+                .refactors
+                .iter()
+                .map(|refactor| PaletteItem {
+                    filter_text: format!(
+                        "{} ({} files, {})",
+                        refactor.title,
+                        refactor.files.len(),
+                        refactor.time.format("%H:%M:%S")
+                    ),
+                    content: PaletteItemContent::Refactor { id: refactor.id },
+                    score: 0,
+                    indices: Vec::new(),
+                })
+                .collect()
+        });
+        self.items.set(items);
+    }
+
+    fn get_grammars(&self) {
+        let items = installed_grammars()
+            .into_iter()
+            .map(|grammar| PaletteItem {
+                filter_text: format!(
+                    "{} ({})",
+                    grammar.name,
+                    grammar.source.as_deref().unwrap_or("comes with Lapce")
+                ),
+                content: PaletteItemContent::Grammar {
+                    name: grammar.name,
+                    source: grammar.source,
+                },
+                score: 0,
+                indices: Vec::new(),
+            })
+            .collect();
+        self.items.set(items);
+    }
+
+    fn get_command_history(&self) {
+        let items = self.keypress.with_untracked(|keypress| {
+            keypress.history.with_untracked(|history| {
+                history
+                    .entries()
+                    .enumerate()
+                    .map(|(i, entry)| PaletteItem {
+                        content: PaletteItemContent::HistoryCommand { n: i + 1 },
+                        filter_text: entry.label(),
+                        score: 0,
+                        indices: Vec::new(),
+                    })
+                    .collect()
+            })
+        });
+        self.items.set(items);
+    }
+
+    fn get_macros(&self) {
+        let config = self.common.config.get_untracked();
+        let items = config
+            .macros
+            .keys()
+            .sorted()
+            .map(|name| PaletteItem {
+                content: PaletteItemContent::Macro { name: name.clone() },
+                filter_text: name.clone(),
+                score: 0,
+                indices: Vec::new(),
+            })
+            .collect();
+        self.items.set(items);
+    }
+
     fn get_icon_themes(&self) {
         let config = self.common.config.get_untracked();
         let items = config
@@ -1017,38 +1330,107 @@ fn get_scm_references(&self) {
         self.items.set(items);
     }
 
-    fn get_terminal_profiles(&self) {
-        let profiles = self.common.config.get().terminal.profiles.clone();
-        let mut items: im::Vector<PaletteItem> = im::Vector::new();
+    /// Initialize the list of commits that changed a file, newest first.
+    fn get_git_history(&self) {
+        let Some((path, commits)) = self.git_history.get_untracked() else {
+            self.items.update(|items| items.clear());
+            return;
+        };
+        let items = commits
+            .into_iter()
+            .map(|commit| {
+                let filter_text =
+                    format!("{} {}", commit.summary, git_commit_hint(&commit));
+                PaletteItem {
+                    content: PaletteItemContent::GitCommit {
+                        path: path.clone(),
+                        commit,
+                    },
+                    filter_text,
+                    score: 0,
+                    indices: Vec::new(),
+                }
+            })
+            .collect();
+        self.items.set(items);
+    }
+
+    /// Browse the machine of a remote workspace from its folder, or from the
+    /// root without one, to pick a folder to open or a file.
+    pub fn browse_remote(&self, pick_folder: bool) {
+        // Start over, so that changes made since the last time show up
+        self.remote_dirs.borrow_mut().clear();
+        let dir = self
+            .workspace
+            .path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("/"));
+        self.remote_browse.set(RemoteBrowse { dir, pick_folder });
+        self.run(PaletteKind::RemotePath);
+    }
 
-        for (name, profile) in profiles.into_iter() {
-            let uri = match lsp_types::Url::parse(&format!(
-                "file://{}",
-                profile.workdir.unwrap_or_default().display()
-            )) {
-                Ok(v) => Some(v),
-                Err(e) => {
-                    error!("Failed to parse uri: {e}");
-                    None
+    /// List the directory being browsed, reading it through the proxy unless
+    /// it was read already.
+    fn get_remote_paths(&self) {
+        let browse = self.remote_browse.get_untracked();
+        if let Some(entries) = self.remote_dirs.borrow().get(&browse.dir) {
+            self.items.set(remote_path_items(&browse, entries));
+            return;
+        }
+
+        self.items.update(|items| items.clear());
+        let remote_dirs = self.remote_dirs.clone();
+        let set_items = self.items.write_only();
+        let run_id = self.run_id;
+        let current_run_id = run_id.get_untracked();
+        let dir = browse.dir.clone();
+        let common = self.common.clone();
+        let send = create_ext_action(self.common.scope, move |result| {
+            let entries = match result {
+                Ok(ProxyResponse::ReadDirResponse { items }) => items
+                    .into_iter()
+                    .map(|item| (item.path, item.is_dir))
+                    .collect(),
+                Ok(_) => return,
+                Err(err) => {
+                    common.report_error(
+                        ErrorReport::new(
+                            "Open Remote Folder",
+                            format!(
+                                "Failed to read {}: {}",
+                                browse.dir.display(),
+                                err.message
+                            ),
+                        )
+                        .path(browse.dir.clone())
+                        .command("read_dir"),
+                    );
+                    // Still offer going back up
+                    Vec::new()
                 }
             };
+            if run_id.get_untracked() == current_run_id {
+                set_items.set(remote_path_items(&browse, &entries));
+            }
+            remote_dirs.borrow_mut().insert(browse.dir, entries);
+        });
+        self.common.proxy.read_dir(dir, move |result| {
+            send(result);
+        });
+    }
 
-            items.push_back(PaletteItem {
-                content: PaletteItemContent::TerminalProfile {
-                    name: name.to_owned(),
-                    profile: lapce_rpc::terminal::TerminalProfile {
-                        name: name.to_owned(),
-                        command: profile.command,
-                        arguments: profile.arguments,
-                        workdir: uri,
-                        environment: profile.environment,
-                    },
-                },
-                filter_text: name.to_owned(),
+    fn get_terminal_profiles(&self) {
+        let profiles = self.common.config.get().terminal.profiles.clone();
+        let items = profiles
+            .into_keys()
+            .sorted()
+            .map(|name| PaletteItem {
+                content: PaletteItemContent::TerminalProfile { name: name.clone() },
+                filter_text: name,
                 score: 0,
                 indices: Vec::new(),
-            });
-        }
+            })
+            .collect();
 
         self.items.set(items);
     }
@@ -1212,6 +1594,22 @@ fn select(&self) {
                         },
                     );
                 }
+                PaletteItemContent::AttachConfig { config } => {
+                    self.attach_config.set(Some(config.clone()));
+                    self.run(PaletteKind::AttachProcess);
+                }
+                PaletteItemContent::Process { process } => {
+                    if let Some(template) =
+                        self.attach_config.try_update(Option::take).flatten()
+                    {
+                        self.common.internal_command.send(
+                            InternalCommand::RunAndDebug {
+                                mode: RunDebugMode::Debug,
+                                config: attach_config(&template, process),
+                            },
+                        );
+                    }
+                }
                 PaletteItemContent::ColorTheme { name } => self
                     .common
                     .internal_command
@@ -1226,6 +1624,10 @@ fn select(&self) {
                         name: name.clone(),
                         save: true,
                     }),
+                PaletteItemContent::Profile { name } => self
+                    .common
+                    .internal_command
+                    .send(InternalCommand::SetProfile { name: name.clone() }),
                 PaletteItemContent::Language { name } => {
                     let editor = self.main_split.active_editor.get_untracked();
                     let doc = match editor {
@@ -1234,16 +1636,38 @@ fn select(&self) {
                             return;
                         }
                     };
-                    if name.is_empty() || name.to_lowercase().eq("plain text") {
-                        doc.set_syntax(Syntax::plaintext())
-                    } else {
-                        let lang = match LapceLanguage::from_name(name) {
-                            Some(v) => v,
-                            None => return,
+                    let lang =
+                        if name.is_empty() || name.to_lowercase().eq("plain text") {
+                            doc.set_syntax(Syntax::plaintext());
+                            LapceLanguage::Plaintext
+                        } else {
+                            let lang = match LapceLanguage::from_name(name) {
+                                Some(v) => v,
+                                None => return,
+                            };
+                            doc.set_language(lang);
+                            lang
                         };
-                        doc.set_language(lang);
-                    }
                     doc.trigger_syntax_change(None);
+
+                    // Remember the choice for the file, unless it's the
+                    // language the file gets anyway
+                    if let DocContent::File { path, .. } =
+                        doc.content.get_untracked()
+                    {
+                        let default = self
+                            .common
+                            .config
+                            .with_untracked(|config| config.language_of(&path));
+                        let db: Arc<LapceDb> = use_context().unwrap();
+                        if let Err(err) = db.save_file_language(
+                            &self.workspace,
+                            &path,
+                            (lang != default).then_some(lang),
+                        ) {
+                            error!("{:?}", err);
+                        }
+                    }
                 }
                 PaletteItemContent::LineEnding { kind } => {
                     let Some(editor) = self.main_split.active_editor.get_untracked()
@@ -1266,12 +1690,84 @@ fn select(&self) {
                         data: Some(serde_json::json!(name.to_owned())),
                     });
                 }
-                PaletteItemContent::TerminalProfile { name: _, profile } => self
+                PaletteItemContent::RemotePath { path, is_dir } => {
+                    if *is_dir {
+                        self.remote_browse
+                            .update(|browse| browse.dir = path.clone());
+                        self.run(PaletteKind::RemotePath);
+                    } else {
+                        self.common
+                            .internal_command
+                            .send(InternalCommand::OpenFile { path: path.clone() });
+                    }
+                }
+                PaletteItemContent::RemoteFolder { path } => {
+                    self.common.window_common.window_command.send(
+                        WindowCommand::SetWorkspace {
+                            workspace: LapceWorkspace {
+                                kind: self.workspace.kind.clone(),
+                                path: Some(path.clone()),
+                                last_open: std::time::SystemTime::now()
+                                    .duration_since(std::time::UNIX_EPOCH)
+                                    .map(|d| d.as_secs())
+                                    .unwrap_or_default(),
+                            },
+                        },
+                    );
+                }
+                PaletteItemContent::GitCommit { path, commit } => {
+                    self.common.internal_command.send(
+                        InternalCommand::OpenCommitChanges {
+                            path: path.clone(),
+                            commit: commit.id.clone(),
+                        },
+                    );
+                }
+                PaletteItemContent::TerminalProfile { name } => self
                     .common
                     .internal_command
                     .send(InternalCommand::NewTerminal {
-                        profile: Some(profile.to_owned()),
+                        profile: Some(name.to_owned()),
                     }),
+                PaletteItemContent::Refactor { id } => self
+                    .common
+                    .internal_command
+                    .send(InternalCommand::RollbackRefactor { id: *id }),
+                PaletteItemContent::HistoryCommand { n } => {
+                    self.common.lapce_command.send(repeat_command(*n));
+                }
+                PaletteItemContent::Macro { name } => {
+                    let cmd = if self.kind.get_untracked() == PaletteKind::SaveMacro
+                    {
+                        LapceCommand {
+                            kind: CommandKind::Workbench(
+                                LapceWorkbenchCommand::SaveMacro,
+                            ),
+                            data: Some(serde_json::json!(name)),
+                        }
+                    } else {
+                        run_macro_command(name, None)
+                    };
+                    self.common.lapce_command.send(cmd);
+                }
+                // The grammars which come with Lapce are updated with it
+                PaletteItemContent::Grammar { source, .. } => {
+                    if let Some(source) = source {
+                        self.common.internal_command.send(
+                            InternalCommand::InstallGrammar {
+                                source: source.clone(),
+                            },
+                        );
+                    }
+                }
+                PaletteItemContent::FileTemplate { path } => {
+                    self.file_template_path.set(None);
+                    if let Some(editor) =
+                        self.main_split.active_editor.get_untracked()
+                    {
+                        editor.apply_file_template(path);
+                    }
+                }
             }
         } else if self.kind.get_untracked() == PaletteKind::SshHost {
             let input = self.input.with_untracked(|input| input.input.clone());
@@ -1285,6 +1781,39 @@ fn select(&self) {
                     },
                 },
             );
+        } else if self.kind.get_untracked() == PaletteKind::RemotePath {
+            let input = self.input.with_untracked(|input| input.input.clone());
+            let path = PathBuf::from(input.trim());
+            if path.is_absolute() {
+                self.remote_browse.update(|browse| browse.dir = path);
+                self.run(PaletteKind::RemotePath);
+            }
+        } else if self.kind.get_untracked() == PaletteKind::Grammar {
+            let input = self.input.with_untracked(|input| input.input.clone());
+            if !input.trim().is_empty() {
+                self.common
+                    .internal_command
+                    .send(InternalCommand::InstallGrammar {
+                        source: input.trim().to_string(),
+                    });
+            }
+        } else if self.kind.get_untracked() == PaletteKind::SaveMacro {
+            let input = self.input.with_untracked(|input| input.input.clone());
+            if is_valid_name(input.trim()) {
+                self.common.lapce_command.send(LapceCommand {
+                    kind: CommandKind::Workbench(LapceWorkbenchCommand::SaveMacro),
+                    data: Some(serde_json::json!(input.trim())),
+                });
+            }
+        } else if self.kind.get_untracked() == PaletteKind::Profile {
+            let input = self.input.with_untracked(|input| input.input.clone());
+            if profile::is_valid_name(&input) {
+                self.common
+                    .internal_command
+                    .send(InternalCommand::SetProfile {
+                        name: Some(input.trim().to_string()),
+                    });
+            }
         }
     }
 
@@ -1332,6 +1861,8 @@ fn preview(&self) {
                 PaletteItemContent::Command { .. } => {}
                 PaletteItemContent::Workspace { .. } => {}
                 PaletteItemContent::RunAndDebug { .. } => {}
+                PaletteItemContent::AttachConfig { .. } => {}
+                PaletteItemContent::Process { .. } => {}
                 PaletteItemContent::SshHost { .. } => {}
                 #[cfg(windows)]
                 PaletteItemContent::WslHost { .. } => {}
@@ -1403,7 +1934,16 @@ fn preview(&self) {
                         save: false,
                     }),
                 PaletteItemContent::SCMReference { .. } => {}
+                PaletteItemContent::GitCommit { .. } => {}
+                PaletteItemContent::RemotePath { .. } => {}
+                PaletteItemContent::RemoteFolder { .. } => {}
                 PaletteItemContent::TerminalProfile { .. } => {}
+                PaletteItemContent::Profile { .. } => {}
+                PaletteItemContent::FileTemplate { .. } => {}
+                PaletteItemContent::Refactor { .. } => {}
+                PaletteItemContent::Grammar { .. } => {}
+                PaletteItemContent::HistoryCommand { .. } => {}
+                PaletteItemContent::Macro { .. } => {}
             }
         }
     }
@@ -1420,6 +1960,8 @@ fn cancel(&self) {
         }
 
         self.left_diff_path.set(None);
+        self.attach_config.set(None);
+        self.file_template_path.set(None);
         self.close();
     }
 
@@ -1633,7 +2175,67 @@ fn run_command(
         CommandExecuted::Yes
     }
 
+    /// The command picked in the palette is recorded, rather than the keys
+    /// picking it
+    fn records_commands(&self) -> bool {
+        false
+    }
+
     fn receive_char(&self, c: &str) {
         self.input_editor.receive_char(c);
     }
 }
+
+/// The entries of the directory being browsed: picking the directory itself
+/// when picking a folder, going up to its parent, and then its directories
+/// and, when picking a file, its files.
+fn remote_path_items(
+    browse: &RemoteBrowse,
+    entries: &[(PathBuf, bool)],
+) -> im::Vector<PaletteItem> {
+    let item = |content, filter_text: String| PaletteItem {
+        content,
+        filter_text,
+        score: 0,
+        indices: Vec::new(),
+    };
+
+    let mut items = im::Vector::new();
+    if browse.pick_folder {
+        items.push_back(item(
+            PaletteItemContent::RemoteFolder {
+                path: browse.dir.clone(),
+            },
+            format!("Open {}", browse.dir.display()),
+        ));
+    }
+    if let Some(parent) = browse.dir.parent() {
+        items.push_back(item(
+            PaletteItemContent::RemotePath {
+                path: parent.to_path_buf(),
+                is_dir: true,
+            },
+            "../".to_string(),
+        ));
+    }
+    for (path, is_dir) in entries {
+        if browse.pick_folder && !is_dir {
+            continue;
+        }
+        let mut name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if *is_dir {
+            name.push('/');
+        }
+        items.push_back(item(
+            PaletteItemContent::RemotePath {
+                path: path.clone(),
+                is_dir: *is_dir,
+            },
+            name,
+        ));
+    }
+    items
+}
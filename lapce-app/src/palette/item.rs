@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 use lapce_core::line_ending::LineEnding;
-use lapce_rpc::dap_types::RunDebugConfig;
+use lapce_rpc::{
+    dap_types::{ProcessInfo, RunDebugConfig},
+    source_control::GitCommitInfo,
+};
 use lsp_types::{Range, SymbolKind};
 
 use crate::{
@@ -19,6 +22,19 @@ pub struct PaletteItem {
     pub indices: Vec<usize>,
 }
 
+/// The short id, author and date of a commit, shown after its summary
+pub fn git_commit_hint(commit: &GitCommitInfo) -> String {
+    let id = commit.id.get(..7).unwrap_or(&commit.id);
+    let date = chrono::DateTime::from_timestamp(commit.time, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_default();
+    format!("{id} {} {date}", commit.author)
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum PaletteItemContent {
     PaletteHelp {
@@ -65,12 +81,23 @@ pub enum PaletteItemContent {
         mode: RunDebugMode,
         config: RunDebugConfig,
     },
+    /// Pick the process to attach the debugger of the config to
+    AttachConfig {
+        config: RunDebugConfig,
+    },
+    Process {
+        process: ProcessInfo,
+    },
     ColorTheme {
         name: String,
     },
     IconTheme {
         name: String,
     },
+    Profile {
+        /// `None` for the default profile
+        name: Option<String>,
+    },
     Language {
         name: String,
     },
@@ -80,8 +107,42 @@ pub enum PaletteItemContent {
     SCMReference {
         name: String,
     },
+    GitCommit {
+        path: PathBuf,
+        commit: GitCommitInfo,
+    },
+    /// A file or directory of a remote workspace's machine
+    RemotePath {
+        path: PathBuf,
+        is_dir: bool,
+    },
+    /// Open the directory being browsed as the remote workspace
+    RemoteFolder {
+        path: PathBuf,
+    },
     TerminalProfile {
         name: String,
-        profile: lapce_rpc::terminal::TerminalProfile,
+    },
+    /// A template to fill the new file with
+    FileTemplate {
+        path: PathBuf,
+    },
+    /// A refactor of the journal, to roll back
+    Refactor {
+        id: u64,
+    },
+    /// An installed tree-sitter grammar, to update from where it was
+    /// installed from
+    Grammar {
+        name: String,
+        source: Option<String>,
+    },
+    /// The `n`th most recent command, to run again
+    HistoryCommand {
+        n: usize,
+    },
+    /// A saved macro, to run or to save the last macro recorded over
+    Macro {
+        name: String,
     },
 }
@@ -16,13 +16,23 @@ pub enum PaletteKind {
     #[cfg(windows)]
     WslHost,
     RunAndDebug,
+    AttachProcess,
     ColorTheme,
     IconTheme,
     Language,
     LineEnding,
     SCMReferences,
+    GitHistory,
+    RemotePath,
     TerminalProfile,
     DiffFiles,
+    Profile,
+    FileTemplate,
+    Refactor,
+    Grammar,
+    CommandHistory,
+    Macro,
+    SaveMacro,
 }
 
 impl PaletteKind {
@@ -41,12 +51,22 @@ pub fn symbol(&self) -> &'static str {
             | PaletteKind::Reference
             | PaletteKind::SshHost
             | PaletteKind::RunAndDebug
+            | PaletteKind::AttachProcess
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
             | PaletteKind::LineEnding
             | PaletteKind::SCMReferences
-            | PaletteKind::DiffFiles => "",
+            | PaletteKind::GitHistory
+            | PaletteKind::RemotePath
+            | PaletteKind::DiffFiles
+            | PaletteKind::Profile
+            | PaletteKind::FileTemplate
+            | PaletteKind::Refactor
+            | PaletteKind::Grammar
+            | PaletteKind::CommandHistory
+            | PaletteKind::Macro
+            | PaletteKind::SaveMacro => "",
             #[cfg(windows)]
             PaletteKind::WslHost => "",
         }
@@ -87,6 +107,9 @@ pub fn command(self) -> Option<LapceWorkbenchCommand> {
             PaletteKind::RunAndDebug => {
                 Some(LapceWorkbenchCommand::PaletteRunAndDebug)
             }
+            PaletteKind::AttachProcess => {
+                Some(LapceWorkbenchCommand::PaletteAttachProcess)
+            }
             PaletteKind::ColorTheme => Some(LapceWorkbenchCommand::ChangeColorTheme),
             PaletteKind::IconTheme => Some(LapceWorkbenchCommand::ChangeIconTheme),
             PaletteKind::Language => Some(LapceWorkbenchCommand::ChangeFileLanguage),
@@ -96,8 +119,21 @@ pub fn command(self) -> Option<LapceWorkbenchCommand> {
             PaletteKind::SCMReferences => {
                 Some(LapceWorkbenchCommand::PaletteSCMReferences)
             }
+            PaletteKind::GitHistory => None, // InternalCommand::PaletteGitHistory
+            PaletteKind::RemotePath => None, // PaletteData::browse_remote
             PaletteKind::TerminalProfile => None, // InternalCommand::NewTerminal
             PaletteKind::DiffFiles => Some(LapceWorkbenchCommand::DiffFiles),
+            PaletteKind::Profile => Some(LapceWorkbenchCommand::SwitchProfile),
+            PaletteKind::FileTemplate => None, // PaletteData::offer_file_templates
+            PaletteKind::Refactor => {
+                Some(LapceWorkbenchCommand::RollBackWorkspaceRefactor)
+            }
+            PaletteKind::Grammar => Some(LapceWorkbenchCommand::ManageGrammars),
+            PaletteKind::CommandHistory => {
+                Some(LapceWorkbenchCommand::PaletteCommandHistory)
+            }
+            PaletteKind::Macro => Some(LapceWorkbenchCommand::RunMacro),
+            PaletteKind::SaveMacro => Some(LapceWorkbenchCommand::SaveMacro),
         }
     }
 
@@ -120,12 +156,22 @@ pub fn get_input<'a>(&self, input: &'a str) -> &'a str {
             | PaletteKind::Reference
             | PaletteKind::SshHost
             | PaletteKind::RunAndDebug
+            | PaletteKind::AttachProcess
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
             | PaletteKind::LineEnding
             | PaletteKind::SCMReferences
-            | PaletteKind::DiffFiles => input,
+            | PaletteKind::GitHistory
+            | PaletteKind::RemotePath
+            | PaletteKind::DiffFiles
+            | PaletteKind::Profile
+            | PaletteKind::FileTemplate
+            | PaletteKind::Refactor
+            | PaletteKind::Grammar
+            | PaletteKind::CommandHistory
+            | PaletteKind::Macro
+            | PaletteKind::SaveMacro => input,
             PaletteKind::PaletteHelp
             | PaletteKind::Command
             | PaletteKind::Workspace
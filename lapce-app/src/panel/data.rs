@@ -31,7 +31,19 @@ pub fn default_panel_order() -> PanelOrder {
     );
     order.insert(
         PanelPosition::BottomLeft,
-        im::vector![PanelKind::Terminal, PanelKind::Search, PanelKind::Problem,],
+        im::vector![
+            PanelKind::Terminal,
+            PanelKind::Search,
+            PanelKind::Problem,
+            PanelKind::DebugConsole,
+            PanelKind::Flame,
+            PanelKind::Output,
+            PanelKind::Notifications,
+        ],
+    );
+    order.insert(
+        PanelPosition::RightTop,
+        im::vector![PanelKind::JsonTree, PanelKind::SyntaxTree],
     );
 
     order
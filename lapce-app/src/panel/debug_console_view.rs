@@ -0,0 +1,237 @@
+use std::{ops::Range, rc::Rc};
+
+use floem::{
+    cosmic_text::TextLayout,
+    event::EventListener,
+    kurbo::{Point, Size},
+    reactive::{create_memo, create_rw_signal},
+    style::{CursorStyle, FlexWrap},
+    views::{
+        container, dyn_stack, label, rich_text,
+        scroll::{scroll, HideBar},
+        stack, virtual_stack, Decorators, VirtualDirection, VirtualItemSize,
+        VirtualVector,
+    },
+    View,
+};
+
+use super::kind::PanelKind;
+use crate::{
+    config::{color::LapceColor, LapceConfig},
+    debug_console::{ConsoleCategory, ConsoleLine, DebugConsoleData},
+    rich_output::{ansi_layout, rich_output_height, rich_output_view},
+    settings::checkbox,
+    text_input::TextInputBuilder,
+    window_tab::{Focus, WindowTabData},
+};
+
+struct ConsoleLines(im::Vector<ConsoleLine>);
+
+impl VirtualVector<ConsoleLine> for ConsoleLines {
+    fn total_len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn slice(&mut self, range: Range<usize>) -> impl Iterator<Item = ConsoleLine> {
+        self.0.slice(range).into_iter()
+    }
+}
+
+pub fn debug_console_panel(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let console = window_tab_data.debug_console.clone();
+    let terminal = window_tab_data.terminal.clone();
+    let hidden = console.hidden;
+
+    // The lines of the active debug session that aren't filtered out
+    let lines = create_memo(move |_| {
+        let Some(dap) = terminal.get_active_dap(true) else {
+            return im::Vector::new();
+        };
+        let hidden = hidden.get();
+        dap.console.with(|console| {
+            console
+                .lines
+                .iter()
+                .filter(|line| !hidden.contains(&line.category.filter()))
+                .cloned()
+                .collect::<im::Vector<_>>()
+        })
+    });
+    let line_height =
+        create_memo(move |_| config.get().terminal_line_height() as f64);
+
+    stack((
+        filters(console.clone()),
+        container(
+            scroll(
+                virtual_stack(
+                    VirtualDirection::Vertical,
+                    VirtualItemSize::Fn(Box::new(move |line: &ConsoleLine| {
+                        let line_height = line_height.get();
+                        line.rich.as_ref().map_or(line_height, |rich| {
+                            rich_output_height(rich, line_height)
+                        })
+                    })),
+                    move || ConsoleLines(lines.get()),
+                    |line| line.id,
+                    move |line| {
+                        if let Some(rich) = line.rich.clone() {
+                            return rich_output_view(
+                                rich,
+                                line_height.get_untracked(),
+                                config,
+                            );
+                        }
+                        let text_layout = line_layout(&line, &config.get());
+                        rich_text(move || text_layout.clone())
+                            .style(move |s| s.height(line_height.get()))
+                            .into_any()
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full().padding_horiz(10.0)),
+            )
+            .scroll_to(move || {
+                lines.track();
+                Some(Point::new(0.0, f64::MAX))
+            })
+            .style(|s| s.absolute().size_full()),
+        )
+        .style(|s| s.width_full().flex_grow(1.0).flex_basis(0.0)),
+        completions(console.clone()),
+        input(console),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .size_full()
+            .background(config.get().color(LapceColor::PANEL_BACKGROUND))
+    })
+}
+
+/// The text of a line in the terminal font, in the colors of its escapes
+fn line_layout(line: &ConsoleLine, config: &LapceConfig) -> TextLayout {
+    let foreground = match line.category {
+        ConsoleCategory::Stderr | ConsoleCategory::Error => {
+            config.color(LapceColor::LAPCE_ERROR)
+        }
+        ConsoleCategory::Console | ConsoleCategory::Input => {
+            config.color(LapceColor::EDITOR_DIM)
+        }
+        ConsoleCategory::Stdout | ConsoleCategory::Result => {
+            config.color(LapceColor::TERMINAL_FOREGROUND)
+        }
+    };
+    ansi_layout(&line.text, &line.spans, foreground, config)
+}
+
+fn filters(console: DebugConsoleData) -> impl View {
+    let config = console.common.config;
+    let filter = move |category: ConsoleCategory, name: &'static str| {
+        let console = console.clone();
+        let hidden = console.hidden;
+        stack((
+            checkbox(move || hidden.with(|h| !h.contains(&category)), config),
+            label(move || name.to_string()).style(|s| s.margin_left(6.0)),
+        ))
+        .on_click_stop(move |_| {
+            console.toggle_category(category);
+        })
+        .style(|s| {
+            s.items_center()
+                .margin_right(20.0)
+                .cursor(CursorStyle::Pointer)
+        })
+    };
+    stack((
+        filter(ConsoleCategory::Stdout, "stdout"),
+        filter(ConsoleCategory::Stderr, "stderr"),
+        filter(ConsoleCategory::Console, "console"),
+    ))
+    .style(move |s| {
+        s.width_full()
+            .items_center()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .border_bottom(1.0)
+            .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+    })
+}
+
+/// The candidates of the last completion, to pick one with the mouse
+fn completions(console: DebugConsoleData) -> impl View {
+    let config = console.common.config;
+    let items = console.completions;
+    dyn_stack(
+        move || items.get(),
+        |item| item.label.clone(),
+        move |item| {
+            let console = console.clone();
+            let text = item.label.clone();
+            label(move || text.clone())
+                .on_click_stop(move |_| {
+                    console.apply_completion(&item);
+                })
+                .style(move |s| {
+                    let config = config.get();
+                    s.padding_horiz(6.0)
+                        .margin_right(4.0)
+                        .border_radius(4.0)
+                        .cursor(CursorStyle::Pointer)
+                        .hover(|s| {
+                            s.background(
+                                config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                        })
+                })
+        },
+    )
+    .style(move |s| {
+        s.width_full()
+            .flex_wrap(FlexWrap::Wrap)
+            .padding_horiz(10.0)
+            .padding_vert(2.0)
+            .apply_if(items.with(|items| items.is_empty()), |s| s.hide())
+    })
+}
+
+fn input(console: DebugConsoleData) -> impl View {
+    let config = console.common.config;
+    let focus = console.common.focus;
+    let is_focused = move || focus.get() == Focus::Panel(PanelKind::DebugConsole);
+    let cursor_x = create_rw_signal(0.0);
+
+    container(
+        scroll(
+            TextInputBuilder::new()
+                .is_focused(is_focused)
+                .build_editor(console.editor.clone())
+                .placeholder(|| "Evaluate an expression".to_string())
+                .on_cursor_pos(move |point| {
+                    cursor_x.set(point.x);
+                })
+                .style(|s| {
+                    s.padding_vert(4.0).padding_horiz(10.0).min_width_pct(100.0)
+                }),
+        )
+        .ensure_visible(move || {
+            Size::new(20.0, 0.0)
+                .to_rect()
+                .with_origin(Point::new(cursor_x.get(), 0.0))
+        })
+        .on_event_cont(EventListener::PointerDown, move |_| {
+            focus.set(Focus::Panel(PanelKind::DebugConsole));
+        })
+        .style(move |s| {
+            let config = config.get();
+            s.set(HideBar, true)
+                .width_full()
+                .cursor(CursorStyle::Text)
+                .items_center()
+                .background(config.color(LapceColor::EDITOR_BACKGROUND))
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+        }),
+    )
+    .style(|s| s.width_full().padding(6.0))
+}
@@ -1,19 +1,21 @@
 use std::{rc::Rc, sync::Arc};
 
 use floem::{
+    action::show_context_menu,
     cosmic_text::Style as FontStyle,
     event::EventListener,
+    menu::{Menu, MenuItem},
     peniko::Color,
-    reactive::{create_rw_signal, ReadSignal, RwSignal},
+    reactive::{create_rw_signal, ReadSignal, Scope},
     style::CursorStyle,
     views::{
-        container, dyn_stack, label, scroll, stack, svg, text, virtual_stack,
-        Decorators, VirtualDirection, VirtualItemSize,
+        container, dyn_container, dyn_stack, label, scroll, stack, svg, text,
+        virtual_stack, Decorators, VirtualDirection, VirtualItemSize,
     },
     View,
 };
 use lapce_rpc::{
-    dap_types::{DapId, ThreadId},
+    dap_types::{DapId, StackFrame, ThreadId},
     terminal::TermId,
 };
 
@@ -22,11 +24,16 @@
     app::clickable_icon,
     command::InternalCommand,
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
-    debug::{DapVariable, RunDebugMode, StackTraceData},
+    debug::{
+        stack_frame_rows, thread_matches, DapData, DapVariable, DapVariableEdit,
+        DapVariableEditData, RunDebugData, RunDebugMode, ScopeOrVar, StackFrameRow,
+        StackTraceData,
+    },
     editor::location::{EditorLocation, EditorPosition},
     listener::Listener,
     settings::checkbox,
     terminal::panel::TerminalPanelData,
+    text_input::TextInputBuilder,
     window_tab::WindowTabData,
 };
 
@@ -36,9 +43,8 @@ pub fn debug_panel(
 ) -> impl View {
     let config = window_tab_data.common.config;
     let terminal = window_tab_data.terminal.clone();
-    let internal_command = window_tab_data.common.internal_command;
 
-    PanelBuilder::new(config, position)
+    let sections = PanelBuilder::new(config, position)
         .add_height(
             "Processes",
             150.0,
@@ -52,7 +58,7 @@ pub fn debug_panel(
         )
         .add(
             "Stack Frames",
-            debug_stack_traces(terminal.clone(), internal_command, config),
+            debug_stack_traces(window_tab_data.clone()),
             window_tab_data.panel.section_open(PanelSection::StackFrame),
         )
         .add_height(
@@ -61,8 +67,91 @@ pub fn debug_panel(
             breakpoints_view(window_tab_data.clone()),
             window_tab_data.panel.section_open(PanelSection::Breakpoint),
         )
-        .build()
-        .debug_name("Debug Panel")
+        .build();
+
+    stack((
+        debug_session_switcher(terminal, config),
+        sections.style(|s| s.flex_grow(1.0).flex_basis(0.0).min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().size_full())
+    .debug_name("Debug Panel")
+}
+
+/// The session whose variables and stack frames are shown, picked from a
+/// menu of the debug sessions. Hidden when nothing is being debugged.
+fn debug_session_switcher(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let active_name = {
+        let terminal = terminal.clone();
+        move || {
+            let active = terminal.debug.active_term.get();
+            terminal
+                .debug_sessions(true)
+                .into_iter()
+                .find(|(term_id, _)| Some(*term_id) == active)
+                .map(|(_, p)| p.config.name)
+                .unwrap_or_else(|| "Select a session".to_string())
+        }
+    };
+    let has_sessions = {
+        let terminal = terminal.clone();
+        move || !terminal.debug_sessions(true).is_empty()
+    };
+
+    stack((
+        label(active_name).style(|s| {
+            s.flex_grow(1.0)
+                .flex_basis(0.0)
+                .min_width(0.0)
+                .text_ellipsis()
+        }),
+        svg(move || config.get().ui_svg(LapceIcons::DROPDOWN_ARROW)).style(
+            move |s| {
+                let config = config.get();
+                let size = config.ui.icon_size() as f32;
+                s.size(size, size)
+                    .color(config.color(LapceColor::LAPCE_ICON_ACTIVE))
+            },
+        ),
+    ))
+    .on_click_stop(move |_| {
+        let mut menu = Menu::new("");
+        for (term_id, process) in terminal.debug_sessions(false) {
+            let stopped = terminal.debug.daps.with_untracked(|daps| {
+                daps.get(&process.config.dap_id)
+                    .map(|dap| dap.stopped.get_untracked())
+                    .unwrap_or(false)
+            });
+            let label = if process.stopped {
+                format!("{} (Ended)", process.config.name)
+            } else if stopped {
+                format!("{} (Paused)", process.config.name)
+            } else {
+                process.config.name.clone()
+            };
+            let terminal = terminal.clone();
+            menu = menu.entry(MenuItem::new(label).action(move || {
+                terminal.set_active_session(term_id);
+            }));
+        }
+        show_context_menu(menu, None);
+    })
+    .style(move |s| {
+        let config = config.get();
+        s.items_center()
+            .width_full()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .cursor(CursorStyle::Pointer)
+            .border_bottom(1.0)
+            .border_color(config.color(LapceColor::LAPCE_BORDER))
+            .hover(|s| {
+                s.background(config.color(LapceColor::PANEL_HOVERED_BACKGROUND))
+            })
+            .apply_if(!has_sessions(), |s| s.hide())
+    })
 }
 
 fn debug_process_icons(
@@ -331,6 +420,7 @@ fn debug_processes(
 fn variables_view(window_tab_data: Rc<WindowTabData>) -> impl View {
     let terminal = window_tab_data.terminal.clone();
     let local_terminal = window_tab_data.terminal.clone();
+    let variable_edit = window_tab_data.variable_edit.clone();
     let ui_line_height = window_tab_data.common.ui_line_height;
     let config = window_tab_data.common.config;
     container(
@@ -368,11 +458,29 @@ fn variables_view(window_tab_data: Rc<WindowTabData>) -> impl View {
                 },
                 move |node| {
                     let local_terminal = local_terminal.clone();
+                    let variable_edit = variable_edit.clone();
                     let level = node.level;
                     let reference = node.item.reference();
                     let name = node.item.name();
                     let ty = node.item.ty();
                     let type_exists = ty.map(|ty| !ty.is_empty()).unwrap_or(false);
+                    let value = node.item.value().unwrap_or("").to_string();
+                    let is_var = matches!(node.item, ScopeOrVar::Var(_));
+                    let dap_id =
+                        local_terminal.get_active_dap(false).map(|dap| dap.dap_id);
+                    let edit = dap_id.map(|dap_id| DapVariableEdit {
+                        dap_id,
+                        parent: node.parent.clone(),
+                        name: name.to_string(),
+                    });
+                    let editing = variable_edit.editing;
+                    let is_editing = {
+                        let edit = edit.clone();
+                        move || {
+                            edit.is_some()
+                                && editing.with(|editing| editing == &edit)
+                        }
+                    };
                     stack((
                         svg(move || {
                             let config = config.get();
@@ -403,9 +511,39 @@ fn variables_view(window_tab_data: Rc<WindowTabData>) -> impl View {
                                     s.hide()
                                 })
                         }),
-                        text(format!(" = {}", node.item.value().unwrap_or("")))
-                            .style(move |s| s.apply_if(reference > 0, |s| s.hide())),
+                        {
+                            let variable_edit = variable_edit.clone();
+                            let value = value.clone();
+                            dyn_container(is_editing, move |is_editing| {
+                                if is_editing {
+                                    variable_value_input(variable_edit.clone())
+                                        .into_any()
+                                } else {
+                                    text(format!(" = {value}"))
+                                        .style(move |s| {
+                                            s.apply_if(reference > 0, |s| s.hide())
+                                        })
+                                        .into_any()
+                                }
+                            })
+                        },
                     ))
+                    .on_secondary_click_stop({
+                        let local_terminal = local_terminal.clone();
+                        let parent = node.parent.clone();
+                        move |_| {
+                            let (Some(edit), true) = (edit.clone(), is_var) else {
+                                return;
+                            };
+                            variable_context_menu(
+                                &local_terminal,
+                                &variable_edit,
+                                edit,
+                                &parent,
+                                &value,
+                            );
+                        }
+                    })
                     .on_click_stop(move |_| {
                         if reference > 0 {
                             let dap = local_terminal.get_active_dap(false);
@@ -450,15 +588,79 @@ fn variables_view(window_tab_data: Rc<WindowTabData>) -> impl View {
     .style(|s| s.width_full().line_height(1.6).flex_grow(1.0).flex_basis(0))
 }
 
+/// The input replacing the value of a variable while it's being edited
+fn variable_value_input(variable_edit: DapVariableEditData) -> impl View {
+    let config = variable_edit.editor.common.config;
+    let editor = variable_edit.editor.clone();
+    let input = TextInputBuilder::new()
+        .is_focused(|| true)
+        .key_focus(variable_edit.clone())
+        .build_editor(editor)
+        .on_event_stop(EventListener::FocusLost, move |_| {
+            variable_edit.cancel();
+        })
+        .style(move |s| {
+            s.min_width(100.0)
+                .padding_horiz(4.0)
+                .border(1.0)
+                .border_radius(4.0)
+                .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+        });
+    input.id().request_focus();
+    stack((text(" = "), input)).style(|s| s.items_center())
+}
+
+/// The entries for a variable, if its adapter supports any of them
+fn variable_context_menu(
+    terminal: &TerminalPanelData,
+    variable_edit: &DapVariableEditData,
+    edit: DapVariableEdit,
+    parent: &[usize],
+    value: &str,
+) {
+    let debug = &terminal.debug;
+    let dap_id = edit.dap_id;
+    let Some(dap) = debug.daps.with_untracked(|daps| daps.get(&dap_id).cloned())
+    else {
+        return;
+    };
+
+    let mut menu = Menu::new("");
+    let mut empty = true;
+    if debug.supports(dap_id, |c| c.supports_set_variable) {
+        let variable_edit = variable_edit.clone();
+        let value = value.to_string();
+        let edit = edit.clone();
+        menu = menu.entry(MenuItem::new("Set Value").action(move || {
+            variable_edit.start(edit.clone(), &value);
+        }));
+        empty = false;
+    }
+    if debug.supports(dap_id, |c| c.supports_data_breakpoints) {
+        let parent = parent.to_vec();
+        let name = edit.name;
+        menu =
+            menu.entry(MenuItem::new("Break on Value Change").action(move || {
+                dap.add_data_breakpoint(&parent, name.clone());
+            }));
+        empty = false;
+    }
+    if !empty {
+        show_context_menu(menu, None);
+    }
+}
+
 fn debug_stack_frames(
-    dap_id: DapId,
+    dap: DapData,
     thread_id: ThreadId,
     stack_trace: StackTraceData,
-    stopped: RwSignal<bool>,
+    debug: RunDebugData,
     internal_command: Listener<InternalCommand>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
     let expanded = stack_trace.expanded;
+    let expanded_external = stack_trace.expanded_external;
+    let stopped = dap.stopped;
     stack((
         container(label(move || thread_id.to_string()))
             .on_click_stop(move |_| {
@@ -477,82 +679,50 @@ fn debug_stack_frames(
             move || {
                 let expanded = stack_trace.expanded.get() && stopped.get();
                 if expanded {
-                    stack_trace.frames.get()
+                    let frames = stack_trace.frames.get();
+                    expanded_external
+                        .with(|expanded| stack_frame_rows(&frames, expanded))
                 } else {
-                    im::Vector::new()
+                    Vec::new()
                 }
             },
-            |frame| frame.id,
-            move |frame| {
-                let full_path = frame.source.as_ref().and_then(|s| s.path.clone());
-                let line = frame.line.saturating_sub(1);
-                let col = frame.column.saturating_sub(1);
-
-                let source_path = frame
-                    .source
-                    .as_ref()
-                    .and_then(|s| s.path.as_ref())
-                    .and_then(|p| p.file_name())
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("")
-                    .to_string();
-                let has_source = !source_path.is_empty();
-                let source_path = format!("{source_path}:{}", frame.line);
-
-                container(stack((
-                    label(move || frame.name.clone()).style(move |s| {
-                        s.hover(|s| {
-                            s.background(
-                                config
-                                    .get()
-                                    .color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                            )
-                        })
-                    }),
-                    label(move || source_path.clone()).style(move |s| {
-                        s.margin_left(10.0)
-                            .color(config.get().color(LapceColor::EDITOR_DIM))
-                            .font_style(FontStyle::Italic)
-                            .apply_if(!has_source, |s| s.hide())
-                    }),
-                )))
-                .on_click_stop(move |_| {
-                    if let Some(path) = full_path.clone() {
-                        internal_command.send(InternalCommand::JumpToLocation {
-                            location: EditorLocation {
-                                path,
-                                position: Some(EditorPosition::Position(
-                                    lsp_types::Position {
-                                        line: line as u32,
-                                        character: col as u32,
-                                    },
-                                )),
-                                scroll_offset: None,
-                                ignore_unconfirmed: false,
-                                same_editor_tab: false,
-                            },
-                        });
-                    }
-                    internal_command.send(InternalCommand::DapFrameScopes {
-                        dap_id,
-                        frame_id: frame.id,
-                    });
-                })
-                .style(move |s| {
-                    let config = config.get();
-                    s.padding_left(20.0)
-                        .padding_right(10.0)
-                        .min_width_pct(100.0)
-                        .apply_if(!has_source, |s| {
-                            s.color(config.color(LapceColor::EDITOR_DIM))
+            |row| match row {
+                StackFrameRow::Frame(frame) => (frame.id, 0),
+                StackFrameRow::External { first_id, count } => (*first_id, *count),
+            },
+            move |row| match row {
+                StackFrameRow::Frame(frame) => debug_stack_frame(
+                    dap.clone(),
+                    frame,
+                    debug.clone(),
+                    internal_command,
+                    config,
+                )
+                .into_any(),
+                StackFrameRow::External { first_id, count } => {
+                    label(move || format!("{count} external frames"))
+                        .on_click_stop(move |_| {
+                            expanded_external.update(|expanded| {
+                                expanded.insert(first_id);
+                            });
                         })
-                        .hover(|s| {
-                            s.background(
-                                config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                            )
-                            .apply_if(has_source, |s| s.cursor(CursorStyle::Pointer))
+                        .style(move |s| {
+                            let config = config.get();
+                            s.padding_left(20.0)
+                                .padding_right(10.0)
+                                .min_width_pct(100.0)
+                                .color(config.color(LapceColor::EDITOR_DIM))
+                                .font_style(FontStyle::Italic)
+                                .hover(|s| {
+                                    s.cursor(CursorStyle::Pointer).background(
+                                        config.color(
+                                            LapceColor::PANEL_HOVERED_BACKGROUND,
+                                        ),
+                                    )
+                                })
                         })
-                })
+                        .into_any()
+                }
             },
         )
         .style(|s| s.flex_col().min_width_pct(100.0)),
@@ -560,190 +730,391 @@ fn debug_stack_frames(
     .style(|s| s.flex_col().min_width_pct(100.0))
 }
 
-fn debug_stack_traces(
-    terminal: TerminalPanelData,
+/// A frame, which is selected on click for the variables, the debug console
+/// and the break line, and can be restarted from its context menu
+fn debug_stack_frame(
+    dap: DapData,
+    frame: StackFrame,
+    debug: RunDebugData,
     internal_command: Listener<InternalCommand>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
-    container(
-        scroll({
-            let local_terminal = terminal.clone();
-            dyn_stack(
-                move || {
-                    let dap = local_terminal.get_active_dap(true);
-                    if let Some(dap) = dap {
-                        let process_stopped = local_terminal
-                            .get_terminal(&dap.term_id)
-                            .and_then(|t| {
-                                t.run_debug.with(|r| r.as_ref().map(|r| r.stopped))
-                            })
-                            .unwrap_or(true);
-                        if process_stopped {
-                            return Vec::new();
-                        }
-                        let main_thread = dap.thread_id.get();
-                        let stack_traces = dap.stack_traces.get();
-                        let mut traces = stack_traces
-                            .into_iter()
-                            .map(|(thread_id, stack_trace)| {
-                                (dap.dap_id, dap.stopped, thread_id, stack_trace)
-                            })
-                            .collect::<Vec<_>>();
-                        traces.sort_by_key(|(_, _, id, _)| main_thread != Some(*id));
-                        traces
-                    } else {
-                        Vec::new()
-                    }
-                },
-                |(dap_id, stopped, thread_id, _)| {
-                    (*dap_id, *thread_id, stopped.get_untracked())
-                },
-                move |(dap_id, stopped, thread_id, stack_trace)| {
-                    debug_stack_frames(
-                        dap_id,
-                        thread_id,
-                        stack_trace,
-                        stopped,
-                        internal_command,
-                        config,
-                    )
+    let dap_id = dap.dap_id;
+    let frame_id = frame.id;
+    let selected_frame = dap.frame_id;
+    let can_restart = frame.can_restart != Some(false);
+    let full_path = frame.source.as_ref().and_then(|s| s.path.clone());
+    let line = frame.line.saturating_sub(1);
+    let col = frame.column.saturating_sub(1);
+
+    let source_path = frame
+        .source
+        .as_ref()
+        .and_then(|s| s.path.as_ref())
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let has_source = !source_path.is_empty();
+    let source_path = format!("{source_path}:{}", frame.line);
+
+    container(stack((
+        label(move || frame.name.clone()).style(move |s| {
+            s.hover(|s| {
+                s.background(
+                    config.get().color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+        }),
+        label(move || source_path.clone()).style(move |s| {
+            s.margin_left(10.0)
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+                .font_style(FontStyle::Italic)
+                .apply_if(!has_source, |s| s.hide())
+        }),
+    )))
+    .on_click_stop(move |_| {
+        if let Some(path) = full_path.clone() {
+            internal_command.send(InternalCommand::JumpToLocation {
+                location: EditorLocation {
+                    path,
+                    position: Some(EditorPosition::Position(lsp_types::Position {
+                        line: line as u32,
+                        character: col as u32,
+                    })),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
                 },
-            )
-            .style(|s| s.flex_col().min_width_pct(100.0))
-        })
-        .style(|s| s.absolute().size_pct(100.0, 100.0)),
-    )
+            });
+        }
+        internal_command.send(InternalCommand::DapFrameScopes { dap_id, frame_id });
+    })
+    .on_secondary_click_stop(move |_| {
+        if !can_restart || !debug.supports(dap_id, |c| c.supports_restart_frame) {
+            return;
+        }
+        let dap = dap.clone();
+        let menu =
+            Menu::new("").entry(MenuItem::new("Restart Frame").action(move || {
+                dap.restart_frame(frame_id);
+            }));
+        show_context_menu(menu, None);
+    })
+    .style(move |s| {
+        let config = config.get();
+        let selected = selected_frame.get() == Some(frame_id);
+        s.padding_left(20.0)
+            .padding_right(10.0)
+            .min_width_pct(100.0)
+            .apply_if(!has_source, |s| {
+                s.color(config.color(LapceColor::EDITOR_DIM))
+            })
+            .apply_if(selected, |s| {
+                s.background(config.color(LapceColor::PANEL_CURRENT_BACKGROUND))
+            })
+            .hover(|s| {
+                s.background(config.color(LapceColor::PANEL_HOVERED_BACKGROUND))
+                    .apply_if(has_source, |s| s.cursor(CursorStyle::Pointer))
+            })
+    })
+}
+fn debug_stack_traces(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let terminal = window_tab_data.terminal.clone();
+    let internal_command = window_tab_data.common.internal_command;
+    let config = window_tab_data.common.config;
+    let debug = terminal.debug.clone();
+
+    let cx = Scope::current();
+    let filter_input = TextInputBuilder::new()
+        .build(
+            cx,
+            window_tab_data.main_split.editors,
+            window_tab_data.common.clone(),
+        )
+        .placeholder(|| "Filter threads".to_string());
+    let filter_doc = filter_input.doc_signal();
+
+    stack((
+        container(filter_input.style(move |s| {
+            let config = config.get();
+            s.width_pct(100.0)
+                .padding_horiz(6.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+        }))
+        .style(|s| s.width_pct(100.0).padding_horiz(10.0).padding_vert(4.0)),
+        container(
+            scroll({
+                let local_terminal = terminal.clone();
+                dyn_stack(
+                    move || {
+                        let dap = local_terminal.get_active_dap(true);
+                        if let Some(dap) = dap {
+                            let process_stopped = local_terminal
+                                .get_terminal(&dap.term_id)
+                                .and_then(|t| {
+                                    t.run_debug
+                                        .with(|r| r.as_ref().map(|r| r.stopped))
+                                })
+                                .unwrap_or(true);
+                            if process_stopped {
+                                return Vec::new();
+                            }
+                            let filter = filter_doc
+                                .get()
+                                .buffer
+                                .with(|buffer| buffer.to_string());
+                            let main_thread = dap.thread_id.get();
+                            let stack_traces = dap.stack_traces.get();
+                            let mut traces = stack_traces
+                                .into_iter()
+                                .filter(|(thread_id, stack_trace)| {
+                                    stack_trace.frames.with(|frames| {
+                                        thread_matches(*thread_id, frames, &filter)
+                                    })
+                                })
+                                .map(|(thread_id, stack_trace)| {
+                                    (dap.clone(), thread_id, stack_trace)
+                                })
+                                .collect::<Vec<_>>();
+                            traces
+                                .sort_by_key(|(_, id, _)| main_thread != Some(*id));
+                            traces
+                        } else {
+                            Vec::new()
+                        }
+                    },
+                    |(dap, thread_id, _)| {
+                        (dap.dap_id, *thread_id, dap.stopped.get_untracked())
+                    },
+                    move |(dap, thread_id, stack_trace)| {
+                        debug_stack_frames(
+                            dap,
+                            thread_id,
+                            stack_trace,
+                            debug.clone(),
+                            internal_command,
+                            config,
+                        )
+                    },
+                )
+                .style(|s| s.flex_col().min_width_pct(100.0))
+            })
+            .style(|s| s.absolute().size_pct(100.0, 100.0)),
+        )
+        .style(|s| s.width_pct(100.0).flex_grow(1.0).flex_basis(0.0)),
+    ))
     .style(|s| {
-        s.width_pct(100.0)
+        s.flex_col()
+            .width_pct(100.0)
             .line_height(1.6)
             .flex_grow(1.0)
             .flex_basis(0.0)
     })
 }
 
+/// The data breakpoints of the active debug session
+fn data_breakpoints_view(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    dyn_stack(
+        move || {
+            terminal
+                .get_active_dap(true)
+                .map(|dap| {
+                    dap.data_breakpoints
+                        .get()
+                        .into_iter()
+                        .map(|b| (dap.clone(), b))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        },
+        |(dap, b)| (dap.dap_id, b.data_id.clone(), b.verified),
+        move |(dap, b)| {
+            let data_id = b.data_id.clone();
+            stack((
+                clickable_icon(
+                    move || LapceIcons::CLOSE,
+                    move || {
+                        dap.remove_data_breakpoint(&data_id);
+                    },
+                    || false,
+                    || false,
+                    || "Remove",
+                    config,
+                )
+                .on_event_stop(EventListener::PointerDown, |_| {}),
+                text(b.description).style(move |s| {
+                    s.text_ellipsis()
+                        .min_width(0.0)
+                        .margin_left(6.0)
+                        .apply_if(!b.verified, |s| {
+                            s.color(config.get().color(LapceColor::EDITOR_DIM))
+                        })
+                }),
+            ))
+            .style(move |s| {
+                s.items_center()
+                    .padding_horiz(10.0)
+                    .width_pct(100.0)
+                    .hover(|s| {
+                        s.background(
+                            config.get().color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+            })
+        },
+    )
+    .style(|s| s.flex_col().line_height(1.6).width_pct(100.0))
+}
+
 fn breakpoints_view(window_tab_data: Rc<WindowTabData>) -> impl View {
-    let breakpoints = window_tab_data.terminal.debug.breakpoints;
+    let debug = window_tab_data.terminal.debug.clone();
+    let proxy = window_tab_data.common.proxy.clone();
+    let breakpoints = debug.breakpoints;
     let config = window_tab_data.common.config;
     let workspace = window_tab_data.common.workspace.clone();
     let available_width = create_rw_signal(0.0);
     let internal_command = window_tab_data.common.internal_command;
+    let terminal = window_tab_data.terminal.clone();
     container(
         scroll(
-            dyn_stack(
-                move || {
-                    breakpoints
-                        .get()
-                        .into_iter()
-                        .flat_map(|(path, breakpoints)| {
-                            breakpoints.into_values().map(move |b| (path.clone(), b))
-                        })
-                },
-                move |(path, breakpoint)| {
-                    (path.clone(), breakpoint.line, breakpoint.active)
-                },
-                move |(path, breakpoint)| {
-                    let line = breakpoint.line;
-                    let full_path = path.clone();
-                    let full_path_for_jump = path.clone();
-                    let full_path_for_close = path.clone();
-                    let path = if let Some(workspace_path) = workspace.path.as_ref()
-                    {
-                        path.strip_prefix(workspace_path)
-                            .unwrap_or(&full_path)
-                            .to_path_buf()
-                    } else {
-                        path
-                    };
-
-                    let file_name =
-                        path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-                    let folder =
-                        path.parent().and_then(|s| s.to_str()).unwrap_or("");
-                    let folder_empty = folder.is_empty();
-
-                    stack((
-                        clickable_icon(
-                            move || LapceIcons::CLOSE,
-                            move || {
-                                breakpoints.update(|breakpoints| {
-                                    if let Some(breakpoints) =
-                                        breakpoints.get_mut(&full_path_for_close)
-                                    {
-                                        breakpoints.remove(&line);
-                                    }
-                                });
+            stack((
+                data_breakpoints_view(terminal, config),
+                dyn_stack(
+                    move || {
+                        breakpoints.get().into_iter().flat_map(
+                            |(path, breakpoints)| {
+                                breakpoints
+                                    .into_values()
+                                    .map(move |b| (path.clone(), b))
                             },
-                            || false,
-                            || false,
-                            || "Remove",
-                            config,
                         )
-                        .on_event_stop(EventListener::PointerDown, |_| {}),
-                        checkbox(move || breakpoint.active, config)
-                            .style(|s| {
-                                s.margin_right(6.0).cursor(CursorStyle::Pointer)
-                            })
-                            .on_click_stop(move |_| {
-                                breakpoints.update(|breakpoints| {
-                                    if let Some(breakpoints) =
-                                        breakpoints.get_mut(&full_path)
-                                    {
-                                        if let Some(breakpoint) =
-                                            breakpoints.get_mut(&line)
+                    },
+                    move |(path, breakpoint)| {
+                        (path.clone(), breakpoint.line, breakpoint.active)
+                    },
+                    move |(path, breakpoint)| {
+                        let line = breakpoint.line;
+                        let close_debug = debug.clone();
+                        let close_proxy = proxy.clone();
+                        let debug = debug.clone();
+                        let proxy = proxy.clone();
+                        let full_path = path.clone();
+                        let full_path_for_jump = path.clone();
+                        let full_path_for_close = path.clone();
+                        let path =
+                            if let Some(workspace_path) = workspace.path.as_ref() {
+                                path.strip_prefix(workspace_path)
+                                    .unwrap_or(&full_path)
+                                    .to_path_buf()
+                            } else {
+                                path
+                            };
+
+                        let file_name =
+                            path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+                        let folder =
+                            path.parent().and_then(|s| s.to_str()).unwrap_or("");
+                        let folder_empty = folder.is_empty();
+
+                        stack((
+                            clickable_icon(
+                                move || LapceIcons::CLOSE,
+                                move || {
+                                    breakpoints.update(|breakpoints| {
+                                        if let Some(breakpoints) =
+                                            breakpoints.get_mut(&full_path_for_close)
                                         {
-                                            breakpoint.active = !breakpoint.active;
+                                            breakpoints.remove(&line);
                                         }
-                                    }
-                                });
+                                    });
+                                    close_debug.sync_breakpoints(
+                                        &close_proxy,
+                                        &full_path_for_close,
+                                    );
+                                },
+                                || false,
+                                || false,
+                                || "Remove",
+                                config,
+                            )
+                            .on_event_stop(EventListener::PointerDown, |_| {}),
+                            checkbox(move || breakpoint.active, config)
+                                .style(|s| {
+                                    s.margin_right(6.0).cursor(CursorStyle::Pointer)
+                                })
+                                .on_click_stop(move |_| {
+                                    breakpoints.update(|breakpoints| {
+                                        if let Some(breakpoints) =
+                                            breakpoints.get_mut(&full_path)
+                                        {
+                                            if let Some(breakpoint) =
+                                                breakpoints.get_mut(&line)
+                                            {
+                                                breakpoint.active =
+                                                    !breakpoint.active;
+                                            }
+                                        }
+                                    });
+                                    debug.sync_breakpoints(&proxy, &full_path);
+                                }),
+                            text(format!("{file_name}:{}", breakpoint.line + 1))
+                                .style(move |s| {
+                                    let size = config.get().ui.icon_size() as f32;
+                                    s.text_ellipsis().max_width(
+                                        available_width.get() as f32
+                                            - 20.0
+                                            - size
+                                            - 6.0
+                                            - size
+                                            - 8.0,
+                                    )
+                                }),
+                            text(folder).style(move |s| {
+                                s.text_ellipsis()
+                                    .flex_grow(1.0)
+                                    .flex_basis(0.0)
+                                    .color(
+                                        config.get().color(LapceColor::EDITOR_DIM),
+                                    )
+                                    .min_width(0.0)
+                                    .margin_left(6.0)
+                                    .apply_if(folder_empty, |s| s.hide())
                             }),
-                        text(format!("{file_name}:{}", breakpoint.line + 1)).style(
-                            move |s| {
-                                let size = config.get().ui.icon_size() as f32;
-                                s.text_ellipsis().max_width(
-                                    available_width.get() as f32
-                                        - 20.0
-                                        - size
-                                        - 6.0
-                                        - size
-                                        - 8.0,
-                                )
-                            },
-                        ),
-                        text(folder).style(move |s| {
-                            s.text_ellipsis()
-                                .flex_grow(1.0)
-                                .flex_basis(0.0)
-                                .color(config.get().color(LapceColor::EDITOR_DIM))
-                                .min_width(0.0)
-                                .margin_left(6.0)
-                                .apply_if(folder_empty, |s| s.hide())
-                        }),
-                    ))
-                    .style(move |s| {
-                        s.items_center().padding_horiz(10.0).width_pct(100.0).hover(
-                            |s| {
-                                s.background(
-                                    config
-                                        .get()
-                                        .color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                                )
-                            },
-                        )
-                    })
-                    .on_click_stop(move |_| {
-                        internal_command.send(InternalCommand::JumpToLocation {
-                            location: EditorLocation {
-                                path: full_path_for_jump.clone(),
-                                position: Some(EditorPosition::Line(line)),
-                                scroll_offset: None,
-                                ignore_unconfirmed: false,
-                                same_editor_tab: false,
-                            },
-                        });
-                    })
-                },
-            )
-            .style(|s| s.flex_col().line_height(1.6).width_pct(100.0)),
+                        ))
+                        .style(move |s| {
+                            s.items_center()
+                                .padding_horiz(10.0)
+                                .width_pct(100.0)
+                                .hover(|s| {
+                                    s.background(
+                                        config.get().color(
+                                            LapceColor::PANEL_HOVERED_BACKGROUND,
+                                        ),
+                                    )
+                                })
+                        })
+                        .on_click_stop(move |_| {
+                            internal_command.send(InternalCommand::JumpToLocation {
+                                location: EditorLocation {
+                                    path: full_path_for_jump.clone(),
+                                    position: Some(EditorPosition::Line(line)),
+                                    scroll_offset: None,
+                                    ignore_unconfirmed: false,
+                                    same_editor_tab: false,
+                                },
+                            });
+                        })
+                    },
+                )
+                .style(|s| s.flex_col().line_height(1.6).width_pct(100.0)),
+            ))
+            .style(|s| s.flex_col().width_pct(100.0)),
         )
         .on_resize(move |rect| {
             let width = rect.width();
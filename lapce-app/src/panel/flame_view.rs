@@ -0,0 +1,239 @@
+use std::{
+    hash::{Hash, Hasher},
+    rc::Rc,
+    sync::Arc,
+};
+
+use floem::{
+    action::show_context_menu,
+    event::EventListener,
+    menu::{Menu, MenuItem},
+    peniko::Color,
+    reactive::{create_memo, create_rw_signal, Memo, ReadSignal, RwSignal},
+    style::CursorStyle,
+    views::{dyn_stack, label, scroll, stack, Decorators},
+    View,
+};
+
+use crate::{
+    config::{color::LapceColor, LapceConfig},
+    flame::{flame_rects, frame_location, FlameData, FlameRect},
+    window_tab::WindowTabData,
+};
+
+/// The frames narrower than this many pixels aren't drawn
+const MIN_FRAME_WIDTH: f64 = 2.0;
+
+pub fn flame_panel(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let flame = window_tab_data.flame.clone();
+
+    stack((toolbar(flame.clone(), config), graph(flame, config))).style(move |s| {
+        s.flex_col()
+            .size_full()
+            .background(config.get().color(LapceColor::PANEL_BACKGROUND))
+    })
+}
+
+fn toolbar(flame: FlameData, config: ReadSignal<Arc<LapceConfig>>) -> impl View {
+    let graph = flame.graph;
+    let zoom = flame.zoom;
+    let hovered = flame.hovered;
+    let error = flame.error;
+
+    let button = move |name: &'static str, action: Box<dyn Fn()>| {
+        label(move || name.to_string())
+            .on_click_stop(move |_| action())
+            .style(move |s| {
+                let config = config.get();
+                s.padding_horiz(8.0)
+                    .margin_right(6.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            })
+    };
+
+    let status = move || {
+        if let Some(error) = error.get() {
+            return error;
+        }
+        let Some(graph) = graph.get() else {
+            return "Open a collapsed stack file or the output of `perf script`"
+                .to_string();
+        };
+        let total = graph.root.value.max(1) as f64;
+        match hovered.get() {
+            Some(rect) => format!(
+                "{} ({} samples, {:.2}%)",
+                rect.name,
+                rect.value,
+                rect.value as f64 * 100.0 / total
+            ),
+            None => {
+                format!("{} ({} samples)", graph.path.display(), graph.root.value)
+            }
+        }
+    };
+
+    stack((
+        button(
+            "Open Profile",
+            Box::new({
+                let flame = flame.clone();
+                move || flame.open()
+            }),
+        ),
+        button("Reset Zoom", Box::new(move || zoom.set(Vec::new()))).style(
+            move |s| s.apply_if(zoom.with(|zoom| zoom.is_empty()), |s| s.hide()),
+        ),
+        label(status).style(move |s| {
+            let config = config.get();
+            s.min_width(0.0)
+                .text_ellipsis()
+                .color(config.color(LapceColor::EDITOR_DIM))
+                .apply_if(error.with(|e| e.is_some()), |s| {
+                    s.color(config.color(LapceColor::LAPCE_ERROR))
+                })
+        }),
+    ))
+    .style(move |s| {
+        s.width_full()
+            .items_center()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .border_bottom(1.0)
+            .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+    })
+}
+
+fn graph(flame: FlameData, config: ReadSignal<Arc<LapceConfig>>) -> impl View {
+    let graph = flame.graph;
+    let zoom = flame.zoom;
+    let hovered = flame.hovered;
+    let width = create_rw_signal(0.0);
+    let row_height = create_memo(move |_| config.get().ui.font_size() as f64 + 8.0);
+
+    let rects = create_memo(move |_| {
+        let width = width.get();
+        let zoom = zoom.get();
+        graph.with(|graph| {
+            graph.as_ref().map_or_else(Vec::new, |graph| {
+                flame_rects(&graph.root, &zoom, MIN_FRAME_WIDTH / width.max(1.0))
+            })
+        })
+    });
+    let depth = create_memo(move |_| {
+        rects.with(|rects| rects.iter().map(|r| r.depth + 1).max().unwrap_or(0))
+    });
+
+    scroll(
+        dyn_stack(
+            move || rects.get(),
+            |rect| {
+                (
+                    rect.path.clone(),
+                    rect.start.to_bits(),
+                    rect.width.to_bits(),
+                )
+            },
+            move |rect| frame(flame.clone(), rect, width, row_height, config),
+        )
+        .style(move |s| s.width_full().height(depth.get() as f64 * row_height.get()))
+        .on_event_cont(EventListener::PointerLeave, move |_| {
+            hovered.set(None);
+        }),
+    )
+    .on_resize(move |rect| {
+        width.set(rect.width());
+    })
+    .style(|s| s.width_full().flex_grow(1.0).flex_basis(0.0))
+}
+
+fn frame(
+    flame: FlameData,
+    rect: FlameRect,
+    width: RwSignal<f64>,
+    row_height: Memo<f64>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let hovered = flame.hovered;
+    let zoom = flame.zoom;
+    let name = rect.name.clone();
+    let has_source = frame_location(&name).is_some();
+    let background = frame_color(&name);
+    let path = rect.path.clone();
+    let hovered_rect = rect.clone();
+
+    label(move || name.clone())
+        .on_event_stop(EventListener::PointerEnter, move |_| {
+            hovered.set(Some(hovered_rect.clone()));
+        })
+        .on_click_stop(move |_| {
+            zoom.set(path.clone());
+        })
+        .on_double_click_stop({
+            let flame = flame.clone();
+            let name = rect.name.clone();
+            move |_| {
+                flame.jump_to_source(&name);
+            }
+        })
+        .on_secondary_click_stop({
+            let name = rect.name.clone();
+            move |_| {
+                if !has_source {
+                    return;
+                }
+                let flame = flame.clone();
+                let name = name.clone();
+                let menu = Menu::new("").entry(
+                    MenuItem::new("Go to Source").action(move || {
+                        flame.jump_to_source(&name);
+                    }),
+                );
+                show_context_menu(menu, None);
+            }
+        })
+        .style(move |s| {
+            let width = width.get();
+            let row_height = row_height.get();
+            let config = config.get();
+            s.absolute()
+                .inset_left(rect.start * width)
+                .inset_top(rect.depth as f64 * row_height)
+                .width(rect.width * width - 1.0)
+                .height(row_height - 1.0)
+                .padding_horiz(3.0)
+                .items_center()
+                .text_ellipsis()
+                .font_size(config.ui.font_size() as f32 - 1.0)
+                .color(Color::BLACK)
+                .background(background)
+                .hover(|s| {
+                    s.cursor(CursorStyle::Pointer)
+                        .border(1.0)
+                        .border_color(config.color(LapceColor::EDITOR_FOCUS))
+                })
+                .apply_if(has_source, |s| s.font_bold())
+                .selectable(false)
+        })
+}
+
+/// A warm color picked by the name of a frame, so that the same function has
+/// the same color wherever it is
+fn frame_color(name: &str) -> Color {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    name.hash(&mut hasher);
+    let hash = hasher.finish();
+    let r = 205 + (hash % 50) as u8;
+    let g = ((hash >> 8) % 180) as u8;
+    let b = ((hash >> 16) % 55) as u8;
+    Color::rgb8(r, g, b)
+}
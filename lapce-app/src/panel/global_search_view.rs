@@ -117,6 +117,7 @@ fn search_result(
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
     let ui_line_height = global_search_data.common.ui_line_height;
+    let search_results = global_search_data.search_result;
     container({
         scroll({
             virtual_stack(
@@ -301,6 +302,24 @@ fn search_result(
                                                 },
                                             },
                                         );
+                                        // The file of the next results is
+                                        // likely to be jumped to next
+                                        let next = search_results.with_untracked(
+                                            |results| {
+                                                let index =
+                                                    results.get_index_of(&path)?;
+                                                results
+                                                    .get_index(index + 1)
+                                                    .map(|(path, _)| path.clone())
+                                            },
+                                        );
+                                        if let Some(path) = next {
+                                            internal_command.send(
+                                                InternalCommand::PrefetchDoc {
+                                                    path,
+                                                },
+                                            );
+                                        }
                                     },
                                 )
                             },
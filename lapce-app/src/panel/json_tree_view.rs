@@ -0,0 +1,149 @@
+use std::rc::Rc;
+
+use floem::{
+    peniko::Color,
+    reactive::{create_effect, create_memo},
+    style::CursorStyle,
+    taffy::AlignItems,
+    views::{
+        label, scroll, stack, svg, virtual_stack, Decorators, VirtualDirection,
+        VirtualItemSize,
+    },
+    View,
+};
+
+use crate::{
+    config::{color::LapceColor, icon::LapceIcons},
+    json_tree::visible_rows,
+    window_tab::{Focus, WindowTabData},
+};
+
+pub fn json_tree_panel(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let focus = window_tab_data.common.focus;
+    let ui_line_height = window_tab_data.common.ui_line_height;
+    let active_editor = window_tab_data.main_split.active_editor;
+    let json_tree = window_tab_data.json_tree.clone();
+    let expanded = json_tree.expanded;
+
+    let tree = move || {
+        let editor = active_editor.get()?;
+        editor.doc_signal().with(|doc| doc.json_tree.get())
+    };
+    // The node at the cursor of the active editor
+    let cursor_path = create_memo(move |_| {
+        let editor = active_editor.get()?;
+        let tree = tree()?;
+        let root = tree.root.as_ref()?;
+        let offset = editor.cursor().with(|cursor| cursor.offset());
+        Some(root.path_at(offset))
+    });
+    {
+        let json_tree = json_tree.clone();
+        create_effect(move |_| {
+            if let Some(path) = cursor_path.get() {
+                json_tree.reveal(&path);
+            }
+        });
+    }
+    let rows = create_memo(move |_| {
+        let Some(root) = tree().and_then(|tree| tree.root.clone()) else {
+            return im::Vector::new();
+        };
+        expanded.with(|expanded| visible_rows(&root, expanded).into())
+    });
+
+    let status = label(move || match tree() {
+        None => "The active editor isn't a JSON document".to_string(),
+        Some(tree) => tree.error.clone().unwrap_or_default(),
+    })
+    .style(move |s| {
+        let config = config.get();
+        let tree = tree();
+        let is_error = tree.as_ref().is_some_and(|tree| tree.error.is_some());
+        s.padding_horiz(10.0)
+            .padding_vert(4.0)
+            .apply_if(is_error, |s| s.color(config.color(LapceColor::LAPCE_ERROR)))
+            .apply_if(!is_error, |s| s.color(config.color(LapceColor::EDITOR_DIM)))
+            .apply_if(tree.is_some_and(|tree| tree.error.is_none()), |s| s.hide())
+    });
+
+    let list = scroll(
+        virtual_stack(
+            VirtualDirection::Vertical,
+            VirtualItemSize::Fixed(Box::new(move || ui_line_height.get())),
+            move || rows.get(),
+            |row| (row.path.clone(), row.label.clone(), row.range.clone()),
+            move |row| {
+                let level = row.path.len();
+                let has_children = row.has_children;
+                let range = row.range.clone();
+                let json_tree = json_tree.clone();
+                let toggle_path = row.path.clone();
+                let path = row.path.clone();
+                let text = row.label.clone();
+                stack((
+                    svg(move || {
+                        let open =
+                            expanded.with(|expanded| expanded.contains(&path));
+                        config.get().ui_svg(if open {
+                            LapceIcons::ITEM_OPENED
+                        } else {
+                            LapceIcons::ITEM_CLOSED
+                        })
+                    })
+                    .on_click_stop(move |_| {
+                        if has_children {
+                            json_tree.toggle(&toggle_path);
+                        }
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        let color = if has_children {
+                            config.color(LapceColor::LAPCE_ICON_ACTIVE)
+                        } else {
+                            Color::TRANSPARENT
+                        };
+                        s.size(size, size).flex_shrink(0.0).color(color)
+                    }),
+                    label(move || text.clone()).style(|s| {
+                        s.margin_left(6.0).text_ellipsis().selectable(false)
+                    }),
+                ))
+                .on_click_stop(move |_| {
+                    if let Some(editor) = active_editor.get_untracked() {
+                        editor.select_range(range.start, range.end);
+                        focus.set(Focus::Workbench);
+                    }
+                })
+                .style(move |s| {
+                    let config = config.get();
+                    let is_current = cursor_path
+                        .with(|p| p.as_ref().is_some_and(|p| *p == row.path));
+                    s.padding_left((10 + level * 10) as f32)
+                        .padding_right(10.0)
+                        .height(ui_line_height.get())
+                        .align_items(AlignItems::Center)
+                        .apply_if(is_current, |s| {
+                            s.background(
+                                config.color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                            )
+                        })
+                        .hover(|s| {
+                            s.background(
+                                config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                            .cursor(CursorStyle::Pointer)
+                        })
+                })
+            },
+        )
+        .style(|s| s.flex_col().align_items(AlignItems::Stretch).width_full()),
+    )
+    .style(|s| s.flex_grow(1.0).flex_basis(0.0).width_full());
+
+    stack((status, list))
+        .style(|s| s.flex_col().size_full())
+        .debug_name("JSON Tree Panel")
+}
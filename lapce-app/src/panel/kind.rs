@@ -15,6 +15,12 @@ pub enum PanelKind {
     Search,
     Problem,
     Debug,
+    DebugConsole,
+    Flame,
+    Output,
+    JsonTree,
+    SyntaxTree,
+    Notifications,
 }
 
 impl PanelKind {
@@ -27,6 +33,12 @@ pub fn svg_name(&self) -> &'static str {
             PanelKind::Search => LapceIcons::SEARCH,
             PanelKind::Problem => LapceIcons::PROBLEM,
             PanelKind::Debug => LapceIcons::DEBUG,
+            PanelKind::DebugConsole => LapceIcons::DEBUG_CONSOLE,
+            PanelKind::Flame => LapceIcons::FLAME,
+            PanelKind::Output => LapceIcons::OUTPUT,
+            PanelKind::JsonTree => LapceIcons::JSON_TREE,
+            PanelKind::SyntaxTree => LapceIcons::SYNTAX_TREE,
+            PanelKind::Notifications => LapceIcons::NOTIFICATIONS,
         }
     }
 
@@ -0,0 +1,216 @@
+use std::{rc::Rc, sync::Arc};
+
+use floem::{
+    cosmic_text::Weight,
+    reactive::ReadSignal,
+    style::CursorStyle,
+    views::{container, dyn_stack, label, scroll, stack, svg, text, Decorators},
+    View,
+};
+use lsp_types::MessageType;
+
+use crate::{
+    command::InternalCommand,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    listener::Listener,
+    notification::{Notification, NotificationFilter},
+    window_tab::WindowTabData,
+};
+
+pub fn notification_panel(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let notifications = window_tab_data.common.notifications;
+
+    let button = move |text: &'static str,
+                       active: Box<dyn Fn() -> bool>,
+                       on_click: Box<dyn Fn()>| {
+        label(move || text.to_string())
+            .on_click_stop(move |_| on_click())
+            .style(move |s| {
+                let config = config.get();
+                s.padding_horiz(8.0)
+                    .margin_right(6.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .apply_if(active(), |s| {
+                        s.background(
+                            config.color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                        )
+                    })
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            })
+    };
+    let filter_button = move |text: &'static str, filter: NotificationFilter| {
+        button(
+            text,
+            Box::new(move || notifications.with(|n| n.filter == filter)),
+            Box::new(move || notifications.update(|n| n.filter = filter)),
+        )
+    };
+
+    let toolbar = stack((
+        filter_button("All", NotificationFilter::All),
+        filter_button("Warnings", NotificationFilter::Warnings),
+        filter_button("Errors", NotificationFilter::Errors),
+        button(
+            "Do Not Disturb",
+            Box::new(move || notifications.with(|n| n.do_not_disturb)),
+            Box::new(move || {
+                notifications.update(|n| n.set_do_not_disturb(!n.do_not_disturb))
+            }),
+        ),
+        button(
+            "Clear",
+            Box::new(|| false),
+            Box::new(move || notifications.update(|n| n.clear())),
+        ),
+        label(|| "No notifications".to_string()).style(move |s| {
+            s.margin_left(4.0)
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+                .apply_if(!notifications.with(|n| n.filtered().is_empty()), |s| {
+                    s.hide()
+                })
+        }),
+    ))
+    .style(move |s| {
+        s.width_full()
+            .items_center()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .border_bottom(1.0)
+            .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+    });
+
+    stack((
+        toolbar,
+        container(
+            scroll(
+                dyn_stack(
+                    move || notifications.with(|n| n.filtered()),
+                    |notification| notification.id,
+                    move |notification| {
+                        notification_view(
+                            &notification,
+                            internal_command,
+                            config,
+                            || {},
+                        )
+                        .style(move |s| {
+                            s.width_full()
+                                .padding_vert(6.0)
+                                .border_bottom(1.0)
+                                .border_color(
+                                    config.get().color(LapceColor::LAPCE_BORDER),
+                                )
+                        })
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full().padding_horiz(10.0)),
+            )
+            .style(|s| s.absolute().size_full()),
+        )
+        .style(|s| s.width_full().flex_grow(1.0).flex_basis(0.0)),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .size_full()
+            .background(config.get().color(LapceColor::PANEL_BACKGROUND))
+    })
+    .debug_name("Notifications Panel")
+}
+
+/// A notification with its severity, its title and the buttons of its
+/// actions, as listed in the panel and shown as a toast; `on_action` runs
+/// after any of the actions
+pub fn notification_view(
+    notification: &Notification,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+    on_action: impl Fn() + Clone + 'static,
+) -> impl View {
+    let typ = notification.typ;
+    let time = notification.time.format("%H:%M:%S").to_string();
+    let actions = notification.actions.clone();
+    let has_actions = !actions.is_empty();
+    stack((
+        svg(move || {
+            config.get().ui_svg(match typ {
+                MessageType::ERROR => LapceIcons::ERROR,
+                MessageType::WARNING => LapceIcons::WARNING,
+                _ => LapceIcons::INFO,
+            })
+        })
+        .style(move |s| {
+            let config = config.get();
+            let size = config.ui.icon_size() as f32;
+            let color = match typ {
+                MessageType::ERROR => config.color(LapceColor::LAPCE_ERROR),
+                MessageType::WARNING => config.color(LapceColor::LAPCE_WARN),
+                _ => config.color(LapceColor::LAPCE_ICON_ACTIVE),
+            };
+            s.min_width(size)
+                .size(size, size)
+                .margin_right(10.0)
+                .margin_top(4.0)
+                .color(color)
+        }),
+        stack((
+            stack((
+                text(notification.title.clone()).style(|s| {
+                    s.min_width(0.0).line_height(1.6).font_weight(Weight::BOLD)
+                }),
+                text(time).style(move |s| {
+                    s.margin_left(8.0)
+                        .line_height(1.6)
+                        .color(config.get().color(LapceColor::EDITOR_DIM))
+                }),
+            ))
+            .style(|s| s.items_center()),
+            text(notification.message.clone())
+                .style(|s| s.min_width(0.0).line_height(1.6).margin_top(5.0)),
+            dyn_stack(
+                move || actions.clone(),
+                |action| action.clone(),
+                move |action| {
+                    let on_action = on_action.clone();
+                    let text = action.label();
+                    label(move || text.to_string())
+                        .on_click_stop(move |_| {
+                            internal_command.send(
+                                InternalCommand::RunNotificationAction {
+                                    action: action.clone(),
+                                },
+                            );
+                            on_action();
+                        })
+                        .style(move |s| {
+                            let config = config.get();
+                            s.padding_horiz(8.0)
+                                .margin_right(6.0)
+                                .border(1.0)
+                                .border_radius(4.0)
+                                .border_color(config.color(LapceColor::LAPCE_BORDER))
+                                .hover(|s| {
+                                    s.cursor(CursorStyle::Pointer).background(
+                                        config.color(
+                                            LapceColor::PANEL_HOVERED_BACKGROUND,
+                                        ),
+                                    )
+                                })
+                                .selectable(false)
+                        })
+                },
+            )
+            .style(move |s| s.margin_top(6.0).apply_if(!has_actions, |s| s.hide())),
+        ))
+        .style(|s| s.flex_col().min_width(0.0).flex_basis(0.0).flex_grow(1.0)),
+    ))
+    .style(|s| s.items_start())
+}
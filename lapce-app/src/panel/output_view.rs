@@ -0,0 +1,104 @@
+use std::rc::Rc;
+
+use floem::{
+    kurbo::Point,
+    reactive::create_memo,
+    style::CursorStyle,
+    views::{container, dyn_stack, label, scroll, stack, Decorators},
+    View,
+};
+
+use crate::{
+    config::color::LapceColor, rich_output::rich_output_view,
+    window_tab::WindowTabData,
+};
+
+pub fn output_panel(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let output = window_tab_data.output.clone();
+    let entries = output.entries;
+    let line_height =
+        create_memo(move |_| config.get().terminal_line_height() as f64);
+
+    let toolbar = stack((
+        label(|| "Clear".to_string())
+            .on_click_stop(move |_| {
+                output.clear();
+            })
+            .style(move |s| {
+                let config = config.get();
+                s.padding_horiz(8.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(config.color(LapceColor::LAPCE_BORDER))
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            }),
+        label(|| "No output from the plugins yet".to_string()).style(move |s| {
+            s.margin_left(10.0)
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+                .apply_if(!entries.with(|entries| entries.is_empty()), |s| s.hide())
+        }),
+    ))
+    .style(move |s| {
+        s.width_full()
+            .items_center()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .border_bottom(1.0)
+            .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+    });
+
+    stack((
+        toolbar,
+        container(
+            scroll(
+                dyn_stack(
+                    move || entries.get(),
+                    |entry| entry.id,
+                    move |entry| {
+                        let source = entry.source.clone();
+                        stack((
+                            label(move || source.clone()).style(move |s| {
+                                s.font_size(config.get().ui.font_size() as f32 - 1.0)
+                                    .color(
+                                        config.get().color(LapceColor::EDITOR_DIM),
+                                    )
+                            }),
+                            rich_output_view(
+                                entry.output,
+                                line_height.get_untracked(),
+                                config,
+                            ),
+                        ))
+                        .style(move |s| {
+                            s.flex_col()
+                                .width_full()
+                                .padding_vert(4.0)
+                                .border_bottom(1.0)
+                                .border_color(
+                                    config.get().color(LapceColor::LAPCE_BORDER),
+                                )
+                        })
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full().padding_horiz(10.0)),
+            )
+            .scroll_to(move || {
+                entries.track();
+                Some(Point::new(0.0, f64::MAX))
+            })
+            .style(|s| s.absolute().size_full()),
+        )
+        .style(|s| s.width_full().flex_grow(1.0).flex_basis(0.0)),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .size_full()
+            .background(config.get().color(LapceColor::PANEL_BACKGROUND))
+    })
+}
@@ -17,8 +17,8 @@
     editor::location::{EditorLocation, EditorPosition},
     listener::Listener,
     lsp::path_from_url,
+    main_split::MainSplitData,
     window_tab::WindowTabData,
-    workspace::LapceWorkspace,
 };
 
 pub fn problem_panel(
@@ -61,7 +61,7 @@ fn problem_section(
                 |(p, _)| p.clone(),
                 move |(path, diagnostic_data)| {
                     file_view(
-                        main_split.common.workspace.clone(),
+                        main_split.clone(),
                         path,
                         diagnostic_data,
                         severity,
@@ -78,7 +78,7 @@ fn problem_section(
 }
 
 fn file_view(
-    workspace: Arc<LapceWorkspace>,
+    main_split: MainSplitData,
     path: PathBuf,
     diagnostic_data: DiagnosticData,
     severity: DiagnosticSeverity,
@@ -124,6 +124,7 @@ fn file_view(
     });
 
     let full_path = path.clone();
+    let workspace = main_split.common.workspace.clone();
     let path = if let Some(workspace_path) = workspace.path.as_ref() {
         path.strip_prefix(workspace_path)
             .unwrap_or(&full_path)
@@ -230,8 +231,10 @@ fn file_view(
             |d| (d.range, d.diagnostic.range),
             move |d| {
                 item_view(
+                    main_split.clone(),
                     full_path.clone(),
                     d,
+                    severity,
                     icon,
                     icon_color,
                     internal_command,
@@ -250,8 +253,10 @@ fn file_view(
 }
 
 fn item_view(
+    main_split: MainSplitData,
     path: PathBuf,
     d: EditorDiagnostic,
+    severity: DiagnosticSeverity,
     icon: &'static str,
     icon_color: impl Fn() -> Color + 'static,
     internal_command: Listener<InternalCommand>,
@@ -264,7 +269,7 @@ fn item_view(
         EditorPosition::Position(d.diagnostic.range.start)
     };
     let location = EditorLocation {
-        path,
+        path: path.clone(),
         position: Some(position),
         scroll_offset: None,
         ignore_unconfirmed: false,
@@ -307,6 +312,7 @@ fn item_view(
             internal_command.send(InternalCommand::JumpToLocation {
                 location: location.clone(),
             });
+            main_split.prefetch_next_problem(&path, severity);
         })
         .style(|s| s.width_pct(100.0).min_width_pct(0.0)),
         related_view(related, internal_command, config),
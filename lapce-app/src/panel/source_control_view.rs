@@ -8,9 +8,8 @@
     reactive::{create_memo, create_rw_signal},
     style::{CursorStyle, Style},
     views::{
-        container, dyn_stack,
-        editor::view::{cursor_caret, LineRegion},
-        label, scroll, stack, svg, text, Decorators,
+        container, dyn_stack, editor::view::LineRegion, label, scroll, stack, svg,
+        text, Decorators,
     },
     View,
 };
@@ -24,7 +23,7 @@
 use crate::{
     command::{CommandKind, InternalCommand, LapceCommand, LapceWorkbenchCommand},
     config::{color::LapceColor, icon::LapceIcons},
-    editor::view::editor_view,
+    editor::view::{caret_region, editor_view},
     settings::checkbox,
     source_control::SourceControlData,
     window_tab::{Focus, WindowTabData},
@@ -120,7 +119,7 @@ pub fn source_control_panel(
                     let e_data = editor.get_untracked();
                     e_data.doc_signal().track();
                     e_data.kind.track();
-                    let LineRegion { x, width, rvline } = cursor_caret(
+                    let LineRegion { x, width, rvline } = caret_region(
                         &e_data.editor,
                         offset,
                         !cursor.is_insert(),
@@ -0,0 +1,257 @@
+use std::rc::Rc;
+
+use floem::{
+    peniko::Color,
+    reactive::{create_effect, create_memo, Scope},
+    style::CursorStyle,
+    taffy::AlignItems,
+    views::{
+        container, label, scroll, stack, svg, virtual_stack, Decorators,
+        VirtualDirection, VirtualItemSize,
+    },
+    View,
+};
+
+use crate::{
+    config::{color::LapceColor, icon::LapceIcons},
+    syntax_tree::{capture_label, node_label},
+    text_input::TextInputBuilder,
+    window_tab::{Focus, WindowTabData},
+};
+
+pub fn syntax_tree_panel(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let focus = window_tab_data.common.focus;
+    let ui_line_height = window_tab_data.common.ui_line_height;
+    let active_editor = window_tab_data.main_split.active_editor;
+    let syntax_tree = window_tab_data.syntax_tree.clone();
+    let expanded = syntax_tree.expanded;
+
+    let syntax = move || {
+        let editor = active_editor.get()?;
+        Some(editor.doc_signal().with(|doc| doc.syntax()))
+    };
+    // The node at the cursor of the active editor
+    let cursor_path = create_memo(move |_| {
+        let editor = active_editor.get()?;
+        let syntax = syntax()?;
+        let offset = editor.cursor().with(|cursor| cursor.offset());
+        Some(syntax.with(|syntax| syntax.tree_path_at(offset)))
+    });
+    {
+        let syntax_tree = syntax_tree.clone();
+        create_effect(move |_| {
+            if let Some(path) = cursor_path.get() {
+                syntax_tree.reveal(&path);
+            }
+        });
+    }
+    let rows = create_memo(move |_| {
+        let Some(syntax) = syntax() else {
+            return im::Vector::new();
+        };
+        expanded
+            .with(|expanded| syntax.with(|syntax| syntax.tree_rows(expanded).into()))
+    });
+
+    let status = label(move || {
+        if syntax().is_none() {
+            "There's no active editor".to_string()
+        } else {
+            "The document has no syntax tree".to_string()
+        }
+    })
+    .style(move |s| {
+        let config = config.get();
+        s.padding_horiz(10.0)
+            .padding_vert(4.0)
+            .color(config.color(LapceColor::EDITOR_DIM))
+            .apply_if(!rows.with(|rows| rows.is_empty()), |s| s.hide())
+    });
+
+    let tree = scroll(
+        virtual_stack(
+            VirtualDirection::Vertical,
+            VirtualItemSize::Fixed(Box::new(move || ui_line_height.get())),
+            move || rows.get(),
+            |row| (row.path.clone(), row.kind, row.range.clone()),
+            move |row| {
+                let level = row.path.len();
+                let has_children = row.has_children;
+                let range = row.range.clone();
+                let syntax_tree = syntax_tree.clone();
+                let toggle_path = row.path.clone();
+                let path = row.path.clone();
+                let text = node_label(&row);
+                stack((
+                    svg(move || {
+                        let open =
+                            expanded.with(|expanded| expanded.contains(&path));
+                        config.get().ui_svg(if open {
+                            LapceIcons::ITEM_OPENED
+                        } else {
+                            LapceIcons::ITEM_CLOSED
+                        })
+                    })
+                    .on_click_stop(move |_| {
+                        if has_children {
+                            syntax_tree.toggle(&toggle_path);
+                        }
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        let color = if has_children {
+                            config.color(LapceColor::LAPCE_ICON_ACTIVE)
+                        } else {
+                            Color::TRANSPARENT
+                        };
+                        s.size(size, size).flex_shrink(0.0).color(color)
+                    }),
+                    label(move || text.clone()).style(|s| {
+                        s.margin_left(6.0).text_ellipsis().selectable(false)
+                    }),
+                ))
+                .on_click_stop(move |_| {
+                    if let Some(editor) = active_editor.get_untracked() {
+                        editor.select_range(range.start, range.end);
+                        focus.set(Focus::Workbench);
+                    }
+                })
+                .style(move |s| {
+                    let config = config.get();
+                    let is_current = cursor_path
+                        .with(|p| p.as_ref().is_some_and(|p| *p == row.path));
+                    s.padding_left((10 + level * 10) as f32)
+                        .padding_right(10.0)
+                        .height(ui_line_height.get())
+                        .align_items(AlignItems::Center)
+                        .apply_if(is_current, |s| {
+                            s.background(
+                                config.color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                            )
+                        })
+                        .hover(|s| {
+                            s.background(
+                                config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                            .cursor(CursorStyle::Pointer)
+                        })
+                })
+            },
+        )
+        .style(|s| s.flex_col().align_items(AlignItems::Stretch).width_full()),
+    )
+    .style(|s| s.flex_grow(1.0).flex_basis(0.0).width_full());
+
+    let cx = Scope::current();
+    let query_input = TextInputBuilder::new()
+        .build(
+            cx,
+            window_tab_data.main_split.editors,
+            window_tab_data.common.clone(),
+        )
+        .placeholder(|| "Query, such as (identifier) @name".to_string());
+    let query_doc = query_input.doc_signal();
+    // The captures of the query over the active document, or why it's
+    // invalid, `None` while there's no query
+    let captures = create_memo(move |_| {
+        let query = query_doc.get().buffer.with(|buffer| buffer.to_string());
+        if query.trim().is_empty() {
+            return None;
+        }
+        let syntax = syntax()?;
+        Some(
+            syntax
+                .with(|syntax| syntax.query_captures(&query))
+                .map(im::Vector::from),
+        )
+    });
+
+    let query_status = label(move || {
+        captures.with(|captures| match captures {
+            Some(Ok(captures)) if captures.is_empty() => {
+                "The query captures nothing".to_string()
+            }
+            Some(Ok(captures)) => format!("{} captures", captures.len()),
+            Some(Err(err)) => err.clone(),
+            None => String::new(),
+        })
+    })
+    .style(move |s| {
+        let config = config.get();
+        let is_error = captures.with(|c| matches!(c, Some(Err(_))));
+        s.padding_horiz(10.0)
+            .padding_vert(4.0)
+            .apply_if(is_error, |s| s.color(config.color(LapceColor::LAPCE_ERROR)))
+            .apply_if(!is_error, |s| s.color(config.color(LapceColor::EDITOR_DIM)))
+            .apply_if(captures.with(|c| c.is_none()), |s| s.hide())
+    });
+
+    let capture_list = scroll(
+        virtual_stack(
+            VirtualDirection::Vertical,
+            VirtualItemSize::Fixed(Box::new(move || ui_line_height.get())),
+            move || {
+                captures.with(|captures| match captures {
+                    Some(Ok(captures)) => captures.clone(),
+                    _ => im::Vector::new(),
+                })
+            },
+            |capture| (capture.name.clone(), capture.range.clone()),
+            move |capture| {
+                let range = capture.range.clone();
+                let text = capture_label(&capture);
+                label(move || text.clone())
+                    .on_click_stop(move |_| {
+                        if let Some(editor) = active_editor.get_untracked() {
+                            editor.select_range(range.start, range.end);
+                            focus.set(Focus::Workbench);
+                        }
+                    })
+                    .style(move |s| {
+                        let config = config.get();
+                        s.padding_horiz(10.0)
+                            .height(ui_line_height.get())
+                            .items_center()
+                            .text_ellipsis()
+                            .selectable(false)
+                            .hover(|s| {
+                                s.background(
+                                    config
+                                        .color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                                )
+                                .cursor(CursorStyle::Pointer)
+                            })
+                    })
+            },
+        )
+        .style(|s| s.flex_col().align_items(AlignItems::Stretch).width_full()),
+    )
+    .style(|s| s.flex_grow(1.0).flex_basis(0.0).width_full());
+
+    stack((
+        status,
+        tree,
+        container(query_input.style(move |s| {
+            let config = config.get();
+            s.width_pct(100.0)
+                .padding_horiz(6.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+        }))
+        .style(move |s| {
+            let config = config.get();
+            s.width_pct(100.0)
+                .padding_horiz(10.0)
+                .padding_vert(4.0)
+                .border_top(1.0)
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+        }),
+        query_status,
+        capture_list,
+    ))
+    .style(|s| s.flex_col().size_full())
+    .debug_name("Syntax Tree Panel")
+}
@@ -1,8 +1,10 @@
 use std::rc::Rc;
 
 use floem::{
+    action::show_context_menu,
     event::{Event, EventListener, EventPropagation},
     kurbo::Size,
+    menu::{Menu, MenuItem},
     reactive::create_rw_signal,
     views::{
         container, dyn_stack, empty, label,
@@ -11,6 +13,7 @@
     },
     View,
 };
+use itertools::Itertools;
 
 use super::kind::PanelKind;
 use crate::{
@@ -87,6 +90,10 @@ fn terminal_tab_header(window_tab_data: Rc<WindowTabData>) -> impl View {
                     }
                 };
 
+                let profile = {
+                    let tab = tab.clone();
+                    move || tab.active_terminal(true).and_then(|t| t.profile)
+                };
                 let svg_string = move || {
                     let terminal = tab.active_terminal(true);
                     let run_debug = terminal.as_ref().map(|t| t.run_debug);
@@ -105,8 +112,18 @@ fn terminal_tab_header(window_tab_data: Rc<WindowTabData>) -> impl View {
                             return svg;
                         }
                     }
-                    LapceIcons::TERMINAL
+                    match terminal.and_then(|t| t.profile) {
+                        Some(profile) => {
+                            config.get().terminal.profile_icon(&profile)
+                        }
+                        None => LapceIcons::TERMINAL,
+                    }
                 };
+                let profile_color = move || {
+                    let profile = profile()?;
+                    config.get().terminal.profile_color(&profile)
+                };
+                let icon_color = profile_color.clone();
                 stack((
                     container({
                         stack((
@@ -116,9 +133,11 @@ fn terminal_tab_header(window_tab_data: Rc<WindowTabData>) -> impl View {
                                         let config = config.get();
                                         let size = config.ui.icon_size() as f32;
                                         s.size(size, size).color(
-                                            config.color(
-                                                LapceColor::LAPCE_ICON_ACTIVE,
-                                            ),
+                                            icon_color().unwrap_or_else(|| {
+                                                config.color(
+                                                    LapceColor::LAPCE_ICON_ACTIVE,
+                                                )
+                                            }),
                                         )
                                     }),
                             )
@@ -152,9 +171,15 @@ fn terminal_tab_header(window_tab_data: Rc<WindowTabData>) -> impl View {
                             }),
                         ))
                         .style(move |s| {
-                            s.items_center().width(200.0).border_color(
-                                config.get().color(LapceColor::LAPCE_BORDER),
-                            )
+                            let color = profile_color();
+                            s.items_center()
+                                .width(200.0)
+                                .border_color(
+                                    config.get().color(LapceColor::LAPCE_BORDER),
+                                )
+                                .apply_opt(color, |s, color| {
+                                    s.border_top(2.0).border_color(color)
+                                })
                         })
                     })
                     .style(|s| s.items_center()),
@@ -213,15 +238,25 @@ fn terminal_tab_header(window_tab_data: Rc<WindowTabData>) -> impl View {
             let size = scroll_size.get();
             s.size(size.width, size.height)
         }),
-        container(clickable_icon(
-            || LapceIcons::ADD,
-            move || {
-                workbench_command.send(LapceWorkbenchCommand::NewTerminalTab);
-            },
-            || false,
-            || false,
-            || "New Terminal",
-            config,
+        stack((
+            clickable_icon(
+                || LapceIcons::ADD,
+                move || {
+                    workbench_command.send(LapceWorkbenchCommand::NewTerminalTab);
+                },
+                || false,
+                || false,
+                || "New Terminal",
+                config,
+            ),
+            clickable_icon(
+                || LapceIcons::DROPDOWN_ARROW,
+                move || profile_menu(window_tab_data.clone()),
+                || false,
+                || false,
+                || "New Terminal With Profile",
+                config,
+            ),
         ))
         .on_resize(move |rect| {
             let width = rect.size().width;
@@ -300,14 +335,14 @@ fn terminal_tab_split(
                 .style(|s| s.size_pct(100.0, 100.0))
             })
             .style(move |s| {
-                s.size_pct(100.0, 100.0).padding_horiz(10.0).apply_if(
-                    index.get() > 0,
-                    |s| {
+                s.size_pct(100.0, 100.0)
+                    .padding_left(2.0)
+                    .padding_right(10.0)
+                    .apply_if(index.get() > 0, |s| {
                         s.border_left(1.0).border_color(
                             config.get().color(LapceColor::LAPCE_BORDER),
                         )
-                    },
-                )
+                    })
             })
         },
     )
@@ -327,3 +362,31 @@ fn terminal_tab_content(window_tab_data: Rc<WindowTabData>) -> impl View {
     )
     .style(|s| s.size_pct(100.0, 100.0))
 }
+
+/// A menu of the terminal profiles to open a new terminal with.
+fn profile_menu(window_tab_data: Rc<WindowTabData>) {
+    let config = window_tab_data.common.config.get_untracked();
+    let default = config.terminal.default_profile_name().to_string();
+    let mut menu = Menu::new("");
+    for name in config.terminal.profiles.keys().sorted() {
+        let label = if *name == default {
+            format!("{name} (Default)")
+        } else {
+            name.to_string()
+        };
+        let window_tab_data = window_tab_data.clone();
+        let name = name.clone();
+        menu = menu.entry(MenuItem::new(label).action(move || {
+            let profile = window_tab_data.terminal_profile(Some(&name));
+            window_tab_data.terminal.new_tab(profile);
+            if !window_tab_data.panel.is_panel_visible(&PanelKind::Terminal) {
+                window_tab_data.panel.show_panel(&PanelKind::Terminal);
+            }
+            window_tab_data
+                .common
+                .focus
+                .set(Focus::Panel(PanelKind::Terminal));
+        }));
+    }
+    show_context_menu(menu, None);
+}
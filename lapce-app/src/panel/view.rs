@@ -15,13 +15,19 @@
 };
 
 use super::{
+    debug_console_view::debug_console_panel,
     debug_view::debug_panel,
+    flame_view::flame_panel,
     global_search_view::global_search_panel,
+    json_tree_view::json_tree_panel,
     kind::PanelKind,
+    notification_view::notification_panel,
+    output_view::output_panel,
     plugin_view::plugin_panel,
     position::{PanelContainerPosition, PanelPosition},
     problem_view::problem_panel,
     source_control_view::source_control_panel,
+    syntax_tree_view::syntax_tree_panel,
     terminal_view::terminal_panel,
 };
 use crate::{
@@ -484,6 +490,22 @@ fn panel_view(
                 PanelKind::Debug => {
                     debug_panel(window_tab_data.clone(), position).into_any()
                 }
+                PanelKind::DebugConsole => {
+                    debug_console_panel(window_tab_data.clone()).into_any()
+                }
+                PanelKind::Flame => flame_panel(window_tab_data.clone()).into_any(),
+                PanelKind::Output => {
+                    output_panel(window_tab_data.clone()).into_any()
+                }
+                PanelKind::JsonTree => {
+                    json_tree_panel(window_tab_data.clone()).into_any()
+                }
+                PanelKind::SyntaxTree => {
+                    syntax_tree_panel(window_tab_data.clone()).into_any()
+                }
+                PanelKind::Notifications => {
+                    notification_panel(window_tab_data.clone()).into_any()
+                }
             };
             view.style(|s| s.size_pct(100.0, 100.0))
         },
@@ -538,6 +560,16 @@ fn panel_picker(
                 PanelKind::Search => (LapceIcons::SEARCH, "Search"),
                 PanelKind::Problem => (LapceIcons::PROBLEM, "Problems"),
                 PanelKind::Debug => (LapceIcons::DEBUG_ALT, "Debug"),
+                PanelKind::DebugConsole => {
+                    (LapceIcons::DEBUG_CONSOLE, "Debug Console")
+                }
+                PanelKind::Flame => (LapceIcons::FLAME, "Flame Graph"),
+                PanelKind::Output => (LapceIcons::OUTPUT, "Output"),
+                PanelKind::JsonTree => (LapceIcons::JSON_TREE, "JSON Tree"),
+                PanelKind::SyntaxTree => (LapceIcons::SYNTAX_TREE, "Syntax Tree"),
+                PanelKind::Notifications => {
+                    (LapceIcons::NOTIFICATIONS, "Notifications")
+                }
             };
             let is_active = {
                 let window_tab_data = window_tab_data.clone();
@@ -0,0 +1,283 @@
+//! Opt-in latency instrumentation.
+//!
+//! When `ui.show-perf-overlay` is enabled, the time spent in a few hot paths is
+//! recorded here, summarised as rolling percentiles in a corner overlay, and
+//! any sample above the subsystem's budget is logged as a slow frame.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use floem::{
+    action::{exec_after, TimerToken},
+    reactive::{create_effect, create_rw_signal, RwSignal},
+    views::{label, Decorators},
+    View,
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tracing::warn;
+
+use crate::{config::color::LapceColor, window_tab::WindowTabData};
+
+/// The number of samples kept for each subsystem
+const SAMPLE_LIMIT: usize = 256;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STATS: Lazy<Mutex<PerfStats>> =
+    Lazy::new(|| Mutex::new(PerfStats::default()));
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PerfSubsystem {
+    /// From a key press being received to the next paint of the editor
+    KeyToPaint,
+    /// Painting the editor view
+    Paint,
+    /// Computing the screen lines of an editor
+    ScreenLines,
+    /// Round trip of a request to the language server, through the proxy
+    Lsp,
+    /// Painting an editor view for the first time after its document loaded
+    FirstPaint,
+    /// Laying out text in the fonts at startup
+    FontWarmup,
+}
+
+impl PerfSubsystem {
+    const ALL: [PerfSubsystem; 6] = [
+        PerfSubsystem::KeyToPaint,
+        PerfSubsystem::Paint,
+        PerfSubsystem::ScreenLines,
+        PerfSubsystem::Lsp,
+        PerfSubsystem::FirstPaint,
+        PerfSubsystem::FontWarmup,
+    ];
+
+    /// Samples above this are logged as slow
+    fn budget(&self) -> Duration {
+        match self {
+            PerfSubsystem::KeyToPaint => Duration::from_millis(32),
+            PerfSubsystem::Paint | PerfSubsystem::ScreenLines => {
+                Duration::from_millis(16)
+            }
+            PerfSubsystem::Lsp => Duration::from_millis(500),
+            PerfSubsystem::FirstPaint => Duration::from_millis(50),
+            PerfSubsystem::FontWarmup => Duration::from_millis(1000),
+        }
+    }
+}
+
+impl fmt::Display for PerfSubsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PerfSubsystem::KeyToPaint => "key to paint",
+            PerfSubsystem::Paint => "paint",
+            PerfSubsystem::ScreenLines => "screen lines",
+            PerfSubsystem::Lsp => "lsp",
+            PerfSubsystem::FirstPaint => "first paint",
+            PerfSubsystem::FontWarmup => "font warm-up",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Default)]
+struct PerfStats {
+    samples: HashMap<PerfSubsystem, VecDeque<Duration>>,
+    /// When the last key press that hasn't been painted yet was received
+    pending_key: Option<Instant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PerfSummary {
+    pub subsystem: PerfSubsystem,
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        *STATS.lock() = PerfStats::default();
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn record(subsystem: PerfSubsystem, duration: Duration) {
+    if !is_enabled() {
+        return;
+    }
+
+    if duration > subsystem.budget() {
+        warn!("slow frame: {subsystem} took {duration:?}");
+    }
+
+    let mut stats = STATS.lock();
+    let samples = stats.samples.entry(subsystem).or_default();
+    if samples.len() == SAMPLE_LIMIT {
+        samples.pop_front();
+    }
+    samples.push_back(duration);
+}
+
+/// Run `f`, recording how long it took.
+pub fn time<T>(subsystem: PerfSubsystem, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    record(subsystem, start.elapsed());
+    result
+}
+
+/// Mark that a key press was received, so that the next paint can be
+/// attributed to it.
+pub fn key_pressed() {
+    if !is_enabled() {
+        return;
+    }
+    let mut stats = STATS.lock();
+    if stats.pending_key.is_none() {
+        stats.pending_key = Some(Instant::now());
+    }
+}
+
+/// Mark that the editor was painted.
+pub fn painted() {
+    if !is_enabled() {
+        return;
+    }
+    let pending_key = STATS.lock().pending_key.take();
+    if let Some(start) = pending_key {
+        record(PerfSubsystem::KeyToPaint, start.elapsed());
+    }
+}
+
+pub fn summary() -> Vec<PerfSummary> {
+    let stats = STATS.lock();
+    PerfSubsystem::ALL
+        .iter()
+        .filter_map(|subsystem| {
+            let samples = stats.samples.get(subsystem)?;
+            if samples.is_empty() {
+                return None;
+            }
+            let mut samples: Vec<Duration> = samples.iter().copied().collect();
+            samples.sort();
+            Some(PerfSummary {
+                subsystem: *subsystem,
+                count: samples.len(),
+                p50: percentile(&samples, 0.5),
+                p90: percentile(&samples, 0.9),
+                p99: percentile(&samples, 0.99),
+            })
+        })
+        .collect()
+}
+
+/// The nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn format_summary(summary: &[PerfSummary]) -> String {
+    if summary.is_empty() {
+        return "No samples yet".to_string();
+    }
+    summary
+        .iter()
+        .map(|s| {
+            format!(
+                "{}: p50 {:.1}ms p90 {:.1}ms p99 {:.1}ms ({})",
+                s.subsystem,
+                s.p50.as_secs_f64() * 1000.0,
+                s.p90.as_secs_f64() * 1000.0,
+                s.p99.as_secs_f64() * 1000.0,
+                s.count
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn perf_overlay(window_tab_data: Rc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let window_focused = window_tab_data.common.window_common.focused;
+    let text = create_rw_signal(String::new());
+    let timer = create_rw_signal(TimerToken::INVALID);
+
+    fn refresh(text: RwSignal<String>, timer: RwSignal<TimerToken>) {
+        text.set(format_summary(&summary()));
+        let token = exec_after(REFRESH_INTERVAL, move |token| {
+            // The overlay might have been closed along with the window tab,
+            // or stopped refreshing
+            if timer.try_get_untracked() == Some(token) {
+                refresh(text, timer);
+            }
+        });
+        timer.set(token);
+    }
+
+    // Refreshed while the window has the focus
+    create_effect(move |was_running: Option<bool>| {
+        let enabled = config.with(|config| config.ui.show_perf_overlay);
+        set_enabled(enabled);
+        let running = enabled && window_focused.get();
+        if running && was_running != Some(true) {
+            refresh(text, timer);
+        } else if !running {
+            timer.set(TimerToken::INVALID);
+        }
+        running
+    });
+
+    label(move || text.get())
+        .style(move |s| {
+            let config = config.get();
+            s.absolute()
+                .inset_right(10.0)
+                .inset_bottom(config.ui.status_height() as f32 + 10.0)
+                .padding(6.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .font_family(config.editor.font_family.clone())
+                .font_size(config.ui.font_size() as f32 - 1.0)
+                .color(config.color(LapceColor::PANEL_FOREGROUND))
+                .border_color(config.color(LapceColor::LAPCE_BORDER))
+                .background(config.color(LapceColor::PANEL_BACKGROUND))
+                .apply_if(!config.ui.show_perf_overlay, |s| s.hide())
+        })
+        .debug_name("Perf Overlay")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::percentile;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 0.5), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 0.9), Duration::from_millis(90));
+        assert_eq!(percentile(&samples, 0.99), Duration::from_millis(99));
+        assert_eq!(percentile(&samples[..1], 0.99), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+}
@@ -37,6 +37,8 @@
     main_split::Editors,
     markdown::{parse_markdown, MarkdownContent},
     panel::plugin_view::VOLT_DEFAULT_PNG,
+    profile,
+    tracing::*,
     web_link::web_link,
     window_tab::CommonData,
 };
@@ -101,6 +103,8 @@ pub struct PluginData {
     pub all: RwSignal<im::HashMap<VoltID, AvailableVoltData>>,
     pub disabled: RwSignal<HashSet<VoltID>>,
     pub workspace_disabled: RwSignal<HashSet<VoltID>>,
+    /// Disabled by the window tab's profile
+    pub profile_disabled: RwSignal<HashSet<VoltID>>,
     pub common: Rc<CommonData>,
 }
 
@@ -154,6 +158,7 @@ pub fn new(
         cx: Scope,
         disabled: HashSet<VoltID>,
         workspace_disabled: HashSet<VoltID>,
+        profile_disabled: HashSet<VoltID>,
         editors: Editors,
         common: Rc<CommonData>,
     ) -> Self {
@@ -167,6 +172,7 @@ pub fn new(
         };
         let disabled = cx.create_rw_signal(disabled);
         let workspace_disabled = cx.create_rw_signal(workspace_disabled);
+        let profile_disabled = cx.create_rw_signal(profile_disabled);
 
         let plugin = Self {
             installed,
@@ -174,6 +180,7 @@ pub fn new(
             all: cx.create_rw_signal(im::HashMap::new()),
             disabled,
             workspace_disabled,
+            profile_disabled,
             common,
         };
 
@@ -493,9 +500,41 @@ pub fn install_volt(&self, info: VoltInfo) {
         }
     }
 
+    /// Install the plugins that aren't installed yet, looking them up in the
+    /// registry by id.
+    pub fn install_volts_by_id(&self, ids: Vec<VoltID>) {
+        let ids: Vec<VoltID> = self.installed.with_untracked(|installed| {
+            ids.into_iter()
+                .filter(|id| !installed.contains_key(id))
+                .collect()
+        });
+        if ids.is_empty() {
+            return;
+        }
+
+        let plugin = self.clone();
+        let send =
+            create_ext_action(self.common.scope, move |volts: Vec<VoltInfo>| {
+                for volt in volts {
+                    plugin.install_volt(volt);
+                }
+            });
+        std::thread::spawn(move || {
+            let volts = ids
+                .iter()
+                .filter_map(|id| {
+                    let volts = Self::query_volts(&id.name, 0).ok()?;
+                    volts.plugins.into_iter().find(|volt| &volt.id() == id)
+                })
+                .collect();
+            send(volts);
+        });
+    }
+
     pub fn plugin_disabled(&self, id: &VoltID) -> bool {
         self.disabled.with_untracked(|d| d.contains(id))
             || self.workspace_disabled.with_untracked(|d| d.contains(id))
+            || self.profile_disabled.with_untracked(|d| d.contains(id))
     }
 
     pub fn enable_volt(&self, volt: VoltInfo) {
@@ -548,6 +587,67 @@ pub fn disable_volt_for_ws(&self, volt: VoltInfo) {
         );
     }
 
+    pub fn enable_volt_for_profile(&self, volt: VoltInfo) {
+        let id = volt.id();
+        self.profile_disabled.update(|d| {
+            d.remove(&id);
+        });
+        if !self.plugin_disabled(&id) {
+            self.common.proxy.enable_volt(volt);
+        }
+        self.save_profile_disabled_volts();
+    }
+
+    pub fn disable_volt_for_profile(&self, volt: VoltInfo) {
+        let id = volt.id();
+        self.profile_disabled.update(|d| {
+            d.insert(id);
+        });
+        self.common.proxy.disable_volt(volt);
+        self.save_profile_disabled_volts();
+    }
+
+    fn save_profile_disabled_volts(&self) {
+        let Some(name) = self.common.profile.get_untracked() else {
+            return;
+        };
+        if let Err(err) = profile::save_disabled_volts(
+            &name,
+            self.profile_disabled.get_untracked().into_iter().collect(),
+        ) {
+            trace!(
+                TraceLevel::ERROR,
+                "Failed to save the disabled plugins of profile {name}: {err}"
+            );
+        }
+    }
+
+    /// Switch to the plugins disabled by another profile, starting and
+    /// stopping the ones that change.
+    pub fn set_profile_disabled(&self, profile_disabled: HashSet<VoltID>) {
+        let old = self.profile_disabled.get_untracked();
+        self.profile_disabled.set(profile_disabled.clone());
+
+        let installed = self.installed.get_untracked();
+        for id in old.symmetric_difference(&profile_disabled) {
+            let disabled_elsewhere =
+                self.disabled.with_untracked(|d| d.contains(id))
+                    || self.workspace_disabled.with_untracked(|d| d.contains(id));
+            if disabled_elsewhere {
+                continue;
+            }
+            let Some(volt) = installed.get(id) else {
+                continue;
+            };
+            let volt = volt.meta.get_untracked().info();
+            if profile_disabled.contains(id) {
+                self.common.proxy.disable_volt(volt);
+            } else {
+                self.common.proxy.enable_volt(volt);
+            }
+        }
+    }
+
     pub fn uninstall_volt(&self, volt: VoltMetadata) {
         if volt.wasm.is_some() {
             self.common.proxy.remove_volt(volt);
@@ -658,13 +758,51 @@ pub fn plugin_controls(&self, meta: VoltMetadata, latest: VoltInfo) -> Menu {
                         }
                     }),
             )
-            .separator()
-            .entry(MenuItem::new("Uninstall").action({
-                let plugin = self.clone();
-                move || {
-                    plugin.uninstall_volt(meta.clone());
-                }
-            }));
+            .separator();
+        if self
+            .common
+            .profile
+            .with_untracked(|profile| profile.is_some())
+        {
+            menu = menu
+                .entry(
+                    MenuItem::new("Enable For Profile")
+                        .enabled(
+                            self.profile_disabled.with_untracked(|disabled| {
+                                disabled.contains(&volt_id)
+                            }),
+                        )
+                        .action({
+                            let plugin = self.clone();
+                            let volt = meta.info();
+                            move || {
+                                plugin.enable_volt_for_profile(volt.clone());
+                            }
+                        }),
+                )
+                .entry(
+                    MenuItem::new("Disable For Profile")
+                        .enabled(
+                            self.profile_disabled.with_untracked(|disabled| {
+                                !disabled.contains(&volt_id)
+                            }),
+                        )
+                        .action({
+                            let plugin = self.clone();
+                            let volt = meta.info();
+                            move || {
+                                plugin.disable_volt_for_profile(volt.clone());
+                            }
+                        }),
+                )
+                .separator();
+        }
+        menu = menu.entry(MenuItem::new("Uninstall").action({
+            let plugin = self.clone();
+            move || {
+                plugin.uninstall_volt(meta.clone());
+            }
+        }));
         menu
     }
 }
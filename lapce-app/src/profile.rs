@@ -0,0 +1,126 @@
+//! Named configuration profiles, such as "work", "oss" or "minimal".
+//!
+//! A profile is a folder in [`Directory::profiles_directory`] with its own
+//! `settings.toml` and `keymaps.toml`, which are layered over the user's, and
+//! a list of plugins that it disables on top of the ones disabled globally.
+//! Each window tab runs with one profile. The profile last picked for a
+//! workspace is used again when the workspace is opened.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use lapce_core::directory::Directory;
+use lapce_rpc::plugin::VoltID;
+
+const SETTINGS_FILE: &str = "settings.toml";
+const KEYMAPS_FILE: &str = "keymaps.toml";
+const DISABLED_VOLTS_FILE: &str = "disabled-plugins.json";
+
+/// The name shown for running without a profile
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// The names of the existing profiles, sorted.
+pub fn profiles() -> Vec<String> {
+    let Some(dir) = Directory::profiles_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+        .filter(|name| is_valid_name(name))
+        .collect();
+    profiles.sort();
+    profiles
+}
+
+/// Create the profile's folder if it doesn't exist yet.
+pub fn create(name: &str) -> Result<()> {
+    let dir = profile_directory(name)
+        .ok_or_else(|| anyhow!("can't get the profiles folder"))?;
+    std::fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+pub fn exists(name: &str) -> bool {
+    profile_directory(name).is_some_and(|dir| dir.is_dir())
+}
+
+/// Profile names become folder names, so they have to stay inside the
+/// profiles folder
+pub fn is_valid_name(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.eq_ignore_ascii_case(DEFAULT_PROFILE_NAME)
+        && !name.contains(['/', '\\', ':'])
+}
+
+fn profile_directory(name: &str) -> Option<PathBuf> {
+    if !is_valid_name(name) {
+        return None;
+    }
+    Some(Directory::profiles_directory()?.join(name.trim()))
+}
+
+fn profile_file(name: &str, file: &str) -> Option<PathBuf> {
+    let dir = profile_directory(name)?;
+    if !dir.is_dir() {
+        return None;
+    }
+    let path = dir.join(file);
+    if !path.exists() {
+        let _ = std::fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&path);
+    }
+    Some(path)
+}
+
+pub fn settings_file(name: &str) -> Option<PathBuf> {
+    profile_file(name, SETTINGS_FILE)
+}
+
+pub fn keymaps_file(name: &str) -> Option<PathBuf> {
+    profile_file(name, KEYMAPS_FILE)
+}
+
+/// The plugins the profile disables, in addition to the globally disabled
+/// ones
+pub fn disabled_volts(name: &str) -> Vec<VoltID> {
+    profile_directory(name)
+        .and_then(|dir| std::fs::read_to_string(dir.join(DISABLED_VOLTS_FILE)).ok())
+        .and_then(|volts| serde_json::from_str(&volts).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_disabled_volts(name: &str, volts: Vec<VoltID>) -> Result<()> {
+    let dir = profile_directory(name)
+        .ok_or_else(|| anyhow!("invalid profile name {name}"))?;
+    std::fs::create_dir_all(&dir)?;
+    let volts = serde_json::to_string_pretty(&volts)?;
+    std::fs::write(dir.join(DISABLED_VOLTS_FILE), volts)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_name;
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("work"));
+        assert!(is_valid_name("open source"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("  "));
+        assert!(!is_valid_name(".."));
+        assert!(!is_valid_name("default"));
+        assert!(!is_valid_name("../work"));
+        assert!(!is_valid_name("C:\\work"));
+    }
+}
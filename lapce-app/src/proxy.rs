@@ -9,14 +9,17 @@
     proxy::{ProxyRpcHandler, ProxyStatus},
     terminal::TermId,
 };
+use lsp_types::{MessageType, ShowMessageParams};
 use tracing::error;
 
-use self::{remote::start_remote, ssh::SshRemote};
+use self::{container::ContainerRemote, remote::start_remote, ssh::SshRemote};
 use crate::{
+    devcontainer,
     terminal::event::TermEvent,
     workspace::{LapceWorkspace, LapceWorkspaceType},
 };
 
+mod container;
 mod remote;
 mod ssh;
 #[cfg(windows)]
@@ -86,6 +89,39 @@ pub fn new_proxy(
                         proxy_rpc.clone(),
                     ) {
                         error!("Failed to start SSH remote: {e}");
+                        core_rpc.notification(CoreNotification::ShowMessage {
+                            title: "SSH Remote".to_string(),
+                            message: ShowMessageParams {
+                                typ: MessageType::ERROR,
+                                message: format!(
+                                    "Failed to connect to {remote}: {e}"
+                                ),
+                            },
+                        });
+                    }
+                }
+                LapceWorkspaceType::RemoteContainer(remote) => {
+                    let started = devcontainer::start(remote).and_then(|_| {
+                        start_remote(
+                            ContainerRemote {
+                                container: remote.clone(),
+                            },
+                            core_rpc.clone(),
+                            proxy_rpc.clone(),
+                        )
+                    });
+                    if let Err(e) = started {
+                        error!("Failed to start container remote: {e}");
+                        core_rpc.notification(CoreNotification::ShowMessage {
+                            title: "Dev Container".to_string(),
+                            message: ShowMessageParams {
+                                typ: MessageType::ERROR,
+                                message: format!(
+                                    "Failed to start container {}: {e}",
+                                    remote.name
+                                ),
+                            },
+                        });
                     }
                 }
                 #[cfg(windows)]
@@ -97,7 +133,16 @@ pub fn new_proxy(
                         core_rpc.clone(),
                         proxy_rpc.clone(),
                     ) {
-                        error!("Failed to start SSH remote: {e}");
+                        error!("Failed to start WSL remote: {e}");
+                        core_rpc.notification(CoreNotification::ShowMessage {
+                            title: "WSL Remote".to_string(),
+                            message: ShowMessageParams {
+                                typ: MessageType::ERROR,
+                                message: format!(
+                                    "Failed to connect to {remote}: {e}"
+                                ),
+                            },
+                        });
                     }
                 }
             }
@@ -0,0 +1,46 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{anyhow, Result};
+
+use super::{new_command, remote::Remote};
+use crate::workspace::ContainerHost;
+
+pub struct ContainerRemote {
+    pub container: ContainerHost,
+}
+
+impl Remote for ContainerRemote {
+    fn upload_file(&self, local: impl AsRef<Path>, remote: &str) -> Result<()> {
+        // `cp` doesn't go through a shell in the container
+        let remote = if remote.starts_with('~') {
+            remote.replacen('~', &self.home_dir()?, 1)
+        } else {
+            remote.to_string()
+        };
+        let output = new_command(&self.container.engine)
+            .arg("cp")
+            .arg(local.as_ref())
+            .arg(format!("{}:{remote}", self.container.name))
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn command_builder(&self) -> Command {
+        let mut cmd = new_command(&self.container.engine);
+        cmd.arg("exec").arg("-i");
+        if let Some(user) = &self.container.user {
+            cmd.arg("-u").arg(user);
+        }
+        // The arguments are run by a shell like ssh does, so that `~`, `$HOME`
+        // and pipes work the same
+        cmd.arg(&self.container.name)
+            .args(["sh", "-c", "eval \"$*\"", "sh"]);
+        cmd
+    }
+}
@@ -0,0 +1,148 @@
+//! The journal of the refactors that edit several files at once, such as
+//! renaming a symbol, kept so that they can be rolled back as a whole.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use lapce_xi_rope::Rope;
+
+/// How many refactors the journal remembers
+const MAX_REFACTORS: usize = 20;
+
+/// The text of a file edited by a refactor, before and after it
+#[derive(Clone)]
+pub struct RefactorFile {
+    pub path: PathBuf,
+    /// `None` until the edits of the refactor are applied to the file
+    pub texts: Option<(Rope, Rope)>,
+}
+
+#[derive(Clone)]
+pub struct Refactor {
+    pub id: u64,
+    pub title: String,
+    pub time: DateTime<Local>,
+    pub files: Vec<RefactorFile>,
+}
+
+impl Refactor {
+    /// The files whose edits have been applied, with their texts from before
+    /// and after the refactor
+    pub fn applied_files(&self) -> impl Iterator<Item = (&Path, &Rope, &Rope)> {
+        self.files.iter().filter_map(|file| {
+            let (before, after) = file.texts.as_ref()?;
+            Some((file.path.as_path(), before, after))
+        })
+    }
+}
+
+/// The recent refactors, the last one first
+#[derive(Clone, Default)]
+pub struct RefactorJournal {
+    pub refactors: im::Vector<Refactor>,
+    next_id: u64,
+}
+
+impl RefactorJournal {
+    /// Start recording a refactor which is about to edit the files at
+    /// `paths`
+    pub fn begin(&mut self, title: String, paths: Vec<PathBuf>) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.refactors.push_front(Refactor {
+            id,
+            title,
+            time: Local::now(),
+            files: paths
+                .into_iter()
+                .map(|path| RefactorFile { path, texts: None })
+                .collect(),
+        });
+        if self.refactors.len() > MAX_REFACTORS {
+            self.refactors.truncate(MAX_REFACTORS);
+        }
+    }
+
+    /// Record the texts of a file from before and after edits applied to it,
+    /// for the first refactor still waiting for the edits of the file.
+    /// Returns whether there was such a refactor.
+    pub fn record(&mut self, path: &Path, before: Rope, after: Rope) -> bool {
+        let file = self.refactors.iter_mut().rev().find_map(|refactor| {
+            refactor
+                .files
+                .iter_mut()
+                .find(|file| file.texts.is_none() && file.path == path)
+        });
+        match file {
+            Some(file) => {
+                file.texts = Some((before, after));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a refactor is waiting for the edits of the file at `path`
+    pub fn is_pending(&self, path: &Path) -> bool {
+        self.refactors.iter().any(|refactor| {
+            refactor
+                .files
+                .iter()
+                .any(|file| file.texts.is_none() && file.path == path)
+        })
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Refactor> {
+        self.refactors.iter().find(|refactor| refactor.id == id)
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        self.refactors.retain(|refactor| refactor.id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use lapce_xi_rope::Rope;
+
+    use super::RefactorJournal;
+
+    #[test]
+    fn test_record() {
+        let mut journal = RefactorJournal::default();
+        journal.begin(
+            "Rename to b".to_string(),
+            vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")],
+        );
+        journal.begin("Rename to c".to_string(), vec![PathBuf::from("a.rs")]);
+        assert!(journal.is_pending(Path::new("b.rs")));
+
+        // The edits are applied in the order of the refactors
+        assert!(journal.record(Path::new("a.rs"), Rope::from("a"), Rope::from("b")));
+        assert!(journal.record(Path::new("a.rs"), Rope::from("b"), Rope::from("c")));
+        assert!(!journal.record(
+            Path::new("a.rs"),
+            Rope::from("a"),
+            Rope::from("b")
+        ));
+        assert!(journal.record(Path::new("b.rs"), Rope::from("a"), Rope::from("b")));
+        assert!(!journal.is_pending(Path::new("b.rs")));
+
+        let ids = journal.refactors.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 0]);
+        let first = journal.get(0).unwrap();
+        assert_eq!(first.applied_files().count(), 2);
+        let last = journal.get(1).unwrap();
+        let (path, before, after) = last.applied_files().next().unwrap();
+        assert_eq!(path, Path::new("a.rs"));
+        assert_eq!(
+            (before.to_string(), after.to_string()),
+            ("b".into(), "c".into())
+        );
+
+        journal.remove(1);
+        assert!(journal.get(1).is_none());
+    }
+}
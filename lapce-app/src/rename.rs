@@ -134,10 +134,11 @@ fn confirm(&self) {
             let path = self.path.get_untracked();
             let position = self.position.get_untracked();
             let internal_command = self.common.internal_command;
+            let title = format!("Rename to {new_name}");
             let send = create_ext_action(self.common.scope, move |result| {
                 if let Ok(ProxyResponse::Rename { edit }) = result {
                     internal_command
-                        .send(InternalCommand::ApplyWorkspaceEdit { edit });
+                        .send(InternalCommand::ApplyWorkspaceEdit { edit, title });
                 }
             });
             self.common.proxy.rename(
@@ -0,0 +1,291 @@
+//! The rendering of output richer than lines of text, text colored by ANSI
+//! escapes, tables and images, which plugins show in the output panel and
+//! debug adapters in the debug console.
+
+use std::{rc::Rc, sync::Arc};
+
+use alacritty_terminal::term::color::Colors;
+use base64::{engine::general_purpose, Engine as _};
+use floem::{
+    cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout, Weight},
+    peniko::Color,
+    reactive::{ReadSignal, RwSignal, Scope},
+    views::{
+        container, img, label, rich_text, stack, stack_from_iter, svg, Decorators,
+    },
+    AnyView, View,
+};
+use lapce_rpc::rich_output::RichOutput;
+
+use crate::{
+    config::{color::LapceColor, LapceConfig},
+    debug_console::{parse_ansi, AnsiSpan, AnsiStyle},
+    window_tab::CommonData,
+};
+
+/// The rows of a table after which the rest are only counted
+const MAX_TABLE_ROWS: usize = 50;
+/// How high an image is shown, keeping its aspect ratio
+const IMAGE_HEIGHT: f64 = 200.0;
+const PADDING: f64 = 4.0;
+/// How many pieces of output the output panel keeps
+const MAX_ENTRIES: usize = 1_000;
+
+/// How high the view of an output is with lines of `line_height`, for the
+/// virtual lists needing the heights of their items up front
+pub fn rich_output_height(output: &RichOutput, line_height: f64) -> f64 {
+    let lines = match output {
+        RichOutput::Text { text } | RichOutput::Ansi { text } => {
+            return text.trim_end_matches('\n').lines().count().max(1) as f64
+                * line_height;
+        }
+        RichOutput::Table { rows, .. } => {
+            // The header, the rows and the count of the rows left out
+            1 + rows.len().min(MAX_TABLE_ROWS)
+                + usize::from(rows.len() > MAX_TABLE_ROWS)
+        }
+        RichOutput::Image { .. } => return IMAGE_HEIGHT + PADDING * 2.0,
+    };
+    lines as f64 * line_height + PADDING * 2.0
+}
+
+/// Lines of text in the terminal font, in the colors their escapes set, the
+/// rest in `foreground`
+pub fn ansi_layout(
+    text: &str,
+    spans: &[AnsiSpan],
+    foreground: Color,
+    config: &LapceConfig,
+) -> TextLayout {
+    let family: Vec<FamilyOwned> =
+        FamilyOwned::parse_list(config.terminal_font_family()).collect();
+    let attrs = Attrs::new()
+        .family(&family)
+        .font_size(config.terminal_font_size() as f32)
+        .color(foreground);
+    let mut attrs_list = AttrsList::new(attrs);
+    let colors = Colors::default();
+    for span in spans {
+        let mut attrs = attrs;
+        if let Some(fg) = span.style.fg.as_ref() {
+            attrs = attrs.color(config.terminal_get_color(fg, &colors));
+        }
+        if span.style.bold {
+            attrs = attrs.weight(Weight::BOLD);
+        }
+        attrs_list.add_span(span.range.clone(), attrs);
+    }
+    let mut text_layout = TextLayout::new();
+    text_layout.set_text(text, attrs_list);
+    text_layout
+}
+
+/// The text of several lines without their escapes, and the spans of the
+/// colors the escapes set
+fn parse_ansi_lines(text: &str) -> (String, Vec<AnsiSpan>) {
+    let mut style = AnsiStyle::default();
+    let mut out = String::with_capacity(text.len());
+    let mut spans = Vec::new();
+    for (i, line) in text.trim_end_matches('\n').lines().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let (line, line_spans) = parse_ansi(line, &mut style);
+        let offset = out.len();
+        out.push_str(&line);
+        spans.extend(line_spans.into_iter().map(|span| AnsiSpan {
+            range: span.range.start + offset..span.range.end + offset,
+            style: span.style,
+        }));
+    }
+    (out, spans)
+}
+
+pub fn rich_output_view(
+    output: Rc<RichOutput>,
+    line_height: f64,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> AnyView {
+    let height = rich_output_height(&output, line_height);
+    match output.as_ref() {
+        RichOutput::Text { text } => {
+            let text = text.trim_end_matches('\n').to_string();
+            rich_text(move || {
+                let config = config.get();
+                let foreground = config.color(LapceColor::TERMINAL_FOREGROUND);
+                ansi_layout(&text, &[], foreground, &config)
+            })
+            .style(move |s| s.height(height))
+            .into_any()
+        }
+        RichOutput::Ansi { text } => {
+            let (text, spans) = parse_ansi_lines(text);
+            rich_text(move || {
+                let config = config.get();
+                let foreground = config.color(LapceColor::TERMINAL_FOREGROUND);
+                ansi_layout(&text, &spans, foreground, &config)
+            })
+            .style(move |s| s.height(height))
+            .into_any()
+        }
+        RichOutput::Table { headers, rows } => {
+            table_view(headers.clone(), rows.clone(), line_height, config)
+                .style(move |s| s.height(height).padding_vert(PADDING))
+                .into_any()
+        }
+        RichOutput::Image { mime, data } => image_view(mime, data, config)
+            .style(move |s| s.height(height).padding_vert(PADDING))
+            .into_any(),
+    }
+}
+
+fn table_view(
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    line_height: f64,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let hidden_rows = rows.len().saturating_sub(MAX_TABLE_ROWS);
+    let cell = move |text: String, header: bool| {
+        label(move || text.clone()).style(move |s| {
+            let config = config.get();
+            s.height(line_height)
+                .items_center()
+                .padding_horiz(8.0)
+                .font_family(config.terminal_font_family().to_string())
+                .font_size(config.terminal_font_size() as f32)
+                .color(config.color(LapceColor::TERMINAL_FOREGROUND))
+                .apply_if(header, |s| {
+                    s.font_bold()
+                        .border_bottom(1.0)
+                        .border_color(config.color(LapceColor::LAPCE_BORDER))
+                })
+        })
+    };
+    let columns = headers.into_iter().enumerate().map(|(i, header)| {
+        let cells = std::iter::once(cell(header, true)).chain(
+            rows.iter()
+                .take(MAX_TABLE_ROWS)
+                .map(|row| cell(row.get(i).cloned().unwrap_or_default(), false)),
+        );
+        stack_from_iter(cells).style(|s| s.flex_col())
+    });
+
+    stack((
+        stack_from_iter(columns),
+        label(move || format!("{hidden_rows} more rows")).style(move |s| {
+            s.height(line_height)
+                .padding_horiz(8.0)
+                .items_center()
+                .color(config.get().color(LapceColor::EDITOR_DIM))
+                .apply_if(hidden_rows == 0, |s| s.hide())
+        }),
+    ))
+    .style(|s| s.flex_col())
+}
+
+fn image_view(
+    mime: &str,
+    data: &str,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> AnyView {
+    if mime == "image/svg+xml" {
+        let data = data.to_string();
+        return svg(move || data.clone())
+            .style(|s| s.size_full())
+            .into_any();
+    }
+    match general_purpose::STANDARD.decode(data) {
+        Ok(bytes) => {
+            container(img(move || bytes.clone()).style(|s| s.height_full()))
+                .style(|s| s.height_full())
+                .into_any()
+        }
+        Err(e) => {
+            let message = format!("Invalid {mime} image: {e}");
+            label(move || message.clone())
+                .style(move |s| s.color(config.get().color(LapceColor::LAPCE_ERROR)))
+                .into_any()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputEntry {
+    pub id: usize,
+    /// The plugin showing the output
+    pub source: String,
+    pub output: Rc<RichOutput>,
+}
+
+/// The output the plugins show in the output panel
+#[derive(Clone)]
+pub struct OutputData {
+    pub entries: RwSignal<im::Vector<OutputEntry>>,
+    next_id: RwSignal<usize>,
+    pub common: Rc<CommonData>,
+}
+
+impl OutputData {
+    pub fn new(cx: Scope, common: Rc<CommonData>) -> Self {
+        Self {
+            entries: cx.create_rw_signal(im::Vector::new()),
+            next_id: cx.create_rw_signal(0),
+            common,
+        }
+    }
+
+    pub fn push(&self, source: &str, items: Vec<RichOutput>) {
+        let mut next_id = self.next_id.get_untracked();
+        self.entries.update(|entries| {
+            for output in items {
+                entries.push_back(OutputEntry {
+                    id: next_id,
+                    source: source.to_string(),
+                    output: Rc::new(output),
+                });
+                next_id += 1;
+            }
+            while entries.len() > MAX_ENTRIES {
+                entries.pop_front();
+            }
+        });
+        self.next_id.set(next_id);
+    }
+
+    pub fn clear(&self) {
+        self.entries.set(im::Vector::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::rich_output::RichOutput;
+
+    use super::{parse_ansi_lines, rich_output_height, MAX_TABLE_ROWS, PADDING};
+
+    #[test]
+    fn test_parse_ansi_lines() {
+        let (text, spans) = parse_ansi_lines("a\n\x1b[31mb\nc\x1b[0m\n");
+        assert_eq!(text, "a\nb\nc");
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].range, 2..3);
+        assert_eq!(spans[1].range, 4..5);
+    }
+
+    #[test]
+    fn test_rich_output_height() {
+        let text = RichOutput::Ansi {
+            text: "a\nb\n".to_string(),
+        };
+        assert_eq!(rich_output_height(&text, 10.0), 20.0);
+        let table = RichOutput::Table {
+            headers: vec!["a".to_string()],
+            rows: vec![vec!["1".to_string()]; MAX_TABLE_ROWS + 5],
+        };
+        assert_eq!(
+            rich_output_height(&table, 10.0),
+            (MAX_TABLE_ROWS + 2) as f64 * 10.0 + PADDING * 2.0
+        );
+    }
+}
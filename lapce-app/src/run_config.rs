@@ -0,0 +1,836 @@
+//! The run and debug configurations of the workspace as a form, written back
+//! to `.lapce/run.toml` so that they don't have to be edited as TOML.
+
+use std::{collections::HashMap, path::PathBuf, rc::Rc};
+
+use anyhow::Result;
+use floem::{
+    reactive::{create_effect, create_memo, RwSignal, Scope},
+    style::{CursorStyle, Style},
+    views::{dyn_container, dyn_stack, label, scroll, stack, text, Decorators},
+    View,
+};
+use lapce_rpc::dap_types::{ProcessInfo, RunDebugConfig, RunDebugProgram};
+use lapce_xi_rope::Rope;
+
+use crate::{
+    command::InternalCommand,
+    config::color::LapceColor,
+    debug::RunDebugConfigs,
+    doc::Doc,
+    main_split::{Editors, MainSplitData},
+    text_input::TextInputBuilder,
+    window_tab::CommonData,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RunConfigField {
+    Name,
+    Type,
+    Program,
+    Args,
+    Cwd,
+    Env,
+    PrelaunchProgram,
+    PrelaunchArgs,
+}
+
+impl RunConfigField {
+    pub const ALL: [RunConfigField; 8] = [
+        RunConfigField::Name,
+        RunConfigField::Type,
+        RunConfigField::Program,
+        RunConfigField::Args,
+        RunConfigField::Cwd,
+        RunConfigField::Env,
+        RunConfigField::PrelaunchProgram,
+        RunConfigField::PrelaunchArgs,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RunConfigField::Name => "Name",
+            RunConfigField::Type => "Debug Adapter",
+            RunConfigField::Program => "Program",
+            RunConfigField::Args => "Arguments",
+            RunConfigField::Cwd => "Working Directory",
+            RunConfigField::Env => "Environment",
+            RunConfigField::PrelaunchProgram => "Before Launch",
+            RunConfigField::PrelaunchArgs => "Before Launch Arguments",
+        }
+    }
+
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            RunConfigField::Name => "",
+            RunConfigField::Type => "lldb, debugpy... Empty if it can only be run",
+            RunConfigField::Program => "",
+            RunConfigField::Args => "arg1 \"arg 2\"",
+            RunConfigField::Cwd => "${workspace}",
+            RunConfigField::Env => "VAR1=VAL1 VAR2=\"VAL 2\"",
+            RunConfigField::PrelaunchProgram => "A program to run first, e.g. cargo",
+            RunConfigField::PrelaunchArgs => "build",
+        }
+    }
+}
+
+/// A config with the text of each of its fields as they are typed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunConfigForm {
+    values: HashMap<RunConfigField, String>,
+}
+
+impl RunConfigForm {
+    pub fn from_config(config: &RunDebugConfig) -> Self {
+        let mut form = Self::default();
+        form.set(RunConfigField::Name, config.name.clone());
+        form.set(RunConfigField::Type, config.ty.clone().unwrap_or_default());
+        form.set(RunConfigField::Program, config.program.clone());
+        form.set(
+            RunConfigField::Args,
+            join_args(config.args.as_deref().unwrap_or_default()),
+        );
+        form.set(RunConfigField::Cwd, config.cwd.clone().unwrap_or_default());
+        form.set(
+            RunConfigField::Env,
+            config.env.as_ref().map(format_env).unwrap_or_default(),
+        );
+        if let Some(prelaunch) = &config.prelaunch {
+            form.set(RunConfigField::PrelaunchProgram, prelaunch.program.clone());
+            form.set(
+                RunConfigField::PrelaunchArgs,
+                join_args(prelaunch.args.as_deref().unwrap_or_default()),
+            );
+        }
+        form
+    }
+
+    pub fn get(&self, field: RunConfigField) -> &str {
+        self.values.get(&field).map(|v| v.as_str()).unwrap_or("")
+    }
+
+    pub fn set(&mut self, field: RunConfigField, value: String) {
+        if value.is_empty() {
+            self.values.remove(&field);
+        } else {
+            self.values.insert(field, value);
+        }
+    }
+
+    /// The config, or the first field that isn't valid and why
+    pub fn to_config(&self) -> Result<RunDebugConfig, (RunConfigField, String)> {
+        let text = |field| self.get(field).trim().to_string();
+        let optional = |field| Some(text(field)).filter(|v| !v.is_empty());
+        let args = |field| {
+            split_args(self.get(field))
+                .map(|args| Some(args).filter(|args| !args.is_empty()))
+                .map_err(|e| (field, e))
+        };
+
+        let name = text(RunConfigField::Name);
+        if name.is_empty() {
+            return Err((RunConfigField::Name, "A name is required".to_string()));
+        }
+        let program = text(RunConfigField::Program);
+        if program.is_empty() {
+            return Err((
+                RunConfigField::Program,
+                "A program is required".to_string(),
+            ));
+        }
+        let env = parse_env(self.get(RunConfigField::Env))
+            .map_err(|e| (RunConfigField::Env, e))?;
+        let prelaunch_args = args(RunConfigField::PrelaunchArgs)?;
+        let prelaunch = match optional(RunConfigField::PrelaunchProgram) {
+            Some(program) => Some(RunDebugProgram {
+                program,
+                args: prelaunch_args,
+            }),
+            None if prelaunch_args.is_some() => {
+                return Err((
+                    RunConfigField::PrelaunchProgram,
+                    "The arguments are for a program to run first".to_string(),
+                ))
+            }
+            None => None,
+        };
+
+        Ok(RunDebugConfig {
+            ty: optional(RunConfigField::Type),
+            name,
+            program,
+            args: args(RunConfigField::Args)?,
+            cwd: optional(RunConfigField::Cwd),
+            env: Some(env).filter(|env| !env.is_empty()),
+            prelaunch,
+            debug_command: None,
+            dap_id: Default::default(),
+            attach_pid: None,
+        })
+    }
+}
+
+/// The configs of the forms, or the first one that isn't valid, which field
+/// and why
+pub fn validate(
+    forms: &[RunConfigForm],
+) -> Result<Vec<RunDebugConfig>, (usize, RunConfigField, String)> {
+    let mut configs: Vec<RunDebugConfig> = Vec::with_capacity(forms.len());
+    for (i, form) in forms.iter().enumerate() {
+        let config = form.to_config().map_err(|(field, e)| (i, field, e))?;
+        if configs.iter().any(|c| c.name == config.name) {
+            return Err((
+                i,
+                RunConfigField::Name,
+                format!("There is already a config named {}", config.name),
+            ));
+        }
+        configs.push(config);
+    }
+    Ok(configs)
+}
+
+/// The config to attach the debugger of `template` to a running process,
+/// instead of launching its program
+pub fn attach_config(
+    template: &RunDebugConfig,
+    process: &ProcessInfo,
+) -> RunDebugConfig {
+    let program = process
+        .cmdline
+        .split_whitespace()
+        .next()
+        .unwrap_or(&process.name);
+    RunDebugConfig {
+        ty: template.ty.clone(),
+        name: format!("Attach to {} ({})", process.name, process.pid),
+        program: program.to_string(),
+        args: None,
+        cwd: template.cwd.clone(),
+        env: None,
+        prelaunch: None,
+        debug_command: None,
+        dap_id: Default::default(),
+        attach_pid: Some(process.pid),
+    }
+}
+
+/// A starting point for a new config
+pub struct RunConfigTemplate {
+    pub name: &'static str,
+    pub ty: Option<&'static str>,
+    pub program: &'static str,
+    pub args: &'static [&'static str],
+    pub prelaunch: Option<(&'static str, &'static [&'static str])>,
+}
+
+pub const TEMPLATES: &[RunConfigTemplate] = &[
+    RunConfigTemplate {
+        name: "Program",
+        ty: None,
+        program: "",
+        args: &[],
+        prelaunch: None,
+    },
+    RunConfigTemplate {
+        name: "Rust (lldb)",
+        ty: Some("lldb"),
+        program: "target/debug/",
+        args: &[],
+        prelaunch: Some(("cargo", &["build"])),
+    },
+    RunConfigTemplate {
+        name: "C/C++ (lldb)",
+        ty: Some("lldb"),
+        program: "",
+        args: &[],
+        prelaunch: Some(("make", &[])),
+    },
+    RunConfigTemplate {
+        name: "Python (debugpy)",
+        ty: Some("debugpy"),
+        program: "python3",
+        args: &["main.py"],
+        prelaunch: None,
+    },
+];
+
+impl RunConfigTemplate {
+    pub fn form(&self, name: String) -> RunConfigForm {
+        let mut form = RunConfigForm::default();
+        form.set(RunConfigField::Name, name);
+        form.set(
+            RunConfigField::Type,
+            self.ty.unwrap_or_default().to_string(),
+        );
+        form.set(RunConfigField::Program, self.program.to_string());
+        form.set(RunConfigField::Args, self.args.join(" "));
+        form.set(RunConfigField::Cwd, "${workspace}".to_string());
+        if let Some((program, args)) = self.prelaunch {
+            form.set(RunConfigField::PrelaunchProgram, program.to_string());
+            form.set(RunConfigField::PrelaunchArgs, args.join(" "));
+        }
+        form
+    }
+}
+
+/// Split the arguments at whitespace, except in quotes
+pub fn split_args(text: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => {
+                args.extend(arg.take());
+            }
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None | Some('"'), '\\') => {
+                let arg = arg.get_or_insert_with(String::new);
+                arg.extend(chars.next());
+            }
+            (_, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(quote) = quote {
+        return Err(format!("A {quote} isn't closed"));
+    }
+    args.extend(arg);
+    Ok(args)
+}
+
+fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if !arg.is_empty()
+                && !arg.contains(|c: char| {
+                    c.is_whitespace() || c == '"' || c == '\'' || c == '\\'
+                })
+            {
+                return arg.clone();
+            }
+            let arg = arg.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("\"{arg}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse `NAME=VALUE` pairs, split like the arguments
+fn parse_env(text: &str) -> Result<HashMap<String, String>, String> {
+    split_args(text)?
+        .into_iter()
+        .map(|var| match var.split_once('=') {
+            Some((name, value)) if !name.is_empty() => {
+                Ok((name.to_string(), value.to_string()))
+            }
+            _ => Err(format!("{var} isn't NAME=VALUE")),
+        })
+        .collect()
+}
+
+fn format_env(env: &HashMap<String, String>) -> String {
+    let mut vars: Vec<_> = env.iter().collect();
+    vars.sort();
+    let vars: Vec<String> = vars
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+    join_args(&vars)
+}
+
+/// The text of run.toml with its configs replaced. The comments of the file
+/// and of each config that is still there are kept.
+pub fn write_configs(text: &str, configs: &[RunDebugConfig]) -> Result<String> {
+    let mut document: toml_edit::Document = text.parse()?;
+    let new: toml_edit::Document = toml::to_string(&RunDebugConfigs {
+        configs: configs.to_vec(),
+    })?
+    .parse()?;
+    let mut new_configs = new
+        .get("configs")
+        .and_then(|item| item.as_array_of_tables())
+        .cloned()
+        .unwrap_or_default();
+
+    if let Some(old_configs) = document
+        .get("configs")
+        .and_then(|item| item.as_array_of_tables())
+    {
+        for (old, new) in old_configs.iter().zip(new_configs.iter_mut()) {
+            *new.decor_mut() = old.decor().clone();
+        }
+    }
+    for config in new_configs.iter_mut() {
+        if let Some(env) = config.get_mut("env").and_then(|e| e.as_table_mut()) {
+            env.sort_values();
+        }
+    }
+    document.insert("configs", toml_edit::Item::ArrayOfTables(new_configs));
+    Ok(document.to_string())
+}
+
+#[derive(Clone)]
+pub struct RunConfigData {
+    pub forms: RwSignal<Vec<RunConfigForm>>,
+    pub selected: RwSignal<Option<usize>>,
+    /// Why the file couldn't be read, in which case it isn't written either
+    pub error: RwSignal<Option<String>>,
+    pub path: Option<PathBuf>,
+    doc: Option<Rc<Doc>>,
+    pub common: Rc<CommonData>,
+}
+
+impl RunConfigData {
+    pub fn new(cx: Scope, main_split: &MainSplitData) -> Self {
+        let common = main_split.common.clone();
+        let path = common
+            .workspace
+            .path
+            .as_ref()
+            .map(|path| path.join(".lapce").join("run.toml"));
+        let doc = path
+            .as_ref()
+            .map(|path| main_split.get_doc(path.clone(), None).0);
+        let data = Self {
+            forms: cx.create_rw_signal(Vec::new()),
+            selected: cx.create_rw_signal(None),
+            error: cx.create_rw_signal(None),
+            path,
+            doc: doc.clone(),
+            common,
+        };
+
+        if let Some(doc) = doc {
+            let loaded = doc.loaded;
+            let data = data.clone();
+            cx.create_effect(move |prev_loaded| {
+                if prev_loaded == Some(true) {
+                    return true;
+                }
+                let loaded = loaded.get();
+                if loaded {
+                    data.load(&doc.buffer.with_untracked(|b| b.to_string()));
+                }
+                loaded
+            });
+        }
+        data
+    }
+
+    fn load(&self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        match toml::from_str::<RunDebugConfigs>(text) {
+            Ok(configs) => {
+                self.forms.set(
+                    configs
+                        .configs
+                        .iter()
+                        .map(RunConfigForm::from_config)
+                        .collect(),
+                );
+                self.selected
+                    .set((!configs.configs.is_empty()).then_some(0));
+                self.error.set(None);
+            }
+            Err(e) => {
+                self.error.set(Some(format!("run.toml can't be read: {e}")));
+            }
+        }
+    }
+
+    pub fn add(&self, template: &RunConfigTemplate) {
+        let names: Vec<String> = self.forms.with_untracked(|forms| {
+            forms
+                .iter()
+                .map(|form| form.get(RunConfigField::Name).to_string())
+                .collect()
+        });
+        let name = (1..)
+            .map(|i| {
+                if i == 1 {
+                    template.name.to_string()
+                } else {
+                    format!("{} {i}", template.name)
+                }
+            })
+            .find(|name| !names.contains(name))
+            .unwrap_or_default();
+        self.forms.update(|forms| forms.push(template.form(name)));
+        self.selected.set(Some(names.len()));
+    }
+
+    pub fn remove(&self, index: usize) {
+        self.forms.update(|forms| {
+            if index < forms.len() {
+                forms.remove(index);
+            }
+        });
+        let len = self.forms.with_untracked(|forms| forms.len());
+        self.selected
+            .set(len.checked_sub(1).map(|last| index.min(last)));
+    }
+
+    pub fn set_value(&self, index: usize, field: RunConfigField, value: String) {
+        self.forms.update(|forms| {
+            if let Some(form) = forms.get_mut(index) {
+                form.set(field, value);
+            }
+        });
+    }
+
+    /// Write the configs back to run.toml, unless one of them isn't valid
+    pub fn save(&self) {
+        let Some(doc) = self.doc.as_ref() else {
+            return;
+        };
+        if self.error.with_untracked(|e| e.is_some()) {
+            return;
+        }
+        let configs = match self.forms.with_untracked(|forms| validate(forms)) {
+            Ok(configs) => configs,
+            Err((index, _, _)) => {
+                self.selected.set(Some(index));
+                return;
+            }
+        };
+        let text = doc.buffer.with_untracked(|b| b.to_string());
+        match write_configs(&text, &configs) {
+            Ok(text) => {
+                doc.reload(Rope::from(text), false);
+                doc.save(|| {});
+            }
+            Err(e) => {
+                self.error
+                    .set(Some(format!("run.toml can't be written: {e}")));
+            }
+        }
+    }
+}
+
+pub fn run_configs_view(main_split: MainSplitData) -> impl View {
+    let cx = Scope::current();
+    let data = RunConfigData::new(cx, &main_split);
+    let editors = main_split.editors;
+    let config = data.common.config;
+
+    let button = move |s: Style| {
+        let config = config.get();
+        s.justify_center()
+            .padding_horiz(12.0)
+            .padding_vert(6.0)
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(config.color(LapceColor::LAPCE_BORDER))
+            .hover(|s| {
+                s.cursor(CursorStyle::Pointer)
+                    .background(config.color(LapceColor::PANEL_HOVERED_BACKGROUND))
+            })
+            .active(|s| {
+                s.background(
+                    config.color(LapceColor::PANEL_HOVERED_ACTIVE_BACKGROUND),
+                )
+            })
+    };
+
+    let list =
+        {
+            let data = data.clone();
+            let forms = data.forms;
+            let selected = data.selected;
+            stack((
+                dyn_stack(
+                    move || {
+                        forms.with(|forms| {
+                            forms
+                                .iter()
+                                .map(|form| {
+                                    form.get(RunConfigField::Name).to_string()
+                                })
+                                .enumerate()
+                                .collect::<Vec<_>>()
+                        })
+                    },
+                    |(i, name)| (*i, name.clone()),
+                    move |(i, name)| {
+                        label(move || name.clone())
+                            .on_click_stop(move |_| {
+                                selected.set(Some(i));
+                            })
+                            .style(move |s| {
+                                let config = config.get();
+                                s.width_full()
+                                    .padding_horiz(10.0)
+                                    .padding_vert(4.0)
+                                    .text_ellipsis()
+                                    .cursor(CursorStyle::Pointer)
+                                    .apply_if(selected.get() == Some(i), |s| {
+                                        s.background(config.color(
+                                            LapceColor::PANEL_CURRENT_BACKGROUND,
+                                        ))
+                                    })
+                                    .hover(|s| {
+                                        s.background(config.color(
+                                            LapceColor::PANEL_HOVERED_BACKGROUND,
+                                        ))
+                                    })
+                            })
+                    },
+                )
+                .style(|s| s.flex_col().width_full()),
+                label(|| "New Configuration".to_string()).style(move |s| {
+                    s.margin_top(20.0)
+                        .padding_horiz(10.0)
+                        .color(config.get().color(LapceColor::EDITOR_DIM))
+                }),
+                dyn_stack(
+                    || TEMPLATES.iter().enumerate(),
+                    |(i, _)| *i,
+                    move |(_, template)| {
+                        let data = data.clone();
+                        label(move || template.name.to_string())
+                            .on_click_stop(move |_| {
+                                data.add(template);
+                            })
+                            .style(move |s| {
+                                s.width_full()
+                                    .padding_horiz(10.0)
+                                    .padding_vert(4.0)
+                                    .cursor(CursorStyle::Pointer)
+                                    .hover(|s| {
+                                        s.background(config.get().color(
+                                            LapceColor::PANEL_HOVERED_BACKGROUND,
+                                        ))
+                                    })
+                            })
+                    },
+                )
+                .style(|s| s.flex_col().width_full()),
+            ))
+            .style(move |s| {
+                s.flex_col()
+                    .width(220.0)
+                    .height_full()
+                    .padding_vert(10.0)
+                    .border_right(1.0)
+                    .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+            })
+        };
+
+    let form = {
+        let data = data.clone();
+        let forms = data.forms;
+        let selected = data.selected;
+        dyn_container(
+            move || (selected.get(), forms.with(|forms| forms.len())),
+            move |(selected, _)| {
+                let Some(index) = selected else {
+                    return label(|| {
+                        "Select a configuration, or add one from a template"
+                            .to_string()
+                    })
+                    .style(|s| s.padding(20.0))
+                    .into_any();
+                };
+                form_view(data.clone(), editors, index, button).into_any()
+            },
+        )
+        .style(|s| s.flex_grow(1.0).flex_basis(0.0).height_full())
+    };
+
+    let error = data.error;
+    stack((
+        label(move || error.get().unwrap_or_default()).style(move |s| {
+            s.width_full()
+                .padding(10.0)
+                .color(config.get().color(LapceColor::LAPCE_ERROR))
+                .apply_if(error.with(|e| e.is_none()), |s| s.hide())
+        }),
+        stack((
+            list,
+            scroll(form).style(|s| s.flex_grow(1.0).flex_basis(0.0)),
+        ))
+        .style(|s| s.width_full().flex_grow(1.0).flex_basis(0.0)),
+    ))
+    .style(|s| s.flex_col().size_full())
+}
+
+fn form_view(
+    data: RunConfigData,
+    editors: Editors,
+    index: usize,
+    button: impl Fn(Style) -> Style + Copy + 'static,
+) -> impl View {
+    let config = data.common.config;
+    let forms = data.forms;
+    let invalid = create_memo(move |_| forms.with(|forms| validate(forms).err()));
+
+    let field_view = {
+        let data = data.clone();
+        move |field: RunConfigField| {
+            let cx = Scope::current();
+            let value = forms.with_untracked(|forms| {
+                forms
+                    .get(index)
+                    .map(|form| form.get(field).to_string())
+                    .unwrap_or_default()
+            });
+            let text_input_view = TextInputBuilder::new()
+                .value(value)
+                .build(cx, editors, data.common.clone())
+                .placeholder(move || field.placeholder().to_string());
+            let doc = text_input_view.doc_signal();
+            let data = data.clone();
+            create_effect(move |last| {
+                let doc = doc.get_untracked();
+                let rev = doc.buffer.with(|b| b.rev());
+                if last.is_some() && last != Some(rev) {
+                    let value = doc.buffer.with_untracked(|b| b.to_string());
+                    data.set_value(index, field, value);
+                }
+                rev
+            });
+
+            let error = move || {
+                invalid.with(|invalid| match invalid {
+                    Some((i, f, e)) if *i == index && *f == field => Some(e.clone()),
+                    _ => None,
+                })
+            };
+            stack((
+                label(move || field.label().to_string())
+                    .style(|s| s.font_bold().line_height(1.6)),
+                text_input_view.keyboard_navigatable().style(move |s| {
+                    s.width(500.0)
+                        .border(1.0)
+                        .border_radius(6.0)
+                        .border_color(config.get().color(LapceColor::LAPCE_BORDER))
+                }),
+                label(move || error().unwrap_or_default()).style(move |s| {
+                    s.line_height(1.6)
+                        .color(config.get().color(LapceColor::LAPCE_ERROR))
+                        .apply_if(error().is_none(), |s| s.hide())
+                }),
+            ))
+            .style(|s| s.flex_col().margin_bottom(12.0))
+        }
+    };
+
+    let path = data.path.clone();
+    let internal_command = data.common.internal_command;
+    stack((
+        dyn_stack(move || RunConfigField::ALL, |field| *field, field_view)
+            .style(|s| s.flex_col()),
+        stack((
+            {
+                let data = data.clone();
+                text("Save")
+                    .on_click_stop(move |_| {
+                        data.save();
+                    })
+                    .style(button)
+            },
+            text("Delete")
+                .on_click_stop(move |_| {
+                    data.remove(index);
+                })
+                .style(move |s| button(s).margin_left(10.0)),
+            text("Open run.toml")
+                .on_click_stop(move |_| {
+                    if let Some(path) = path.clone() {
+                        internal_command.send(InternalCommand::OpenFile { path });
+                    }
+                })
+                .style(move |s| button(s).margin_left(10.0)),
+        ))
+        .style(|s| s.margin_top(8.0)),
+    ))
+    .style(|s| s.flex_col().padding(20.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::dap_types::ProcessInfo;
+
+    use super::{
+        attach_config, join_args, parse_env, split_args, validate, write_configs,
+        RunConfigField, RunConfigForm, TEMPLATES,
+    };
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args(r#"a "b c" 'd "e"' f\ g"#).unwrap(),
+            vec!["a", "b c", "d \"e\"", "f g"]
+        );
+        assert_eq!(split_args(r#"x """#).unwrap(), vec!["x", ""]);
+        assert!(split_args("\"open").is_err());
+
+        let args = vec!["plain".to_string(), "with space".to_string()];
+        assert_eq!(split_args(&join_args(&args)).unwrap(), args);
+        assert!(parse_env("A=1 B").is_err());
+        assert_eq!(parse_env("A=\"x y\"").unwrap()["A"], "x y");
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut form = TEMPLATES[1].form("app".to_string());
+        assert_eq!(
+            validate(&[form.clone(), form.clone()]).unwrap_err().1,
+            RunConfigField::Name
+        );
+        form.set(RunConfigField::Program, String::new());
+        assert_eq!(form.to_config().unwrap_err().0, RunConfigField::Program);
+
+        form.set(RunConfigField::Program, "target/debug/app".to_string());
+        let config = form.to_config().unwrap();
+        assert_eq!(config.ty.as_deref(), Some("lldb"));
+        assert_eq!(RunConfigForm::from_config(&config), form);
+    }
+
+    #[test]
+    fn test_write_configs() {
+        let text =
+            "# The configs\n\n[[configs]]\n# first\nname = \"a\"\nprogram = \"x\"\n";
+        let mut form = TEMPLATES[0].form("b".to_string());
+        form.set(RunConfigField::Program, "y".to_string());
+        form.set(RunConfigField::Env, "B=2 A=1".to_string());
+        let written = write_configs(text, &[form.to_config().unwrap()]).unwrap();
+        assert!(written.starts_with("# The configs\n\n[[configs]]\n# first\n"));
+        assert!(written.contains("name = \"b\""));
+        assert!(!written.contains("name = \"a\""));
+        assert!(written.find("A = ").unwrap() < written.find("B = ").unwrap());
+    }
+
+    #[test]
+    fn test_attach_config() {
+        let template = TEMPLATES[1].form("Rust".to_string()).to_config().unwrap();
+        let process = ProcessInfo {
+            pid: 4242,
+            name: "server".to_string(),
+            cmdline: "/srv/bin/server --port 80".to_string(),
+        };
+        let config = attach_config(&template, &process);
+        assert_eq!(config.ty.as_deref(), Some("lldb"));
+        assert_eq!(config.name, "Attach to server (4242)");
+        assert_eq!(config.program, "/srv/bin/server");
+        assert_eq!(config.cwd.as_deref(), Some("${workspace}"));
+        assert_eq!(config.attach_pid, Some(4242));
+        assert!(config.prelaunch.is_none());
+        assert_ne!(config.dap_id, template.dap_id);
+
+        let process = ProcessInfo {
+            pid: 7,
+            name: "app.exe".to_string(),
+            cmdline: String::new(),
+        };
+        assert_eq!(attach_config(&template, &process).program, "app.exe");
+    }
+}
@@ -21,16 +21,23 @@
 };
 use indexmap::IndexMap;
 use inflector::Inflector;
+use itertools::Itertools;
 use lapce_core::mode::Mode;
 use lapce_rpc::plugin::VoltID;
 use lapce_xi_rope::Rope;
 use serde::Serialize;
+use strum::VariantNames;
 
 use crate::{
     command::CommandExecuted,
     config::{
-        color::LapceColor, core::CoreConfig, editor::EditorConfig, icon::LapceIcons,
-        terminal::TerminalConfig, ui::UIConfig, DropdownInfo, LapceConfig,
+        color::LapceColor,
+        core::CoreConfig,
+        editor::EditorConfig,
+        icon::LapceIcons,
+        terminal::{TerminalConfig, TerminalCwd, TerminalProfile, PROFILE_ICONS},
+        ui::UIConfig,
+        DropdownInfo, LapceConfig,
     },
     keypress::KeyPressFocus,
     main_split::Editors,
@@ -46,6 +53,8 @@ pub enum SettingsValue {
     String(String),
     Bool(bool),
     Dropdown(DropdownInfo),
+    /// Edited as the items separated by spaces
+    List(Vec<String>),
     Empty,
 }
 
@@ -219,6 +228,48 @@ fn into_settings_map(
             }
         }
 
+        let profiles: BTreeMap<_, _> = config.terminal.profiles.iter().collect();
+        for (profile_name, profile) in profiles {
+            let kind = format!("Terminal Profile: {profile_name}");
+            let pos = cx.create_rw_signal(Point::new(0.0, item_height_accum));
+            items.push_back(SettingsItem {
+                kind: kind.clone(),
+                name: "".to_string(),
+                field: "".to_string(),
+                filter_text: "".to_string(),
+                description: "".to_string(),
+                value: SettingsValue::Empty,
+                pos,
+                size: cx.create_rw_signal(Size::ZERO),
+                header: true,
+            });
+            kinds.push_back((kind.clone(), pos));
+            for (name, desc) in TerminalProfile::FIELDS
+                .iter()
+                .zip(TerminalProfile::DESCS.iter())
+            {
+                let field = name.replace('_', "-");
+                let value = terminal_profile_value(profile, &field);
+                let name =
+                    format!("{kind}: {}", name.replace('_', " ").to_title_case());
+                let filter_text = format!("{name} {desc}").to_lowercase();
+                let filter_text =
+                    format!("{filter_text}{}", filter_text.replace(' ', ""));
+                items.push_back(SettingsItem {
+                    kind: format!("terminal.profiles.{profile_name}"),
+                    name,
+                    field,
+                    filter_text,
+                    description: desc.to_string(),
+                    value,
+                    pos: cx.create_rw_signal(Point::ZERO),
+                    size: cx.create_rw_signal(Size::ZERO),
+                    header: false,
+                });
+                item_height_accum += 50.0;
+            }
+        }
+
         let plugin_items = cx.create_rw_signal(im::Vector::new());
         let plugin_kinds = cx.create_rw_signal(im::Vector::new());
 
@@ -521,6 +572,52 @@ pub fn settings_view(
     .debug_name("Settings")
 }
 
+/// The value of a field of a terminal profile, in the settings editor
+fn terminal_profile_value(profile: &TerminalProfile, field: &str) -> SettingsValue {
+    let string = |value: Option<&str>| {
+        SettingsValue::String(value.unwrap_or_default().to_string())
+    };
+    match field {
+        "command" => string(profile.command.as_deref()),
+        "arguments" => {
+            SettingsValue::List(profile.arguments.clone().unwrap_or_default())
+        }
+        "workdir" => SettingsValue::String(
+            profile
+                .workdir
+                .as_ref()
+                .map(|workdir| workdir.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        ),
+        "environment" => SettingsValue::List(
+            profile
+                .environment
+                .iter()
+                .flatten()
+                .map(|(key, value)| format!("{key}={value}"))
+                .sorted()
+                .collect(),
+        ),
+        "cwd" => SettingsValue::Dropdown(DropdownInfo {
+            active_index: profile.cwd as usize,
+            items: TerminalCwd::VARIANTS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }),
+        "icon" => SettingsValue::Dropdown(DropdownInfo {
+            active_index: profile
+                .icon
+                .as_deref()
+                .and_then(|icon| PROFILE_ICONS.iter().position(|i| *i == icon))
+                .unwrap_or(0),
+            items: PROFILE_ICONS.iter().map(|s| s.to_string()).collect(),
+        }),
+        "color" => string(profile.color.as_deref()),
+        _ => SettingsValue::Empty,
+    }
+}
+
 fn settings_item_view(
     editors: Editors,
     settings_data: SettingsData,
@@ -540,6 +637,7 @@ fn settings_item_view(
         SettingsValue::Float(n) => Some(n.to_string()),
         SettingsValue::Integer(n) => Some(n.to_string()),
         SettingsValue::String(s) => Some(s.to_string()),
+        SettingsValue::List(items) => Some(items.join(" ")),
         SettingsValue::Bool(_) => None,
         SettingsValue::Dropdown(_) => None,
         SettingsValue::Empty => None,
@@ -595,6 +693,16 @@ fn settings_item_view(
                                     ).ok()
                                             })
                                         }
+                                        SettingsValue::List(_) => {
+                                            serde::Serialize::serialize(
+                                                &value
+                                                    .split_whitespace()
+                                                    .collect::<Vec<_>>(),
+                                                toml_edit::ser::ValueSerializer::new(
+                                                ),
+                                            )
+                                            .ok()
+                                        }
                                         _ => serde::Serialize::serialize(
                                             &value,
                                             toml_edit::ser::ValueSerializer::new(),
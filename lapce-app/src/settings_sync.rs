@@ -0,0 +1,300 @@
+//! Moving the user's configuration between machines.
+//!
+//! A [`SettingsBundle`] holds the settings, keymaps, themes and the list of
+//! installed plugins. It can be exported to and imported from a single file,
+//! or kept in sync through a git repository set in
+//! `core.settings-sync-repository`, where it is stored as separate files so
+//! that changes from different machines merge line by line.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use anyhow::{anyhow, Context, Result};
+use lapce_core::directory::Directory;
+use lapce_rpc::plugin::VoltID;
+use serde::{Deserialize, Serialize};
+
+use crate::config::LapceConfig;
+
+const BUNDLE_VERSION: u32 = 1;
+
+const SETTINGS_FILE: &str = "settings.toml";
+const KEYMAPS_FILE: &str = "keymaps.toml";
+const THEMES_DIR: &str = "themes";
+const PLUGINS_FILE: &str = "plugins.json";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub settings: String,
+    pub keymaps: String,
+    /// The contents of the theme files, by file name
+    pub themes: BTreeMap<String, String>,
+    pub plugins: Vec<VoltID>,
+}
+
+impl SettingsBundle {
+    /// Gather the current configuration of this machine.
+    pub fn collect(plugins: Vec<VoltID>) -> Result<Self> {
+        let settings = LapceConfig::settings_file()
+            .map(|path| std::fs::read_to_string(path).unwrap_or_default())
+            .unwrap_or_default();
+        let keymaps = LapceConfig::keymaps_file()
+            .map(|path| std::fs::read_to_string(path).unwrap_or_default())
+            .unwrap_or_default();
+        let themes = match Directory::themes_directory() {
+            Some(dir) => read_themes(&dir)?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            settings,
+            keymaps,
+            themes,
+            plugins,
+        })
+    }
+
+    /// Write the bundle over the current configuration, keeping a `.bak` copy
+    /// of the files that are replaced. The config watcher picks up the
+    /// changes. Plugins are left to the caller.
+    pub fn apply(&self) -> Result<()> {
+        if let Some(path) = LapceConfig::settings_file() {
+            replace_file(&path, &self.settings)?;
+        }
+        if let Some(path) = LapceConfig::keymaps_file() {
+            replace_file(&path, &self.keymaps)?;
+        }
+        if let Some(dir) = Directory::themes_directory() {
+            for (name, content) in &self.themes {
+                replace_file(&dir.join(theme_file_name(name)?), content)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self> {
+        let bundle: Self = serde_json::from_str(s)?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(anyhow!(
+                "the settings were exported by a newer version of Lapce"
+            ));
+        }
+        Ok(bundle)
+    }
+
+    /// Store the bundle as separate files in the folder.
+    pub fn write_dir(&self, dir: &Path) -> Result<()> {
+        std::fs::write(dir.join(SETTINGS_FILE), &self.settings)?;
+        std::fs::write(dir.join(KEYMAPS_FILE), &self.keymaps)?;
+
+        let themes_dir = dir.join(THEMES_DIR);
+        let _ = std::fs::remove_dir_all(&themes_dir);
+        if !self.themes.is_empty() {
+            std::fs::create_dir_all(&themes_dir)?;
+            for (name, content) in &self.themes {
+                std::fs::write(themes_dir.join(theme_file_name(name)?), content)?;
+            }
+        }
+
+        let mut plugins = self.plugins.clone();
+        plugins.sort_by_key(|id| id.to_string());
+        std::fs::write(
+            dir.join(PLUGINS_FILE),
+            serde_json::to_string_pretty(&plugins)?,
+        )?;
+        Ok(())
+    }
+
+    /// Read a bundle stored with [`SettingsBundle::write_dir`].
+    pub fn read_dir(dir: &Path) -> Result<Self> {
+        let read =
+            |name: &str| std::fs::read_to_string(dir.join(name)).unwrap_or_default();
+        let themes_dir = dir.join(THEMES_DIR);
+        let themes = if themes_dir.is_dir() {
+            read_themes(&themes_dir)?
+        } else {
+            BTreeMap::new()
+        };
+        let plugins = read(PLUGINS_FILE);
+        let plugins = if plugins.trim().is_empty() {
+            Vec::new()
+        } else {
+            serde_json::from_str(&plugins)?
+        };
+
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            settings: read(SETTINGS_FILE),
+            keymaps: read(KEYMAPS_FILE),
+            themes,
+            plugins,
+        })
+    }
+}
+
+/// What to do when the local and the repository settings both changed the
+/// same lines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    KeepLocal,
+    UseRepository,
+}
+
+pub enum SyncOutcome {
+    /// The merged settings, which have been pushed to the repository
+    Synced(SettingsBundle),
+    /// Both sides changed the same settings, and nothing was changed. Sync
+    /// again with a [`ConflictResolution`].
+    Conflict,
+}
+
+/// Merge the local settings with the ones in the repository, and push the
+/// result. This runs `git`, so it blocks.
+pub fn sync(
+    repository: &str,
+    local: &SettingsBundle,
+    resolution: Option<ConflictResolution>,
+) -> Result<SyncOutcome> {
+    let dir = Directory::settings_sync_directory()
+        .ok_or_else(|| anyhow!("can't get the settings sync folder"))?;
+
+    if !dir.join(".git").exists() {
+        let _ = std::fs::remove_dir_all(&dir);
+        git(None, &["clone", repository, &dir.to_string_lossy()])?;
+    }
+
+    local.write_dir(&dir)?;
+    git(Some(&dir), &["add", "-A"])?;
+    let status = git(Some(&dir), &["status", "--porcelain"])?;
+    if !status.stdout.is_empty() {
+        git(Some(&dir), &["commit", "-m", "Update settings"])?;
+    }
+
+    // An empty repository has nothing to merge with yet
+    let remote = git(Some(&dir), &["ls-remote", "--heads", "origin"])?;
+    if !remote.stdout.is_empty() {
+        let mut args = vec!["pull", "--no-rebase", "--no-edit"];
+        match resolution {
+            Some(ConflictResolution::KeepLocal) => args.extend(["-X", "ours"]),
+            Some(ConflictResolution::UseRepository) => args.extend(["-X", "theirs"]),
+            None => {}
+        }
+        if let Err(e) = git(Some(&dir), &args) {
+            let conflicts =
+                git(Some(&dir), &["diff", "--name-only", "--diff-filter=U"])?;
+            if conflicts.stdout.is_empty() {
+                return Err(e);
+            }
+            git(Some(&dir), &["merge", "--abort"])?;
+            return Ok(SyncOutcome::Conflict);
+        }
+    }
+
+    let merged = SettingsBundle::read_dir(&dir)?;
+    git(Some(&dir), &["push", "-u", "origin", "HEAD"])?;
+    Ok(SyncOutcome::Synced(merged))
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<Output> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = cmd
+        .args(args)
+        .output()
+        .context("failed to run git, is it installed?")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output)
+}
+
+fn read_themes(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut themes = BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            themes.insert(name.to_string(), std::fs::read_to_string(&path)?);
+        }
+    }
+    Ok(themes)
+}
+
+/// Theme names come from the bundle, so they mustn't be able to point outside
+/// of the themes folder
+fn theme_file_name(name: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(name);
+    if path.components().count() != 1 || path.file_name().is_none() {
+        return Err(anyhow!("invalid theme file name {name}"));
+    }
+    Ok(path)
+}
+
+fn replace_file(path: &Path, content: &str) -> Result<()> {
+    if let Ok(current) = std::fs::read_to_string(path) {
+        if current == content {
+            return Ok(());
+        }
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        std::fs::write(backup, current)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::plugin::VoltID;
+
+    use super::{theme_file_name, SettingsBundle, BUNDLE_VERSION};
+
+    #[test]
+    fn test_json_round_trip() {
+        let bundle = SettingsBundle {
+            version: BUNDLE_VERSION,
+            settings: "[core]\nmodal = true\n".to_string(),
+            keymaps: String::new(),
+            themes: [("mine.toml".to_string(), "[theme]\n".to_string())]
+                .into_iter()
+                .collect(),
+            plugins: vec![VoltID {
+                author: "lapce".to_string(),
+                name: "lapce-rust".to_string(),
+            }],
+        };
+        let json = bundle.to_json().unwrap();
+        assert_eq!(SettingsBundle::from_json(&json).unwrap(), bundle);
+
+        let newer = SettingsBundle {
+            version: BUNDLE_VERSION + 1,
+            ..bundle
+        };
+        assert!(SettingsBundle::from_json(&newer.to_json().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_theme_file_name() {
+        assert!(theme_file_name("mine.toml").is_ok());
+        assert!(theme_file_name("../settings.toml").is_err());
+        assert!(theme_file_name("/etc/passwd").is_err());
+        assert!(theme_file_name("").is_err());
+    }
+}
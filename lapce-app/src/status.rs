@@ -38,6 +38,7 @@ pub fn status(
     let editor = window_tab_data.main_split.active_editor;
     let panel = window_tab_data.panel.clone();
     let palette = window_tab_data.palette.clone();
+    let profile = window_tab_data.common.profile;
     let diagnostic_count = create_memo(move |_| {
         let mut errors = 0;
         let mut warnings = 0;
@@ -69,6 +70,12 @@ pub fn status(
     };
 
     let progresses = window_tab_data.progresses;
+    let macros = window_tab_data
+        .common
+        .keypress
+        .with_untracked(|keypress| keypress.macros);
+    let recording =
+        create_memo(move |_| macros.with(|macros| macros.is_recording()));
     let mode = create_memo(move |_| window_tab_data.mode());
     let pointer_down = floem::reactive::create_rw_signal(false);
 
@@ -122,6 +129,29 @@ pub fn status(
                     .align_items(Some(AlignItems::Center))
                     .selectable(false)
             }),
+            label(|| "Recording Macro".to_string())
+                .on_click_stop(move |_| {
+                    workbench_command
+                        .send(LapceWorkbenchCommand::StopMacroRecording);
+                })
+                .style(move |s| {
+                    let config = config.get();
+                    s.display(if recording.get() {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    })
+                    .padding_horiz(10.0)
+                    .color(config.color(LapceColor::LAPCE_ERROR))
+                    .height_pct(100.0)
+                    .align_items(Some(AlignItems::Center))
+                    .selectable(false)
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                }),
             stack((
                 svg(move || config.get().ui_svg(LapceIcons::SCM)).style(move |s| {
                     let config = config.get();
@@ -317,6 +347,30 @@ pub fn status(
                 .color(config.get().color(LapceColor::STATUS_FOREGROUND))
         }),
         stack({
+            let palette_clone = palette.clone();
+            let profile_info = label(move || {
+                profile
+                    .get()
+                    .map(|name| format!("Profile: {name}"))
+                    .unwrap_or_default()
+            })
+            .on_click_stop(move |_| {
+                palette_clone.run(PaletteKind::Profile);
+            })
+            .style(move |s| {
+                let config = config.get();
+                s.apply_if(profile.with(|profile| profile.is_none()), |s| s.hide())
+                    .height_full()
+                    .padding_horiz(10.0)
+                    .items_center()
+                    .color(config.color(LapceColor::STATUS_FOREGROUND))
+                    .hover(|s| {
+                        s.cursor(CursorStyle::Pointer).background(
+                            config.color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                    .selectable(false)
+            });
             let palette_clone = palette.clone();
             let cursor_info = status_text(config, editor, move || {
                 if let Some(editor) = editor.get() {
@@ -378,7 +432,7 @@ pub fn status(
             .on_click_stop(move |_| {
                 palette_clone.run(PaletteKind::Language);
             });
-            (cursor_info, line_ending_info, language_info)
+            (profile_info, cursor_info, line_ending_info, language_info)
         })
         .style(|s| {
             s.height_pct(100.0)
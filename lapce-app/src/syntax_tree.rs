@@ -0,0 +1,98 @@
+//! The state of the syntax tree panel, the tree-sitter tree of the active
+//! document and a query tested against it, for working on grammars and
+//! queries.
+
+use std::collections::HashSet;
+
+use floem::reactive::{RwSignal, Scope};
+use lapce_core::syntax::inspect::{QueryCaptureRow, SyntaxTreeRow};
+
+#[derive(Clone)]
+pub struct SyntaxTreeData {
+    /// The paths of the nodes whose children are shown
+    pub expanded: RwSignal<HashSet<Vec<usize>>>,
+}
+
+impl SyntaxTreeData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            expanded: cx.create_rw_signal(HashSet::from([Vec::new()])),
+        }
+    }
+
+    pub fn toggle(&self, path: &[usize]) {
+        self.expanded.update(|expanded| {
+            if !expanded.remove(path) {
+                expanded.insert(path.to_vec());
+            }
+        });
+    }
+
+    /// Expand the ancestors of a node for it to be shown
+    pub fn reveal(&self, path: &[usize]) {
+        let hidden = self.expanded.with_untracked(|expanded| {
+            (0..path.len()).any(|i| !expanded.contains(&path[..i]))
+        });
+        if hidden {
+            self.expanded.update(|expanded| {
+                for i in 0..path.len() {
+                    expanded.insert(path[..i].to_vec());
+                }
+            });
+        }
+    }
+}
+
+/// The text of a node in the panel, such as `name: identifier [3:5 - 3:9]`,
+/// with the lines and the columns counted from one
+pub fn node_label(row: &SyntaxTreeRow) -> String {
+    let (start_line, start_col) = row.start;
+    let (end_line, end_col) = row.end;
+    let position = format!(
+        "[{}:{} - {}:{}]",
+        start_line + 1,
+        start_col + 1,
+        end_line + 1,
+        end_col + 1
+    );
+    match row.field {
+        Some(field) => format!("{field}: {} {position}", row.kind),
+        None => format!("{} {position}", row.kind),
+    }
+}
+
+/// The text of a capture in the panel, such as `@function 3:5`
+pub fn capture_label(capture: &QueryCaptureRow) -> String {
+    let (line, col) = capture.start;
+    format!("@{} {}:{}", capture.name, line + 1, col + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::syntax::inspect::{QueryCaptureRow, SyntaxTreeRow};
+
+    use super::{capture_label, node_label};
+
+    #[test]
+    fn test_labels() {
+        let mut row = SyntaxTreeRow {
+            path: vec![0, 1],
+            kind: "identifier",
+            field: Some("name"),
+            range: 10..14,
+            start: (2, 4),
+            end: (2, 8),
+            has_children: false,
+        };
+        assert_eq!(node_label(&row), "name: identifier [3:5 - 3:9]");
+        row.field = None;
+        assert_eq!(node_label(&row), "identifier [3:5 - 3:9]");
+
+        let capture = QueryCaptureRow {
+            name: "function".to_string(),
+            range: 10..14,
+            start: (2, 4),
+        };
+        assert_eq!(capture_label(&capture), "@function 3:5");
+    }
+}
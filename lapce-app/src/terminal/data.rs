@@ -10,6 +10,7 @@
 use anyhow::anyhow;
 use floem::{
     keyboard::{Key, KeyEvent, Modifiers, NamedKey},
+    kurbo::Vec2,
     reactive::{RwSignal, Scope},
     views::editor::text::SystemClipboard,
 };
@@ -44,6 +45,8 @@ pub struct TerminalData {
     pub term_id: TermId,
     pub workspace: Arc<LapceWorkspace>,
     pub title: RwSignal<String>,
+    /// The name of the profile it was launched with
+    pub profile: Option<String>,
     pub launch_error: RwSignal<Option<String>>,
     pub mode: RwSignal<Mode>,
     pub visual_mode: RwSignal<VisualMode>,
@@ -330,6 +333,7 @@ pub fn new_run_debug(
         };
 
         let launch_error = cx.create_rw_signal(None);
+        let profile_name = profile.as_ref().map(|profile| profile.name.clone());
 
         let raw = Self::new_raw_terminal(
             &workspace,
@@ -351,6 +355,7 @@ pub fn new_run_debug(
             workspace,
             raw,
             title,
+            profile: profile_name,
             run_debug,
             mode,
             visual_mode,
@@ -373,17 +378,15 @@ fn new_raw_terminal(
             common.term_notification_tx.clone(),
         )));
 
-        let mut profile = profile.unwrap_or_default();
-
-        if profile.workdir.is_none() {
-            profile.workdir = if let Ok(path) = url::Url::from_file_path(
-                workspace.path.as_ref().cloned().unwrap_or_default(),
-            ) {
-                Some(path)
-            } else {
-                None
-            };
-        }
+        // A profile has its directory already, which is left empty for the
+        // home folder
+        let mut profile = profile.unwrap_or_else(|| TerminalProfile {
+            workdir: workspace
+                .path
+                .as_ref()
+                .and_then(|path| workspace.path_url(path)),
+            ..Default::default()
+        });
 
         let exp_run_debug = run_debug
             .as_ref()
@@ -407,6 +410,9 @@ fn new_raw_terminal(
             None
         });
 
+        // A process that is attached to is running already, so the terminal
+        // is a shell
+        let attach = run_debug.is_some_and(|r| r.config.attach_pid.is_some());
         if let Some(run_debug) = exp_run_debug {
             if let Some(work_dir) = run_debug.work_dir {
                 profile.workdir = Some(work_dir);
@@ -414,8 +420,10 @@ fn new_raw_terminal(
 
             profile.environment = run_debug.env;
 
-            profile.command = Some(run_debug.program);
-            profile.arguments = run_debug.args;
+            if !attach {
+                profile.command = Some(run_debug.program);
+                profile.arguments = run_debug.args;
+            }
         }
 
         {
@@ -619,6 +627,7 @@ macro_rules! term_sequence {
 
     pub fn wheel_scroll(&self, delta: f64) {
         let config = self.common.config.get_untracked();
+        let delta = config.editor.wheel_delta(Vec2::new(0.0, delta), false).y;
         let step = config.terminal_line_height() as f64;
         let raw = self.raw.get_untracked();
         let mut raw = raw.write();
@@ -1,11 +1,19 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use lapce_rpc::terminal::TermId;
 use parking_lot::RwLock;
 
 use super::raw::RawTerminal;
 
+/// Terminal output is painted at most this often, about once per frame of a
+/// display, however fast it comes
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
 /// The notifications for terminals to send back to main thread
 pub enum TermNotification {
     SetTitle { term_id: TermId, title: String },
@@ -23,16 +31,20 @@ pub fn terminal_update_process(
     term_notification_tx: Sender<TermNotification>,
 ) {
     let mut terminals = HashMap::new();
-    let mut last_redraw = Instant::now();
-    let mut last_event = None;
+    let mut paints = PaintCoalescer::new(FRAME_INTERVAL);
     loop {
-        let (term_id, event) = if let Some((term_id, event)) = last_event.take() {
-            (term_id, event)
-        } else {
-            match receiver.recv() {
-                Ok((term_id, event)) => (term_id, event),
-                Err(_) => return,
-            }
+        if paints.flush(Instant::now()) {
+            let _ = term_notification_tx.send(TermNotification::RequestPaint);
+        }
+        let received = match paints.wait(Instant::now()) {
+            Some(timeout) => receiver.recv_timeout(timeout),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let (term_id, event) = match received {
+            Ok((term_id, event)) => (term_id, event),
+            // The output that came during the frame is painted above
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
         };
         match event {
             TermEvent::CloseTerminal => {
@@ -46,15 +58,7 @@ pub fn terminal_update_process(
                     {
                         raw.write().update_content(content);
                     }
-                    last_event = receiver.try_recv().ok();
-                    if last_event.is_some() {
-                        if last_redraw.elapsed().as_millis() > 10 {
-                            last_redraw = Instant::now();
-                            let _ = term_notification_tx
-                                .send(TermNotification::RequestPaint);
-                        }
-                    } else {
-                        last_redraw = Instant::now();
+                    if paints.changed(Instant::now()) {
                         let _ = term_notification_tx
                             .send(TermNotification::RequestPaint);
                     }
@@ -63,3 +67,89 @@ pub fn terminal_update_process(
         }
     }
 }
+
+/// Limits the paints of terminal output to one per interval. The first change
+/// is painted right away, and the ones that follow in the same interval are
+/// painted together at its end.
+struct PaintCoalescer {
+    interval: Duration,
+    last_paint: Option<Instant>,
+    /// Whether there are changes that weren't painted yet
+    pending: bool,
+}
+
+impl PaintCoalescer {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_paint: None,
+            pending: false,
+        }
+    }
+
+    /// Whether the content that just changed is to be painted now
+    fn changed(&mut self, now: Instant) -> bool {
+        match self.last_paint {
+            Some(last) if now.duration_since(last) < self.interval => {
+                self.pending = true;
+                false
+            }
+            _ => {
+                self.last_paint = Some(now);
+                self.pending = false;
+                true
+            }
+        }
+    }
+
+    /// How long until the pending changes are to be painted, `None` if there
+    /// are none
+    fn wait(&self, now: Instant) -> Option<Duration> {
+        let last = self.last_paint?;
+        self.pending
+            .then(|| (last + self.interval).saturating_duration_since(now))
+    }
+
+    /// Whether the pending changes are to be painted now, their interval
+    /// being over
+    fn flush(&mut self, now: Instant) -> bool {
+        if self.wait(now) == Some(Duration::ZERO) {
+            self.last_paint = Some(now);
+            self.pending = false;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::PaintCoalescer;
+
+    #[test]
+    fn test_paint_coalescer() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut paints = PaintCoalescer::new(Duration::from_millis(16));
+        assert_eq!(paints.wait(at(0)), None);
+
+        // The first change is painted right away
+        assert!(paints.changed(at(0)));
+        assert_eq!(paints.wait(at(0)), None);
+
+        // The next ones wait for the end of the interval
+        assert!(!paints.changed(at(5)));
+        assert!(!paints.changed(at(10)));
+        assert_eq!(paints.wait(at(10)), Some(Duration::from_millis(6)));
+        assert!(!paints.flush(at(10)));
+        assert!(paints.flush(at(16)));
+        assert_eq!(paints.wait(at(16)), None);
+        assert!(!paints.flush(at(30)));
+
+        // A change in a later interval is painted right away
+        assert!(paints.changed(at(40)));
+    }
+}
@@ -2,5 +2,6 @@
 pub mod event;
 pub mod panel;
 pub mod raw;
+pub mod shell_integration;
 pub mod tab;
 pub mod view;
@@ -7,9 +7,10 @@
 use lapce_core::mode::Mode;
 use lapce_rpc::{
     dap_types::{
-        self, DapId, RunDebugConfig, StackFrame, Stopped, ThreadId, Variable,
+        self, DapId, Output, RunDebugConfig, StackFrame, Stopped, ThreadId, Variable,
     },
     proxy::ProxyResponse,
+    rich_output::RichOutput,
     terminal::{TermId, TerminalProfile},
 };
 
@@ -19,6 +20,7 @@
         DapData, DapVariable, RunDebugData, RunDebugMode, RunDebugProcess,
         ScopeOrVar,
     },
+    debug_console::ConsoleCategory,
     id::TerminalTabId,
     keypress::{EventRef, KeyPressData, KeyPressFocus, KeyPressHandle},
     panel::kind::PanelKind,
@@ -594,6 +596,7 @@ pub fn dap_continued(&self, dap_id: &DapId) {
         if let Some(dap) = dap {
             dap.thread_id.set(None);
             dap.stopped.set(false);
+            dap.frame_id.set(None);
         }
     }
 
@@ -610,10 +613,32 @@ pub fn dap_stopped(
             .with_untracked(|daps| daps.get(dap_id).cloned());
         if let Some(dap) = dap {
             dap.stopped(self.cx, stopped, stack_frames, variables);
+            // Show the session that stopped, when several are running
+            if self.debug.active_term.get_untracked() != Some(dap.term_id) {
+                self.debug.active_term.set(Some(dap.term_id));
+            }
         }
         floem::action::focus_window();
     }
 
+    /// Print what the program or its adapter output in the debug console
+    pub fn dap_output(&self, dap_id: &DapId, output: &Output) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            let category = ConsoleCategory::from_output(output.category.as_deref());
+            let rich = output.data.as_ref().and_then(RichOutput::from_mime_bundle);
+            dap.console.update(|console| match rich {
+                Some(
+                    rich @ (RichOutput::Table { .. } | RichOutput::Image { .. }),
+                ) => console.push_rich(category, rich),
+                _ => console.push(category, &output.output),
+            });
+        }
+    }
+
     pub fn dap_continue(&self, term_id: TermId) -> Option<()> {
         let terminal = self.get_terminal(&term_id)?;
         let dap_id = terminal
@@ -684,6 +709,19 @@ pub fn dap_step_out(&self, term_id: TermId) -> Option<()> {
         Some(())
     }
 
+    /// The terminals of the debug sessions, oldest first, with their processes
+    pub fn debug_sessions(&self, tracked: bool) -> Vec<(TermId, RunDebugProcess)> {
+        let mut sessions = self.run_debug_process(tracked);
+        sessions.retain(|(_, process)| process.mode == RunDebugMode::Debug);
+        sessions
+    }
+
+    /// Show the variables and stack frames of the session of the terminal
+    pub fn set_active_session(&self, term_id: TermId) {
+        self.debug.active_term.set(Some(term_id));
+        self.focus_terminal(term_id);
+    }
+
     pub fn get_active_dap(&self, tracked: bool) -> Option<DapData> {
         let active_term = if tracked {
             self.debug.active_term.get()?
@@ -716,6 +754,7 @@ pub fn get_dap(&self, term_id: TermId, tracked: bool) -> Option<DapData> {
 
     pub fn dap_frame_scopes(&self, dap_id: DapId, frame_id: usize) {
         if let Some(dap) = self.debug.daps.get_untracked().get(&dap_id) {
+            dap.frame_id.set(Some(frame_id));
             let variables = dap.variables;
             let send = create_ext_action(self.common.scope, move |result| {
                 if let Ok(ProxyResponse::DapGetScopesResponse { scopes }) = result {
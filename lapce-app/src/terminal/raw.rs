@@ -1,6 +1,6 @@
 use alacritty_terminal::{
     event::EventListener,
-    grid::Dimensions,
+    grid::{Dimensions, Scroll},
     index::{Column, Direction, Line, Point},
     term::{
         search::{Match, RegexIter, RegexSearch},
@@ -12,7 +12,13 @@
 use crossbeam_channel::Sender;
 use lapce_rpc::{proxy::ProxyRpcHandler, terminal::TermId};
 
-use super::event::TermNotification;
+use super::{
+    event::TermNotification,
+    shell_integration::{CommandBlock, MarkScanner, ShellMark},
+};
+
+/// How many commands are remembered for each terminal
+const MAX_COMMANDS: usize = 1000;
 
 pub struct EventProxy {
     term_id: TermId,
@@ -46,6 +52,9 @@ pub struct RawTerminal {
     pub parser: ansi::Processor,
     pub term: Term<EventProxy>,
     pub scroll_delta: f64,
+    scanner: MarkScanner,
+    /// The commands the shell reported through shell integration, oldest first
+    pub commands: Vec<CommandBlock>,
 }
 
 impl RawTerminal {
@@ -72,14 +81,139 @@ pub fn new(
             parser,
             term,
             scroll_delta: 0.0,
+            scanner: MarkScanner::default(),
+            commands: Vec::new(),
         }
     }
 
     pub fn update_content(&mut self, content: Vec<u8>) {
         for byte in content {
             self.parser.advance(&mut self.term, byte);
+            if let Some(mark) = self.scanner.advance(byte) {
+                self.shell_mark(mark);
+            }
         }
     }
+
+    /// The cursor, with its line counted from the top of the scrollback
+    fn cursor(&self) -> (usize, usize) {
+        let point = self.term.grid().cursor.point;
+        (self.absolute_line(point.line), point.column.0)
+    }
+
+    /// A line of the grid counted from the top of the scrollback
+    pub fn absolute_line(&self, line: Line) -> usize {
+        (self.term.grid().history_size() as i32 + line.0).max(0) as usize
+    }
+
+    /// The line of the grid of a line counted from the top of the scrollback
+    fn grid_line(&self, line: usize) -> Line {
+        Line(line as i32 - self.term.grid().history_size() as i32)
+            .max(self.term.topmost_line())
+    }
+
+    fn shell_mark(&mut self, mark: ShellMark) {
+        let cursor = self.cursor();
+        match mark {
+            ShellMark::PromptStart => {
+                // The screen was cleared, which took the earlier lines with it
+                self.commands
+                    .retain(|command| command.prompt_line < cursor.0);
+                if self.commands.len() >= MAX_COMMANDS {
+                    self.commands.remove(0);
+                }
+                self.commands.push(CommandBlock {
+                    prompt_line: cursor.0,
+                    ..Default::default()
+                });
+            }
+            ShellMark::CommandStart => {
+                if let Some(command) = self.commands.last_mut() {
+                    command.command_start = Some(cursor);
+                }
+            }
+            ShellMark::OutputStart => {
+                let text = self
+                    .commands
+                    .last()
+                    .and_then(|command| command.command_start)
+                    .map(|start| self.text_between(start, cursor))
+                    .unwrap_or_default();
+                if let Some(command) = self.commands.last_mut() {
+                    command.output_start = Some(cursor.0);
+                    command.command = text.trim().to_string();
+                }
+            }
+            ShellMark::CommandEnd(exit_code) => {
+                if let Some(command) = self
+                    .commands
+                    .last_mut()
+                    .filter(|command| command.has_output() && command.end.is_none())
+                {
+                    command.end = Some(cursor);
+                    command.exit_code = exit_code;
+                }
+            }
+        }
+    }
+
+    /// The text from `start` up to `end`, which is excluded
+    fn text_between(&self, start: (usize, usize), end: (usize, usize)) -> String {
+        let end = if end.1 > 0 {
+            Point::new(self.grid_line(end.0), Column(end.1 - 1))
+        } else if end.0 > start.0 {
+            Point::new(self.grid_line(end.0 - 1), self.term.last_column())
+        } else {
+            return String::new();
+        };
+        let start = Point::new(self.grid_line(start.0), Column(start.1));
+        if start > end {
+            return String::new();
+        }
+        self.term.bounds_to_string(start, end)
+    }
+
+    /// The command whose prompt is on `line` of the grid
+    pub fn command_at(&self, line: Line) -> Option<&CommandBlock> {
+        let line = self.absolute_line(line);
+        self.commands
+            .iter()
+            .rev()
+            .find(|command| command.prompt_line == line)
+    }
+
+    /// Scroll the prompt of the next or previous command to the top
+    pub fn scroll_to_command(&mut self, next: bool) {
+        let history_size = self.term.grid().history_size();
+        let display_offset = self.term.grid().display_offset();
+        let top = history_size - display_offset;
+        let target = if next {
+            self.commands
+                .iter()
+                .find(|command| command.prompt_line > top)
+        } else {
+            self.commands
+                .iter()
+                .rev()
+                .find(|command| command.prompt_line < top)
+        };
+        let offset = match target {
+            Some(command) => history_size.saturating_sub(command.prompt_line),
+            None if next => 0,
+            None => return,
+        };
+        self.term
+            .scroll_display(Scroll::Delta(offset as i32 - display_offset as i32));
+    }
+
+    /// The output of the last command that ran, up to the cursor if it's
+    /// still running. `None` if the shell doesn't report its commands.
+    pub fn last_output(&self) -> Option<(String, String)> {
+        let command = self.commands.iter().rev().find(|c| c.has_output())?;
+        let start = (command.output_start?, 0);
+        let end = command.end.unwrap_or_else(|| self.cursor());
+        Some((command.command.clone(), self.text_between(start, end)))
+    }
 }
 
 pub fn visible_regex_match_iter<'a, EventProxy>(
@@ -0,0 +1,135 @@
+//! Shell integration through the OSC 133 sequences, which shells send around
+//! each command when their prompt is set up for it, as fish does on its own:
+//! `A` where the prompt starts, `B` where the command starts, `C` where its
+//! output starts and `D;<exit code>` where it finishes.
+
+/// The longest OSC sequence looked at, longer ones aren't shell marks
+const MAX_OSC_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    PromptStart,
+    CommandStart,
+    OutputStart,
+    CommandEnd(Option<i32>),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Finds the shell marks in the output of the terminal, which can be split
+/// anywhere between the updates.
+#[derive(Debug, Default)]
+pub struct MarkScanner {
+    state: ScanState,
+    osc: Vec<u8>,
+}
+
+impl MarkScanner {
+    /// The mark that `byte` completes, if any
+    pub fn advance(&mut self, byte: u8) -> Option<ShellMark> {
+        match (self.state, byte) {
+            (ScanState::Ground, 0x1b) => self.state = ScanState::Escape,
+            (ScanState::Ground, _) => {}
+            (ScanState::Escape, b']') => {
+                self.osc.clear();
+                self.state = ScanState::Osc;
+            }
+            (ScanState::Escape, 0x1b) => {}
+            (ScanState::Escape, _) => self.state = ScanState::Ground,
+            // Terminated by BEL, or by ST which is `ESC \`
+            (ScanState::Osc, 0x07) | (ScanState::OscEscape, b'\\') => {
+                self.state = ScanState::Ground;
+                return parse_mark(&self.osc);
+            }
+            (ScanState::Osc, 0x1b) => self.state = ScanState::OscEscape,
+            (ScanState::Osc, _) => {
+                if self.osc.len() < MAX_OSC_LEN {
+                    self.osc.push(byte);
+                } else {
+                    self.state = ScanState::Ground;
+                }
+            }
+            (ScanState::OscEscape, _) => self.state = ScanState::Ground,
+        }
+        None
+    }
+}
+
+fn parse_mark(osc: &[u8]) -> Option<ShellMark> {
+    let osc = std::str::from_utf8(osc).ok()?;
+    let mut params = osc.strip_prefix("133;")?.split(';');
+    Some(match params.next()? {
+        "A" => ShellMark::PromptStart,
+        "B" => ShellMark::CommandStart,
+        "C" => ShellMark::OutputStart,
+        "D" => {
+            ShellMark::CommandEnd(params.next().and_then(|code| code.parse().ok()))
+        }
+        _ => return None,
+    })
+}
+
+/// A command run in the terminal. The lines are counted from the top of the
+/// scrollback, so that they stay put as the output scrolls.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandBlock {
+    pub prompt_line: usize,
+    /// The line and column where the command typed after the prompt starts
+    pub command_start: Option<(usize, usize)>,
+    pub output_start: Option<usize>,
+    /// The line and column where the output ends
+    pub end: Option<(usize, usize)>,
+    pub exit_code: Option<i32>,
+    pub command: String,
+}
+
+impl CommandBlock {
+    /// Whether the command ran, rather than the prompt being left empty
+    pub fn has_output(&self) -> bool {
+        self.output_start.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarkScanner, ShellMark};
+
+    fn scan(chunks: &[&[u8]]) -> Vec<ShellMark> {
+        let mut scanner = MarkScanner::default();
+        chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .filter_map(|byte| scanner.advance(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_mark_scanner() {
+        assert_eq!(
+            scan(&[b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07"]),
+            vec![
+                ShellMark::PromptStart,
+                ShellMark::CommandStart,
+                ShellMark::OutputStart
+            ]
+        );
+        // Split between updates
+        assert_eq!(
+            scan(&[b"out\x1b]13", b"3;D;1", b"\x07"]),
+            vec![ShellMark::CommandEnd(Some(1))]
+        );
+        assert_eq!(
+            scan(&[b"\x1b]133;D\x07"]),
+            vec![ShellMark::CommandEnd(None)]
+        );
+        // Other sequences
+        assert!(scan(&[b"\x1b]0;title\x07\x1b[1m\x1b]133;Z\x07"]).is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use std::{sync::Arc, time::SystemTime};
 
 use alacritty_terminal::{
-    grid::Dimensions,
+    grid::{Dimensions, Scroll},
     index::Side,
     selection::{Selection, SelectionType},
     term::{cell::Flags, test::TermSize, RenderableContent},
@@ -11,7 +11,7 @@
     cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout, Weight},
     event::{Event, EventPropagation},
     peniko::{
-        kurbo::{Point, Rect, Size},
+        kurbo::{Circle, Point, Rect, Size},
         Color,
     },
     pointer::PointerInputEvent,
@@ -41,6 +41,9 @@
 /// Threshold used for double_click/triple_click.
 const CLICK_THRESHOLD: u128 = 400;
 
+/// The space left of the text for the marks of the commands
+const GUTTER_WIDTH: f64 = 8.0;
+
 enum TerminalViewState {
     Config,
     Focus(bool),
@@ -163,7 +166,7 @@ fn terminal_size(&self) -> (usize, usize) {
         let config = self.config.get_untracked();
         let line_height = config.terminal_line_height() as f64;
         let char_width = self.char_size().width;
-        let width = (self.size.width / char_width).floor() as usize;
+        let width = ((self.size.width - GUTTER_WIDTH) / char_width).floor() as usize;
         let height = (self.size.height / line_height).floor() as usize;
         (width.max(1), height.max(1))
     }
@@ -207,6 +210,26 @@ fn click(&self, pos: Point) -> Option<()> {
         None
     }
 
+    /// Run again the command whose mark is at `pos`
+    fn rerun_command(&self, pos: Point) -> Option<()> {
+        if pos.x >= GUTTER_WIDTH {
+            return None;
+        }
+        let line = self.get_terminal_point(pos).line;
+        let command = {
+            let raw = self.raw.read();
+            let command = raw.command_at(line)?;
+            if command.end.is_none() || command.command.is_empty() {
+                return None;
+            }
+            command.command.clone()
+        };
+        self.raw.write().term.scroll_display(Scroll::Bottom);
+        self.proxy
+            .terminal_write(self.term_id, format!("{command}\r"));
+        Some(())
+    }
+
     fn update_mouse_action_by_down(&mut self, mouse: &PointerInputEvent) {
         let mut next_action = MouseAction::None;
         match self.current_mouse_action {
@@ -307,7 +330,8 @@ fn update_mouse_action_by_up(&mut self, mouse: &PointerInputEvent) {
 
     fn get_terminal_point(&self, pos: Point) -> alacritty_terminal::index::Point {
         let raw = self.raw.read();
-        let col = (pos.x / self.char_size().width) as usize;
+        let col =
+            ((pos.x - GUTTER_WIDTH).max(0.0) / self.char_size().width) as usize;
         let line_no = pos.y as i32
             / (self.config.get().terminal_line_height() as i32)
             - raw.term.grid().display_offset() as i32;
@@ -349,7 +373,7 @@ fn paint_content(
             let cell = item.cell;
             let inverse = cell.flags.contains(Flags::INVERSE);
 
-            let x = point.column.0 as f64 * char_width;
+            let x = GUTTER_WIDTH + point.column.0 as f64 * char_width;
             let y =
                 (point.line.0 as f64 + content.display_offset as f64) * line_height;
             let char_y = y + (line_height - char_size.height) / 2.0;
@@ -417,6 +441,54 @@ fn paint_content(
         self.paint_line_content(cx, &line_content, line_height, char_width, config);
     }
 
+    /// A mark left of the prompt of each command that ran, with the color of
+    /// how it exited: hollow while it runs, red if it failed.
+    fn paint_command_marks(
+        &self,
+        cx: &mut PaintCx,
+        raw: &RawTerminal,
+        display_offset: usize,
+        line_height: f64,
+        config: &LapceConfig,
+    ) {
+        let top = raw.term.grid().history_size() as i64 - display_offset as i64;
+        let screen_lines = raw.term.screen_lines() as i64;
+        let radius = (GUTTER_WIDTH / 2.0 - 1.0).min(line_height / 4.0);
+        for command in raw.commands.iter().filter(|command| command.has_output()) {
+            let row = command.prompt_line as i64 - top;
+            if row < 0 || row >= screen_lines {
+                continue;
+            }
+            let center = Point::new(
+                GUTTER_WIDTH / 2.0,
+                row as f64 * line_height + line_height / 2.0,
+            );
+            let circle = Circle::new(center, radius);
+            match (command.end, command.exit_code) {
+                (None, _) => {
+                    cx.stroke(
+                        &circle,
+                        config.color(LapceColor::TERMINAL_FOREGROUND),
+                        1.0,
+                    );
+                }
+                (Some(_), Some(0)) => {
+                    cx.fill(&circle, config.color(LapceColor::TERMINAL_BLUE), 0.0);
+                }
+                (Some(_), Some(_)) => {
+                    cx.fill(&circle, config.color(LapceColor::TERMINAL_RED), 0.0);
+                }
+                (Some(_), None) => {
+                    cx.fill(
+                        &circle,
+                        config.color(LapceColor::TERMINAL_BRIGHT_BLACK),
+                        0.0,
+                    );
+                }
+            }
+        }
+    }
+
     fn paint_line_content(
         &self,
         cx: &mut PaintCx,
@@ -431,7 +503,10 @@ fn paint_line_content(
                 line_height,
             )
             .to_rect()
-            .with_origin(Point::new(*start as f64 * char_width, line_content.y));
+            .with_origin(Point::new(
+                GUTTER_WIDTH + *start as f64 * char_width,
+                line_content.y,
+            ));
             cx.fill(&rect, bg, 0.0);
         }
 
@@ -439,7 +514,10 @@ fn paint_line_content(
             let rect =
                 Size::new(char_width * (end.saturating_sub(*start) as f64), 1.0)
                     .to_rect()
-                    .with_origin(Point::new(*start as f64 * char_width, y - 1.0));
+                    .with_origin(Point::new(
+                        GUTTER_WIDTH + *start as f64 * char_width,
+                        y - 1.0,
+                    ));
             cx.fill(&rect, fg, 0.0);
         }
 
@@ -501,6 +579,9 @@ fn event_before_children(
                 match self.current_mouse_action {
                     MouseAction::LeftOnce { pos, .. } => {
                         clear_selection = true;
+                        if self.rerun_command(pos).is_some() {
+                            return EventPropagation::Stop;
+                        }
                         if e.modifiers.control() && self.click(pos).is_some() {
                             return EventPropagation::Stop;
                         }
@@ -675,8 +756,8 @@ fn paint(&mut self, cx: &mut floem::context::PaintCx) {
                 } else {
                     term.last_column().0
                 };
-                let x0 = left_col as f64 * char_width;
-                let x1 = right_col as f64 * char_width;
+                let x0 = GUTTER_WIDTH + left_col as f64 * char_width;
+                let x1 = GUTTER_WIDTH + right_col as f64 * char_width;
                 let y0 = line as f64 * line_height;
                 let y1 = y0 + line_height;
                 cx.fill(
@@ -696,7 +777,9 @@ fn paint(&mut self, cx: &mut floem::context::PaintCx) {
             );
         }
 
+        let display_offset = content.display_offset;
         self.paint_content(cx, content, line_height, char_size, &config);
+        self.paint_command_marks(cx, &raw, display_offset, line_height, &config);
         // if data.find.visual {
         //     if let Some(search_string) = data.find.search_string.as_ref() {
         //         if let Ok(dfas) = RegexSearch::new(&regex::escape(search_string)) {
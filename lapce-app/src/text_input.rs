@@ -142,6 +142,7 @@ fn text_input_full<T: KeyPressFocus + 'static>(
     let doc = e_data.doc_signal();
     let cursor = e_data.cursor();
     let config = e_data.common.config;
+    let wrap_style = e_data.wrap_style;
     let keypress = e_data.common.keypress;
     let window_origin = create_rw_signal(Point::ZERO);
     let cursor_line = create_rw_signal(Line::new(Point::ZERO, Point::ZERO));
@@ -233,7 +234,7 @@ fn text_input_full<T: KeyPressFocus + 'static>(
         style: Default::default(),
     }
     .style(move |s| {
-        editor_style(config, doc, s)
+        editor_style(config, doc, wrap_style, s)
             .cursor(CursorStyle::Text)
             .padding_horiz(10.0)
             .padding_vert(6.0)
@@ -48,6 +48,9 @@ pub struct WindowCommonData {
     pub cursor_blink_timer: RwSignal<TimerToken>,
     // the value to be update by curosr blinking
     pub hide_cursor: RwSignal<bool>,
+    /// Whether the window has the focus. The cursor doesn't blink and the
+    /// background refreshes wait while it doesn't.
+    pub focused: RwSignal<bool>,
     pub app_view_id: RwSignal<ViewId>,
     pub extra_plugin_paths: Arc<Vec<PathBuf>>,
 }
@@ -91,8 +94,12 @@ pub fn new(
         app_command: Listener<AppCommand>,
     ) -> Self {
         let cx = Scope::new();
-        let config =
-            LapceConfig::load(&LapceWorkspace::default(), &[], &extra_plugin_paths);
+        let config = LapceConfig::load(
+            &LapceWorkspace::default(),
+            None,
+            &[],
+            &extra_plugin_paths,
+        );
         let config = cx.create_rw_signal(Arc::new(config));
         let root_view_id = cx.create_rw_signal(ViewId::new());
 
@@ -107,6 +114,7 @@ pub fn new(
         let window_tab_header_height = cx.create_rw_signal(0.0);
         let cursor_blink_timer = cx.create_rw_signal(TimerToken::INVALID);
         let hide_cursor = cx.create_rw_signal(false);
+        let focused = cx.create_rw_signal(true);
 
         let common = Rc::new(WindowCommonData {
             window_command,
@@ -119,6 +127,7 @@ pub fn new(
             ime_allowed,
             cursor_blink_timer,
             hide_cursor,
+            focused,
             app_view_id,
             extra_plugin_paths,
         });
@@ -186,6 +195,7 @@ pub fn new(
     pub fn reload_config(&self) {
         let config = LapceConfig::load(
             &LapceWorkspace::default(),
+            None,
             &[],
             &self.common.extra_plugin_paths,
         );
@@ -196,6 +206,13 @@ pub fn reload_config(&self) {
         }
     }
 
+    pub fn reload_syntax(&self) {
+        let window_tabs = self.window_tabs.get_untracked();
+        for (_, window_tab) in window_tabs {
+            window_tab.main_split.reload_syntax();
+        }
+    }
+
     pub fn run_window_command(&self, cmd: WindowCommand) {
         match cmd {
             WindowCommand::SetWorkspace { workspace } => {
@@ -352,6 +369,30 @@ pub fn info(&self) -> WindowInfo {
         }
     }
 
+    /// Pause the cursor blink and the background refreshes when the window
+    /// loses the focus, and catch up with them when it gets it back.
+    pub fn set_focused(&self, focused: bool) {
+        if self.common.focused.get_untracked() == focused {
+            return;
+        }
+        self.common.focused.set(focused);
+        if focused {
+            for (_, window_tab) in self.window_tabs.get_untracked() {
+                window_tab.window_focused();
+            }
+            if let Some(window_tab) = self.active_window_tab() {
+                window_tab
+                    .common
+                    .internal_command
+                    .send(InternalCommand::ResetBlinkCursor);
+            }
+        } else {
+            // The pending blink is dropped and the cursor stays shown
+            self.common.cursor_blink_timer.set(TimerToken::INVALID);
+            self.common.hide_cursor.set(false);
+        }
+    }
+
     pub fn active_window_tab(&self) -> Option<Rc<WindowTabData>> {
         let window_tabs = self.window_tabs.get_untracked();
         let active = self
@@ -9,7 +9,7 @@
 
 use crossbeam_channel::Sender;
 use floem::{
-    action::{open_file, TimerToken},
+    action::{open_file, save_as, TimerToken},
     cosmic_text::{Attrs, AttrsList, FamilyOwned, LineHeightValue, TextLayout},
     ext_event::{create_ext_action, create_signal_from_channel},
     file::FileDialogOptions,
@@ -17,61 +17,94 @@
     kurbo::Size,
     peniko::kurbo::{Point, Rect, Vec2},
     reactive::{use_context, Memo, ReadSignal, RwSignal, Scope, WriteSignal},
+    views::editor::{id::EditorId, text::SystemClipboard},
     ViewId,
 };
 use indexmap::IndexMap;
 use itertools::Itertools;
 use lapce_core::{
-    command::FocusCommand, cursor::CursorAffinity, directory::Directory, meta,
-    mode::Mode, register::Register,
+    command::FocusCommand,
+    cursor::CursorAffinity,
+    directory::Directory,
+    language::LapceLanguage,
+    meta,
+    mode::Mode,
+    register::{Clipboard, Register},
 };
 use lapce_rpc::{
+    automation::AutomationEvent,
     core::CoreNotification,
+    coverage::FileCoverage,
     dap_types::RunDebugConfig,
     file::{Naming, PathObject},
+    plugin::PluginId,
     proxy::{ProxyResponse, ProxyRpcHandler, ProxyStatus},
     source_control::FileDiff,
     terminal::TermId,
     RpcError,
 };
-use lsp_types::{Diagnostic, ProgressParams, ProgressToken, ShowMessageParams};
+use lsp_types::{
+    Diagnostic, MessageType, ProgressParams, ProgressToken, ServerInfo,
+    ShowMessageParams,
+};
 use serde_json::Value;
 use tracing::{debug, error, event, Level};
 
 use crate::{
     about::AboutData,
+    accessibility::{focus_name, AccessibilityData, AccessibilityEvent},
     alert::{AlertBoxData, AlertButton},
+    automation,
     code_action::{CodeActionData, CodeActionStatus},
     command::{
         CommandExecuted, CommandKind, InternalCommand, LapceCommand,
         LapceWorkbenchCommand, WindowCommand,
     },
-    completion::{CompletionData, CompletionStatus},
+    command_macro::{
+        is_macro_command, is_valid_name, step_command, steps_to_toml, MacroRun,
+        MacroStep,
+    },
+    completion::{CompletionData, CompletionProvider, CompletionStatus},
     config::LapceConfig,
     db::LapceDb,
-    debug::{DapData, LapceBreakpoint, RunDebugMode, RunDebugProcess},
+    debug::{
+        DapData, DapVariableEditData, LapceBreakpoint, RunDebugMode, RunDebugProcess,
+    },
+    debug_console::DebugConsoleData,
+    diagnostic_bundle::DiagnosticBundle,
     doc::DocContent,
     editor::location::{EditorLocation, EditorPosition},
+    editor_event::{selected_ranges, selection_changed, EditorEvent},
     editor_tab::EditorTabChild,
+    error_report::{write_incident_report, ErrorReport},
     file_explorer::data::FileExplorerData,
     find::Find,
+    flame::FlameData,
+    formatter::BuiltinFormat,
     global_search::GlobalSearchData,
     hover::HoverData,
     id::WindowTabId,
     inline_completion::InlineCompletionData,
+    json_tree::JsonTreeData,
     keypress::{condition::Condition, EventRef, KeyPressData, KeyPressFocus},
     listener::Listener,
     lsp::path_from_url,
     main_split::{MainSplitData, SplitData, SplitDirection, SplitMoveDirection},
+    notification::{NotificationAction, NotificationCenter},
     palette::{kind::PaletteKind, PaletteData, PaletteStatus},
     panel::{
         data::{default_panel_order, PanelData},
         kind::PanelKind,
         position::PanelContainerPosition,
     },
+    perf,
     plugin::PluginData,
+    profile,
     proxy::{new_proxy, ProxyData},
+    refactor::RefactorJournal,
     rename::RenameData,
+    rich_output::OutputData,
+    settings_sync::{self, ConflictResolution, SettingsBundle, SyncOutcome},
     source_control::SourceControlData,
     terminal::{
         event::{terminal_update_process, TermEvent, TermNotification},
@@ -112,6 +145,10 @@ pub struct WorkProgress {
     pub percentage: Option<u32>,
 }
 
+/// The active editor, with its cursor offset, selected ranges and mode, to
+/// tell which of them changed
+type ActiveCursor = (EditorId, usize, Vec<(usize, usize)>, Mode);
+
 #[derive(Clone)]
 pub struct CommonData {
     pub workspace: Arc<LapceWorkspace>,
@@ -128,6 +165,8 @@ pub struct CommonData {
     pub internal_command: Listener<InternalCommand>,
     pub lapce_command: Listener<LapceCommand>,
     pub workbench_command: Listener<LapceWorkbenchCommand>,
+    /// The events of the documents and editors, for following them
+    pub editor_events: Listener<EditorEvent>,
     pub term_tx: Sender<(TermId, TermEvent)>,
     pub term_notification_tx: Sender<TermNotification>,
     pub proxy: ProxyRpcHandler,
@@ -135,14 +174,62 @@ pub struct CommonData {
     pub ui_line_height: Memo<f64>,
     pub dragging: RwSignal<Option<DragContent>>,
     pub config: ReadSignal<Arc<LapceConfig>>,
+    /// The configuration profile of the window tab, `None` for the default
+    pub profile: RwSignal<Option<String>>,
     pub proxy_status: RwSignal<Option<ProxyStatus>>,
     pub mouse_hover_timer: RwSignal<TimerToken>,
     pub breakpoints: RwSignal<BTreeMap<PathBuf, BTreeMap<usize, LapceBreakpoint>>>,
+    /// The coverage of the files in the coverage report, by their paths
+    pub coverage: RwSignal<im::HashMap<PathBuf, Rc<FileCoverage>>>,
+    /// The recent refactors that edited several files
+    pub refactors: RwSignal<RefactorJournal>,
+    pub notifications: RwSignal<NotificationCenter>,
+    /// The language servers that were started, with the name and version
+    /// they reported
+    pub language_servers: RwSignal<im::HashMap<PluginId, String>>,
     // the current focused view which will receive keyboard events
     pub keyboard_focus: RwSignal<Option<ViewId>>,
+    pub accessibility: AccessibilityData,
     pub window_common: Rc<WindowCommonData>,
 }
 
+impl CommonData {
+    /// Tell the user about an error with its context, which can be copied,
+    /// and write it to an incident report when `core.incident-reports` is on
+    pub fn report_error(&self, report: ErrorReport) {
+        let time = chrono::Local::now();
+        let details = report.details(time);
+        error!("{details}");
+        let mut actions = vec![NotificationAction::CopyDetails(details.clone())];
+        if self.config.get_untracked().core.incident_reports {
+            match write_incident_report(&details, time) {
+                Ok(path) => {
+                    actions.push(NotificationAction::OpenIncidentReport(path))
+                }
+                Err(err) => error!("Failed to write the incident report: {err}"),
+            }
+        }
+        self.notifications.update(|notifications| {
+            notifications.push(
+                report.title,
+                MessageType::ERROR,
+                report.message,
+                actions,
+            );
+        });
+    }
+
+    /// The language of the file at `path`: the one last chosen for it in the
+    /// workspace, or else the one of the config
+    pub fn language_of(&self, path: &Path) -> LapceLanguage {
+        let db: Arc<LapceDb> = use_context().unwrap();
+        db.get_file_language(&self.workspace, path)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| self.config.with_untracked(|c| c.language_of(path)))
+    }
+}
+
 impl std::fmt::Debug for CommonData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CommonData")
@@ -164,6 +251,12 @@ pub struct WindowTabData {
     pub plugin: PluginData,
     pub code_action: RwSignal<CodeActionData>,
     pub source_control: SourceControlData,
+    pub debug_console: DebugConsoleData,
+    pub flame: FlameData,
+    pub output: OutputData,
+    pub json_tree: JsonTreeData,
+    pub syntax_tree: SyntaxTreeData,
+    pub variable_edit: DapVariableEditData,
     pub rename: RenameData,
     pub global_search: GlobalSearchData,
     pub about_data: AboutData,
@@ -175,7 +268,9 @@ pub struct WindowTabData {
     pub set_config: WriteSignal<Arc<LapceConfig>>,
     pub update_in_progress: RwSignal<bool>,
     pub progresses: RwSignal<IndexMap<ProgressToken, WorkProgress>>,
-    pub messages: RwSignal<Vec<(String, ShowMessageParams)>>,
+    /// Whether the `head` versions of the documents changed while the window
+    /// didn't have the focus, to be retrieved when it gets it back
+    pub heads_outdated: RwSignal<bool>,
     pub common: Rc<CommonData>,
 }
 
@@ -213,7 +308,7 @@ fn run_command(
     ) -> CommandExecuted {
         match &command.kind {
             CommandKind::Workbench(cmd) => {
-                self.run_workbench_command(cmd.clone(), None);
+                self.run_workbench_command(cmd.clone(), command.data.clone());
             }
             CommandKind::Focus(cmd) => {
                 if self.common.focus.get_untracked() == Focus::Workbench {
@@ -277,8 +372,18 @@ pub fn new(
         let workspace_disabled_volts = db
             .get_workspace_disabled_volts(&workspace)
             .unwrap_or_default();
+        let profile = db
+            .get_workspace_profile(&workspace)
+            .ok()
+            .flatten()
+            .filter(|name| profile::exists(name));
+        let profile_disabled_volts = profile
+            .as_deref()
+            .map(profile::disabled_volts)
+            .unwrap_or_default();
         let mut all_disabled_volts = disabled_volts.clone();
         all_disabled_volts.extend(workspace_disabled_volts.clone());
+        all_disabled_volts.extend(profile_disabled_volts.clone());
 
         let workspace_info = if workspace.path.is_some() {
             db.get_workspace_info(&workspace).ok()
@@ -292,12 +397,14 @@ pub fn new(
 
         let config = LapceConfig::load(
             &workspace,
+            profile.as_deref(),
             &all_disabled_volts,
             &window_common.extra_plugin_paths,
         );
         let lapce_command = Listener::new_empty(cx);
         let workbench_command = Listener::new_empty(cx);
         let internal_command = Listener::new_empty(cx);
+        let editor_events = Listener::new_empty(cx);
         let keypress = cx.create_rw_signal(KeyPressData::new(cx, &config));
         let proxy_status = cx.create_rw_signal(None);
 
@@ -357,6 +464,7 @@ pub fn new(
             internal_command,
             lapce_command,
             workbench_command,
+            editor_events,
             term_tx,
             term_notification_tx,
             proxy: proxy.proxy_rpc.clone(),
@@ -365,11 +473,17 @@ pub fn new(
             dragging: cx.create_rw_signal(None),
             workbench_size: cx.create_rw_signal(Size::ZERO),
             config,
+            profile: cx.create_rw_signal(profile),
             proxy_status,
             mouse_hover_timer: cx.create_rw_signal(TimerToken::INVALID),
             window_origin: cx.create_rw_signal(Point::ZERO),
             breakpoints: cx.create_rw_signal(BTreeMap::new()),
+            coverage: cx.create_rw_signal(im::HashMap::new()),
+            refactors: cx.create_rw_signal(RefactorJournal::default()),
+            notifications: cx.create_rw_signal(NotificationCenter::default()),
+            language_servers: cx.create_rw_signal(im::HashMap::new()),
             keyboard_focus: cx.create_rw_signal(None),
+            accessibility: AccessibilityData::new(cx),
             window_common: window_common.clone(),
         });
 
@@ -381,6 +495,54 @@ pub fn new(
         let file_explorer =
             FileExplorerData::new(cx, main_split.editors, common.clone());
 
+        {
+            // Documents keep the bracket colorization settings they were
+            // opened with, so pass on changes
+            let docs = main_split.docs;
+            let scratch_docs = main_split.scratch_docs;
+            cx.create_effect(move |_| {
+                let (active, limit) = config.with(|config| {
+                    (
+                        config.editor.bracket_pair_colorization,
+                        config.editor.bracket_colorization_limit,
+                    )
+                });
+                let docs = docs.get_untracked();
+                let scratch_docs = scratch_docs.get_untracked();
+                for doc in docs.values().chain(scratch_docs.values()) {
+                    doc.set_bracket_colorization(active, limit);
+                }
+            });
+        }
+
+        {
+            // Watch the coverage report once the proxy is up, and again
+            // whenever its path changes
+            let coverage_file = cx.create_memo(move |_| {
+                config.with(|c| c.editor.coverage_file.clone())
+            });
+            let proxy_status = common.proxy_status;
+            let proxy = common.proxy.clone();
+            let workspace = common.workspace.clone();
+            cx.create_effect(move |_| {
+                let coverage_file = coverage_file.get();
+                if !matches!(proxy_status.get(), Some(ProxyStatus::Connected)) {
+                    return;
+                }
+                let path = Some(coverage_file.trim())
+                    .filter(|file| !file.is_empty())
+                    .and_then(|file| {
+                        let file = PathBuf::from(file);
+                        if file.is_absolute() {
+                            Some(file)
+                        } else {
+                            workspace.path.as_ref().map(|path| path.join(file))
+                        }
+                    });
+                proxy.watch_coverage(path);
+            });
+        }
+
         if let Some(info) = workspace_info.as_ref() {
             let root_split = main_split.root_split;
             info.split.to_data(main_split.clone(), None, root_split);
@@ -468,7 +630,27 @@ pub fn new(
 
         let terminal = TerminalPanelData::new(
             workspace.clone(),
-            common.config.get_untracked().terminal.get_default_profile(),
+            common
+                .config
+                .get_untracked()
+                .terminal
+                .get_default_profile(&workspace, None),
+            common.clone(),
+        );
+        let debug_console = DebugConsoleData::new(
+            cx,
+            main_split.editors,
+            terminal.clone(),
+            common.clone(),
+        );
+        let flame = FlameData::new(cx, common.clone());
+        let output = OutputData::new(cx, common.clone());
+        let json_tree = JsonTreeData::new(cx);
+        let syntax_tree = SyntaxTreeData::new(cx);
+        let variable_edit = DapVariableEditData::new(
+            cx,
+            main_split.editors,
+            terminal.debug.daps,
             common.clone(),
         );
         if let Some(workspace_info) = workspace_info.as_ref() {
@@ -497,6 +679,7 @@ pub fn new(
             cx,
             HashSet::from_iter(disabled_volts),
             HashSet::from_iter(workspace_disabled_volts),
+            HashSet::from_iter(profile_disabled_volts),
             main_split.editors,
             common.clone(),
         );
@@ -504,6 +687,7 @@ pub fn new(
         {
             let notification = create_signal_from_channel(term_notification_rx);
             let terminal = terminal.clone();
+            let panel = panel.clone();
             cx.create_effect(move |_| {
                 notification.with(|notification| {
                     if let Some(notification) = notification.as_ref() {
@@ -511,8 +695,11 @@ pub fn new(
                             TermNotification::SetTitle { term_id, title } => {
                                 terminal.set_title(term_id, title);
                             }
+                            // The output is painted when the panel is shown
                             TermNotification::RequestPaint => {
-                                view_id.get_untracked().request_paint();
+                                if panel.is_panel_visible(&PanelKind::Terminal) {
+                                    view_id.get_untracked().request_paint();
+                                }
                             }
                         }
                     }
@@ -534,6 +721,12 @@ pub fn new(
             file_explorer,
             code_action,
             source_control,
+            debug_console,
+            flame,
+            output,
+            json_tree,
+            syntax_tree,
+            variable_edit,
             plugin,
             rename,
             global_search,
@@ -546,7 +739,7 @@ pub fn new(
             set_config,
             update_in_progress: cx.create_rw_signal(false),
             progresses: cx.create_rw_signal(IndexMap::new()),
-            messages: cx.create_rw_signal(Vec::new()),
+            heads_outdated: cx.create_rw_signal(false),
             common,
         };
 
@@ -566,6 +759,118 @@ pub fn new(
             });
         }
 
+        {
+            let focus = window_tab_data.common.focus;
+            let config = window_tab_data.common.config;
+            let accessibility = window_tab_data.common.accessibility.clone();
+            cx.create_effect(move |_| {
+                let focus = focus.get();
+                if config.with_untracked(|c| c.core.screen_reader_support) {
+                    accessibility.announce(AccessibilityEvent::FocusChanged {
+                        name: focus_name(&focus),
+                    });
+                }
+            });
+        }
+
+        {
+            let active_editor = window_tab_data.main_split.active_editor;
+            cx.create_effect(move |last: Option<Option<PathBuf>>| {
+                let path = active_editor.get().and_then(|editor| {
+                    editor.doc().content.with(|content| content.path().cloned())
+                });
+                if last.as_ref() != Some(&path) {
+                    if let Some(path) = path.clone() {
+                        automation::emit(AutomationEvent::EditorFocused { path });
+                    }
+                }
+                path
+            });
+        }
+
+        {
+            let active_editor = window_tab_data.main_split.active_editor;
+            let focused = window_tab_data.common.window_common.focused;
+            let editor_events = window_tab_data.common.editor_events;
+            // The path of the document being edited, none while the window
+            // doesn't have the focus
+            cx.create_effect(move |last: Option<Option<PathBuf>>| {
+                let path = if focused.get() {
+                    active_editor.get().and_then(|editor| {
+                        editor.doc().content.with(|content| content.path().cloned())
+                    })
+                } else {
+                    None
+                };
+                if let Some(Some(last)) = last {
+                    if path.as_ref() != Some(&last) {
+                        editor_events.send(EditorEvent::FocusLost { path: last });
+                    }
+                }
+                path
+            });
+        }
+
+        {
+            let active_editor = window_tab_data.main_split.active_editor;
+            let editor_events = window_tab_data.common.editor_events;
+            cx.create_effect(move |last: Option<Option<ActiveCursor>>| {
+                let editor = active_editor.get()?;
+                let editor_id = editor.id();
+                let (offset, ranges, mode) = editor.cursor().with(|cursor| {
+                    (
+                        cursor.offset(),
+                        selected_ranges(&cursor.mode),
+                        cursor.get_mode(),
+                    )
+                });
+                if let Some(Some((last_id, last_offset, last_ranges, last_mode))) =
+                    last
+                {
+                    if last_id == editor_id {
+                        if last_offset != offset {
+                            editor_events.send(EditorEvent::CursorMoved {
+                                editor_id,
+                                offset,
+                            });
+                        }
+                        if selection_changed(&last_ranges, &ranges) {
+                            editor_events
+                                .send(EditorEvent::SelectionChanged { editor_id });
+                        }
+                        if last_mode != mode {
+                            editor_events
+                                .send(EditorEvent::ModeChanged { editor_id, mode });
+                        }
+                    }
+                }
+                Some((editor_id, offset, ranges, mode))
+            });
+        }
+
+        {
+            let window_tab_data = window_tab_data.clone();
+            let editor_events = window_tab_data.common.editor_events;
+            editor_events.listen(move |event| {
+                let EditorEvent::FocusLost { path } = event else {
+                    return;
+                };
+                let config = window_tab_data.common.config.get_untracked();
+                if !config.editor.autosave_on_focus_lost {
+                    return;
+                }
+                let doc = window_tab_data
+                    .main_split
+                    .docs
+                    .with_untracked(|docs| docs.get(&path).cloned());
+                if let Some(doc) = doc {
+                    if !doc.is_pristine() {
+                        doc.save(|| {});
+                    }
+                }
+            });
+        }
+
         {
             let window_tab_data = window_tab_data.clone();
             window_tab_data.common.lapce_command.listen(move |cmd| {
@@ -610,21 +915,53 @@ pub fn reload_config(&self) {
         let workspace_disabled_volts = db
             .get_workspace_disabled_volts(&self.workspace)
             .unwrap_or_default();
+        let profile = self.common.profile.get_untracked();
         let mut all_disabled_volts = disabled_volts;
         all_disabled_volts.extend(workspace_disabled_volts);
+        all_disabled_volts.extend(
+            profile
+                .as_deref()
+                .map(profile::disabled_volts)
+                .unwrap_or_default(),
+        );
 
         let config = LapceConfig::load(
             &self.workspace,
+            profile.as_deref(),
             &all_disabled_volts,
             &self.common.window_common.extra_plugin_paths,
         );
         self.common.keypress.update(|keypress| {
             keypress.update_keymaps(&config);
         });
+        self.replace_config(config);
+    }
+
+    /// Replace the config, keeping the text layouts of documents unless a
+    /// value they depend on changed.
+    fn replace_config(&self, mut config: LapceConfig) {
+        let old_config = self.common.config.get_untracked();
+        config.carry_layout_rev(&old_config);
+        let blame_changed =
+            config.editor.blame_heat_map != old_config.editor.blame_heat_map;
         self.set_config.set(Arc::new(config));
+        if blame_changed {
+            for doc in self.main_split.docs.get_untracked().values() {
+                doc.retrieve_blame();
+            }
+        }
     }
 
     pub fn run_lapce_command(&self, cmd: LapceCommand) {
+        self.run_lapce_command_count(cmd, None);
+    }
+
+    /// Run `cmd` with the count typed before it, which only the editor
+    /// commands use
+    fn run_lapce_command_count(&self, cmd: LapceCommand, count: Option<usize>) {
+        self.common
+            .keypress
+            .with_untracked(|keypress| keypress.record_command(&cmd, count));
         match cmd.kind {
             CommandKind::Workbench(command) => {
                 self.run_workbench_command(command, cmd.data);
@@ -632,22 +969,74 @@ pub fn run_lapce_command(&self, cmd: LapceCommand) {
             CommandKind::Scroll(_)
             | CommandKind::Focus(_)
             | CommandKind::Edit(_)
-            | CommandKind::Move(_) => {
+            | CommandKind::Move(_)
+            | CommandKind::MultiSelection(_) => {
                 if self.palette.status.get_untracked() != PaletteStatus::Inactive {
-                    self.palette.run_command(&cmd, None, Modifiers::empty());
+                    self.palette.run_command(&cmd, count, Modifiers::empty());
                 } else if let Some(editor_data) =
                     self.main_split.active_editor.get_untracked()
                 {
-                    editor_data.run_command(&cmd, None, Modifiers::empty());
+                    editor_data.run_command(&cmd, count, Modifiers::empty());
                 } else {
                     // TODO: dispatch to current focused view?
                 }
             }
             CommandKind::MotionMode(_) => {}
-            CommandKind::MultiSelection(_) => {}
         }
     }
 
+    /// Run the `n`th most recent command again, from 1 for the last one
+    fn repeat_command(&self, n: usize) {
+        let entry = self.common.keypress.with_untracked(|keypress| {
+            keypress
+                .history
+                .with_untracked(|history| history.nth(n).cloned())
+        });
+        if let Some(entry) = entry {
+            self.run_lapce_command_count(entry.cmd, entry.count);
+        }
+    }
+
+    /// Run the `steps` of a macro `count` times, the commands as though they
+    /// were run from the palette and the text as though it was typed
+    fn run_macro(&self, steps: &[MacroStep], count: usize) {
+        let commands = self
+            .common
+            .keypress
+            .with_untracked(|keypress| keypress.commands.clone());
+        for _ in 0..count {
+            for step in steps {
+                if let MacroStep::Text { text } = step {
+                    if self.common.focus.get_untracked() == Focus::Palette {
+                        self.palette.receive_char(text);
+                    } else if let Some(editor) =
+                        self.main_split.active_editor.get_untracked()
+                    {
+                        editor.receive_char(text);
+                    }
+                } else if let Some((cmd, count)) = step_command(step, &commands) {
+                    if !is_macro_command(&cmd.kind) {
+                        self.run_lapce_command_count(cmd, count);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Save the last macro recorded under `name` in the settings
+    fn save_macro(&self, name: &str) {
+        let steps = self.common.keypress.with_untracked(|keypress| {
+            keypress
+                .macros
+                .with_untracked(|macros| macros.last().to_vec())
+        });
+        if steps.is_empty() || !is_valid_name(name) {
+            return;
+        }
+        // The config file is watched
+        LapceConfig::update_file("macros", name, steps_to_toml(&steps));
+    }
+
     pub fn run_workbench_command(
         &self,
         cmd: LapceWorkbenchCommand,
@@ -689,6 +1078,8 @@ pub fn run_workbench_command(
                                 .send(WindowCommand::SetWorkspace { workspace });
                         }
                     });
+                } else {
+                    self.open_remote_file(true);
                 }
             }
             CloseFolder => {
@@ -718,10 +1109,13 @@ pub fn run_workbench_command(
                             })
                         }
                     });
+                } else {
+                    self.open_remote_file(false);
                 }
             }
             NewFile => {
                 self.main_split.new_file();
+                self.palette.offer_file_templates(None);
             }
             RevealActiveFileInFileExplorer => {
                 if let Some(editor_data) = self.main_split.active_editor.get() {
@@ -740,6 +1134,9 @@ pub fn run_workbench_command(
                 }
             }
 
+            CloseAndFree => {
+                self.main_split.close_and_free_active();
+            }
             SaveAll => {
                 self.main_split.editors.with_editors_untracked(|editors| {
                     let mut paths = HashSet::new();
@@ -795,6 +1192,9 @@ pub fn run_workbench_command(
             OpenKeyboardShortcuts => {
                 self.main_split.open_keymap();
             }
+            OpenRunConfigurations => {
+                self.main_split.open_run_configs();
+            }
             OpenKeyboardShortcutsFile => {
                 if let Some(path) = LapceConfig::keymaps_file() {
                     self.main_split.jump_to_location(
@@ -809,6 +1209,12 @@ pub fn run_workbench_command(
                     );
                 }
             }
+            OpenProfileSettingsFile => {
+                self.open_profile_file(profile::settings_file);
+            }
+            OpenProfileKeyboardShortcutsFile => {
+                self.open_profile_file(profile::keymaps_file);
+            }
             OpenLogFile => {
                 if let Some(dir) = Directory::logs_directory() {
                     self.open_paths(&[PathObject::from_path(
@@ -825,6 +1231,9 @@ pub fn run_workbench_command(
                     open_uri(&dir);
                 }
             }
+            ExportDiagnosticBundle => {
+                self.export_diagnostic_bundle();
+            }
             OpenProxyDirectory => {
                 if let Some(dir) = Directory::proxy_directory() {
                     open_uri(&dir);
@@ -840,12 +1249,82 @@ pub fn run_workbench_command(
                     open_uri(&dir);
                 }
             }
+            OpenFileTemplatesDirectory => {
+                if let Some(dir) = Directory::templates_directory() {
+                    open_uri(&dir);
+                }
+            }
+            OpenQueryOverridesDirectory => {
+                if let Some(dir) = Directory::query_overrides_directory() {
+                    open_uri(&dir);
+                }
+            }
 
             InstallTheme => {}
             ExportCurrentThemeSettings => {
                 self.main_split.export_theme();
             }
             ToggleInlayHints => {}
+            ToggleWordWrap => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.toggle_word_wrap();
+                }
+            }
+            ToggleLogMode => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.doc().toggle_log_mode();
+                }
+            }
+            ToggleLogFollow => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    let doc = editor.doc();
+                    if doc.log.enabled.get_untracked() {
+                        doc.log.follow.update(|follow| *follow = !*follow);
+                    }
+                }
+            }
+            CopyJsonPath => {
+                if let Some(path) = self
+                    .main_split
+                    .active_editor
+                    .get_untracked()
+                    .and_then(|editor| editor.json_path())
+                {
+                    let mut clipboard = SystemClipboard::new();
+                    clipboard.put_string(path);
+                }
+            }
+            FormatJson => self.format_builtin(BuiltinFormat::Json),
+            MinifyJson => self.format_builtin(BuiltinFormat::JsonMinify),
+            SortJsonKeys => self.format_builtin(BuiltinFormat::JsonSortKeys),
+            FormatToml => self.format_builtin(BuiltinFormat::Toml),
+            FormatXml => self.format_builtin(BuiltinFormat::Xml),
+            SelectAllMatchesInSelection => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.select_all_matches_in_selection();
+                }
+            }
+            SplitSelectionIntoLines => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.split_selection_into_lines();
+                }
+            }
+            SelectAllFindMatches => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.select_all_find_matches();
+                }
+            }
+            AddCursorAtNextFindMatch => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.add_cursor_at_next_find_match();
+                }
+            }
+            ShowFileHistory => {
+                self.show_file_history(false);
+            }
+            ShowLineHistory => {
+                self.show_file_history(true);
+            }
 
             // ==== Window ====
             ReloadWindow => {
@@ -957,13 +1436,7 @@ pub fn run_workbench_command(
 
             // ==== Terminal ====
             NewTerminalTab => {
-                self.terminal.new_tab(
-                    self.common
-                        .config
-                        .get_untracked()
-                        .terminal
-                        .get_default_profile(),
-                );
+                self.terminal.new_tab(self.terminal_profile(None));
                 if !self.panel.is_panel_visible(&PanelKind::Terminal) {
                     self.panel.show_panel(&PanelKind::Terminal);
                 }
@@ -1001,6 +1474,9 @@ pub fn run_workbench_command(
                 }
                 self.common.focus.set(Focus::Panel(PanelKind::Terminal));
             }
+            TerminalPreviousCommand => self.scroll_terminal_to_command(false),
+            TerminalNextCommand => self.scroll_terminal_to_command(true),
+            OpenLastCommandOutput => self.open_last_command_output(),
 
             // ==== Remote ====
             ConnectSshHost => {
@@ -1010,6 +1486,9 @@ pub fn run_workbench_command(
             ConnectWslHost => {
                 self.palette.run(PaletteKind::WslHost);
             }
+            OpenFolderInContainer => self.open_folder_in_container(),
+            RebuildContainer => self.container_lifecycle(true),
+            StopContainer => self.container_lifecycle(false),
             DisconnectRemote => {
                 self.common.window_common.window_command.send(
                     WindowCommand::SetWorkspace {
@@ -1024,6 +1503,56 @@ pub fn run_workbench_command(
 
             // ==== Palette Commands ====
             PaletteHelp => self.palette.run(PaletteKind::PaletteHelp),
+            PaletteCommandHistory => {
+                self.palette.run(PaletteKind::CommandHistory);
+            }
+            RepeatLastCommand => self.repeat_command(1),
+            RepeatCommand => {
+                if let Some(n) = data.and_then(|data| data.as_u64()) {
+                    self.repeat_command(n as usize);
+                }
+            }
+            StartMacroRecording => {
+                self.common.keypress.with_untracked(|keypress| {
+                    keypress.macros.update(|macros| macros.start())
+                });
+            }
+            StopMacroRecording => {
+                self.common.keypress.with_untracked(|keypress| {
+                    keypress.macros.update(|macros| macros.stop())
+                });
+            }
+            ReplayMacro => {
+                let steps = self.common.keypress.with_untracked(|keypress| {
+                    keypress
+                        .macros
+                        .with_untracked(|macros| macros.last().to_vec())
+                });
+                let count = data.and_then(|data| data.as_u64()).unwrap_or(1);
+                self.run_macro(&steps, count as usize);
+            }
+            SaveMacro => {
+                let name = data.and_then(|data| data.as_str().map(String::from));
+                match name {
+                    Some(name) => self.save_macro(&name),
+                    None => self.palette.run(PaletteKind::SaveMacro),
+                }
+            }
+            RunMacro => {
+                let run = data
+                    .and_then(|data| serde_json::from_value::<MacroRun>(data).ok());
+                match run {
+                    Some(run) => {
+                        let steps = self.common.config.with_untracked(|config| {
+                            config.macros.get(&run.name).cloned()
+                        });
+                        if let Some(steps) = steps {
+                            self.run_macro(&steps, run.count.unwrap_or(1));
+                        }
+                    }
+                    None => self.palette.run(PaletteKind::Macro),
+                }
+            }
             PaletteLine => {
                 self.palette.run(PaletteKind::Line);
             }
@@ -1043,6 +1572,9 @@ pub fn run_workbench_command(
             PaletteRunAndDebug => {
                 self.palette.run(PaletteKind::RunAndDebug);
             }
+            PaletteAttachProcess => {
+                self.palette.run(PaletteKind::AttachProcess);
+            }
             PaletteSCMReferences => {
                 self.palette.run(PaletteKind::SCMReferences);
             }
@@ -1052,6 +1584,15 @@ pub fn run_workbench_command(
             ChangeIconTheme => {
                 self.palette.run(PaletteKind::IconTheme);
             }
+            SwitchProfile => {
+                self.palette.run(PaletteKind::Profile);
+            }
+            RollBackWorkspaceRefactor => {
+                self.palette.run(PaletteKind::Refactor);
+            }
+            ManageGrammars => {
+                self.palette.run(PaletteKind::Grammar);
+            }
             ChangeFileLanguage => {
                 self.palette.run(PaletteKind::Language);
             }
@@ -1179,6 +1720,35 @@ pub fn run_workbench_command(
             ToggleProblemFocus => {
                 self.toggle_panel_focus(PanelKind::Problem);
             }
+            ToggleDebugConsoleFocus => {
+                self.toggle_panel_focus(PanelKind::DebugConsole);
+            }
+            ToggleFlameFocus => {
+                self.toggle_panel_focus(PanelKind::Flame);
+            }
+            ToggleOutputFocus => {
+                self.toggle_panel_focus(PanelKind::Output);
+            }
+            ToggleJsonTreeFocus => {
+                self.toggle_panel_focus(PanelKind::JsonTree);
+            }
+            ToggleSyntaxTreeFocus => {
+                self.toggle_panel_focus(PanelKind::SyntaxTree);
+            }
+            ToggleNotificationsFocus => {
+                self.toggle_panel_focus(PanelKind::Notifications);
+            }
+            ToggleDoNotDisturb => {
+                self.common.notifications.update(|notifications| {
+                    notifications
+                        .set_do_not_disturb(!notifications.do_not_disturb);
+                });
+            }
+            ClearNotifications => {
+                self.common.notifications.update(|notifications| {
+                    notifications.clear();
+                });
+            }
             ToggleSearchFocus => {
                 self.toggle_panel_focus(PanelKind::Search);
             }
@@ -1200,6 +1770,24 @@ pub fn run_workbench_command(
             ToggleDebugVisual => {
                 self.toggle_panel_visual(PanelKind::Debug);
             }
+            ToggleDebugConsoleVisual => {
+                self.toggle_panel_visual(PanelKind::DebugConsole);
+            }
+            ToggleFlameVisual => {
+                self.toggle_panel_visual(PanelKind::Flame);
+            }
+            ToggleOutputVisual => {
+                self.toggle_panel_visual(PanelKind::Output);
+            }
+            ToggleJsonTreeVisual => {
+                self.toggle_panel_visual(PanelKind::JsonTree);
+            }
+            ToggleSyntaxTreeVisual => {
+                self.toggle_panel_visual(PanelKind::SyntaxTree);
+            }
+            ToggleNotificationsVisual => {
+                self.toggle_panel_visual(PanelKind::Notifications);
+            }
             ToggleSearchVisual => {
                 self.toggle_panel_visual(PanelKind::Search);
             }
@@ -1215,6 +1803,27 @@ pub fn run_workbench_command(
             ShowEnvironment => {
                 self.main_split.show_env();
             }
+            TogglePerfOverlay => {
+                let show = !self.common.config.get_untracked().ui.show_perf_overlay;
+                LapceConfig::update_file(
+                    "ui",
+                    "show-perf-overlay",
+                    toml_edit::Value::from(show),
+                );
+            }
+            ExportSettings => {
+                self.export_settings();
+            }
+            ImportSettings => {
+                self.import_settings();
+            }
+            OpenFlameGraph => {
+                self.show_panel(PanelKind::Flame);
+                self.flame.open();
+            }
+            SyncSettings => {
+                self.sync_settings(None);
+            }
 
             // ==== Source Control ====
             SourceControlInit => {
@@ -1492,6 +2101,7 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             }
             InternalCommand::FinishNewNode { is_dir, path } => {
                 let file_explorer = self.file_explorer.clone();
+                let palette = self.palette.clone();
                 let internal_command = self.common.internal_command;
 
                 let send = create_ext_action(
@@ -1508,8 +2118,11 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                                 {
                                     if !is_dir {
                                         internal_command.send(
-                                            InternalCommand::OpenFile { path },
+                                            InternalCommand::OpenFile {
+                                                path: path.clone(),
+                                            },
                                         );
+                                        palette.offer_file_templates(Some(path));
                                     }
                                 }
                             }
@@ -1555,10 +2168,20 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             InternalCommand::JumpToLocation { location } => {
                 self.main_split.jump_to_location(location, None);
             }
+            InternalCommand::PrefetchDoc { path } => {
+                self.main_split.prefetch(path);
+            }
             InternalCommand::PaletteReferences { references } => {
                 self.palette.references.set(references);
                 self.palette.run(PaletteKind::Reference);
             }
+            InternalCommand::PaletteGitHistory { path, commits } => {
+                self.palette.git_history.set(Some((path, commits)));
+                self.palette.run(PaletteKind::GitHistory);
+            }
+            InternalCommand::OpenCommitChanges { path, commit } => {
+                self.main_split.open_commit_changes(path, commit);
+            }
             InternalCommand::Split {
                 direction,
                 editor_tab_id,
@@ -1607,8 +2230,85 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             InternalCommand::RunCodeAction { plugin_id, action } => {
                 self.main_split.run_code_action(plugin_id, action);
             }
-            InternalCommand::ApplyWorkspaceEdit { edit } => {
-                self.main_split.apply_workspace_edit(&edit);
+            InternalCommand::ApplyWorkspaceEdit { edit, title } => {
+                self.main_split.apply_workspace_edit(&edit, title);
+            }
+            InternalCommand::InstallGrammar { source } => {
+                let notifications = self.common.notifications;
+                let send = create_ext_action(
+                    self.scope,
+                    move |result: anyhow::Result<(String, bool)>| {
+                        let (typ, message) = match result {
+                            Ok((name, false)) => (
+                                MessageType::INFO,
+                                format!("Installed the {name} grammar"),
+                            ),
+                            Ok((name, true)) => (
+                                MessageType::INFO,
+                                format!(
+                                    "Updated the {name} grammar. If it was in use \
+                                     already, the update applies after a restart"
+                                ),
+                            ),
+                            Err(err) => (
+                                MessageType::ERROR,
+                                format!("Failed to install the grammar: {err}"),
+                            ),
+                        };
+                        notifications.update(|notifications| {
+                            notifications.push(
+                                "Tree-sitter Grammars",
+                                typ,
+                                message,
+                                Vec::new(),
+                            );
+                        });
+                    },
+                );
+                std::thread::spawn(move || {
+                    send(crate::grammar::install_grammar(&source));
+                });
+            }
+            InternalCommand::RunNotificationAction { action } => match action {
+                NotificationAction::OpenSettings => {
+                    self.run_workbench_command(
+                        LapceWorkbenchCommand::OpenSettings,
+                        None,
+                    );
+                }
+                NotificationAction::OpenLogFile => {
+                    self.run_workbench_command(
+                        LapceWorkbenchCommand::OpenLogFile,
+                        None,
+                    );
+                }
+                NotificationAction::CopyDetails(details) => {
+                    SystemClipboard::new().put_string(details);
+                }
+                NotificationAction::OpenIncidentReport(path) => {
+                    self.open_paths(&[PathObject::from_path(path, false)]);
+                }
+                NotificationAction::RestartPlugin(id) => {
+                    let meta = self.plugin.installed.with_untracked(|installed| {
+                        installed.get(&id).map(|volt| volt.meta.get_untracked())
+                    });
+                    if let Some(meta) = meta {
+                        self.plugin.reload_volt(meta);
+                    }
+                }
+            },
+            InternalCommand::RollbackRefactor { id } => {
+                let notifications = self.common.notifications;
+                self.main_split.rollback_refactor(id, move |message| {
+                    notifications.update(|notifications| {
+                        notifications.push(
+                            "Workspace Refactors",
+                            MessageType::ERROR,
+                            message,
+                            Vec::new(),
+                        );
+                    });
+                });
             }
             InternalCommand::SaveJumpLocation {
                 path,
@@ -1619,7 +2319,8 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                     .save_jump_location(path, offset, scroll_offset);
             }
             InternalCommand::NewTerminal { profile } => {
-                self.terminal.new_tab(profile);
+                self.terminal
+                    .new_tab(self.terminal_profile(profile.as_deref()));
             }
             InternalCommand::SplitTerminal { term_id } => {
                 self.terminal.split(term_id);
@@ -1683,10 +2384,10 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                         toml_edit::Value::from(name),
                     );
                 } else {
-                    let mut new_config = self.common.config.get_untracked();
-                    Arc::make_mut(&mut new_config)
-                        .set_color_theme(&self.workspace, &name);
-                    self.set_config.set(new_config);
+                    let mut new_config =
+                        (*self.common.config.get_untracked()).clone();
+                    new_config.set_color_theme(&self.workspace, &name);
+                    self.replace_config(new_config);
                 }
             }
             InternalCommand::SetIconTheme { name, save } => {
@@ -1698,12 +2399,15 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                         toml_edit::Value::from(name),
                     );
                 } else {
-                    let mut new_config = self.common.config.get_untracked();
-                    Arc::make_mut(&mut new_config)
-                        .set_icon_theme(&self.workspace, &name);
-                    self.set_config.set(new_config);
+                    let mut new_config =
+                        (*self.common.config.get_untracked()).clone();
+                    new_config.set_icon_theme(&self.workspace, &name);
+                    self.replace_config(new_config);
                 }
             }
+            InternalCommand::SetProfile { name } => {
+                self.set_profile(name);
+            }
             InternalCommand::SetModal { modal } => {
                 LapceConfig::update_file(
                     "core",
@@ -1763,21 +2467,35 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                 right_path,
             } => self.main_split.open_diff_files(left_path, right_path),
             InternalCommand::ExecuteProcess { program, arguments } => {
-                let mut cmd = match std::process::Command::new(program)
+                let command_line = std::iter::once(program.as_str())
+                    .chain(arguments.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut cmd = match std::process::Command::new(&program)
                     .args(arguments)
                     .spawn()
                 {
                     Ok(v) => v,
                     Err(e) => {
-                        return event!(Level::ERROR, "Failed to spawn process: {e}")
+                        return self.common.report_error(
+                            ErrorReport::new(
+                                "Process",
+                                format!("Failed to spawn {program}: {e}"),
+                            )
+                            .command(command_line),
+                        );
                     }
                 };
 
                 match cmd.wait() {
                     Ok(v) => event!(Level::TRACE, "Process exited with status {v}"),
-                    Err(e) => {
-                        event!(Level::ERROR, "Proces exited with an error: {e}")
-                    }
+                    Err(e) => self.common.report_error(
+                        ErrorReport::new(
+                            "Process",
+                            format!("{program} exited with an error: {e}"),
+                        )
+                        .command(command_line),
+                    ),
                 };
             }
         }
@@ -1789,6 +2507,31 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
             CoreNotification::ProxyStatus { status } => {
                 self.common.proxy_status.set(Some(status.to_owned()));
             }
+            CoreNotification::CoverageChanged { coverage } => {
+                self.common.coverage.set(
+                    coverage
+                        .iter()
+                        .map(|(path, file)| (path.clone(), Rc::new(file.clone())))
+                        .collect(),
+                );
+            }
+            CoreNotification::InlineCompletionItems {
+                request_id,
+                provider,
+                items,
+            } => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.set_plugin_inline_completions(
+                        *request_id,
+                        provider,
+                        items,
+                    );
+                }
+            }
+            CoreNotification::RichOutput { source, items } => {
+                self.output.push(source, items.clone());
+                self.show_panel(PanelKind::Output);
+            }
             CoreNotification::DiffInfo { diff } => {
                 self.source_control.branch.set(diff.head.clone());
                 self.source_control
@@ -1811,11 +2554,47 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                         .collect();
                 });
 
-                let docs = self.main_split.docs.get_untracked();
-                for (_, doc) in docs {
-                    doc.retrieve_head();
+                if self.common.window_common.focused.get_untracked() {
+                    self.retrieve_heads();
+                } else {
+                    self.heads_outdated.set(true);
                 }
             }
+            CoreNotification::CompletionProvider {
+                plugin_id,
+                name,
+                trigger_characters,
+            } => {
+                self.common.completion.update(|completion| {
+                    completion.providers.insert(
+                        *plugin_id,
+                        CompletionProvider {
+                            name: name.clone(),
+                            trigger_characters: trigger_characters.clone(),
+                        },
+                    );
+                });
+            }
+            CoreNotification::LanguageServerStarted {
+                plugin_id,
+                name,
+                server_info,
+            } => {
+                let description = match server_info {
+                    Some(ServerInfo {
+                        name: server,
+                        version: Some(version),
+                    }) => format!("{name}: {server} {version}"),
+                    Some(ServerInfo {
+                        name: server,
+                        version: None,
+                    }) => format!("{name}: {server}"),
+                    None => name.clone(),
+                };
+                self.common.language_servers.update(|servers| {
+                    servers.insert(*plugin_id, description);
+                });
+            }
             CoreNotification::CompletionResponse {
                 request_id,
                 input,
@@ -1859,6 +2638,10 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 {
                     doc.init_diagnostics();
                 }
+                self.common
+                    .editor_events
+                    .send(EditorEvent::DiagnosticsUpdated { path: path.clone() });
+                automation::emit(AutomationEvent::DiagnosticsChanged { path });
             }
             CoreNotification::TerminalProcessStopped { term_id } => {
                 let _ = self
@@ -1904,6 +2687,17 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
             CoreNotification::DapContinued { dap_id } => {
                 self.terminal.dap_continued(dap_id);
             }
+            CoreNotification::DapOutput { dap_id, output } => {
+                self.terminal.dap_output(dap_id, output);
+            }
+            CoreNotification::DapCapabilities {
+                dap_id,
+                capabilities,
+            } => {
+                self.terminal.debug.capabilities.update(|all| {
+                    all.insert(*dap_id, capabilities.clone());
+                });
+            }
             CoreNotification::DapBreakpointsResp {
                 path, breakpoints, ..
             } => {
@@ -1945,6 +2739,27 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
             CoreNotification::OpenFileChanged { path, content } => {
                 self.main_split.open_file_changed(path, content);
             }
+            CoreNotification::BufferLoadProgress {
+                buffer_id,
+                loaded,
+                total,
+            } => {
+                let doc = self.main_split.docs.with_untracked(|docs| {
+                    docs.values()
+                        .find(|doc| doc.buffer_id == *buffer_id)
+                        .cloned()
+                });
+                if let Some(doc) = doc.filter(|doc| !doc.loaded()) {
+                    doc.load_progress.set(Some((*loaded, *total)));
+                }
+            }
+            CoreNotification::OpenFileAppended {
+                path,
+                offset,
+                content,
+            } => {
+                self.main_split.open_file_appended(path, *offset, content);
+            }
             CoreNotification::VoltInstalled { volt, icon } => {
                 self.plugin.volt_installed(volt, icon);
             }
@@ -1955,7 +2770,42 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 self.update_progress(progress);
             }
             CoreNotification::ShowMessage { title, message } => {
-                self.show_message(title, message);
+                // A failing language server can often be dealt with by
+                // restarting its plugin
+                let plugin = title
+                    .strip_prefix("Plugin: ")
+                    .filter(|_| message.typ == MessageType::ERROR)
+                    .and_then(|name| {
+                        self.plugin.installed.with_untracked(|installed| {
+                            installed
+                                .iter()
+                                .find(|(_, volt)| {
+                                    volt.meta.with_untracked(|meta| {
+                                        meta.display_name == name
+                                    })
+                                })
+                                .map(|(id, _)| id.clone())
+                        })
+                    });
+                let mut actions: Vec<NotificationAction> = plugin
+                    .map(NotificationAction::RestartPlugin)
+                    .into_iter()
+                    .collect();
+                // The log has the details of why a remote didn't start
+                if message.typ == MessageType::ERROR
+                    && ["SSH Remote", "WSL Remote", "Dev Container"]
+                        .contains(&title.as_str())
+                {
+                    actions.push(NotificationAction::OpenLogFile);
+                }
+                self.common.notifications.update(|notifications| {
+                    notifications.push(
+                        title,
+                        message.typ,
+                        &message.message,
+                        actions,
+                    );
+                });
             }
             CoreNotification::Log {
                 level,
@@ -2018,6 +2868,7 @@ pub fn key_down<'a>(&self, event: impl Into<EventRef<'a>> + Copy) -> bool {
         if self.alert_data.active.get_untracked() {
             return false;
         }
+        perf::key_pressed();
         let focus = self.common.focus.get_untracked();
         let keypress = self.common.keypress.get_untracked();
         let handle = match focus {
@@ -2041,6 +2892,9 @@ pub fn key_down<'a>(&self, event: impl Into<EventRef<'a>> + Copy) -> bool {
             Focus::Panel(PanelKind::SourceControl) => {
                 Some(keypress.key_down(event, &self.source_control))
             }
+            Focus::Panel(PanelKind::DebugConsole) => {
+                Some(keypress.key_down(event, &self.debug_console))
+            }
             _ => None,
         };
 
@@ -2318,14 +3172,20 @@ fn toggle_panel_focus(&self, kind: PanelKind) {
             PanelKind::FileExplorer
             | PanelKind::Plugin
             | PanelKind::Problem
-            | PanelKind::Debug => {
+            | PanelKind::Debug
+            | PanelKind::Flame
+            | PanelKind::Output
+            | PanelKind::JsonTree
+            | PanelKind::SyntaxTree
+            | PanelKind::Notifications => {
                 // Some panels don't accept focus (yet). Fall back to visibility check
                 // in those cases.
                 self.panel.is_panel_visible(&kind)
             }
-            PanelKind::Terminal | PanelKind::SourceControl | PanelKind::Search => {
-                self.is_panel_focused(kind)
-            }
+            PanelKind::Terminal
+            | PanelKind::SourceControl
+            | PanelKind::Search
+            | PanelKind::DebugConsole => self.is_panel_focused(kind),
         };
         if should_hide {
             self.hide_panel(kind);
@@ -2390,13 +3250,7 @@ pub fn show_panel(&self, kind: PanelKind) {
                 .tab_info
                 .with_untracked(|info| info.tabs.is_empty())
         {
-            self.terminal.new_tab(
-                self.common
-                    .config
-                    .get_untracked()
-                    .terminal
-                    .get_default_profile(),
-            );
+            self.terminal.new_tab(self.terminal_profile(None));
         }
         self.panel.show_panel(&kind);
         if kind == PanelKind::Search
@@ -2424,7 +3278,15 @@ fn run_and_debug(
                 self.run_in_terminal(cx, mode, config, false);
             }
             RunDebugMode::Debug => {
-                if config.prelaunch.is_some() {
+                if config.attach_pid.is_some() {
+                    // The adapter doesn't ask for a terminal to attach, so
+                    // the session gets a terminal in the process' directory
+                    self.run_in_terminal(cx, mode, config, true);
+                    self.common.proxy.dap_start(
+                        config.clone(),
+                        self.terminal.debug.source_breakpoints(),
+                    )
+                } else if config.prelaunch.is_some() {
                     self.run_in_terminal(cx, mode, config, false);
                 } else {
                     self.common.proxy.dap_start(
@@ -2534,6 +3396,324 @@ pub fn show_alert(&self, title: String, msg: String, buttons: Vec<AlertButton>)
         self.alert_data.active.set(true);
     }
 
+    fn settings_bundle(&self) -> Option<SettingsBundle> {
+        let plugins = self
+            .plugin
+            .installed
+            .with_untracked(|installed| installed.keys().cloned().collect());
+        match SettingsBundle::collect(plugins) {
+            Ok(bundle) => Some(bundle),
+            Err(e) => {
+                self.show_settings_message(
+                    MessageType::ERROR,
+                    format!("Failed to read the settings: {e}"),
+                );
+                None
+            }
+        }
+    }
+
+    /// Run the window tab with another configuration profile, creating it if
+    /// it doesn't exist, and make it the default for the workspace.
+    fn set_profile(&self, name: Option<String>) {
+        if let Some(name) = name.as_deref() {
+            if let Err(err) = profile::create(name) {
+                self.show_message(
+                    "Profiles",
+                    &ShowMessageParams {
+                        typ: MessageType::ERROR,
+                        message: format!("Failed to create profile {name}: {err}"),
+                    },
+                );
+                return;
+            }
+        }
+
+        let db: Arc<LapceDb> = use_context().unwrap();
+        if let Err(err) = db.save_workspace_profile(&self.workspace, name.as_deref())
+        {
+            error!("Failed to save the profile of the workspace: {err}");
+            self.show_message(
+                "Profiles",
+                &ShowMessageParams {
+                    typ: MessageType::ERROR,
+                    message: format!(
+                        "Failed to save the profile of the workspace: {err}"
+                    ),
+                },
+            );
+        }
+
+        let profile_disabled = name
+            .as_deref()
+            .map(profile::disabled_volts)
+            .unwrap_or_default();
+        self.common.profile.set(name);
+        self.plugin
+            .set_profile_disabled(HashSet::from_iter(profile_disabled));
+        self.reload_config();
+    }
+
+    fn open_profile_file(&self, file: fn(&str) -> Option<PathBuf>) {
+        let Some(name) = self.common.profile.get_untracked() else {
+            self.show_message(
+                "Profiles",
+                &ShowMessageParams {
+                    typ: MessageType::INFO,
+                    message: "The default profile only uses the user settings. \
+                              Switch to another profile first."
+                        .to_string(),
+                },
+            );
+            return;
+        };
+        if let Some(path) = file(&name) {
+            self.main_split.jump_to_location(
+                EditorLocation {
+                    path,
+                    position: None,
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                },
+                None,
+            );
+        }
+    }
+
+    fn show_settings_message(&self, typ: MessageType, message: String) {
+        let actions = if typ == MessageType::ERROR {
+            vec![NotificationAction::OpenSettings]
+        } else {
+            Vec::new()
+        };
+        self.common.notifications.update(|notifications| {
+            notifications.push("Settings", typ, message, actions);
+        });
+    }
+
+    fn export_settings(&self) {
+        let Some(bundle) = self.settings_bundle() else {
+            return;
+        };
+        let window_tab_data = self.clone();
+        let options = FileDialogOptions::new().default_name("lapce-settings.json");
+        save_as(options, move |file| {
+            let Some(path) = file.and_then(|mut file| file.path.pop()) else {
+                return;
+            };
+            let result = bundle.to_json().and_then(|json| {
+                std::fs::write(&path, json).map_err(anyhow::Error::from)
+            });
+            match result {
+                Ok(()) => window_tab_data.show_settings_message(
+                    MessageType::INFO,
+                    format!("Exported the settings to {}", path.display()),
+                ),
+                Err(e) => window_tab_data.show_settings_message(
+                    MessageType::ERROR,
+                    format!("Failed to export the settings: {e}"),
+                ),
+            }
+        });
+    }
+
+    /// Retrieve the `head` versions of the documents, along with their blame
+    fn retrieve_heads(&self) {
+        let docs = self.main_split.docs.get_untracked();
+        for (_, doc) in docs {
+            doc.retrieve_head();
+        }
+    }
+
+    /// Catch up with the refreshes that waited for the window to get the
+    /// focus back
+    pub fn window_focused(&self) {
+        if self.heads_outdated.get_untracked() {
+            self.heads_outdated.set(false);
+            self.retrieve_heads();
+        }
+    }
+
+    fn export_diagnostic_bundle(&self) {
+        let mut open_files: Vec<PathBuf> = self
+            .main_split
+            .docs
+            .with_untracked(|docs| docs.keys().cloned().collect());
+        open_files.sort();
+        let plugins = self.plugin.installed.with_untracked(|installed| {
+            installed
+                .values()
+                .map(|volt| {
+                    let meta = volt.meta.get_untracked();
+                    format!("{} {} ({})", meta.display_name, meta.version, meta.id())
+                })
+                .collect()
+        });
+        let mut language_servers: Vec<String> = self
+            .common
+            .language_servers
+            .with_untracked(|servers| servers.values().cloned().collect());
+        language_servers.sort();
+
+        let mut config_files: Vec<PathBuf> =
+            [LapceConfig::settings_file(), LapceConfig::keymaps_file()]
+                .into_iter()
+                .flatten()
+                .collect();
+        if let Some(profile) = self.common.profile.get_untracked() {
+            config_files.extend(profile::settings_file(&profile));
+            config_files.extend(profile::keymaps_file(&profile));
+        }
+        if let (LapceWorkspaceType::Local, Some(path)) =
+            (&self.workspace.kind, &self.workspace.path)
+        {
+            config_files.push(path.join(".lapce").join("settings.toml"));
+        }
+
+        let bundle = DiagnosticBundle {
+            workspace: self.workspace.path.clone(),
+            open_files,
+            config_files,
+            plugins,
+            language_servers,
+            perf: if perf::is_enabled() {
+                perf::format_summary(&perf::summary())
+            } else {
+                "Not recorded, enable ui.show-perf-overlay to record it".to_string()
+            },
+        };
+
+        let window_tab_data = self.clone();
+        let options = FileDialogOptions::new().default_name(format!(
+            "lapce-diagnostics-{}.zip",
+            chrono::Local::now().format("%Y-%m-%d")
+        ));
+        save_as(options, move |file| {
+            let Some(path) = file.and_then(|mut file| file.path.pop()) else {
+                return;
+            };
+            let common = window_tab_data.common.clone();
+            let send = create_ext_action(
+                window_tab_data.scope,
+                move |(path, result): (PathBuf, anyhow::Result<()>)| match result {
+                    Ok(()) => common.notifications.update(|notifications| {
+                        notifications.push(
+                            "Diagnostic Bundle",
+                            MessageType::INFO,
+                            format!(
+                                "Exported the diagnostic bundle to {}",
+                                path.display()
+                            ),
+                            Vec::new(),
+                        );
+                    }),
+                    Err(e) => common.report_error(
+                        ErrorReport::new("Diagnostic Bundle", e.to_string())
+                            .path(path)
+                            .command("export_diagnostic_bundle"),
+                    ),
+                },
+            );
+            std::thread::spawn(move || {
+                let result = bundle.write_zip(&path);
+                send((path, result));
+            });
+        });
+    }
+
+    fn import_settings(&self) {
+        let window_tab_data = self.clone();
+        open_file(FileDialogOptions::new(), move |file| {
+            let Some(path) = file.and_then(|mut file| file.path.pop()) else {
+                return;
+            };
+            let bundle = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| SettingsBundle::from_json(&json));
+            match bundle {
+                Ok(bundle) => window_tab_data.apply_settings_bundle(bundle),
+                Err(e) => window_tab_data.show_settings_message(
+                    MessageType::ERROR,
+                    format!("Failed to import {}: {e}", path.display()),
+                ),
+            }
+        });
+    }
+
+    fn apply_settings_bundle(&self, bundle: SettingsBundle) {
+        if let Err(e) = bundle.apply() {
+            self.show_settings_message(
+                MessageType::ERROR,
+                format!("Failed to apply the settings: {e}"),
+            );
+            return;
+        }
+        self.plugin.install_volts_by_id(bundle.plugins);
+        self.show_settings_message(
+            MessageType::INFO,
+            "The settings have been updated".to_string(),
+        );
+    }
+
+    pub fn sync_settings(&self, resolution: Option<ConflictResolution>) {
+        let repository = self
+            .common
+            .config
+            .with_untracked(|config| config.core.settings_sync_repository.clone());
+        if repository.is_empty() {
+            self.show_settings_message(
+                MessageType::INFO,
+                "Set `core.settings-sync-repository` to a git repository to sync \
+                 the settings through"
+                    .to_string(),
+            );
+            return;
+        }
+        let Some(local) = self.settings_bundle() else {
+            return;
+        };
+
+        let window_tab_data = self.clone();
+        let send = create_ext_action(self.scope, move |result| match result {
+            Ok(SyncOutcome::Synced(bundle)) => {
+                window_tab_data.apply_settings_bundle(bundle);
+            }
+            Ok(SyncOutcome::Conflict) => {
+                window_tab_data.show_settings_sync_conflict();
+            }
+            Err(e) => window_tab_data.show_settings_message(
+                MessageType::ERROR,
+                format!("Failed to sync the settings: {e}"),
+            ),
+        });
+        std::thread::spawn(move || {
+            send(settings_sync::sync(&repository, &local, resolution));
+        });
+    }
+
+    fn show_settings_sync_conflict(&self) {
+        let internal_command = self.common.internal_command;
+        let button = |text: &str, resolution: ConflictResolution| {
+            let window_tab_data = self.clone();
+            AlertButton {
+                text: text.to_string(),
+                action: Rc::new(move || {
+                    internal_command.send(InternalCommand::HideAlert);
+                    window_tab_data.sync_settings(Some(resolution));
+                }),
+            }
+        };
+        self.show_alert(
+            "The settings were changed here and in the sync repository".to_string(),
+            "Choose which changes to keep where they conflict.".to_string(),
+            vec![
+                button("Keep Local", ConflictResolution::KeepLocal),
+                button("Use Repository", ConflictResolution::UseRepository),
+            ],
+        );
+    }
+
     fn update_progress(&self, progress: &ProgressParams) {
         let token = progress.token.clone();
         match &progress.value {
@@ -2566,11 +3746,286 @@ fn update_progress(&self, progress: &ProgressParams) {
         }
     }
 
-    fn show_message(&self, title: &str, message: &ShowMessageParams) {
-        self.messages.update(|messages| {
-            messages.push((title.to_string(), message.clone()));
+    pub fn show_message(&self, title: &str, message: &ShowMessageParams) {
+        self.common.notifications.update(|notifications| {
+            notifications.push(title, message.typ, &message.message, Vec::new());
+        });
+    }
+
+    /// Format the active editor with a built-in formatter, telling why when
+    /// its document can't be formatted
+    fn format_builtin(&self, format: BuiltinFormat) {
+        let Some(editor) = self.main_split.active_editor.get_untracked() else {
+            return;
+        };
+        if let Err(error) = editor.format_builtin(format) {
+            self.show_message(
+                format.name(),
+                &ShowMessageParams {
+                    typ: MessageType::ERROR,
+                    message: error,
+                },
+            );
+        }
+    }
+
+    /// Scroll the active terminal to the prompt of the next or previous
+    /// command the shell reported.
+    fn scroll_terminal_to_command(&self, next: bool) {
+        if let Some(terminal) = self
+            .terminal
+            .active_tab(false)
+            .and_then(|tab| tab.active_terminal(false))
+        {
+            terminal.raw.get_untracked().write().scroll_to_command(next);
+            self.common.view_id.get_untracked().request_paint();
+        }
+    }
+
+    /// Open what the last command of the active terminal printed in a scratch
+    /// editor, where the locations in it can be followed.
+    fn open_last_command_output(&self) {
+        let output = self
+            .terminal
+            .active_tab(false)
+            .and_then(|tab| tab.active_terminal(false))
+            .and_then(|terminal| terminal.raw.get_untracked().read().last_output());
+        let Some((command, output)) = output else {
+            self.show_message(
+                "Terminal",
+                &ShowMessageParams {
+                    typ: MessageType::INFO,
+                    message: "No command output was found. The shell needs to \
+                              mark its commands with the OSC 133 sequences of \
+                              shell integration."
+                        .to_string(),
+                },
+            );
+            return;
+        };
+        self.main_split.show_command_output(&command, &output);
+    }
+
+    /// The terminal profile `name`, or the default one, resolved against the
+    /// workspace and the active file.
+    pub fn terminal_profile(
+        &self,
+        name: Option<&str>,
+    ) -> Option<lapce_rpc::terminal::TerminalProfile> {
+        let editor = self.main_split.active_editor.get_untracked();
+        let file = editor.and_then(|editor| {
+            editor
+                .doc()
+                .content
+                .with_untracked(|content| content.path().cloned())
+        });
+        let config = self.common.config.get_untracked();
+        let name = name.unwrap_or_else(|| config.terminal.default_profile_name());
+        config
+            .terminal
+            .get_profile(name, &self.workspace, file.as_deref())
+    }
+
+    /// Open the workspace folder in its dev container, or pick the folder
+    /// when there isn't a local one open.
+    fn open_folder_in_container(&self) {
+        let window_command = self.common.window_common.window_command;
+        let notifications = self.common.notifications;
+        let open =
+            move |folder: PathBuf| match crate::devcontainer::container_workspace(
+                &folder,
+            ) {
+                Ok(workspace) => {
+                    window_command.send(WindowCommand::SetWorkspace { workspace });
+                }
+                Err(e) => notifications.update(|notifications| {
+                    notifications.push(
+                        "Dev Container",
+                        MessageType::ERROR,
+                        e.to_string(),
+                        Vec::new(),
+                    );
+                }),
+            };
+
+        if let (LapceWorkspaceType::Local, Some(path)) =
+            (&self.workspace.kind, &self.workspace.path)
+        {
+            open(path.clone());
+            return;
+        }
+        let options = FileDialogOptions::new().select_directories();
+        open_file(options, move |file| {
+            if let Some(path) = file.and_then(|mut file| file.path.pop()) {
+                open(path);
+            }
+        });
+    }
+
+    /// Stop the container of the workspace and reopen its folder locally, or
+    /// with `rebuild` create the container again from its config.
+    fn container_lifecycle(&self, rebuild: bool) {
+        let LapceWorkspaceType::RemoteContainer(host) = &self.workspace.kind else {
+            return;
+        };
+        let host = host.clone();
+        let window_command = self.common.window_common.window_command;
+        let notifications = self.common.notifications;
+        let workspace = if rebuild {
+            (*self.workspace).clone()
+        } else {
+            LapceWorkspace {
+                kind: LapceWorkspaceType::Local,
+                path: Some(host.folder.clone()),
+                last_open: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            }
+        };
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: Result<(), String>| match result {
+                Ok(()) => {
+                    window_command.send(WindowCommand::SetWorkspace { workspace });
+                }
+                Err(message) => notifications.update(|notifications| {
+                    notifications.push(
+                        "Dev Container",
+                        MessageType::ERROR,
+                        message,
+                        Vec::new(),
+                    );
+                }),
+            },
+        );
+        std::thread::spawn(move || {
+            let result = if rebuild {
+                crate::devcontainer::remove(&host)
+            } else {
+                crate::devcontainer::stop(&host)
+            };
+            send(result.map_err(|e| format!("{}: {e}", host.name)));
+        });
+    }
+
+    /// Pick a folder to open or a file of a remote workspace.
+    fn open_remote_file(&self, pick_folder: bool) {
+        #[cfg(windows)]
+        if let LapceWorkspaceType::RemoteWSL(wsl) = &self.workspace.kind {
+            self.open_wsl_file(wsl.clone(), pick_folder);
+            return;
+        }
+        self.palette.browse_remote(pick_folder);
+    }
+
+    /// Pick a folder to open or a file of a WSL workspace with the system
+    /// dialog, which sees the distro through its network share.
+    #[cfg(windows)]
+    fn open_wsl_file(&self, wsl: crate::workspace::WslHost, pick_folder: bool) {
+        let mut options = FileDialogOptions::new();
+        if pick_folder {
+            options = options.select_directories();
+        }
+        if let Some(path) = self.workspace.path.as_deref() {
+            options = options.force_starting_directory(wsl.to_windows_path(path));
+        }
+
+        let window_command = self.common.window_common.window_command;
+        let internal_command = self.common.internal_command;
+        open_file(options, move |file| {
+            let Some(path) = file.and_then(|mut file| file.path.pop()) else {
+                return;
+            };
+            let Some(path) = wsl.to_wsl_path(&path) else {
+                error!("{path:?} can't be opened from WSL {}", wsl.host);
+                return;
+            };
+            if pick_folder {
+                window_command.send(WindowCommand::SetWorkspace {
+                    workspace: LapceWorkspace {
+                        kind: LapceWorkspaceType::RemoteWSL(wsl.clone()),
+                        path: Some(path),
+                        last_open: std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_secs())
+                            .unwrap_or_default(),
+                    },
+                });
+            } else {
+                internal_command.send(InternalCommand::OpenFile { path });
+            }
         });
     }
+
+    /// List the commits that changed the active file in the palette, or with
+    /// `selection` only those that changed the selected lines.
+    fn show_file_history(&self, selection: bool) {
+        let Some(editor) = self.main_split.active_editor.get_untracked() else {
+            return;
+        };
+        let doc = editor.doc();
+        let Some(path) = doc.content.with_untracked(|content| {
+            content.path().filter(|_| content.is_file()).cloned()
+        }) else {
+            return;
+        };
+
+        let lines = if selection {
+            let (start, end) = editor.selection_lines();
+            // The history starts at `HEAD`, so follow the lines back to it
+            match doc.head_lines(start, end) {
+                Some(lines) => Some(lines),
+                None => {
+                    self.show_message(
+                        "Line History",
+                        &ShowMessageParams {
+                            typ: MessageType::INFO,
+                            message: "The selected lines haven't been committed"
+                                .to_string(),
+                        },
+                    );
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        let internal_command = self.common.internal_command;
+        let common = self.common.clone();
+        let send = {
+            let path = path.clone();
+            create_ext_action(self.scope, move |result| match result {
+                Ok(ProxyResponse::GitFileHistory { commits }) => {
+                    internal_command.send(InternalCommand::PaletteGitHistory {
+                        path: path.clone(),
+                        commits,
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    common.report_error(
+                        ErrorReport::new(
+                            "Git History",
+                            format!(
+                                "Failed to get the history of {}: {}",
+                                path.display(),
+                                err.message
+                            ),
+                        )
+                        .path(path.clone())
+                        .command("git_file_history"),
+                    );
+                }
+            })
+        };
+        self.common
+            .proxy
+            .git_file_history(path, lines, move |result| {
+                send(result);
+            });
+    }
 }
 
 /// Open path with the default application without blocking.
@@ -1,6 +1,11 @@
-use std::{collections::HashMap, fmt::Display, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 use crate::{debug::LapceBreakpoint, main_split::SplitInfo, panel::data::PanelInfo};
 
@@ -54,6 +59,92 @@ pub struct WslHost {
     pub host: String,
 }
 
+#[cfg(windows)]
+impl WslHost {
+    /// The path inside the distro of a path as Windows sees it, or `None` for
+    /// a path of another distro or of a network share
+    pub fn to_wsl_path(&self, path: &Path) -> Option<PathBuf> {
+        windows_to_wsl_path(&self.host, &path.to_string_lossy()).map(PathBuf::from)
+    }
+
+    /// The path as Windows sees it of a path inside the distro
+    pub fn to_windows_path(&self, path: &Path) -> PathBuf {
+        let share = if Path::new(r"\\wsl.localhost\").exists() {
+            "wsl.localhost"
+        } else {
+            "wsl$"
+        };
+        PathBuf::from(wsl_to_windows_path(
+            share,
+            &self.host,
+            &path.to_string_lossy(),
+        ))
+    }
+}
+
+/// `C:\a` is `/mnt/c/a` in WSL, and `\\wsl.localhost\<distro>\a` or
+/// `\\wsl$\<distro>\a` is `/a` in that distro.
+#[cfg(any(windows, test))]
+fn windows_to_wsl_path(distro: &str, path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    // Already a path inside the distro
+    if path.starts_with('/') && !path.starts_with("//") {
+        return Some(path);
+    }
+
+    // Long paths such as `\\?\C:\a`
+    let (path, long) = match path.strip_prefix("//?/") {
+        Some(rest) => (rest.to_string(), true),
+        None => (path, false),
+    };
+    let unc = if long {
+        path.strip_prefix("UNC/")
+    } else {
+        path.strip_prefix("//")
+    };
+    if let Some(unc) = unc {
+        let (share, rest) = unc.split_once('/')?;
+        if !share.eq_ignore_ascii_case("wsl.localhost")
+            && !share.eq_ignore_ascii_case("wsl$")
+        {
+            return None;
+        }
+        let (name, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        if !name.eq_ignore_ascii_case(distro) {
+            return None;
+        }
+        return Some(format!("/{rest}"));
+    }
+
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    if chars.next() != Some(':') {
+        return None;
+    }
+    let rest = chars.as_str().trim_start_matches('/');
+    Some(format!("/mnt/{}/{rest}", drive.to_ascii_lowercase()))
+}
+
+/// The reverse of [`windows_to_wsl_path`], through the `share` WSL is served
+/// from, `wsl.localhost` or `wsl$` on older versions of Windows.
+#[cfg(any(windows, test))]
+fn wsl_to_windows_path(share: &str, distro: &str, path: &str) -> String {
+    if let Some(rest) = path.strip_prefix("/mnt/") {
+        let (drive, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        if drive.len() == 1 && drive.chars().all(|c| c.is_ascii_alphabetic()) {
+            return format!(
+                "{}:\\{}",
+                drive.to_ascii_uppercase(),
+                rest.replace('/', "\\")
+            );
+        }
+    }
+    format!(
+        "\\\\{share}\\{distro}{}",
+        path.replace('/', "\\").trim_end_matches('\\')
+    )
+}
+
 #[cfg(windows)]
 impl Display for WslHost {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -62,10 +153,28 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+/// A dev container, with the local `folder` mounted in it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct ContainerHost {
+    /// `docker` or `podman`
+    pub engine: String,
+    pub name: String,
+    pub folder: PathBuf,
+    /// The user the proxy runs as, the container's own user if `None`
+    pub user: Option<String>,
+}
+
+impl Display for ContainerHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LapceWorkspaceType {
     Local,
     RemoteSSH(SshHost),
+    RemoteContainer(ContainerHost),
     #[cfg(windows)]
     RemoteWSL(WslHost),
 }
@@ -79,10 +188,10 @@ pub fn is_remote(&self) -> bool {
         use LapceWorkspaceType::*;
 
         #[cfg(not(windows))]
-        return matches!(self, RemoteSSH(_));
+        return matches!(self, RemoteSSH(_) | RemoteContainer(_));
 
         #[cfg(windows)]
-        return matches!(self, RemoteSSH(_) | RemoteWSL(_));
+        return matches!(self, RemoteSSH(_) | RemoteContainer(_) | RemoteWSL(_));
     }
 }
 
@@ -93,6 +202,9 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             LapceWorkspaceType::RemoteSSH(remote) => {
                 write!(f, "ssh://{remote}")
             }
+            LapceWorkspaceType::RemoteContainer(remote) => {
+                write!(f, "{remote} (Container)")
+            }
             #[cfg(windows)]
             LapceWorkspaceType::RemoteWSL(remote) => {
                 write!(f, "{remote} (WSL)")
@@ -109,6 +221,19 @@ pub struct LapceWorkspace {
 }
 
 impl LapceWorkspace {
+    /// The `file:` URL of a path of the workspace. The paths of remote
+    /// workspaces are Unix paths, which aren't absolute to
+    /// [`Url::from_file_path`] on Windows.
+    pub fn path_url(&self, path: &Path) -> Option<Url> {
+        if self.kind.is_remote() {
+            let mut url = Url::parse("file:///").ok()?;
+            url.set_path(&path.to_string_lossy().replace('\\', "/"));
+            Some(url)
+        } else {
+            Url::from_file_path(path).ok()
+        }
+    }
+
     pub fn display(&self) -> Option<String> {
         let path = self.path.as_ref()?;
         let path = path
@@ -121,6 +246,9 @@ pub fn display(&self) -> Option<String> {
             LapceWorkspaceType::RemoteSSH(remote) => {
                 format!(" [SSH: {}]", remote.host)
             }
+            LapceWorkspaceType::RemoteContainer(remote) => {
+                format!(" [Container: {}]", remote.name)
+            }
             #[cfg(windows)]
             LapceWorkspaceType::RemoteWSL(remote) => {
                 format!(" [WSL: {}]", remote.host)
@@ -157,3 +285,35 @@ pub struct WorkspaceInfo {
     pub panel: PanelInfo,
     pub breakpoints: HashMap<PathBuf, Vec<LapceBreakpoint>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{windows_to_wsl_path, wsl_to_windows_path};
+
+    #[test]
+    fn test_wsl_paths() {
+        let to_wsl = |path| windows_to_wsl_path("Ubuntu", path);
+        assert_eq!(
+            to_wsl(r"C:\Users\me\src").as_deref(),
+            Some("/mnt/c/Users/me/src")
+        );
+        assert_eq!(
+            to_wsl(r"\\wsl.localhost\Ubuntu\home\me").as_deref(),
+            Some("/home/me")
+        );
+        assert_eq!(to_wsl(r"\\wsl$\ubuntu").as_deref(), Some("/"));
+        assert_eq!(to_wsl("/home/me").as_deref(), Some("/home/me"));
+        assert_eq!(to_wsl(r"\\?\D:\a").as_deref(), Some("/mnt/d/a"));
+        assert_eq!(to_wsl(r"\\wsl.localhost\Debian\home"), None);
+        assert_eq!(to_wsl(r"\\server\share\a"), None);
+
+        let to_windows = |path| wsl_to_windows_path("wsl.localhost", "Ubuntu", path);
+        assert_eq!(to_windows("/mnt/d/work"), r"D:\work");
+        assert_eq!(to_windows("/home/me/"), r"\\wsl.localhost\Ubuntu\home\me");
+        assert_eq!(to_windows("/"), r"\\wsl.localhost\Ubuntu");
+        assert_eq!(
+            to_windows("/mnt/wsl/x"),
+            r"\\wsl.localhost\Ubuntu\mnt\wsl\x"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Auto-closing and auto-surrounding for the pairs of a language that the
+//! editor core doesn't know about.
+//!
+//! The editor core handles brackets and quotes itself, including wrapping the
+//! selection and typing over a closing character. [`insert`] does the same for
+//! the rest of [`LapceLanguage::auto_pairs`](crate::language::LapceLanguage::auto_pairs),
+//! such as `` ` `` or `<`, and [`is_builtin`] tells which characters are left
+//! to the core.
+
+use floem_editor_core::{
+    buffer::{rope_text::RopeText, Buffer, InvalLines},
+    cursor::{Cursor, CursorMode},
+    editor::EditType,
+    selection::{SelRegion, Selection},
+};
+use lapce_xi_rope::{Rope, RopeDelta};
+
+/// The characters the editor core pairs on its own
+const BUILTIN: &[char] = &['(', ')', '[', ']', '{', '}', '"', '\''];
+
+/// Whether the editor core already handles pairing `c`.
+pub fn is_builtin(c: char) -> bool {
+    BUILTIN.contains(&c)
+}
+
+/// Whether `c` is the opening or closing character of one of the pairs.
+pub fn is_paired(pairs: &[(char, char)], c: char) -> bool {
+    pairs.iter().any(|(open, close)| *open == c || *close == c)
+}
+
+/// Type `c`, wrapping the selections in the pair it opens, or typing over it
+/// when it is already right after the cursor. Returns `None` when `c` isn't
+/// one of the pairs handled here, in which case it should be inserted as
+/// usual.
+pub fn insert(
+    cursor: &mut Cursor,
+    buffer: &mut Buffer,
+    c: char,
+    pairs: &[(char, char)],
+    auto_closing: bool,
+    auto_surround: bool,
+) -> Option<Vec<(Rope, RopeDelta, InvalLines)>> {
+    if is_builtin(c) {
+        return None;
+    }
+    let CursorMode::Insert(selection) = &cursor.mode else {
+        return None;
+    };
+    let opens = pairs
+        .iter()
+        .find(|(open, _)| *open == c)
+        .map(|(_, close)| *close);
+    let closes = pairs.iter().any(|(_, close)| *close == c);
+    if opens.is_none() && !closes {
+        return None;
+    }
+
+    let len = c.len_utf8();
+    let mut edits: Vec<(Selection, String)> = Vec::new();
+    let mut new_selection = Selection::new();
+    // How far the edits so far have moved the text after them
+    let mut shift = 0isize;
+    let moved = |offset: usize, shift: isize| (offset as isize + shift) as usize;
+
+    for region in selection.regions() {
+        let (start, end) = (region.min(), region.max());
+        let next = buffer.char_at_offset(end);
+
+        if start != end && auto_surround {
+            if let Some(close) = opens {
+                edits.push((Selection::caret(start), c.to_string()));
+                edits.push((Selection::caret(end), close.to_string()));
+                new_selection.add_region(SelRegion::new(
+                    moved(start, shift) + len,
+                    moved(end, shift) + len,
+                    None,
+                ));
+                shift += (len + close.len_utf8()) as isize;
+                continue;
+            }
+        }
+
+        if start == end && auto_closing {
+            if closes && next == Some(c) {
+                new_selection.add_region(SelRegion::caret(moved(end, shift) + len));
+                continue;
+            }
+
+            if let Some(close) = opens {
+                if should_close(buffer, end, c, close) {
+                    edits.push((Selection::caret(end), format!("{c}{close}")));
+                    new_selection
+                        .add_region(SelRegion::caret(moved(end, shift) + len));
+                    shift += (len + close.len_utf8()) as isize;
+                    continue;
+                }
+            }
+        }
+
+        edits.push((Selection::region(start, end), c.to_string()));
+        new_selection.add_region(SelRegion::caret(moved(start, shift) + len));
+        shift += len as isize - (end - start) as isize;
+    }
+
+    let mut deltas = Vec::new();
+    if !edits.is_empty() {
+        let edits: Vec<(Selection, &str)> = edits
+            .iter()
+            .map(|(selection, s)| (selection.clone(), s.as_str()))
+            .collect();
+        deltas.push(buffer.edit(&edits, EditType::InsertChars));
+    }
+    cursor.mode = CursorMode::Insert(new_selection);
+    cursor.horiz = None;
+    Some(deltas)
+}
+
+/// Only close the pair before whitespace or punctuation, so that typing in
+/// front of a word doesn't add a stray closing character. A character that
+/// closes itself isn't closed after a word either, as in `it's`.
+fn should_close(buffer: &Buffer, offset: usize, open: char, close: char) -> bool {
+    let next_ok = buffer
+        .char_at_offset(offset)
+        .map(|c| c.is_whitespace() || (c.is_ascii_punctuation() && c != open))
+        .unwrap_or(true);
+    if !next_ok {
+        return false;
+    }
+    if open != close {
+        return true;
+    }
+    buffer
+        .text()
+        .prev_codepoint_offset(offset)
+        .and_then(|prev| buffer.char_at_offset(prev))
+        .map(|c| !c.is_alphanumeric())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use floem_editor_core::{
+        buffer::{rope_text::RopeText, Buffer},
+        cursor::{Cursor, CursorMode},
+        selection::{SelRegion, Selection},
+    };
+
+    use super::insert;
+
+    const PAIRS: &[(char, char)] = &[('(', ')'), ('`', '`'), ('<', '>')];
+
+    fn type_char(
+        text: &str,
+        start: usize,
+        end: usize,
+        c: char,
+    ) -> (String, usize, usize) {
+        let mut buffer = Buffer::new(text);
+        let mut selection = Selection::new();
+        selection.add_region(SelRegion::new(start, end, None));
+        let mut cursor = Cursor::new(CursorMode::Insert(selection), None, None);
+        insert(&mut cursor, &mut buffer, c, PAIRS, true, true).unwrap();
+        let CursorMode::Insert(selection) = &cursor.mode else {
+            unreachable!()
+        };
+        let region = selection.regions()[0];
+        (buffer.text().to_string(), region.start, region.end)
+    }
+
+    #[test]
+    fn test_surround() {
+        assert_eq!(type_char("a b c", 2, 3, '`'), ("a `b` c".to_string(), 3, 4));
+        assert_eq!(type_char("Vec", 0, 3, '<'), ("<Vec>".to_string(), 1, 4));
+    }
+
+    #[test]
+    fn test_auto_close_and_skip() {
+        assert_eq!(type_char("a ", 2, 2, '`'), ("a ``".to_string(), 3, 3));
+        assert_eq!(type_char("``", 1, 1, '`'), ("``".to_string(), 2, 2));
+        assert_eq!(type_char("<>", 1, 1, '>'), ("<>".to_string(), 2, 2));
+        // Not in front of or after a word
+        assert_eq!(type_char("ab", 1, 1, '`'), ("a`b".to_string(), 2, 2));
+        assert_eq!(type_char("it", 2, 2, '`'), ("it`".to_string(), 3, 3));
+    }
+
+    #[test]
+    fn test_builtin_and_unpaired() {
+        let mut buffer = Buffer::new("");
+        let mut cursor =
+            Cursor::new(CursorMode::Insert(Selection::caret(0)), None, None);
+        assert!(insert(&mut cursor, &mut buffer, '(', PAIRS, true, true).is_none());
+        assert!(insert(&mut cursor, &mut buffer, '$', PAIRS, true, true).is_none());
+    }
+}
@@ -128,10 +128,33 @@ pub fn config_directory() -> Option<PathBuf> {
         }
     }
 
+    /// Get the path to the profiles folder
+    /// Each profile is a folder with its own settings and keymaps files
+    pub fn profiles_directory() -> Option<PathBuf> {
+        if let Some(dir) = Self::config_directory() {
+            let dir = dir.join("profiles");
+            if !dir.exists() {
+                let _ = std::fs::create_dir(&dir);
+            }
+            Some(dir)
+        } else {
+            None
+        }
+    }
+
     pub fn local_socket() -> Option<PathBuf> {
         Self::data_local_directory().map(|dir| dir.join("local.sock"))
     }
 
+    pub fn automation_socket() -> Option<PathBuf> {
+        Self::data_local_directory().map(|dir| dir.join("automation.sock"))
+    }
+
+    /// The clone of the settings sync repository
+    pub fn settings_sync_directory() -> Option<PathBuf> {
+        Self::data_local_directory().map(|dir| dir.join("settings-sync"))
+    }
+
     pub fn updates_directory() -> Option<PathBuf> {
         if let Some(dir) = Self::data_local_directory() {
             let dir = dir.join("updates");
@@ -157,6 +180,38 @@ pub fn queries_directory() -> Option<PathBuf> {
         }
     }
 
+    /// Get the path to the folder of the user's own queries, which are used in
+    /// place of the downloaded ones in `queries_directory`, laid out the same
+    /// way as it
+    pub fn query_overrides_directory() -> Option<PathBuf> {
+        if let Some(dir) = Self::queries_directory() {
+            let dir = dir.join("overrides");
+            if !dir.exists() {
+                let _ = std::fs::create_dir(&dir);
+            }
+
+            Some(dir)
+        } else {
+            None
+        }
+    }
+
+    /// Get the path to the file templates folder
+    /// Each template is a `<name>.<extension>` file offered for the new files
+    /// with the extension
+    pub fn templates_directory() -> Option<PathBuf> {
+        if let Some(dir) = Self::config_directory() {
+            let dir = dir.join("templates");
+            if !dir.exists() {
+                let _ = std::fs::create_dir(&dir);
+            }
+
+            Some(dir)
+        } else {
+            None
+        }
+    }
+
     pub fn grammars_directory() -> Option<PathBuf> {
         if let Some(dir) = Self::data_local_directory() {
             let dir = dir.join("grammars");
@@ -1,44 +1,93 @@
-/// Convert a utf8 offset into a utf16 offset, if possible  
-/// `text` is what the offsets are into
-pub fn offset_utf8_to_utf16(
-    char_indices: impl Iterator<Item = (usize, char)>,
-    offset: usize,
-) -> usize {
-    if offset == 0 {
-        return 0;
+use lsp_types::PositionEncodingKind;
+
+/// How the columns of LSP positions count the characters of a line, as
+/// negotiated with a language server. UTF-16 is what the protocol assumes
+/// when nothing else was negotiated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// The encoding a server chose, UTF-16 if it didn't or chose one unknown
+    pub fn from_kind(kind: Option<&PositionEncodingKind>) -> Self {
+        match kind.map(|kind| kind.as_str()) {
+            Some("utf-8") => PositionEncoding::Utf8,
+            Some("utf-32") => PositionEncoding::Utf32,
+            _ => PositionEncoding::Utf16,
+        }
     }
 
-    let mut utf16_offset = 0;
-    let mut last_ich = None;
-    for (utf8_offset, ch) in char_indices {
-        last_ich = Some((utf8_offset, ch));
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+            PositionEncoding::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
 
-        match utf8_offset.cmp(&offset) {
-            std::cmp::Ordering::Less => {}
-            // We found the right offset
-            std::cmp::Ordering::Equal => {
-                return utf16_offset;
+    /// The length of `ch` in the units of the encoding
+    pub fn char_len(self, ch: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => ch.len_utf8(),
+            PositionEncoding::Utf16 => ch.len_utf16(),
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+
+    /// Convert a utf8 offset into an offset in this encoding, if possible  
+    /// An offset inside of a character is moved to the end of it
+    pub fn offset_from_utf8(
+        self,
+        char_indices: impl Iterator<Item = (usize, char)>,
+        offset: usize,
+    ) -> usize {
+        let mut encoded_offset = 0;
+        for (utf8_offset, ch) in char_indices {
+            if utf8_offset >= offset {
+                return encoded_offset;
             }
-            // Implies that the offset was inside of a character
-            std::cmp::Ordering::Greater => return utf16_offset,
+            encoded_offset += self.char_len(ch);
         }
 
-        utf16_offset += ch.len_utf16();
+        // The offset is at the end or past the end, which the encoded offset
+        // is now the length of
+        encoded_offset
     }
 
-    // TODO: We could use TrustedLen when that is stabilized and it is impl'd on
-    // the iterators we use
+    /// Convert an offset in this encoding into a utf8 offset, if possible  
+    /// An offset inside of a character, such as between the two halves of a
+    /// surrogate pair, is moved to the end of it
+    pub fn offset_to_utf8(
+        self,
+        char_indices: impl Iterator<Item = (usize, char)>,
+        offset: usize,
+    ) -> usize {
+        let mut encoded_offset = 0;
+        let mut last_ich = None;
+        for (utf8_offset, ch) in char_indices {
+            if encoded_offset >= offset {
+                return utf8_offset;
+            }
+            last_ich = Some((utf8_offset, ch));
+            encoded_offset += self.char_len(ch);
+        }
 
-    // We did not find the offset. This means that it is either at the end
-    // or past the end.
-    let text_len = last_ich.map(|(i, c)| i + c.len_utf8());
-    if text_len == Some(offset) {
-        // Since the utf16 offset was being incremented each time, by now it is equivalent to the length
-        // but in utf16 characters
-        return utf16_offset;
+        // The offset is at the end or past the end, so it is the utf8 length
+        last_ich.map_or(0, |(utf8_offset, ch)| utf8_offset + ch.len_utf8())
     }
+}
 
-    utf16_offset
+/// Convert a utf8 offset into a utf16 offset, if possible  
+/// `text` is what the offsets are into
+pub fn offset_utf8_to_utf16(
+    char_indices: impl Iterator<Item = (usize, char)>,
+    offset: usize,
+) -> usize {
+    PositionEncoding::Utf16.offset_from_utf8(char_indices, offset)
 }
 
 pub fn offset_utf8_to_utf16_str(text: &str, offset: usize) -> usize {
@@ -47,48 +96,11 @@ pub fn offset_utf8_to_utf16_str(text: &str, offset: usize) -> usize {
 
 /// Convert a utf16 offset into a utf8 offset, if possible  
 /// `char_indices` is an iterator over utf8 offsets and the characters
-/// It is cloneable so that it can be iterated multiple times. Though it should be cheaply cloneable.
 pub fn offset_utf16_to_utf8(
     char_indices: impl Iterator<Item = (usize, char)>,
     offset: usize,
 ) -> usize {
-    if offset == 0 {
-        return 0;
-    }
-
-    // We accumulate the utf16 char lens until we find the utf8 offset that matches it
-    // or, we find out that it went into the middle of sometext
-    // We also keep track of the last offset and char in order to calculate the length of the text
-    // if we the index was at the end of the string
-    let mut utf16_offset = 0;
-    let mut last_ich = None;
-    for (utf8_offset, ch) in char_indices {
-        last_ich = Some((utf8_offset, ch));
-
-        let ch_utf16_len = ch.len_utf16();
-
-        match utf16_offset.cmp(&offset) {
-            std::cmp::Ordering::Less => {}
-            // We found the right offset
-            std::cmp::Ordering::Equal => {
-                return utf8_offset;
-            }
-            // This implies that the offset was in the middle of a character as we skipped over it
-            std::cmp::Ordering::Greater => return utf8_offset,
-        }
-
-        utf16_offset += ch_utf16_len;
-    }
-
-    // We did not find the offset, this means that it was either at the end
-    // or past the end
-    // Since we've iterated over all the char indices, the utf16_offset is now the
-    // utf16 length
-    if let Some((last_utf8_offset, last_ch)) = last_ich {
-        last_utf8_offset + last_ch.len_utf8()
-    } else {
-        0
-    }
+    PositionEncoding::Utf16.offset_to_utf8(char_indices, offset)
 }
 
 pub fn offset_utf16_to_utf8_str(text: &str, offset: usize) -> usize {
@@ -97,9 +109,11 @@ pub fn offset_utf16_to_utf8_str(text: &str, offset: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
-    // TODO: more tests with unicode characters
+    use lsp_types::PositionEncodingKind;
 
-    use crate::encoding::{offset_utf16_to_utf8_str, offset_utf8_to_utf16_str};
+    use crate::encoding::{
+        offset_utf16_to_utf8_str, offset_utf8_to_utf16_str, PositionEncoding,
+    };
 
     #[test]
     fn utf8_to_utf16() {
@@ -160,4 +174,60 @@ fn utf16_to_utf8() {
         assert_eq!(offset_utf16_to_utf8_str("×a", 1), 2);
         assert_eq!(offset_utf16_to_utf8_str("×a", 2), 3);
     }
+
+    #[test]
+    fn utf16_astral() {
+        // An emoji is four bytes and a surrogate pair in utf16
+        let text = "a😀b";
+        assert_eq!(offset_utf8_to_utf16_str(text, 1), 1);
+        assert_eq!(offset_utf8_to_utf16_str(text, 5), 3);
+        assert_eq!(offset_utf8_to_utf16_str(text, 6), 4);
+        // Inside of the emoji
+        assert_eq!(offset_utf8_to_utf16_str(text, 3), 3);
+
+        assert_eq!(offset_utf16_to_utf8_str(text, 1), 1);
+        assert_eq!(offset_utf16_to_utf8_str(text, 3), 5);
+        assert_eq!(offset_utf16_to_utf8_str(text, 4), 6);
+        // Between the halves of the surrogate pair
+        assert_eq!(offset_utf16_to_utf8_str(text, 2), 5);
+    }
+
+    #[test]
+    fn position_encodings() {
+        // A CJK character is three bytes and one utf16 unit, and a ZWJ
+        // sequence is several characters
+        let text = "中👩‍💻x";
+        let x = text.len() - 1;
+        let cases = [
+            (PositionEncoding::Utf8, x),
+            (PositionEncoding::Utf16, 1 + 2 + 1 + 2),
+            (PositionEncoding::Utf32, 1 + 1 + 1 + 1),
+        ];
+        for (encoding, encoded_x) in cases {
+            assert_eq!(encoding.offset_from_utf8(text.char_indices(), x), encoded_x);
+            assert_eq!(encoding.offset_to_utf8(text.char_indices(), encoded_x), x);
+            assert_eq!(
+                encoding.offset_to_utf8(text.char_indices(), encoded_x + 5),
+                text.len()
+            );
+            assert_eq!(encoding.offset_from_utf8(text.char_indices(), 0), 0);
+        }
+    }
+
+    #[test]
+    fn position_encoding_kind() {
+        assert_eq!(
+            PositionEncoding::from_kind(Some(&PositionEncodingKind::UTF8)),
+            PositionEncoding::Utf8
+        );
+        assert_eq!(
+            PositionEncoding::from_kind(Some(&PositionEncodingKind::UTF32)),
+            PositionEncoding::Utf32
+        );
+        assert_eq!(PositionEncoding::from_kind(None), PositionEncoding::Utf16);
+        assert_eq!(
+            PositionEncoding::from_kind(Some(&PositionEncodingKind::new("utf-7"))),
+            PositionEncoding::Utf16
+        );
+    }
 }
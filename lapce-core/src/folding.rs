@@ -0,0 +1,343 @@
+//! Folding ranges that don't need a language server: the block of imports at
+//! the top of a file, the regions between `#region` and `#endregion`
+//! comments, and the nodes of the syntax tree spanning several lines.
+
+use lapce_xi_rope::Rope;
+
+use crate::language::LapceLanguage;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldingKind {
+    Imports,
+    Region,
+    /// A node of the syntax tree, such as a function or a block
+    Block,
+    /// A comment of the syntax tree spanning several lines
+    Comment,
+}
+
+/// Lines `start_line` to `end_line` fold into `start_line`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldingRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: FoldingKind,
+}
+
+/// The block of imports at the start of the file, from the first import to the
+/// last one, with the comments and blank lines between them. Returns `None`
+/// when there are fewer than two lines to fold.
+pub fn import_block(text: &Rope, language: LapceLanguage) -> Option<FoldingRange> {
+    let comment = language.comment_token();
+    let mut start_line = None;
+    let mut end_line = 0;
+    // How many brackets of a multi-line import are still open
+    let mut depth = 0i32;
+
+    for (line, content) in text.lines(0..text.len()).enumerate() {
+        let trimmed = content.trim();
+        if depth > 0 || is_import(trimmed, language) {
+            start_line.get_or_insert(line);
+            end_line = line;
+            depth += bracket_depth(trimmed);
+            continue;
+        }
+        let skipped = trimmed.is_empty()
+            || (!comment.is_empty() && trimmed.starts_with(comment))
+            || can_skip(trimmed, language);
+        if !skipped {
+            break;
+        }
+    }
+
+    let start_line = start_line?;
+    (end_line > start_line).then_some(FoldingRange {
+        start_line,
+        end_line,
+        kind: FoldingKind::Imports,
+    })
+}
+
+/// The regions between `#region` and `#endregion` comments, written with the
+/// language's own comment token such as `// #region` or `# region`. Markers
+/// without a match are ignored.
+pub fn region_markers(text: &Rope, language: LapceLanguage) -> Vec<FoldingRange> {
+    let comment = language.comment_token();
+    if comment.is_empty() {
+        return Vec::new();
+    }
+
+    let mut open = Vec::new();
+    let mut ranges = Vec::new();
+    for (line, content) in text.lines(0..text.len()).enumerate() {
+        match region_marker(&content, comment) {
+            Some(true) => open.push(line),
+            Some(false) => {
+                if let Some(start_line) = open.pop() {
+                    ranges.push(FoldingRange {
+                        start_line,
+                        end_line: line,
+                        kind: FoldingKind::Region,
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+    ranges.sort_by_key(|range| range.start_line);
+    ranges
+}
+
+/// The ranges of syntax nodes to fold, keeping the outermost of those which
+/// start on the same line and dropping those on a single line, by start line
+pub fn outermost_ranges(
+    nodes: impl IntoIterator<Item = FoldingRange>,
+) -> Vec<FoldingRange> {
+    let mut ranges = nodes
+        .into_iter()
+        .filter(|range| range.end_line > range.start_line)
+        .collect::<Vec<_>>();
+    ranges.sort_by(|a, b| {
+        a.start_line
+            .cmp(&b.start_line)
+            .then(b.end_line.cmp(&a.end_line))
+    });
+    ranges.dedup_by_key(|range| range.start_line);
+    ranges
+}
+
+/// The text shown in place of a folded range, such as `… 12 lines, fn foo`:
+/// how many lines are hidden, and the name of what starts the range. The
+/// name is read up to the parameters, body or type of the first line that
+/// isn't blank or a comment, and is left out if there isn't one.
+pub fn placeholder(
+    text: &Rope,
+    range: &FoldingRange,
+    language: LapceLanguage,
+) -> String {
+    let hidden = range.end_line - range.start_line;
+    let lines = if hidden == 1 {
+        "1 line".to_string()
+    } else {
+        format!("{hidden} lines")
+    };
+    let comment = language.comment_token();
+    let name = match range.kind {
+        FoldingKind::Imports => Some("imports".to_string()),
+        FoldingKind::Comment => Some("comment".to_string()),
+        FoldingKind::Region | FoldingKind::Block => text
+            .lines(0..text.len())
+            .skip(range.start_line)
+            .take(hidden + 1)
+            .find_map(|line| {
+                let line = line.trim();
+                if line.is_empty()
+                    || (!comment.is_empty() && line.starts_with(comment))
+                {
+                    return None;
+                }
+                let end = line.find(['(', '{', '[', '<', ':', '=', ';']);
+                let name = line[..end.unwrap_or(line.len())].trim();
+                (!name.is_empty()).then(|| truncate(name, PLACEHOLDER_NAME_LEN))
+            }),
+    };
+    match name {
+        Some(name) => format!("… {lines}, {name}"),
+        None => format!("… {lines}"),
+    }
+}
+
+/// The longest name shown in a fold placeholder, in characters
+const PLACEHOLDER_NAME_LEN: usize = 40;
+
+fn truncate(s: &str, len: usize) -> String {
+    match s.char_indices().nth(len) {
+        Some((end, _)) => format!("{}…", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+/// `Some(true)` for a line opening a region, `Some(false)` for one closing it.
+fn region_marker(line: &str, comment: &str) -> Option<bool> {
+    let marker = line.trim_start().strip_prefix(comment)?.trim_start();
+    let marker = marker.strip_prefix('#').unwrap_or(marker);
+    let (opens, rest) = if let Some(rest) = marker.strip_prefix("endregion") {
+        (false, rest)
+    } else {
+        (true, marker.strip_prefix("region")?)
+    };
+    // Not a longer word such as `regional`
+    (rest.is_empty() || rest.starts_with(|c: char| !c.is_alphanumeric()))
+        .then_some(opens)
+}
+
+fn is_import(line: &str, language: LapceLanguage) -> bool {
+    let prefixes: &[&str] = match language {
+        LapceLanguage::Rust => {
+            let line = strip_visibility(line);
+            return line.starts_with("use ")
+                || line.starts_with("extern crate ")
+                || (line.starts_with("mod ") && line.ends_with(';'));
+        }
+        LapceLanguage::Python => &["import ", "from "],
+        LapceLanguage::C | LapceLanguage::Cpp => {
+            &["#include ", "#include<", "#import "]
+        }
+        LapceLanguage::Csharp => {
+            return line.starts_with("using ") && !line.contains('(')
+        }
+        LapceLanguage::Php => &["use ", "require", "include"],
+        LapceLanguage::Elixir => &["import ", "alias ", "require ", "use "],
+        _ => &["import ", "import(", "import{"],
+    };
+    prefixes.iter().any(|prefix| line.starts_with(prefix))
+}
+
+/// Lines other than comments that can come before or between the imports,
+/// such as Go's `package` or Rust's attributes.
+fn can_skip(line: &str, language: LapceLanguage) -> bool {
+    match language {
+        LapceLanguage::Rust => line.starts_with('#'),
+        LapceLanguage::Php => {
+            line.starts_with("<?php") || line.starts_with("namespace ")
+        }
+        LapceLanguage::Python => {
+            line.starts_with("\"\"\"") && line.len() > 3 && line.ends_with("\"\"\"")
+        }
+        LapceLanguage::Javascript
+        | LapceLanguage::Jsx
+        | LapceLanguage::Typescript
+        | LapceLanguage::Tsx => {
+            line.starts_with("\"use ") || line.starts_with("'use ")
+        }
+        _ => line.starts_with("package ") || line.starts_with("#!"),
+    }
+}
+
+fn strip_visibility(line: &str) -> &str {
+    let Some(rest) = line.strip_prefix("pub") else {
+        return line;
+    };
+    let rest = match rest.strip_prefix('(') {
+        Some(rest) => match rest.find(')') {
+            Some(end) => &rest[end + 1..],
+            None => return line,
+        },
+        None => rest,
+    };
+    rest.strip_prefix(' ').unwrap_or(line)
+}
+
+fn bracket_depth(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '{' | '(' | '[' => 1,
+            '}' | ')' | ']' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_xi_rope::Rope;
+
+    use super::{
+        import_block, outermost_ranges, placeholder, region_markers, FoldingKind,
+        FoldingRange,
+    };
+    use crate::language::LapceLanguage;
+
+    fn imports(text: &str, language: LapceLanguage) -> Option<(usize, usize)> {
+        import_block(&Rope::from(text), language)
+            .map(|range| (range.start_line, range.end_line))
+    }
+
+    #[test]
+    fn test_import_block() {
+        let rust = "//! Docs\n\n#[cfg(unix)]\nuse std::{\n    fs,\n    io,\n};\n\n// Local\npub(crate) use crate::a;\n\nfn main() {}\n";
+        assert_eq!(imports(rust, LapceLanguage::Rust), Some((3, 9)));
+
+        let python = "import os\nfrom sys import (\n    argv,\n)\nx = 1\n";
+        assert_eq!(imports(python, LapceLanguage::Python), Some((0, 3)));
+
+        let go = "package main\n\nimport (\n\t\"fmt\"\n)\n";
+        assert_eq!(imports(go, LapceLanguage::Go), Some((2, 4)));
+
+        // A single line isn't worth folding
+        assert_eq!(imports("use a;\nfn b() {}\n", LapceLanguage::Rust), None);
+        // Imports after code don't count
+        assert_eq!(
+            imports("fn b() {}\nuse a;\nuse c;\n", LapceLanguage::Rust),
+            None
+        );
+    }
+
+    #[test]
+    fn test_region_markers() {
+        let text = "// #region outer\nlet a;\n//#region\nlet b;\n// #endregion\n// #endregion\n// #endregion\n// regional\n";
+        let ranges = region_markers(&Rope::from(text), LapceLanguage::Rust);
+        assert_eq!(
+            ranges,
+            vec![
+                FoldingRange {
+                    start_line: 0,
+                    end_line: 5,
+                    kind: FoldingKind::Region
+                },
+                FoldingRange {
+                    start_line: 2,
+                    end_line: 4,
+                    kind: FoldingKind::Region
+                },
+            ]
+        );
+
+        let python = "#region\nx = 1\n# endregion\n// #region\n";
+        let ranges = region_markers(&Rope::from(python), LapceLanguage::Python);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!((ranges[0].start_line, ranges[0].end_line), (0, 2));
+    }
+
+    #[test]
+    fn test_placeholder() {
+        let text = Rope::from(
+            "// #region\n// Helpers\npub fn foo(a: u32) {\n}\n// #endregion\n",
+        );
+        let region = |start_line, end_line| FoldingRange {
+            start_line,
+            end_line,
+            kind: FoldingKind::Region,
+        };
+        assert_eq!(
+            placeholder(&text, &region(0, 4), LapceLanguage::Rust),
+            "… 4 lines, pub fn foo"
+        );
+        assert_eq!(
+            placeholder(&text, &region(0, 1), LapceLanguage::Rust),
+            "… 1 line"
+        );
+        let imports = FoldingRange {
+            start_line: 0,
+            end_line: 2,
+            kind: FoldingKind::Imports,
+        };
+        assert_eq!(
+            placeholder(&text, &imports, LapceLanguage::Rust),
+            "… 2 lines, imports"
+        );
+    }
+
+    #[test]
+    fn test_outermost_ranges() {
+        let block = |start_line, end_line| FoldingRange {
+            start_line,
+            end_line,
+            kind: FoldingKind::Block,
+        };
+        // A function, its body starting on the same line, a statement inside
+        // it and a single line one
+        let nodes = [block(2, 3), block(0, 5), block(0, 4), block(1, 1)];
+        assert_eq!(outermost_ranges(nodes), vec![block(0, 5), block(2, 3)]);
+    }
+}
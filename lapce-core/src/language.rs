@@ -957,6 +957,38 @@ pub enum LapceLanguage {
     },
 ];
 
+const DEFAULT_AUTO_PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+];
+
+/// `'` starts lifetimes, symbols or type variables rather than strings
+const NO_SINGLE_QUOTE_AUTO_PAIRS: &[(char, char)] =
+    &[('(', ')'), ('[', ']'), ('{', '}'), ('"', '"')];
+
+const MARKUP_AUTO_PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+    ('<', '>'),
+];
+
+const LATEX_AUTO_PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('`', '`'),
+    ('$', '$'),
+];
+
 impl LapceLanguage {
     const HIGHLIGHTS_QUERIES_FILE_NAME: &'static str = "highlights.scm";
     const HIGHLIGHTS_INJECTIONS_FILE_NAME: &'static str = "injections.scm";
@@ -1008,6 +1040,36 @@ pub fn from_name(name: &str) -> Option<LapceLanguage> {
         }
     }
 
+    /// The language an injection names, such as the info string of a
+    /// markdown code fence. Besides the names of the languages, their file
+    /// extensions and a few common aliases are accepted, such as `js` or
+    /// `shell`.
+    pub fn from_injection_name(name: &str) -> Option<LapceLanguage> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        if let Ok(language) = LapceLanguage::from_str(&name) {
+            return Some(language);
+        }
+        let alias = match name.as_str() {
+            "shell" | "console" | "shellsession" => Some(LapceLanguage::Bash),
+            "c#" | "cs" => Some(LapceLanguage::Csharp),
+            "golang" => Some(LapceLanguage::Go),
+            "docker" => Some(LapceLanguage::Dockerfile),
+            "jsonc" | "json5" => Some(LapceLanguage::Json),
+            "proto" => Some(LapceLanguage::ProtoBuf),
+            "tex" => Some(LapceLanguage::Latex),
+            _ => None,
+        };
+        alias.or_else(|| {
+            LANGUAGES
+                .iter()
+                .find(|properties| properties.extensions.contains(&name.as_str()))
+                .map(|properties| properties.id)
+        })
+    }
+
     pub fn languages() -> Vec<&'static str> {
         let mut langs = vec![];
         for l in LANGUAGES {
@@ -1053,6 +1115,26 @@ pub fn indent_unit(&self) -> &str {
         self.properties().indent
     }
 
+    /// The pairs that are closed automatically when the opening character is
+    /// typed, and wrapped around the selection.
+    pub fn auto_pairs(&self) -> &'static [(char, char)] {
+        match self {
+            LapceLanguage::Rust
+            | LapceLanguage::Clojure
+            | LapceLanguage::Scheme
+            | LapceLanguage::Ocaml
+            | LapceLanguage::OcamlInterface
+            | LapceLanguage::Haskell
+            | LapceLanguage::Elm => NO_SINGLE_QUOTE_AUTO_PAIRS,
+            LapceLanguage::Html
+            | LapceLanguage::Xml
+            | LapceLanguage::Vue
+            | LapceLanguage::Svelte => MARKUP_AUTO_PAIRS,
+            LapceLanguage::Latex => LATEX_AUTO_PAIRS,
+            _ => DEFAULT_AUTO_PAIRS,
+        }
+    }
+
     fn get_grammar(&self) -> Option<tree_sitter::Language> {
         let grammar_name = self.grammar_name();
 
@@ -1100,16 +1182,20 @@ fn grammar_name(&self) -> String {
     fn get_grammar_query(&self) -> (String, String) {
         let query_name = self.query_name();
 
-        // Try reading highlights from user config dir
+        // Try reading highlights from user config dir, the user's overrides
+        // first
         if let Some(queries_dir) = Directory::queries_directory() {
+            let overrides_dir = Directory::query_overrides_directory();
             return (
                 read_grammar_query(
                     &queries_dir,
+                    overrides_dir.as_deref(),
                     &query_name,
                     Self::HIGHLIGHTS_QUERIES_FILE_NAME,
                 ),
                 read_grammar_query(
                     &queries_dir,
+                    overrides_dir.as_deref(),
                     &query_name,
                     Self::HIGHLIGHTS_INJECTIONS_FILE_NAME,
                 ),
@@ -1147,7 +1233,10 @@ pub(crate) fn new_highlight_config(
         let (query, injection) = self.get_grammar_query();
 
         match HighlightConfiguration::new(grammar, &query, &injection, "") {
-            Ok(x) => Ok(x),
+            Ok(mut x) => {
+                x.lapce_language = *self;
+                Ok(x)
+            }
             Err(x) => {
                 let str = format!("Encountered {x:?} while trying to construct HighlightConfiguration for {}", strum::EnumMessage::get_message(self).unwrap_or(self.as_ref()));
                 error!("{str}");
@@ -1312,11 +1401,21 @@ pub(crate) fn walk_tree_bracket_ast(
     }
 }
 
-fn read_grammar_query(queries_dir: &Path, name: &str, kind: &str) -> String {
+/// Read a query, from `overrides_dir` when it has one of the language, with
+/// the queries it inherits
+fn read_grammar_query(
+    queries_dir: &Path,
+    overrides_dir: Option<&Path>,
+    name: &str,
+    kind: &str,
+) -> String {
     static INHERITS_REGEX: Lazy<Regex> =
         Lazy::new(|| Regex::new(r";+\s*inherits\s*:?\s*([a-z_,()-]+)\s*").unwrap());
 
-    let file = queries_dir.join(name).join(kind);
+    let file = overrides_dir
+        .map(|dir| dir.join(name).join(kind))
+        .filter(|file| file.exists())
+        .unwrap_or_else(|| queries_dir.join(name).join(kind));
     let query = std::fs::read_to_string(file).unwrap_or_default();
 
     INHERITS_REGEX
@@ -1327,7 +1426,7 @@ fn read_grammar_query(queries_dir: &Path, name: &str, kind: &str) -> String {
                     write!(
                         output,
                         "\n{}\n",
-                        read_grammar_query(queries_dir, name, kind)
+                        read_grammar_query(queries_dir, overrides_dir, name, kind)
                     )
                     .unwrap();
                     output
@@ -1347,4 +1446,17 @@ fn test_lanaguage_from_path() {
         let l = LapceLanguage::from_path(&PathBuf::new().join("test.rs"));
         assert_eq!(l, LapceLanguage::Rust);
     }
+
+    #[test]
+    fn test_language_from_injection_name() {
+        let l = LapceLanguage::from_injection_name;
+        assert_eq!(l("rust"), Some(LapceLanguage::Rust));
+        assert_eq!(l("JavaScript"), Some(LapceLanguage::Javascript));
+        assert_eq!(l("js"), Some(LapceLanguage::Javascript));
+        assert_eq!(l("py"), Some(LapceLanguage::Python));
+        assert_eq!(l("c++"), Some(LapceLanguage::Cpp));
+        assert_eq!(l("shell"), Some(LapceLanguage::Bash));
+        assert_eq!(l(""), None);
+        assert_eq!(l("not-a-language"), None);
+    }
 }
@@ -1,7 +1,9 @@
 #![allow(clippy::manual_clamp)]
 
+pub mod auto_pairs;
 pub mod directory;
 pub mod encoding;
+pub mod folding;
 pub mod language;
 pub mod lens;
 pub mod meta;
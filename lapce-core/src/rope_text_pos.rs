@@ -1,36 +1,72 @@
 use floem_editor_core::buffer::rope_text::RopeText;
 use lsp_types::Position;
 
-use crate::encoding::{offset_utf16_to_utf8, offset_utf8_to_utf16};
+use crate::encoding::PositionEncoding;
 
 pub trait RopeTextPosition: RopeText {
     /// Converts a UTF8 offset to a UTF16 LSP position
     /// Returns None if it is not a valid UTF16 offset
     fn offset_to_position(&self, offset: usize) -> Position {
+        self.offset_to_position_in(offset, PositionEncoding::Utf16)
+    }
+
+    fn offset_of_position(&self, pos: &Position) -> usize {
+        self.offset_of_position_in(pos, PositionEncoding::Utf16)
+    }
+
+    fn position_to_line_col(&self, pos: &Position) -> (usize, usize) {
+        self.position_to_line_col_in(pos, PositionEncoding::Utf16)
+    }
+
+    /// Converts a UTF8 offset to an LSP position whose column counts in
+    /// `encoding`
+    fn offset_to_position_in(
+        &self,
+        offset: usize,
+        encoding: PositionEncoding,
+    ) -> Position {
         let (line, col) = self.offset_to_line_col(offset);
         let line_offset = self.offset_of_line(line);
 
-        let utf16_col =
-            offset_utf8_to_utf16(self.char_indices_iter(line_offset..), col);
+        let encoded_col =
+            encoding.offset_from_utf8(self.char_indices_iter(line_offset..), col);
 
         Position {
             line: line as u32,
-            character: utf16_col as u32,
+            character: encoded_col as u32,
         }
     }
 
-    fn offset_of_position(&self, pos: &Position) -> usize {
-        let (line, column) = self.position_to_line_col(pos);
+    fn offset_of_position_in(
+        &self,
+        pos: &Position,
+        encoding: PositionEncoding,
+    ) -> usize {
+        let (line, column) = self.position_to_line_col_in(pos, encoding);
 
-        self.offset_of_line_col(line, column)
+        self.offset_of_line(line) + column
     }
 
-    fn position_to_line_col(&self, pos: &Position) -> (usize, usize) {
+    /// The line and the UTF8 column of an LSP position whose column counts in
+    /// `encoding`. A column past the end of the line is the end of the line,
+    /// before its line ending, and a line past the last is the end of the
+    /// text, as servers send for edits up to the end of a file.
+    fn position_to_line_col_in(
+        &self,
+        pos: &Position,
+        encoding: PositionEncoding,
+    ) -> (usize, usize) {
         let line = pos.line as usize;
+        let last_line = self.last_line();
+        if line > last_line {
+            return (last_line, self.len() - self.offset_of_line(last_line));
+        }
         let line_offset = self.offset_of_line(line);
+        let line_end = self.offset_of_line(line + 1);
 
-        let column = offset_utf16_to_utf8(
-            self.char_indices_iter(line_offset..),
+        let column = encoding.offset_to_utf8(
+            self.char_indices_iter(line_offset..line_end)
+                .take_while(|(_, c)| *c != '\n' && *c != '\r'),
             pos.character as usize,
         );
 
@@ -38,3 +74,58 @@ fn position_to_line_col(&self, pos: &Position) -> (usize, usize) {
     }
 }
 impl<T: RopeText> RopeTextPosition for T {}
+
+#[cfg(test)]
+mod tests {
+    use floem_editor_core::buffer::rope_text::RopeTextRef;
+    use lapce_xi_rope::Rope;
+    use lsp_types::Position;
+
+    use super::RopeTextPosition;
+    use crate::encoding::PositionEncoding;
+
+    #[test]
+    fn test_position_round_trip() {
+        let rope = Rope::from("fn 😀() {}\r\nlet 中 = \"👩‍💻\";\n");
+        let text = RopeTextRef::new(&rope);
+        for encoding in [
+            PositionEncoding::Utf8,
+            PositionEncoding::Utf16,
+            PositionEncoding::Utf32,
+        ] {
+            let content = rope.slice_to_cow(..);
+            // The LF of a CRLF line ending isn't a position of its own
+            let offsets = content.char_indices().filter(|(_, c)| *c != '\n');
+            for (offset, _) in offsets {
+                let position = text.offset_to_position_in(offset, encoding);
+                assert_eq!(text.offset_of_position_in(&position, encoding), offset);
+            }
+        }
+    }
+
+    #[test]
+    fn test_position_after_astral() {
+        let rope = Rope::from("a😀b\nc");
+        let text = RopeTextRef::new(&rope);
+        // "b" is after a surrogate pair in utf16
+        assert_eq!(text.offset_to_position(5), Position::new(0, 3));
+        assert_eq!(text.offset_of_position(&Position::new(0, 3)), 5);
+        assert_eq!(
+            text.offset_to_position_in(5, PositionEncoding::Utf32),
+            Position::new(0, 2)
+        );
+    }
+
+    #[test]
+    fn test_position_past_line_end() {
+        let rope = Rope::from("ab\r\ncd\nef");
+        let text = RopeTextRef::new(&rope);
+        // Stays on its line rather than going into the next one
+        assert_eq!(text.offset_of_position(&Position::new(0, 10)), 2);
+        assert_eq!(text.offset_of_position(&Position::new(1, 10)), 6);
+        assert_eq!(text.offset_of_position(&Position::new(2, 10)), 9);
+        // Past the last line is the end of the text, without a trailing newline
+        assert_eq!(text.offset_of_position(&Position::new(3, 0)), 9);
+        assert_eq!(text.offset_of_position(&Position::new(7, 1)), 9);
+    }
+}
@@ -29,20 +29,30 @@
 use crate::{language::LapceLanguage, style::SCOPES};
 
 thread_local! {
-    static HIGHLIGHT_CONFIGS: RefCell<HashMap<LapceLanguage, Result<Arc<HighlightConfiguration>, HighlightIssue>>> = Default::default();
+    /// The configurations loaded by this thread, with the generation they are
+    /// from
+    static HIGHLIGHT_CONFIGS: RefCell<(usize, HashMap<LapceLanguage, Result<Arc<HighlightConfiguration>, HighlightIssue>>)> = Default::default();
 }
 
+/// Bumped to have every thread load the configurations again
+static HIGHLIGHT_CONFIGS_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Load the grammars and queries again the next time they are needed, on
+/// every thread, for example once they've been installed or changed
 pub fn reset_highlight_configs() {
-    HIGHLIGHT_CONFIGS.with_borrow_mut(|configs| {
-        configs.clear();
-    });
+    HIGHLIGHT_CONFIGS_GENERATION.fetch_add(1, Ordering::Relaxed);
 }
 
 pub(crate) fn get_highlight_config(
     lang: LapceLanguage,
 ) -> Result<Arc<HighlightConfiguration>, HighlightIssue> {
     HIGHLIGHT_CONFIGS.with(|configs| {
-        let mut configs = configs.borrow_mut();
+        let (generation, configs) = &mut *configs.borrow_mut();
+        let current = HIGHLIGHT_CONFIGS_GENERATION.load(Ordering::Relaxed);
+        if *generation != current {
+            configs.clear();
+            *generation = current;
+        }
         let config = configs
             .entry(lang)
             .or_insert_with(|| lang.new_highlight_config().map(Arc::new));
@@ -99,6 +109,8 @@ pub enum InjectionLanguageMarker<'a> {
 #[derive(Debug)]
 pub struct HighlightConfiguration {
     pub language: Language,
+    /// The language the grammar was loaded for
+    pub lapce_language: LapceLanguage,
     pub query: Query,
     pub injections_query: Query,
     pub combined_injections_patterns: Vec<usize>,
@@ -208,6 +220,7 @@ pub fn new(
             ArcSwap::from_pointee(vec![None; query.capture_names().len()]);
         let conf = Self {
             language,
+            lapce_language: LapceLanguage::default(),
             query,
             injections_query,
             combined_injections_patterns,
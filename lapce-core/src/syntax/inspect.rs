@@ -0,0 +1,150 @@
+//! The syntax tree of a document as shown in the syntax tree inspector, and
+//! the queries tested against it.
+
+use std::{collections::HashSet, ops::Range};
+
+use tree_sitter::{Node, Query, QueryCursor};
+
+use super::{util::RopeProvider, Syntax, TREE_SITTER_MATCH_LIMIT};
+
+/// How many captures of a query are listed at most
+const MAX_QUERY_CAPTURES: usize = 1000;
+
+/// A named node of the syntax tree, shown in the inspector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxTreeRow {
+    /// The indices of the node and of its ancestors among the named children
+    /// of their parents
+    pub path: Vec<usize>,
+    pub kind: &'static str,
+    /// The name of the field of its parent the node is in, if any
+    pub field: Option<&'static str>,
+    pub range: Range<usize>,
+    /// The line and column the node starts at
+    pub start: (usize, usize),
+    /// The line and column the node ends at
+    pub end: (usize, usize),
+    pub has_children: bool,
+}
+
+/// A capture of a query tested in the inspector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryCaptureRow {
+    pub name: String,
+    pub range: Range<usize>,
+    /// The line and column the capture starts at
+    pub start: (usize, usize),
+}
+
+impl Syntax {
+    /// The named nodes of the syntax tree of the document's own language,
+    /// the children of the `expanded` ones included, in order
+    pub fn tree_rows(&self, expanded: &HashSet<Vec<usize>>) -> Vec<SyntaxTreeRow> {
+        let Some(tree) = self.layers.as_ref().and_then(|layers| layers.try_tree())
+        else {
+            return Vec::new();
+        };
+        let mut rows = Vec::new();
+        push_rows(tree.root_node(), None, &mut Vec::new(), expanded, &mut rows);
+        rows
+    }
+
+    /// The path of the deepest named node at `offset`
+    pub fn tree_path_at(&self, offset: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let Some(tree) = self.layers.as_ref().and_then(|layers| layers.try_tree())
+        else {
+            return path;
+        };
+        let mut node = tree.root_node();
+        let mut cursor = node.walk();
+        while let Some((i, child)) = node
+            .named_children(&mut cursor)
+            .enumerate()
+            .find(|(_, child)| {
+                child.start_byte() <= offset && offset < child.end_byte()
+            })
+        {
+            path.push(i);
+            node = child;
+        }
+        path
+    }
+
+    /// The captures of a query over the syntax tree of the document's own
+    /// language, in the order of the text, or why the query is invalid
+    pub fn query_captures(
+        &self,
+        query: &str,
+    ) -> Result<Vec<QueryCaptureRow>, String> {
+        let layer = self
+            .layers
+            .as_ref()
+            .map(|layers| &layers.layers[layers.root])
+            .ok_or_else(|| "The language has no syntax tree".to_string())?;
+        let tree = layer
+            .try_tree()
+            .ok_or_else(|| "The document isn't parsed yet".to_string())?;
+        let query = Query::new(&layer.config.language, query)
+            .map_err(|err| err.to_string())?;
+        let names = query.capture_names();
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_match_limit(TREE_SITTER_MATCH_LIMIT);
+        let captures = cursor
+            .captures(&query, tree.root_node(), RopeProvider(&self.text))
+            .map(|(mat, i)| {
+                let capture = mat.captures[i];
+                let start = capture.node.start_position();
+                QueryCaptureRow {
+                    name: names[capture.index as usize].to_string(),
+                    range: capture.node.byte_range(),
+                    start: (start.row, start.column),
+                }
+            })
+            .take(MAX_QUERY_CAPTURES)
+            .collect();
+        Ok(captures)
+    }
+}
+
+fn push_rows(
+    node: Node,
+    field: Option<&'static str>,
+    path: &mut Vec<usize>,
+    expanded: &HashSet<Vec<usize>>,
+    rows: &mut Vec<SyntaxTreeRow>,
+) {
+    let start = node.start_position();
+    let end = node.end_position();
+    rows.push(SyntaxTreeRow {
+        path: path.clone(),
+        kind: node.kind(),
+        field,
+        range: node.byte_range(),
+        start: (start.row, start.column),
+        end: (end.row, end.column),
+        has_children: node.named_child_count() > 0,
+    });
+    if !expanded.contains(path.as_slice()) {
+        return;
+    }
+
+    let mut cursor = node.walk();
+    if !cursor.goto_first_child() {
+        return;
+    }
+    let mut i = 0;
+    loop {
+        let child = cursor.node();
+        if child.is_named() {
+            path.push(i);
+            push_rows(child, cursor.field_name(), path, expanded, rows);
+            path.pop();
+            i += 1;
+        }
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
@@ -11,6 +11,7 @@
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     hash::{Hash, Hasher},
     mem,
+    ops::Range,
     path::Path,
     sync::{atomic::AtomicUsize, Arc},
 };
@@ -39,6 +40,7 @@
 };
 use crate::{
     buffer::{rope_text::RopeText, Buffer},
+    folding::{outermost_ranges, FoldingKind, FoldingRange},
     language::{self, LapceLanguage},
     lens::{Lens, LensBuilder},
     style::SCOPES,
@@ -46,10 +48,23 @@
 };
 pub mod edit;
 pub mod highlight;
+pub mod inspect;
 pub mod util;
 
 const TREE_SITTER_MATCH_LIMIT: u32 = 256;
 
+/// The size in bytes of the chunks the text is highlighted in, so that the
+/// highlights of a large file come in bit by bit instead of all at once
+pub const HIGHLIGHT_CHUNK_LEN: usize = 32 * 1024;
+
+/// The node kinds of the opening and closing tags of HTML and JSX elements
+const TAG_KINDS: &[&str] = &[
+    "start_tag",
+    "end_tag",
+    "jsx_opening_element",
+    "jsx_closing_element",
+];
+
 // Uses significant portions Helix's implementation, and on tree-sitter's highlighter implementation
 
 pub struct TsParser {
@@ -491,7 +506,7 @@ pub fn new(
 
         let cancel_flag = AtomicUsize::new(0);
         if let Some(source) = source {
-            let _ = syntax.update(0, 0, source, None, &cancel_flag);
+            let _ = syntax.update(0, 0, source, None, None, &cancel_flag);
         }
 
         syntax
@@ -503,6 +518,7 @@ pub fn update(
         new_rev: u64,
         source: &Rope,
         syntax_edits: Option<&[SyntaxEdit]>,
+        injection_range: Option<&Range<usize>>,
         cancellation_flag: &AtomicUsize,
     ) -> Result<(), Error> {
         let mut queue = VecDeque::new();
@@ -511,12 +527,14 @@ pub fn update(
         let injection_callback = |language: &InjectionLanguageMarker| {
             let language = match language {
                 InjectionLanguageMarker::Name(name) => {
-                    LapceLanguage::from_name(name)
+                    LapceLanguage::from_injection_name(name)
                 }
                 InjectionLanguageMarker::Filename(path) => {
                     LapceLanguage::from_path_raw(path)
                 }
-                InjectionLanguageMarker::Shebang(id) => LapceLanguage::from_name(id),
+                InjectionLanguageMarker::Shebang(id) => {
+                    LapceLanguage::from_injection_name(id)
+                }
             };
             language
                 .map(get_highlight_config)
@@ -723,6 +741,15 @@ fn point_sub(a: Point, b: Point) -> Point {
                         if let (Some(injection_capture), Some(content_node)) =
                             (injection_capture, content_node)
                         {
+                            // The injections away from what is shown are
+                            // parsed once they are scrolled to
+                            if let Some(range) = injection_range {
+                                if content_node.end_byte() < range.start
+                                    || content_node.start_byte() > range.end
+                                {
+                                    continue;
+                                }
+                            }
                             if let Ok(config) =
                                 (injection_callback)(&injection_capture)
                             {
@@ -895,6 +922,9 @@ pub struct Syntax {
     pub line_height: usize,
     pub lens_height: usize,
     pub styles: Option<Spans<Style>>,
+    /// The range of the text whose injected languages are parsed, `None`
+    /// for all of it
+    pub injection_range: Option<Range<usize>>,
     pub cancel_flag: Arc<AtomicUsize>,
 }
 
@@ -934,62 +964,93 @@ pub fn from_language(language: LapceLanguage) -> Syntax {
             lens_height: 0,
             normal_lines: Vec::new(),
             styles: None,
+            injection_range: None,
             cancel_flag: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Parse the text after the edits and highlight all of it.
     pub fn parse(
         &mut self,
         new_rev: u64,
         new_text: Rope,
         edits: Option<&[SyntaxEdit]>,
     ) {
+        for range in self.parse_tree(new_rev, new_text, edits) {
+            let spans = self.highlight(range.clone());
+            self.set_highlights(range, spans);
+        }
+    }
+
+    /// Parse the text after the edits, reusing the trees of the layers, and
+    /// give the chunks of the text whose highlights are out of date, the ones
+    /// in the injection range first. Until a chunk is highlighted with
+    /// [`Syntax::highlight`] and [`Syntax::set_highlights`], it keeps the
+    /// styles it had before the edits.
+    pub fn parse_tree(
+        &mut self,
+        new_rev: u64,
+        new_text: Rope,
+        edits: Option<&[SyntaxEdit]>,
+    ) -> Vec<Range<usize>> {
         let layers = match &mut self.layers {
             Some(layers) => layers,
-            None => return,
+            None => return Vec::new(),
         };
         let edits = edits.filter(|edits| new_rev == self.rev + edits.len() as u64);
-        let _ =
-            layers.update(self.rev, new_rev, &new_text, edits, &self.cancel_flag);
+        let old_tree = layers.try_tree().cloned();
+        let _ = layers.update(
+            self.rev,
+            new_rev,
+            &new_text,
+            edits,
+            self.injection_range.as_ref(),
+            &self.cancel_flag,
+        );
         let tree = layers.try_tree();
 
-        let styles = if tree.is_some() {
-            let mut current_hl: Option<Highlight> = None;
-            let mut highlights: SpansBuilder<Style> =
-                SpansBuilder::new(new_text.len());
-
-            // TODO: Should we be ignoring highlight errors via flattening them?
-            for highlight in layers
-                .highlight_iter(
-                    &new_text,
-                    Some(0..new_text.len()),
-                    Some(&self.cancel_flag),
-                )
-                .flatten()
-            {
-                match highlight {
-                    HighlightEvent::Source { start, end } => {
-                        if let Some(hl) = current_hl {
-                            if let Some(hl) = SCOPES.get(hl.0) {
-                                highlights.add_span(
-                                    Interval::new(start, end),
-                                    Style {
-                                        fg_color: Some(hl.to_string()),
-                                    },
-                                );
-                            }
-                        }
-                    }
-                    HighlightEvent::HighlightStart(hl) => {
-                        current_hl = Some(hl);
-                    }
-                    HighlightEvent::HighlightEnd => current_hl = None,
+        let styles_valid = self
+            .styles
+            .as_ref()
+            .is_some_and(|styles| styles.len() == new_text.len());
+        let mut ranges = match (edits, old_tree, tree) {
+            (Some(edits), Some(mut old_tree), Some(tree)) if styles_valid => {
+                let edits: Vec<&tree_sitter::InputEdit> =
+                    edits.iter().flat_map(|edit| &edit.0).collect();
+                for edit in &edits {
+                    old_tree.edit(edit);
                 }
+                let mut ranges = edited_ranges(&edits);
+                ranges.extend(
+                    old_tree
+                        .changed_ranges(tree)
+                        .map(|range| range.start_byte..range.end_byte),
+                );
+                ranges
+            }
+            (None, Some(_), Some(_)) if styles_valid && new_rev == self.rev => {
+                Vec::new()
             }
+            _ => vec![0..new_text.len()],
+        };
 
-            Some(highlights.build())
+        let chunks = if tree.is_some() {
+            if !styles_valid {
+                self.styles = Some(SpansBuilder::new(new_text.len()).build());
+            }
+            // The injections around the viewport may just have been parsed
+            if let Some(range) = &self.injection_range {
+                ranges.push(range.clone());
+            }
+            highlight_chunks(
+                &new_text,
+                ranges,
+                self.injection_range.as_ref(),
+                HIGHLIGHT_CHUNK_LEN,
+            )
         } else {
-            None
+            self.styles = None;
+            Vec::new()
         };
 
         let normal_lines = if let Some(tree) = tree {
@@ -1011,8 +1072,66 @@ pub fn parse(
         self.rev = new_rev;
         self.lens = lens;
         self.normal_lines = normal_lines;
-        self.styles = styles;
-        self.text = new_text
+        self.text = new_text;
+        chunks
+    }
+
+    /// The styles of a range of the text, with offsets relative to its start
+    pub fn highlight(&self, range: Range<usize>) -> Spans<Style> {
+        let range = range.start.min(self.text.len())..range.end.min(self.text.len());
+        let mut highlights: SpansBuilder<Style> = SpansBuilder::new(range.len());
+        let Some(layers) = &self.layers else {
+            return highlights.build();
+        };
+        if layers.try_tree().is_none() {
+            return highlights.build();
+        }
+
+        let mut current_hl: Option<Highlight> = None;
+        // TODO: Should we be ignoring highlight errors via flattening them?
+        for highlight in layers
+            .highlight_iter(&self.text, Some(range.clone()), Some(&self.cancel_flag))
+            .flatten()
+        {
+            match highlight {
+                HighlightEvent::Source { start, end } => {
+                    let start = start.max(range.start);
+                    let end = end.min(range.end);
+                    if start >= end {
+                        continue;
+                    }
+                    if let Some(hl) = current_hl {
+                        if let Some(hl) = SCOPES.get(hl.0) {
+                            highlights.add_span(
+                                Interval::new(
+                                    start - range.start,
+                                    end - range.start,
+                                ),
+                                Style {
+                                    fg_color: Some(hl.to_string()),
+                                },
+                            );
+                        }
+                    }
+                }
+                HighlightEvent::HighlightStart(hl) => {
+                    current_hl = Some(hl);
+                }
+                HighlightEvent::HighlightEnd => current_hl = None,
+            }
+        }
+
+        highlights.build()
+    }
+
+    /// Replace the styles of a range of the text with the ones given by
+    /// [`Syntax::highlight`] for it
+    pub fn set_highlights(&mut self, range: Range<usize>, spans: Spans<Style>) {
+        if let Some(styles) = self.styles.as_mut() {
+            if range.start + spans.len() == range.end && range.end <= styles.len() {
+                styles.edit(Interval::new(range.start, range.end), spans);
+            }
+        }
     }
 
     pub fn update_lens_height(&mut self, line_height: usize, lens_height: usize) {
@@ -1048,6 +1167,27 @@ pub fn lens_from_normal_lines(
         builder.build()
     }
 
+    /// The language at the offset, which is the language of the innermost
+    /// injection containing it if there is one, such as CSS in a `<style>`
+    /// element.
+    pub fn language_at(&self, offset: usize) -> LapceLanguage {
+        let Some(layers) = &self.layers else {
+            return self.language;
+        };
+        layers
+            .layers
+            .values()
+            .filter(|layer| {
+                layer.depth > 0
+                    && layer.ranges.iter().any(|range| {
+                        range.start_byte <= offset && offset <= range.end_byte
+                    })
+            })
+            .max_by_key(|layer| layer.depth)
+            .map(|layer| layer.config.lapce_language)
+            .unwrap_or(self.language)
+    }
+
     pub fn find_matching_pair(&self, offset: usize) -> Option<usize> {
         let tree = self.layers.as_ref()?.try_tree()?;
         let node = tree
@@ -1136,6 +1276,63 @@ fn find_tag_in_children(&self, node: Node, tag: &str) -> Option<usize> {
         None
     }
 
+    /// The range of the name of the HTML or JSX tag at the offset, and the
+    /// range of the name of the tag it pairs with, if they are the same name.
+    pub fn find_tag_name_pair(
+        &self,
+        offset: usize,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let mut layers: Vec<&LanguageLayer> = self
+            .layers
+            .as_ref()?
+            .layers
+            .values()
+            .filter(|layer| {
+                layer.ranges.iter().any(|range| {
+                    range.start_byte <= offset && offset <= range.end_byte
+                })
+            })
+            .collect();
+        // Injections first, as they are inside of their parents
+        layers.sort_by_key(|layer| std::cmp::Reverse(layer.depth));
+
+        layers.into_iter().find_map(|layer| {
+            let tree = layer.try_tree()?;
+            let mut tag =
+                tree.root_node().descendant_for_byte_range(offset, offset)?;
+            while !TAG_KINDS.contains(&tag.kind()) {
+                tag = tag.parent()?;
+            }
+            let name = Self::tag_name(tag)?;
+            if offset < name.start_byte() || name.end_byte() < offset {
+                return None;
+            }
+
+            let element = tag.parent()?;
+            let other = (0..element.child_count())
+                .filter_map(|i| element.child(i))
+                .find(|child| {
+                    child.id() != tag.id() && TAG_KINDS.contains(&child.kind())
+                })?;
+            let other_name = Self::tag_name(other)?;
+            if self.text.slice_to_cow(name.byte_range())
+                != self.text.slice_to_cow(other_name.byte_range())
+            {
+                return None;
+            }
+            Some((name.byte_range(), other_name.byte_range()))
+        })
+    }
+
+    fn tag_name(tag: Node) -> Option<Node> {
+        // JSX has a `name` field, HTML a `tag_name` child
+        tag.child_by_field_name("name").or_else(|| {
+            (0..tag.child_count())
+                .filter_map(|i| tag.child(i))
+                .find(|child| child.kind() == "tag_name")
+        })
+    }
+
     pub fn sticky_headers(&self, offset: usize) -> Option<Vec<usize>> {
         let tree = self.layers.as_ref()?.try_tree()?;
         let mut node = tree.root_node().descendant_for_byte_range(offset, offset)?;
@@ -1154,6 +1351,58 @@ pub fn sticky_headers(&self, offset: usize) -> Option<Vec<usize>> {
         Some(offsets)
     }
 
+    /// The folding ranges of the nodes of the syntax tree which span several
+    /// lines, for when the language server doesn't provide any
+    pub fn folding_ranges(&self) -> Vec<FoldingRange> {
+        let Some(tree) = self.layers.as_ref().and_then(|layers| layers.try_tree())
+        else {
+            return Vec::new();
+        };
+        let mut cursor = tree.walk();
+        if !cursor.goto_first_child() {
+            return Vec::new();
+        }
+
+        let mut nodes = Vec::new();
+        // The depth of the cursor below the root
+        let mut depth = 1;
+        loop {
+            let node = cursor.node();
+            let start_line = node.start_position().row;
+            let end = node.end_position();
+            // A node ending with its line ends on the line before
+            let end_line = if end.column == 0 && end.row > start_line {
+                end.row - 1
+            } else {
+                end.row
+            };
+            // Only the nodes spanning several lines can have children which do
+            if end_line > start_line {
+                if node.is_named() {
+                    nodes.push(FoldingRange {
+                        start_line,
+                        end_line,
+                        kind: if node.kind().contains("comment") {
+                            FoldingKind::Comment
+                        } else {
+                            FoldingKind::Block
+                        },
+                    });
+                }
+                if cursor.goto_first_child() {
+                    depth += 1;
+                    continue;
+                }
+            }
+            while !cursor.goto_next_sibling() {
+                if depth == 1 || !cursor.goto_parent() {
+                    return outermost_ranges(nodes);
+                }
+                depth -= 1;
+            }
+        }
+    }
+
     pub fn find_enclosing_parentheses(
         &self,
         offset: usize,
@@ -1227,10 +1476,130 @@ pub fn find_enclosing_pair(&self, offset: usize) -> Option<(usize, usize)> {
     }
 }
 
+/// The ranges of the text touched by the edits, one after the other, in the
+/// offsets of the text after all of them
+fn edited_ranges(edits: &[&tree_sitter::InputEdit]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for edit in edits {
+        let shift = |offset: usize, deleted: usize| {
+            if offset >= edit.old_end_byte {
+                offset - edit.old_end_byte + edit.new_end_byte
+            } else if offset > edit.start_byte {
+                deleted
+            } else {
+                offset
+            }
+        };
+        for range in ranges.iter_mut() {
+            range.start = shift(range.start, edit.start_byte);
+            range.end = shift(range.end, edit.new_end_byte).max(range.start);
+        }
+        ranges.push(edit.start_byte..edit.new_end_byte);
+    }
+    ranges
+}
+
+/// Extend the ranges to whole lines, merge the ones that overlap, and split
+/// them in chunks of whole lines of about `chunk_len` bytes, the ones
+/// overlapping `first` first
+fn highlight_chunks(
+    text: &Rope,
+    mut ranges: Vec<Range<usize>>,
+    first: Option<&Range<usize>>,
+    chunk_len: usize,
+) -> Vec<Range<usize>> {
+    let line_end = |offset: usize| {
+        text.offset_of_line(text.line_of_offset(offset) + 1)
+            .min(text.len())
+    };
+    for range in ranges.iter_mut() {
+        let start = range.start.min(text.len());
+        let end = range.end.clamp(start, text.len());
+        *range = text.offset_of_line(text.line_of_offset(start))..line_end(end);
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+
+    let mut chunks = Vec::new();
+    for range in merged {
+        let mut start = range.start;
+        while start < range.end {
+            let end = if range.end - start <= chunk_len {
+                range.end
+            } else {
+                line_end(start + chunk_len).min(range.end)
+            };
+            chunks.push(start..end);
+            start = end;
+        }
+    }
+    if let Some(first) = first {
+        chunks.sort_by_key(|chunk| {
+            !(chunk.start < first.end && first.start < chunk.end)
+        });
+    }
+    chunks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn input_edit(
+        start: usize,
+        old_end: usize,
+        new_end: usize,
+    ) -> tree_sitter::InputEdit {
+        tree_sitter::InputEdit {
+            start_byte: start,
+            old_end_byte: old_end,
+            new_end_byte: new_end,
+            start_position: Point::new(0, start),
+            old_end_position: Point::new(0, old_end),
+            new_end_position: Point::new(0, new_end),
+        }
+    }
+
+    #[test]
+    fn test_edited_ranges() {
+        // Insert 3 bytes at 10, then delete 2..5 and insert 1 byte there
+        let edits = [input_edit(10, 10, 13), input_edit(2, 5, 3)];
+        let edits: Vec<&tree_sitter::InputEdit> = edits.iter().collect();
+        assert_eq!(edited_ranges(&edits), vec![8..11, 2..3]);
+
+        // Delete the text that was inserted
+        let edits = [input_edit(4, 4, 8), input_edit(2, 10, 2)];
+        let edits: Vec<&tree_sitter::InputEdit> = edits.iter().collect();
+        assert_eq!(edited_ranges(&edits), vec![2..2, 2..2]);
+    }
+
+    #[test]
+    fn test_highlight_chunks() {
+        let text = Rope::from("aaaa\nbbbb\ncccc\ndddd\neeee\n");
+        assert_eq!(
+            highlight_chunks(&text, vec![6..7, 12..13], None, 100),
+            vec![5..15]
+        );
+        assert_eq!(
+            highlight_chunks(&text, vec![0..text.len()], None, 8),
+            vec![0..10, 10..20, 20..25]
+        );
+        assert_eq!(
+            highlight_chunks(&text, vec![0..2, 21..22], Some(&(20..23)), 100),
+            vec![20..25, 0..5]
+        );
+        assert!(highlight_chunks(&text, vec![25..25], None, 100).is_empty());
+    }
+
     #[test]
     fn test_lens() {
         let lens = Syntax::lens_from_normal_lines(5, 25, 2, &[4]);
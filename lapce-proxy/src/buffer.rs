@@ -3,15 +3,17 @@
     ffi::OsString,
     fs,
     fs::File,
-    io::{Read, Write},
+    io::{Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
     time::SystemTime,
 };
 
 use anyhow::{anyhow, Result};
 use floem_editor_core::buffer::rope_text::CharIndicesJoin;
-use lapce_core::encoding::offset_utf8_to_utf16;
-use lapce_rpc::buffer::BufferId;
+use lapce_core::encoding::{offset_utf16_to_utf8, offset_utf8_to_utf16};
+use lapce_rpc::{
+    buffer::BufferId, inline_completion::INLINE_COMPLETION_CONTEXT_LINES,
+};
 use lapce_xi_rope::{interval::IntervalBounds, rope::Rope, RopeDelta};
 use lsp_types::*;
 
@@ -28,19 +30,18 @@ pub struct Buffer {
 
 impl Buffer {
     pub fn new(id: BufferId, path: PathBuf) -> Buffer {
-        let (s, read_only) = match load_file(&path) {
-            Ok(s) => (s, false),
-            Err(err) => match err.downcast_ref::<std::io::Error>() {
-                Some(err) => match err.kind() {
-                    std::io::ErrorKind::PermissionDenied => {
-                        ("Permission Denied".to_string(), true)
-                    }
-                    std::io::ErrorKind::NotFound => ("".to_string(), false),
-                    _ => ("Not Supported".to_string(), true),
-                },
-                None => ("Not Supported".to_string(), true),
-            },
-        };
+        let (s, read_only) = buffer_content(load_file(&path));
+        Self::with_content(id, path, s, read_only)
+    }
+
+    /// A buffer of a file already read, with the content given by
+    /// [`buffer_content`]
+    pub fn with_content(
+        id: BufferId,
+        path: PathBuf,
+        s: String,
+        read_only: bool,
+    ) -> Buffer {
         let rope = Rope::from(s);
         let rev = u64::from(!rope.is_empty());
         let language_id = language_id_from_path(&path).unwrap_or("");
@@ -159,6 +160,25 @@ pub fn offset_to_position(&self, offset: usize) -> Position {
         }
     }
 
+    /// The text of the lines around an LSP position, before and after it, for
+    /// the providers of inline completions
+    pub fn inline_completion_context(&self, position: Position) -> (String, String) {
+        let line = (position.line as usize).min(self.line_of_offset(self.len()));
+        let line_offset = self.offset_of_line(line);
+        let col = offset_utf16_to_utf8(
+            self.char_indices_iter(line_offset..self.offset_of_line(line + 1)),
+            position.character as usize,
+        );
+        let offset = line_offset + col;
+        let start = self
+            .offset_of_line(line.saturating_sub(INLINE_COMPLETION_CONTEXT_LINES));
+        let end = self.offset_of_line(line + INLINE_COMPLETION_CONTEXT_LINES + 1);
+        (
+            self.slice_to_cow(start..offset).into_owned(),
+            self.slice_to_cow(offset..end).into_owned(),
+        )
+    }
+
     pub fn slice_to_cow<T: IntervalBounds>(&self, range: T) -> Cow<str> {
         self.rope.slice_to_cow(range)
     }
@@ -185,6 +205,49 @@ pub fn load_file(path: &Path) -> Result<String> {
     read_path_to_string(path)
 }
 
+/// The content of a buffer from the result of reading its file, and whether
+/// it is read only. A file that can't be read gets a message in its place.
+pub fn buffer_content(result: Result<String>) -> (String, bool) {
+    match result {
+        Ok(s) => (s, false),
+        Err(err) => match err.downcast_ref::<std::io::Error>() {
+            Some(err) => match err.kind() {
+                std::io::ErrorKind::PermissionDenied => {
+                    ("Permission Denied".to_string(), true)
+                }
+                std::io::ErrorKind::NotFound => ("".to_string(), false),
+                _ => ("Not Supported".to_string(), true),
+            },
+            None => ("Not Supported".to_string(), true),
+        },
+    }
+}
+
+/// Read a file in chunks of `chunk_len` bytes, calling `progress` with the
+/// bytes read so far and the size of the file after each one. Reading stops
+/// with `None` once `progress` returns false.
+pub fn read_path_chunked(
+    path: &Path,
+    chunk_len: usize,
+    mut progress: impl FnMut(u64, u64) -> bool,
+) -> Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut buffer = Vec::with_capacity(total as usize);
+    let mut chunk = vec![0; chunk_len.max(1)];
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if !progress(buffer.len() as u64, total.max(buffer.len() as u64)) {
+            return Ok(None);
+        }
+    }
+    Ok(Some(String::from_utf8(buffer)?))
+}
+
 pub fn read_path_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
     let path = path.as_ref();
 
@@ -328,6 +391,31 @@ fn get_document_content_changes(
     }
 }
 
+/// The text appended to the file at `path` since it had the content of `rope`,
+/// such as the lines written to a log. Returns `None` when the file changed
+/// otherwise, so it has to be loaded as a whole.
+pub fn load_appended(path: &Path, rope: &Rope) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let len = rope.len();
+    if file.metadata().ok()?.len() <= len as u64 {
+        return None;
+    }
+
+    // Checking the end of the old content is enough to tell an append from a
+    // rewrite without reading the whole file
+    let tail_start = len.saturating_sub(256);
+    let mut tail = vec![0; len - tail_start];
+    file.seek(SeekFrom::Start(tail_start as u64)).ok()?;
+    file.read_exact(&mut tail).ok()?;
+    if tail != rope.slice_to_cow(tail_start..len).as_bytes() {
+        return None;
+    }
+
+    let mut appended = Vec::new();
+    file.read_to_end(&mut appended).ok()?;
+    String::from_utf8(appended).ok()
+}
+
 /// Returns the modification timestamp for the file at a given path,
 /// if present.
 pub fn get_mod_time<P: AsRef<Path>>(path: P) -> Option<SystemTime> {
@@ -336,3 +424,30 @@ pub fn get_mod_time<P: AsRef<Path>>(path: P) -> Option<SystemTime> {
         .and_then(|meta| meta.modified())
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::read_path_chunked;
+
+    #[test]
+    fn test_read_path_chunked() {
+        let path = std::env::temp_dir()
+            .join(format!("lapce-read-chunked-{}", std::process::id()));
+        std::fs::write(&path, "hello, world").unwrap();
+
+        let mut progress = Vec::new();
+        let content = read_path_chunked(&path, 5, |loaded, total| {
+            progress.push((loaded, total));
+            true
+        })
+        .unwrap();
+        assert_eq!(content.as_deref(), Some("hello, world"));
+        assert_eq!(progress, [(5, 12), (10, 12), (12, 12)]);
+
+        // Stopped after the first chunk
+        let content = read_path_chunked(&path, 5, |_, _| false).unwrap();
+        assert_eq!(content, None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
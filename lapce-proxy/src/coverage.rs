@@ -0,0 +1,224 @@
+//! Coverage reports in the lcov and Cobertura formats, which is what most
+//! coverage tools can write, such as `cargo llvm-cov`, `grcov` and
+//! `coverage.py`.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use lapce_rpc::coverage::{FileCoverage, LineCoverage};
+
+/// The coverage of the files in a report, whose relative paths are resolved
+/// against `base`
+pub fn parse_coverage(content: &str, base: &Path) -> HashMap<PathBuf, FileCoverage> {
+    if content.trim_start().starts_with('<') {
+        parse_cobertura(content, base)
+    } else {
+        parse_lcov(content, base)
+    }
+}
+
+/// An lcov tracefile, with a record for each source file:
+///
+/// ```text
+/// SF:<path>
+/// DA:<line>,<hits>
+/// BRDA:<line>,<block>,<branch>,<taken or ->
+/// end_of_record
+/// ```
+pub fn parse_lcov(content: &str, base: &Path) -> HashMap<PathBuf, FileCoverage> {
+    let mut files: HashMap<PathBuf, FileCoverage> = HashMap::new();
+    let mut path: Option<PathBuf> = None;
+    let mut hits: HashMap<usize, u64> = HashMap::new();
+    // The lines with a branch that never ran
+    let mut missed_branches: Vec<usize> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(file) = line.strip_prefix("SF:") {
+            path = Some(base.join(file));
+        } else if let Some(data) = line.strip_prefix("DA:") {
+            let mut parts = data.split(',');
+            let (Some(Ok(line)), Some(Ok(count))) = (
+                parts.next().map(str::parse::<usize>),
+                parts.next().map(str::parse::<u64>),
+            ) else {
+                continue;
+            };
+            *hits.entry(line).or_default() += count;
+        } else if let Some(data) = line.strip_prefix("BRDA:") {
+            let mut parts = data.split(',');
+            let Some(Ok(line)) = parts.next().map(str::parse::<usize>) else {
+                continue;
+            };
+            let taken = parts.nth(2).unwrap_or("-");
+            if taken == "-" || taken == "0" {
+                missed_branches.push(line);
+            }
+        } else if line == "end_of_record" {
+            if let Some(path) = path.take() {
+                let file = files.entry(path).or_default();
+                for (line, count) in hits.drain() {
+                    let coverage = if count == 0 {
+                        LineCoverage::Uncovered
+                    } else if missed_branches.contains(&line) {
+                        LineCoverage::Partial
+                    } else {
+                        LineCoverage::Covered
+                    };
+                    file.add(line.saturating_sub(1), coverage);
+                }
+            }
+            hits.clear();
+            missed_branches.clear();
+        }
+    }
+    files
+}
+
+/// A Cobertura XML report, with the lines of each class, whose file names are
+/// relative to one of the `<source>` directories:
+///
+/// ```xml
+/// <class filename="src/main.rs">
+///   <lines>
+///     <line number="1" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+///   </lines>
+/// </class>
+/// ```
+pub fn parse_cobertura(
+    content: &str,
+    base: &Path,
+) -> HashMap<PathBuf, FileCoverage> {
+    let sources: Vec<PathBuf> = content
+        .split("<source>")
+        .skip(1)
+        .filter_map(|s| s.split("</source>").next())
+        .map(|s| base.join(unescape_xml(s.trim())))
+        .collect();
+    let resolve = |filename: &str| {
+        let filename = unescape_xml(filename);
+        sources
+            .iter()
+            .map(|source| source.join(&filename))
+            .find(|path| path.exists())
+            .or_else(|| sources.first().map(|source| source.join(&filename)))
+            .unwrap_or_else(|| base.join(&filename))
+    };
+
+    let mut files: HashMap<PathBuf, FileCoverage> = HashMap::new();
+    let mut path: Option<PathBuf> = None;
+    for tag in content.split('<').skip(1) {
+        if tag.starts_with("class ") {
+            path = xml_attr(tag, "filename").map(resolve);
+        } else if tag.starts_with("/class") {
+            path = None;
+        } else if tag.starts_with("line ") {
+            let Some(path) = path.as_ref() else {
+                continue;
+            };
+            let (Some(Ok(line)), Some(Ok(hits))) = (
+                xml_attr(tag, "number").map(str::parse::<usize>),
+                xml_attr(tag, "hits").map(str::parse::<u64>),
+            ) else {
+                continue;
+            };
+            let all_branches = xml_attr(tag, "condition-coverage")
+                .map_or(true, |c| c.starts_with("100%"));
+            let coverage = if hits == 0 {
+                LineCoverage::Uncovered
+            } else if !all_branches {
+                LineCoverage::Partial
+            } else {
+                LineCoverage::Covered
+            };
+            files
+                .entry(path.clone())
+                .or_default()
+                .add(line.saturating_sub(1), coverage);
+        }
+    }
+    files
+}
+
+/// The value of an attribute in the text of a tag
+fn xml_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let tag = tag.split('>').next()?;
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().last();
+        let after = &rest[i + name.len()..];
+        if before.is_some_and(char::is_whitespace) {
+            if let Some(value) = after.strip_prefix("=\"") {
+                return value.split('"').next();
+            }
+        }
+        rest = after;
+    }
+    None
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lapce_rpc::coverage::LineCoverage;
+
+    use super::{parse_cobertura, parse_lcov};
+
+    #[test]
+    fn test_parse_lcov() {
+        let content = "TN:
+SF:src/main.rs
+DA:1,3
+DA:2,0
+DA:4,1
+BRDA:4,0,0,1
+BRDA:4,0,1,0
+end_of_record
+SF:src/main.rs
+DA:2,1
+end_of_record
+";
+        let files = parse_lcov(content, Path::new("/ws"));
+        let file = &files[&Path::new("/ws/src/main.rs").to_path_buf()];
+        assert_eq!(file.lines.get(&0), Some(&LineCoverage::Covered));
+        // Covered by the second record
+        assert_eq!(file.lines.get(&1), Some(&LineCoverage::Covered));
+        assert_eq!(file.lines.get(&3), Some(&LineCoverage::Partial));
+        assert_eq!(file.lines.len(), 3);
+        assert_eq!(file.percent(), Some(100.0));
+    }
+
+    #[test]
+    fn test_parse_cobertura() {
+        let content = r#"<?xml version="1.0" ?>
+<coverage line-rate="0.5">
+  <sources><source>/ws</source></sources>
+  <packages><package name="a"><classes>
+    <class name="main" filename="src/main.rs" line-rate="0.5">
+      <lines>
+        <line number="1" hits="2"/>
+        <line number="2" hits="0"/>
+        <line number="3" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+      </lines>
+    </class>
+  </classes></package></packages>
+</coverage>"#;
+        let files = parse_cobertura(content, Path::new("/other"));
+        let file = &files[&Path::new("/ws/src/main.rs").to_path_buf()];
+        assert_eq!(file.lines.get(&0), Some(&LineCoverage::Covered));
+        assert_eq!(file.lines.get(&1), Some(&LineCoverage::Uncovered));
+        assert_eq!(file.lines.get(&2), Some(&LineCoverage::Partial));
+        assert_eq!(file.percent().map(|p| p.round()), Some(67.0));
+    }
+}
@@ -3,7 +3,7 @@
     fs, io,
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     thread,
@@ -12,34 +12,46 @@
 
 use alacritty_terminal::{event::WindowSize, event_loop::Msg};
 use anyhow::{anyhow, Context, Result};
-use crossbeam_channel::Sender;
+use crossbeam_channel::{Receiver, Sender};
 use git2::{
-    build::CheckoutBuilder, DiffOptions, ErrorCode::NotFound, Oid, Repository,
+    build::CheckoutBuilder, DiffOptions, ErrorCode::NotFound, IndexEntry, IndexTime,
+    Oid, Repository,
 };
 use grep_matcher::Matcher;
 use grep_regex::RegexMatcherBuilder;
 use grep_searcher::{sinks::UTF8, SearcherBuilder};
 use indexmap::IndexMap;
 use lapce_rpc::{
+    buffer::BufferId,
     core::{CoreNotification, CoreRpcHandler},
+    dap_types::ProcessInfo,
     file::FileNodeItem,
+    inline_completion::ProvideInlineCompletionParams,
+    large_file::FILE_TOO_LARGE,
+    plugin::PluginId,
     proxy::{
         ProxyHandler, ProxyNotification, ProxyRequest, ProxyResponse,
         ProxyRpcHandler, SearchMatch,
     },
-    source_control::{DiffInfo, FileDiff},
+    source_control::{BlameInfo, DiffInfo, FileDiff, GitCommitInfo},
     style::{LineStyle, SemanticStyles},
     terminal::TermId,
     RequestId, RpcError,
 };
 use lapce_xi_rope::Rope;
 use lsp_types::{
-    MessageType, Position, Range, ShowMessageParams, TextDocumentItem, Url,
+    MessageType, Position, Range, SemanticToken, SemanticTokens, SemanticTokensEdit,
+    SemanticTokensFullDeltaResult, ShowMessageParams, TextDocumentIdentifier,
+    TextDocumentItem, TextDocumentPositionParams, Url,
 };
 use parking_lot::Mutex;
 
 use crate::{
-    buffer::{get_mod_time, load_file, Buffer},
+    buffer::{
+        buffer_content, get_mod_time, load_appended, load_file, read_path_chunked,
+        Buffer,
+    },
+    coverage, large_file,
     plugin::{catalog::PluginCatalog, PluginCatalogRpcHandler},
     terminal::{Terminal, TerminalSender},
     watcher::{FileWatcher, Notify, WatchToken},
@@ -47,6 +59,10 @@
 
 const OPEN_FILE_EVENT_TOKEN: WatchToken = WatchToken(1);
 const WORKSPACE_EVENT_TOKEN: WatchToken = WatchToken(2);
+const COVERAGE_EVENT_TOKEN: WatchToken = WatchToken(3);
+/// The size of the chunks a file being opened is read in, the progress
+/// being reported after each one
+const READ_CHUNK_LEN: usize = 1024 * 1024;
 
 pub struct Dispatcher {
     workspace: Option<PathBuf>,
@@ -54,14 +70,26 @@ pub struct Dispatcher {
     core_rpc: CoreRpcHandler,
     catalog_rpc: PluginCatalogRpcHandler,
     buffers: HashMap<PathBuf, Buffer>,
+    /// The files being read to be opened, with the flag to stop reading them
+    loading_buffers: HashMap<BufferId, Arc<AtomicBool>>,
+    /// The buffers of the files read in the background, added to `buffers`
+    /// before the next message is handled
+    read_buffer_tx: Sender<Buffer>,
+    read_buffer_rx: Receiver<Buffer>,
     terminals: HashMap<TermId, TerminalSender>,
     file_watcher: FileWatcher,
+    /// The watched directory of the coverage report, and the report
+    coverage: Option<(PathBuf, PathBuf)>,
+    /// The last semantic tokens of the files, with the plugin they came
+    /// from, kept while they have a result id to ask for deltas with
+    semantic_tokens: Arc<Mutex<HashMap<PathBuf, (PluginId, SemanticTokens)>>>,
     window_id: usize,
     tab_id: usize,
 }
 
 impl ProxyHandler for Dispatcher {
     fn handle_notification(&mut self, rpc: ProxyNotification) {
+        self.add_read_buffers();
         use ProxyNotification::*;
         match rpc {
             Initialize {
@@ -112,16 +140,60 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 self.core_rpc
                     .notification(CoreNotification::OpenPaths { paths });
             }
+            WatchCoverage { path } => {
+                if let Some((dir, _)) = self.coverage.take() {
+                    self.file_watcher.unwatch(&dir, COVERAGE_EVENT_TOKEN);
+                }
+                // The report is watched through its directory, since coverage
+                // tools tend to replace it rather than write to it
+                if let Some(path) = path {
+                    if let Some(dir) =
+                        path.parent().and_then(|dir| dir.canonicalize().ok())
+                    {
+                        let file_name = path.file_name().map(|n| n.to_os_string());
+                        self.file_watcher.watch_filtered(
+                            &dir,
+                            false,
+                            COVERAGE_EVENT_TOKEN,
+                            move |p| p.file_name() == file_name.as_deref(),
+                        );
+                        self.coverage = Some((dir, path));
+                    }
+                }
+                self.load_coverage();
+            }
+            CoverageFileChanged {} => {
+                self.load_coverage();
+            }
             OpenFileChanged { path } => {
                 if let Some(buffer) = self.buffers.get(&path) {
                     if get_mod_time(&buffer.path) == buffer.mod_time {
                         return;
                     }
-                    if let Ok(content) = load_file(&buffer.path) {
+                    if let Some(content) = load_appended(&buffer.path, &buffer.rope)
+                    {
+                        self.core_rpc.open_file_appended(
+                            path,
+                            buffer.len(),
+                            content,
+                        );
+                    } else if let Ok(content) = load_file(&buffer.path) {
                         self.core_rpc.open_file_changed(path, content);
                     }
                 }
             }
+            CancelNewBuffer { buffer_id } => {
+                if let Some(cancelled) = self.loading_buffers.remove(&buffer_id) {
+                    cancelled.store(true, Ordering::Relaxed);
+                }
+            }
+            CloseBuffer { path } => {
+                if self.buffers.remove(&path).is_some() {
+                    self.file_watcher.unwatch(&path, OPEN_FILE_EVENT_TOKEN);
+                    self.semantic_tokens.lock().remove(&path);
+                    self.catalog_rpc.did_close_document(&path);
+                }
+            }
             Completion {
                 request_id,
                 path,
@@ -138,6 +210,9 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             } => {
                 self.catalog_rpc.signature_help(request_id, &path, position);
             }
+            CancelInlineCompletion { request_id } => {
+                self.catalog_rpc.cancel_inline_completion(request_id);
+            }
             Shutdown {} => {
                 self.catalog_rpc.shutdown();
                 for (_, sender) in self.terminals.iter() {
@@ -160,7 +235,16 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             UpdatePluginConfigs { configs } => {
                 let _ = self.catalog_rpc.update_plugin_configs(configs);
             }
-            NewTerminal { term_id, profile } => {
+            NewTerminal {
+                term_id,
+                mut profile,
+            } => {
+                // The profile's own variables, such as those of a run config,
+                // win over the workspace's
+                let mut environment =
+                    crate::env::workspace_env(self.workspace.as_deref());
+                environment.extend(profile.environment.take().unwrap_or_default());
+                profile.environment = Some(environment);
                 let mut terminal = match Terminal::new(term_id, profile, 50, 10) {
                     Ok(terminal) => terminal,
                     Err(e) => {
@@ -245,6 +329,9 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             DapStepOut { dap_id, thread_id } => {
                 let _ = self.catalog_rpc.dap_step_out(dap_id, thread_id);
             }
+            DapRestartFrame { dap_id, frame_id } => {
+                let _ = self.catalog_rpc.dap_restart_frame(dap_id, frame_id);
+            }
             DapStop { dap_id } => {
                 let _ = self.catalog_rpc.dap_stop(dap_id);
             }
@@ -325,6 +412,13 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                     }
                 }
             }
+            GitStageContent { path, content } => {
+                if let Some(workspace) = self.workspace.as_ref() {
+                    if let Err(e) = git_stage_content(workspace, &path, &content) {
+                        eprintln!("{e:?}");
+                    }
+                }
+            }
             GitInit {} => {
                 if let Some(workspace) = self.workspace.as_ref() {
                     match git_init(workspace) {
@@ -337,24 +431,80 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
     }
 
     fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
+        self.add_read_buffers();
         use ProxyRequest::*;
         match rpc {
-            NewBuffer { buffer_id, path } => {
-                let buffer = Buffer::new(buffer_id, path.clone());
-                let content = buffer.rope.to_string();
-                let read_only = buffer.read_only;
-                self.catalog_rpc.did_open_document(
-                    &path,
-                    buffer.language_id.to_string(),
-                    buffer.rev as i32,
-                    content.clone(),
-                );
-                self.file_watcher.watch(&path, false, OPEN_FILE_EVENT_TOKEN);
-                self.buffers.insert(path, buffer);
-                self.respond_rpc(
-                    id,
-                    Ok(ProxyResponse::NewBufferResponse { content, read_only }),
-                );
+            NewBuffer {
+                buffer_id,
+                path,
+                max_size,
+            } => {
+                if let Some(max_size) = max_size {
+                    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    if size > max_size {
+                        self.respond_rpc(
+                            id,
+                            Err(RpcError {
+                                code: FILE_TOO_LARGE,
+                                message: format!(
+                                    "{} is too large to open",
+                                    path.display()
+                                ),
+                            }),
+                        );
+                        return;
+                    }
+                }
+                // Read in the background, so that the other requests aren't
+                // held up by a large or slow file. The buffer is handed back
+                // to the dispatcher before the request is answered, so it's
+                // there for anything the core sends about it afterwards.
+                let cancelled = Arc::new(AtomicBool::new(false));
+                self.loading_buffers.insert(buffer_id, cancelled.clone());
+                let core_rpc = self.core_rpc.clone();
+                let proxy_rpc = self.proxy_rpc.clone();
+                let read_buffer_tx = self.read_buffer_tx.clone();
+                thread::spawn(move || {
+                    let result =
+                        read_path_chunked(&path, READ_CHUNK_LEN, |loaded, total| {
+                            if loaded < total {
+                                core_rpc
+                                    .buffer_load_progress(buffer_id, loaded, total);
+                            }
+                            !cancelled.load(Ordering::Relaxed)
+                        });
+                    let (content, read_only) = match result {
+                        Ok(Some(content)) if !cancelled.load(Ordering::Relaxed) => {
+                            (content, false)
+                        }
+                        Ok(_) => {
+                            proxy_rpc.handle_response(
+                                id,
+                                Err(RpcError {
+                                    code: 0,
+                                    message: format!(
+                                        "opening {} was cancelled",
+                                        path.display()
+                                    ),
+                                }),
+                            );
+                            return;
+                        }
+                        Err(e) => buffer_content(Err(e)),
+                    };
+                    let buffer = Buffer::with_content(
+                        buffer_id,
+                        path.clone(),
+                        content,
+                        read_only,
+                    );
+                    let content = buffer.rope.to_string();
+                    let _ = read_buffer_tx.send(buffer);
+                    proxy_rpc.handle_response(
+                        id,
+                        Ok(ProxyResponse::NewBufferResponse { content, read_only }),
+                    );
+                });
             }
             BufferHead { path } => {
                 let result = if let Some(workspace) = self.workspace.as_ref() {
@@ -378,6 +528,26 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 };
                 self.respond_rpc(id, result);
             }
+            BufferVersion { path, version } => {
+                let result = if let Some(workspace) = self.workspace.as_ref() {
+                    match file_get_version(workspace, &path, &version) {
+                        Ok(content) => Ok(ProxyResponse::BufferHeadResponse {
+                            version,
+                            content,
+                        }),
+                        Err(e) => Err(RpcError {
+                            code: 0,
+                            message: format!("can't get file version: {e}"),
+                        }),
+                    }
+                } else {
+                    Err(RpcError {
+                        code: 0,
+                        message: "no workspace set".to_string(),
+                    })
+                };
+                self.respond_rpc(id, result);
+            }
             GlobalSearch {
                 pattern,
                 case_sensitive,
@@ -444,7 +614,7 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 position,
             } => {
                 let proxy_rpc = self.proxy_rpc.clone();
-                self.catalog_rpc.hover(&path, position, move |_, result| {
+                self.catalog_rpc.hover(&path, position, move |result| {
                     let result = result.map(|hover| ProxyResponse::HoverResponse {
                         request_id,
                         hover,
@@ -477,16 +647,98 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     }
                 }
             }
+            GitFileHistory { path, lines } => {
+                let Some(workspace) = self.workspace.clone() else {
+                    self.respond_rpc(
+                        id,
+                        Err(RpcError {
+                            code: 0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    );
+                    return;
+                };
+                let proxy_rpc = self.proxy_rpc.clone();
+                // Walking the history diffs every commit, so keep it off the
+                // dispatcher
+                thread::spawn(move || {
+                    let result = git_file_history(&workspace, &path, lines)
+                        .map(|commits| ProxyResponse::GitFileHistory { commits })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: format!("can't get file history: {e}"),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            ReadFileChunk { path, offset, len } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = large_file::read_chunk(&path, offset, len)
+                        .map(|chunk| ProxyResponse::ReadFileChunkResponse { chunk })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: format!("can't read file: {e}"),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            SearchLargeFile {
+                path,
+                pattern,
+                case_sensitive,
+                is_regex,
+                max_results,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = large_file::search(
+                        &path,
+                        &pattern,
+                        case_sensitive,
+                        is_regex,
+                        max_results,
+                    )
+                    .map(|(matches, complete)| {
+                        ProxyResponse::SearchLargeFileResponse { matches, complete }
+                    })
+                    .map_err(|e| RpcError {
+                        code: 0,
+                        message: format!("can't search file: {e}"),
+                    });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            GitBlame { path } => {
+                let Some(workspace) = self.workspace.clone() else {
+                    self.respond_rpc(
+                        id,
+                        Err(RpcError {
+                            code: 0,
+                            message: "no workspace set".to_string(),
+                        }),
+                    );
+                    return;
+                };
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = git_blame(&workspace, &path)
+                        .map(|blame| ProxyResponse::GitBlame { blame })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: format!("can't blame file: {e}"),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
             GetDefinition {
                 request_id,
                 path,
                 position,
             } => {
                 let proxy_rpc = self.proxy_rpc.clone();
-                self.catalog_rpc.get_definition(
-                    &path,
-                    position,
-                    move |_, result| {
+                self.catalog_rpc
+                    .get_definition(&path, position, move |result| {
                         let result = result.map(|definition| {
                             ProxyResponse::GetDefinitionResponse {
                                 request_id,
@@ -494,8 +746,7 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                             }
                         });
                         proxy_rpc.handle_response(id, result);
-                    },
-                );
+                    });
             }
             GetTypeDefinition {
                 request_id,
@@ -535,7 +786,32 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 path,
                 position,
                 trigger_kind,
+                request_id,
+                disabled_providers,
             } => {
+                if let Some(buffer) = self.buffers.get(&path) {
+                    let (prefix, suffix) =
+                        buffer.inline_completion_context(position);
+                    let params = ProvideInlineCompletionParams {
+                        request_id,
+                        text_document_position: TextDocumentPositionParams {
+                            text_document: TextDocumentIdentifier {
+                                uri: Url::from_file_path(&path).unwrap(),
+                            },
+                            position,
+                        },
+                        language_id: buffer.language_id.to_string(),
+                        trigger_kind,
+                        prefix,
+                        suffix,
+                    };
+                    self.catalog_rpc.provide_inline_completion(
+                        &path,
+                        params,
+                        disabled_providers,
+                    );
+                }
+
                 let proxy_rpc = self.proxy_rpc.clone();
                 self.catalog_rpc.get_inline_completions(
                     &path,
@@ -578,23 +854,67 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         }
                     };
 
-                let proxy_rpc = self.proxy_rpc.clone();
-                self.catalog_rpc.get_semantic_tokens(
-                    &path,
-                    move |plugin_id, result| match result {
-                        Ok(result) => {
-                            catalog_rpc.format_semantic_tokens(
-                                plugin_id,
-                                result,
-                                text,
-                                Box::new(handle_tokens),
-                            );
-                        }
-                        Err(e) => {
-                            proxy_rpc.handle_response(id, Err(e));
+                // Keep the tokens to ask for the next ones as a delta, and
+                // turn them into styles
+                let format = {
+                    let semantic_tokens = self.semantic_tokens.clone();
+                    let path = path.clone();
+                    move |plugin_id: PluginId, tokens: SemanticTokens| {
+                        if tokens.result_id.is_some() {
+                            semantic_tokens
+                                .lock()
+                                .insert(path, (plugin_id, tokens.clone()));
+                        } else {
+                            semantic_tokens.lock().remove(&path);
                         }
+                        catalog_rpc.format_semantic_tokens(
+                            plugin_id,
+                            tokens,
+                            text,
+                            Box::new(handle_tokens),
+                        );
+                    }
+                };
+
+                let previous = self.semantic_tokens.lock().get(&path).and_then(
+                    |(plugin_id, tokens)| {
+                        let result_id = tokens.result_id.clone()?;
+                        Some((*plugin_id, result_id, tokens.data.clone()))
                     },
                 );
+                match previous {
+                    Some((plugin_id, result_id, data)) => {
+                        let catalog_rpc = self.catalog_rpc.clone();
+                        let proxy_rpc = self.proxy_rpc.clone();
+                        let delta_path = path.clone();
+                        self.catalog_rpc.get_semantic_tokens_delta(
+                            plugin_id,
+                            &delta_path,
+                            result_id,
+                            move |result| {
+                                let tokens =
+                                    semantic_tokens_from_delta(&data, result);
+                                match tokens {
+                                    Some(tokens) => format(plugin_id, tokens),
+                                    None => get_full_semantic_tokens(
+                                        &catalog_rpc,
+                                        proxy_rpc,
+                                        id,
+                                        &path,
+                                        format,
+                                    ),
+                                }
+                            },
+                        );
+                    }
+                    None => get_full_semantic_tokens(
+                        &self.catalog_rpc,
+                        self.proxy_rpc.clone(),
+                        id,
+                        &path,
+                        format,
+                    ),
+                }
             }
             GetCodeActions {
                 path,
@@ -987,6 +1307,32 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     },
                 );
             }
+            GetLinkedEditingRange { path, position } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_linked_editing_range(
+                    path.as_path(),
+                    position,
+                    move |_, result| {
+                        let result = result.map(|ranges| {
+                            ProxyResponse::GetLinkedEditingRange { ranges }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            GetDocumentHighlight { path, position } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_document_highlight(
+                    path.as_path(),
+                    position,
+                    move |_, result| {
+                        let result = result.map(|highlights| {
+                            ProxyResponse::GetDocumentHighlight { highlights }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
             CodeActionResolve {
                 action_item,
                 plugin_id,
@@ -1029,6 +1375,124 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         );
                     });
             }
+            DapEvaluate {
+                dap_id,
+                frame_id,
+                expression,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_evaluate(
+                    dap_id,
+                    frame_id,
+                    expression,
+                    move |result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|result| {
+                                ProxyResponse::DapEvaluateResponse { result }
+                            }),
+                        );
+                    },
+                );
+            }
+            DapCompletions {
+                dap_id,
+                frame_id,
+                text,
+                column,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_completions(
+                    dap_id,
+                    frame_id,
+                    text,
+                    column,
+                    move |result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|targets| {
+                                ProxyResponse::DapCompletionsResponse { targets }
+                            }),
+                        );
+                    },
+                );
+            }
+            DapSetVariable {
+                dap_id,
+                reference,
+                name,
+                value,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_set_variable(
+                    dap_id,
+                    reference,
+                    name,
+                    value,
+                    move |result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|resp| {
+                                ProxyResponse::DapSetVariableResponse { resp }
+                            }),
+                        );
+                    },
+                );
+            }
+            DapDataBreakpointInfo {
+                dap_id,
+                reference,
+                name,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_data_breakpoint_info(
+                    dap_id,
+                    reference,
+                    name,
+                    move |result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|info| {
+                                ProxyResponse::DapDataBreakpointInfoResponse { info }
+                            }),
+                        );
+                    },
+                );
+            }
+            DapSetDataBreakpoints {
+                dap_id,
+                breakpoints,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_set_data_breakpoints(
+                    dap_id,
+                    breakpoints,
+                    move |result| {
+                        proxy_rpc.handle_response(
+                            id,
+                            result.map(|breakpoints| {
+                                ProxyResponse::DapSetDataBreakpointsResponse {
+                                    breakpoints,
+                                }
+                            }),
+                        );
+                    },
+                );
+            }
+            ListProcesses {} => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = list_processes()
+                        .map(|processes| ProxyResponse::ListProcessesResponse {
+                            processes,
+                        })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: e.to_string(),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
         }
     }
 }
@@ -1039,6 +1503,7 @@ pub fn new(core_rpc: CoreRpcHandler, proxy_rpc: ProxyRpcHandler) -> Self {
             PluginCatalogRpcHandler::new(core_rpc.clone(), proxy_rpc.clone());
 
         let file_watcher = FileWatcher::new();
+        let (read_buffer_tx, read_buffer_rx) = crossbeam_channel::unbounded();
 
         Self {
             workspace: None,
@@ -1046,13 +1511,59 @@ pub fn new(core_rpc: CoreRpcHandler, proxy_rpc: ProxyRpcHandler) -> Self {
             core_rpc,
             catalog_rpc: plugin_rpc,
             buffers: HashMap::new(),
+            loading_buffers: HashMap::new(),
+            read_buffer_tx,
+            read_buffer_rx,
             terminals: HashMap::new(),
             file_watcher,
+            coverage: None,
+            semantic_tokens: Arc::new(Mutex::new(HashMap::new())),
             window_id: 1,
             tab_id: 1,
         }
     }
 
+    /// Add the buffers of the files read in the background, which the core
+    /// may be about to send edits or requests for. A buffer whose loading
+    /// was cancelled in the meantime is dropped, so it's never opened.
+    fn add_read_buffers(&mut self) {
+        while let Ok(buffer) = self.read_buffer_rx.try_recv() {
+            if self.loading_buffers.remove(&buffer.id).is_none() {
+                continue;
+            }
+            self.catalog_rpc.did_open_document(
+                &buffer.path,
+                buffer.language_id.to_string(),
+                buffer.rev as i32,
+                buffer.rope.to_string(),
+            );
+            self.semantic_tokens.lock().remove(&buffer.path);
+            self.file_watcher
+                .watch(&buffer.path, false, OPEN_FILE_EVENT_TOKEN);
+            self.buffers.insert(buffer.path.clone(), buffer);
+        }
+    }
+
+    /// Send the coverage of the files in the coverage report, which is empty
+    /// when there's no report
+    fn load_coverage(&self) {
+        let path = self.coverage.as_ref().map(|(_, path)| path.clone());
+        let workspace = self.workspace.clone();
+        let core_rpc = self.core_rpc.clone();
+        thread::spawn(move || {
+            let coverage = path
+                .and_then(|path| {
+                    let content = fs::read_to_string(&path).ok()?;
+                    let base = workspace
+                        .or_else(|| path.parent().map(Path::to_path_buf))
+                        .unwrap_or_default();
+                    Some(coverage::parse_coverage(&content, &base))
+                })
+                .unwrap_or_default();
+            core_rpc.coverage_changed(coverage);
+        });
+    }
+
     fn respond_rpc(&self, id: RequestId, result: Result<ProxyResponse, RpcError>) {
         self.proxy_rpc.handle_response(id, result);
     }
@@ -1105,6 +1616,12 @@ fn handle_fs_events(&self, events: Vec<(WatchToken, notify::Event)>) {
             match token {
                 OPEN_FILE_EVENT_TOKEN => self.handle_open_file_fs_event(event),
                 WORKSPACE_EVENT_TOKEN => self.handle_workspace_fs_event(event),
+                COVERAGE_EVENT_TOKEN => {
+                    if event.kind.is_create() || event.kind.is_modify() {
+                        self.proxy_rpc
+                            .notification(ProxyNotification::CoverageFileChanged {});
+                    }
+                }
                 _ => {}
             }
         }
@@ -1184,6 +1701,75 @@ pub struct DiffHunk {
     pub header: String,
 }
 
+/// Ask the plugins for all the semantic tokens of a file, responding with
+/// the error of the request if none of them has them
+fn get_full_semantic_tokens(
+    catalog_rpc: &PluginCatalogRpcHandler,
+    proxy_rpc: ProxyRpcHandler,
+    id: RequestId,
+    path: &Path,
+    format: impl FnOnce(PluginId, SemanticTokens) + Clone + Send + 'static,
+) {
+    catalog_rpc.get_semantic_tokens(path, move |plugin_id, result| match result {
+        Ok(tokens) => format(plugin_id, tokens),
+        Err(e) => {
+            proxy_rpc.handle_response(id, Err(e));
+        }
+    });
+}
+
+/// The tokens answering a delta request, either all of them when the server
+/// sent them instead, or the `previous` ones with the edits applied. `None`
+/// when the server rejected the delta or it doesn't fit the tokens, for all
+/// of them to be asked for again.
+fn semantic_tokens_from_delta(
+    previous: &[SemanticToken],
+    result: Result<SemanticTokensFullDeltaResult, RpcError>,
+) -> Option<SemanticTokens> {
+    match result {
+        Ok(SemanticTokensFullDeltaResult::Tokens(tokens)) => Some(tokens),
+        Ok(SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+            let data = apply_semantic_token_edits(previous, &delta.edits)?;
+            Some(SemanticTokens {
+                result_id: delta.result_id,
+                data,
+            })
+        }
+        Ok(SemanticTokensFullDeltaResult::PartialTokensDelta { .. }) | Err(_) => {
+            None
+        }
+    }
+}
+
+/// Apply the edits of a semantic tokens delta to the tokens it was computed
+/// from. The edits count in integers, five for a token, and are all relative
+/// to the tokens before any of them. `None` when they don't fit the tokens.
+fn apply_semantic_token_edits(
+    tokens: &[SemanticToken],
+    edits: &[SemanticTokensEdit],
+) -> Option<Vec<SemanticToken>> {
+    let mut edits: Vec<&SemanticTokensEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.start);
+
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut next = 0;
+    for edit in edits {
+        if edit.start % 5 != 0 || edit.delete_count % 5 != 0 {
+            return None;
+        }
+        let start = edit.start as usize / 5;
+        let end = start + edit.delete_count as usize / 5;
+        if start < next || end > tokens.len() {
+            return None;
+        }
+        result.extend_from_slice(&tokens[next..start]);
+        result.extend(edit.data.iter().flatten().copied());
+        next = end;
+    }
+    result.extend_from_slice(&tokens[next..]);
+    Some(result)
+}
+
 fn git_init(workspace_path: &Path) -> Result<()> {
     if Repository::discover(workspace_path).is_err() {
         Repository::init(workspace_path)?;
@@ -1284,6 +1870,36 @@ fn git_discard_files_changes<'a>(
     Ok(())
 }
 
+fn git_stage_content(
+    workspace_path: &Path,
+    path: &Path,
+    content: &str,
+) -> Result<()> {
+    let repo = Repository::discover(workspace_path)?;
+    let mut index = repo.index()?;
+    let relative = path.strip_prefix(workspace_path)?;
+    let entry = match index.get_path(relative, 0) {
+        Some(entry) => entry,
+        None => IndexEntry {
+            ctime: IndexTime::new(0, 0),
+            mtime: IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: 0o100644,
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: Oid::zero(),
+            flags: 0,
+            flags_extended: 0,
+            path: relative.to_string_lossy().as_bytes().to_vec(),
+        },
+    };
+    index.add_frombuffer(&entry, content.as_bytes())?;
+    index.write()?;
+    Ok(())
+}
+
 fn git_discard_workspace_changes(workspace_path: &Path) -> Result<()> {
     let repo = Repository::discover(workspace_path)?;
     let mut checkout_b = CheckoutBuilder::new();
@@ -1433,6 +2049,224 @@ fn file_get_head(workspace_path: &Path, path: &Path) -> Result<(String, String)>
     Ok((id, content))
 }
 
+/// The content of the file at `version`, a revision such as a commit id or
+/// `<id>^`. A file that doesn't exist at that revision is empty.
+fn file_get_version(
+    workspace_path: &Path,
+    path: &Path,
+    version: &str,
+) -> Result<String> {
+    let repo = Repository::discover(workspace_path)?;
+    let tree = repo.revparse_single(version)?.peel_to_tree()?;
+    let tree_entry = match tree.get_path(path.strip_prefix(workspace_path)?) {
+        Ok(tree_entry) => tree_entry,
+        Err(e) if e.code() == NotFound => return Ok(String::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let blob = repo.find_blob(tree_entry.id())?;
+    let content = std::str::from_utf8(blob.content())
+        .with_context(|| "content bytes to string")?
+        .to_string();
+    Ok(content)
+}
+
+/// The most commits listed in a file's history
+const FILE_HISTORY_LIMIT: usize = 500;
+
+/// The commits reachable from `HEAD` through first parents that changed the
+/// file, newest first. With `lines`, only the commits that changed those
+/// lines, which are followed back through each commit as they move. Renames
+/// aren't followed.
+fn git_file_history(
+    workspace_path: &Path,
+    path: &Path,
+    mut lines: Option<(usize, usize)>,
+) -> Result<Vec<GitCommitInfo>> {
+    let repo = Repository::discover(workspace_path)?;
+    let relative_path = path.strip_prefix(workspace_path)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.simplify_first_parent()?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        if commits.len() >= FILE_HISTORY_LIMIT {
+            break;
+        }
+        let commit = repo.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+
+        let mut opts = DiffOptions::new();
+        opts.pathspec(relative_path)
+            .disable_pathspec_match(true)
+            .context_lines(0);
+        let diff = repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&tree),
+            Some(&mut opts),
+        )?;
+        if diff.deltas().len() == 0 {
+            continue;
+        }
+
+        // Whether this commit added all of the lines, so that there is nothing
+        // left to follow
+        let mut added = false;
+        if let Some(range) = lines.as_mut() {
+            let mut hunks = Vec::new();
+            diff.foreach(
+                &mut |_, _| true,
+                None,
+                Some(&mut |_, hunk| {
+                    hunks.push(LineHunk::new(
+                        hunk.old_start() as usize,
+                        hunk.old_lines() as usize,
+                        hunk.new_start() as usize,
+                        hunk.new_lines() as usize,
+                    ));
+                    true
+                }),
+                None,
+            )?;
+            if !hunks_touch_lines(&hunks, *range) {
+                continue;
+            }
+            match lines_in_parent(&hunks, *range) {
+                Some(parent_range) => *range = parent_range,
+                None => added = true,
+            }
+        }
+
+        commits.push(git_commit_info(&commit));
+        if added {
+            break;
+        }
+    }
+    Ok(commits)
+}
+
+/// The commit that last changed each line of the file at `HEAD`.
+fn git_blame(workspace_path: &Path, path: &Path) -> Result<BlameInfo> {
+    let repo = Repository::discover(workspace_path)?;
+    let blame = repo.blame_file(path.strip_prefix(workspace_path)?, None)?;
+
+    let mut commit_indices: HashMap<Oid, usize> = HashMap::new();
+    let mut result = BlameInfo::default();
+    for hunk in blame.iter() {
+        let oid = hunk.final_commit_id();
+        let index = match commit_indices.get(&oid) {
+            Some(index) => *index,
+            None => {
+                let commit = repo.find_commit(oid)?;
+                let index = result.commits.len();
+                result.commits.push(git_commit_info(&commit));
+                commit_indices.insert(oid, index);
+                index
+            }
+        };
+        let start = hunk.final_start_line().saturating_sub(1);
+        let end = start + hunk.lines_in_hunk();
+        if result.lines.len() < end {
+            result.lines.resize(end, index);
+        }
+        result.lines[start..end].fill(index);
+    }
+    Ok(result)
+}
+
+fn git_commit_info(commit: &git2::Commit) -> GitCommitInfo {
+    GitCommitInfo {
+        id: commit.id().to_string(),
+        summary: commit.summary().unwrap_or("").to_string(),
+        author: commit.author().name().unwrap_or("").to_string(),
+        time: commit.time().seconds(),
+    }
+}
+
+/// A hunk of a diff with 0-based line numbers. A side without lines starts
+/// where they would be inserted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LineHunk {
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+}
+
+impl LineHunk {
+    /// From git's 1-based hunk header, in which a side without lines starts at
+    /// the line before them
+    fn new(
+        old_start: usize,
+        old_lines: usize,
+        new_start: usize,
+        new_lines: usize,
+    ) -> Self {
+        let start = |start: usize, lines: usize| {
+            if lines == 0 {
+                start
+            } else {
+                start.saturating_sub(1)
+            }
+        };
+        Self {
+            old_start: start(old_start, old_lines),
+            old_lines,
+            new_start: start(new_start, new_lines),
+            new_lines,
+        }
+    }
+}
+
+/// Whether the hunks change any of the lines, or remove lines from between
+/// them.
+fn hunks_touch_lines(hunks: &[LineHunk], (start, end): (usize, usize)) -> bool {
+    hunks.iter().any(|hunk| {
+        if hunk.new_lines == 0 {
+            hunk.new_start > start && hunk.new_start <= end
+        } else {
+            hunk.new_start <= end && hunk.new_start + hunk.new_lines > start
+        }
+    })
+}
+
+/// Where the lines were before the hunks changed them, or `None` when the
+/// hunks added all of them.
+fn lines_in_parent(
+    hunks: &[LineHunk],
+    (start, end): (usize, usize),
+) -> Option<(usize, usize)> {
+    let start = old_line(hunks, start, false)?;
+    let end = old_line(hunks, end, true)?;
+    (end >= start).then_some((start, end))
+}
+
+/// The line before the hunks that `line` comes from. A line inside a hunk
+/// maps to the first of the hunk's old lines, or with `last` to the last of
+/// them, which is `None` before the start of the file.
+fn old_line(hunks: &[LineHunk], line: usize, last: bool) -> Option<usize> {
+    let mut shift = 0isize;
+    for hunk in hunks {
+        if hunk.new_start + hunk.new_lines <= line {
+            shift += hunk.old_lines as isize - hunk.new_lines as isize;
+        } else if hunk.new_start <= line {
+            return if last {
+                (hunk.old_start + hunk.old_lines).checked_sub(1)
+            } else {
+                Some(hunk.old_start)
+            };
+        } else {
+            break;
+        }
+    }
+    usize::try_from(line as isize + shift).ok()
+}
+
 fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String> {
     let repo = Repository::discover(workspace_path)?;
     let head = repo.head()?;
@@ -1559,3 +2393,221 @@ fn search_in_path(
 
     Ok(ProxyResponse::GlobalSearchResponse { matches })
 }
+
+#[cfg(not(windows))]
+fn list_processes() -> Result<Vec<ProcessInfo>> {
+    let output = std::process::Command::new("ps")
+        .args(["-eo", "pid=,args="])
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ps failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(windows)]
+fn list_processes() -> Result<Vec<ProcessInfo>> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = std::process::Command::new("tasklist")
+        .args(["/fo", "csv", "/nh"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "tasklist failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(parse_tasklist(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the lines of `ps -eo pid=,args=`, naming each process after the
+/// file name of its executable.
+#[cfg_attr(windows, allow(dead_code))]
+fn parse_ps(output: &str) -> Vec<ProcessInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let (pid, rest) = line.split_once(char::is_whitespace)?;
+            let pid = pid.parse().ok()?;
+            let cmdline = rest.trim();
+            let program = cmdline.split_whitespace().next().unwrap_or(cmdline);
+            let name = program.rsplit('/').next().unwrap_or(program);
+            Some(ProcessInfo {
+                pid,
+                name: name.to_string(),
+                cmdline: cmdline.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the lines of `tasklist /fo csv /nh`, which are quoted fields with
+/// the image name first and the pid second.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_tasklist(output: &str) -> Vec<ProcessInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line
+                .trim()
+                .strip_prefix('"')?
+                .strip_suffix('"')?
+                .split("\",\"");
+            let name = fields.next()?;
+            let pid = fields.next()?.parse().ok()?;
+            Some(ProcessInfo {
+                pid,
+                name: name.to_string(),
+                cmdline: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::dap_types::ProcessInfo;
+    use lsp_types::{SemanticToken, SemanticTokensEdit};
+
+    use super::{
+        apply_semantic_token_edits, hunks_touch_lines, lines_in_parent, parse_ps,
+        parse_tasklist, LineHunk,
+    };
+
+    #[test]
+    fn test_line_history_hunks() {
+        // Line 1 replaced by two lines, and line 4 removed:
+        // a b c d e -> a X Y c d
+        let hunks = [LineHunk::new(2, 1, 2, 2), LineHunk::new(5, 1, 5, 0)];
+        assert_eq!(hunks[0].old_start, 1);
+        assert_eq!(hunks[1].new_start, 5);
+
+        assert!(hunks_touch_lines(&hunks, (2, 2)));
+        assert!(!hunks_touch_lines(&hunks, (3, 4)));
+        // The removed line was after the end of the lines
+        assert!(!hunks_touch_lines(&hunks, (4, 4)));
+        assert!(hunks_touch_lines(&hunks, (4, 5)));
+
+        assert_eq!(lines_in_parent(&hunks, (3, 4)), Some((2, 3)));
+        assert_eq!(lines_in_parent(&hunks, (1, 3)), Some((1, 2)));
+        assert_eq!(lines_in_parent(&hunks, (0, 0)), Some((0, 0)));
+
+        // Lines 1 and 2 added: a d -> a b c d
+        let hunks = [LineHunk::new(1, 0, 2, 2)];
+        assert_eq!(hunks[0].old_start, 1);
+        assert_eq!(lines_in_parent(&hunks, (1, 2)), None);
+        assert_eq!(lines_in_parent(&hunks, (1, 3)), Some((1, 1)));
+        assert_eq!(lines_in_parent(&hunks, (3, 3)), Some((1, 1)));
+
+        // The file was added
+        let hunks = [LineHunk::new(0, 0, 1, 3)];
+        assert_eq!(lines_in_parent(&hunks, (0, 2)), None);
+    }
+
+    #[test]
+    fn test_parse_processes() {
+        let output = "    1 /sbin/init splash\n\
+                      4242 python3 -m http.server  \n\
+                      77 [kthreadd]\n\
+                      not a process\n";
+        assert_eq!(
+            parse_ps(output),
+            vec![
+                ProcessInfo {
+                    pid: 1,
+                    name: "init".to_string(),
+                    cmdline: "/sbin/init splash".to_string(),
+                },
+                ProcessInfo {
+                    pid: 4242,
+                    name: "python3".to_string(),
+                    cmdline: "python3 -m http.server".to_string(),
+                },
+                ProcessInfo {
+                    pid: 77,
+                    name: "[kthreadd]".to_string(),
+                    cmdline: "[kthreadd]".to_string(),
+                },
+            ]
+        );
+
+        let output = "\"System Idle Process\",\"0\",\"Services\",\"0\",\"8 K\"\r\n\
+                      \"app.exe\",\"1234\",\"Console\",\"1\",\"10,240 K\"\r\n";
+        assert_eq!(
+            parse_tasklist(output),
+            vec![
+                ProcessInfo {
+                    pid: 0,
+                    name: "System Idle Process".to_string(),
+                    cmdline: "System Idle Process".to_string(),
+                },
+                ProcessInfo {
+                    pid: 1234,
+                    name: "app.exe".to_string(),
+                    cmdline: "app.exe".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_semantic_token_edits() {
+        let token = |delta_line| SemanticToken {
+            delta_line,
+            delta_start: 0,
+            length: 1,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        };
+        let tokens: Vec<SemanticToken> = (0..4).map(token).collect();
+        let lines = |tokens: Vec<SemanticToken>| {
+            tokens.iter().map(|t| t.delta_line).collect::<Vec<_>>()
+        };
+
+        // Replace the second token with two, and remove the last one, the
+        // edits given out of order
+        let edits = [
+            SemanticTokensEdit {
+                start: 15,
+                delete_count: 5,
+                data: None,
+            },
+            SemanticTokensEdit {
+                start: 5,
+                delete_count: 5,
+                data: Some(vec![token(10), token(11)]),
+            },
+        ];
+        let result = apply_semantic_token_edits(&tokens, &edits).unwrap();
+        assert_eq!(lines(result), vec![0, 10, 11, 2]);
+
+        let insert_at_end = [SemanticTokensEdit {
+            start: 20,
+            delete_count: 0,
+            data: Some(vec![token(9)]),
+        }];
+        let result = apply_semantic_token_edits(&tokens, &insert_at_end).unwrap();
+        assert_eq!(lines(result), vec![0, 1, 2, 3, 9]);
+
+        // Edits that don't fit the tokens
+        let misaligned = [SemanticTokensEdit {
+            start: 3,
+            delete_count: 0,
+            data: None,
+        }];
+        assert!(apply_semantic_token_edits(&tokens, &misaligned).is_none());
+        let past_the_end = [SemanticTokensEdit {
+            start: 15,
+            delete_count: 10,
+            data: None,
+        }];
+        assert!(apply_semantic_token_edits(&tokens, &past_the_end).is_none());
+    }
+}
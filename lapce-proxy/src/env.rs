@@ -0,0 +1,132 @@
+//! The environment of the processes the proxy starts: terminals, tasks and
+//! language servers. A process that wasn't started from a shell, such as
+//! Lapce opened from the desktop or the proxy over SSH, lacks what the
+//! user's shell profile sets up, `PATH` most of all, so that is read from a
+//! login shell once. A workspace can override it with `.lapce/env.toml`.
+
+use std::{
+    collections::HashMap, io::IsTerminal, path::Path, process::Command,
+    sync::OnceLock,
+};
+
+use anyhow::Result;
+use tracing::error;
+
+/// The file of a workspace with its environment, a table of variables such
+/// as `PATH = "${PATH}:/opt/tools/bin"`
+pub const WORKSPACE_ENV_FILE: &str = ".lapce/env.toml";
+
+static SHELL_ENV: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// The environment of the user's login shell, read the first time it's
+/// needed. It's empty when the process was started from a terminal, which
+/// means it already has it.
+pub fn shell_env() -> &'static HashMap<String, String> {
+    SHELL_ENV.get_or_init(|| {
+        if std::io::stdin().is_terminal() {
+            return HashMap::new();
+        }
+        capture_shell_env().unwrap_or_else(|e| {
+            error!("Failed to obtain shell environment: {e}");
+            HashMap::new()
+        })
+    })
+}
+
+/// The variables to set on the processes started for `workspace`: those of
+/// the shell, with the workspace's own on top of them.
+pub fn workspace_env(workspace: Option<&Path>) -> HashMap<String, String> {
+    let mut env = shell_env().clone();
+    let Some(workspace) = workspace else {
+        return env;
+    };
+
+    let path = workspace.join(WORKSPACE_ENV_FILE);
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return env;
+    };
+    let overrides: HashMap<String, String> = match toml::from_str(&text) {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            error!("Invalid {}: {e}", path.display());
+            return env;
+        }
+    };
+    let mut overrides: Vec<_> = overrides.into_iter().collect();
+    overrides.sort();
+    for (key, value) in overrides {
+        let value = expand(&value, |name| {
+            env.get(name).cloned().or_else(|| std::env::var(name).ok())
+        });
+        env.insert(key, value);
+    }
+    env
+}
+
+/// Set the shell environment on the current process, for what it starts
+/// other than through [`workspace_env`].
+pub fn load_shell_env() {
+    for (key, value) in shell_env() {
+        if let Ok(v) = std::env::var(key) {
+            if &v != value {
+                tracing::warn!("Overwriting '{key}', previous value: '{v}', new value '{value}'");
+            }
+        };
+        std::env::set_var(key, value);
+    }
+}
+
+fn capture_shell_env() -> Result<HashMap<String, String>> {
+    #[cfg(not(windows))]
+    let shell = std::env::var("SHELL")?;
+
+    #[cfg(windows)]
+    let shell = "powershell";
+
+    let mut command = Command::new(shell);
+
+    #[cfg(not(windows))]
+    command.args(["--login", "-c", "printenv"]);
+
+    #[cfg(windows)]
+    command.args(["{ ls env: | foreach { '{0}={1}' -f $_.Name, $_.Value } }"]);
+
+    let output = command.output()?;
+    let env = String::from_utf8(output.stdout).unwrap_or_default();
+    Ok(env
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect())
+}
+
+/// Replace `${NAME}` with the value of the variable, or nothing if it isn't
+/// set.
+fn expand(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&lookup(&rest[start + 2..start + 2 + len]).unwrap_or_default());
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+
+    #[test]
+    fn test_expand() {
+        let lookup = |name: &str| (name == "PATH").then(|| "/usr/bin".to_string());
+        assert_eq!(expand("${PATH}:/opt/bin", lookup), "/usr/bin:/opt/bin");
+        assert_eq!(expand("a${UNSET}b", lookup), "ab");
+        assert_eq!(expand("no vars", lookup), "no vars");
+        assert_eq!(expand("${PATH", lookup), "${PATH");
+    }
+}
@@ -0,0 +1,120 @@
+//! Reading files too large to be opened a chunk at a time, for the read-only
+//! viewer of the editor.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::Result;
+use lapce_rpc::large_file::{FileChunk, FileMatch};
+use regex::RegexBuilder;
+
+/// How much of a matching line is sent
+const MAX_MATCH_LEN: usize = 200;
+
+/// The whole lines of the `len` bytes of the file at `path` from `offset`,
+/// the partial lines at the edges left out unless a line is longer than the
+/// whole chunk
+pub fn read_chunk(path: &Path, offset: u64, len: u64) -> Result<FileChunk> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let offset = offset.min(size);
+    let len = len.min(size - offset);
+    let mut bytes = vec![0; len as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)?;
+
+    let (start, end) = whole_lines(&bytes, offset > 0, offset + len < size);
+    Ok(FileChunk {
+        size,
+        start: offset + start as u64,
+        end: offset + end as u64,
+        text: String::from_utf8_lossy(&bytes[start..end]).into_owned(),
+    })
+}
+
+/// The range of `bytes` without the partial first line when it starts in the
+/// middle of the file and without the partial last line when it ends there
+fn whole_lines(
+    bytes: &[u8],
+    partial_start: bool,
+    partial_end: bool,
+) -> (usize, usize) {
+    let start = if partial_start {
+        bytes.iter().position(|b| *b == b'\n').map_or(0, |i| i + 1)
+    } else {
+        0
+    };
+    let end = if partial_end {
+        bytes[start..]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(bytes.len(), |i| start + i + 1)
+    } else {
+        bytes.len()
+    };
+    (start, end)
+}
+
+/// The lines of the file at `path` matching `pattern`, at most `max_results`
+/// of them, and whether the whole file was searched
+pub fn search(
+    path: &Path,
+    pattern: &str,
+    case_sensitive: bool,
+    is_regex: bool,
+    max_results: usize,
+) -> Result<(Vec<FileMatch>, bool)> {
+    let pattern = if is_regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive)
+        .build()?;
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut matches = Vec::new();
+    let mut bytes = Vec::new();
+    let mut offset = 0;
+    let mut line = 0;
+    loop {
+        bytes.clear();
+        let len = reader.read_until(b'\n', &mut bytes)?;
+        if len == 0 {
+            return Ok((matches, true));
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if regex.is_match(text) {
+            if matches.len() == max_results {
+                return Ok((matches, false));
+            }
+            matches.push(FileMatch {
+                line,
+                offset,
+                text: text.chars().take(MAX_MATCH_LEN).collect(),
+            });
+        }
+        offset += len as u64;
+        line += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::whole_lines;
+
+    #[test]
+    fn test_whole_lines() {
+        let bytes = b"ne one\nline two\nline th";
+        assert_eq!(whole_lines(bytes, true, true), (7, 16));
+        assert_eq!(whole_lines(bytes, false, true), (0, 16));
+        assert_eq!(whole_lines(bytes, true, false), (7, bytes.len()));
+        // A line longer than the chunk is kept as is
+        assert_eq!(whole_lines(b"a very long li", true, true), (0, 14));
+    }
+}
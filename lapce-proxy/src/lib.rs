@@ -2,7 +2,10 @@
 
 pub mod buffer;
 pub mod cli;
+pub mod coverage;
 pub mod dispatch;
+pub mod env;
+pub mod large_file;
 pub mod plugin;
 pub mod terminal;
 pub mod watcher;
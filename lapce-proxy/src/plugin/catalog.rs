@@ -11,6 +11,7 @@
 
 use lapce_rpc::{
     dap_types::{self, DapId, DapServer, SetBreakpointsResponse},
+    inline_completion::{ProvideInlineCompletion, ProvideInlineCompletionParams},
     plugin::{PluginId, VoltID, VoltInfo, VoltMetadata},
     proxy::ProxyResponse,
     style::LineStyle,
@@ -18,9 +19,10 @@
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
-    notification::DidOpenTextDocument, request::Request, DidOpenTextDocumentParams,
-    SemanticTokens, TextDocumentIdentifier, TextDocumentItem,
-    VersionedTextDocumentIdentifier,
+    notification::{DidCloseTextDocument, DidOpenTextDocument},
+    request::Request,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, SemanticTokens,
+    TextDocumentIdentifier, TextDocumentItem, VersionedTextDocumentIdentifier,
 };
 use parking_lot::Mutex;
 use psp_types::Notification;
@@ -304,6 +306,26 @@ pub fn handle_did_open_text_document(&mut self, document: TextDocumentItem) {
         }
     }
 
+    pub fn handle_did_close_text_document(
+        &mut self,
+        text_document: TextDocumentIdentifier,
+    ) {
+        let path = text_document.uri.to_file_path().ok();
+        let language_id =
+            path.as_ref().and_then(|path| self.open_files.remove(path));
+        for (_, plugin) in self.plugins.iter() {
+            plugin.server_notification(
+                DidCloseTextDocument::METHOD,
+                DidCloseTextDocumentParams {
+                    text_document: text_document.clone(),
+                },
+                language_id.clone(),
+                path.clone(),
+                true,
+            );
+        }
+    }
+
     pub fn handle_did_save_text_document(
         &mut self,
         language_id: String,
@@ -321,6 +343,26 @@ pub fn handle_did_save_text_document(
         }
     }
 
+    pub fn provide_inline_completion(
+        &self,
+        path: PathBuf,
+        params: ProvideInlineCompletionParams,
+        disabled_providers: &[String],
+    ) {
+        for (_, plugin) in self.plugins.iter() {
+            if disabled_providers.contains(&plugin.volt_id.name) {
+                continue;
+            }
+            plugin.server_notification(
+                ProvideInlineCompletion::METHOD,
+                params.clone(),
+                Some(params.language_id.clone()),
+                Some(path.clone()),
+                true,
+            );
+        }
+    }
+
     pub fn handle_did_change_text_document(
         &mut self,
         language_id: String,
@@ -457,6 +499,123 @@ pub fn dap_get_scopes(
         }
     }
 
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        expression: String,
+        f: Box<dyn RpcCallback<dap_types::EvaluateResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.evaluate_async(
+                frame_id,
+                expression,
+                |result: Result<dap_types::EvaluateResponse, RpcError>| {
+                    f.call(result)
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "plugin doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: Box<dyn RpcCallback<dap_types::SetVariableResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.set_variable_async(
+                reference,
+                name,
+                value,
+                |result: Result<dap_types::SetVariableResponse, RpcError>| {
+                    f.call(result)
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "plugin doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_data_breakpoint_info(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: Box<dyn RpcCallback<dap_types::DataBreakpointInfoResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.data_breakpoint_info_async(
+                reference,
+                name,
+                |result: Result<dap_types::DataBreakpointInfoResponse, RpcError>| {
+                    f.call(result)
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "plugin doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_set_data_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::DataBreakpoint>,
+        f: Box<dyn RpcCallback<Vec<dap_types::Breakpoint>, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.set_data_breakpoints_async(
+                breakpoints,
+                |result: Result<dap_types::SetDataBreakpointsResponse, RpcError>| {
+                    f.call(result.map(|resp| resp.breakpoints))
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "plugin doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_completions(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: Box<dyn RpcCallback<Vec<dap_types::CompletionItem>, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.completions_async(
+                frame_id,
+                text,
+                column,
+                |result: Result<dap_types::CompletionsResponse, RpcError>| {
+                    f.call(result.map(|resp| resp.targets))
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "plugin doesn't exist".to_string(),
+            }));
+        }
+    }
+
     pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
         use PluginCatalogNotification::*;
         match notification {
@@ -625,6 +784,11 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                     dap.step_out(thread_id);
                 }
             }
+            DapRestartFrame { dap_id, frame_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    dap.restart_frame(frame_id);
+                }
+            }
             DapStop { dap_id } => {
                 if let Some(dap) = self.daps.get(&dap_id) {
                     dap.stop();
@@ -14,16 +14,22 @@
 use crossbeam_channel::{Receiver, Sender};
 use lapce_rpc::{
     dap_types::{
-        self, ConfigurationDone, Continue, ContinueArguments, ContinueResponse,
-        DapEvent, DapId, DapPayload, DapRequest, DapResponse, DapServer,
-        DebuggerCapabilities, Disconnect, Initialize, Launch, Next, NextArguments,
-        Pause, PauseArguments, Request, RunDebugConfig, RunInTerminal,
-        RunInTerminalArguments, RunInTerminalResponse, Scope, Scopes,
-        ScopesArguments, ScopesResponse, SetBreakpoints, SetBreakpointsArguments,
-        SetBreakpointsResponse, Source, SourceBreakpoint, StackTrace,
-        StackTraceArguments, StackTraceResponse, StepIn, StepInArguments, StepOut,
-        StepOutArguments, Terminate, ThreadId, Threads, ThreadsResponse, Variable,
-        Variables, VariablesArguments, VariablesResponse,
+        self, Attach, Completions, CompletionsArguments, CompletionsResponse,
+        ConfigurationDone, Continue, ContinueArguments, ContinueResponse, DapEvent,
+        DapId, DapPayload, DapRequest, DapResponse, DapServer, DataBreakpoint,
+        DataBreakpointInfo, DataBreakpointInfoArguments, DataBreakpointInfoResponse,
+        DebuggerCapabilities, Disconnect, Evaluate, EvaluateArguments,
+        EvaluateResponse, Initialize, Launch, Next, NextArguments, Pause,
+        PauseArguments, Request, RestartFrame, RestartFrameArguments,
+        RunDebugConfig, RunInTerminal, RunInTerminalArguments,
+        RunInTerminalResponse, Scope, Scopes, ScopesArguments, ScopesResponse,
+        SetBreakpoints, SetBreakpointsArguments, SetBreakpointsResponse,
+        SetDataBreakpoints, SetDataBreakpointsArguments, SetDataBreakpointsResponse,
+        SetVariable, SetVariableArguments, SetVariableResponse, Source,
+        SourceBreakpoint, StackTrace, StackTraceArguments, StackTraceResponse,
+        StepIn, StepInArguments, StepOut, StepOutArguments, Terminate, ThreadId,
+        Threads, ThreadsResponse, Variable, Variables, VariablesArguments,
+        VariablesResponse,
     },
     terminal::TermId,
     RpcError,
@@ -162,6 +168,7 @@ fn process(
         }
 
         process.args(args);
+        process.envs(crate::env::shell_env());
 
         // CREATE_NO_WINDOW
         // (https://learn.microsoft.com/en-us/windows/win32/procthread/process-creation-flags)
@@ -291,7 +298,13 @@ fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
                 let _ = self.check_restart();
             }
             DapEvent::Thread { .. } => {}
-            DapEvent::Output(_) => {}
+            DapEvent::Output(output) => {
+                if output.category.as_deref() != Some("telemetry") {
+                    self.plugin_rpc
+                        .core_rpc
+                        .dap_output(self.config.dap_id, output.clone());
+                }
+            }
             DapEvent::Breakpoint { .. } => {}
             DapEvent::Module { .. } => {}
             DapEvent::LoadedSource { .. } => {}
@@ -327,19 +340,28 @@ pub(crate) fn initialize(&mut self) -> Result<()> {
             .dap_rpc
             .request::<Initialize>(params)
             .map_err(|e| anyhow!(e.message))?;
+        self.plugin_rpc
+            .core_rpc
+            .dap_capabilities(self.config.dap_id, resp.clone());
         self.capabilities = Some(resp);
 
         Ok(())
     }
 
+    /// Whether the session is stopped with a terminate request. A process
+    /// that was attached to is only disconnected from, to keep it running.
+    fn stops_with_terminate(&self) -> bool {
+        self.config.attach_pid.is_none()
+            && self
+                .capabilities
+                .as_ref()
+                .and_then(|c| c.supports_terminate_request)
+                .unwrap_or(false)
+    }
+
     fn stop(&self) {
         let dap_rpc = self.dap_rpc.clone();
-        if self
-            .capabilities
-            .as_ref()
-            .and_then(|c| c.supports_terminate_request)
-            .unwrap_or(false)
-        {
+        if self.stops_with_terminate() {
             thread::spawn(move || {
                 let _ = dap_rpc.terminate();
             });
@@ -357,13 +379,7 @@ fn check_restart(&mut self) -> Result<()> {
         if !self.restarted {
             return Ok(());
         }
-        if !self
-            .capabilities
-            .as_ref()
-            .and_then(|c| c.supports_terminate_request)
-            .unwrap_or(false)
-            && !self.disconnected
-        {
+        if !self.stops_with_terminate() && !self.disconnected {
             return Ok(());
         }
 
@@ -588,6 +604,19 @@ pub fn handle_server_message(&self, message_str: &str) {
     }
 
     pub fn launch(&self, config: &RunDebugConfig) -> Result<()> {
+        if let Some(pid) = config.attach_pid {
+            // Adapters disagree on the name of the pid argument
+            let params = serde_json::json!({
+                "pid": pid,
+                "processId": pid,
+                "program": config.program,
+                "cwd": config.cwd,
+            });
+            self.request::<Attach>(params)
+                .map_err(|e| anyhow!(e.message))?;
+            return Ok(());
+        }
+
         let params = serde_json::json!({
             "program": config.program,
             "args": config.args,
@@ -755,6 +784,81 @@ pub fn variables_async(
         self.request_async::<Variables>(args, f);
     }
 
+    pub fn evaluate_async(
+        &self,
+        frame_id: Option<usize>,
+        expression: String,
+        f: impl RpcCallback<EvaluateResponse, RpcError> + 'static,
+    ) {
+        let args = EvaluateArguments {
+            expression,
+            frame_id,
+            context: Some("repl".to_string()),
+            format: None,
+        };
+
+        self.request_async::<Evaluate>(args, f);
+    }
+
+    pub fn completions_async(
+        &self,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: impl RpcCallback<CompletionsResponse, RpcError> + 'static,
+    ) {
+        let args = CompletionsArguments {
+            frame_id,
+            text,
+            column,
+            line: None,
+        };
+
+        self.request_async::<Completions>(args, f);
+    }
+
+    pub fn set_variable_async(
+        &self,
+        reference: usize,
+        name: String,
+        value: String,
+        f: impl RpcCallback<SetVariableResponse, RpcError> + 'static,
+    ) {
+        let args = SetVariableArguments {
+            variables_reference: reference,
+            name,
+            value,
+            format: None,
+        };
+
+        self.request_async::<SetVariable>(args, f);
+    }
+
+    pub fn data_breakpoint_info_async(
+        &self,
+        reference: usize,
+        name: String,
+        f: impl RpcCallback<DataBreakpointInfoResponse, RpcError> + 'static,
+    ) {
+        let args = DataBreakpointInfoArguments {
+            variables_reference: Some(reference),
+            name,
+            frame_id: None,
+        };
+
+        self.request_async::<DataBreakpointInfo>(args, f);
+    }
+
+    pub fn set_data_breakpoints_async(
+        &self,
+        breakpoints: Vec<DataBreakpoint>,
+        f: impl RpcCallback<SetDataBreakpointsResponse, RpcError> + 'static,
+    ) {
+        let args = SetDataBreakpointsArguments { breakpoints };
+
+        self.request_async::<SetDataBreakpoints>(args, f);
+    }
+
     pub fn next(&self, thread_id: ThreadId) {
         let args = NextArguments {
             thread_id,
@@ -782,4 +886,10 @@ pub fn step_out(&self, thread_id: ThreadId) {
 
         self.request_async::<StepOut>(args, move |_| {});
     }
+
+    pub fn restart_frame(&self, frame_id: usize) {
+        let args = RestartFrameArguments { frame_id };
+
+        self.request_async::<RestartFrame>(args, move |_| {});
+    }
 }
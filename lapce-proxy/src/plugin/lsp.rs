@@ -89,7 +89,8 @@ fn handle_handler_notification(
                 self.initialize();
             }
             InitializeResult(result) => {
-                self.host.server_capabilities = result.capabilities;
+                self.host.set_server_capabilities(result.capabilities);
+                self.host.server_started(result.server_info);
             }
             Shutdown => {
                 self.shutdown();
@@ -369,7 +370,8 @@ fn initialize(&mut self) {
             false,
         ) {
             let result: InitializeResult = serde_json::from_value(value).unwrap();
-            self.host.server_capabilities = result.capabilities;
+            self.host.set_server_capabilities(result.capabilities);
+            self.host.server_started(result.server_info);
             self.server_rpc.server_notification(
                 Initialized::METHOD,
                 InitializedParams {},
@@ -414,6 +416,9 @@ fn process(
         }
 
         process.args(args);
+        process.envs(crate::env::workspace_env(
+            workspace.map(|workspace| workspace.as_path()),
+        ));
 
         #[cfg(target_os = "windows")]
         let process = process.creation_flags(0x08000000);
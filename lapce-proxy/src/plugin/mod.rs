@@ -20,10 +20,14 @@
 use crossbeam_channel::{Receiver, Sender};
 use dyn_clone::DynClone;
 use flate2::read::GzDecoder;
-use lapce_core::directory::Directory;
+use lapce_core::{directory::Directory, encoding::PositionEncoding};
 use lapce_rpc::{
     core::CoreRpcHandler,
     dap_types::{self, DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
+    inline_completion::{
+        CancelInlineCompletion, CancelInlineCompletionParams,
+        ProvideInlineCompletionParams,
+    },
     plugin::{PluginId, VoltInfo, VoltMetadata},
     proxy::ProxyRpcHandler,
     style::LineStyle,
@@ -34,10 +38,11 @@
 use lsp_types::{
     request::{
         CodeActionRequest, CodeActionResolveRequest, Completion,
-        DocumentSymbolRequest, Formatting, GotoDefinition, GotoTypeDefinition,
-        GotoTypeDefinitionParams, GotoTypeDefinitionResponse, HoverRequest,
-        InlayHintRequest, InlineCompletionRequest, PrepareRenameRequest, References,
-        Rename, Request, ResolveCompletionItem, SelectionRangeRequest,
+        DocumentHighlightRequest, DocumentSymbolRequest, Formatting, GotoDefinition,
+        GotoTypeDefinition, GotoTypeDefinitionParams, GotoTypeDefinitionResponse,
+        HoverRequest, InlayHintRequest, InlineCompletionRequest, LinkedEditingRange,
+        PrepareRenameRequest, References, Rename, Request, ResolveCompletionItem,
+        SelectionRangeRequest, SemanticTokensFullDeltaRequest,
         SemanticTokensFullRequest, SignatureHelpRequest, WorkspaceSymbolRequest,
     },
     ClientCapabilities, CodeAction, CodeActionCapabilityResolveSupport,
@@ -46,16 +51,22 @@
     CodeActionResponse, CompletionClientCapabilities, CompletionItem,
     CompletionItemCapability, CompletionItemCapabilityResolveSupport,
     CompletionParams, CompletionResponse, Diagnostic, DocumentFormattingParams,
-    DocumentSymbolParams, DocumentSymbolResponse, FormattingOptions, GotoCapability,
-    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverClientCapabilities,
+    DocumentHighlight, DocumentHighlightClientCapabilities, DocumentHighlightParams,
+    DocumentSymbolParams, DocumentSymbolResponse, FormattingOptions,
+    GeneralClientCapabilities, GotoCapability, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverClientCapabilities, HoverContents,
     HoverParams, InlayHint, InlayHintClientCapabilities, InlayHintParams,
     InlineCompletionClientCapabilities, InlineCompletionParams,
-    InlineCompletionResponse, InlineCompletionTriggerKind, Location, MarkupKind,
+    InlineCompletionResponse, InlineCompletionTriggerKind,
+    LinkedEditingRangeClientCapabilities, LinkedEditingRangeParams,
+    LinkedEditingRanges, Location, MarkedString, MarkupKind,
     MessageActionItemCapabilities, ParameterInformationSettings,
     PartialResultParams, Position, PrepareRenameResponse,
     PublishDiagnosticsClientCapabilities, Range, ReferenceContext, ReferenceParams,
     RenameParams, SelectionRange, SelectionRangeParams, SemanticTokens,
-    SemanticTokensClientCapabilities, SemanticTokensParams,
+    SemanticTokensClientCapabilities, SemanticTokensClientCapabilitiesRequests,
+    SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+    SemanticTokensFullOptions, SemanticTokensParams,
     ShowMessageRequestClientCapabilities, SignatureHelp,
     SignatureHelpClientCapabilities, SignatureHelpParams,
     SignatureInformationSettings, SymbolInformation, TextDocumentClientCapabilities,
@@ -81,6 +92,26 @@
 
 pub type PluginName = String;
 
+/// How long the answers of all the plugins are waited for, for the requests
+/// whose answers are merged, before going on with those there are
+const MERGED_REQUEST_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// The answers collected for a request sent to all the plugins
+struct CollectedResults<Resp> {
+    results: Vec<(PluginId, Resp)>,
+    received: usize,
+    timed_out: bool,
+    cb: Option<Box<dyn FnOnce(Vec<(PluginId, Resp)>) + Send>>,
+}
+
+impl<Resp> CollectedResults<Resp> {
+    fn finish(&mut self) {
+        if let Some(cb) = self.cb.take() {
+            cb(std::mem::take(&mut self.results));
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 pub enum PluginCatalogRpc {
     ServerRequest {
@@ -122,9 +153,43 @@ pub enum PluginCatalogRpc {
             >,
         >,
     },
+    DapEvaluate {
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        expression: String,
+        f: Box<dyn RpcCallback<dap_types::EvaluateResponse, RpcError>>,
+    },
+    DapCompletions {
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: Box<dyn RpcCallback<Vec<dap_types::CompletionItem>, RpcError>>,
+    },
+    DapSetVariable {
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: Box<dyn RpcCallback<dap_types::SetVariableResponse, RpcError>>,
+    },
+    DapDataBreakpointInfo {
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: Box<dyn RpcCallback<dap_types::DataBreakpointInfoResponse, RpcError>>,
+    },
+    DapSetDataBreakpoints {
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::DataBreakpoint>,
+        f: Box<dyn RpcCallback<Vec<dap_types::Breakpoint>, RpcError>>,
+    },
     DidOpenTextDocument {
         document: TextDocumentItem,
     },
+    DidCloseTextDocument {
+        text_document: TextDocumentIdentifier,
+    },
     DidChangeTextDocument {
         language_id: String,
         document: VersionedTextDocumentIdentifier,
@@ -138,6 +203,11 @@ pub enum PluginCatalogRpc {
         text_document: TextDocumentIdentifier,
         text: Rope,
     },
+    ProvideInlineCompletion {
+        path: PathBuf,
+        params: ProvideInlineCompletionParams,
+        disabled_providers: Vec<String>,
+    },
     Handler(PluginCatalogNotification),
     RemoveVolt {
         volt: VoltInfo,
@@ -182,6 +252,10 @@ pub enum PluginCatalogNotification {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    DapRestartFrame {
+        dap_id: DapId,
+        frame_id: usize,
+    },
     DapPause {
         dap_id: DapId,
         thread_id: ThreadId,
@@ -297,6 +371,9 @@ pub fn mainloop(&self, plugin: &mut PluginCatalog) {
                 PluginCatalogRpc::DidOpenTextDocument { document } => {
                     plugin.handle_did_open_text_document(document);
                 }
+                PluginCatalogRpc::DidCloseTextDocument { text_document } => {
+                    plugin.handle_did_close_text_document(text_document);
+                }
                 PluginCatalogRpc::DidSaveTextDocument {
                     language_id,
                     path,
@@ -339,9 +416,61 @@ pub fn mainloop(&self, plugin: &mut PluginCatalog) {
                 } => {
                     plugin.dap_get_scopes(dap_id, frame_id, f);
                 }
+                PluginCatalogRpc::DapEvaluate {
+                    dap_id,
+                    frame_id,
+                    expression,
+                    f,
+                } => {
+                    plugin.dap_evaluate(dap_id, frame_id, expression, f);
+                }
+                PluginCatalogRpc::DapCompletions {
+                    dap_id,
+                    frame_id,
+                    text,
+                    column,
+                    f,
+                } => {
+                    plugin.dap_completions(dap_id, frame_id, text, column, f);
+                }
+                PluginCatalogRpc::DapSetVariable {
+                    dap_id,
+                    reference,
+                    name,
+                    value,
+                    f,
+                } => {
+                    plugin.dap_set_variable(dap_id, reference, name, value, f);
+                }
+                PluginCatalogRpc::DapDataBreakpointInfo {
+                    dap_id,
+                    reference,
+                    name,
+                    f,
+                } => {
+                    plugin.dap_data_breakpoint_info(dap_id, reference, name, f);
+                }
+                PluginCatalogRpc::DapSetDataBreakpoints {
+                    dap_id,
+                    breakpoints,
+                    f,
+                } => {
+                    plugin.dap_set_data_breakpoints(dap_id, breakpoints, f);
+                }
                 PluginCatalogRpc::Shutdown => {
                     return;
                 }
+                PluginCatalogRpc::ProvideInlineCompletion {
+                    path,
+                    params,
+                    disabled_providers,
+                } => {
+                    plugin.provide_inline_completion(
+                        path,
+                        params,
+                        &disabled_providers,
+                    );
+                }
                 PluginCatalogRpc::RemoveVolt { volt, f } => {
                     plugin.shutdown_volt(volt, f);
                 }
@@ -416,6 +545,68 @@ fn send_request_to_all_plugins<P, Resp>(
         );
     }
 
+    /// Send a request to all the plugins, giving the answers of those which
+    /// have one. They are given once all the plugins have answered, or once
+    /// `timeout` has passed and there's at least one, so that a slow plugin
+    /// doesn't hold up the others.
+    fn collect_from_all_plugins<P, Resp>(
+        &self,
+        method: &'static str,
+        params: P,
+        language_id: Option<String>,
+        path: Option<PathBuf>,
+        timeout: Duration,
+        cb: impl FnOnce(Vec<(PluginId, Resp)>) + Send + 'static,
+    ) where
+        P: Serialize,
+        Resp: DeserializeOwned + Send + 'static,
+    {
+        let request_sent = Arc::new(AtomicUsize::new(0));
+        let collected = Arc::new(Mutex::new(CollectedResults {
+            results: Vec::new(),
+            received: 0,
+            timed_out: false,
+            cb: Some(Box::new(cb)),
+        }));
+
+        {
+            let collected = collected.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(timeout);
+                let mut collected = collected.lock();
+                collected.timed_out = true;
+                if !collected.results.is_empty() {
+                    collected.finish();
+                }
+            });
+        }
+
+        self.send_request(
+            None,
+            Some(request_sent.clone()),
+            method,
+            params,
+            language_id,
+            path,
+            true,
+            move |plugin_id, result| {
+                let mut collected = collected.lock();
+                collected.received += 1;
+                if let Some(resp) = result
+                    .ok()
+                    .and_then(|value| serde_json::from_value::<Resp>(value).ok())
+                {
+                    collected.results.push((plugin_id, resp));
+                }
+                if collected.received >= request_sent.load(Ordering::Acquire)
+                    || (collected.timed_out && !collected.results.is_empty())
+                {
+                    collected.finish();
+                }
+            },
+        );
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn send_request<P: Serialize>(
         &self,
@@ -515,14 +706,13 @@ pub fn did_change_text_document(
             });
     }
 
+    /// Get the definitions at a position from all the plugins and language
+    /// servers which provide them
     pub fn get_definition(
         &self,
         path: &Path,
         position: Position,
-        cb: impl FnOnce(PluginId, Result<GotoDefinitionResponse, RpcError>)
-            + Clone
-            + Send
-            + 'static,
+        cb: impl FnOnce(Result<GotoDefinitionResponse, RpcError>) + Send + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
         let method = GotoDefinition::METHOD;
@@ -537,12 +727,21 @@ pub fn get_definition(
 
         let language_id =
             Some(language_id_from_path(path).unwrap_or("").to_string());
-        self.send_request_to_all_plugins(
+        self.collect_from_all_plugins(
             method,
             params,
             language_id,
             Some(path.to_path_buf()),
-            cb,
+            MERGED_REQUEST_TIMEOUT,
+            move |results: Vec<(PluginId, GotoDefinitionResponse)>| {
+                cb(merge_definitions(
+                    results.into_iter().map(|(_, definition)| definition),
+                )
+                .ok_or_else(|| RpcError {
+                    code: 0,
+                    message: "no definition".to_string(),
+                }))
+            },
         );
     }
 
@@ -709,6 +908,34 @@ pub fn get_inline_completions(
         );
     }
 
+    /// Ask the plugins which provide inline completions, except the disabled
+    /// ones, for the completions at a position
+    pub fn provide_inline_completion(
+        &self,
+        path: &Path,
+        params: ProvideInlineCompletionParams,
+        disabled_providers: Vec<String>,
+    ) {
+        let _ = self
+            .plugin_tx
+            .send(PluginCatalogRpc::ProvideInlineCompletion {
+                path: path.to_path_buf(),
+                params,
+                disabled_providers,
+            });
+    }
+
+    pub fn cancel_inline_completion(&self, request_id: u64) {
+        self.send_notification(
+            None,
+            CancelInlineCompletion::METHOD,
+            CancelInlineCompletionParams { request_id },
+            None,
+            None,
+            true,
+        );
+    }
+
     pub fn get_document_symbols(
         &self,
         path: &Path,
@@ -865,6 +1092,49 @@ pub fn get_semantic_tokens(
         );
     }
 
+    /// Ask the plugin the tokens with `previous_result_id` came from how they
+    /// changed since
+    pub fn get_semantic_tokens_delta(
+        &self,
+        plugin_id: PluginId,
+        path: &Path,
+        previous_result_id: String,
+        cb: impl FnOnce(Result<SemanticTokensFullDeltaResult, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = SemanticTokensFullDeltaRequest::METHOD;
+        let params = SemanticTokensDeltaParams {
+            text_document: TextDocumentIdentifier { uri },
+            previous_result_id,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request(
+            Some(plugin_id),
+            None,
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            true,
+            move |_, result| {
+                let result = result.and_then(|value| {
+                    serde_json::from_value(value).map_err(|_| RpcError {
+                        code: 0,
+                        message: "semantic tokens delta deserialize error"
+                            .to_string(),
+                    })
+                });
+                cb(result)
+            },
+        );
+    }
+
     pub fn get_selection_range(
         &self,
         path: &Path,
@@ -893,11 +1163,72 @@ pub fn get_selection_range(
         );
     }
 
+    pub fn get_linked_editing_range(
+        &self,
+        path: &Path,
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<Option<LinkedEditingRanges>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = LinkedEditingRange::METHOD;
+        let params = LinkedEditingRangeParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    pub fn get_document_highlight(
+        &self,
+        path: &Path,
+        position: Position,
+        cb: impl FnOnce(PluginId, Result<Option<Vec<DocumentHighlight>>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = DocumentHighlightRequest::METHOD;
+        let params = DocumentHighlightParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
+    /// Get the hovers at a position from all the plugins and language
+    /// servers which provide them, merged into one
     pub fn hover(
         &self,
         path: &Path,
         position: Position,
-        cb: impl FnOnce(PluginId, Result<Hover, RpcError>) + Clone + Send + 'static,
+        cb: impl FnOnce(Result<Hover, RpcError>) + Send + 'static,
     ) {
         let uri = Url::from_file_path(path).unwrap();
         let method = HoverRequest::METHOD;
@@ -911,12 +1242,19 @@ pub fn hover(
         let language_id =
             Some(language_id_from_path(path).unwrap_or("").to_string());
 
-        self.send_request_to_all_plugins(
+        self.collect_from_all_plugins(
             method,
             params,
             language_id,
             Some(path.to_path_buf()),
-            cb,
+            MERGED_REQUEST_TIMEOUT,
+            move |results: Vec<(PluginId, Hover)>| {
+                cb(merge_hovers(results.into_iter().map(|(_, hover)| hover))
+                    .ok_or_else(|| RpcError {
+                        code: 0,
+                        message: "no hover".to_string(),
+                    }))
+            },
         );
     }
 
@@ -1099,6 +1437,20 @@ pub fn did_open_document(
         }
     }
 
+    pub fn did_close_document(&self, path: &Path) {
+        match Url::from_file_path(path) {
+            Ok(path) => {
+                let _ =
+                    self.plugin_tx.send(PluginCatalogRpc::DidCloseTextDocument {
+                        text_document: TextDocumentIdentifier::new(path),
+                    });
+            }
+            Err(_) => {
+                tracing::error!("Failed to parse URL from file path: {path:?}");
+            }
+        }
+    }
+
     pub fn unactivated_volts(&self, volts: Vec<VoltMetadata>) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::UnactivatedVolts(volts))
     }
@@ -1230,6 +1582,13 @@ pub fn dap_step_out(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
         })
     }
 
+    pub fn dap_restart_frame(&self, dap_id: DapId, frame_id: usize) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapRestartFrame {
+            dap_id,
+            frame_id,
+        })
+    }
+
     pub fn dap_stop(&self, dap_id: DapId) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::DapStop { dap_id })
     }
@@ -1293,6 +1652,89 @@ pub fn dap_get_scopes(
         });
     }
 
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        expression: String,
+        f: impl FnOnce(Result<dap_types::EvaluateResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapEvaluate {
+            dap_id,
+            frame_id,
+            expression,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_completions(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: impl FnOnce(Result<Vec<dap_types::CompletionItem>, RpcError>) + Send + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapCompletions {
+            dap_id,
+            frame_id,
+            text,
+            column,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: impl FnOnce(Result<dap_types::SetVariableResponse, RpcError>) + Send + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapSetVariable {
+            dap_id,
+            reference,
+            name,
+            value,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_data_breakpoint_info(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: impl FnOnce(Result<dap_types::DataBreakpointInfoResponse, RpcError>)
+            + Send
+            + 'static,
+    ) {
+        let _ = self
+            .plugin_tx
+            .send(PluginCatalogRpc::DapDataBreakpointInfo {
+                dap_id,
+                reference,
+                name,
+                f: Box::new(f),
+            });
+    }
+
+    pub fn dap_set_data_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::DataBreakpoint>,
+        f: impl FnOnce(Result<Vec<dap_types::Breakpoint>, RpcError>) + Send + 'static,
+    ) {
+        let _ = self
+            .plugin_tx
+            .send(PluginCatalogRpc::DapSetDataBreakpoints {
+                dap_id,
+                breakpoints,
+                f: Box::new(f),
+            });
+    }
+
     pub fn register_debugger_type(
         &self,
         debugger_type: String,
@@ -1442,6 +1884,69 @@ pub fn remove_volt(
     Ok(())
 }
 
+/// Merge the hovers of several providers into one, with the range of the
+/// first which has one
+fn merge_hovers(hovers: impl Iterator<Item = Hover>) -> Option<Hover> {
+    let mut hovers = hovers.collect::<Vec<_>>();
+    if hovers.len() <= 1 {
+        return hovers.pop();
+    }
+
+    let range = hovers.iter().find_map(|hover| hover.range);
+    let contents = hovers
+        .into_iter()
+        .flat_map(|hover| match hover.contents {
+            HoverContents::Scalar(content) => vec![content],
+            HoverContents::Array(contents) => contents,
+            HoverContents::Markup(content) => {
+                vec![MarkedString::String(content.value)]
+            }
+        })
+        .filter(|content| match content {
+            MarkedString::String(text) => !text.trim().is_empty(),
+            MarkedString::LanguageString(code) => !code.value.trim().is_empty(),
+        })
+        .collect::<Vec<_>>();
+    if contents.is_empty() {
+        return None;
+    }
+    Some(Hover {
+        contents: HoverContents::Array(contents),
+        range,
+    })
+}
+
+/// Merge the definitions of several providers, leaving out the duplicates
+fn merge_definitions(
+    definitions: impl Iterator<Item = GotoDefinitionResponse>,
+) -> Option<GotoDefinitionResponse> {
+    let mut definitions = definitions.collect::<Vec<_>>();
+    if definitions.len() <= 1 {
+        return definitions.pop();
+    }
+
+    let mut locations: Vec<Location> = Vec::new();
+    for definition in definitions {
+        let definition = match definition {
+            GotoDefinitionResponse::Scalar(location) => vec![location],
+            GotoDefinitionResponse::Array(locations) => locations,
+            GotoDefinitionResponse::Link(links) => links
+                .into_iter()
+                .map(|link| Location {
+                    uri: link.target_uri,
+                    range: link.target_selection_range,
+                })
+                .collect(),
+        };
+        for location in definition {
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+    }
+    Some(GotoDefinitionResponse::Array(locations))
+}
+
 fn client_capabilities() -> ClientCapabilities {
     ClientCapabilities {
         text_document: Some(TextDocumentClientCapabilities {
@@ -1509,6 +2014,12 @@ fn client_capabilities() -> ClientCapabilities {
                 ..Default::default()
             }),
             semantic_tokens: Some(SemanticTokensClientCapabilities {
+                requests: SemanticTokensClientCapabilitiesRequests {
+                    range: None,
+                    full: Some(SemanticTokensFullOptions::Delta {
+                        delta: Some(true),
+                    }),
+                },
                 ..Default::default()
             }),
             type_definition: Some(GotoCapability {
@@ -1526,6 +2037,12 @@ fn client_capabilities() -> ClientCapabilities {
             inline_completion: Some(InlineCompletionClientCapabilities {
                 ..Default::default()
             }),
+            linked_editing_range: Some(LinkedEditingRangeClientCapabilities {
+                ..Default::default()
+            }),
+            document_highlight: Some(DocumentHighlightClientCapabilities {
+                ..Default::default()
+            }),
 
             ..Default::default()
         }),
@@ -1546,6 +2063,75 @@ fn client_capabilities() -> ClientCapabilities {
             workspace_folders: Some(true),
             ..Default::default()
         }),
+        general: Some(GeneralClientCapabilities {
+            // The editor converts the positions it gets from the servers as
+            // UTF-16, so that is the only encoding offered
+            position_encodings: Some(vec![PositionEncoding::Utf16.kind()]),
+            ..Default::default()
+        }),
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        GotoDefinitionResponse, Hover, HoverContents, Location, LocationLink,
+        MarkedString, MarkupContent, MarkupKind, Position, Range, Url,
+    };
+
+    use super::{merge_definitions, merge_hovers};
+
+    #[test]
+    fn test_merge_hovers() {
+        let markdown = |value: &str| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: value.to_string(),
+            }),
+            range: None,
+        };
+        let hover = merge_hovers(vec![markdown("a")].into_iter()).unwrap();
+        assert_eq!(hover, markdown("a"));
+
+        let hover = merge_hovers(
+            vec![markdown("a"), markdown(" "), markdown("b")].into_iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            hover.contents,
+            HoverContents::Array(vec![
+                MarkedString::String("a".to_string()),
+                MarkedString::String("b".to_string()),
+            ])
+        );
+        assert!(merge_hovers(vec![markdown(""), markdown("")].into_iter()).is_none());
+    }
+
+    #[test]
+    fn test_merge_definitions() {
+        let uri = Url::parse("file:///a.rs").unwrap();
+        let range =
+            |line| Range::new(Position::new(line, 0), Position::new(line, 1));
+        let location = |line| Location::new(uri.clone(), range(line));
+
+        let definitions = vec![
+            GotoDefinitionResponse::Scalar(location(1)),
+            GotoDefinitionResponse::Link(vec![LocationLink {
+                origin_selection_range: None,
+                target_uri: uri.clone(),
+                target_range: range(0),
+                target_selection_range: range(2),
+            }]),
+            GotoDefinitionResponse::Array(vec![location(1)]),
+        ];
+        assert_eq!(
+            merge_definitions(definitions.into_iter()),
+            Some(GotoDefinitionResponse::Array(vec![
+                location(1),
+                location(2)
+            ]))
+        );
+        assert_eq!(merge_definitions(Vec::new().into_iter()), None);
+    }
+}
@@ -14,36 +14,46 @@
 use dyn_clone::DynClone;
 use floem_editor_core::buffer::rope_text::{RopeText, RopeTextRef};
 use jsonrpc_lite::{Id, JsonRpc, Params};
-use lapce_core::{encoding::offset_utf16_to_utf8, rope_text_pos::RopeTextPosition};
+use lapce_core::{encoding::PositionEncoding, rope_text_pos::RopeTextPosition};
 use lapce_rpc::{
+    completion::{RegisterCompletionProvider, RegisterCompletionProviderParams},
     core::CoreRpcHandler,
+    inline_completion::{
+        CancelInlineCompletion, InlineCompletionItems, InlineCompletionItemsParams,
+        ProvideInlineCompletion, RegisterInlineCompletionProvider,
+    },
+    language_provider::{RegisterDefinitionProvider, RegisterHoverProvider},
     plugin::{PluginId, VoltID},
+    rich_output::{ShowRichOutput, ShowRichOutputParams},
     style::{LineStyle, Style},
     RpcError,
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
     notification::{
-        DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument,
-        Initialized, LogMessage, Notification, Progress, PublishDiagnostics,
-        ShowMessage,
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        DidSaveTextDocument, Initialized, LogMessage, Notification, Progress,
+        PublishDiagnostics, ShowMessage,
     },
     request::{
         CodeActionRequest, CodeActionResolveRequest, Completion,
-        DocumentSymbolRequest, Formatting, GotoDefinition, GotoTypeDefinition,
-        HoverRequest, Initialize, InlayHintRequest, InlineCompletionRequest,
-        PrepareRenameRequest, References, RegisterCapability, Rename,
-        ResolveCompletionItem, SelectionRangeRequest, SemanticTokensFullRequest,
-        SignatureHelpRequest, WorkDoneProgressCreate, WorkspaceSymbolRequest,
+        DocumentHighlightRequest, DocumentSymbolRequest, Formatting, GotoDefinition,
+        GotoTypeDefinition, HoverRequest, Initialize, InlayHintRequest,
+        InlineCompletionRequest, LinkedEditingRange, PrepareRenameRequest,
+        References, RegisterCapability, Rename, ResolveCompletionItem,
+        SelectionRangeRequest, SemanticTokensFullDeltaRequest,
+        SemanticTokensFullRequest, SignatureHelpRequest, WorkDoneProgressCreate,
+        WorkspaceSymbolRequest,
     },
     CodeActionProviderCapability, DidChangeTextDocumentParams,
     DidSaveTextDocumentParams, DocumentSelector, HoverProviderCapability,
     InitializeResult, LogMessageParams, OneOf, ProgressParams,
     PublishDiagnosticsParams, Range, Registration, RegistrationParams,
-    SemanticTokens, SemanticTokensLegend, SemanticTokensServerCapabilities,
-    ServerCapabilities, ShowMessageParams, TextDocumentContentChangeEvent,
-    TextDocumentIdentifier, TextDocumentSaveRegistrationOptions,
-    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncSaveOptions,
+    SemanticTokens, SemanticTokensFullOptions, SemanticTokensLegend,
+    SemanticTokensServerCapabilities, ServerCapabilities, ServerInfo,
+    ShowMessageParams, TextDocumentContentChangeEvent, TextDocumentIdentifier,
+    TextDocumentSaveRegistrationOptions, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncSaveOptions,
     VersionedTextDocumentIdentifier,
 };
 use parking_lot::Mutex;
@@ -602,6 +612,14 @@ pub struct PluginHostHandler {
     /// Language servers that this plugin has spawned.  
     /// Note that these plugin ids could be 'dead' if the LSP died/exited.  
     spawned_lsp: HashMap<PluginId, SpawnedLspInfo>,
+    /// Whether the plugin registered as a provider of inline completions
+    inline_completion_provider: bool,
+    /// How the plugin provides completions, if it registered as a provider
+    completion_provider: Option<RegisterCompletionProviderParams>,
+    /// Whether the plugin registered as a provider of hovers
+    hover_provider: bool,
+    /// Whether the plugin registered as a provider of definitions
+    definition_provider: bool,
 }
 
 impl PluginHostHandler {
@@ -632,6 +650,10 @@ pub fn new(
             server_capabilities: ServerCapabilities::default(),
             server_registrations: ServerRegistrations::default(),
             spawned_lsp: HashMap::new(),
+            inline_completion_provider: false,
+            completion_provider: None,
+            hover_provider: false,
+            definition_provider: false,
         }
     }
 
@@ -668,14 +690,20 @@ pub fn method_registered(&mut self, method: &str) -> bool {
             Initialized::METHOD => true,
             Completion::METHOD => {
                 self.server_capabilities.completion_provider.is_some()
+                    || self.completion_provider.is_some()
             }
-            ResolveCompletionItem::METHOD => self
-                .server_capabilities
-                .completion_provider
-                .as_ref()
-                .and_then(|c| c.resolve_provider)
-                .unwrap_or(false),
-            DidOpenTextDocument::METHOD => {
+            ResolveCompletionItem::METHOD => {
+                self.server_capabilities
+                    .completion_provider
+                    .as_ref()
+                    .and_then(|c| c.resolve_provider)
+                    .unwrap_or(false)
+                    || self
+                        .completion_provider
+                        .as_ref()
+                        .is_some_and(|p| p.resolve_provider)
+            }
+            DidOpenTextDocument::METHOD | DidCloseTextDocument::METHOD => {
                 match &self.server_capabilities.text_document_sync {
                     Some(TextDocumentSyncCapability::Kind(kind)) => {
                         kind != &TextDocumentSyncKind::NONE
@@ -706,24 +734,28 @@ pub fn method_registered(&mut self, method: &str) -> bool {
             SignatureHelpRequest::METHOD => {
                 self.server_capabilities.signature_help_provider.is_some()
             }
-            HoverRequest::METHOD => self
-                .server_capabilities
-                .hover_provider
-                .as_ref()
-                .map(|c| match c {
-                    HoverProviderCapability::Simple(is_capable) => *is_capable,
-                    HoverProviderCapability::Options(_) => true,
-                })
-                .unwrap_or(false),
-            GotoDefinition::METHOD => self
-                .server_capabilities
-                .definition_provider
-                .as_ref()
-                .map(|d| match d {
-                    OneOf::Left(is_capable) => *is_capable,
-                    OneOf::Right(_) => true,
-                })
-                .unwrap_or(false),
+            HoverRequest::METHOD => {
+                self.server_capabilities
+                    .hover_provider
+                    .as_ref()
+                    .map(|c| match c {
+                        HoverProviderCapability::Simple(is_capable) => *is_capable,
+                        HoverProviderCapability::Options(_) => true,
+                    })
+                    .unwrap_or(false)
+                    || self.hover_provider
+            }
+            GotoDefinition::METHOD => {
+                self.server_capabilities
+                    .definition_provider
+                    .as_ref()
+                    .map(|d| match d {
+                        OneOf::Left(is_capable) => *is_capable,
+                        OneOf::Right(_) => true,
+                    })
+                    .unwrap_or(false)
+                    || self.definition_provider
+            }
             GotoTypeDefinition::METHOD => {
                 self.server_capabilities.type_definition_provider.is_some()
             }
@@ -757,6 +789,11 @@ pub fn method_registered(&mut self, method: &str) -> bool {
             SemanticTokensFullRequest::METHOD => {
                 self.server_capabilities.semantic_tokens_provider.is_some()
             }
+            SemanticTokensFullDeltaRequest::METHOD => self
+                .server_capabilities
+                .semantic_tokens_provider
+                .as_ref()
+                .is_some_and(semantic_tokens_delta),
             InlayHintRequest::METHOD => {
                 self.server_capabilities.inlay_hint_provider.is_some()
             }
@@ -774,9 +811,20 @@ pub fn method_registered(&mut self, method: &str) -> bool {
                 self.server_capabilities.rename_provider.is_some()
             }
             Rename::METHOD => self.server_capabilities.rename_provider.is_some(),
+            ProvideInlineCompletion::METHOD | CancelInlineCompletion::METHOD => {
+                self.inline_completion_provider
+            }
             SelectionRangeRequest::METHOD => {
                 self.server_capabilities.selection_range_provider.is_some()
             }
+            LinkedEditingRange::METHOD => self
+                .server_capabilities
+                .linked_editing_range_provider
+                .is_some(),
+            DocumentHighlightRequest::METHOD => self
+                .server_capabilities
+                .document_highlight_provider
+                .is_some(),
             CodeActionResolveRequest::METHOD => {
                 self.server_capabilities.code_action_provider.is_some()
             }
@@ -900,6 +948,29 @@ pub fn process_request(
                     stderr: Some(output.stderr),
                 });
             }
+            RegisterCompletionProvider::METHOD => {
+                let params: RegisterCompletionProviderParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                self.catalog_rpc.core_rpc.completion_provider(
+                    self.server_rpc.plugin_id,
+                    self.volt_display_name.clone(),
+                    params.trigger_characters.clone(),
+                );
+                self.completion_provider = Some(params);
+                resp.send_null();
+            }
+            RegisterHoverProvider::METHOD => {
+                self.hover_provider = true;
+                resp.send_null();
+            }
+            RegisterDefinitionProvider::METHOD => {
+                self.definition_provider = true;
+                resp.send_null();
+            }
+            RegisterInlineCompletionProvider::METHOD => {
+                self.inline_completion_provider = true;
+                resp.send_null();
+            }
             RegisterDebuggerType::METHOD => {
                 let params: RegisterDebuggerTypeParams =
                     serde_json::from_value(serde_json::to_value(params)?)?;
@@ -1061,6 +1132,24 @@ pub fn handle_notification(
                 let title = format!("Plugin: {}", self.volt_display_name);
                 self.catalog_rpc.core_rpc.show_message(title, message);
             }
+            InlineCompletionItems::METHOD => {
+                let params: InlineCompletionItemsParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                if self.inline_completion_provider {
+                    self.catalog_rpc.core_rpc.inline_completion_items(
+                        params.request_id,
+                        self.volt_id.name.clone(),
+                        params.items,
+                    );
+                }
+            }
+            ShowRichOutput::METHOD => {
+                let params: ShowRichOutputParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                self.catalog_rpc
+                    .core_rpc
+                    .rich_output(self.volt_display_name.clone(), params.items);
+            }
             LogMessage::METHOD => {
                 let message: LogMessageParams =
                     serde_json::from_value(serde_json::to_value(params)?)?;
@@ -1153,16 +1242,23 @@ pub fn handle_did_change_text_document(
                 }
             }
             TextDocumentSyncKind::INCREMENTAL => {
-                if let Some(c) = existing.1.as_ref() {
+                let encoding = self.position_encoding();
+                // The change is computed once for all the servers using
+                // UTF-16, which is most of them
+                let shared = encoding == PositionEncoding::Utf16;
+                if let Some(c) = existing.1.as_ref().filter(|_| shared) {
                     c.clone()
                 } else {
-                    let change = get_document_content_change(&text, &delta)
-                        .unwrap_or_else(|| TextDocumentContentChangeEvent {
-                            range: None,
-                            range_length: None,
-                            text: new_text.to_string(),
-                        });
-                    existing.1 = Some(change.clone());
+                    let change =
+                        get_document_content_change(&text, &delta, encoding)
+                            .unwrap_or_else(|| TextDocumentContentChangeEvent {
+                                range: None,
+                                range_length: None,
+                                text: new_text.to_string(),
+                            });
+                    if shared {
+                        existing.1 = Some(change.clone());
+                    }
                     change
                 }
             }
@@ -1196,6 +1292,7 @@ pub fn format_semantic_tokens(
             &text,
             self.server_capabilities.semantic_tokens_provider.as_ref(),
             &tokens,
+            self.position_encoding(),
         )
         .ok_or_else(|| RpcError {
             code: 0,
@@ -1204,6 +1301,36 @@ pub fn format_semantic_tokens(
         f.call(result);
     }
 
+    /// How the columns of the positions exchanged with the server count,
+    /// as it answered the initialization with
+    pub fn position_encoding(&self) -> PositionEncoding {
+        PositionEncoding::from_kind(
+            self.server_capabilities.position_encoding.as_ref(),
+        )
+    }
+
+    /// Set the capabilities the server answered the initialization with,
+    /// telling the core about its completions
+    pub fn set_server_capabilities(&mut self, capabilities: ServerCapabilities) {
+        if let Some(completion) = &capabilities.completion_provider {
+            self.core_rpc.completion_provider(
+                self.server_rpc.plugin_id,
+                self.volt_display_name.clone(),
+                completion.trigger_characters.clone().unwrap_or_default(),
+            );
+        }
+        self.server_capabilities = capabilities;
+    }
+
+    /// Tell the editor which server was started, for the diagnostic bundle
+    pub fn server_started(&self, server_info: Option<ServerInfo>) {
+        self.core_rpc.language_server_started(
+            self.server_rpc.plugin_id,
+            self.volt_display_name.clone(),
+            server_info,
+        );
+    }
+
     pub fn handle_spawned_plugin_loaded(&mut self, plugin_id: PluginId) {
         if let Some(info) = self.spawned_lsp.get_mut(&plugin_id) {
             let Some(resp) = info.resp.take() else {
@@ -1232,6 +1359,7 @@ struct SpawnedLspInfo {
 fn get_document_content_change(
     text: &Rope,
     delta: &RopeDelta,
+    encoding: PositionEncoding,
 ) -> Option<TextDocumentContentChangeEvent> {
     let (interval, _) = delta.summary();
     let (start, end) = interval.start_end();
@@ -1241,9 +1369,9 @@ fn get_document_content_change(
     // TODO: Handle more trivial cases like typing when there's a selection or transpose
     if let Some(node) = delta.as_simple_insert() {
         let (start, end) = interval.start_end();
-        let start = text.offset_to_position(start);
+        let start = text.offset_to_position_in(start, encoding);
 
-        let end = text.offset_to_position(end);
+        let end = text.offset_to_position_in(end, encoding);
 
         let text = String::from(node);
         let text_document_content_change_event = TextDocumentContentChangeEvent {
@@ -1256,9 +1384,9 @@ fn get_document_content_change(
     }
     // Or a simple delete
     else if delta.is_simple_delete() {
-        let end_position = text.offset_to_position(end);
+        let end_position = text.offset_to_position_in(end, encoding);
 
-        let start = text.offset_to_position(start);
+        let start = text.offset_to_position_in(start, encoding);
 
         let text_document_content_change_event = TextDocumentContentChangeEvent {
             range: Some(Range {
@@ -1279,6 +1407,7 @@ fn format_semantic_styles(
     text: &Rope,
     semantic_tokens_provider: Option<&SemanticTokensServerCapabilities>,
     tokens: &SemanticTokens,
+    encoding: PositionEncoding,
 ) -> Option<Vec<LineStyle>> {
     let semantic_tokens_provider = semantic_tokens_provider?;
     let semantic_legends = semantic_tokens_legend(semantic_tokens_provider);
@@ -1295,11 +1424,12 @@ fn format_semantic_styles(
         }
 
         let sub_text = text.char_indices_iter(start..);
-        start += offset_utf16_to_utf8(sub_text, semantic_token.delta_start as usize);
+        start +=
+            encoding.offset_to_utf8(sub_text, semantic_token.delta_start as usize);
 
         let sub_text = text.char_indices_iter(start..);
-        let end =
-            start + offset_utf16_to_utf8(sub_text, semantic_token.length as usize);
+        let end = start
+            + encoding.offset_to_utf8(sub_text, semantic_token.length as usize);
 
         let kind = semantic_legends.token_types[semantic_token.token_type as usize]
             .as_str()
@@ -1332,3 +1462,20 @@ fn semantic_tokens_legend(
         ) => &options.semantic_tokens_options.legend,
     }
 }
+
+/// Whether the server gives the changes of the semantic tokens since the
+/// last ones it gave
+fn semantic_tokens_delta(
+    semantic_tokens_provider: &SemanticTokensServerCapabilities,
+) -> bool {
+    let options = match semantic_tokens_provider {
+        SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+        SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
+            options,
+        ) => &options.semantic_tokens_options,
+    };
+    matches!(
+        options.full,
+        Some(SemanticTokensFullOptions::Delta { delta: Some(true) })
+    )
+}
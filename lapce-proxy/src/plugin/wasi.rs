@@ -112,7 +112,7 @@ fn handle_handler_notification(
                 self.initialize();
             }
             InitializeResult(result) => {
-                self.host.server_capabilities = result.capabilities;
+                self.host.set_server_capabilities(result.capabilities);
             }
             Shutdown => {
                 self.shutdown();
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use lsp_types::Diagnostic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::RpcMessage;
+
+/// Requests accepted on the automation socket. Paths that are optional refer
+/// to the active editor when left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "method", content = "params")]
+pub enum AutomationRequest {
+    OpenFile {
+        path: PathBuf,
+        /// One based, like on the command line
+        line: Option<usize>,
+        /// One based, like on the command line
+        column: Option<usize>,
+    },
+    RunCommand {
+        /// The name of the command as used in keymaps, e.g. `"save"`
+        command: String,
+        data: Option<Value>,
+    },
+    GetText {
+        path: Option<PathBuf>,
+    },
+    GetDiagnostics {
+        path: Option<PathBuf>,
+    },
+    /// Turn the connection into a stream of [`AutomationEvent`]
+    /// notifications. No further requests are read from it.
+    Subscribe,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "method", content = "params")]
+pub enum AutomationResponse {
+    Done,
+    Text {
+        path: PathBuf,
+        content: String,
+    },
+    Diagnostics {
+        path: PathBuf,
+        diagnostics: Vec<Diagnostic>,
+    },
+    Subscribed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "method", content = "params")]
+pub enum AutomationEvent {
+    EditorFocused { path: PathBuf },
+    FileSaved { path: PathBuf },
+    DiagnosticsChanged { path: PathBuf },
+}
+
+pub type AutomationMessage =
+    RpcMessage<AutomationRequest, AutomationEvent, AutomationResponse>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::{AutomationMessage, AutomationRequest};
+    use crate::{
+        stdio::{read_msg, write_msg},
+        RpcMessage,
+    };
+
+    #[test]
+    fn test_request_round_trip() {
+        let mut buf = Vec::new();
+        let msg: AutomationMessage =
+            RpcMessage::Request(1, AutomationRequest::Subscribe);
+        write_msg(&mut buf, msg).unwrap();
+        let msg: AutomationMessage = RpcMessage::Request(
+            2,
+            AutomationRequest::OpenFile {
+                path: "src/main.rs".into(),
+                line: Some(3),
+                column: None,
+            },
+        );
+        write_msg(&mut buf, msg).unwrap();
+
+        let mut reader = BufReader::new(buf.as_slice());
+        let msg: Option<AutomationMessage> = read_msg(&mut reader).unwrap();
+        assert!(matches!(
+            msg,
+            Some(RpcMessage::Request(1, AutomationRequest::Subscribe))
+        ));
+        let msg: Option<AutomationMessage> = read_msg(&mut reader).unwrap();
+        match msg {
+            Some(RpcMessage::Request(
+                2,
+                AutomationRequest::OpenFile { path, line, column },
+            )) => {
+                assert_eq!(path.to_str(), Some("src/main.rs"));
+                assert_eq!(line, Some(3));
+                assert_eq!(column, None);
+            }
+            _ => panic!("unexpected message {msg:?}"),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! The protocol plugins use to provide completions without a language
+//! server.
+//!
+//! A plugin registers as a provider with the characters which trigger its
+//! completions, then is sent the `textDocument/completion` requests for the
+//! documents it is activated for, and `completionItem/resolve` if it
+//! resolves its items. Its items are shown in the completion list with those
+//! of the language servers, ordered by their sort text.
+
+use lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterCompletionProviderParams {
+    /// The characters which start a completion when typed, besides those of
+    /// identifiers
+    #[serde(default)]
+    pub trigger_characters: Vec<String>,
+    /// Whether the provider answers `completionItem/resolve` for the items
+    /// which have `data`
+    #[serde(default)]
+    pub resolve_provider: bool,
+}
+
+/// The request a plugin sends to be asked for completions
+pub enum RegisterCompletionProvider {}
+
+impl Request for RegisterCompletionProvider {
+    type Params = RegisterCompletionProviderParams;
+    type Result = ();
+    const METHOD: &'static str = "lapce/registerCompletionProvider";
+}
@@ -9,19 +9,22 @@
 
 use crossbeam_channel::{Receiver, Sender};
 use lsp_types::{
-    CompletionResponse, LogMessageParams, ProgressParams, PublishDiagnosticsParams,
-    ShowMessageParams, SignatureHelp,
+    CompletionResponse, InlineCompletionItem, LogMessageParams, ProgressParams,
+    PublishDiagnosticsParams, ServerInfo, ShowMessageParams, SignatureHelp,
 };
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    buffer::BufferId,
+    coverage::FileCoverage,
     dap_types::{
         self, DapId, RunDebugConfig, Scope, StackFrame, Stopped, ThreadId, Variable,
     },
     file::PathObject,
     plugin::{PluginId, VoltInfo, VoltMetadata},
     proxy::ProxyStatus,
+    rich_output::RichOutput,
     source_control::DiffInfo,
     terminal::TermId,
     RequestId, RpcError, RpcMessage,
@@ -44,20 +47,72 @@ pub enum CoreNotification {
         path: PathBuf,
         content: String,
     },
+    /// The text appended to an open file at `offset`, the length the file had
+    OpenFileAppended {
+        path: PathBuf,
+        offset: usize,
+        content: String,
+    },
     CompletionResponse {
         request_id: usize,
         input: String,
         resp: CompletionResponse,
         plugin_id: PluginId,
     },
+    /// A plugin or language server which provides completions, sent when it
+    /// registers or starts
+    CompletionProvider {
+        plugin_id: PluginId,
+        name: String,
+        trigger_characters: Vec<String>,
+    },
+    /// How much of a file being opened has been read, in bytes
+    BufferLoadProgress {
+        buffer_id: BufferId,
+        loaded: u64,
+        total: u64,
+    },
+    /// A language server finished starting, with the name and the version
+    /// it reported if it did
+    LanguageServerStarted {
+        plugin_id: PluginId,
+        name: String,
+        server_info: Option<ServerInfo>,
+    },
     SignatureHelpResponse {
         request_id: usize,
         resp: SignatureHelp,
         plugin_id: PluginId,
     },
+    /// The items a plugin provides for an inline completion request, in
+    /// place of those it provided before
+    InlineCompletionItems {
+        request_id: u64,
+        /// The name of the plugin
+        provider: String,
+        items: Vec<InlineCompletionItem>,
+    },
     OpenPaths {
         paths: Vec<PathObject>,
     },
+    /// Sent by a second `lapce --new-window` invocation
+    OpenPathsInNewWindow {
+        paths: Vec<PathObject>,
+    },
+    /// Sent by a second `lapce --diff` invocation
+    OpenDiffFiles {
+        left_path: PathBuf,
+        right_path: PathBuf,
+    },
+    /// Sent by a second `lapce --open-url` invocation
+    OpenUrl {
+        url: String,
+    },
+    /// Sent by a second `lapce --wait` invocation, which is only answered
+    /// once none of the files are open anymore
+    WaitPathsClosed {
+        paths: Vec<PathBuf>,
+    },
     WorkspaceFileChange,
     PublishDiagnostics {
         diagnostics: PublishDiagnosticsParams,
@@ -95,6 +150,15 @@ pub enum CoreNotification {
     DiffInfo {
         diff: DiffInfo,
     },
+    /// The coverage of the files in the coverage report, by their paths
+    CoverageChanged {
+        coverage: HashMap<PathBuf, FileCoverage>,
+    },
+    /// Output a plugin shows in the output panel
+    RichOutput {
+        source: String,
+        items: Vec<RichOutput>,
+    },
     UpdateTerminal {
         term_id: TermId,
         content: Vec<u8>,
@@ -127,11 +191,20 @@ pub enum CoreNotification {
     DapContinued {
         dap_id: DapId,
     },
+    /// What the adapter of the session supports, once it's initialized
+    DapCapabilities {
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    },
     DapBreakpointsResp {
         dap_id: DapId,
         path: PathBuf,
         breakpoints: Vec<dap_types::Breakpoint>,
     },
+    DapOutput {
+        dap_id: DapId,
+        output: dap_types::Output,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -235,10 +308,39 @@ pub fn diff_info(&self, diff: DiffInfo) {
         self.notification(CoreNotification::DiffInfo { diff });
     }
 
+    pub fn coverage_changed(&self, coverage: HashMap<PathBuf, FileCoverage>) {
+        self.notification(CoreNotification::CoverageChanged { coverage });
+    }
+
+    pub fn rich_output(&self, source: String, items: Vec<RichOutput>) {
+        self.notification(CoreNotification::RichOutput { source, items });
+    }
+
     pub fn open_file_changed(&self, path: PathBuf, content: String) {
         self.notification(CoreNotification::OpenFileChanged { path, content });
     }
 
+    pub fn inline_completion_items(
+        &self,
+        request_id: u64,
+        provider: String,
+        items: Vec<InlineCompletionItem>,
+    ) {
+        self.notification(CoreNotification::InlineCompletionItems {
+            request_id,
+            provider,
+            items,
+        });
+    }
+
+    pub fn open_file_appended(&self, path: PathBuf, offset: usize, content: String) {
+        self.notification(CoreNotification::OpenFileAppended {
+            path,
+            offset,
+            content,
+        });
+    }
+
     pub fn completion_response(
         &self,
         request_id: usize,
@@ -254,6 +356,45 @@ pub fn completion_response(
         });
     }
 
+    pub fn completion_provider(
+        &self,
+        plugin_id: PluginId,
+        name: String,
+        trigger_characters: Vec<String>,
+    ) {
+        self.notification(CoreNotification::CompletionProvider {
+            plugin_id,
+            name,
+            trigger_characters,
+        });
+    }
+
+    pub fn buffer_load_progress(
+        &self,
+        buffer_id: BufferId,
+        loaded: u64,
+        total: u64,
+    ) {
+        self.notification(CoreNotification::BufferLoadProgress {
+            buffer_id,
+            loaded,
+            total,
+        });
+    }
+
+    pub fn language_server_started(
+        &self,
+        plugin_id: PluginId,
+        name: String,
+        server_info: Option<ServerInfo>,
+    ) {
+        self.notification(CoreNotification::LanguageServerStarted {
+            plugin_id,
+            name,
+            server_info,
+        });
+    }
+
     pub fn signature_help_response(
         &self,
         request_id: usize,
@@ -352,6 +493,17 @@ pub fn dap_continued(&self, dap_id: DapId) {
         self.notification(CoreNotification::DapContinued { dap_id });
     }
 
+    pub fn dap_capabilities(
+        &self,
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    ) {
+        self.notification(CoreNotification::DapCapabilities {
+            dap_id,
+            capabilities,
+        });
+    }
+
     pub fn dap_breakpoints_resp(
         &self,
         dap_id: DapId,
@@ -365,6 +517,10 @@ pub fn dap_breakpoints_resp(
         });
     }
 
+    pub fn dap_output(&self, dap_id: DapId, output: dap_types::Output) {
+        self.notification(CoreNotification::DapOutput { dap_id, output });
+    }
+
     pub fn home_dir(&self, path: PathBuf) {
         self.notification(CoreNotification::HomeDir { path });
     }
@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How much of a line the tests ran, from the worst to the best
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum LineCoverage {
+    Uncovered,
+    /// The line ran, but not all of its branches
+    Partial,
+    Covered,
+}
+
+/// The coverage of the lines of a file, as of the last coverage report
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileCoverage {
+    /// The lines with code, by their zero based line numbers
+    pub lines: BTreeMap<usize, LineCoverage>,
+}
+
+impl FileCoverage {
+    /// Record the coverage of a line, which a report can have several times,
+    /// such as once for each test binary, keeping the best of them.
+    pub fn add(&mut self, line: usize, coverage: LineCoverage) {
+        let current = self.lines.entry(line).or_insert(coverage);
+        *current = (*current).max(coverage);
+    }
+
+    /// The percentage of the lines with code that ran, `None` when the file
+    /// has no code
+    pub fn percent(&self) -> Option<f64> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        let ran = self
+            .lines
+            .values()
+            .filter(|c| **c != LineCoverage::Uncovered)
+            .count();
+        Some(ran as f64 * 100.0 / self.lines.len() as f64)
+    }
+}
@@ -57,6 +57,17 @@ pub struct RunDebugConfig {
     pub debug_command: Option<Vec<String>>,
     #[serde(skip)]
     pub dap_id: DapId,
+    /// The process the debugger attaches to instead of launching `program`
+    #[serde(skip)]
+    pub attach_pid: Option<u32>,
+}
+
+/// A process running on the machine of the proxy
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: String,
 }
 
 pub trait Request {
@@ -482,6 +493,14 @@ impl Request for Launch {
     const COMMAND: &'static str = "launch";
 }
 
+pub enum Attach {}
+
+impl Request for Attach {
+    type Arguments = Value;
+    type Result = Value;
+    const COMMAND: &'static str = "attach";
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunInTerminalResponse {
@@ -855,3 +874,212 @@ impl Request for StepOut {
     type Result = ();
     const COMMAND: &'static str = "stepOut";
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartFrameArguments {
+    pub frame_id: usize,
+}
+
+#[derive(Debug)]
+pub enum RestartFrame {}
+
+impl Request for RestartFrame {
+    type Arguments = RestartFrameArguments;
+    type Result = ();
+    const COMMAND: &'static str = "restartFrame";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ValueFormat>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<VariablePresentationHint>,
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Evaluate {}
+
+impl Request for Evaluate {
+    type Arguments = EvaluateArguments;
+    type Result = EvaluateResponse;
+    const COMMAND: &'static str = "evaluate";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionsArguments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+    pub text: String,
+    /// In UTF-16 code units, counted from 1 as the adapter is told when it's
+    /// initialized
+    pub column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    /// Where the text replaces the expression, counted like the column of
+    /// the request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionsResponse {
+    pub targets: Vec<CompletionItem>,
+}
+
+#[derive(Debug)]
+pub enum Completions {}
+
+impl Request for Completions {
+    type Arguments = CompletionsArguments;
+    type Result = CompletionsResponse;
+    const COMMAND: &'static str = "completions";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableArguments {
+    pub variables_reference: usize,
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ValueFormat>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableResponse {
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum SetVariable {}
+
+impl Request for SetVariable {
+    type Arguments = SetVariableArguments;
+    type Result = SetVariableResponse;
+    const COMMAND: &'static str = "setVariable";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataBreakpointAccessType {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpointInfoArguments {
+    /// The container of the variable, or none for an expression
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<usize>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpointInfoResponse {
+    /// None if no data breakpoint can be set on the variable, and the
+    /// description says why
+    pub data_id: Option<String>,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_types: Option<Vec<DataBreakpointAccessType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_persist: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum DataBreakpointInfo {}
+
+impl Request for DataBreakpointInfo {
+    type Arguments = DataBreakpointInfoArguments;
+    type Result = DataBreakpointInfoResponse;
+    const COMMAND: &'static str = "dataBreakpointInfo";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpoint {
+    pub data_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_type: Option<DataBreakpointAccessType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDataBreakpointsArguments {
+    /// All the data breakpoints, replacing the ones set before
+    pub breakpoints: Vec<DataBreakpoint>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDataBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Debug)]
+pub enum SetDataBreakpoints {}
+
+impl Request for SetDataBreakpoints {
+    type Arguments = SetDataBreakpointsArguments;
+    type Result = SetDataBreakpointsResponse;
+    const COMMAND: &'static str = "setDataBreakpoints";
+}
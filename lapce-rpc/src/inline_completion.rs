@@ -0,0 +1,84 @@
+//! The protocol plugins use to provide inline completions without a language
+//! server, such as the suggestions of a model, which can be streamed while
+//! they are generated.
+//!
+//! A plugin registers as a provider once, then is sent
+//! [`ProvideInlineCompletion`] for the documents it is activated for. It
+//! answers with [`InlineCompletionItems`], as many times as it wants for a
+//! request, each time with all the items it has so far. A request is
+//! cancelled with [`CancelInlineCompletion`] once the document is edited or
+//! the completion is dismissed, after which its items are ignored.
+
+use lsp_types::{
+    notification::Notification, request::Request, InlineCompletionItem,
+    InlineCompletionTriggerKind, TextDocumentPositionParams,
+};
+use serde::{Deserialize, Serialize};
+
+/// How many lines around the cursor are sent to the providers with a request
+pub const INLINE_COMPLETION_CONTEXT_LINES: usize = 100;
+
+/// The request a plugin sends to be asked for inline completions
+pub enum RegisterInlineCompletionProvider {}
+
+impl Request for RegisterInlineCompletionProvider {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "lapce/registerInlineCompletionProvider";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvideInlineCompletionParams {
+    /// The id the items and the cancellation of the request refer to
+    pub request_id: u64,
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+    pub language_id: String,
+    pub trigger_kind: InlineCompletionTriggerKind,
+    /// The text of the lines before the cursor and of its line up to it
+    pub prefix: String,
+    /// The text of its line after the cursor and of the lines after it
+    pub suffix: String,
+}
+
+/// The notification asking a provider for the inline completions at a
+/// position
+pub enum ProvideInlineCompletion {}
+
+impl Notification for ProvideInlineCompletion {
+    type Params = ProvideInlineCompletionParams;
+    const METHOD: &'static str = "lapce/provideInlineCompletion";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionItemsParams {
+    pub request_id: u64,
+    /// All the items of the provider for the request, in place of those it
+    /// sent before
+    pub items: Vec<InlineCompletionItem>,
+}
+
+/// The notification a provider sends with its items for a request
+pub enum InlineCompletionItems {}
+
+impl Notification for InlineCompletionItems {
+    type Params = InlineCompletionItemsParams;
+    const METHOD: &'static str = "lapce/inlineCompletionItems";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelInlineCompletionParams {
+    pub request_id: u64,
+}
+
+/// The notification telling the providers the items of a request aren't
+/// wanted any longer
+pub enum CancelInlineCompletion {}
+
+impl Notification for CancelInlineCompletion {
+    type Params = CancelInlineCompletionParams;
+    const METHOD: &'static str = "lapce/cancelInlineCompletion";
+}
@@ -0,0 +1,28 @@
+//! The requests plugins send to provide hovers and definitions without a
+//! language server, such as looking up documentation or jumping to the
+//! definition of a configuration key.
+//!
+//! Once registered, a plugin is sent the `textDocument/hover` and
+//! `textDocument/definition` requests for the documents it is activated for,
+//! along with the language servers. Their answers are merged, those which
+//! come too late after the first being left out.
+
+use lsp_types::request::Request;
+
+/// The request a plugin sends to be asked for hovers
+pub enum RegisterHoverProvider {}
+
+impl Request for RegisterHoverProvider {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "lapce/registerHoverProvider";
+}
+
+/// The request a plugin sends to be asked for definitions
+pub enum RegisterDefinitionProvider {}
+
+impl Request for RegisterDefinitionProvider {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "lapce/registerDefinitionProvider";
+}
@@ -0,0 +1,38 @@
+//! Files too large to be loaded into a rope, which are read a chunk at a time
+//! in a read-only viewer instead.
+
+use serde::{Deserialize, Serialize};
+
+/// The code of the error the proxy answers a new buffer with when the file is
+/// over the size limit, for the file to be opened in the viewer instead
+pub const FILE_TOO_LARGE: i64 = 413;
+
+/// Whole lines of a file, from the byte offset `start` to `end`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChunk {
+    /// The size of the whole file
+    pub size: u64,
+    pub start: u64,
+    pub end: u64,
+    /// The text of the chunk, the invalid UTF-8 replaced
+    pub text: String,
+}
+
+impl FileChunk {
+    /// How long the lines of the chunk are on average, to estimate where a
+    /// line of the file is from
+    pub fn average_line_len(&self) -> Option<f64> {
+        let lines = self.text.lines().count();
+        (lines > 0).then(|| (self.end - self.start) as f64 / lines as f64)
+    }
+}
+
+/// A line of a file matching a search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMatch {
+    /// The zero based line number
+    pub line: usize,
+    /// The byte offset of the line in the file
+    pub offset: u64,
+    pub text: String,
+}
@@ -1,13 +1,20 @@
 #![allow(clippy::manual_clamp)]
 
+pub mod automation;
 pub mod buffer;
+pub mod completion;
 pub mod core;
 pub mod counter;
+pub mod coverage;
 pub mod dap_types;
 pub mod file;
+pub mod inline_completion;
+pub mod language_provider;
+pub mod large_file;
 mod parse;
 pub mod plugin;
 pub mod proxy;
+pub mod rich_output;
 pub mod source_control;
 pub mod stdio;
 pub mod style;
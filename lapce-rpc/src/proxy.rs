@@ -12,10 +12,11 @@
 use lapce_xi_rope::RopeDelta;
 use lsp_types::{
     request::GotoTypeDefinitionResponse, CodeAction, CodeActionResponse,
-    CompletionItem, Diagnostic, DocumentSymbolResponse, GotoDefinitionResponse,
-    Hover, InlayHint, InlineCompletionResponse, InlineCompletionTriggerKind,
-    Location, Position, PrepareRenameResponse, SelectionRange, SymbolInformation,
-    TextDocumentItem, TextEdit, WorkspaceEdit,
+    CompletionItem, Diagnostic, DocumentHighlight, DocumentSymbolResponse,
+    GotoDefinitionResponse, Hover, InlayHint, InlineCompletionResponse,
+    InlineCompletionTriggerKind, LinkedEditingRanges, Location, Position,
+    PrepareRenameResponse, SelectionRange, SymbolInformation, TextDocumentItem,
+    TextEdit, WorkspaceEdit,
 };
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -25,8 +26,9 @@
     buffer::BufferId,
     dap_types::{self, DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
     file::{FileNodeItem, PathObject},
+    large_file::{FileChunk, FileMatch},
     plugin::{PluginId, VoltInfo, VoltMetadata},
-    source_control::FileDiff,
+    source_control::{BlameInfo, FileDiff, GitCommitInfo},
     style::SemanticStyles,
     terminal::{TermId, TerminalProfile},
     RequestId, RpcError, RpcMessage,
@@ -61,10 +63,18 @@ pub enum ProxyRequest {
     NewBuffer {
         buffer_id: BufferId,
         path: PathBuf,
+        /// The size over which the file is refused with
+        /// [`FILE_TOO_LARGE`](crate::large_file::FILE_TOO_LARGE)
+        max_size: Option<u64>,
     },
     BufferHead {
         path: PathBuf,
     },
+    /// The content of the file at a git revision, such as a commit id
+    BufferVersion {
+        path: PathBuf,
+        version: String,
+    },
     GlobalSearch {
         pattern: String,
         case_sensitive: bool,
@@ -92,9 +102,26 @@ pub enum ProxyRequest {
         path: PathBuf,
         positions: Vec<Position>,
     },
+    GetLinkedEditingRange {
+        path: PathBuf,
+        position: Position,
+    },
+    GetDocumentHighlight {
+        path: PathBuf,
+        position: Position,
+    },
     GitGetRemoteFileUrl {
         file: PathBuf,
     },
+    /// The commits that changed the file, newest first, or only those that
+    /// changed the given lines (0-based, inclusive) of its current version
+    GitFileHistory {
+        path: PathBuf,
+        lines: Option<(usize, usize)>,
+    },
+    GitBlame {
+        path: PathBuf,
+    },
     GetReferences {
         path: PathBuf,
         position: Position,
@@ -116,6 +143,10 @@ pub enum ProxyRequest {
         path: PathBuf,
         position: Position,
         trigger_kind: InlineCompletionTriggerKind,
+        /// The id the items of the plugin providers are sent with
+        request_id: u64,
+        /// The plugin providers not to ask
+        disabled_providers: Vec<String>,
     },
     GetSemanticTokens {
         path: PathBuf,
@@ -193,6 +224,52 @@ pub enum ProxyRequest {
         dap_id: DapId,
         frame_id: usize,
     },
+    DapEvaluate {
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        expression: String,
+    },
+    DapCompletions {
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+    },
+    DapSetVariable {
+        dap_id: DapId,
+        /// The variables reference of the variable's container
+        reference: usize,
+        name: String,
+        value: String,
+    },
+    DapDataBreakpointInfo {
+        dap_id: DapId,
+        /// The variables reference of the variable's container
+        reference: usize,
+        name: String,
+    },
+    DapSetDataBreakpoints {
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::DataBreakpoint>,
+    },
+    /// The processes running on the machine of the proxy, to attach a
+    /// debugger to
+    ListProcesses {},
+    /// The whole lines around `len` bytes from `offset` of a file too large to
+    /// be opened
+    ReadFileChunk {
+        path: PathBuf,
+        offset: u64,
+        len: u64,
+    },
+    /// The lines of a file too large to be opened matching a pattern
+    SearchLargeFile {
+        path: PathBuf,
+        pattern: String,
+        case_sensitive: bool,
+        is_regex: bool,
+        max_results: usize,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -211,10 +288,29 @@ pub enum ProxyNotification {
     OpenFileChanged {
         path: PathBuf,
     },
+    /// Stop reading a file opened with [`ProxyRequest::NewBuffer`]
+    CancelNewBuffer {
+        buffer_id: BufferId,
+    },
+    /// The editor freed the document of a file, for its buffer to be dropped
+    /// and the language servers told it was closed
+    CloseBuffer {
+        path: PathBuf,
+    },
+    /// Load the coverage report at the path, and again whenever it changes
+    WatchCoverage {
+        path: Option<PathBuf>,
+    },
+    CoverageFileChanged {},
     OpenPaths {
         paths: Vec<PathObject>,
     },
     Shutdown {},
+    /// Tell the plugin providers of inline completions that the items of a
+    /// request aren't wanted any longer
+    CancelInlineCompletion {
+        request_id: u64,
+    },
     Completion {
         request_id: usize,
         path: PathBuf,
@@ -264,6 +360,12 @@ pub enum ProxyNotification {
         files: Vec<PathBuf>,
     },
     GitDiscardWorkspaceChanges {},
+    /// Stage the file with `content` rather than as it is on disk, for
+    /// staging only some of its changes
+    GitStageContent {
+        path: PathBuf,
+        content: String,
+    },
     GitInit {},
     TerminalWrite {
         term_id: TermId,
@@ -302,6 +404,10 @@ pub enum ProxyNotification {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    DapRestartFrame {
+        dap_id: DapId,
+        frame_id: usize,
+    },
     DapPause {
         dap_id: DapId,
         thread_id: ThreadId,
@@ -330,6 +436,12 @@ pub enum ProxyResponse {
     GitGetRemoteFileUrl {
         file_url: String,
     },
+    GitFileHistory {
+        commits: Vec<GitCommitInfo>,
+    },
+    GitBlame {
+        blame: BlameInfo,
+    },
     NewBufferResponse {
         content: String,
         read_only: bool,
@@ -381,6 +493,12 @@ pub enum ProxyResponse {
     GetSelectionRange {
         ranges: Vec<SelectionRange>,
     },
+    GetLinkedEditingRange {
+        ranges: Option<LinkedEditingRanges>,
+    },
+    GetDocumentHighlight {
+        highlights: Option<Vec<DocumentHighlight>>,
+    },
     GetInlayHints {
         hints: Vec<InlayHint>,
     },
@@ -408,6 +526,33 @@ pub enum ProxyResponse {
     DapGetScopesResponse {
         scopes: Vec<(dap_types::Scope, Vec<dap_types::Variable>)>,
     },
+    DapEvaluateResponse {
+        result: dap_types::EvaluateResponse,
+    },
+    DapCompletionsResponse {
+        targets: Vec<dap_types::CompletionItem>,
+    },
+    DapSetVariableResponse {
+        resp: dap_types::SetVariableResponse,
+    },
+    DapDataBreakpointInfoResponse {
+        info: dap_types::DataBreakpointInfoResponse,
+    },
+    DapSetDataBreakpointsResponse {
+        breakpoints: Vec<dap_types::Breakpoint>,
+    },
+    ListProcessesResponse {
+        processes: Vec<dap_types::ProcessInfo>,
+    },
+    ReadFileChunkResponse {
+        chunk: FileChunk,
+    },
+    SearchLargeFileResponse {
+        matches: Vec<FileMatch>,
+        /// Whether the search went through the whole file rather than
+        /// stopping at the most results
+        complete: bool,
+    },
     CreatePathResponse {
         path: PathBuf,
     },
@@ -536,6 +681,10 @@ pub fn git_init(&self) {
         self.notification(ProxyNotification::GitInit {});
     }
 
+    pub fn watch_coverage(&self, path: Option<PathBuf>) {
+        self.notification(ProxyNotification::WatchCoverage { path });
+    }
+
     pub fn git_commit(&self, message: String, diffs: Vec<FileDiff>) {
         self.notification(ProxyNotification::GitCommit { message, diffs });
     }
@@ -640,15 +789,32 @@ pub fn new_buffer(
         &self,
         buffer_id: BufferId,
         path: PathBuf,
+        max_size: Option<u64>,
         f: impl ProxyCallback + 'static,
     ) {
-        self.request_async(ProxyRequest::NewBuffer { buffer_id, path }, f);
+        self.request_async(
+            ProxyRequest::NewBuffer {
+                buffer_id,
+                path,
+                max_size,
+            },
+            f,
+        );
     }
 
     pub fn get_buffer_head(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::BufferHead { path }, f);
     }
 
+    pub fn get_buffer_version(
+        &self,
+        path: PathBuf,
+        version: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::BufferVersion { path, version }, f);
+    }
+
     pub fn create_file(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::CreateFile { path }, f);
     }
@@ -923,6 +1089,19 @@ pub fn git_get_remote_file_url(
         self.request_async(ProxyRequest::GitGetRemoteFileUrl { file }, f);
     }
 
+    pub fn git_file_history(
+        &self,
+        path: PathBuf,
+        lines: Option<(usize, usize)>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitFileHistory { path, lines }, f);
+    }
+
+    pub fn git_blame(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::GitBlame { path }, f);
+    }
+
     pub fn rename(
         &self,
         path: PathBuf,
@@ -949,6 +1128,8 @@ pub fn get_inline_completions(
         path: PathBuf,
         position: Position,
         trigger_kind: InlineCompletionTriggerKind,
+        request_id: u64,
+        disabled_providers: Vec<String>,
         f: impl ProxyCallback + 'static,
     ) {
         self.request_async(
@@ -956,11 +1137,25 @@ pub fn get_inline_completions(
                 path,
                 position,
                 trigger_kind,
+                request_id,
+                disabled_providers,
             },
             f,
         );
     }
 
+    pub fn cancel_new_buffer(&self, buffer_id: BufferId) {
+        self.notification(ProxyNotification::CancelNewBuffer { buffer_id });
+    }
+
+    pub fn close_buffer(&self, path: PathBuf) {
+        self.notification(ProxyNotification::CloseBuffer { path });
+    }
+
+    pub fn cancel_inline_completion(&self, request_id: u64) {
+        self.notification(ProxyNotification::CancelInlineCompletion { request_id });
+    }
+
     pub fn update(&self, path: PathBuf, delta: RopeDelta, rev: u64) {
         self.notification(ProxyNotification::Update { path, delta, rev });
     }
@@ -980,6 +1175,10 @@ pub fn git_discard_workspace_changes(&self) {
         self.notification(ProxyNotification::GitDiscardWorkspaceChanges {});
     }
 
+    pub fn git_stage_content(&self, path: PathBuf, content: String) {
+        self.notification(ProxyNotification::GitStageContent { path, content });
+    }
+
     pub fn get_selection_range(
         &self,
         path: PathBuf,
@@ -989,6 +1188,27 @@ pub fn get_selection_range(
         self.request_async(ProxyRequest::GetSelectionRange { path, positions }, f);
     }
 
+    pub fn get_linked_editing_range(
+        &self,
+        path: PathBuf,
+        position: Position,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetLinkedEditingRange { path, position },
+            f,
+        );
+    }
+
+    pub fn get_document_highlight(
+        &self,
+        path: PathBuf,
+        position: Position,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GetDocumentHighlight { path, position }, f);
+    }
+
     pub fn dap_start(
         &self,
         config: RunDebugConfig,
@@ -1040,6 +1260,10 @@ pub fn dap_step_out(&self, dap_id: DapId, thread_id: ThreadId) {
         self.notification(ProxyNotification::DapStepOut { dap_id, thread_id })
     }
 
+    pub fn dap_restart_frame(&self, dap_id: DapId, frame_id: usize) {
+        self.notification(ProxyNotification::DapRestartFrame { dap_id, frame_id })
+    }
+
     pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) {
         self.notification(ProxyNotification::DapPause { dap_id, thread_id })
     }
@@ -1082,6 +1306,130 @@ pub fn dap_get_scopes(
     ) {
         self.request_async(ProxyRequest::DapGetScopes { dap_id, frame_id }, f);
     }
+
+    /// Evaluate an expression typed in the debug console, in the scope of
+    /// the frame if there is one
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        expression: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapEvaluate {
+                dap_id,
+                frame_id,
+                expression,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_completions(
+        &self,
+        dap_id: DapId,
+        frame_id: Option<usize>,
+        text: String,
+        column: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapCompletions {
+                dap_id,
+                frame_id,
+                text,
+                column,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapSetVariable {
+                dap_id,
+                reference,
+                name,
+                value,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_data_breakpoint_info(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapDataBreakpointInfo {
+                dap_id,
+                reference,
+                name,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_set_data_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::DataBreakpoint>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapSetDataBreakpoints {
+                dap_id,
+                breakpoints,
+            },
+            f,
+        );
+    }
+
+    pub fn list_processes(&self, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::ListProcesses {}, f);
+    }
+
+    pub fn read_file_chunk(
+        &self,
+        path: PathBuf,
+        offset: u64,
+        len: u64,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::ReadFileChunk { path, offset, len }, f);
+    }
+
+    pub fn search_large_file(
+        &self,
+        path: PathBuf,
+        pattern: String,
+        case_sensitive: bool,
+        is_regex: bool,
+        max_results: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::SearchLargeFile {
+                path,
+                pattern,
+                case_sensitive,
+                is_regex,
+                max_results,
+            },
+            f,
+        );
+    }
 }
 
 impl Default for ProxyRpcHandler {
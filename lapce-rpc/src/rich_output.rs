@@ -0,0 +1,182 @@
+//! Output richer than lines of text, such as tables and images, which
+//! plugins and debug adapters can show in the panels, after the display data
+//! of Jupyter.
+
+use lsp_types::notification::Notification;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The image types that can be shown, the SVG images being text and the
+/// others base64 encoded
+const IMAGE_MIMES: &[&str] =
+    &["image/png", "image/jpeg", "image/gif", "image/svg+xml"];
+/// The tabular data resource of pandas and the like
+const TABLE_MIME: &str = "application/vnd.dataresource+json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RichOutput {
+    Text {
+        text: String,
+    },
+    /// Text colored by ANSI escapes
+    Ansi {
+        text: String,
+    },
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Image {
+        mime: String,
+        data: String,
+    },
+}
+
+impl RichOutput {
+    /// The richest output of a Jupyter mime bundle, which has the same output
+    /// in several types by their mime types:
+    ///
+    /// ```json
+    /// { "image/png": "iVBORw0KGgo...", "text/plain": "<Figure>" }
+    /// ```
+    pub fn from_mime_bundle(bundle: &Value) -> Option<RichOutput> {
+        let bundle = bundle.as_object()?;
+        if let Some((mime, data)) = IMAGE_MIMES
+            .iter()
+            .find_map(|mime| Some((mime, bundle.get(*mime).and_then(joined_text)?)))
+        {
+            return Some(RichOutput::Image {
+                mime: mime.to_string(),
+                data: data.replace('\n', ""),
+            });
+        }
+        if let Some(table) = bundle.get(TABLE_MIME).and_then(table_from_resource) {
+            return Some(table);
+        }
+        bundle
+            .get("text/plain")
+            .and_then(joined_text)
+            .map(|text| RichOutput::Ansi { text })
+    }
+
+    /// The output as text, for where it can't be shown
+    pub fn plain_text(&self) -> String {
+        match self {
+            RichOutput::Text { text } | RichOutput::Ansi { text } => text.clone(),
+            RichOutput::Table { headers, rows } => std::iter::once(headers)
+                .chain(rows.iter())
+                .map(|row| row.join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            RichOutput::Image { mime, .. } => format!("[{mime}]"),
+        }
+    }
+}
+
+/// The text of a mime bundle entry, which Jupyter can split into lines
+fn joined_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(lines) => lines
+            .iter()
+            .map(|line| line.as_str())
+            .collect::<Option<String>>(),
+        _ => None,
+    }
+}
+
+/// A table of a tabular data resource:
+///
+/// ```json
+/// {
+///   "schema": { "fields": [{ "name": "a" }, { "name": "b" }] },
+///   "data": [{ "a": 1, "b": "x" }]
+/// }
+/// ```
+fn table_from_resource(resource: &Value) -> Option<RichOutput> {
+    let headers: Vec<String> = resource
+        .pointer("/schema/fields")?
+        .as_array()?
+        .iter()
+        .filter_map(|field| field.get("name")?.as_str().map(str::to_string))
+        .collect();
+    let rows = resource
+        .get("data")?
+        .as_array()?
+        .iter()
+        .map(|row| {
+            headers
+                .iter()
+                .map(|name| match row.get(name) {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(Value::Null) | None => String::new(),
+                    Some(value) => value.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    Some(RichOutput::Table { headers, rows })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowRichOutputParams {
+    pub items: Vec<RichOutput>,
+}
+
+/// The notification a plugin sends to show output in the output panel
+pub enum ShowRichOutput {}
+
+impl Notification for ShowRichOutput {
+    type Params = ShowRichOutputParams;
+    const METHOD: &'static str = "lapce/showRichOutput";
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::RichOutput;
+
+    #[test]
+    fn test_from_mime_bundle() {
+        let bundle = json!({
+            "text/plain": ["<Figure ", "size 640x480>"],
+            "image/png": "iVBORw0K\nGgo=",
+        });
+        assert_eq!(
+            RichOutput::from_mime_bundle(&bundle),
+            Some(RichOutput::Image {
+                mime: "image/png".to_string(),
+                data: "iVBORw0KGgo=".to_string(),
+            })
+        );
+
+        let bundle = json!({
+            "text/plain": "   a  b",
+            "application/vnd.dataresource+json": {
+                "schema": { "fields": [{ "name": "a" }, { "name": "b" }] },
+                "data": [{ "a": 1, "b": "x" }, { "a": 2.5 }],
+            },
+        });
+        assert_eq!(
+            RichOutput::from_mime_bundle(&bundle),
+            Some(RichOutput::Table {
+                headers: vec!["a".to_string(), "b".to_string()],
+                rows: vec![
+                    vec!["1".to_string(), "x".to_string()],
+                    vec!["2.5".to_string(), String::new()],
+                ],
+            })
+        );
+
+        assert_eq!(
+            RichOutput::from_mime_bundle(&json!({ "text/plain": "42" })),
+            Some(RichOutput::Ansi {
+                text: "42".to_string()
+            })
+        );
+        assert_eq!(RichOutput::from_mime_bundle(&json!("42")), None);
+    }
+}
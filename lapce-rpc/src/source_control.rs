@@ -38,6 +38,24 @@ pub fn kind(&self) -> FileDiffKind {
     }
 }
 
+/// A commit in the history of a file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitCommitInfo {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    /// Seconds since the Unix epoch
+    pub time: i64,
+}
+
+/// Which commit last changed each line of a file, as of `HEAD`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct BlameInfo {
+    pub commits: Vec<GitCommitInfo>,
+    /// The index in `commits` of the commit of each line
+    pub lines: Vec<usize>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileDiffKind {
     Modified,
@@ -24,6 +24,7 @@ cargo build --profile release-lto --package lapce-app --frozen
 %install
 install -Dm755 target/release-lto/lapce %{buildroot}%{_bindir}/lapce
 install -Dm644 extra/linux/dev.lapce.lapce.desktop %{buildroot}/usr/share/applications/dev.lapce.lapce.desktop
+install -Dm644 extra/linux/dev.lapce.lapce-url-handler.desktop %{buildroot}/usr/share/applications/dev.lapce.lapce-url-handler.desktop
 install -Dm644 extra/linux/dev.lapce.lapce.metainfo.xml %{buildroot}/usr/share/metainfo/dev.lapce.lapce.metainfo.xml
 install -Dm644 extra/images/logo.png %{buildroot}/usr/share/pixmaps/dev.lapce.lapce.png
 
@@ -32,6 +33,7 @@ install -Dm644 extra/images/logo.png %{buildroot}/usr/share/pixmaps/dev.lapce.la
 %doc *.md
 %{_bindir}/lapce
 /usr/share/applications/dev.lapce.lapce.desktop
+/usr/share/applications/dev.lapce.lapce-url-handler.desktop
 /usr/share/metainfo/dev.lapce.lapce.metainfo.xml
 /usr/share/pixmaps/dev.lapce.lapce.png
 